@@ -20,6 +20,18 @@ impl From<tracing_core::Level> for metadata::Level {
     }
 }
 
+impl From<metadata::Level> for tracing_core::Level {
+    fn from(level: metadata::Level) -> Self {
+        match level {
+            metadata::Level::Error => tracing_core::Level::ERROR,
+            metadata::Level::Warn => tracing_core::Level::WARN,
+            metadata::Level::Info => tracing_core::Level::INFO,
+            metadata::Level::Debug => tracing_core::Level::DEBUG,
+            metadata::Level::Trace => tracing_core::Level::TRACE,
+        }
+    }
+}
+
 impl From<tracing_core::metadata::Kind> for metadata::Kind {
     fn from(kind: tracing_core::metadata::Kind) -> Self {
         // /!\ Note that this is intentionally *not* implemented using match.
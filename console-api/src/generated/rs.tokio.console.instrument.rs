@@ -5,20 +5,127 @@
 /// TODO: In the future allow for the request to specify
 /// only the data that the caller cares about (i.e. only
 /// tasks but no resources)
+#[cfg_attr(feature = "serde", derive(::serde::Serialize, ::serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
 #[derive(Clone, Copy, PartialEq, ::prost::Message)]
-pub struct InstrumentRequest {}
+pub struct InstrumentRequest {
+    /// How long the caller would like data for completed tasks, resources and
+    /// async ops to be retained while it is watching.
+    ///
+    /// While the caller is attached, the server retains closed data for at
+    /// least this long, up to the maximum retention it is configured with.
+    /// If this is not set, or is shorter than the server's own retention, the
+    /// server's retention is used.
+    #[prost(message, optional, tag = "1")]
+    #[cfg_attr(feature = "serde", serde(default, with = "crate::serde_util::option_string"))]
+    pub retention: ::core::option::Option<::prost_types::Duration>,
+    /// The token of the last update the caller received from this server, if
+    /// it is reconnecting.
+    ///
+    /// If the server still has the updates it published since then, it sends
+    /// only those changes rather than the full state, so that the caller can
+    /// keep the state it already has. Otherwise, the first update includes the
+    /// full state, as it does when this is not set.
+    #[prost(message, optional, tag = "2")]
+    pub resume_token: ::core::option::Option<ResumeToken>,
+}
+/// Identifies an update sent on a `WatchUpdates` stream, so that a client
+/// which reconnects can resume watching from it.
+#[cfg_attr(feature = "serde", derive(::serde::Serialize, ::serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+#[derive(Clone, Copy, PartialEq, ::prost::Message)]
+pub struct ResumeToken {
+    /// Identifies the server which sent the update. This is different every
+    /// time the instrumented process starts.
+    #[prost(uint64, tag = "1")]
+    pub epoch: u64,
+    /// The number of updates the server had published when the update was
+    /// sent.
+    #[prost(uint64, tag = "2")]
+    pub sequence: u64,
+}
 /// TaskDetailsRequest requests the stream of updates about
 /// the specific task identified in the request.
+#[cfg_attr(feature = "serde", derive(::serde::Serialize, ::serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
 #[derive(Clone, Copy, PartialEq, ::prost::Message)]
 pub struct TaskDetailsRequest {
     /// Identifies the task for which details were requested.
     #[prost(message, optional, tag = "1")]
     pub id: ::core::option::Option<super::common::Id>,
+    /// How often to send updates about the task.
+    ///
+    /// If this is not set, updates are sent at the server's publish interval.
+    /// Intervals shorter than the server's minimum, such as 50ms, are raised
+    /// to it.
+    #[prost(message, optional, tag = "2")]
+    #[cfg_attr(feature = "serde", serde(default, with = "crate::serde_util::option_string"))]
+    pub interval: ::core::option::Option<::prost_types::Duration>,
+    /// The number of significant figures of precision of the histograms in
+    /// each update.
+    ///
+    /// Histograms with fewer significant figures are smaller, which is useful
+    /// when updates are requested frequently. If this is not set, or is
+    /// greater than the precision the histograms are recorded with, they are
+    /// sent with their full precision.
+    #[prost(uint32, optional, tag = "3")]
+    pub histogram_significant_figures: ::core::option::Option<u32>,
 }
+/// TaskDetailsSetRequest requests the stream of updates about a set of
+/// tasks, identified by their IDs or by where they were spawned.
+#[cfg_attr(feature = "serde", derive(::serde::Serialize, ::serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct TaskDetailsSetRequest {
+    /// Identifies the tasks for which details were requested.
+    #[prost(message, repeated, tag = "1")]
+    pub ids: ::prost::alloc::vec::Vec<super::common::Id>,
+    /// If set, details are also sent for the tasks spawned from this
+    /// location, including tasks spawned after the request.
+    #[prost(message, optional, tag = "2")]
+    pub location: ::core::option::Option<super::common::Location>,
+    /// How often to send updates about the tasks.
+    ///
+    /// If this is not set, updates are sent at the server's publish interval.
+    /// Intervals shorter than the server's minimum, such as 50ms, are raised
+    /// to it.
+    #[prost(message, optional, tag = "3")]
+    #[cfg_attr(feature = "serde", serde(default, with = "crate::serde_util::option_string"))]
+    pub interval: ::core::option::Option<::prost_types::Duration>,
+    /// The number of significant figures of precision of the histograms in
+    /// each update.
+    ///
+    /// See `TaskDetailsRequest.histogram_significant_figures`.
+    #[prost(uint32, optional, tag = "4")]
+    pub histogram_significant_figures: ::core::option::Option<u32>,
+}
+/// TaskGroupsRequest requests the stream of statistics for groups of tasks
+/// spawned from the same location.
+#[cfg_attr(feature = "serde", derive(::serde::Serialize, ::serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+#[derive(Clone, Copy, PartialEq, ::prost::Message)]
+pub struct TaskGroupsRequest {}
 /// PauseRequest requests the stream of updates to pause.
+#[cfg_attr(feature = "serde", derive(::serde::Serialize, ::serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
 #[derive(Clone, Copy, PartialEq, ::prost::Message)]
-pub struct PauseRequest {}
+pub struct PauseRequest {
+    /// If true, the aggregator also freezes its notion of the current time
+    /// while paused.
+    ///
+    /// Any updates sent while paused (such as the initial update for a new
+    /// `WatchUpdates` stream, or task details for a newly watched task) are
+    /// then timestamped with the time at which the aggregator was paused, so
+    /// that durations calculated relative to them do not keep accruing.
+    ///
+    /// Once time is frozen, it stays frozen until the updates are resumed,
+    /// even if the aggregator is paused again without this set.
+    #[prost(bool, tag = "1")]
+    pub freeze_time: bool,
+}
 /// ResumeRequest requests the stream of updates to resume after a pause.
+#[cfg_attr(feature = "serde", derive(::serde::Serialize, ::serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
 #[derive(Clone, Copy, PartialEq, ::prost::Message)]
 pub struct ResumeRequest {}
 /// Update carries all information regarding tasks, resources, async operations
@@ -29,6 +136,8 @@ pub struct ResumeRequest {}
 /// - we can have all the new_metadata in one place
 /// - things such as async ops and resource ops do not make sense
 ///    on their own as they have relations to tasks and resources
+#[cfg_attr(feature = "serde", derive(::serde::Serialize, ::serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct Update {
     /// The system time when this update was recorded.
@@ -36,6 +145,7 @@ pub struct Update {
     /// This is the timestamp any durations in the included `Stats` were
     /// calculated relative to.
     #[prost(message, optional, tag = "1")]
+    #[cfg_attr(feature = "serde", serde(default, with = "crate::serde_util::option_string"))]
     pub now: ::core::option::Option<::prost_types::Timestamp>,
     /// Task state update.
     #[prost(message, optional, tag = "2")]
@@ -49,23 +159,196 @@ pub struct Update {
     /// Any new span metadata that was registered since the last update.
     #[prost(message, optional, tag = "5")]
     pub new_metadata: ::core::option::Option<super::common::RegisterMetadata>,
+    /// Runtime worker thread update.
+    #[prost(message, optional, tag = "6")]
+    pub runtime_update: ::core::option::Option<super::runtime::RuntimeUpdate>,
+    /// Whether the server has stopped sending this client the full state,
+    /// because the updates sent to it exceeded the server's bandwidth limit for
+    /// each client.
+    ///
+    /// Once this is set, every later update on the stream is a summary: it only
+    /// includes the task aggregates and activity samples, and the runtime's
+    /// workers. The client can reconnect to receive the full state again.
+    #[prost(bool, tag = "7")]
+    pub degraded: bool,
+    /// Watched span state update.
+    #[prost(message, optional, tag = "8")]
+    pub span_update: ::core::option::Option<super::spans::SpanUpdate>,
+    /// The token which a client that reconnects sends to resume watching from
+    /// this update.
+    ///
+    /// This is not set on summaries sent to degraded clients, since they don't
+    /// include the full state.
+    #[prost(message, optional, tag = "9")]
+    pub resume_token: ::core::option::Option<ResumeToken>,
 }
 /// StateRequest requests the current state of the aggregator.
+#[cfg_attr(feature = "serde", derive(::serde::Serialize, ::serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
 #[derive(Clone, Copy, PartialEq, ::prost::Message)]
 pub struct StateRequest {}
 /// State carries the current state of the aggregator.
+#[cfg_attr(feature = "serde", derive(::serde::Serialize, ::serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
 #[derive(Clone, Copy, PartialEq, ::prost::Message)]
 pub struct State {
     #[prost(enumeration = "Temporality", tag = "1")]
     pub temporality: i32,
+    /// How long the aggregator was paused for, the last time it was resumed.
+    ///
+    /// This is not set if the aggregator has never been paused and resumed.
+    #[prost(message, optional, tag = "2")]
+    #[cfg_attr(feature = "serde", serde(default, with = "crate::serde_util::option_string"))]
+    pub last_pause: ::core::option::Option<::prost_types::Duration>,
+    /// Whether the server is read-only, refusing requests to pause or resume
+    /// the aggregator.
+    #[prost(bool, tag = "3")]
+    pub read_only: bool,
+    /// Whether the instrumented process was built with `--cfg tokio_unstable`,
+    /// which Tokio requires in order to instrument tasks.
+    ///
+    /// This is not set by servers which predate it. Those could only be built
+    /// with `tokio_unstable`.
+    #[prost(bool, optional, tag = "4")]
+    pub tokio_unstable: ::core::option::Option<bool>,
+    /// How many times the server's tracing layer has panicked.
+    ///
+    /// Once it has panicked, the layer stops recording anything, so the tasks,
+    /// resources, and async ops the server sends are no longer updated.
+    #[prost(uint64, tag = "5")]
+    pub layer_panics: u64,
+    /// Approximately how much memory the server uses to record tasks.
+    ///
+    /// This is not set by servers which predate it.
+    #[prost(message, optional, tag = "6")]
+    pub memory: ::core::option::Option<MemoryUsage>,
+}
+/// `MemoryUsage` is an approximation of the memory the server uses for the
+/// data it records, so that its precision settings can be tuned.
+///
+/// The sizes only include the server's own data structures, and not the
+/// allocator's overhead for them.
+#[cfg_attr(feature = "serde", derive(::serde::Serialize, ::serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+#[derive(Clone, Copy, PartialEq, ::prost::Message)]
+pub struct MemoryUsage {
+    /// The number of duration histograms recorded for tasks and resources.
+    #[prost(uint64, tag = "1")]
+    pub histograms: u64,
+    /// The approximate number of bytes used by the duration histograms.
+    #[prost(uint64, tag = "2")]
+    pub histogram_bytes: u64,
+    /// The number of significant figures task histograms are recorded with.
+    ///
+    /// Each additional significant figure makes the histograms roughly ten
+    /// times as large.
+    #[prost(uint32, tag = "3")]
+    pub histogram_significant_figures: u32,
+    /// The number of tasks the server is retaining, including completed
+    /// tasks.
+    #[prost(uint64, tag = "4")]
+    pub tasks: u64,
+    /// The approximate number of bytes used by the tasks and their stats, not
+    /// including their histograms.
+    #[prost(uint64, tag = "5")]
+    pub task_bytes: u64,
 }
 /// `PauseResponse` is the value returned after a pause request.
+#[cfg_attr(feature = "serde", derive(::serde::Serialize, ::serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
 #[derive(Clone, Copy, PartialEq, ::prost::Message)]
 pub struct PauseResponse {}
 /// `ResumeResponse` is the value returned after a resume request.
+#[cfg_attr(feature = "serde", derive(::serde::Serialize, ::serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
 #[derive(Clone, Copy, PartialEq, ::prost::Message)]
 pub struct ResumeResponse {}
+/// `AuditLogRequest` requests the server's log of client requests.
+#[cfg_attr(feature = "serde", derive(::serde::Serialize, ::serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+#[derive(Clone, Copy, PartialEq, ::prost::Message)]
+pub struct AuditLogRequest {}
+/// `AuditLogResponse` is the server's log of the most recent client requests.
+#[cfg_attr(feature = "serde", derive(::serde::Serialize, ::serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct AuditLogResponse {
+    /// The logged requests, oldest first.
+    #[prost(message, repeated, tag = "1")]
+    pub entries: ::prost::alloc::vec::Vec<AuditEntry>,
+    /// How many older requests were dropped from the log to make room for
+    /// newer ones.
+    #[prost(uint64, tag = "2")]
+    pub dropped_entries: u64,
+}
+/// `ListTasksRequest` requests a snapshot of the tasks.
+#[cfg_attr(feature = "serde", derive(::serde::Serialize, ::serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ListTasksRequest {
+    /// If set, only the tasks spawned from this location are listed.
+    #[prost(message, optional, tag = "1")]
+    pub location: ::core::option::Option<super::common::Location>,
+    /// Whether to list tasks which have completed but are still retained by
+    /// the server.
+    #[prost(bool, tag = "2")]
+    pub include_completed: bool,
+    /// The maximum number of tasks to list, or 0 to list all of them.
+    ///
+    /// Tasks are listed in order of their IDs, so the tasks with the lowest
+    /// IDs are listed when there are more than this.
+    #[prost(uint32, tag = "3")]
+    pub limit: u32,
+}
+/// `ListTasksResponse` is a snapshot of the tasks.
+#[cfg_attr(feature = "serde", derive(::serde::Serialize, ::serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ListTasksResponse {
+    /// The system time when this snapshot was taken.
+    ///
+    /// This is the timestamp any durations in the included `Stats` were
+    /// calculated relative to.
+    #[prost(message, optional, tag = "1")]
+    #[cfg_attr(feature = "serde", serde(default, with = "crate::serde_util::option_string"))]
+    pub now: ::core::option::Option<::prost_types::Timestamp>,
+    /// The listed tasks, in order of their IDs.
+    #[prost(message, repeated, tag = "2")]
+    pub tasks: ::prost::alloc::vec::Vec<super::tasks::Task>,
+    /// The stats of each listed task, by task ID.
+    #[prost(map = "uint64, message", tag = "3")]
+    pub stats: ::std::collections::HashMap<u64, super::tasks::Stats>,
+    /// All of the span metadata registered so far, which the tasks' metadata
+    /// IDs refer to.
+    #[prost(message, optional, tag = "4")]
+    pub metadata: ::core::option::Option<super::common::RegisterMetadata>,
+    /// How many tasks matched the request, but weren't listed because of its
+    /// limit.
+    #[prost(uint64, tag = "5")]
+    pub omitted_tasks: u64,
+}
+/// A request a client made to the server.
+#[cfg_attr(feature = "serde", derive(::serde::Serialize, ::serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct AuditEntry {
+    /// When the request was received.
+    #[prost(message, optional, tag = "1")]
+    #[cfg_attr(feature = "serde", serde(default, with = "crate::serde_util::option_string"))]
+    pub received_at: ::core::option::Option<::prost_types::Timestamp>,
+    /// The address the client connected from.
+    ///
+    /// This is empty if the address is unknown, such as for clients connected
+    /// over a Unix domain socket.
+    #[prost(string, tag = "2")]
+    pub client_addr: ::prost::alloc::string::String,
+    /// The name of the RPC the client called, such as `WatchUpdates` or
+    /// `Pause`.
+    #[prost(string, tag = "3")]
+    pub rpc: ::prost::alloc::string::String,
+}
 /// The time "state" of the aggregator.
+#[cfg_attr(feature = "serde", derive(::serde::Serialize, ::serde::Deserialize))]
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
 #[repr(i32)]
 pub enum Temporality {
@@ -95,6 +378,7 @@ impl Temporality {
     }
 }
 /// Generated client implementations.
+#[cfg(feature = "client")]
 pub mod instrument_client {
     #![allow(
         unused_variables,
@@ -217,6 +501,10 @@ pub mod instrument_client {
             self.inner.server_streaming(req, path, codec).await
         }
         /// Produces a stream of updates describing the activity of a specific task.
+        ///
+        /// The stream ends with a `NOT_FOUND` status once the task has completed and
+        /// is no longer retained, or a `RESOURCE_EXHAUSTED` status if the client
+        /// falls too far behind in reading the updates.
         pub async fn watch_task_details(
             &mut self,
             request: impl tonic::IntoRequest<super::TaskDetailsRequest>,
@@ -246,6 +534,68 @@ pub mod instrument_client {
                 );
             self.inner.server_streaming(req, path, codec).await
         }
+        /// Produces a stream of updates describing the activity of a set of tasks.
+        pub async fn watch_task_details_set(
+            &mut self,
+            request: impl tonic::IntoRequest<super::TaskDetailsSetRequest>,
+        ) -> std::result::Result<
+            tonic::Response<
+                tonic::codec::Streaming<super::super::tasks::TaskDetailsSet>,
+            >,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::unknown(
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/rs.tokio.console.instrument.Instrument/WatchTaskDetailsSet",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(
+                    GrpcMethod::new(
+                        "rs.tokio.console.instrument.Instrument",
+                        "WatchTaskDetailsSet",
+                    ),
+                );
+            self.inner.server_streaming(req, path, codec).await
+        }
+        /// Produces a stream of statistics merged across the tasks spawned from each location.
+        pub async fn watch_task_groups(
+            &mut self,
+            request: impl tonic::IntoRequest<super::TaskGroupsRequest>,
+        ) -> std::result::Result<
+            tonic::Response<tonic::codec::Streaming<super::super::tasks::TaskGroups>>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::unknown(
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/rs.tokio.console.instrument.Instrument/WatchTaskGroups",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(
+                    GrpcMethod::new(
+                        "rs.tokio.console.instrument.Instrument",
+                        "WatchTaskGroups",
+                    ),
+                );
+            self.inner.server_streaming(req, path, codec).await
+        }
         /// Produces a stream of state of the aggregator.
         pub async fn watch_state(
             &mut self,
@@ -277,6 +627,8 @@ pub mod instrument_client {
             self.inner.server_streaming(req, path, codec).await
         }
         /// Registers that the console observer wants to pause the stream.
+        ///
+        /// This fails with `PERMISSION_DENIED` if the server is read-only.
         pub async fn pause(
             &mut self,
             request: impl tonic::IntoRequest<super::PauseRequest>,
@@ -301,6 +653,8 @@ pub mod instrument_client {
             self.inner.unary(req, path, codec).await
         }
         /// Registers that the console observer wants to resume the stream.
+        ///
+        /// This fails with `PERMISSION_DENIED` if the server is read-only.
         pub async fn resume(
             &mut self,
             request: impl tonic::IntoRequest<super::ResumeRequest>,
@@ -324,9 +678,67 @@ pub mod instrument_client {
                 );
             self.inner.unary(req, path, codec).await
         }
+        /// Returns the log of which clients have called the server's RPCs.
+        pub async fn audit_log(
+            &mut self,
+            request: impl tonic::IntoRequest<super::AuditLogRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::AuditLogResponse>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::unknown(
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/rs.tokio.console.instrument.Instrument/AuditLog",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(
+                    GrpcMethod::new("rs.tokio.console.instrument.Instrument", "AuditLog"),
+                );
+            self.inner.unary(req, path, codec).await
+        }
+        /// Returns a single snapshot of the tasks and their stats.
+        ///
+        /// Unlike `WatchUpdates`, this doesn't require the caller to manage a
+        /// stream, which is useful for scripts and health checks.
+        pub async fn list_tasks(
+            &mut self,
+            request: impl tonic::IntoRequest<super::ListTasksRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::ListTasksResponse>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::unknown(
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/rs.tokio.console.instrument.Instrument/ListTasks",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(
+                    GrpcMethod::new("rs.tokio.console.instrument.Instrument", "ListTasks"),
+                );
+            self.inner.unary(req, path, codec).await
+        }
     }
 }
 /// Generated server implementations.
+#[cfg(feature = "server")]
 pub mod instrument_server {
     #![allow(
         unused_variables,
@@ -363,6 +775,10 @@ pub mod instrument_server {
             + std::marker::Send
             + 'static;
         /// Produces a stream of updates describing the activity of a specific task.
+        ///
+        /// The stream ends with a `NOT_FOUND` status once the task has completed and
+        /// is no longer retained, or a `RESOURCE_EXHAUSTED` status if the client
+        /// falls too far behind in reading the updates.
         async fn watch_task_details(
             &self,
             request: tonic::Request<super::TaskDetailsRequest>,
@@ -370,6 +786,40 @@ pub mod instrument_server {
             tonic::Response<Self::WatchTaskDetailsStream>,
             tonic::Status,
         >;
+        /// Server streaming response type for the WatchTaskDetailsSet method.
+        type WatchTaskDetailsSetStream: tonic::codegen::tokio_stream::Stream<
+                Item = std::result::Result<
+                    super::super::tasks::TaskDetailsSet,
+                    tonic::Status,
+                >,
+            >
+            + std::marker::Send
+            + 'static;
+        /// Produces a stream of updates describing the activity of a set of tasks.
+        async fn watch_task_details_set(
+            &self,
+            request: tonic::Request<super::TaskDetailsSetRequest>,
+        ) -> std::result::Result<
+            tonic::Response<Self::WatchTaskDetailsSetStream>,
+            tonic::Status,
+        >;
+        /// Server streaming response type for the WatchTaskGroups method.
+        type WatchTaskGroupsStream: tonic::codegen::tokio_stream::Stream<
+                Item = std::result::Result<
+                    super::super::tasks::TaskGroups,
+                    tonic::Status,
+                >,
+            >
+            + std::marker::Send
+            + 'static;
+        /// Produces a stream of statistics merged across the tasks spawned from each location.
+        async fn watch_task_groups(
+            &self,
+            request: tonic::Request<super::TaskGroupsRequest>,
+        ) -> std::result::Result<
+            tonic::Response<Self::WatchTaskGroupsStream>,
+            tonic::Status,
+        >;
         /// Server streaming response type for the WatchState method.
         type WatchStateStream: tonic::codegen::tokio_stream::Stream<
                 Item = std::result::Result<super::State, tonic::Status>,
@@ -382,15 +832,38 @@ pub mod instrument_server {
             request: tonic::Request<super::StateRequest>,
         ) -> std::result::Result<tonic::Response<Self::WatchStateStream>, tonic::Status>;
         /// Registers that the console observer wants to pause the stream.
+        ///
+        /// This fails with `PERMISSION_DENIED` if the server is read-only.
         async fn pause(
             &self,
             request: tonic::Request<super::PauseRequest>,
         ) -> std::result::Result<tonic::Response<super::PauseResponse>, tonic::Status>;
         /// Registers that the console observer wants to resume the stream.
+        ///
+        /// This fails with `PERMISSION_DENIED` if the server is read-only.
         async fn resume(
             &self,
             request: tonic::Request<super::ResumeRequest>,
         ) -> std::result::Result<tonic::Response<super::ResumeResponse>, tonic::Status>;
+        /// Returns the log of which clients have called the server's RPCs.
+        async fn audit_log(
+            &self,
+            request: tonic::Request<super::AuditLogRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::AuditLogResponse>,
+            tonic::Status,
+        >;
+        /// Returns a single snapshot of the tasks and their stats.
+        ///
+        /// Unlike `WatchUpdates`, this doesn't require the caller to manage a
+        /// stream, which is useful for scripts and health checks.
+        async fn list_tasks(
+            &self,
+            request: tonic::Request<super::ListTasksRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::ListTasksResponse>,
+            tonic::Status,
+        >;
     }
     /// `InstrumentServer<T>` implements `Instrument` as a service.
     #[derive(Debug)]
@@ -561,6 +1034,98 @@ pub mod instrument_server {
                     };
                     Box::pin(fut)
                 }
+                "/rs.tokio.console.instrument.Instrument/WatchTaskDetailsSet" => {
+                    #[allow(non_camel_case_types)]
+                    struct WatchTaskDetailsSetSvc<T: Instrument>(pub Arc<T>);
+                    impl<
+                        T: Instrument,
+                    > tonic::server::ServerStreamingService<super::TaskDetailsSetRequest>
+                    for WatchTaskDetailsSetSvc<T> {
+                        type Response = super::super::tasks::TaskDetailsSet;
+                        type ResponseStream = T::WatchTaskDetailsSetStream;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::ResponseStream>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::TaskDetailsSetRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as Instrument>::watch_task_details_set(&inner, request).await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let method = WatchTaskDetailsSetSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.server_streaming(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/rs.tokio.console.instrument.Instrument/WatchTaskGroups" => {
+                    #[allow(non_camel_case_types)]
+                    struct WatchTaskGroupsSvc<T: Instrument>(pub Arc<T>);
+                    impl<
+                        T: Instrument,
+                    > tonic::server::ServerStreamingService<super::TaskGroupsRequest>
+                    for WatchTaskGroupsSvc<T> {
+                        type Response = super::super::tasks::TaskGroups;
+                        type ResponseStream = T::WatchTaskGroupsStream;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::ResponseStream>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::TaskGroupsRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as Instrument>::watch_task_groups(&inner, request).await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let method = WatchTaskGroupsSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.server_streaming(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
                 "/rs.tokio.console.instrument.Instrument/WatchState" => {
                     #[allow(non_camel_case_types)]
                     struct WatchStateSvc<T: Instrument>(pub Arc<T>);
@@ -693,6 +1258,96 @@ pub mod instrument_server {
                     };
                     Box::pin(fut)
                 }
+                "/rs.tokio.console.instrument.Instrument/AuditLog" => {
+                    #[allow(non_camel_case_types)]
+                    struct AuditLogSvc<T: Instrument>(pub Arc<T>);
+                    impl<
+                        T: Instrument,
+                    > tonic::server::UnaryService<super::AuditLogRequest>
+                    for AuditLogSvc<T> {
+                        type Response = super::AuditLogResponse;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::AuditLogRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as Instrument>::audit_log(&inner, request).await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let method = AuditLogSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/rs.tokio.console.instrument.Instrument/ListTasks" => {
+                    #[allow(non_camel_case_types)]
+                    struct ListTasksSvc<T: Instrument>(pub Arc<T>);
+                    impl<
+                        T: Instrument,
+                    > tonic::server::UnaryService<super::ListTasksRequest>
+                    for ListTasksSvc<T> {
+                        type Response = super::ListTasksResponse;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::ListTasksRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as Instrument>::list_tasks(&inner, request).await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let method = ListTasksSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
                 _ => {
                     Box::pin(async move {
                         let mut response = http::Response::new(empty_body());
@@ -6,6 +6,8 @@
 /// - any new resources that were created since the last update
 /// - the current stats for any resource whose stats changed since the last update
 /// - any new poll ops that have been invoked on a resource
+#[cfg_attr(feature = "serde", derive(::serde::Serialize, ::serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct ResourceUpdate {
     /// A list of new resources that were created since the last `ResourceUpdate` was
@@ -30,8 +32,17 @@ pub struct ResourceUpdate {
     /// this will always be 0.
     #[prost(uint64, tag = "4")]
     pub dropped_events: u64,
+    /// Deadline overrun statistics for each timer callsite whose timers have
+    /// fired since the last update.
+    ///
+    /// Each entry is a complete snapshot of the statistics for that callsite,
+    /// which replaces any previously received entry with the same location.
+    #[prost(message, repeated, tag = "5")]
+    pub timer_stats: ::prost::alloc::vec::Vec<TimerStats>,
 }
 /// Static data recorded when a new resource is created.
+#[cfg_attr(feature = "serde", derive(::serde::Serialize, ::serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct Resource {
     /// The resources's ID.
@@ -66,6 +77,8 @@ pub struct Resource {
 /// Nested message and enum types in `Resource`.
 pub mod resource {
     /// The kind of resource (e.g. timer, mutex).
+    #[cfg_attr(feature = "serde", derive(::serde::Serialize, ::serde::Deserialize))]
+    #[cfg_attr(feature = "serde", serde(default))]
     #[derive(Clone, PartialEq, ::prost::Message)]
     pub struct Kind {
         /// Every resource is either a known kind or an other (unknown) kind.
@@ -75,6 +88,7 @@ pub mod resource {
     /// Nested message and enum types in `Kind`.
     pub mod kind {
         /// `Known` collects the kinds of resources that are known in this version of the API.
+        #[cfg_attr(feature = "serde", derive(::serde::Serialize, ::serde::Deserialize))]
         #[derive(
             Clone,
             Copy,
@@ -110,6 +124,7 @@ pub mod resource {
             }
         }
         /// Every resource is either a known kind or an other (unknown) kind.
+        #[cfg_attr(feature = "serde", derive(::serde::Serialize, ::serde::Deserialize))]
         #[derive(Clone, PartialEq, ::prost::Oneof)]
         pub enum Kind {
             /// `known` signals that this kind of resource is known to the console API.
@@ -122,13 +137,17 @@ pub mod resource {
     }
 }
 /// Task runtime stats of a resource.
+#[cfg_attr(feature = "serde", derive(::serde::Serialize, ::serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct Stats {
     /// Timestamp of when the resource was created.
     #[prost(message, optional, tag = "1")]
+    #[cfg_attr(feature = "serde", serde(default, with = "crate::serde_util::option_string"))]
     pub created_at: ::core::option::Option<::prost_types::Timestamp>,
     /// Timestamp of when the resource was dropped.
     #[prost(message, optional, tag = "2")]
+    #[cfg_attr(feature = "serde", serde(default, with = "crate::serde_util::option_string"))]
     pub dropped_at: ::core::option::Option<::prost_types::Timestamp>,
     /// State attributes of the resource. These are dependent on the type of the resource.
     /// For example, a timer resource will have a duration while a semaphore resource may
@@ -137,9 +156,67 @@ pub struct Stats {
     /// static data describing the resource.
     #[prost(message, repeated, tag = "3")]
     pub attributes: ::prost::alloc::vec::Vec<super::common::Attribute>,
+    /// A histogram of how long values spent in the resource between being sent
+    /// and being received.
+    ///
+    /// This is only present for channel resources which have received at least
+    /// one value.
+    #[prost(message, optional, tag = "4")]
+    pub recv_latency_histogram: ::core::option::Option<super::tasks::DurationHistogram>,
+    /// Statistics for reading from and writing to the resource.
+    ///
+    /// This is only present for I/O resources, such as sockets, which have
+    /// been polled at least once.
+    #[prost(message, optional, tag = "5")]
+    pub io_stats: ::core::option::Option<IoStats>,
+}
+/// I/O statistics for a resource, such as a socket.
+#[cfg_attr(feature = "serde", derive(::serde::Serialize, ::serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+#[derive(Clone, Copy, PartialEq, ::prost::Message)]
+pub struct IoStats {
+    /// Statistics for reading from the resource.
+    #[prost(message, optional, tag = "1")]
+    pub read: ::core::option::Option<IoDirectionStats>,
+    /// Statistics for writing to the resource.
+    #[prost(message, optional, tag = "2")]
+    pub write: ::core::option::Option<IoDirectionStats>,
+}
+/// I/O statistics for one direction (reading or writing) of a resource.
+#[cfg_attr(feature = "serde", derive(::serde::Serialize, ::serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+#[derive(Clone, Copy, PartialEq, ::prost::Message)]
+pub struct IoDirectionStats {
+    /// The total number of bytes transferred.
+    #[prost(uint64, tag = "1")]
+    pub bytes: u64,
+    /// The number of completed operations.
+    #[prost(uint64, tag = "2")]
+    pub ops: u64,
+    /// The number of times the resource was polled, including polls which
+    /// returned `Pending` because the resource was not ready.
+    #[prost(uint64, tag = "3")]
+    pub polls: u64,
+    /// The total time operations spent waiting for the resource to become
+    /// ready.
+    #[prost(message, optional, tag = "4")]
+    #[cfg_attr(feature = "serde", serde(default, with = "crate::serde_util::option_string"))]
+    pub total_wait: ::core::option::Option<::prost_types::Duration>,
+    /// The longest time a single operation spent waiting for the resource to
+    /// become ready.
+    #[prost(message, optional, tag = "5")]
+    #[cfg_attr(feature = "serde", serde(default, with = "crate::serde_util::option_string"))]
+    pub max_wait: ::core::option::Option<::prost_types::Duration>,
+    /// When the current operation started waiting for the resource to become
+    /// ready, if it is waiting.
+    #[prost(message, optional, tag = "6")]
+    #[cfg_attr(feature = "serde", serde(default, with = "crate::serde_util::option_string"))]
+    pub waiting_since: ::core::option::Option<::prost_types::Timestamp>,
 }
 /// A `PollOp` describes each poll operation that completes within the async
 /// application.
+#[cfg_attr(feature = "serde", derive(::serde::Serialize, ::serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct PollOp {
     /// The numeric ID of the op's `Metadata`.
@@ -165,3 +242,23 @@ pub struct PollOp {
     #[prost(bool, tag = "7")]
     pub is_ready: bool,
 }
+/// Deadline overrun statistics for the timers created at a single callsite.
+///
+/// A timer's overrun is the time between its scheduled deadline and the task
+/// which is waiting on it observing that it has fired. Large overruns mean that
+/// the timer's task could not be polled in time, usually because the runtime's
+/// workers were blocked.
+#[cfg_attr(feature = "serde", derive(::serde::Serialize, ::serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct TimerStats {
+    /// The location in code where the timers were created.
+    #[prost(message, optional, tag = "1")]
+    pub location: ::core::option::Option<super::common::Location>,
+    /// The number of times a timer created at this location has fired.
+    #[prost(uint64, tag = "2")]
+    pub fires: u64,
+    /// A histogram of the deadline overruns of timers created at this location.
+    #[prost(message, optional, tag = "3")]
+    pub overrun_histogram: ::core::option::Option<super::tasks::DurationHistogram>,
+}
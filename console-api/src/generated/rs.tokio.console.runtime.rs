@@ -0,0 +1,93 @@
+// This file is @generated by prost-build.
+/// A runtime state update.
+///
+/// This describes the threads which poll tasks in the instrumented process.
+#[cfg_attr(feature = "serde", derive(::serde::Serialize, ::serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct RuntimeUpdate {
+    /// Every thread which has polled a task and is still running.
+    ///
+    /// Each entry is a complete snapshot of the statistics for that thread.
+    #[prost(message, repeated, tag = "1")]
+    pub workers: ::prost::alloc::vec::Vec<Worker>,
+}
+/// Statistics for a thread which polls tasks.
+///
+/// Most of the threads which poll tasks are the runtime's worker threads. If
+/// the instrumented process provides its runtime's metrics, the statistics the
+/// runtime records for each of its workers are also included. This requires
+/// Tokio to be built with `--cfg tokio_unstable`.
+#[cfg_attr(feature = "serde", derive(::serde::Serialize, ::serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct Worker {
+    /// An ID for the thread, which is unique within the instrumented process.
+    ///
+    /// This is the same ID that identifies the thread in
+    /// `tasks.ThreadPolls`.
+    #[prost(uint64, tag = "1")]
+    pub thread_id: u64,
+    /// The name of the thread, if it has one.
+    #[prost(string, optional, tag = "2")]
+    pub thread_name: ::core::option::Option<::prost::alloc::string::String>,
+    /// The task which is currently being polled on this thread, if any.
+    #[prost(message, optional, tag = "3")]
+    pub current_task: ::core::option::Option<super::common::Id>,
+    /// The total number of times tasks have been polled on this thread.
+    ///
+    /// Polls of tasks spawned onto a `LocalSet` are counted separately, in
+    /// `local_polls`.
+    #[prost(uint64, tag = "4")]
+    pub polls: u64,
+    /// The index of the worker in the runtime, if this thread is one of the
+    /// runtime's worker threads.
+    ///
+    /// The remaining fields are only present if this is set.
+    #[prost(uint64, optional, tag = "5")]
+    pub worker_index: ::core::option::Option<u64>,
+    /// The number of tasks the worker has stolen from other workers.
+    #[prost(uint64, optional, tag = "6")]
+    pub steals: ::core::option::Option<u64>,
+    /// The number of times the worker has parked.
+    #[prost(uint64, optional, tag = "7")]
+    pub parks: ::core::option::Option<u64>,
+    /// The number of times the worker has been unparked.
+    #[prost(uint64, optional, tag = "8")]
+    pub unparks: ::core::option::Option<u64>,
+    /// The scheduler of the tasks most recently polled on this thread, other
+    /// than those spawned onto a `LocalSet`.
+    #[prost(enumeration = "super::tasks::task::Scheduler", tag = "9")]
+    pub scheduler: i32,
+    /// The total number of times tasks spawned onto a `LocalSet` have been
+    /// polled on this thread.
+    #[prost(uint64, tag = "10")]
+    pub local_polls: u64,
+    /// The total time this thread has spent polling tasks, broken down by the
+    /// locations the tasks were spawned at.
+    ///
+    /// Only a limited number of locations are tracked for each thread. Time
+    /// spent polling tasks spawned at any other location, or at an unknown
+    /// one, is counted in `other_busy_time`.
+    #[prost(message, repeated, tag = "11")]
+    pub busy_by_location: ::prost::alloc::vec::Vec<LocationBusyTime>,
+    /// The total time this thread has spent polling tasks which are not
+    /// included in `busy_by_location`.
+    #[prost(message, optional, tag = "12")]
+    #[cfg_attr(feature = "serde", serde(default, with = "crate::serde_util::option_string"))]
+    pub other_busy_time: ::core::option::Option<::prost_types::Duration>,
+}
+/// The total time a thread has spent polling the tasks spawned at a single
+/// location.
+#[cfg_attr(feature = "serde", derive(::serde::Serialize, ::serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct LocationBusyTime {
+    /// The location the tasks were spawned at.
+    #[prost(message, optional, tag = "1")]
+    pub location: ::core::option::Option<super::common::Location>,
+    /// The total time spent polling the tasks.
+    #[prost(message, optional, tag = "2")]
+    #[cfg_attr(feature = "serde", serde(default, with = "crate::serde_util::option_string"))]
+    pub busy_time: ::core::option::Option<::prost_types::Duration>,
+}
@@ -0,0 +1,88 @@
+// This file is @generated by prost-build.
+/// A watched span state update.
+///
+/// This includes a list of any new watched spans, and updates to the associated
+/// statistics for any watched spans that have changed since the last update.
+#[cfg_attr(feature = "serde", derive(::serde::Serialize, ::serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct SpanUpdate {
+    /// A list of new watched spans that were created since the last `SpanUpdate`
+    /// was sent.
+    #[prost(message, repeated, tag = "1")]
+    pub new_spans: ::prost::alloc::vec::Vec<Span>,
+    /// Any watched span stats that have changed since the last update.
+    #[prost(map = "uint64, message", tag = "2")]
+    pub stats_update: ::std::collections::HashMap<u64, Stats>,
+    /// A count of how many watched span events (e.g. creation, entering,
+    /// exiting, etc) were not recorded because the application's event buffer
+    /// was at capacity.
+    ///
+    /// If everything is working normally, this should be 0. If it is greater
+    /// than 0, that may indicate that some data is missing from this update, and
+    /// it may be necessary to increase the number of events buffered by the
+    /// application to ensure that data loss is avoided.
+    #[prost(uint64, tag = "3")]
+    pub dropped_events: u64,
+}
+/// A watched span.
+///
+/// This is a `tracing` span which isn't a task, resource, or async op, but
+/// which the instrumented application has asked the console to track, so that
+/// the time spent in it can be shown. Applications mark a span as watched by
+/// giving it a `console.watch` field, such as with the `watch_span!` macro in
+/// `console-subscriber`.
+#[cfg_attr(feature = "serde", derive(::serde::Serialize, ::serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct Span {
+    /// The span's ID.
+    ///
+    /// This uniquely identifies this span across all *currently live* watched
+    /// spans.
+    #[prost(message, optional, tag = "1")]
+    pub id: ::core::option::Option<super::common::Id>,
+    /// The numeric ID of the span's `Metadata`.
+    ///
+    /// The metadata for this ID will have been sent in a prior
+    /// `RegisterMetadata` message. It includes the span's name and target.
+    #[prost(message, optional, tag = "2")]
+    pub metadata: ::core::option::Option<super::common::MetaId>,
+    /// The span's fields, other than `console.watch`.
+    #[prost(message, repeated, tag = "3")]
+    pub fields: ::prost::alloc::vec::Vec<super::common::Field>,
+    /// The location in code where the span was created.
+    #[prost(message, optional, tag = "4")]
+    pub location: ::core::option::Option<super::common::Location>,
+    /// The ID of the task which the span was created in.
+    ///
+    /// This field is empty if the span wasn't created while a task was being
+    /// polled.
+    #[prost(message, optional, tag = "5")]
+    pub task_id: ::core::option::Option<super::common::Id>,
+    /// The number of fields which were not recorded, because the span had more
+    /// fields than the maximum number of fields per task configured in the
+    /// instrumented application.
+    #[prost(uint64, tag = "6")]
+    pub omitted_fields: u64,
+}
+/// Statistics associated with a given watched span.
+#[cfg_attr(feature = "serde", derive(::serde::Serialize, ::serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct Stats {
+    /// Timestamp of when the span was created.
+    #[prost(message, optional, tag = "1")]
+    #[cfg_attr(feature = "serde", serde(default, with = "crate::serde_util::option_string"))]
+    pub created_at: ::core::option::Option<::prost_types::Timestamp>,
+    /// Timestamp of when the span was closed.
+    #[prost(message, optional, tag = "2")]
+    #[cfg_attr(feature = "serde", serde(default, with = "crate::serde_util::option_string"))]
+    pub dropped_at: ::core::option::Option<::prost_types::Timestamp>,
+    /// How many times the span has been entered, and for how long.
+    ///
+    /// Each time the span is entered and exited is counted as a poll, so
+    /// `busy_time` is the total time spent inside the span.
+    #[prost(message, optional, tag = "3")]
+    pub enter_stats: ::core::option::Option<super::common::PollStats>,
+}
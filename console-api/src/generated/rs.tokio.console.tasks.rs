@@ -5,6 +5,8 @@
 /// update. This includes:
 /// - any new tasks that were spawned since the last update
 /// - the current stats for any task whose stats changed since the last update
+#[cfg_attr(feature = "serde", derive(::serde::Serialize, ::serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct TaskUpdate {
     /// A list of new tasks that were spawned since the last `TaskUpdate` was
@@ -34,8 +36,49 @@ pub struct TaskUpdate {
     /// this will always be 0.
     #[prost(uint64, tag = "4")]
     pub dropped_events: u64,
+    /// Aggregate statistics for all tasks spawned over the lifetime of the
+    /// instrumented process.
+    ///
+    /// Unlike the per-task `Stats`, these are not affected by the retention
+    /// period for completed tasks, so they reflect history from before the
+    /// client connected.
+    #[prost(message, optional, tag = "5")]
+    pub aggregates: ::core::option::Option<Aggregates>,
+    /// Samples of task activity recorded since the last `TaskUpdate` was sent.
+    ///
+    /// In the first update sent to a client, this contains every sample
+    /// retained by the instrumented process, so that recent activity is
+    /// available as soon as the client connects.
+    #[prost(message, optional, tag = "6")]
+    pub activity: ::core::option::Option<ActivitySamples>,
+    /// Statistics for any instrumented `select!` callsites whose branch
+    /// statistics changed since the last update.
+    ///
+    /// Each entry is a complete snapshot of the statistics for that callsite,
+    /// which replaces any previously received entry with the same location.
+    #[prost(message, repeated, tag = "7")]
+    pub select_stats: ::prost::alloc::vec::Vec<SelectStats>,
+    /// The extensions of any tasks whose extensions changed since the last
+    /// update.
+    ///
+    /// This is a map of task IDs to the complete list of that task's
+    /// extensions, which replaces any previously received list. Tasks which
+    /// are first sent in this update's `new_tasks` are not included, since
+    /// their extensions are part of the `Task`.
+    #[prost(map = "uint64, message", tag = "8")]
+    pub extensions_update: ::std::collections::HashMap<u64, Extensions>,
+    /// The completed tasks whose wakers are still alive, long after the
+    /// instrumented application stopped retaining the tasks themselves.
+    ///
+    /// If this is not set, the set of such tasks and their waker counts have
+    /// not changed since the last update. Otherwise, it is a complete snapshot
+    /// which replaces any previously received one.
+    #[prost(message, optional, tag = "9")]
+    pub waker_leaks: ::core::option::Option<WakerLeaks>,
 }
 /// A task details update
+#[cfg_attr(feature = "serde", derive(::serde::Serialize, ::serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct TaskDetails {
     /// The task's ID which the details belong to.
@@ -43,6 +86,7 @@ pub struct TaskDetails {
     pub task_id: ::core::option::Option<super::common::Id>,
     /// The timestamp for when the update to the task took place.
     #[prost(message, optional, tag = "2")]
+    #[cfg_attr(feature = "serde", serde(default, with = "crate::serde_util::option_string"))]
     pub now: ::core::option::Option<::prost_types::Timestamp>,
     /// A histogram of task scheduled durations.
     ///
@@ -50,6 +94,24 @@ pub struct TaskDetails {
     /// woken and when it is next polled.
     #[prost(message, optional, tag = "5")]
     pub scheduled_times_histogram: ::core::option::Option<DurationHistogram>,
+    /// The threads which have polled the task, and how many times each one
+    /// polled it.
+    ///
+    /// Only a limited number of threads are tracked for each task. Polls on
+    /// any other threads are counted in `other_thread_polls`.
+    #[prost(message, repeated, tag = "6")]
+    pub thread_polls: ::prost::alloc::vec::Vec<ThreadPolls>,
+    /// The number of polls on threads which are not included in
+    /// `thread_polls`.
+    #[prost(uint64, tag = "7")]
+    pub other_thread_polls: u64,
+    /// A histogram of the time between consecutive wakes of the task.
+    ///
+    /// Many wakes in quick succession, which show up as a spike of very
+    /// short intervals, suggest that the code notifying the task is waking it
+    /// far more often than it needs to be polled.
+    #[prost(message, optional, tag = "8")]
+    pub wake_intervals_histogram: ::core::option::Option<DurationHistogram>,
     /// A histogram of task poll durations.
     ///
     /// This is either:
@@ -67,6 +129,7 @@ pub mod task_details {
     /// - the raw binary representation of a HdrHistogram.rs `Histogram`
     ///    serialized to binary in the V2 format (legacy)
     /// - a binary histogram plus details on outliers (current)
+    #[cfg_attr(feature = "serde", derive(::serde::Serialize, ::serde::Deserialize))]
     #[derive(Clone, PartialEq, ::prost::Oneof)]
     pub enum PollTimesHistogram {
         /// HdrHistogram.rs `Histogram` serialized to binary in the V2 format
@@ -77,7 +140,77 @@ pub mod task_details {
         Histogram(super::DurationHistogram),
     }
 }
+/// The number of times a task was polled on a single thread.
+#[cfg_attr(feature = "serde", derive(::serde::Serialize, ::serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ThreadPolls {
+    /// An ID for the thread, which is unique within the instrumented process.
+    ///
+    /// Threads are numbered in the order in which they first poll a task, so
+    /// this is not the same as the operating system's ID for the thread.
+    #[prost(uint64, tag = "1")]
+    pub thread_id: u64,
+    /// The name of the thread, if it has one.
+    #[prost(string, optional, tag = "2")]
+    pub name: ::core::option::Option<::prost::alloc::string::String>,
+    /// The number of times the thread polled the task.
+    #[prost(uint64, tag = "3")]
+    pub polls: u64,
+}
+/// Task details updates for a set of tasks.
+#[cfg_attr(feature = "serde", derive(::serde::Serialize, ::serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct TaskDetailsSet {
+    /// The timestamp for when the update to the tasks took place.
+    #[prost(message, optional, tag = "1")]
+    #[cfg_attr(feature = "serde", serde(default, with = "crate::serde_util::option_string"))]
+    pub now: ::core::option::Option<::prost_types::Timestamp>,
+    /// The details of each watched task which still exists.
+    ///
+    /// Tasks which have been dropped are no longer included.
+    #[prost(message, repeated, tag = "2")]
+    pub details: ::prost::alloc::vec::Vec<TaskDetails>,
+}
+/// Statistics for groups of tasks spawned from the same location.
+#[cfg_attr(feature = "serde", derive(::serde::Serialize, ::serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct TaskGroups {
+    /// The timestamp for when the update to the groups took place.
+    #[prost(message, optional, tag = "1")]
+    #[cfg_attr(feature = "serde", serde(default, with = "crate::serde_util::option_string"))]
+    pub now: ::core::option::Option<::prost_types::Timestamp>,
+    /// The groups of tasks, one for each location that tasks have been
+    /// spawned from.
+    ///
+    /// Each entry is a complete snapshot of the statistics for that group.
+    #[prost(message, repeated, tag = "2")]
+    pub groups: ::prost::alloc::vec::Vec<TaskGroup>,
+}
+/// Statistics merged across all the tasks spawned from the same location.
+#[cfg_attr(feature = "serde", derive(::serde::Serialize, ::serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct TaskGroup {
+    /// The location the tasks in this group were spawned from.
+    #[prost(message, optional, tag = "1")]
+    pub location: ::core::option::Option<super::common::Location>,
+    /// A histogram of poll durations, merged from the poll duration
+    /// histograms of every task in the group.
+    #[prost(message, optional, tag = "2")]
+    pub poll_times_histogram: ::core::option::Option<DurationHistogram>,
+    /// A histogram of how long each task in the group waited between being
+    /// spawned and being polled for the first time.
+    ///
+    /// Tasks which haven't been polled yet aren't included.
+    #[prost(message, optional, tag = "3")]
+    pub first_poll_delay_histogram: ::core::option::Option<DurationHistogram>,
+}
 /// Data recorded when a new task is spawned.
+#[cfg_attr(feature = "serde", derive(::serde::Serialize, ::serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct Task {
     /// The task's ID.
@@ -118,10 +251,44 @@ pub struct Task {
     /// The location in code where the task was spawned.
     #[prost(message, optional, tag = "6")]
     pub location: ::core::option::Option<super::common::Location>,
+    /// The number of fields which were not recorded, because the task had more
+    /// fields than the maximum number of fields per task configured in the
+    /// instrumented application.
+    #[prost(uint64, tag = "7")]
+    pub omitted_fields: u64,
+    /// The kind of scheduler which runs the task.
+    #[prost(enumeration = "task::Scheduler", tag = "8")]
+    pub scheduler: i32,
+    /// Payloads attached to this task by the instrumented application.
+    ///
+    /// These are defined by the application rather than by this protocol, so
+    /// clients which don't know a payload's type should display it as raw
+    /// data. A task has at most one payload of each type.
+    #[prost(message, repeated, tag = "9")]
+    #[cfg_attr(feature = "serde", serde(default, with = "crate::serde_util::any_list"))]
+    pub extensions: ::prost::alloc::vec::Vec<::prost_types::Any>,
+    /// Whether the task had already completed, and its retention period had
+    /// elapsed, when the client started watching.
+    ///
+    /// Such tasks are only sent in the first update to a new client, if the
+    /// instrumented application keeps its most recently completed tasks, so
+    /// that a client which attaches just after something happened can still
+    /// see which tasks recently finished. Clients should keep them even though
+    /// they completed longer ago than the client would retain tasks for.
+    #[prost(bool, tag = "10")]
+    pub historical: bool,
+    /// The level of the `tracing` span representing the task.
+    ///
+    /// This is the same as the level in the task's metadata. If this is not
+    /// present, the instrumented application predates this field, and clients
+    /// should use the metadata's level instead.
+    #[prost(enumeration = "super::common::metadata::Level", optional, tag = "11")]
+    pub level: ::core::option::Option<i32>,
 }
 /// Nested message and enum types in `Task`.
 pub mod task {
     /// The category of task this task belongs to.
+    #[cfg_attr(feature = "serde", derive(::serde::Serialize, ::serde::Deserialize))]
     #[derive(
         Clone,
         Copy,
@@ -162,15 +329,70 @@ pub mod task {
             }
         }
     }
+    /// The kind of scheduler which runs a task.
+    #[cfg_attr(feature = "serde", derive(::serde::Serialize, ::serde::Deserialize))]
+    #[derive(
+        Clone,
+        Copy,
+        Debug,
+        PartialEq,
+        Eq,
+        Hash,
+        PartialOrd,
+        Ord,
+        ::prost::Enumeration
+    )]
+    #[repr(i32)]
+    pub enum Scheduler {
+        /// The scheduler isn't known, such as for blocking tasks, or tasks
+        /// spawned from outside of a runtime.
+        Unknown = 0,
+        /// The task was spawned onto a multi-threaded runtime, and may be
+        /// polled by any of its workers.
+        MultiThread = 1,
+        /// The task was spawned onto a current-thread runtime.
+        CurrentThread = 2,
+        /// The task was spawned onto a `LocalSet`, and is only polled by the
+        /// thread running it.
+        Local = 3,
+    }
+    impl Scheduler {
+        /// String value of the enum field names used in the ProtoBuf definition.
+        ///
+        /// The values are not transformed in any way and thus are considered stable
+        /// (if the ProtoBuf definition does not change) and safe for programmatic use.
+        pub fn as_str_name(&self) -> &'static str {
+            match self {
+                Self::Unknown => "UNKNOWN",
+                Self::MultiThread => "MULTI_THREAD",
+                Self::CurrentThread => "CURRENT_THREAD",
+                Self::Local => "LOCAL",
+            }
+        }
+        /// Creates an enum from field names used in the ProtoBuf definition.
+        pub fn from_str_name(value: &str) -> ::core::option::Option<Self> {
+            match value {
+                "UNKNOWN" => Some(Self::Unknown),
+                "MULTI_THREAD" => Some(Self::MultiThread),
+                "CURRENT_THREAD" => Some(Self::CurrentThread),
+                "LOCAL" => Some(Self::Local),
+                _ => None,
+            }
+        }
+    }
 }
 /// Task performance statistics.
-#[derive(Clone, Copy, PartialEq, ::prost::Message)]
+#[cfg_attr(feature = "serde", derive(::serde::Serialize, ::serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+#[derive(Clone, PartialEq, ::prost::Message)]
 pub struct Stats {
     /// Timestamp of when the task was spawned.
     #[prost(message, optional, tag = "1")]
+    #[cfg_attr(feature = "serde", serde(default, with = "crate::serde_util::option_string"))]
     pub created_at: ::core::option::Option<::prost_types::Timestamp>,
     /// Timestamp of when the task was dropped.
     #[prost(message, optional, tag = "2")]
+    #[cfg_attr(feature = "serde", serde(default, with = "crate::serde_util::option_string"))]
     pub dropped_at: ::core::option::Option<::prost_types::Timestamp>,
     /// The total number of times this task has been woken over its lifetime.
     #[prost(uint64, tag = "3")]
@@ -185,6 +407,7 @@ pub struct Stats {
     ///
     /// If this is `None`, the task has not yet been woken.
     #[prost(message, optional, tag = "6")]
+    #[cfg_attr(feature = "serde", serde(default, with = "crate::serde_util::option_string"))]
     pub last_wake: ::core::option::Option<::prost_types::Timestamp>,
     /// Contains task poll statistics.
     #[prost(message, optional, tag = "7")]
@@ -202,8 +425,81 @@ pub struct Stats {
     /// amount of time it spent unable to progress because it was waiting on
     /// some resource.
     #[prost(message, optional, tag = "9")]
+    #[cfg_attr(feature = "serde", serde(default, with = "crate::serde_util::option_string"))]
     pub scheduled_time: ::core::option::Option<::prost_types::Duration>,
+    /// The duration of this task's longest poll.
+    ///
+    /// Unlike the poll times histogram, this isn't clamped to a maximum value,
+    /// so it's exact however long the poll took. If this is `None`, no poll of
+    /// this task has completed yet.
+    #[prost(message, optional, tag = "10")]
+    #[cfg_attr(feature = "serde", serde(default, with = "crate::serde_util::option_string"))]
+    pub max_poll_time: ::core::option::Option<::prost_types::Duration>,
+    /// The timestamp of when this task's longest poll started.
+    #[prost(message, optional, tag = "11")]
+    #[cfg_attr(feature = "serde", serde(default, with = "crate::serde_util::option_string"))]
+    pub max_poll_started_at: ::core::option::Option<::prost_types::Timestamp>,
+    /// The other tasks which have woken this task, and how many times each one
+    /// woke it.
+    ///
+    /// Only a limited number of waking tasks are tracked for each task. Wakes
+    /// by any other tasks are counted in `other_task_wakes`. Wakes from
+    /// outside of any task, such as by the runtime's I/O and timer drivers,
+    /// are not included, and neither are the task's wakes of itself.
+    #[prost(message, repeated, tag = "12")]
+    pub woken_by: ::prost::alloc::vec::Vec<TaskWakes>,
+    /// The number of wakes by tasks which are not included in `woken_by`.
+    #[prost(uint64, tag = "13")]
+    pub other_task_wakes: u64,
+    /// The memory allocated and deallocated while this task was being polled.
+    ///
+    /// This is only present if the instrumented application tracks
+    /// allocations, which requires it to use the console subscriber's
+    /// tracking allocator as its global allocator.
+    #[prost(message, optional, tag = "14")]
+    pub alloc_stats: ::core::option::Option<AllocStats>,
+    /// The number of this task's wakers which are currently alive, which is
+    /// `waker_clones` minus `waker_drops`.
+    ///
+    /// This is negative if more wakers were dropped than were cloned, which
+    /// means that the instrumentation missed some clones. If this is `None`,
+    /// the subscriber predates this field and it must be computed from
+    /// `waker_clones` and `waker_drops`.
+    #[prost(int64, optional, tag = "15")]
+    pub live_wakers: ::core::option::Option<i64>,
+}
+/// Counts of the allocations made while a task was being polled.
+#[cfg_attr(feature = "serde", derive(::serde::Serialize, ::serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+#[derive(Clone, Copy, PartialEq, ::prost::Message)]
+pub struct AllocStats {
+    /// The number of allocations.
+    #[prost(uint64, tag = "1")]
+    pub allocations: u64,
+    /// The number of deallocations.
+    #[prost(uint64, tag = "2")]
+    pub deallocations: u64,
+    /// The total number of bytes allocated.
+    #[prost(uint64, tag = "3")]
+    pub allocated_bytes: u64,
+    /// The total number of bytes deallocated.
+    #[prost(uint64, tag = "4")]
+    pub deallocated_bytes: u64,
 }
+/// The number of times a task was woken by another task.
+#[cfg_attr(feature = "serde", derive(::serde::Serialize, ::serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+#[derive(Clone, Copy, PartialEq, ::prost::Message)]
+pub struct TaskWakes {
+    /// The span ID of the task which performed the wakes.
+    #[prost(message, optional, tag = "1")]
+    pub task_id: ::core::option::Option<super::common::Id>,
+    /// The number of times that task performed a wake.
+    #[prost(uint64, tag = "2")]
+    pub wakes: u64,
+}
+#[cfg_attr(feature = "serde", derive(::serde::Serialize, ::serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct DurationHistogram {
     /// HdrHistogram.rs `Histogram` serialized to binary in the V2 format
@@ -220,3 +516,144 @@ pub struct DurationHistogram {
     #[prost(uint64, optional, tag = "4")]
     pub highest_outlier: ::core::option::Option<u64>,
 }
+/// Aggregate statistics for all tasks spawned over the lifetime of the
+/// instrumented process.
+#[cfg_attr(feature = "serde", derive(::serde::Serialize, ::serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+#[derive(Clone, Copy, PartialEq, ::prost::Message)]
+pub struct Aggregates {
+    /// The total number of tasks that have been spawned.
+    #[prost(uint64, tag = "1")]
+    pub total_spawned: u64,
+    /// The total number of tasks that have completed.
+    #[prost(uint64, tag = "2")]
+    pub total_completed: u64,
+    /// The highest number of tasks that have been alive at the same time.
+    #[prost(uint64, tag = "3")]
+    pub max_concurrent: u64,
+    /// The total time spent polling tasks, summed across all tasks.
+    #[prost(message, optional, tag = "4")]
+    #[cfg_attr(feature = "serde", serde(default, with = "crate::serde_util::option_string"))]
+    pub total_busy_time: ::core::option::Option<::prost_types::Duration>,
+}
+/// Counts of task activity, recorded by the instrumented process once per
+/// sampling interval.
+///
+/// Each repeated field contains one entry per interval, ordered from oldest to
+/// newest, and all repeated fields have the same length.
+#[cfg_attr(feature = "serde", derive(::serde::Serialize, ::serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ActivitySamples {
+    /// The length of each sampled interval.
+    #[prost(message, optional, tag = "1")]
+    #[cfg_attr(feature = "serde", serde(default, with = "crate::serde_util::option_string"))]
+    pub interval: ::core::option::Option<::prost_types::Duration>,
+    /// The number of tasks spawned during each interval.
+    #[prost(uint64, repeated, tag = "2")]
+    pub spawned: ::prost::alloc::vec::Vec<u64>,
+    /// The number of tasks that completed during each interval.
+    #[prost(uint64, repeated, tag = "3")]
+    pub completed: ::prost::alloc::vec::Vec<u64>,
+    /// The number of times any task was woken during each interval.
+    #[prost(uint64, repeated, tag = "4")]
+    pub wakes: ::prost::alloc::vec::Vec<u64>,
+    /// The sequence number of the first sample in this message.
+    ///
+    /// Samples are numbered consecutively from zero, starting with the first
+    /// sample recorded by the instrumented process. This allows clients which
+    /// reconnect to discard samples they have already received.
+    #[prost(uint64, tag = "5")]
+    pub first_sample: u64,
+}
+/// Statistics for a single instrumented `select!` callsite.
+#[cfg_attr(feature = "serde", derive(::serde::Serialize, ::serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct SelectStats {
+    /// The location of the `select!` in the source code.
+    #[prost(message, optional, tag = "1")]
+    pub location: ::core::option::Option<super::common::Location>,
+    /// Statistics for each branch of the `select!`, in the order in which the
+    /// branches are declared.
+    #[prost(message, repeated, tag = "2")]
+    pub branches: ::prost::alloc::vec::Vec<SelectBranchStats>,
+}
+/// Statistics for a single branch of an instrumented `select!`.
+#[cfg_attr(feature = "serde", derive(::serde::Serialize, ::serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+#[derive(Clone, Copy, PartialEq, ::prost::Message)]
+pub struct SelectBranchStats {
+    /// The number of times this branch completed the `select!`.
+    #[prost(uint64, tag = "1")]
+    pub completions: u64,
+    /// The total time spent waiting in the `select!` before this branch
+    /// completed, summed across all completions.
+    #[prost(message, optional, tag = "2")]
+    #[cfg_attr(feature = "serde", serde(default, with = "crate::serde_util::option_string"))]
+    pub total_wait: ::core::option::Option<::prost_types::Duration>,
+    /// The longest time spent waiting in the `select!` before this branch
+    /// completed.
+    #[prost(message, optional, tag = "3")]
+    #[cfg_attr(feature = "serde", serde(default, with = "crate::serde_util::option_string"))]
+    pub max_wait: ::core::option::Option<::prost_types::Duration>,
+}
+/// The payloads attached to a task by the instrumented application.
+#[cfg_attr(feature = "serde", derive(::serde::Serialize, ::serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct Extensions {
+    /// The task's payloads, at most one of each type.
+    #[prost(message, repeated, tag = "1")]
+    #[cfg_attr(feature = "serde", serde(default, with = "crate::serde_util::any_list"))]
+    pub extensions: ::prost::alloc::vec::Vec<::prost_types::Any>,
+}
+/// The completed tasks which may be leaking wakers.
+#[cfg_attr(feature = "serde", derive(::serde::Serialize, ::serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct WakerLeaks {
+    /// Each task which still had wakers alive when the instrumented
+    /// application stopped retaining it, and still does.
+    #[prost(message, repeated, tag = "1")]
+    pub leaks: ::prost::alloc::vec::Vec<WakerLeak>,
+}
+/// A completed task with wakers which are still alive.
+///
+/// Wakers which outlive their task keep the task's memory allocated, so a
+/// task which is still listed here long after it completed has most likely
+/// had a waker leaked, such as by a future which stores wakers without ever
+/// removing them.
+#[cfg_attr(feature = "serde", derive(::serde::Serialize, ::serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct WakerLeak {
+    /// The task's span ID.
+    ///
+    /// This is the ID the task was sent with while it was retained, so it may
+    /// since have been reused by another task.
+    #[prost(message, optional, tag = "1")]
+    pub id: ::core::option::Option<super::common::Id>,
+    /// The task's `task.id` field, if it had one.
+    #[prost(uint64, optional, tag = "2")]
+    pub task_id: ::core::option::Option<u64>,
+    /// The task's `task.name` field, if it had one.
+    #[prost(string, optional, tag = "3")]
+    pub name: ::core::option::Option<::prost::alloc::string::String>,
+    /// The location in code where the task was spawned.
+    #[prost(message, optional, tag = "4")]
+    pub location: ::core::option::Option<super::common::Location>,
+    /// When the task completed.
+    #[prost(message, optional, tag = "5")]
+    #[cfg_attr(feature = "serde", serde(default, with = "crate::serde_util::option_string"))]
+    pub dropped_at: ::core::option::Option<::prost_types::Timestamp>,
+    /// The total number of times the task's waker has been cloned.
+    #[prost(uint64, tag = "6")]
+    pub waker_clones: u64,
+    /// The total number of times the task's waker has been dropped.
+    ///
+    /// The task's wakers which are still alive are `waker_clones` minus
+    /// `waker_drops`.
+    #[prost(uint64, tag = "7")]
+    pub waker_drops: u64,
+}
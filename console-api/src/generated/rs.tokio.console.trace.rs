@@ -1,5 +1,7 @@
 // This file is @generated by prost-build.
 /// Start watching trace events with the provided filter.
+#[cfg_attr(feature = "serde", derive(::serde::Serialize, ::serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct WatchRequest {
     /// Specifies which trace events should be streamed.
@@ -7,6 +9,8 @@ pub struct WatchRequest {
     pub filter: ::prost::alloc::string::String,
 }
 /// A trace event
+#[cfg_attr(feature = "serde", derive(::serde::Serialize, ::serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct TraceEvent {
     /// A trace event
@@ -16,6 +20,8 @@ pub struct TraceEvent {
 /// Nested message and enum types in `TraceEvent`.
 pub mod trace_event {
     /// `RegisterThreads` signals that a new thread was registered.
+    #[cfg_attr(feature = "serde", derive(::serde::Serialize, ::serde::Deserialize))]
+    #[cfg_attr(feature = "serde", serde(default))]
     #[derive(Clone, PartialEq, ::prost::Message)]
     pub struct RegisterThreads {
         /// `names` maps the registered thread id's to their associated name.
@@ -23,6 +29,8 @@ pub mod trace_event {
         pub names: ::std::collections::HashMap<u64, ::prost::alloc::string::String>,
     }
     /// `Enter` signals that a span was entered.
+    #[cfg_attr(feature = "serde", derive(::serde::Serialize, ::serde::Deserialize))]
+    #[cfg_attr(feature = "serde", serde(default))]
     #[derive(Clone, Copy, PartialEq, ::prost::Message)]
     pub struct Enter {
         /// `span_id` identifies the span that was entered.
@@ -33,9 +41,12 @@ pub mod trace_event {
         pub thread_id: u64,
         /// `at` identifies when the span was entered.
         #[prost(message, optional, tag = "3")]
+        #[cfg_attr(feature = "serde", serde(default, with = "crate::serde_util::option_string"))]
         pub at: ::core::option::Option<::prost_types::Timestamp>,
     }
     /// `Exit` signals that a span was exited.
+    #[cfg_attr(feature = "serde", derive(::serde::Serialize, ::serde::Deserialize))]
+    #[cfg_attr(feature = "serde", serde(default))]
     #[derive(Clone, Copy, PartialEq, ::prost::Message)]
     pub struct Exit {
         /// `span_id` identifies the span that was exited.
@@ -46,9 +57,12 @@ pub mod trace_event {
         pub thread_id: u64,
         /// `at` identifies when the span was exited.
         #[prost(message, optional, tag = "3")]
+        #[cfg_attr(feature = "serde", serde(default, with = "crate::serde_util::option_string"))]
         pub at: ::core::option::Option<::prost_types::Timestamp>,
     }
     /// `Close` signals that a span was closed.
+    #[cfg_attr(feature = "serde", derive(::serde::Serialize, ::serde::Deserialize))]
+    #[cfg_attr(feature = "serde", serde(default))]
     #[derive(Clone, Copy, PartialEq, ::prost::Message)]
     pub struct Close {
         /// `span_id` identifies the span that was closed.
@@ -56,9 +70,11 @@ pub mod trace_event {
         pub span_id: ::core::option::Option<super::super::common::SpanId>,
         /// `at` identifies when the span was closed.
         #[prost(message, optional, tag = "2")]
+        #[cfg_attr(feature = "serde", serde(default, with = "crate::serde_util::option_string"))]
         pub at: ::core::option::Option<::prost_types::Timestamp>,
     }
     /// A trace event
+    #[cfg_attr(feature = "serde", derive(::serde::Serialize, ::serde::Deserialize))]
     #[derive(Clone, PartialEq, ::prost::Oneof)]
     pub enum Event {
         /// A new thread was registered.
@@ -82,6 +98,7 @@ pub mod trace_event {
     }
 }
 /// Generated client implementations.
+#[cfg(feature = "client")]
 pub mod trace_client {
     #![allow(
         unused_variables,
@@ -201,6 +218,7 @@ pub mod trace_client {
     }
 }
 /// Generated server implementations.
+#[cfg(feature = "server")]
 pub mod trace_server {
     #![allow(
         unused_variables,
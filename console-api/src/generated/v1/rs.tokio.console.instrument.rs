@@ -0,0 +1,96 @@
+// This file is @generated by prost-build.
+/// InstrumentRequest requests the stream of updates
+/// to observe the async runtime state over time.
+///
+/// TODO: In the future allow for the request to specify
+/// only the data that the caller cares about (i.e. only
+/// tasks but no resources)
+#[derive(Clone, Copy, PartialEq, ::prost::Message)]
+pub struct InstrumentRequest {}
+/// TaskDetailsRequest requests the stream of updates about
+/// the specific task identified in the request.
+#[derive(Clone, Copy, PartialEq, ::prost::Message)]
+pub struct TaskDetailsRequest {
+    /// Identifies the task for which details were requested.
+    #[prost(message, optional, tag = "1")]
+    pub id: ::core::option::Option<super::common::Id>,
+}
+/// PauseRequest requests the stream of updates to pause.
+#[derive(Clone, Copy, PartialEq, ::prost::Message)]
+pub struct PauseRequest {}
+/// ResumeRequest requests the stream of updates to resume after a pause.
+#[derive(Clone, Copy, PartialEq, ::prost::Message)]
+pub struct ResumeRequest {}
+/// Update carries all information regarding tasks, resources, async operations
+/// and resource operations in one message. There are a couple of reasons to combine all
+/// of these into a single message:
+///
+/// - we can use one single timestamp for all the data
+/// - we can have all the new_metadata in one place
+/// - things such as async ops and resource ops do not make sense
+///    on their own as they have relations to tasks and resources
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct Update {
+    /// The system time when this update was recorded.
+    ///
+    /// This is the timestamp any durations in the included `Stats` were
+    /// calculated relative to.
+    #[prost(message, optional, tag = "1")]
+    pub now: ::core::option::Option<::prost_types::Timestamp>,
+    /// Task state update.
+    #[prost(message, optional, tag = "2")]
+    pub task_update: ::core::option::Option<super::tasks::TaskUpdate>,
+    /// Resource state update.
+    #[prost(message, optional, tag = "3")]
+    pub resource_update: ::core::option::Option<super::resources::ResourceUpdate>,
+    /// Async operations state update
+    #[prost(message, optional, tag = "4")]
+    pub async_op_update: ::core::option::Option<super::async_ops::AsyncOpUpdate>,
+    /// Any new span metadata that was registered since the last update.
+    #[prost(message, optional, tag = "5")]
+    pub new_metadata: ::core::option::Option<super::common::RegisterMetadata>,
+}
+/// StateRequest requests the current state of the aggregator.
+#[derive(Clone, Copy, PartialEq, ::prost::Message)]
+pub struct StateRequest {}
+/// State carries the current state of the aggregator.
+#[derive(Clone, Copy, PartialEq, ::prost::Message)]
+pub struct State {
+    #[prost(enumeration = "Temporality", tag = "1")]
+    pub temporality: i32,
+}
+/// `PauseResponse` is the value returned after a pause request.
+#[derive(Clone, Copy, PartialEq, ::prost::Message)]
+pub struct PauseResponse {}
+/// `ResumeResponse` is the value returned after a resume request.
+#[derive(Clone, Copy, PartialEq, ::prost::Message)]
+pub struct ResumeResponse {}
+/// The time "state" of the aggregator.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
+#[repr(i32)]
+pub enum Temporality {
+    /// The aggregator is currently live.
+    Live = 0,
+    /// The aggregator is currently paused.
+    Paused = 1,
+}
+impl Temporality {
+    /// String value of the enum field names used in the ProtoBuf definition.
+    ///
+    /// The values are not transformed in any way and thus are considered stable
+    /// (if the ProtoBuf definition does not change) and safe for programmatic use.
+    pub fn as_str_name(&self) -> &'static str {
+        match self {
+            Self::Live => "LIVE",
+            Self::Paused => "PAUSED",
+        }
+    }
+    /// Creates an enum from field names used in the ProtoBuf definition.
+    pub fn from_str_name(value: &str) -> ::core::option::Option<Self> {
+        match value {
+            "LIVE" => Some(Self::Live),
+            "PAUSED" => Some(Self::Paused),
+            _ => None,
+        }
+    }
+}
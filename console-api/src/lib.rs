@@ -8,8 +8,17 @@ mod common;
 pub mod instrument;
 /// Represents updates to the resources in an async runtime.
 pub mod resources;
+/// Represents updates to the worker threads of an async runtime.
+pub mod runtime;
+#[cfg(feature = "serde")]
+mod serde_util;
+/// Represents updates to the spans an application has asked the console to watch.
+pub mod spans;
 /// Represents updates to the tasks in an async runtime.
 pub mod tasks;
 /// Represents events on the tracing subsystem: thread registration and span activities.
 pub mod trace;
+/// Represents the wire format as of `console-api` 0.8, and converts it to the
+/// latest version.
+pub mod v1;
 pub use common::*;
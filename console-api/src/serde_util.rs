@@ -0,0 +1,72 @@
+//! Helpers for serializing the wire format's messages with `serde`.
+
+/// Serializes an optional value, such as a `prost_types::Timestamp` or
+/// `prost_types::Duration`, as its string representation.
+///
+/// This matches how these types are represented in protobuf's JSON mapping,
+/// for example `"2024-01-01T00:00:00Z"` and `"1.5s"`.
+pub(crate) mod option_string {
+    use serde::{de, Deserialize, Deserializer, Serializer};
+    use std::{fmt, str::FromStr};
+
+    pub(crate) fn serialize<T, S>(value: &Option<T>, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        T: fmt::Display,
+        S: Serializer,
+    {
+        match value {
+            Some(value) => serializer.collect_str(value),
+            None => serializer.serialize_none(),
+        }
+    }
+
+    pub(crate) fn deserialize<'de, T, D>(deserializer: D) -> Result<Option<T>, D::Error>
+    where
+        T: FromStr,
+        T::Err: fmt::Display,
+        D: Deserializer<'de>,
+    {
+        Option::<String>::deserialize(deserializer)?
+            .map(|value| value.parse().map_err(de::Error::custom))
+            .transpose()
+    }
+}
+
+/// Serializes a list of `prost_types::Any` payloads, each as its type URL and
+/// the bytes of its value.
+pub(crate) mod any_list {
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    #[derive(Serialize, Deserialize)]
+    #[serde(remote = "prost_types::Any")]
+    struct Any {
+        type_url: String,
+        value: Vec<u8>,
+    }
+
+    struct Ref<'a>(&'a prost_types::Any);
+
+    #[derive(Deserialize)]
+    struct Owned(#[serde(with = "Any")] prost_types::Any);
+
+    impl Serialize for Ref<'_> {
+        fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            Any::serialize(self.0, serializer)
+        }
+    }
+
+    pub(crate) fn serialize<S>(value: &[prost_types::Any], serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.collect_seq(value.iter().map(Ref))
+    }
+
+    pub(crate) fn deserialize<'de, D>(deserializer: D) -> Result<Vec<prost_types::Any>, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let list = Vec::<Owned>::deserialize(deserializer)?;
+        Ok(list.into_iter().map(|Owned(any)| any).collect())
+    }
+}
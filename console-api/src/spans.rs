@@ -0,0 +1,3 @@
+#![allow(warnings)]
+
+include!("generated/rs.tokio.console.spans.rs");
@@ -0,0 +1,67 @@
+//! These messages are a frozen copy of the protobuf bindings from before any
+//! of the fields and messages added since (such as the task aggregates, I/O
+//! and timer statistics for resources, and the runtime's workers), and must
+//! never change. They describe the data sent by older subscribers, and the
+//! data which older consumers expect.
+//!
+//! Each top-level message converts into its latest version with [`From`].
+//! So far, the wire format has only gained fields, which the latest version
+//! of a message decodes as their default values, so the conversions just
+//! re-encode the message. If a later change alters the meaning of an
+//! existing field, its conversion must instead be written by hand, so that
+//! data from older subscribers keeps its original meaning.
+
+use prost::Message;
+
+pub mod async_ops {
+    #![allow(warnings)]
+    include!("generated/v1/rs.tokio.console.async_ops.rs");
+}
+
+pub mod common {
+    #![allow(warnings)]
+    include!("generated/v1/rs.tokio.console.common.rs");
+}
+
+pub mod instrument {
+    #![allow(warnings)]
+    include!("generated/v1/rs.tokio.console.instrument.rs");
+}
+
+pub mod resources {
+    #![allow(warnings)]
+    include!("generated/v1/rs.tokio.console.resources.rs");
+}
+
+pub mod tasks {
+    #![allow(warnings)]
+    include!("generated/v1/rs.tokio.console.tasks.rs");
+}
+
+macro_rules! upgrade {
+    ($($v1:path => $latest:path),+ $(,)?) => {
+        $(
+            impl From<$v1> for $latest {
+                fn from(message: $v1) -> Self {
+                    upgrade(&message)
+                }
+            }
+        )+
+    };
+}
+
+upgrade! {
+    instrument::Update => crate::instrument::Update,
+    instrument::State => crate::instrument::State,
+    tasks::TaskUpdate => crate::tasks::TaskUpdate,
+    tasks::TaskDetails => crate::tasks::TaskDetails,
+    resources::ResourceUpdate => crate::resources::ResourceUpdate,
+    async_ops::AsyncOpUpdate => crate::async_ops::AsyncOpUpdate,
+}
+
+/// Converts a message to its latest version by re-encoding it, which leaves
+/// any fields added since at their default values.
+fn upgrade<M: Message, Latest: Message + Default>(message: &M) -> Latest {
+    Latest::decode(message.encode_to_vec().as_slice())
+        .expect("the latest version of a message should decode its v1 encoding")
+}
@@ -1,5 +1,7 @@
 use std::{fs, path::PathBuf, process::Command};
 
+mod codegen;
+
 #[test]
 fn bootstrap() {
     let root_dir = PathBuf::from(std::env!("CARGO_MANIFEST_DIR"));
@@ -31,11 +33,7 @@ fn bootstrap() {
 
     let out_dir = root_dir.join("src").join("generated");
 
-    if let Err(error) = tonic_build::configure()
-        .build_client(true)
-        .build_server(true)
-        .emit_rerun_if_changed(false)
-        .protoc_arg("--experimental_allow_proto3_optional")
+    if let Err(error) = codegen::configure()
         .out_dir(&out_dir)
         .compile_protos(&proto_files[..], &[proto_dir])
     {
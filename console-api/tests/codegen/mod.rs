@@ -0,0 +1,93 @@
+//! The configuration used to generate `console-api`'s protobuf bindings.
+//!
+//! This is shared by the `bootstrap` test, which checks that the bindings are
+//! up to date, and by `cargo xtask gen-proto`, which regenerates them, so
+//! that both always produce the same code.
+
+/// Derives `serde`'s traits for every message, if the `serde` feature is enabled.
+const SERDE_DERIVE: &str =
+    r#"#[cfg_attr(feature = "serde", derive(::serde::Serialize, ::serde::Deserialize))]"#;
+
+/// Fills in fields which are missing when deserializing with their defaults,
+/// as protobuf does.
+const SERDE_DEFAULT: &str = r#"#[cfg_attr(feature = "serde", serde(default))]"#;
+
+/// Serializes well-known types which don't implement `serde`'s traits as strings.
+const SERDE_AS_STRING: &str =
+    r#"#[cfg_attr(feature = "serde", serde(default, with = "crate::serde_util::option_string"))]"#;
+
+/// Serializes lists of `Any` payloads, which don't implement `serde`'s traits.
+const SERDE_ANY_LIST: &str =
+    r#"#[cfg_attr(feature = "serde", serde(default, with = "crate::serde_util::any_list"))]"#;
+
+/// Only compiles the clients with the `client` feature enabled.
+const CLIENT_CFG: &str = r#"#[cfg(feature = "client")]"#;
+
+/// Only compiles the servers with the `server` feature enabled.
+const SERVER_CFG: &str = r#"#[cfg(feature = "server")]"#;
+
+/// The `Timestamp` and `Duration` fields, which are serialized as strings.
+const TIME_FIELDS: &[&str] = &[
+    ".rs.tokio.console.async_ops.Stats.created_at",
+    ".rs.tokio.console.async_ops.Stats.dropped_at",
+    ".rs.tokio.console.common.Span.at",
+    ".rs.tokio.console.common.PollStats.first_poll",
+    ".rs.tokio.console.common.PollStats.last_poll_started",
+    ".rs.tokio.console.common.PollStats.last_poll_ended",
+    ".rs.tokio.console.common.PollStats.busy_time",
+    ".rs.tokio.console.instrument.InstrumentRequest.retention",
+    ".rs.tokio.console.instrument.TaskDetailsRequest.interval",
+    ".rs.tokio.console.instrument.TaskDetailsSetRequest.interval",
+    ".rs.tokio.console.instrument.Update.now",
+    ".rs.tokio.console.instrument.State.last_pause",
+    ".rs.tokio.console.resources.Stats.created_at",
+    ".rs.tokio.console.resources.Stats.dropped_at",
+    ".rs.tokio.console.resources.IoDirectionStats.total_wait",
+    ".rs.tokio.console.resources.IoDirectionStats.max_wait",
+    ".rs.tokio.console.resources.IoDirectionStats.waiting_since",
+    ".rs.tokio.console.spans.Stats.created_at",
+    ".rs.tokio.console.spans.Stats.dropped_at",
+    ".rs.tokio.console.tasks.TaskDetails.now",
+    ".rs.tokio.console.tasks.TaskDetailsSet.now",
+    ".rs.tokio.console.tasks.TaskGroups.now",
+    ".rs.tokio.console.tasks.Stats.created_at",
+    ".rs.tokio.console.tasks.Stats.dropped_at",
+    ".rs.tokio.console.tasks.Stats.last_wake",
+    ".rs.tokio.console.tasks.Stats.scheduled_time",
+    ".rs.tokio.console.tasks.Stats.max_poll_time",
+    ".rs.tokio.console.tasks.Stats.max_poll_started_at",
+    ".rs.tokio.console.tasks.Aggregates.total_busy_time",
+    ".rs.tokio.console.tasks.ActivitySamples.interval",
+    ".rs.tokio.console.tasks.SelectBranchStats.total_wait",
+    ".rs.tokio.console.tasks.SelectBranchStats.max_wait",
+    ".rs.tokio.console.tasks.WakerLeak.dropped_at",
+    ".rs.tokio.console.trace.TraceEvent.Enter.at",
+    ".rs.tokio.console.trace.TraceEvent.Exit.at",
+    ".rs.tokio.console.trace.TraceEvent.Close.at",
+];
+
+/// The lists of `Any` payloads.
+const ANY_FIELDS: &[&str] = &[
+    ".rs.tokio.console.tasks.Task.extensions",
+    ".rs.tokio.console.tasks.Extensions.extensions",
+];
+
+/// Returns a builder which generates the bindings with every attribute they
+/// need.
+pub fn configure() -> tonic_build::Builder {
+    let builder = tonic_build::configure()
+        .build_client(true)
+        .build_server(true)
+        .client_mod_attribute(".", CLIENT_CFG)
+        .server_mod_attribute(".", SERVER_CFG)
+        .emit_rerun_if_changed(false)
+        .protoc_arg("--experimental_allow_proto3_optional")
+        .type_attribute(".rs.tokio.console", SERDE_DERIVE)
+        .message_attribute(".rs.tokio.console", SERDE_DEFAULT);
+    let builder = TIME_FIELDS.iter().fold(builder, |builder, field| {
+        builder.field_attribute(field, SERDE_AS_STRING)
+    });
+    ANY_FIELDS.iter().fold(builder, |builder, field| {
+        builder.field_attribute(field, SERDE_ANY_LIST)
+    })
+}
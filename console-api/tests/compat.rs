@@ -0,0 +1,93 @@
+use std::collections::HashMap;
+
+use console_api::{instrument, tasks, v1};
+use prost::Message;
+
+#[test]
+fn v1_update_converts_to_latest() {
+    let update = v1::instrument::Update {
+        now: Some(timestamp(10)),
+        task_update: Some(v1::tasks::TaskUpdate {
+            new_tasks: vec![v1::tasks::Task {
+                id: Some(v1::common::Id { id: 1 }),
+                metadata: Some(v1::common::MetaId { id: 2 }),
+                fields: vec![v1::common::Field {
+                    name: Some(v1::common::field::Name::StrName("task.name".to_string())),
+                    value: Some(v1::common::field::Value::StrVal("worker".to_string())),
+                    metadata_id: Some(v1::common::MetaId { id: 2 }),
+                }],
+                ..Default::default()
+            }],
+            stats_update: HashMap::from([(
+                1,
+                v1::tasks::Stats {
+                    created_at: Some(timestamp(1)),
+                    wakes: 3,
+                    poll_stats: Some(v1::common::PollStats {
+                        polls: 4,
+                        ..Default::default()
+                    }),
+                    ..Default::default()
+                },
+            )]),
+            dropped_events: 5,
+        }),
+        resource_update: Some(v1::resources::ResourceUpdate {
+            new_resources: vec![v1::resources::Resource {
+                id: Some(v1::common::Id { id: 6 }),
+                concrete_type: "Sleep".to_string(),
+                ..Default::default()
+            }],
+            ..Default::default()
+        }),
+        ..Default::default()
+    };
+
+    let latest = instrument::Update::from(update);
+    let task_update = latest.task_update.unwrap();
+    let task = &task_update.new_tasks[0];
+    assert_eq!(task.id.map(|id| id.id), Some(1));
+    assert_eq!(task.fields[0].to_string(), "task.name=worker");
+    assert!(!task.fields[0].truncated);
+    assert_eq!(task.scheduler(), tasks::task::Scheduler::Unknown);
+    let poll_stats = task_update.stats_update[&1].poll_stats.unwrap();
+    assert_eq!(poll_stats.polls, 4);
+    assert_eq!(poll_stats.pending_polls + poll_stats.ready_polls, 0);
+    assert_eq!(task_update.dropped_events, 5);
+    // Messages added since v1 are missing, rather than empty.
+    assert_eq!(task_update.aggregates, None);
+    assert_eq!(task_update.activity, None);
+    assert_eq!(latest.runtime_update, None);
+
+    let resource_update = latest.resource_update.unwrap();
+    assert_eq!(resource_update.new_resources[0].concrete_type, "Sleep");
+    assert_eq!(resource_update.stats_update.len(), 0);
+}
+
+#[test]
+fn latest_task_update_decodes_as_v1() {
+    let update = tasks::TaskUpdate {
+        new_tasks: vec![tasks::Task {
+            id: Some(console_api::Id { id: 1 }),
+            omitted_fields: 2,
+            scheduler: tasks::task::Scheduler::Local as i32,
+            ..Default::default()
+        }],
+        dropped_events: 3,
+        aggregates: Some(tasks::Aggregates {
+            total_spawned: 4,
+            ..Default::default()
+        }),
+        ..Default::default()
+    };
+
+    // Older consumers skip the fields they don't know about.
+    let v1 = v1::tasks::TaskUpdate::decode(update.encode_to_vec().as_slice())
+        .expect("v1 should decode the latest task update");
+    assert_eq!(v1.new_tasks[0].id, Some(v1::common::Id { id: 1 }));
+    assert_eq!(v1.dropped_events, 3);
+}
+
+fn timestamp(seconds: i64) -> prost_types::Timestamp {
+    prost_types::Timestamp { seconds, nanos: 0 }
+}
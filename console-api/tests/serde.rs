@@ -0,0 +1,71 @@
+#![cfg(feature = "serde")]
+
+use std::time::Duration;
+
+use console_api::{
+    instrument,
+    tasks::{self, task},
+    Field, Id, MetaId, PollStats,
+};
+
+#[test]
+fn task_update_round_trips_through_json() {
+    let update = tasks::TaskUpdate {
+        new_tasks: vec![tasks::Task {
+            id: Some(Id { id: 1 }),
+            metadata: Some(MetaId { id: 2 }),
+            fields: vec![Field {
+                name: Some("task.name".into()),
+                value: Some("worker".into()),
+                metadata_id: Some(MetaId { id: 2 }),
+                truncated: false,
+            }],
+            scheduler: task::Scheduler::MultiThread as i32,
+            extensions: vec![prost_types::Any {
+                type_url: "type.example.com/app.Load".into(),
+                value: vec![8, 42],
+            }],
+            ..Default::default()
+        }],
+        stats_update: [(
+            1,
+            tasks::Stats {
+                created_at: Some(prost_types::Timestamp {
+                    seconds: 1_700_000_000,
+                    nanos: 0,
+                }),
+                poll_stats: Some(PollStats {
+                    polls: 3,
+                    busy_time: Some(Duration::from_millis(1500).try_into().unwrap()),
+                    ..Default::default()
+                }),
+                ..Default::default()
+            },
+        )]
+        .into(),
+        ..Default::default()
+    };
+
+    let json = serde_json::to_value(&update).unwrap();
+    let stats = &json["stats_update"]["1"];
+    assert_eq!(stats["created_at"], "2023-11-14T22:13:20Z");
+    assert_eq!(stats["dropped_at"], serde_json::Value::Null);
+    assert_eq!(stats["poll_stats"]["busy_time"], "1.500s");
+    assert_eq!(
+        json["new_tasks"][0]["fields"][0]["value"]["StrVal"],
+        "worker"
+    );
+    let extension = &json["new_tasks"][0]["extensions"][0];
+    assert_eq!(extension["type_url"], "type.example.com/app.Load");
+    assert_eq!(extension["value"], serde_json::json!([8, 42]));
+
+    let decoded: tasks::TaskUpdate = serde_json::from_value(json).unwrap();
+    assert_eq!(decoded, update);
+}
+
+#[test]
+fn missing_times_deserialize_as_none() {
+    let state: instrument::State = serde_json::from_str(r#"{"temporality": 1}"#).unwrap();
+    assert_eq!(state.temporality(), instrument::Temporality::Paused);
+    assert_eq!(state.last_pause, None);
+}
@@ -0,0 +1,296 @@
+//! Golden-file tests for the wire format.
+//!
+//! Each test encodes a representative message and compares it against a
+//! checked-in binary fixture, along with a JSON dump of the fields on the
+//! wire. Renumbering a field, or changing its type, changes the encoding,
+//! so this catches changes to the protos which would break compatibility
+//! between the console and instrumented applications built with different
+//! versions of this crate.
+//!
+//! If a change to the wire format is intended, regenerate the fixtures with
+//! `UPDATE_WIRE_FIXTURES=1 cargo test -p console-api --test wire_format` and
+//! check in the result.
+
+use std::{collections::HashMap, fmt::Debug, fs, path::PathBuf, time::Duration};
+
+use console_api::{
+    field, metadata,
+    tasks::{self, task, task_details},
+    Field, Id, Location, MetaId, PollStats, SpanId,
+};
+use prost::{
+    encoding::{decode_key, decode_varint, WireType},
+    Message,
+};
+use serde_json::{json, Value};
+
+#[test]
+fn task_update() {
+    check_fixture(
+        "task_update",
+        tasks::TaskUpdate {
+            new_tasks: vec![tasks::Task {
+                id: Some(Id { id: 1 }),
+                kind: task::Kind::Spawn as i32,
+                metadata: Some(MetaId { id: 2 }),
+                fields: vec![
+                    Field {
+                        name: Some(field::Name::StrName("task.name".to_string())),
+                        value: Some(field::Value::StrVal("worker".to_string())),
+                        metadata_id: Some(MetaId { id: 2 }),
+                        truncated: false,
+                    },
+                    Field {
+                        name: Some(field::Name::NameIdx(1)),
+                        value: Some(field::Value::DebugVal("task".to_string())),
+                        metadata_id: Some(MetaId { id: 2 }),
+                        truncated: true,
+                    },
+                    Field {
+                        name: Some(field::Name::NameIdx(2)),
+                        value: Some(field::Value::U64Val(3)),
+                        metadata_id: Some(MetaId { id: 2 }),
+                        truncated: false,
+                    },
+                    Field {
+                        name: Some(field::Name::NameIdx(3)),
+                        value: Some(field::Value::I64Val(-4)),
+                        metadata_id: Some(MetaId { id: 2 }),
+                        truncated: false,
+                    },
+                    Field {
+                        name: Some(field::Name::NameIdx(4)),
+                        value: Some(field::Value::BoolVal(true)),
+                        metadata_id: Some(MetaId { id: 2 }),
+                        truncated: false,
+                    },
+                ],
+                parents: vec![SpanId { id: 5 }],
+                location: Some(location()),
+                omitted_fields: 6,
+                scheduler: task::Scheduler::MultiThread as i32,
+                extensions: vec![extension()],
+                historical: true,
+                level: Some(metadata::Level::Debug as i32),
+            }],
+            stats_update: HashMap::from([(
+                1,
+                tasks::Stats {
+                    created_at: Some(timestamp(10)),
+                    dropped_at: Some(timestamp(20)),
+                    wakes: 7,
+                    waker_clones: 8,
+                    waker_drops: 9,
+                    last_wake: Some(timestamp(15)),
+                    poll_stats: Some(PollStats {
+                        polls: 10,
+                        first_poll: Some(timestamp(11)),
+                        last_poll_started: Some(timestamp(16)),
+                        last_poll_ended: Some(timestamp(17)),
+                        busy_time: Some(duration(2)),
+                        pending_polls: 9,
+                        ready_polls: 1,
+                    }),
+                    self_wakes: 2,
+                    scheduled_time: Some(duration(1)),
+                    max_poll_time: Some(duration(1)),
+                    max_poll_started_at: Some(timestamp(16)),
+                    woken_by: vec![tasks::TaskWakes {
+                        task_id: Some(Id { id: 2 }),
+                        wakes: 3,
+                    }],
+                    other_task_wakes: 2,
+                    alloc_stats: None,
+                    live_wakers: Some(-1),
+                },
+            )]),
+            dropped_events: 11,
+            aggregates: Some(tasks::Aggregates {
+                total_spawned: 12,
+                total_completed: 13,
+                max_concurrent: 14,
+                total_busy_time: Some(duration(3)),
+            }),
+            activity: Some(tasks::ActivitySamples {
+                interval: Some(duration(1)),
+                spawned: vec![1, 2],
+                completed: vec![3, 4],
+                wakes: vec![5, 6],
+                first_sample: 15,
+            }),
+            select_stats: vec![tasks::SelectStats {
+                location: Some(location()),
+                branches: vec![tasks::SelectBranchStats {
+                    completions: 16,
+                    total_wait: Some(duration(4)),
+                    max_wait: Some(duration(1)),
+                }],
+            }],
+            extensions_update: HashMap::from([(
+                1,
+                tasks::Extensions {
+                    extensions: vec![extension()],
+                },
+            )]),
+            waker_leaks: Some(tasks::WakerLeaks {
+                leaks: vec![tasks::WakerLeak {
+                    id: Some(Id { id: 17 }),
+                    task_id: Some(18),
+                    name: Some("leaky".to_string()),
+                    location: Some(location()),
+                    dropped_at: Some(timestamp(20)),
+                    waker_clones: 19,
+                    waker_drops: 17,
+                }],
+            }),
+        },
+    );
+}
+
+#[test]
+fn task_details() {
+    check_fixture(
+        "task_details",
+        tasks::TaskDetails {
+            task_id: Some(Id { id: 1 }),
+            now: Some(timestamp(30)),
+            scheduled_times_histogram: Some(histogram()),
+            thread_polls: vec![tasks::ThreadPolls {
+                thread_id: 2,
+                name: Some("tokio-runtime-worker".to_string()),
+                polls: 3,
+            }],
+            other_thread_polls: 4,
+            wake_intervals_histogram: Some(histogram()),
+            poll_times_histogram: Some(task_details::PollTimesHistogram::Histogram(histogram())),
+        },
+    );
+}
+
+fn location() -> Location {
+    Location {
+        file: Some("src/main.rs".to_string()),
+        module_path: Some("app::worker".to_string()),
+        line: Some(42),
+        column: Some(5),
+    }
+}
+
+fn extension() -> prost_types::Any {
+    prost_types::Any {
+        type_url: "type.example.com/app.Load".to_string(),
+        value: vec![8, 42],
+    }
+}
+
+fn timestamp(seconds: i64) -> prost_types::Timestamp {
+    prost_types::Timestamp {
+        seconds: 1_700_000_000 + seconds,
+        nanos: 500,
+    }
+}
+
+fn duration(seconds: u64) -> prost_types::Duration {
+    Duration::from_secs(seconds).try_into().unwrap()
+}
+
+fn histogram() -> tasks::DurationHistogram {
+    tasks::DurationHistogram {
+        raw_histogram: vec![0x1c, 0x84, 0x93, 0x14],
+        max_value: 5,
+        high_outliers: 6,
+        highest_outlier: Some(7),
+    }
+}
+
+/// Checks that `message` encodes to the checked-in fixture `name`, and that
+/// the fixture decodes back to `message`.
+fn check_fixture<M: Message + Default + PartialEq + Debug>(name: &str, message: M) {
+    let fixtures = PathBuf::from(std::env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join("fixtures");
+    let bin_path = fixtures.join(format!("{name}.bin"));
+    let json_path = fixtures.join(format!("{name}.json"));
+
+    let encoded = message.encode_to_vec();
+    let wire = serde_json::to_string_pretty(&decode_message(&encoded).unwrap()).unwrap() + "\n";
+    if std::env::var_os("UPDATE_WIRE_FIXTURES").is_some() {
+        fs::create_dir_all(&fixtures).unwrap();
+        fs::write(&bin_path, &encoded).unwrap();
+        fs::write(&json_path, &wire).unwrap();
+        return;
+    }
+
+    let expected_wire = fs::read_to_string(&json_path)
+        .unwrap_or_else(|error| panic!("failed to read {}: {error}", json_path.display()));
+    assert_eq!(
+        wire, expected_wire,
+        "the wire format of `{name}` changed; if this is intended, regenerate the fixtures \
+         with UPDATE_WIRE_FIXTURES=1",
+    );
+
+    let expected = fs::read(&bin_path)
+        .unwrap_or_else(|error| panic!("failed to read {}: {error}", bin_path.display()));
+    assert_eq!(
+        encoded, expected,
+        "`{name}` doesn't match its binary fixture"
+    );
+    let decoded = M::decode(&expected[..]).expect("the fixture should decode");
+    assert_eq!(
+        decoded, message,
+        "the fixture should decode to the same message"
+    );
+}
+
+/// Dumps the fields of an encoded message, in the order they are on the wire.
+///
+/// The wire format doesn't say whether a length-delimited field is a string,
+/// bytes, or a nested message, so this guesses: anything which is printable
+/// UTF-8 is a string, and anything else which parses as a message is one.
+/// Returns `None` if `buf` isn't a valid message.
+fn decode_message(mut buf: &[u8]) -> Option<Value> {
+    let mut fields = Vec::new();
+    while !buf.is_empty() {
+        let (tag, wire_type) = decode_key(&mut buf).ok()?;
+        let (kind, value) = match wire_type {
+            WireType::Varint => ("varint", json!(decode_varint(&mut buf).ok()?)),
+            WireType::SixtyFourBit => {
+                let bytes = take(&mut buf, 8)?;
+                ("fixed64", json!(u64::from_le_bytes(bytes.try_into().ok()?)))
+            }
+            WireType::ThirtyTwoBit => {
+                let bytes = take(&mut buf, 4)?;
+                ("fixed32", json!(u32::from_le_bytes(bytes.try_into().ok()?)))
+            }
+            WireType::LengthDelimited => {
+                let len = usize::try_from(decode_varint(&mut buf).ok()?).ok()?;
+                length_delimited(take(&mut buf, len)?)
+            }
+            WireType::StartGroup | WireType::EndGroup => return None,
+        };
+        fields.push(json!({ "field": tag, "type": kind, "value": value }));
+    }
+    Some(Value::Array(fields))
+}
+
+fn length_delimited(bytes: &[u8]) -> (&'static str, Value) {
+    if let Ok(string) = std::str::from_utf8(bytes) {
+        if !string.chars().any(char::is_control) {
+            return ("string", json!(string));
+        }
+    }
+    if let Some(message) = decode_message(bytes) {
+        return ("message", message);
+    }
+    let hex = bytes.iter().map(|byte| format!("{byte:02x}")).collect();
+    ("bytes", Value::String(hex))
+}
+
+fn take<'a>(buf: &mut &'a [u8], len: usize) -> Option<&'a [u8]> {
+    if buf.len() < len {
+        return None;
+    }
+    let (taken, rest) = buf.split_at(len);
+    *buf = rest;
+    Some(taken)
+}
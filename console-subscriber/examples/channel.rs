@@ -0,0 +1,39 @@
+use console_subscriber::channel::mpsc;
+use std::time::Duration;
+use tokio::task;
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    console_subscriber::init();
+    task::Builder::default()
+        .name("main-task")
+        .spawn(async move {
+            let (tx, mut rx) = mpsc::channel::<usize>(16);
+            for i in 0..3 {
+                let tx = tx.clone();
+                let task_name = format!("producer-{}", i);
+                task::Builder::default()
+                    .name(&task_name)
+                    .spawn(async move {
+                        for n in 0.. {
+                            if tx.send(n).await.is_err() {
+                                break;
+                            }
+                            tokio::time::sleep(Duration::from_millis(100)).await;
+                        }
+                    })
+                    .unwrap();
+            }
+            drop(tx);
+
+            // The consumer is slower than the producers, so the channel fills
+            // up and values wait longer and longer to be received.
+            while rx.recv().await.is_some() {
+                tokio::time::sleep(Duration::from_millis(50)).await;
+            }
+        })
+        .unwrap()
+        .await?;
+
+    Ok(())
+}
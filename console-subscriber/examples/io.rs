@@ -0,0 +1,43 @@
+use console_subscriber::io::Instrumented;
+use std::time::Duration;
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::{TcpListener, TcpStream},
+    task,
+};
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    console_subscriber::init();
+
+    let listener = TcpListener::bind("127.0.0.1:0").await?;
+    let addr = listener.local_addr()?;
+
+    // The server reads slowly, so the client's writes back up and it spends
+    // most of its time waiting for the socket to become writable.
+    task::Builder::default()
+        .name("server")
+        .spawn(async move {
+            let (stream, _) = listener.accept().await.unwrap();
+            let mut stream = Instrumented::new(stream);
+            let mut buf = [0; 1024];
+            while stream.read(&mut buf).await.unwrap() > 0 {
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        })
+        .unwrap();
+
+    task::Builder::default()
+        .name("client")
+        .spawn(async move {
+            let mut stream = Instrumented::new(TcpStream::connect(addr).await.unwrap());
+            let chunk = [0; 64 * 1024];
+            loop {
+                stream.write_all(&chunk).await.unwrap();
+            }
+        })
+        .unwrap()
+        .await?;
+
+    Ok(())
+}
@@ -42,10 +42,18 @@ impl<T: Unsent> IdData<T> {
         self.data.iter()
     }
 
+    pub(crate) fn all_mut(&mut self) -> impl Iterator<Item = (&Id, &mut T)> {
+        self.data.iter_mut()
+    }
+
     pub(crate) fn get(&self, id: &Id) -> Option<&T> {
         self.data.get(id)
     }
 
+    pub(crate) fn get_mut(&mut self, id: &Id) -> Option<&mut T> {
+        self.data.get_mut(id)
+    }
+
     pub(crate) fn as_proto_list(
         &mut self,
         include: Include,
@@ -83,13 +91,20 @@ impl<T: Unsent> IdData<T> {
         }
     }
 
+    /// Drops closed entities whose retention period has elapsed, calling
+    /// `on_drop` with the data, if any, and the stats of each entity that is
+    /// dropped.
+    ///
+    /// Returns whether any of the entities dropped had changes which weren't
+    /// sent to any clients.
     pub(crate) fn drop_closed<R: DroppedAt + Unsent>(
         &mut self,
         stats: &mut IdData<R>,
         now: Instant,
         retention: Duration,
         has_watchers: bool,
-    ) {
+        mut on_drop: impl FnMut(Option<&T>, &R),
+    ) -> bool {
         let _span = tracing::debug_span!(
             "drop_closed",
             entity = %std::any::type_name::<T>(),
@@ -100,6 +115,7 @@ impl<T: Unsent> IdData<T> {
         // drop closed entities
         tracing::trace!(?retention, has_watchers, "dropping closed");
 
+        let mut dropped_unsent = false;
         stats.data.retain_and_shrink(|id, stats| {
             if let Some(dropped_at) = stats.dropped_at() {
                 let dropped_for = now.checked_duration_since(dropped_at).unwrap_or_default();
@@ -115,6 +131,10 @@ impl<T: Unsent> IdData<T> {
                     stats.dirty = dirty,
                     should_retain,
                 );
+                if !should_retain {
+                    dropped_unsent |= dirty;
+                    on_drop(self.data.get(id), stats);
+                }
                 return should_retain;
             }
 
@@ -124,5 +144,6 @@ impl<T: Unsent> IdData<T> {
         // drop closed entities which no longer have stats.
         self.data
             .retain_and_shrink(|id, _| stats.data.contains_key(id));
+        dropped_unsent
     }
 }
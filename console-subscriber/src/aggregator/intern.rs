@@ -0,0 +1,200 @@
+use console_api as proto;
+use std::{collections::HashSet, sync::Arc};
+
+/// Interns the strings shared between the tasks and resources stored by the
+/// aggregator, such as field names and the files of their locations.
+///
+/// Applications which spawn many tasks from the same place would otherwise
+/// store a copy of the same strings for every task.
+#[derive(Debug, Default)]
+pub(super) struct Strings {
+    strings: HashSet<Arc<str>>,
+}
+
+/// A span field, with its name and value interned.
+#[derive(Debug)]
+pub(super) struct Field {
+    name: Option<Name>,
+    value: Option<Value>,
+    metadata_id: Option<proto::MetaId>,
+    truncated: bool,
+}
+
+#[derive(Debug)]
+enum Name {
+    Str(Arc<str>),
+    Idx(u64),
+}
+
+#[derive(Debug)]
+enum Value {
+    Debug(Arc<str>),
+    Str(Arc<str>),
+    U64(u64),
+    I64(i64),
+    Bool(bool),
+}
+
+/// A source code location, with its file and module path interned.
+#[derive(Debug)]
+pub(super) struct Location {
+    file: Option<Arc<str>>,
+    module_path: Option<Arc<str>>,
+    line: Option<u32>,
+    column: Option<u32>,
+}
+
+// === impl Strings ===
+
+impl Strings {
+    pub(super) fn intern(&mut self, string: &str) -> Arc<str> {
+        if let Some(interned) = self.strings.get(string) {
+            return interned.clone();
+        }
+
+        let interned: Arc<str> = Arc::from(string);
+        self.strings.insert(interned.clone());
+        interned
+    }
+
+    pub(super) fn field(&mut self, field: proto::Field) -> Field {
+        use proto::field::{Name as PbName, Value as PbValue};
+
+        Field {
+            name: field.name.map(|name| match name {
+                PbName::StrName(name) => Name::Str(self.intern(&name)),
+                PbName::NameIdx(idx) => Name::Idx(idx),
+            }),
+            value: field.value.map(|value| match value {
+                PbValue::DebugVal(value) => Value::Debug(self.intern(&value)),
+                PbValue::StrVal(value) => Value::Str(self.intern(&value)),
+                PbValue::U64Val(value) => Value::U64(value),
+                PbValue::I64Val(value) => Value::I64(value),
+                PbValue::BoolVal(value) => Value::Bool(value),
+            }),
+            metadata_id: field.metadata_id,
+            truncated: field.truncated,
+        }
+    }
+
+    pub(super) fn location(&mut self, location: proto::Location) -> Location {
+        Location {
+            file: location.file.map(|file| self.intern(&file)),
+            module_path: location.module_path.map(|path| self.intern(&path)),
+            line: location.line,
+            column: location.column,
+        }
+    }
+
+    /// Drops any interned strings which are no longer referenced by a stored
+    /// task or resource.
+    pub(super) fn retain_referenced(&mut self) {
+        const FOUR_KILOBYTES: usize = 4 * 1024;
+
+        let len0 = self.strings.len();
+        self.strings.retain(|string| Arc::strong_count(string) > 1);
+
+        let len = self.strings.len();
+        if len < len0 {
+            // only shrink the set if it has a meaningful amount of unused
+            // capacity.
+            let free_cap = (self.strings.capacity() - len) * std::mem::size_of::<Arc<str>>();
+            let should_shrink = free_cap >= FOUR_KILOBYTES;
+
+            tracing::trace!(
+                strings.len = len,
+                dropped = len0 - len,
+                should_shrink,
+                "dropped un-referenced strings",
+            );
+
+            if should_shrink {
+                self.strings.shrink_to_fit();
+            }
+        }
+    }
+}
+
+// === impl Field ===
+
+impl Field {
+    /// Returns whether this field is named `name`.
+    ///
+    /// The layer records every field by name, rather than by its index in
+    /// the span's metadata, so a field which only has an index never matches.
+    pub(super) fn is_named(&self, name: &str) -> bool {
+        matches!(self.name, Some(Name::Str(ref field)) if &**field == name)
+    }
+
+    /// Returns the field's value, if it's an unsigned integer.
+    pub(super) fn u64_value(&self) -> Option<u64> {
+        match self.value {
+            Some(Value::U64(value)) => Some(value),
+            _ => None,
+        }
+    }
+
+    /// Returns the field's value, if it's a string or was recorded with its
+    /// `Debug` or `Display` implementation.
+    pub(super) fn str_value(&self) -> Option<&str> {
+        match self.value {
+            Some(Value::Str(ref value) | Value::Debug(ref value)) => Some(value),
+            _ => None,
+        }
+    }
+
+    pub(super) fn to_proto(&self) -> proto::Field {
+        use proto::field::{Name as PbName, Value as PbValue};
+
+        proto::Field {
+            name: self.name.as_ref().map(|name| match name {
+                Name::Str(name) => PbName::StrName(name.to_string()),
+                Name::Idx(idx) => PbName::NameIdx(*idx),
+            }),
+            value: self.value.as_ref().map(|value| match value {
+                Value::Debug(value) => PbValue::DebugVal(value.to_string()),
+                Value::Str(value) => PbValue::StrVal(value.to_string()),
+                Value::U64(value) => PbValue::U64Val(*value),
+                Value::I64(value) => PbValue::I64Val(*value),
+                Value::Bool(value) => PbValue::BoolVal(*value),
+            }),
+            metadata_id: self.metadata_id,
+            truncated: self.truncated,
+        }
+    }
+}
+
+// === impl Location ===
+
+impl Location {
+    pub(super) fn file(&self) -> Option<&Arc<str>> {
+        self.file.as_ref()
+    }
+
+    pub(super) fn line(&self) -> Option<u32> {
+        self.line
+    }
+
+    pub(super) fn column(&self) -> Option<u32> {
+        self.column
+    }
+
+    /// Returns whether this is the same source code location as `other`.
+    ///
+    /// Like task groups, this compares the file, line, and column, and
+    /// ignores the module path.
+    pub(super) fn matches(&self, other: &proto::Location) -> bool {
+        self.file.as_deref() == other.file.as_deref()
+            && self.line.unwrap_or_default() == other.line.unwrap_or_default()
+            && self.column.unwrap_or_default() == other.column.unwrap_or_default()
+    }
+
+    pub(super) fn to_proto(&self) -> proto::Location {
+        proto::Location {
+            file: self.file.as_deref().map(str::to_owned),
+            module_path: self.module_path.as_deref().map(str::to_owned),
+            line: self.line,
+            column: self.column,
+        }
+    }
+}
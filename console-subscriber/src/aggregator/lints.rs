@@ -0,0 +1,210 @@
+use crate::stats::TaskStats;
+use std::{
+    collections::HashSet,
+    fmt,
+    time::{Duration, Instant},
+};
+use tracing_core::{dispatcher, span::Id, Dispatch};
+
+/// The most warnings which are logged in each [`RATE_LIMIT_PERIOD`].
+///
+/// An application with a systemic problem may have many unhealthy tasks at
+/// once, and logging each of them would flood its logs.
+const MAX_WARNINGS: usize = 10;
+
+/// The period over which [`MAX_WARNINGS`] applies.
+const RATE_LIMIT_PERIOD: Duration = Duration::from_secs(60);
+
+/// How long a task must have been waiting without any wakers before it's
+/// considered to have lost its waker.
+///
+/// Waker counts are recorded as the task runs, so this only allows for a
+/// task which has just yielded to be checked before it registers its waker.
+const LOST_WAKER_AFTER: Duration = Duration::from_secs(1);
+
+/// How long a task's first poll must have run before it's considered to
+/// never yield.
+const NEVER_YIELDED_AFTER: Duration = Duration::from_secs(1);
+
+/// Checks tasks for some of the problems which the console warns about, so
+/// that they can be logged in the instrumented application.
+///
+/// Each problem is only reported once per task, and the number of warnings
+/// reported is rate limited.
+#[derive(Debug)]
+pub(super) struct Lints {
+    /// The problems which have already been reported, by the span ID of the
+    /// task which has them.
+    reported: HashSet<(Lint, Id)>,
+    /// When the current rate limiting period started.
+    period_started: Instant,
+    /// The number of warnings reported in the current period.
+    reported_in_period: usize,
+    /// The number of warnings which weren't reported in the current period,
+    /// because of the rate limit.
+    suppressed: usize,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub(super) enum Lint {
+    /// The task is waiting to be woken, but all of its wakers were dropped.
+    LostWaker,
+    /// The task's first poll has run for a long time without yielding.
+    NeverYielded,
+}
+
+/// The warnings found by a check of the tasks.
+#[derive(Debug)]
+pub(super) struct Report {
+    pub(super) warnings: Vec<Warning>,
+    /// The number of warnings suppressed by the rate limit in the previous
+    /// period, which is reported once the period ends.
+    pub(super) suppressed: usize,
+}
+
+/// A problem found with a task.
+#[derive(Debug)]
+pub(super) struct Warning {
+    pub(super) lint: Lint,
+    /// The task's span ID.
+    pub(super) id: Id,
+    pub(super) task_id: Option<u64>,
+    pub(super) name: Option<String>,
+    pub(super) location: Option<String>,
+    /// How long the task has had the problem for.
+    pub(super) duration: Duration,
+}
+
+// === impl Lints ===
+
+impl Lints {
+    pub(super) fn new(now: Instant) -> Self {
+        Self {
+            reported: HashSet::new(),
+            period_started: now,
+            reported_in_period: 0,
+            suppressed: 0,
+        }
+    }
+
+    /// Forgets the problems of tasks for which `is_retained` returns
+    /// `false`, so that their span IDs can be reused.
+    pub(super) fn retain(&mut self, mut is_retained: impl FnMut(&Id) -> bool) {
+        self.reported.retain(|(_, id)| is_retained(id));
+    }
+
+    /// Returns the problems which the task with the span ID `id` has at
+    /// `now`, and which haven't been reported for it before.
+    pub(super) fn check(
+        &mut self,
+        id: &Id,
+        stats: &TaskStats,
+        now: Instant,
+    ) -> impl Iterator<Item = (Lint, Duration)> + '_ {
+        let id = id.clone();
+        [
+            (Lint::LostWaker, lost_waker(stats, now)),
+            (Lint::NeverYielded, never_yielded(stats, now)),
+        ]
+        .into_iter()
+        .filter_map(move |(lint, duration)| {
+            let duration = duration?;
+            self.reported
+                .insert((lint, id.clone()))
+                .then_some((lint, duration))
+        })
+    }
+
+    /// Drops the warnings which exceed the rate limit at `now`, returning
+    /// the ones which should be reported.
+    pub(super) fn rate_limit(&mut self, mut warnings: Vec<Warning>, now: Instant) -> Report {
+        let mut suppressed = 0;
+        if now.saturating_duration_since(self.period_started) >= RATE_LIMIT_PERIOD {
+            self.period_started = now;
+            self.reported_in_period = 0;
+            suppressed = std::mem::take(&mut self.suppressed);
+        }
+
+        let allowed = MAX_WARNINGS - self.reported_in_period;
+        if warnings.len() > allowed {
+            self.suppressed += warnings.len() - allowed;
+            warnings.truncate(allowed);
+        }
+        self.reported_in_period += warnings.len();
+        Report {
+            warnings,
+            suppressed,
+        }
+    }
+}
+
+fn lost_waker(stats: &TaskStats, now: Instant) -> Option<Duration> {
+    if stats.waker_counts().live() > 0 {
+        return None;
+    }
+    let idle = now.saturating_duration_since(stats.idle_since()?);
+    (idle >= LOST_WAKER_AFTER).then_some(idle)
+}
+
+fn never_yielded(stats: &TaskStats, now: Instant) -> Option<Duration> {
+    let busy = now.saturating_duration_since(stats.first_poll_in_progress()?);
+    (busy >= NEVER_YIELDED_AFTER).then_some(busy)
+}
+
+// === impl Report ===
+
+impl Report {
+    /// Logs the warnings as `tracing` events, with `dispatch` as the default
+    /// subscriber.
+    pub(super) fn log(&self, dispatch: &Dispatch) {
+        dispatcher::with_default(dispatch, || {
+            if self.suppressed > 0 {
+                tracing::warn!(
+                    suppressed = self.suppressed,
+                    period = ?RATE_LIMIT_PERIOD,
+                    "more tasks were unhealthy than could be logged"
+                );
+            }
+            for warning in &self.warnings {
+                warning.log();
+            }
+        });
+    }
+}
+
+// === impl Warning ===
+
+impl Warning {
+    fn log(&self) {
+        let task = TaskDesc(self);
+        match self.lint {
+            Lint::LostWaker => tracing::warn!(
+                %task,
+                location = self.location.as_deref(),
+                idle = ?self.duration,
+                "task has lost its waker, and will never be woken again"
+            ),
+            Lint::NeverYielded => tracing::warn!(
+                %task,
+                location = self.location.as_deref(),
+                busy = ?self.duration,
+                "task has never yielded"
+            ),
+        }
+    }
+}
+
+/// Describes a task by its Tokio task ID and name, or its span ID if it has
+/// neither.
+struct TaskDesc<'a>(&'a Warning);
+
+impl fmt::Display for TaskDesc<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match (self.0.task_id, self.0.name.as_ref()) {
+            (Some(task_id), Some(name)) => write!(f, "{task_id} ({name})"),
+            (Some(task_id), None) => write!(f, "{task_id}"),
+            (None, Some(name)) => f.write_str(name),
+            (None, None) => write!(f, "span {}", self.0.id.into_u64()),
+        }
+    }
+}
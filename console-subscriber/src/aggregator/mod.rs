@@ -1,6 +1,8 @@
 use std::{
+    collections::HashSet,
+    mem,
     sync::{
-        atomic::{AtomicBool, Ordering::*},
+        atomic::{AtomicBool, AtomicU8, AtomicUsize, Ordering::*},
         Arc,
     },
     time::{Duration, Instant},
@@ -8,23 +10,31 @@ use std::{
 
 use console_api as proto;
 use prost::Message;
-use proto::resources::resource;
 use tokio::sync::{mpsc, Notify};
-use tracing_core::{span::Id, Metadata};
+use tracing_core::{dispatcher::WeakDispatch, span::Id};
 
-use super::{Command, Event, Shared, Watch};
-use crate::{
-    stats::{self, Unsent},
-    ToProto, WatchRequest,
-};
+use super::{Command, DetailsOptions, Event, InstrumentRequest, Shared, Watch, WatchSetRequest};
+use crate::{sync::Mutex, ToProto, WatchRequest};
 
 mod id_data;
+mod intern;
+mod lints;
+mod recent_tasks;
+mod resume;
+mod select_stats;
 mod shrink;
-use self::id_data::{IdData, Include};
+mod state;
+mod task_aggregates;
+mod timer_stats;
+mod waker_leaks;
+mod workers;
+use self::lints::Lints;
+use self::resume::ResumeHistory;
 use self::shrink::{ShrinkMap, ShrinkVec};
-
-/// Should match tonic's (private) codec::DEFAULT_MAX_RECV_MESSAGE_SIZE
-const MAX_MESSAGE_SIZE: usize = 4 * 1024 * 1024;
+use self::state::AggregatorState;
+pub(crate) use self::task_aggregates::ConcurrentTasks;
+pub(crate) use self::waker_leaks::LingeringWakers;
+use self::workers::Workers;
 
 /// Aggregates instrumentation traces and prepares state for the instrument
 /// server.
@@ -32,6 +42,9 @@ const MAX_MESSAGE_SIZE: usize = 4 * 1024 * 1024;
 /// The `Aggregator` is responsible for receiving and organizing the
 /// instrumentated events and preparing the data to be served to a instrument
 /// client.
+///
+/// The events themselves are applied to an [`AggregatorState`], which builds
+/// the updates that the `Aggregator` then sends to the clients watching them.
 pub struct Aggregator {
     /// Channel of incoming events emitted by `TaskLayer`s.
     events: mpsc::Receiver<Event>,
@@ -39,12 +52,6 @@ pub struct Aggregator {
     /// New incoming RPCs.
     rpcs: mpsc::Receiver<Command>,
 
-    /// The interval at which new data updates are pushed to clients.
-    publish_interval: Duration,
-
-    /// How long to keep task data after a task has completed.
-    retention: Duration,
-
     /// Shared state, including a `Notify` that triggers a flush when the event
     /// buffer is approaching capacity.
     shared: Arc<Shared>,
@@ -53,86 +60,170 @@ pub struct Aggregator {
     state_watchers: ShrinkVec<Watch<proto::instrument::State>>,
 
     /// Currently active RPCs streaming task events.
-    watchers: ShrinkVec<Watch<proto::instrument::Update>>,
+    watchers: ShrinkVec<UpdatesWatch>,
 
-    /// Currently active RPCs streaming task details events, by task ID.
-    details_watchers: ShrinkMap<Id, Vec<Watch<proto::tasks::TaskDetails>>>,
+    /// The updates recently published to `watchers`, which clients that
+    /// reconnect can resume from.
+    resume: ResumeHistory,
 
-    /// *All* metadata for task spans and user-defined spans that we care about.
-    ///
-    /// This is sent to new clients as part of the initial state.
-    all_metadata: ShrinkVec<proto::register_metadata::NewMetadata>,
+    /// The maximum number of RPCs which may stream task events at once.
+    max_watchers: usize,
 
-    /// *New* metadata that was registered since the last state update.
-    ///
-    /// This is emptied on every state update.
-    new_metadata: Vec<proto::register_metadata::NewMetadata>,
+    /// The most bytes of updates which may be sent to each client per
+    /// second, if this is limited.
+    max_watcher_bandwidth: Option<u64>,
 
-    /// Map of task IDs to task static data.
-    tasks: IdData<Task>,
+    /// The most tasks which an update may include before updates are
+    /// published less often, if the publish interval adapts to load.
+    max_updated_tasks: Option<usize>,
 
-    /// Map of task IDs to task stats.
-    task_stats: IdData<Arc<stats::TaskStats>>,
+    /// Whether clients are refused from pausing and resuming the aggregator,
+    /// which is reported to state watchers.
+    read_only: bool,
 
-    /// Map of resource IDs to resource static data.
-    resources: IdData<Resource>,
+    /// The significant figures task histograms are recorded with, which is
+    /// reported to state watchers along with the memory they use.
+    histogram_significant_figures: u8,
 
-    /// Map of resource IDs to resource stats.
-    resource_stats: IdData<Arc<stats::ResourceStats>>,
+    /// The memory used by the state when it was last measured, if any
+    /// clients have watched the aggregator's state.
+    memory: Option<proto::instrument::MemoryUsage>,
 
-    /// Map of AsyncOp IDs to AsyncOp static data.
-    async_ops: IdData<AsyncOp>,
+    /// Checks for unhealthy tasks, whose warnings are logged in the
+    /// instrumented application, if enabled.
+    lints: Option<Lints>,
 
-    /// Map of AsyncOp IDs to AsyncOp stats.
-    async_op_stats: IdData<Arc<stats::AsyncOpStats>>,
+    /// Currently active RPCs streaming task details events, by task ID.
+    details_watchers: ShrinkMap<Id, Vec<DetailsWatch>>,
 
-    /// `PollOp `events that have occurred since the last update
-    ///
-    /// This is emptied on every state update.
-    poll_ops: Vec<proto::resources::PollOp>,
+    /// Currently active RPCs streaming task details events for sets of tasks.
+    details_set_watchers: ShrinkVec<DetailsSetWatch>,
 
-    /// The time "state" of the aggregator, such as paused or live.
-    temporality: proto::instrument::Temporality,
+    /// Currently active RPCs streaming statistics for groups of tasks.
+    group_watchers: ShrinkVec<Watch<proto::tasks::TaskGroups>>,
 
-    /// Used to anchor monotonic timestamps to a base `SystemTime`, to produce a
-    /// timestamp that can be sent over the wire.
-    base_time: stats::TimeAnchor,
+    /// The tasks, resources, and async ops recorded from events.
+    state: AggregatorState,
+
+    /// The threads which poll tasks, and the runtime's metrics for them.
+    workers: Workers,
 }
 
 #[derive(Debug, Default)]
 pub(crate) struct Flush {
     pub(crate) should_flush: Notify,
     triggered: AtomicBool,
+    /// The most events seen waiting in the event channel since it was last
+    /// drained.
+    peak_depth: AtomicUsize,
 }
 
-// Represent static data for resources
-struct Resource {
-    id: Id,
-    is_dirty: AtomicBool,
-    parent_id: Option<Id>,
-    metadata: &'static Metadata<'static>,
-    concrete_type: String,
-    kind: resource::Kind,
-    location: Option<proto::Location>,
-    is_internal: bool,
+/// Publishes updates more often while the event channel is persistently near
+/// capacity.
+///
+/// Each publish drains the event channel, so publishing more often during a
+/// burst of events keeps the channel from filling up between flushes. The
+/// regular publish interval is unchanged, so that activity samples still
+/// cover the same length of time, and the extra publishes happen between its
+/// ticks.
+#[derive(Debug)]
+struct PublishRate {
+    /// The configured publish interval.
+    interval: Duration,
+    /// How many times per publish interval updates are published.
+    rate: u32,
+    /// The number of publish intervals in a row in which the event channel
+    /// reached its high watermark.
+    pressured_intervals: u32,
+    /// Whether the event channel reached its high watermark in the current
+    /// publish interval.
+    pressured: bool,
+    /// Ticks for the extra publishes, while the rate is raised.
+    extra: Option<tokio::time::Interval>,
+    /// The number of extra publishes left before the next regular one.
+    extra_remaining: u32,
+}
+
+/// Publishes updates less often while they include many tasks, if the
+/// publish interval adapts to load.
+///
+/// Rather than changing the publish interval itself, updates are published
+/// on every `stride`th tick of it, so that activity samples still cover the
+/// same length of time.
+struct PublishStride {
+    /// The most tasks which an update may include before the stride is
+    /// raised, or `None` if it never is.
+    max_updated_tasks: Option<usize>,
+    /// How many publish intervals elapse between published updates.
+    stride: u32,
+    /// The number of publish intervals which have elapsed since an update
+    /// was last due.
+    elapsed: u32,
 }
 
-/// Represents static data for tasks
-struct Task {
-    id: Id,
-    is_dirty: AtomicBool,
-    metadata: &'static Metadata<'static>,
-    fields: Vec<proto::Field>,
+/// Events which the layers couldn't send because the event channel was full
+/// before the aggregator started draining it.
+///
+/// Only the events which register new tasks, resources, and async ops are
+/// kept: without them, the later events for those items can't be applied.
+#[derive(Debug)]
+pub(crate) struct StartupBuffer {
+    /// The buffered events, in the order they were emitted, or `None` once the
+    /// aggregator has started.
+    events: Mutex<Option<Vec<Event>>>,
+    capacity: usize,
+}
+
+/// Whether the aggregator task is still running.
+///
+/// The aggregator is assumed to be running until it's dropped, so events
+/// emitted before it's first polled don't make the layer look inactive.
+#[derive(Debug, Default)]
+pub(crate) struct Liveness(AtomicU8);
+
+/// An RPC streaming updates.
+struct UpdatesWatch {
+    request: InstrumentRequest,
+    /// The limit on how many bytes of updates are sent to the client, if
+    /// there is one.
+    bandwidth: Option<Bandwidth>,
+}
+
+/// A token bucket which limits the bytes of updates sent to a client.
+///
+/// The bucket holds up to a second's worth of bytes, so a client may be sent
+/// an update as large as its limit after being sent nothing for a second.
+struct Bandwidth {
+    bytes_per_sec: u64,
+    /// The bytes which may be sent now.
+    available: f64,
+    refilled_at: Instant,
+    /// Whether an update was too large to be sent, so that the client is
+    /// only sent summaries from then on.
+    exceeded: bool,
+}
+
+/// An RPC streaming the details of a single task.
+struct DetailsWatch {
+    watch: Watch<proto::tasks::TaskDetails>,
+    schedule: DetailsSchedule,
+}
+
+/// An RPC streaming the details of a set of tasks.
+struct DetailsSetWatch {
+    watch: Watch<proto::tasks::TaskDetailsSet>,
+    ids: HashSet<Id>,
+    /// The location to also send the details of the tasks spawned from.
     location: Option<proto::Location>,
+    schedule: DetailsSchedule,
 }
 
-struct AsyncOp {
-    id: Id,
-    is_dirty: AtomicBool,
-    parent_id: Option<Id>,
-    resource_id: Id,
-    metadata: &'static Metadata<'static>,
-    source: String,
+/// When to send task details to a watcher, and how.
+struct DetailsSchedule {
+    options: DetailsOptions,
+    /// When the next update is due, if the client requested its own
+    /// interval rather than the publish interval.
+    next_update: Instant,
 }
 
 impl Aggregator {
@@ -141,28 +232,33 @@ impl Aggregator {
         rpcs: mpsc::Receiver<Command>,
         builder: &crate::Builder,
         shared: Arc<crate::Shared>,
-        base_time: stats::TimeAnchor,
+        base_time: crate::stats::TimeAnchor,
     ) -> Self {
         Self {
             shared,
             rpcs,
-            publish_interval: builder.publish_interval,
-            retention: builder.retention,
             events,
             watchers: Default::default(),
+            resume: ResumeHistory::new(),
+            max_watchers: builder.max_watchers,
+            max_watcher_bandwidth: builder.max_watcher_bandwidth,
+            max_updated_tasks: builder.max_updated_tasks,
+            read_only: builder.read_only,
+            histogram_significant_figures: builder.histogram_significant_figures,
+            memory: None,
+            lints: builder.emit_warnings.then(|| Lints::new(Instant::now())),
             details_watchers: Default::default(),
+            details_set_watchers: Default::default(),
+            group_watchers: Default::default(),
             state_watchers: Default::default(),
-            all_metadata: Default::default(),
-            new_metadata: Default::default(),
-            tasks: IdData::default(),
-            task_stats: IdData::default(),
-            resources: IdData::default(),
-            resource_stats: IdData::default(),
-            async_ops: IdData::default(),
-            async_op_stats: IdData::default(),
-            poll_ops: Default::default(),
-            temporality: proto::instrument::Temporality::Live,
-            base_time,
+            state: AggregatorState::new(
+                builder.publish_interval,
+                builder.retention,
+                builder.max_retention,
+                builder.keep_completed_tasks,
+                base_time,
+            ),
+            workers: Workers::new(builder),
         }
     }
 
@@ -172,20 +268,39 @@ impl Aggregator {
     /// the instrument server is running. If the instrument server stops,
     /// this future can be aborted.
     pub async fn run(mut self) {
-        let mut publish = tokio::time::interval(self.publish_interval);
+        let mut publish = tokio::time::interval(self.state.publish_interval());
+        let mut publish_rate = PublishRate::new(self.state.publish_interval());
+        let mut publish_stride = PublishStride::new(self.max_updated_tasks);
+        let mut started = false;
         loop {
+            let mut should_sample = false;
+            let mut details_due = false;
+            let next_details_update = self.next_details_update();
             let should_send = tokio::select! {
                 // if the flush interval elapses, flush data to the client
                 _ = publish.tick() => {
-                    match self.temporality {
-                        proto::instrument::Temporality::Live => true,
-                        proto::instrument::Temporality::Paused => false,
-                    }
+                    should_sample = true;
+                    publish_rate.interval_elapsed();
+                    publish_stride.interval_elapsed() && !self.state.is_paused()
+                }
+
+                // an extra publish while the event buffer is under pressure
+                _ = publish_rate.extra_tick() => {
+                    !self.state.is_paused()
+                }
+
+                // a task details watcher with its own interval is due an update
+                _ = tokio::time::sleep_until(
+                    next_details_update.unwrap_or_else(Instant::now).into()
+                ), if next_details_update.is_some() => {
+                    details_due = true;
+                    false
                 }
 
                 // triggered when the event buffer is approaching capacity
                 _ = self.shared.flush.should_flush.notified() => {
                     tracing::debug!("approaching capacity; draining buffer");
+                    publish_rate.pressured();
                     false
                 }
 
@@ -198,14 +313,30 @@ impl Aggregator {
                         Some(Command::WatchTaskDetail(watch_request)) => {
                             self.add_task_detail_subscription(watch_request);
                         },
+                        Some(Command::WatchTaskDetailsSet(watch_request)) => {
+                            self.add_task_details_set_subscription(watch_request);
+                        },
+                        Some(Command::WatchTaskGroups(subscription)) => {
+                            self.add_task_groups_subscription(subscription);
+                        },
                         Some(Command::WatchState(subscription)) => {
                             self.add_state_subscription(subscription);
                         }
-                        Some(Command::Pause) => {
-                            self.temporality = proto::instrument::Temporality::Paused;
+                        Some(Command::Pause { freeze_time }) => {
+                            self.state.pause(Instant::now(), freeze_time);
                         }
                         Some(Command::Resume) => {
-                            self.temporality = proto::instrument::Temporality::Live;
+                            self.state.resume(Instant::now());
+                        }
+                        Some(Command::ListTasks(request)) => {
+                            let tasks = self.state.list_tasks(
+                                request.location.as_ref(),
+                                request.include_completed,
+                                request.limit,
+                                self.now(),
+                            );
+                            // the client may have gone away in the meantime
+                            let _ = request.response.send(tasks);
                         }
                         None => {
                             tracing::debug!("rpc channel closed, terminating");
@@ -227,11 +358,12 @@ impl Aggregator {
             // channel is almost full.
             let mut drained = false;
             let mut counts = EventCounts::new();
+            let peak_depth = self.shared.flush.take_peak_depth();
             while let Some(event) = recv_now_or_never(&mut self.events) {
                 match event {
                     Some(event) => {
                         counts.update(&event);
-                        self.update_state(event);
+                        self.state.update(event);
                         drained = true;
                     }
                     // The channel closed, no more events will be emitted...time
@@ -242,126 +374,239 @@ impl Aggregator {
                     }
                 };
             }
+            // the buffered startup events were emitted after everything in
+            // the channel when it filled up, so apply them once it has been
+            // drained for the first time.
+            if !started {
+                started = true;
+                for event in self.shared.startup.take() {
+                    counts.update(&event);
+                    self.state.update(event);
+                }
+            }
             tracing::debug!(
                 async_resource_ops = counts.async_resource_op,
                 metadatas = counts.metadata,
                 poll_ops = counts.poll_op,
                 resources = counts.resource,
+                select_branches = counts.select_branch,
                 spawns = counts.spawn,
+                task_extensions = counts.task_extension,
+                timers_fired = counts.timer_fired,
+                watched_spans = counts.watched_span,
                 total = counts.total(),
+                peak_depth,
+                publish_rate = publish_rate.rate,
                 "event channel drain loop",
             );
 
+            self.state
+                .record_max_concurrent(&self.shared.concurrent_tasks);
+
+            // record task activity once per publish interval, even while
+            // paused, so that samples always cover the same length of time.
+            if should_sample {
+                self.state.record_sample();
+                self.log_warnings();
+            }
+
             if !self.state_watchers.is_empty() {
+                // measuring the memory used visits every task, so it's only
+                // done once per publish interval.
+                if should_sample || self.memory.is_none() {
+                    let usage = self.state.memory_usage();
+                    self.memory = Some(usage.to_proto(self.histogram_significant_figures));
+                }
                 self.publish_state();
             }
 
             // flush data to clients, if there are any currently subscribed
             // watchers and we should send a new update.
             if !self.watchers.is_empty() && should_send {
-                self.publish();
+                let updated_tasks = self.publish();
+                publish_stride.published(updated_tasks);
+            }
+            if details_due {
+                self.publish_task_details(false);
             }
-            self.cleanup_closed();
+            // drop all closed data that has completed *and* whose final data
+            // has already been sent off.
+            self.state
+                .cleanup_closed(Instant::now(), !self.watchers.is_empty());
+            self.forget_dropped_updates();
             if drained {
                 self.shared.flush.has_flushed();
             }
         }
     }
 
-    fn cleanup_closed(&mut self) {
-        // drop all closed have that has completed *and* whose final data has already
-        // been sent off.
-        let now = Instant::now();
-        let has_watchers = !self.watchers.is_empty();
-        self.tasks
-            .drop_closed(&mut self.task_stats, now, self.retention, has_watchers);
-        self.resources
-            .drop_closed(&mut self.resource_stats, now, self.retention, has_watchers);
-        self.async_ops
-            .drop_closed(&mut self.async_op_stats, now, self.retention, has_watchers);
-        if !has_watchers {
-            self.poll_ops.clear();
-        }
+    /// Logs warnings about unhealthy tasks as `tracing` events in the
+    /// instrumented application, if enabled.
+    ///
+    /// The aggregator doesn't otherwise emit its events to the application's
+    /// subscriber, so the warnings are emitted to the subscriber which the
+    /// layer was registered with, for as long as it exists.
+    fn log_warnings(&mut self) {
+        let Some(lints) = self.lints.as_mut() else {
+            return;
+        };
+        let Some(dispatch) = self.shared.dispatch.get().and_then(WeakDispatch::upgrade) else {
+            return;
+        };
+        self.state.lint_tasks(lints, Instant::now()).log(&dispatch);
     }
 
-    /// Add the task subscription to the watchers after sending the first update
-    fn add_instrument_subscription(&mut self, subscription: Watch<proto::instrument::Update>) {
-        tracing::debug!("new instrument subscription");
-        let now = Instant::now();
+    /// Returns the aggregator's notion of the current time.
+    ///
+    /// See [`AggregatorState::current_time`].
+    fn now(&self) -> Instant {
+        self.state.current_time(Instant::now())
+    }
 
-        let update = loop {
-            let update = proto::instrument::Update {
-                task_update: Some(self.task_update(Include::All)),
-                resource_update: Some(self.resource_update(Include::All)),
-                async_op_update: Some(self.async_op_update(Include::All)),
-                now: Some(self.base_time.to_timestamp(now)),
-                new_metadata: Some(proto::RegisterMetadata {
-                    metadata: (*self.all_metadata).clone(),
-                }),
-            };
-            let message_size = update.encoded_len();
-            if message_size < MAX_MESSAGE_SIZE {
-                // normal case
-                break Some(update);
-            }
-            // If the grpc message is bigger than tokio-console will accept, throw away the oldest
-            // inactive data and try again
-            self.retention /= 2;
-            self.cleanup_closed();
-            tracing::debug!(
-                retention = ?self.retention,
-                message_size,
-                max_message_size = MAX_MESSAGE_SIZE,
-                "Message too big, reduced retention",
+    /// Add the task subscription to the watchers after sending the first update
+    fn add_instrument_subscription(&mut self, subscription: InstrumentRequest) {
+        tracing::debug!(
+            retention = ?subscription.retention,
+            resume_token = ?subscription.resume_token,
+            "new instrument subscription"
+        );
+        // Clients which have gone away since the last update don't count
+        // towards the limit.
+        self.watchers
+            .retain_and_shrink(|watcher| !watcher.request.watch.0.is_closed());
+        if self.watchers.len() >= self.max_watchers {
+            tracing::warn!(
+                max_watchers = self.max_watchers,
+                "too many clients are watching updates; rejecting a new client",
             );
-
-            if self.retention <= self.publish_interval {
-                self.retention = self.publish_interval;
-                break None;
-            }
-        };
-
-        match update {
-            // Send the initial state
-            Some(update) => {
-                if !subscription.update(&update) {
-                    // If sending the initial update fails, the subscription is already dead,
-                    // so don't add it to `watchers`.
-                    return;
+            let _ = subscription
+                .watch
+                .0
+                .try_send(Err(tonic::Status::resource_exhausted(format!(
+                    "too many clients are watching updates (at most {} may watch at once)",
+                    self.max_watchers
+                ))));
+            return;
+        }
+        let has_watchers = !self.watchers.is_empty();
+        // Retain closed data for as long as the new client asked before
+        // building its initial update, so that it sees what is still kept.
+        let requested = self.requested_retention().max(subscription.retention);
+        self.state.set_requested_retention(requested);
+        self.state
+            .record_max_concurrent(&self.shared.concurrent_tasks);
+        let resumed = subscription
+            .resume_token
+            .and_then(|token| self.resume(&subscription.watch, &token, has_watchers));
+        let sent = match resumed {
+            Some(sent) => sent,
+            None => match self.state.initial_update(Instant::now(), has_watchers) {
+                Some(mut update) => {
+                    self.forget_dropped_updates();
+                    self.add_runtime_data(&mut update);
+                    update.resume_token = Some(self.resume.token());
+                    subscription.watch.update(&update)
                 }
-            }
-            // User will only get updates.
-            None => tracing::error!(
-                min_retention = ?self.publish_interval,
-                "Message too big. Start with smaller retention.",
-            ),
+                // If the initial state is too big to send, the user will only
+                // get updates.
+                None => true,
+            },
+        };
+        if !sent {
+            // If sending the first update fails, the subscription is already
+            // dead, so don't add it to `watchers`.
+            self.state
+                .set_requested_retention(self.requested_retention());
+            return;
         }
 
-        self.watchers.push(subscription);
+        // The initial update is as large as the whole state, so it isn't
+        // counted against the client's bandwidth.
+        let bandwidth = self
+            .max_watcher_bandwidth
+            .map(|bytes_per_sec| Bandwidth::new(bytes_per_sec, Instant::now()));
+        self.watchers.push(UpdatesWatch {
+            request: subscription,
+            bandwidth,
+        });
     }
 
-    fn task_update(&mut self, include: Include) -> proto::tasks::TaskUpdate {
-        proto::tasks::TaskUpdate {
-            new_tasks: self.tasks.as_proto_list(include, &self.base_time),
-            stats_update: self.task_stats.as_proto(include, &self.base_time),
-            dropped_events: self.shared.dropped_tasks.swap(0, AcqRel) as u64,
+    /// Sends a client which is reconnecting the updates it missed since the
+    /// update with `token`, returning whether they were sent, or `None` if
+    /// it can't resume from there and must be sent the full state instead.
+    ///
+    /// Updates are only published while clients are watching, so if no
+    /// other clients are, the changes since the last update was published
+    /// are sent as a new update too.
+    fn resume(
+        &mut self,
+        watch: &Watch<proto::instrument::Update>,
+        token: &proto::instrument::ResumeToken,
+        has_watchers: bool,
+    ) -> Option<bool> {
+        let missed = self.resume.since(token)?;
+        // Leave room for the update with the changes since the last one.
+        if missed.len() >= watch.0.capacity() {
+            tracing::debug!(
+                missed = missed.len(),
+                "a client missed too many updates to resume"
+            );
+            return None;
+        }
+        tracing::debug!(missed = missed.len(), "resuming a client's watch");
+        for update in missed {
+            if !watch.update(update) {
+                return Some(false);
+            }
+        }
+        if has_watchers {
+            return Some(true);
         }
+        let mut update = self.state.publish_update(Instant::now());
+        self.add_runtime_data(&mut update);
+        let len = update.encoded_len();
+        self.resume.push(&mut update, len);
+        Some(watch.update(&update))
     }
 
-    fn resource_update(&mut self, include: Include) -> proto::resources::ResourceUpdate {
-        proto::resources::ResourceUpdate {
-            new_resources: self.resources.as_proto_list(include, &self.base_time),
-            stats_update: self.resource_stats.as_proto(include, &self.base_time),
-            new_poll_ops: std::mem::take(&mut self.poll_ops),
-            dropped_events: self.shared.dropped_resources.swap(0, AcqRel) as u64,
+    /// Stops clients from resuming from the updates published so far, if
+    /// closed data has been dropped before it was sent to them.
+    ///
+    /// Nothing is published while no clients are watching, so a client which
+    /// reconnects after longer than the retention would otherwise resume
+    /// without ever learning that the dropped data closed.
+    fn forget_dropped_updates(&mut self) {
+        if self.state.take_dropped_unsent() {
+            tracing::debug!("closed data was dropped before it was sent; clients can't resume");
+            self.resume.forget();
         }
     }
 
-    fn async_op_update(&mut self, include: Include) -> proto::async_ops::AsyncOpUpdate {
-        proto::async_ops::AsyncOpUpdate {
-            new_async_ops: self.async_ops.as_proto_list(include, &self.base_time),
-            stats_update: self.async_op_stats.as_proto(include, &self.base_time),
-            dropped_events: self.shared.dropped_async_ops.swap(0, AcqRel) as u64,
+    /// Returns the longest retention requested by the clients watching
+    /// updates, if any of them requested one.
+    fn requested_retention(&self) -> Option<Duration> {
+        self.watchers
+            .iter()
+            .filter_map(|watcher| watcher.request.retention)
+            .max()
+    }
+
+    /// Adds the data to `update` which isn't recorded from events: the
+    /// runtime's workers, and the number of events dropped since the last
+    /// update.
+    fn add_runtime_data(&mut self, update: &mut proto::instrument::Update) {
+        update.runtime_update = Some(self.workers.update(&self.shared.threads));
+        if let Some(task_update) = &mut update.task_update {
+            task_update.dropped_events = self.shared.dropped_tasks.swap(0, AcqRel) as u64;
+        }
+        if let Some(resource_update) = &mut update.resource_update {
+            resource_update.dropped_events = self.shared.dropped_resources.swap(0, AcqRel) as u64;
+        }
+        if let Some(async_op_update) = &mut update.async_op_update {
+            async_op_update.dropped_events = self.shared.dropped_async_ops.swap(0, AcqRel) as u64;
+        }
+        if let Some(span_update) = &mut update.span_update {
+            span_update.dropped_events = self.shared.dropped_spans.swap(0, AcqRel) as u64;
         }
     }
 
@@ -375,22 +620,19 @@ impl Aggregator {
             id,
             stream_sender,
             buffer,
+            options,
         } = watch_request;
-        tracing::debug!(id = ?id, "new task details subscription");
-        if let Some(stats) = self.task_stats.get(&id) {
-            let (tx, rx) = mpsc::channel(buffer);
-            let subscription = Watch(tx);
-            let now = Some(self.base_time.to_timestamp(Instant::now()));
+        tracing::debug!(id = ?id, ?options, "new task details subscription");
+        if let Some(details) = self.state.task_details(&id, self.now(), options) {
+            // Leave room for the status sent when the watch ends.
+            let (tx, rx) = mpsc::channel(buffer + 1);
+            let subscription = DetailsWatch {
+                watch: Watch(tx),
+                schedule: DetailsSchedule::new(options),
+            };
             // Send back the stream receiver.
             // Then send the initial state --- if this fails, the subscription is already dead.
-            if stream_sender.send(rx).is_ok()
-                && subscription.update(&proto::tasks::TaskDetails {
-                    task_id: Some(id.clone().into()),
-                    now,
-                    poll_times_histogram: Some(stats.poll_duration_histogram()),
-                    scheduled_times_histogram: Some(stats.scheduled_duration_histogram()),
-                })
-            {
+            if stream_sender.send(rx).is_ok() && subscription.update(&details) {
                 self.details_watchers
                     .entry(id.clone())
                     .or_default()
@@ -400,6 +642,58 @@ impl Aggregator {
         // If the task is not found, drop `stream_sender` which will result in a not found error
     }
 
+    /// Add the task details set subscription to the watchers after sending
+    /// the first update.
+    fn add_task_details_set_subscription(&mut self, watch_request: WatchSetRequest) {
+        let WatchSetRequest {
+            ids,
+            location,
+            watch,
+            options,
+        } = watch_request;
+        tracing::debug!(
+            ?ids,
+            ?location,
+            ?options,
+            "new task details set subscription"
+        );
+        let subscription = DetailsSetWatch {
+            watch,
+            ids: ids.into_iter().collect(),
+            location,
+            schedule: DetailsSchedule::new(options),
+        };
+        if subscription
+            .watch
+            .update(&self.task_details_set(&subscription, self.now()))
+        {
+            self.details_set_watchers.push(subscription);
+        }
+    }
+
+    /// Returns the details of every task watched by `watch`.
+    fn task_details_set(
+        &self,
+        watch: &DetailsSetWatch,
+        now: Instant,
+    ) -> proto::tasks::TaskDetailsSet {
+        self.state.task_details_set(
+            &watch.ids,
+            watch.location.as_ref(),
+            now,
+            watch.schedule.options,
+        )
+    }
+
+    /// Add the task groups subscription to the watchers after sending the
+    /// first update.
+    fn add_task_groups_subscription(&mut self, subscription: Watch<proto::tasks::TaskGroups>) {
+        tracing::debug!("new task groups subscription");
+        if subscription.update(&self.state.task_groups(self.now())) {
+            self.group_watchers.push(subscription);
+        }
+    }
+
     /// Add a state subscription to the watchers.
     fn add_state_subscription(&mut self, subscription: Watch<proto::instrument::State>) {
         self.state_watchers.push(subscription);
@@ -408,7 +702,10 @@ impl Aggregator {
     /// Publish the current state to all active state watchers.
     fn publish_state(&mut self) {
         let state = proto::instrument::State {
-            temporality: self.temporality.into(),
+            read_only: self.read_only,
+            layer_panics: self.shared.layer_panics.load(Acquire) as u64,
+            memory: self.memory,
+            ..self.state.state()
         };
         self.state_watchers
             .retain_and_shrink(|watch| watch.update(&state));
@@ -418,153 +715,137 @@ impl Aggregator {
     ///
     /// This drops any watchers which have closed the RPC, or whose update
     /// channel has filled up.
-    fn publish(&mut self) {
-        let new_metadata = if !self.new_metadata.is_empty() {
-            Some(proto::RegisterMetadata {
-                metadata: std::mem::take(&mut self.new_metadata),
-            })
-        } else {
-            None
-        };
-        let task_update = Some(self.task_update(Include::UpdatedOnly));
-        let resource_update = Some(self.resource_update(Include::UpdatedOnly));
-        let async_op_update = Some(self.async_op_update(Include::UpdatedOnly));
-
-        let update = proto::instrument::Update {
-            now: Some(self.base_time.to_timestamp(Instant::now())),
-            new_metadata,
-            task_update,
-            resource_update,
-            async_op_update,
-        };
+    /// Publishes an update to the clients watching them, returning the number
+    /// of tasks whose stats it included.
+    fn publish(&mut self) -> usize {
+        let mut update = self.state.publish_update(Instant::now());
+        self.add_runtime_data(&mut update);
+        let updated_tasks = update
+            .task_update
+            .as_ref()
+            .map_or(0, |task_update| task_update.stats_update.len());
 
-        self.watchers
-            .retain_and_shrink(|watch: &Watch<proto::instrument::Update>| watch.update(&update));
-
-        let stats = &self.task_stats;
-        // Assuming there are much fewer task details subscribers than there are
-        // stats updates, iterate over `details_watchers` and compact the map.
-        self.details_watchers.retain_and_shrink(|id, watchers| {
-            if let Some(task_stats) = stats.get(id) {
-                let details = proto::tasks::TaskDetails {
-                    task_id: Some(id.clone().into()),
-                    now: Some(self.base_time.to_timestamp(Instant::now())),
-                    poll_times_histogram: Some(task_stats.poll_duration_histogram()),
-                    scheduled_times_histogram: Some(task_stats.scheduled_duration_histogram()),
-                };
-                watchers.retain(|watch| watch.update(&details));
-                !watchers.is_empty()
+        let now = Instant::now();
+        let len = update.encoded_len();
+        self.resume.push(&mut update, len);
+        // The summary sent to clients which exceeded their bandwidth is only
+        // built if there are any.
+        let mut summary = None;
+        let watchers = self.watchers.len();
+        self.watchers.retain_and_shrink(|watcher| {
+            let Some(ref mut bandwidth) = watcher.bandwidth else {
+                return watcher.request.watch.update(&update);
+            };
+            if !bandwidth.exceeded && !bandwidth.try_send(len, now) {
+                tracing::warn!(
+                    bytes_per_sec = bandwidth.bytes_per_sec,
+                    update_bytes = len,
+                    "a client exceeded its bandwidth; sending it summaries from now on",
+                );
+                bandwidth.exceeded = true;
+            }
+            if bandwidth.exceeded {
+                let summary = summary.get_or_insert_with(|| summarize(&update));
+                watcher.request.watch.update(summary)
             } else {
-                false
+                watcher.request.watch.update(&update)
             }
         });
-    }
-
-    /// Update the current state with data from a single event.
-    fn update_state(&mut self, event: Event) {
-        // do state update
-        match event {
-            Event::Metadata(meta) => {
-                self.all_metadata.push(meta.into());
-                self.new_metadata.push(meta.into());
-            }
-
-            Event::Spawn {
-                id,
-                metadata,
-                stats,
-                fields,
-                location,
-            } => {
-                self.tasks.insert(
-                    id.clone(),
-                    Task {
-                        id: id.clone(),
-                        is_dirty: AtomicBool::new(true),
-                        metadata,
-                        fields,
-                        location,
-                        // TODO: parents
-                    },
-                );
+        if self.watchers.len() != watchers {
+            self.state
+                .set_requested_retention(self.requested_retention());
+        }
 
-                self.task_stats.insert(id, stats);
-            }
+        self.publish_task_details(true);
 
-            Event::Resource {
-                id,
-                parent_id,
-                metadata,
-                kind,
-                concrete_type,
-                location,
-                is_internal,
-                stats,
-            } => {
-                self.resources.insert(
-                    id.clone(),
-                    Resource {
-                        id: id.clone(),
-                        is_dirty: AtomicBool::new(true),
-                        parent_id,
-                        kind,
-                        metadata,
-                        concrete_type,
-                        location,
-                        is_internal,
-                    },
-                );
+        // Merging the histograms of every task is expensive, so only do it
+        // while a client is watching task groups.
+        if !self.group_watchers.is_empty() {
+            let groups = self.state.task_groups(Instant::now());
+            self.group_watchers
+                .retain_and_shrink(|watch| watch.update(&groups));
+        }
+        updated_tasks
+    }
 
-                self.resource_stats.insert(id, stats);
-            }
+    /// Returns when the next update is due for a task details watcher with
+    /// its own interval, if there are any and the aggregator isn't paused.
+    fn next_details_update(&self) -> Option<Instant> {
+        if self.state.is_paused() {
+            return None;
+        }
+        let singles = self.details_watchers.values().flatten();
+        let single_updates = singles.filter_map(|watch| watch.schedule.own_deadline());
+        let set_updates = self
+            .details_set_watchers
+            .iter()
+            .filter_map(|watch| watch.schedule.own_deadline());
+        single_updates.chain(set_updates).min()
+    }
 
-            Event::PollOp {
-                metadata,
-                resource_id,
-                op_name,
-                async_op_id,
-                task_id,
-                is_ready,
-            } => {
-                // CLI doesn't show historical poll ops, so don't save them if no-one is watching
-                if self.watchers.is_empty() {
-                    return;
+    /// Sends task details to the watchers which are due an update.
+    ///
+    /// Watchers without their own interval are only updated when `publish`
+    /// is set, on the publish interval. This drops any watchers which have
+    /// closed the RPC, or whose update channel has filled up. Watchers of
+    /// single tasks are sent a final status saying why they were dropped.
+    fn publish_task_details(&mut self, publish: bool) {
+        let now = Instant::now();
+        let state = &self.state;
+        // Assuming there are much fewer task details subscribers than there are
+        // stats updates, iterate over `details_watchers` and compact the map.
+        self.details_watchers.retain_and_shrink(|id, watchers| {
+            watchers.retain_mut(|watch| {
+                if !watch.schedule.is_due(now, publish) {
+                    return true;
                 }
-                let poll_op = proto::resources::PollOp {
-                    metadata: Some(metadata.into()),
-                    resource_id: Some(resource_id.into()),
-                    name: op_name,
-                    task_id: Some(task_id.into()),
-                    async_op_id: Some(async_op_id.into()),
-                    is_ready,
-                };
+                match state.task_details(id, now, watch.schedule.options) {
+                    Some(details) => watch.update(&details),
+                    None => {
+                        watch.end(tonic::Status::not_found(format!(
+                            "task {} has completed and is no longer retained",
+                            id.into_u64()
+                        )));
+                        false
+                    }
+                }
+            });
+            !watchers.is_empty()
+        });
 
-                self.poll_ops.push(poll_op);
+        if self.details_set_watchers.is_empty() {
+            return;
+        }
+        let mut watchers = std::mem::take(&mut self.details_set_watchers);
+        watchers.retain_and_shrink(|watch| {
+            if !watch.schedule.is_due(now, publish) {
+                return true;
             }
+            watch.watch.update(&self.task_details_set(watch, now))
+        });
+        self.details_set_watchers = watchers;
+    }
+}
 
-            Event::AsyncResourceOp {
-                id,
-                source,
-                resource_id,
-                metadata,
-                parent_id,
-                stats,
-            } => {
-                self.async_ops.insert(
-                    id.clone(),
-                    AsyncOp {
-                        id: id.clone(),
-                        is_dirty: AtomicBool::new(true),
-                        resource_id,
-                        metadata,
-                        source,
-                        parent_id,
-                    },
-                );
-
-                self.async_op_stats.insert(id, stats);
-            }
-        }
+/// Returns the summary of `update` which is sent to clients which have
+/// exceeded their bandwidth: the task aggregates and activity, and the
+/// runtime's workers, without any tasks, resources, or async ops.
+fn summarize(update: &proto::instrument::Update) -> proto::instrument::Update {
+    let task_update = update
+        .task_update
+        .as_ref()
+        .map(|task_update| proto::tasks::TaskUpdate {
+            dropped_events: task_update.dropped_events,
+            aggregates: task_update.aggregates,
+            activity: task_update.activity.clone(),
+            ..Default::default()
+        });
+    proto::instrument::Update {
+        now: update.now,
+        task_update,
+        runtime_update: update.runtime_update.clone(),
+        degraded: true,
+        ..Default::default()
     }
 }
 
@@ -584,7 +865,11 @@ struct EventCounts {
     metadata: usize,
     poll_op: usize,
     resource: usize,
+    select_branch: usize,
     spawn: usize,
+    task_extension: usize,
+    timer_fired: usize,
+    watched_span: usize,
 }
 
 impl EventCounts {
@@ -594,7 +879,11 @@ impl EventCounts {
             metadata: 0,
             poll_op: 0,
             resource: 0,
+            select_branch: 0,
             spawn: 0,
+            task_extension: 0,
+            timer_fired: 0,
+            watched_span: 0,
         }
     }
 
@@ -605,13 +894,25 @@ impl EventCounts {
             Event::Metadata(_) => self.metadata += 1,
             Event::PollOp { .. } => self.poll_op += 1,
             Event::Resource { .. } => self.resource += 1,
+            Event::SelectBranch { .. } => self.select_branch += 1,
             Event::Spawn { .. } => self.spawn += 1,
+            Event::TaskExtension { .. } => self.task_extension += 1,
+            Event::TimerFired { .. } => self.timer_fired += 1,
+            Event::WatchedSpan { .. } => self.watched_span += 1,
         }
     }
 
     /// Total number of events recorded.
     fn total(&self) -> usize {
-        self.async_resource_op + self.metadata + self.poll_op + self.resource + self.spawn
+        self.async_resource_op
+            + self.metadata
+            + self.poll_op
+            + self.resource
+            + self.select_branch
+            + self.spawn
+            + self.task_extension
+            + self.timer_fired
+            + self.watched_span
     }
 }
 
@@ -636,6 +937,226 @@ impl Flush {
             .triggered
             .compare_exchange(true, false, AcqRel, Acquire);
     }
+
+    /// Records that approximately `depth` events are waiting in the event
+    /// channel.
+    pub(crate) fn record_depth(&self, depth: usize) {
+        self.peak_depth.fetch_max(depth, AcqRel);
+    }
+
+    /// Returns the most events seen waiting in the event channel since this
+    /// was last called.
+    fn take_peak_depth(&self) -> usize {
+        self.peak_depth.swap(0, AcqRel)
+    }
+}
+
+// ==== impl PublishRate ===
+
+impl PublishRate {
+    /// The number of publish intervals in a row in which the event channel
+    /// must reach its high watermark before the rate is raised.
+    const PERSISTENT_INTERVALS: u32 = 3;
+
+    /// The most times per publish interval that updates are published.
+    const MAX_RATE: u32 = 8;
+
+    fn new(interval: Duration) -> Self {
+        Self {
+            interval,
+            rate: 1,
+            pressured_intervals: 0,
+            pressured: false,
+            extra: None,
+            extra_remaining: 0,
+        }
+    }
+
+    /// Records that the event channel reached its high watermark.
+    fn pressured(&mut self) {
+        self.pressured = true;
+    }
+
+    /// Adjusts the rate when the publish interval elapses.
+    ///
+    /// The rate is doubled each time the event channel has reached its high
+    /// watermark in several intervals in a row, and halved after each
+    /// interval in which it didn't.
+    fn interval_elapsed(&mut self) {
+        let rate = if mem::take(&mut self.pressured) {
+            self.pressured_intervals += 1;
+            if self.pressured_intervals >= Self::PERSISTENT_INTERVALS {
+                self.pressured_intervals = 0;
+                (self.rate * 2).min(Self::MAX_RATE)
+            } else {
+                self.rate
+            }
+        } else {
+            self.pressured_intervals = 0;
+            (self.rate / 2).max(1)
+        };
+
+        if rate != self.rate {
+            tracing::debug!(
+                rate,
+                "event buffer pressure changed; adjusting publish rate"
+            );
+            self.rate = rate;
+        }
+        // Restart the extra ticks from the regular one, so that they fall
+        // in between the regular ticks.
+        self.extra_remaining = self.rate - 1;
+        self.extra = (self.rate > 1).then(|| {
+            let period = self.interval / self.rate;
+            tokio::time::interval_at(tokio::time::Instant::now() + period, period)
+        });
+    }
+
+    /// Waits for the next extra publish, or forever if there are no more
+    /// before the next regular one.
+    async fn extra_tick(&mut self) {
+        match self.extra {
+            Some(ref mut extra) if self.extra_remaining > 0 => {
+                extra.tick().await;
+                self.extra_remaining -= 1;
+            }
+            _ => std::future::pending().await,
+        }
+    }
+}
+
+// ==== impl PublishStride ===
+
+impl PublishStride {
+    /// The most publish intervals which elapse between published updates.
+    const MAX_STRIDE: u32 = 8;
+
+    fn new(max_updated_tasks: Option<usize>) -> Self {
+        Self {
+            max_updated_tasks,
+            stride: 1,
+            elapsed: 0,
+        }
+    }
+
+    /// Records that the publish interval elapsed, returning whether an update
+    /// is due.
+    fn interval_elapsed(&mut self) -> bool {
+        self.elapsed += 1;
+        if self.elapsed < self.stride {
+            return false;
+        }
+        self.elapsed = 0;
+        true
+    }
+
+    /// Adjusts the stride after publishing an update which included the
+    /// stats of `updated_tasks` tasks.
+    ///
+    /// The stride is doubled after each update with more than the maximum
+    /// number of tasks, and halved after each with no more than half of it,
+    /// so that it doesn't flip back and forth while the load is close to the
+    /// maximum.
+    fn published(&mut self, updated_tasks: usize) {
+        let Some(max) = self.max_updated_tasks else {
+            return;
+        };
+        let stride = if updated_tasks > max {
+            (self.stride * 2).min(Self::MAX_STRIDE)
+        } else if updated_tasks <= max / 2 {
+            (self.stride / 2).max(1)
+        } else {
+            self.stride
+        };
+
+        if stride != self.stride {
+            tracing::debug!(
+                stride,
+                updated_tasks,
+                "task update size changed; adjusting publish stride"
+            );
+            self.stride = stride;
+        }
+    }
+}
+
+impl Drop for Aggregator {
+    fn drop(&mut self) {
+        self.shared.aggregator.stopped(std::thread::panicking());
+    }
+}
+
+// ==== impl Liveness ===
+
+impl Liveness {
+    const RUNNING: u8 = 0;
+    const STOPPED: u8 = 1;
+    const PANICKED: u8 = 2;
+
+    fn stopped(&self, panicked: bool) {
+        let state = if panicked {
+            Self::PANICKED
+        } else {
+            Self::STOPPED
+        };
+        self.0.store(state, Release);
+    }
+
+    pub(crate) fn is_running(&self) -> bool {
+        self.0.load(Acquire) == Self::RUNNING
+    }
+
+    /// Returns why the aggregator isn't running, if it isn't.
+    pub(crate) fn inactive_reason(&self) -> Option<&'static str> {
+        match self.0.load(Acquire) {
+            Self::STOPPED => Some("the aggregator task has stopped"),
+            Self::PANICKED => Some("the aggregator task panicked"),
+            _ => None,
+        }
+    }
+}
+
+// ==== impl StartupBuffer ===
+
+impl StartupBuffer {
+    pub(crate) fn new(capacity: usize) -> Self {
+        Self {
+            events: Mutex::new(Some(Vec::new())),
+            capacity,
+        }
+    }
+
+    /// Buffers `event`, returning `false` if the aggregator has already
+    /// started, the buffer is full, or the event isn't worth keeping.
+    pub(crate) fn push(&self, event: Event) -> bool {
+        if !matches!(
+            event,
+            Event::Metadata(_)
+                | Event::Spawn { .. }
+                | Event::Resource { .. }
+                | Event::AsyncResourceOp { .. }
+                | Event::WatchedSpan { .. }
+        ) {
+            return false;
+        }
+
+        match *self.events.lock() {
+            Some(ref mut events) if events.len() < self.capacity => {
+                events.push(event);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Returns the buffered events, and stops buffering any more.
+    fn take(&self) -> Vec<Event> {
+        let events = mem::take(&mut *self.events.lock()).unwrap_or_default();
+        if !events.is_empty() {
+            tracing::debug!(events = events.len(), "applying buffered startup events");
+        }
+        events
+    }
 }
 
 impl<T: Clone> Watch<T> {
@@ -649,78 +1170,167 @@ impl<T: Clone> Watch<T> {
     }
 }
 
-impl ToProto for Task {
-    type Output = proto::tasks::Task;
+// === impl Bandwidth ===
 
-    fn to_proto(&self, _: &stats::TimeAnchor) -> Self::Output {
-        proto::tasks::Task {
-            id: Some(self.id.clone().into()),
-            // TODO: more kinds of tasks...
-            kind: proto::tasks::task::Kind::Spawn as i32,
-            metadata: Some(self.metadata.into()),
-            parents: Vec::new(), // TODO: implement parents nicely
-            fields: self.fields.clone(),
-            location: self.location.clone(),
+impl Bandwidth {
+    fn new(bytes_per_sec: u64, now: Instant) -> Self {
+        Self {
+            bytes_per_sec,
+            available: bytes_per_sec as f64,
+            refilled_at: now,
+            exceeded: false,
+        }
+    }
+
+    /// Returns whether `len` bytes may be sent at `now`, using them up if so.
+    fn try_send(&mut self, len: usize, now: Instant) -> bool {
+        let elapsed = now.saturating_duration_since(self.refilled_at);
+        let bytes_per_sec = self.bytes_per_sec as f64;
+        self.available =
+            (self.available + elapsed.as_secs_f64() * bytes_per_sec).min(bytes_per_sec);
+        self.refilled_at = now;
+        let len = len as f64;
+        if len > self.available {
+            return false;
         }
+        self.available -= len;
+        true
     }
 }
 
-impl Unsent for Task {
-    fn take_unsent(&self) -> bool {
-        self.is_dirty.swap(false, AcqRel)
+// === impl DetailsWatch ===
+
+impl DetailsWatch {
+    /// Sends `details` to the client, returning `false` if the watch should
+    /// be dropped.
+    ///
+    /// The last slot in the update channel is kept free, so that a client
+    /// which has fallen behind can still be sent a final status saying why
+    /// its watch is ending.
+    fn update(&self, details: &proto::tasks::TaskDetails) -> bool {
+        if self.watch.0.capacity() <= 1 {
+            self.end(tonic::Status::resource_exhausted(
+                "the client fell behind, and the task details update buffer is full",
+            ));
+            return false;
+        }
+        self.watch.update(details)
     }
 
-    fn is_unsent(&self) -> bool {
-        self.is_dirty.load(Acquire)
+    /// Sends the client a final `status`, saying why its watch is ending.
+    fn end(&self, status: tonic::Status) {
+        // The client may have already gone away.
+        let _ = self.watch.0.try_send(Err(status));
     }
 }
 
-impl ToProto for Resource {
-    type Output = proto::resources::Resource;
+// === impl DetailsSchedule ===
 
-    fn to_proto(&self, _: &stats::TimeAnchor) -> Self::Output {
-        proto::resources::Resource {
-            id: Some(self.id.clone().into()),
-            parent_resource_id: self.parent_id.clone().map(Into::into),
-            kind: Some(self.kind.clone()),
-            metadata: Some(self.metadata.into()),
-            concrete_type: self.concrete_type.clone(),
-            location: self.location.clone(),
-            is_internal: self.is_internal,
+impl DetailsSchedule {
+    fn new(options: DetailsOptions) -> Self {
+        Self {
+            options,
+            next_update: Instant::now() + options.interval.unwrap_or_default(),
         }
     }
-}
 
-impl Unsent for Resource {
-    fn take_unsent(&self) -> bool {
-        self.is_dirty.swap(false, AcqRel)
+    /// Returns when the next update is due, if the client requested its own
+    /// interval.
+    fn own_deadline(&self) -> Option<Instant> {
+        self.options.interval.map(|_| self.next_update)
     }
 
-    fn is_unsent(&self) -> bool {
-        self.is_dirty.load(Acquire)
+    /// Returns whether an update is due now, scheduling the next one if so.
+    ///
+    /// Watchers without their own interval are due whenever the aggregator
+    /// `publish`es.
+    fn is_due(&mut self, now: Instant, publish: bool) -> bool {
+        match self.options.interval {
+            Some(_) if self.next_update > now => false,
+            Some(interval) => {
+                self.next_update = now + interval;
+                true
+            }
+            None => publish,
+        }
     }
 }
 
-impl ToProto for AsyncOp {
-    type Output = proto::async_ops::AsyncOp;
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn publish_rate_rises_under_persistent_pressure() {
+        let mut rate = PublishRate::new(Duration::from_secs(1));
+        for _ in 0..PublishRate::PERSISTENT_INTERVALS - 1 {
+            rate.pressured();
+            rate.interval_elapsed();
+        }
+        assert_eq!(rate.rate, 1, "raised before pressure was persistent");
+
+        rate.pressured();
+        rate.interval_elapsed();
+        assert_eq!(rate.rate, 2);
+        assert_eq!(rate.extra_remaining, 1);
 
-    fn to_proto(&self, _: &stats::TimeAnchor) -> Self::Output {
-        proto::async_ops::AsyncOp {
-            id: Some(self.id.clone().into()),
-            metadata: Some(self.metadata.into()),
-            resource_id: Some(self.resource_id.clone().into()),
-            source: self.source.clone(),
-            parent_async_op_id: self.parent_id.clone().map(Into::into),
+        for _ in 0..PublishRate::PERSISTENT_INTERVALS * 4 {
+            rate.pressured();
+            rate.interval_elapsed();
         }
+        assert_eq!(rate.rate, PublishRate::MAX_RATE);
     }
-}
 
-impl Unsent for AsyncOp {
-    fn take_unsent(&self) -> bool {
-        self.is_dirty.swap(false, AcqRel)
+    #[test]
+    fn publish_stride_adapts_to_updated_tasks() {
+        let mut stride = PublishStride::new(Some(100));
+        stride.published(101);
+        assert_eq!(stride.stride, 2);
+        assert!(!stride.interval_elapsed());
+        assert!(stride.interval_elapsed());
+
+        for _ in 0..4 {
+            stride.published(1000);
+        }
+        assert_eq!(stride.stride, PublishStride::MAX_STRIDE);
+
+        // Loads between half the maximum and the maximum keep the stride.
+        stride.published(75);
+        assert_eq!(stride.stride, PublishStride::MAX_STRIDE);
+        stride.published(50);
+        assert_eq!(stride.stride, 4);
+        for _ in 0..3 {
+            stride.published(0);
+        }
+        assert_eq!(stride.stride, 1);
+        assert!(stride.interval_elapsed());
     }
 
-    fn is_unsent(&self) -> bool {
-        self.is_dirty.load(Acquire)
+    #[test]
+    fn publish_stride_is_fixed_without_maximum() {
+        let mut stride = PublishStride::new(None);
+        stride.published(usize::MAX);
+        assert_eq!(stride.stride, 1);
+        assert!(stride.interval_elapsed());
+    }
+
+    #[tokio::test]
+    async fn publish_rate_falls_without_pressure() {
+        let mut rate = PublishRate::new(Duration::from_secs(1));
+        for _ in 0..PublishRate::PERSISTENT_INTERVALS * 2 {
+            rate.pressured();
+            rate.interval_elapsed();
+        }
+        assert_eq!(rate.rate, 4);
+
+        // A single interval under pressure doesn't keep the rate raised.
+        rate.interval_elapsed();
+        assert_eq!(rate.rate, 2);
+        rate.pressured();
+        rate.interval_elapsed();
+        assert_eq!(rate.rate, 2);
+        rate.interval_elapsed();
+        assert_eq!(rate.rate, 1);
+        assert!(rate.extra.is_none());
     }
 }
@@ -0,0 +1,60 @@
+use console_api as proto;
+use std::collections::{hash_map::Entry, VecDeque};
+
+/// The most recently completed tasks which were dropped once their retention
+/// period elapsed, so that clients which attach afterwards can still be sent
+/// them.
+///
+/// Only the tasks' protobuf representations are kept, as their stats no
+/// longer change.
+#[derive(Debug)]
+pub(super) struct RecentTasks {
+    capacity: usize,
+    tasks: VecDeque<(proto::tasks::Task, proto::tasks::Stats)>,
+}
+
+// === impl RecentTasks ===
+
+impl RecentTasks {
+    pub(super) fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            tasks: VecDeque::with_capacity(capacity),
+        }
+    }
+
+    /// Keeps a completed task which is being dropped, forgetting the oldest
+    /// task kept if there are already as many as the capacity.
+    pub(super) fn push(&mut self, mut task: proto::tasks::Task, stats: proto::tasks::Stats) {
+        if self.capacity == 0 {
+            return;
+        }
+        if self.tasks.len() == self.capacity {
+            self.tasks.pop_front();
+        }
+        task.historical = true;
+        self.tasks.push_back((task, stats));
+    }
+
+    pub(super) fn clear(&mut self) {
+        self.tasks.clear();
+    }
+
+    /// Adds the tasks kept to `update`, except those whose IDs have since
+    /// been reused by tasks in it.
+    pub(super) fn backfill(&self, update: &mut proto::tasks::TaskUpdate) {
+        for (task, stats) in &self.tasks {
+            let Some(id) = task.id.as_ref().map(|id| id.id) else {
+                continue;
+            };
+            if let Entry::Vacant(entry) = update.stats_update.entry(id) {
+                entry.insert(stats.clone());
+                update.new_tasks.push(task.clone());
+            }
+        }
+    }
+
+    pub(super) fn is_empty(&self) -> bool {
+        self.tasks.is_empty()
+    }
+}
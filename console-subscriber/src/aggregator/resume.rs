@@ -0,0 +1,183 @@
+use console_api as proto;
+use std::{collections::VecDeque, time::SystemTime};
+
+/// The most recent updates which are kept for clients to resume from.
+///
+/// At the default publish interval, this allows a client to resume after
+/// being disconnected for up to a minute.
+const MAX_UPDATES: usize = 60;
+
+/// The most encoded bytes of updates which are kept for clients to resume
+/// from.
+///
+/// When updates are this large, sending the full state to a client which
+/// reconnects isn't much more expensive than sending the updates it missed.
+const MAX_BYTES: usize = 4 * 1024 * 1024;
+
+/// The most recent updates published to the clients watching updates, so
+/// that a client which reconnects after a brief disconnection can be sent
+/// only the updates it missed, rather than the full state.
+#[derive(Debug)]
+pub(super) struct ResumeHistory {
+    /// Identifies this aggregator in its tokens, so that the tokens of
+    /// another process aren't mistaken for its own.
+    epoch: u64,
+    /// The number of updates published.
+    sequence: u64,
+    /// The updates which are kept, with their encoded lengths, oldest first.
+    /// The last update is the one with the current `sequence`.
+    updates: VecDeque<(proto::instrument::Update, usize)>,
+    /// The sum of the encoded lengths of `updates`.
+    bytes: usize,
+}
+
+// === impl ResumeHistory ===
+
+impl ResumeHistory {
+    pub(super) fn new() -> Self {
+        let epoch = SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .map_or(0, |since| since.as_nanos() as u64);
+        Self {
+            epoch,
+            sequence: 0,
+            updates: VecDeque::new(),
+            bytes: 0,
+        }
+    }
+
+    /// Returns the token of the latest published update, which a client
+    /// that has seen everything it included can resume from.
+    pub(super) fn token(&self) -> proto::instrument::ResumeToken {
+        proto::instrument::ResumeToken {
+            epoch: self.epoch,
+            sequence: self.sequence,
+        }
+    }
+
+    /// Records an update published to every client, whose encoded length is
+    /// `len`, setting its token.
+    ///
+    /// The oldest updates are forgotten once too many are kept.
+    pub(super) fn push(&mut self, update: &mut proto::instrument::Update, len: usize) {
+        self.sequence += 1;
+        update.resume_token = Some(self.token());
+        self.updates.push_back((update.clone(), len));
+        self.bytes += len;
+        while self.updates.len() > MAX_UPDATES || self.bytes > MAX_BYTES {
+            let Some((_, len)) = self.updates.pop_front() else {
+                break;
+            };
+            self.bytes -= len;
+        }
+    }
+
+    /// Stops clients from resuming from any update published so far, such as
+    /// when data which they were never sent has been dropped.
+    ///
+    /// The sequence moves on as if an update had been published, so clients
+    /// sent the full state after this can still resume.
+    pub(super) fn forget(&mut self) {
+        self.sequence += 1;
+        self.updates.clear();
+        self.bytes = 0;
+    }
+
+    /// Returns the updates published since the update with `token`, if a
+    /// client can resume from it, and `None` if the token is from another
+    /// process or its updates have already been forgotten.
+    pub(super) fn since(
+        &self,
+        token: &proto::instrument::ResumeToken,
+    ) -> Option<impl ExactSizeIterator<Item = &proto::instrument::Update>> {
+        if token.epoch != self.epoch || token.sequence > self.sequence {
+            return None;
+        }
+        let missed = (self.sequence - token.sequence) as usize;
+        if missed > self.updates.len() {
+            return None;
+        }
+        Some(
+            self.updates
+                .range(self.updates.len() - missed..)
+                .map(|(update, _)| update),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn push(history: &mut ResumeHistory, len: usize) -> proto::instrument::ResumeToken {
+        let mut update = proto::instrument::Update::default();
+        history.push(&mut update, len);
+        update.resume_token.unwrap()
+    }
+
+    fn sequences(history: &ResumeHistory, token: &proto::instrument::ResumeToken) -> Vec<u64> {
+        history
+            .since(token)
+            .expect("the client can resume")
+            .map(|update| update.resume_token.unwrap().sequence)
+            .collect()
+    }
+
+    #[test]
+    fn clients_resume_from_their_last_update() {
+        let mut history = ResumeHistory::new();
+        let initial = history.token();
+        let first = push(&mut history, 10);
+        push(&mut history, 10);
+        push(&mut history, 10);
+
+        assert_eq!(sequences(&history, &initial), [1, 2, 3]);
+        assert_eq!(sequences(&history, &first), [2, 3]);
+        assert!(sequences(&history, &history.token()).is_empty());
+
+        let other_process = proto::instrument::ResumeToken {
+            epoch: first.epoch + 1,
+            ..first
+        };
+        assert!(history.since(&other_process).is_none());
+        let ahead = proto::instrument::ResumeToken {
+            sequence: 4,
+            ..first
+        };
+        assert!(history.since(&ahead).is_none());
+    }
+
+    #[test]
+    fn old_updates_are_forgotten() {
+        let mut history = ResumeHistory::new();
+        let first = push(&mut history, 10);
+        for _ in 0..MAX_UPDATES {
+            push(&mut history, 10);
+        }
+        assert_eq!(history.since(&first).unwrap().len(), MAX_UPDATES);
+        push(&mut history, 10);
+        assert!(history.since(&first).is_none());
+
+        // A large update pushes out everything before it.
+        let older = push(&mut history, 10);
+        let before_large = push(&mut history, 10);
+        push(&mut history, MAX_BYTES);
+        assert!(history.since(&older).is_none());
+        assert_eq!(history.since(&before_large).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn forgotten_updates_cant_be_resumed_from() {
+        let mut history = ResumeHistory::new();
+        let first = push(&mut history, 10);
+        let latest = push(&mut history, 10);
+        history.forget();
+        assert!(history.since(&first).is_none());
+        assert!(history.since(&latest).is_none());
+
+        let after = history.token();
+        assert!(sequences(&history, &after).is_empty());
+        let next = push(&mut history, 10);
+        assert_eq!(sequences(&history, &after), [next.sequence]);
+    }
+}
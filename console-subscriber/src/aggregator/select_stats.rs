@@ -0,0 +1,129 @@
+use super::id_data::Include;
+use console_api as proto;
+use std::{cmp, collections::HashMap, time::Duration};
+
+/// Branch statistics for each instrumented `select!` callsite, keyed by the
+/// callsite's file, line, and column.
+#[derive(Debug, Default)]
+pub(super) struct SelectStats {
+    callsites: HashMap<(String, u32, u32), Callsite>,
+}
+
+#[derive(Debug)]
+struct Callsite {
+    location: proto::Location,
+    branches: Vec<Branch>,
+    is_dirty: bool,
+}
+
+#[derive(Debug, Default, Clone, Copy)]
+struct Branch {
+    completions: u64,
+    total_wait: Duration,
+    max_wait: Duration,
+}
+
+// === impl SelectStats ===
+
+impl SelectStats {
+    /// The most branches a `tokio::select!` can have.
+    const MAX_BRANCHES: usize = 64;
+
+    /// Records that `branch` of the `select!` at `location` completed after
+    /// waiting for `waited`.
+    ///
+    /// The branches are reported by the instrumented application, so a branch
+    /// that's out of range, or a `select!` with more branches than
+    /// `tokio::select!` allows, is ignored rather than allocated for.
+    pub(super) fn record(
+        &mut self,
+        location: proto::Location,
+        branch: usize,
+        branches: usize,
+        waited: Duration,
+    ) {
+        if branch >= branches || branches > Self::MAX_BRANCHES {
+            return;
+        }
+
+        let key = (
+            location.file.clone().unwrap_or_default(),
+            location.line.unwrap_or_default(),
+            location.column.unwrap_or_default(),
+        );
+        let callsite = self.callsites.entry(key).or_insert_with(|| Callsite {
+            location,
+            branches: Vec::new(),
+            is_dirty: true,
+        });
+
+        if callsite.branches.len() < branches {
+            callsite.branches.resize(branches, Branch::default());
+        }
+
+        let stats = &mut callsite.branches[branch];
+        stats.completions += 1;
+        stats.total_wait += waited;
+        stats.max_wait = cmp::max(stats.max_wait, waited);
+        callsite.is_dirty = true;
+    }
+
+    /// Returns the stats for every callsite if `include` is [`Include::All`],
+    /// or only those which changed since the last update otherwise.
+    pub(super) fn updates(&mut self, include: Include) -> Vec<proto::tasks::SelectStats> {
+        self.callsites
+            .values_mut()
+            .filter_map(|callsite| {
+                if include == Include::UpdatedOnly
+                    && !std::mem::replace(&mut callsite.is_dirty, false)
+                {
+                    return None;
+                }
+                Some(proto::tasks::SelectStats {
+                    location: Some(callsite.location.clone()),
+                    branches: callsite
+                        .branches
+                        .iter()
+                        .map(|branch| proto::tasks::SelectBranchStats {
+                            completions: branch.completions,
+                            total_wait: branch.total_wait.try_into().ok(),
+                            max_wait: branch.max_wait.try_into().ok(),
+                        })
+                        .collect(),
+                })
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn location() -> proto::Location {
+        proto::Location {
+            file: Some("src/main.rs".to_string()),
+            line: Some(10),
+            column: Some(5),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn out_of_range_branches_are_ignored() {
+        let mut stats = SelectStats::default();
+        stats.record(location(), 1, 2, Duration::from_millis(5));
+        stats.record(location(), 2, 2, Duration::from_millis(5));
+        stats.record(location(), usize::MAX, 2, Duration::from_millis(5));
+        stats.record(location(), 1, usize::MAX, Duration::from_millis(5));
+
+        let updates = stats.updates(Include::All);
+        assert_eq!(updates.len(), 1);
+        let completions = updates[0]
+            .branches
+            .iter()
+            .map(|branch| branch.completions)
+            .collect::<Vec<_>>();
+        assert_eq!(completions, [0, 1]);
+    }
+}
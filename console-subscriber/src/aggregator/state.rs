@@ -0,0 +1,1544 @@
+use std::{
+    collections::{HashMap, HashSet},
+    mem,
+    sync::{
+        atomic::{AtomicBool, Ordering::*},
+        Arc,
+    },
+    time::{Duration, Instant},
+};
+
+use console_api as proto;
+use prost::Message;
+use proto::resources::resource;
+use tracing_core::{span::Id, Metadata};
+
+use super::id_data::{IdData, Include};
+use super::intern::{self, Strings};
+use super::lints::{Lint, Lints, Report, Warning};
+use super::recent_tasks::RecentTasks;
+use super::select_stats::SelectStats;
+use super::shrink::ShrinkVec;
+use super::task_aggregates::{ConcurrentTasks, TaskAggregates};
+use super::timer_stats::TimerStats;
+use super::waker_leaks::{LeakedTask, WakerLeaks};
+use crate::{
+    stats::{self, DroppedAt, Unsent},
+    DetailsOptions, Event, ToProto,
+};
+
+/// Should match tonic's (private) codec::DEFAULT_MAX_RECV_MESSAGE_SIZE
+const MAX_MESSAGE_SIZE: usize = 4 * 1024 * 1024;
+
+/// The state built up by the [`Aggregator`] from instrumentation events.
+///
+/// This applies events, drops closed data once its retention period has
+/// elapsed, and builds the updates which are published to clients, but
+/// doesn't know about channels or the runtime. Every operation which depends
+/// on the time is passed the current time, so that the state can be driven
+/// by the aggregator's event loop, by tests, or by replaying recorded events.
+///
+/// [`Aggregator`]: super::Aggregator
+pub(super) struct AggregatorState {
+    /// The interval at which new data updates are pushed to clients.
+    publish_interval: Duration,
+
+    /// How long to keep task data after a task has completed.
+    retention: Duration,
+
+    /// The longest retention that clients may request.
+    max_retention: Duration,
+
+    /// The longest retention requested by the clients which are watching
+    /// updates, if it is longer than `retention`.
+    requested_retention: Option<Duration>,
+
+    /// *All* metadata for task spans and user-defined spans that we care about.
+    ///
+    /// This is sent to new clients as part of the initial state.
+    all_metadata: ShrinkVec<proto::register_metadata::NewMetadata>,
+
+    /// *New* metadata that was registered since the last state update.
+    ///
+    /// This is emptied on every state update.
+    new_metadata: Vec<proto::register_metadata::NewMetadata>,
+
+    /// Map of task IDs to task static data.
+    tasks: IdData<Task>,
+
+    /// Map of task IDs to task stats.
+    task_stats: IdData<Arc<stats::TaskStats>>,
+
+    /// The most recently completed tasks which have been dropped, which are
+    /// sent to new clients as historical tasks.
+    recent_tasks: RecentTasks,
+
+    /// The completed tasks which have been dropped while some of their
+    /// wakers were still alive.
+    waker_leaks: WakerLeaks,
+
+    /// Aggregate stats for all tasks spawned over the lifetime of the process.
+    task_aggregates: TaskAggregates,
+
+    /// Branch statistics for instrumented `select!` callsites.
+    select_stats: SelectStats,
+
+    /// Map of resource IDs to resource static data.
+    resources: IdData<Resource>,
+
+    /// Map of resource IDs to resource stats.
+    resource_stats: IdData<Arc<stats::ResourceStats>>,
+
+    /// Deadline overrun statistics for timer callsites.
+    timer_stats: TimerStats,
+
+    /// Strings shared between the static data of tasks and resources.
+    strings: Strings,
+
+    /// Map of AsyncOp IDs to AsyncOp static data.
+    async_ops: IdData<AsyncOp>,
+
+    /// Map of AsyncOp IDs to AsyncOp stats.
+    async_op_stats: IdData<Arc<stats::AsyncOpStats>>,
+
+    /// Map of watched span IDs to watched span static data.
+    spans: IdData<WatchedSpan>,
+
+    /// Map of watched span IDs to watched span stats.
+    span_stats: IdData<Arc<stats::WatchedSpanStats>>,
+
+    /// `PollOp `events that have occurred since the last update
+    ///
+    /// This is emptied on every state update.
+    poll_ops: Vec<proto::resources::PollOp>,
+
+    /// Whether closed data has been dropped before it was sent to any
+    /// clients, since this was last checked.
+    ///
+    /// Clients which resume from an update published before then would
+    /// never learn that the data closed, so they must be sent the full
+    /// state instead.
+    dropped_unsent: bool,
+
+    /// The time "state" of the aggregator, such as paused or live.
+    temporality: proto::instrument::Temporality,
+
+    /// When the aggregator was paused, if it is currently paused.
+    paused_at: Option<Instant>,
+
+    /// Whether the current pause also freezes the aggregator's notion of the
+    /// current time. See [`AggregatorState::current_time`].
+    freeze_time: bool,
+
+    /// How long the aggregator was paused for, the last time it was resumed.
+    last_pause: Option<Duration>,
+
+    /// Used to anchor monotonic timestamps to a base `SystemTime`, to produce a
+    /// timestamp that can be sent over the wire.
+    base_time: stats::TimeAnchor,
+}
+
+// Represent static data for resources
+struct Resource {
+    id: Id,
+    is_dirty: AtomicBool,
+    parent_id: Option<Id>,
+    metadata: &'static Metadata<'static>,
+    concrete_type: Arc<str>,
+    kind: resource::Kind,
+    location: Option<intern::Location>,
+    is_internal: bool,
+}
+
+/// Represents static data for tasks
+struct Task {
+    id: Id,
+    is_dirty: AtomicBool,
+    metadata: &'static Metadata<'static>,
+    fields: Vec<intern::Field>,
+    omitted_fields: u64,
+    location: Option<intern::Location>,
+    scheduler: proto::tasks::task::Scheduler,
+    /// Payloads attached to the task by the application, at most one of each
+    /// type.
+    extensions: Vec<prost_types::Any>,
+    /// Whether the extensions changed since the last update.
+    extensions_dirty: bool,
+}
+
+struct AsyncOp {
+    id: Id,
+    is_dirty: AtomicBool,
+    parent_id: Option<Id>,
+    resource_id: Id,
+    metadata: &'static Metadata<'static>,
+    source: String,
+}
+
+/// Represents static data for spans the application has asked to watch.
+struct WatchedSpan {
+    id: Id,
+    is_dirty: AtomicBool,
+    metadata: &'static Metadata<'static>,
+    fields: Vec<intern::Field>,
+    omitted_fields: u64,
+    location: intern::Location,
+    task_id: Option<Id>,
+}
+
+// === impl AggregatorState ===
+
+impl AggregatorState {
+    pub(super) fn new(
+        publish_interval: Duration,
+        retention: Duration,
+        max_retention: Duration,
+        recent_completed_tasks: usize,
+        base_time: stats::TimeAnchor,
+    ) -> Self {
+        Self {
+            publish_interval,
+            retention,
+            max_retention,
+            requested_retention: None,
+            all_metadata: Default::default(),
+            new_metadata: Default::default(),
+            tasks: IdData::default(),
+            task_stats: IdData::default(),
+            recent_tasks: RecentTasks::new(recent_completed_tasks),
+            waker_leaks: WakerLeaks::default(),
+            task_aggregates: TaskAggregates::default(),
+            select_stats: SelectStats::default(),
+            resources: IdData::default(),
+            resource_stats: IdData::default(),
+            timer_stats: TimerStats::default(),
+            strings: Strings::default(),
+            async_ops: IdData::default(),
+            async_op_stats: IdData::default(),
+            spans: IdData::default(),
+            span_stats: IdData::default(),
+            poll_ops: Default::default(),
+            dropped_unsent: false,
+            temporality: proto::instrument::Temporality::Live,
+            paused_at: None,
+            freeze_time: false,
+            last_pause: None,
+            base_time,
+        }
+    }
+
+    pub(super) fn publish_interval(&self) -> Duration {
+        self.publish_interval
+    }
+
+    pub(super) fn is_paused(&self) -> bool {
+        self.temporality == proto::instrument::Temporality::Paused
+    }
+
+    pub(super) fn pause(&mut self, now: Instant, freeze_time: bool) {
+        self.temporality = proto::instrument::Temporality::Paused;
+        // if we were already paused, keep the original pause time, so that
+        // repeated pause requests don't move the frozen clock forward, and
+        // keep time frozen if it was, so that a later pause which doesn't
+        // freeze time can't thaw the clock.
+        self.paused_at.get_or_insert(now);
+        self.freeze_time |= freeze_time;
+    }
+
+    pub(super) fn resume(&mut self, now: Instant) {
+        self.temporality = proto::instrument::Temporality::Live;
+        self.freeze_time = false;
+        if let Some(paused_at) = self.paused_at.take() {
+            self.last_pause = Some(now.saturating_duration_since(paused_at));
+        }
+    }
+
+    /// Returns the aggregator's notion of the current time, given that the
+    /// actual time is `now`.
+    ///
+    /// This is the time at which the aggregator was paused if the pause froze
+    /// time, so that any durations which clients calculate relative to it
+    /// don't keep accruing while paused.
+    pub(super) fn current_time(&self, now: Instant) -> Instant {
+        match self.paused_at {
+            Some(paused_at) if self.freeze_time => paused_at,
+            _ => now,
+        }
+    }
+
+    /// Returns the current time "state" of the aggregator.
+    pub(super) fn state(&self) -> proto::instrument::State {
+        proto::instrument::State {
+            temporality: self.temporality.into(),
+            last_pause: self.last_pause.and_then(|pause| pause.try_into().ok()),
+            read_only: false,
+            tokio_unstable: Some(cfg!(tokio_unstable)),
+            layer_panics: 0,
+            memory: None,
+        }
+    }
+
+    /// Returns an approximation of the memory used by the tasks and the
+    /// histograms which are retained.
+    pub(super) fn memory_usage(&self) -> stats::MemoryUsage {
+        let mut usage = stats::MemoryUsage::default();
+        for (_, task) in self.tasks.all() {
+            usage.add_task(task.memory_size());
+        }
+        for (_, stats) in self.task_stats.all() {
+            stats.measure(&mut usage);
+        }
+        for (_, stats) in self.resource_stats.all() {
+            stats.measure(&mut usage);
+        }
+        usage
+    }
+
+    /// Checks the tasks which haven't completed at `now` for problems which
+    /// haven't been reported yet, returning the warnings about them.
+    pub(super) fn lint_tasks(&self, lints: &mut Lints, now: Instant) -> Report {
+        lints.retain(|id| self.tasks.get(id).is_some());
+        let mut warnings = Vec::new();
+        for (id, stats) in self.task_stats.all() {
+            let Some(task) = self.tasks.get(id) else {
+                continue;
+            };
+            // blocking tasks don't yield or use wakers.
+            if task.is_blocking() {
+                continue;
+            }
+            for (lint, duration) in lints.check(id, stats, now) {
+                warnings.push(task.warning(lint, duration));
+            }
+        }
+        lints.rate_limit(warnings, now)
+    }
+
+    /// Records a sample of task activity.
+    ///
+    /// This is called once per publish interval, even while paused, so that
+    /// samples always cover the same length of time.
+    pub(super) fn record_sample(&mut self) {
+        self.task_aggregates.record_sample(&self.task_stats);
+    }
+
+    /// Records the highest number of tasks which have been live at once.
+    pub(super) fn record_max_concurrent(&mut self, concurrent: &ConcurrentTasks) {
+        self.task_aggregates.record_max_concurrent(concurrent);
+    }
+
+    /// Drops all closed data which has completed *and* whose retention period
+    /// has elapsed by `now`.
+    ///
+    /// If any clients are watching updates, data which hasn't been sent to
+    /// them yet is kept regardless of its age. Otherwise, the poll ops
+    /// recorded since the last update are dropped too, as clients don't show
+    /// historical poll ops.
+    ///
+    /// Dropping data which hasn't been sent is recorded, to be checked with
+    /// [`AggregatorState::take_dropped_unsent`]. The poll ops don't count, as
+    /// a client sent the full state doesn't get them either.
+    pub(super) fn cleanup_closed(&mut self, now: Instant, has_watchers: bool) {
+        let retention = self.retention();
+        let task_aggregates = &mut self.task_aggregates;
+        let recent_tasks = &mut self.recent_tasks;
+        let waker_leaks = &mut self.waker_leaks;
+        let base_time = &self.base_time;
+        let mut dropped_unsent = self.tasks.drop_closed(
+            &mut self.task_stats,
+            now,
+            retention,
+            has_watchers,
+            |task, stats| {
+                task_aggregates.retire(stats);
+                if let Some(task) = task {
+                    recent_tasks.push(task.to_proto(base_time), stats.to_proto(base_time));
+                    if let Some(dropped_at) = stats.dropped_at() {
+                        waker_leaks.push(task.leaked(dropped_at), stats.waker_counts());
+                    }
+                }
+            },
+        );
+        dropped_unsent |= self.resources.drop_closed(
+            &mut self.resource_stats,
+            now,
+            retention,
+            has_watchers,
+            |_, _| {},
+        );
+        dropped_unsent |= self.async_ops.drop_closed(
+            &mut self.async_op_stats,
+            now,
+            retention,
+            has_watchers,
+            |_, _| {},
+        );
+        dropped_unsent |= self.spans.drop_closed(
+            &mut self.span_stats,
+            now,
+            retention,
+            has_watchers,
+            |_, _| {},
+        );
+        self.dropped_unsent |= dropped_unsent;
+        self.strings.retain_referenced();
+        if !has_watchers {
+            self.poll_ops.clear();
+        }
+    }
+
+    /// Returns whether closed data has been dropped before it was sent to
+    /// any clients since this was last called.
+    pub(super) fn take_dropped_unsent(&mut self) -> bool {
+        mem::take(&mut self.dropped_unsent)
+    }
+
+    /// Sets the retention requested by the clients watching updates: the
+    /// longest of the retentions they asked for, or `None` if none of them
+    /// asked.
+    ///
+    /// Closed data is retained for the requested retention, up to the maximum
+    /// retention, while it is longer than the configured one.
+    pub(super) fn set_requested_retention(&mut self, requested: Option<Duration>) {
+        self.requested_retention = requested
+            .map(|requested| requested.min(self.max_retention))
+            .filter(|&requested| requested > self.retention);
+    }
+
+    /// Returns how long closed data is currently retained for.
+    fn retention(&self) -> Duration {
+        self.requested_retention.unwrap_or(self.retention)
+    }
+
+    /// Returns the update sent to a new client, which includes all of the
+    /// current state.
+    ///
+    /// If that update would be larger than clients accept, the retention
+    /// period is reduced to drop the oldest closed data until it fits. If it
+    /// still doesn't fit once the retention is reduced to the publish
+    /// interval, this returns `None`.
+    pub(super) fn initial_update(
+        &mut self,
+        now: Instant,
+        has_watchers: bool,
+    ) -> Option<proto::instrument::Update> {
+        let current_time = self.current_time(now);
+        loop {
+            let mut task_update = self.task_update(Include::All);
+            self.recent_tasks.backfill(&mut task_update);
+            let update = proto::instrument::Update {
+                task_update: Some(task_update),
+                resource_update: Some(self.resource_update(Include::All)),
+                async_op_update: Some(self.async_op_update(Include::All)),
+                span_update: Some(self.span_update(Include::All)),
+                now: Some(self.base_time.to_timestamp(current_time)),
+                new_metadata: Some(proto::RegisterMetadata {
+                    metadata: (*self.all_metadata).clone(),
+                }),
+                runtime_update: None,
+                degraded: false,
+                resume_token: None,
+            };
+            let message_size = update.encoded_len();
+            if message_size < MAX_MESSAGE_SIZE {
+                // normal case
+                return Some(update);
+            }
+            // The historical tasks are the oldest data, so they're the first
+            // to go.
+            if !self.recent_tasks.is_empty() {
+                tracing::debug!(
+                    message_size,
+                    max_message_size = MAX_MESSAGE_SIZE,
+                    "Message too big, dropped recently completed tasks",
+                );
+                self.recent_tasks.clear();
+                continue;
+            }
+            // If the grpc message is bigger than tokio-console will accept, throw away the oldest
+            // inactive data and try again. This also gives up on any longer
+            // retention that clients requested.
+            self.retention = self.retention() / 2;
+            self.requested_retention = None;
+            self.cleanup_closed(now, has_watchers);
+            tracing::debug!(
+                retention = ?self.retention,
+                message_size,
+                max_message_size = MAX_MESSAGE_SIZE,
+                "Message too big, reduced retention",
+            );
+
+            if self.retention <= self.publish_interval {
+                self.retention = self.publish_interval;
+                tracing::error!(
+                    min_retention = ?self.publish_interval,
+                    "Message too big. Start with smaller retention.",
+                );
+                return None;
+            }
+        }
+    }
+
+    /// Returns the update published to clients at `now`, which includes only
+    /// the data that changed since the previous update.
+    pub(super) fn publish_update(&mut self, now: Instant) -> proto::instrument::Update {
+        let new_metadata = if !self.new_metadata.is_empty() {
+            Some(proto::RegisterMetadata {
+                metadata: std::mem::take(&mut self.new_metadata),
+            })
+        } else {
+            None
+        };
+        proto::instrument::Update {
+            now: Some(self.base_time.to_timestamp(now)),
+            new_metadata,
+            task_update: Some(self.task_update(Include::UpdatedOnly)),
+            resource_update: Some(self.resource_update(Include::UpdatedOnly)),
+            async_op_update: Some(self.async_op_update(Include::UpdatedOnly)),
+            span_update: Some(self.span_update(Include::UpdatedOnly)),
+            runtime_update: None,
+            degraded: false,
+            resume_token: None,
+        }
+    }
+
+    fn task_update(&mut self, include: Include) -> proto::tasks::TaskUpdate {
+        // This must be taken before the new tasks are, since the extensions
+        // of tasks which haven't been sent yet are sent with the tasks.
+        let extensions_update = self.extensions_update(include);
+        proto::tasks::TaskUpdate {
+            new_tasks: self.tasks.as_proto_list(include, &self.base_time),
+            stats_update: self.task_stats.as_proto(include, &self.base_time),
+            dropped_events: 0,
+            aggregates: Some(self.task_aggregates.aggregates(&self.task_stats)),
+            activity: Some(
+                self.task_aggregates
+                    .activity(include, self.publish_interval),
+            ),
+            select_stats: self.select_stats.updates(include),
+            extensions_update,
+            waker_leaks: self.waker_leaks.update(include, &self.base_time),
+        }
+    }
+
+    /// Returns the extensions of the tasks whose extensions changed since the
+    /// last update, leaving out the tasks which are sent with this update.
+    fn extensions_update(&mut self, include: Include) -> HashMap<u64, proto::tasks::Extensions> {
+        if include == Include::All {
+            // every task is sent with this update, along with its extensions.
+            return HashMap::new();
+        }
+        self.tasks
+            .all_mut()
+            .filter_map(|(id, task)| {
+                // tasks which haven't been sent yet are sent with their
+                // extensions.
+                if !mem::take(&mut task.extensions_dirty) || task.is_unsent() {
+                    return None;
+                }
+                let extensions = proto::tasks::Extensions {
+                    extensions: task.extensions.clone(),
+                };
+                Some((id.into_u64(), extensions))
+            })
+            .collect()
+    }
+
+    fn resource_update(&mut self, include: Include) -> proto::resources::ResourceUpdate {
+        proto::resources::ResourceUpdate {
+            new_resources: self.resources.as_proto_list(include, &self.base_time),
+            stats_update: self.resource_stats.as_proto(include, &self.base_time),
+            new_poll_ops: std::mem::take(&mut self.poll_ops),
+            dropped_events: 0,
+            timer_stats: self.timer_stats.updates(include),
+        }
+    }
+
+    fn async_op_update(&mut self, include: Include) -> proto::async_ops::AsyncOpUpdate {
+        proto::async_ops::AsyncOpUpdate {
+            new_async_ops: self.async_ops.as_proto_list(include, &self.base_time),
+            stats_update: self.async_op_stats.as_proto(include, &self.base_time),
+            dropped_events: 0,
+        }
+    }
+
+    fn span_update(&mut self, include: Include) -> proto::spans::SpanUpdate {
+        proto::spans::SpanUpdate {
+            new_spans: self.spans.as_proto_list(include, &self.base_time),
+            stats_update: self.span_stats.as_proto(include, &self.base_time),
+            dropped_events: 0,
+        }
+    }
+
+    /// Returns the current details of the task with the given `id`, if it
+    /// exists.
+    pub(super) fn task_details(
+        &self,
+        id: &Id,
+        now: Instant,
+        options: DetailsOptions,
+    ) -> Option<proto::tasks::TaskDetails> {
+        let stats = self.task_stats.get(id)?;
+        let sigfig = options.significant_figures;
+        let (thread_polls, other_thread_polls) = stats.thread_polls();
+        Some(proto::tasks::TaskDetails {
+            task_id: Some(id.clone().into()),
+            now: Some(self.base_time.to_timestamp(now)),
+            poll_times_histogram: stats.poll_duration_histogram(sigfig),
+            scheduled_times_histogram: stats.scheduled_duration_histogram(sigfig),
+            thread_polls,
+            other_thread_polls,
+            wake_intervals_histogram: stats.wake_interval_histogram(sigfig),
+        })
+    }
+
+    /// Returns the details of the tasks with the given `ids`, and of every
+    /// other task spawned from `location`, if one is given.
+    pub(super) fn task_details_set(
+        &self,
+        ids: &HashSet<Id>,
+        location: Option<&proto::Location>,
+        now: Instant,
+        options: DetailsOptions,
+    ) -> proto::tasks::TaskDetailsSet {
+        let mut details = ids
+            .iter()
+            .filter_map(|id| self.task_details(id, now, options))
+            .collect::<Vec<_>>();
+        if let Some(location) = location {
+            for (id, task) in self.tasks.all() {
+                let spawned_at = task
+                    .location
+                    .as_ref()
+                    .is_some_and(|loc| loc.matches(location));
+                if !spawned_at || ids.contains(id) {
+                    continue;
+                }
+                details.extend(self.task_details(id, now, options));
+            }
+        }
+
+        proto::tasks::TaskDetailsSet {
+            now: Some(self.base_time.to_timestamp(now)),
+            details,
+        }
+    }
+
+    /// Returns a snapshot of the tasks spawned from `location`, or of all
+    /// tasks if it is `None`, in order of their IDs.
+    ///
+    /// Completed tasks are only listed if `include_completed` is set. Any
+    /// tasks beyond `limit` are counted, but not listed.
+    pub(super) fn list_tasks(
+        &self,
+        location: Option<&proto::Location>,
+        include_completed: bool,
+        limit: Option<usize>,
+        now: Instant,
+    ) -> proto::instrument::ListTasksResponse {
+        let mut matching = self
+            .tasks
+            .all()
+            .filter(|(_, task)| {
+                location.map_or(true, |location| {
+                    task.location
+                        .as_ref()
+                        .is_some_and(|loc| loc.matches(location))
+                })
+            })
+            .filter_map(|(id, task)| Some((id, task, self.task_stats.get(id)?)))
+            .filter(|(_, _, stats)| include_completed || stats.dropped_at().is_none())
+            .collect::<Vec<_>>();
+        matching.sort_unstable_by_key(|(id, _, _)| id.into_u64());
+
+        let limit = limit.unwrap_or(usize::MAX);
+        let omitted_tasks = matching.len().saturating_sub(limit) as u64;
+        matching.truncate(limit);
+        proto::instrument::ListTasksResponse {
+            now: Some(self.base_time.to_timestamp(now)),
+            stats: matching
+                .iter()
+                .map(|(id, _, stats)| (id.into_u64(), stats.to_proto(&self.base_time)))
+                .collect(),
+            tasks: matching
+                .iter()
+                .map(|(_, task, _)| task.to_proto(&self.base_time))
+                .collect(),
+            metadata: Some(proto::RegisterMetadata {
+                metadata: (*self.all_metadata).clone(),
+            }),
+            omitted_tasks,
+        }
+    }
+
+    /// Returns the statistics for each group of tasks spawned from the same
+    /// location, merging the poll duration histograms of the tasks in each
+    /// group, and the delays before each of them was first polled.
+    pub(super) fn task_groups(&self, now: Instant) -> proto::tasks::TaskGroups {
+        let mut groups = HashMap::new();
+        for (id, task) in self.tasks.all() {
+            let (Some(location), Some(stats)) = (&task.location, self.task_stats.get(id)) else {
+                continue;
+            };
+            let key = (
+                location.file().cloned(),
+                location.line().unwrap_or_default(),
+                location.column().unwrap_or_default(),
+            );
+            let (_, poll_times, first_poll_delays) =
+                groups.entry(key).or_insert((location, None, None));
+            stats.merge_poll_durations(poll_times);
+            stats.merge_first_poll_delay(first_poll_delays);
+        }
+
+        proto::tasks::TaskGroups {
+            now: Some(self.base_time.to_timestamp(now)),
+            groups: groups
+                .into_values()
+                .map(
+                    |(location, poll_times, first_poll_delays)| proto::tasks::TaskGroup {
+                        location: Some(location.to_proto()),
+                        poll_times_histogram: poll_times.as_ref().map(stats::Histogram::to_proto),
+                        first_poll_delay_histogram: first_poll_delays
+                            .as_ref()
+                            .map(stats::Histogram::to_proto),
+                    },
+                )
+                .collect(),
+        }
+    }
+
+    /// Update the current state with data from a single event.
+    pub(super) fn update(&mut self, event: Event) {
+        match event {
+            Event::Metadata(meta) => {
+                self.all_metadata.push(meta.into());
+                self.new_metadata.push(meta.into());
+            }
+
+            Event::Spawn {
+                id,
+                metadata,
+                stats,
+                fields,
+                omitted_fields,
+                location,
+                scheduler,
+            } => {
+                self.tasks.insert(
+                    id.clone(),
+                    Task {
+                        id: id.clone(),
+                        is_dirty: AtomicBool::new(true),
+                        metadata,
+                        fields: fields
+                            .into_iter()
+                            .map(|field| self.strings.field(field))
+                            .collect(),
+                        omitted_fields,
+                        location: location.map(|location| self.strings.location(location)),
+                        scheduler,
+                        extensions: Vec::new(),
+                        extensions_dirty: false,
+                        // TODO: parents
+                    },
+                );
+
+                // a task which leaked wakers can't be told apart from the
+                // new task once its span ID is reused.
+                self.waker_leaks.remove(&id);
+                self.task_stats.insert(id, stats);
+                self.task_aggregates.spawn();
+            }
+
+            Event::Resource {
+                id,
+                parent_id,
+                metadata,
+                kind,
+                concrete_type,
+                location,
+                is_internal,
+                stats,
+            } => {
+                self.resources.insert(
+                    id.clone(),
+                    Resource {
+                        id: id.clone(),
+                        is_dirty: AtomicBool::new(true),
+                        parent_id,
+                        kind,
+                        metadata,
+                        concrete_type: self.strings.intern(&concrete_type),
+                        location: location.map(|location| self.strings.location(location)),
+                        is_internal,
+                    },
+                );
+
+                self.resource_stats.insert(id, stats);
+            }
+
+            Event::PollOp {
+                metadata,
+                resource_id,
+                op_name,
+                async_op_id,
+                task_id,
+                is_ready,
+            } => {
+                let poll_op = proto::resources::PollOp {
+                    metadata: Some(metadata.into()),
+                    resource_id: Some(resource_id.into()),
+                    name: op_name,
+                    task_id: Some(task_id.into()),
+                    async_op_id: Some(async_op_id.into()),
+                    is_ready,
+                };
+
+                self.poll_ops.push(poll_op);
+            }
+
+            Event::AsyncResourceOp {
+                id,
+                source,
+                resource_id,
+                metadata,
+                parent_id,
+                stats,
+            } => {
+                self.async_ops.insert(
+                    id.clone(),
+                    AsyncOp {
+                        id: id.clone(),
+                        is_dirty: AtomicBool::new(true),
+                        resource_id,
+                        metadata,
+                        source,
+                        parent_id,
+                    },
+                );
+
+                self.async_op_stats.insert(id, stats);
+            }
+
+            Event::WatchedSpan {
+                id,
+                metadata,
+                fields,
+                omitted_fields,
+                task_id,
+                stats,
+            } => {
+                self.spans.insert(
+                    id.clone(),
+                    WatchedSpan {
+                        id: id.clone(),
+                        is_dirty: AtomicBool::new(true),
+                        metadata,
+                        fields: fields
+                            .into_iter()
+                            .map(|field| self.strings.field(field))
+                            .collect(),
+                        omitted_fields,
+                        location: self.strings.location(metadata.into()),
+                        task_id,
+                    },
+                );
+
+                self.span_stats.insert(id, stats);
+            }
+
+            Event::SelectBranch {
+                location,
+                branch,
+                branches,
+                waited,
+            } => {
+                self.select_stats.record(location, branch, branches, waited);
+            }
+
+            Event::TaskExtension { id, extension } => {
+                if let Some(task) = self.tasks.get_mut(&id) {
+                    match task
+                        .extensions
+                        .iter_mut()
+                        .find(|existing| existing.type_url == extension.type_url)
+                    {
+                        Some(existing) => *existing = extension,
+                        None => task.extensions.push(extension),
+                    }
+                    task.extensions_dirty = true;
+                }
+            }
+
+            Event::TimerFired {
+                resource_id,
+                overrun,
+            } => {
+                let location = self
+                    .resources
+                    .get(&resource_id)
+                    .and_then(|resource| resource.location.as_ref());
+                if let Some(location) = location {
+                    self.timer_stats.record(location, overrun);
+                }
+            }
+        }
+    }
+}
+
+impl Task {
+    /// Returns the approximate number of bytes used by this task's static
+    /// data.
+    ///
+    /// Field names and values are interned, so only the fields themselves
+    /// are counted.
+    fn memory_size(&self) -> usize {
+        let extensions = self
+            .extensions
+            .iter()
+            .map(|ext| {
+                mem::size_of::<prost_types::Any>() + ext.type_url.capacity() + ext.value.capacity()
+            })
+            .sum::<usize>();
+        mem::size_of::<(Id, Self)>()
+            + self.fields.capacity() * mem::size_of::<intern::Field>()
+            + extensions
+    }
+
+    /// Returns what's reported about this task if its wakers outlive it,
+    /// given when it completed.
+    fn leaked(&self, dropped_at: Instant) -> LeakedTask {
+        let field = |name| self.fields.iter().find(|field| field.is_named(name));
+        LeakedTask {
+            id: self.id.into_u64(),
+            task_id: field("task.id").and_then(intern::Field::u64_value),
+            name: field("task.name")
+                .and_then(intern::Field::str_value)
+                .map(str::to_owned),
+            location: self.location.as_ref().map(intern::Location::to_proto),
+            dropped_at,
+        }
+    }
+
+    /// Returns whether the task runs a blocking closure rather than a future.
+    fn is_blocking(&self) -> bool {
+        self.fields
+            .iter()
+            .any(|field| field.is_named("kind") && field.str_value() == Some("blocking"))
+    }
+
+    /// Returns a warning about a problem the task has had for `duration`.
+    fn warning(&self, lint: Lint, duration: Duration) -> Warning {
+        let field = |name| self.fields.iter().find(|field| field.is_named(name));
+        Warning {
+            lint,
+            id: self.id.clone(),
+            task_id: field("task.id").and_then(intern::Field::u64_value),
+            name: field("task.name")
+                .and_then(intern::Field::str_value)
+                .map(str::to_owned),
+            location: self
+                .location
+                .as_ref()
+                .map(|location| location.to_proto().to_string()),
+            duration,
+        }
+    }
+}
+
+impl ToProto for Task {
+    type Output = proto::tasks::Task;
+
+    fn to_proto(&self, _: &stats::TimeAnchor) -> Self::Output {
+        proto::tasks::Task {
+            id: Some(self.id.clone().into()),
+            // TODO: more kinds of tasks...
+            kind: proto::tasks::task::Kind::Spawn as i32,
+            metadata: Some(self.metadata.into()),
+            parents: Vec::new(), // TODO: implement parents nicely
+            fields: self.fields.iter().map(intern::Field::to_proto).collect(),
+            location: self.location.as_ref().map(intern::Location::to_proto),
+            omitted_fields: self.omitted_fields,
+            scheduler: self.scheduler as i32,
+            extensions: self.extensions.clone(),
+            historical: false,
+            level: Some(proto::metadata::Level::from(*self.metadata.level()) as i32),
+        }
+    }
+}
+
+impl Unsent for Task {
+    fn take_unsent(&self) -> bool {
+        self.is_dirty.swap(false, AcqRel)
+    }
+
+    fn is_unsent(&self) -> bool {
+        self.is_dirty.load(Acquire)
+    }
+}
+
+impl ToProto for Resource {
+    type Output = proto::resources::Resource;
+
+    fn to_proto(&self, _: &stats::TimeAnchor) -> Self::Output {
+        proto::resources::Resource {
+            id: Some(self.id.clone().into()),
+            parent_resource_id: self.parent_id.clone().map(Into::into),
+            kind: Some(self.kind.clone()),
+            metadata: Some(self.metadata.into()),
+            concrete_type: self.concrete_type.to_string(),
+            location: self.location.as_ref().map(intern::Location::to_proto),
+            is_internal: self.is_internal,
+        }
+    }
+}
+
+impl Unsent for Resource {
+    fn take_unsent(&self) -> bool {
+        self.is_dirty.swap(false, AcqRel)
+    }
+
+    fn is_unsent(&self) -> bool {
+        self.is_dirty.load(Acquire)
+    }
+}
+
+impl ToProto for AsyncOp {
+    type Output = proto::async_ops::AsyncOp;
+
+    fn to_proto(&self, _: &stats::TimeAnchor) -> Self::Output {
+        proto::async_ops::AsyncOp {
+            id: Some(self.id.clone().into()),
+            metadata: Some(self.metadata.into()),
+            resource_id: Some(self.resource_id.clone().into()),
+            source: self.source.clone(),
+            parent_async_op_id: self.parent_id.clone().map(Into::into),
+        }
+    }
+}
+
+impl Unsent for AsyncOp {
+    fn take_unsent(&self) -> bool {
+        self.is_dirty.swap(false, AcqRel)
+    }
+
+    fn is_unsent(&self) -> bool {
+        self.is_dirty.load(Acquire)
+    }
+}
+
+impl ToProto for WatchedSpan {
+    type Output = proto::spans::Span;
+
+    fn to_proto(&self, _: &stats::TimeAnchor) -> Self::Output {
+        proto::spans::Span {
+            id: Some(self.id.clone().into()),
+            metadata: Some(self.metadata.into()),
+            fields: self.fields.iter().map(intern::Field::to_proto).collect(),
+            location: Some(self.location.to_proto()),
+            task_id: self.task_id.clone().map(Into::into),
+            omitted_fields: self.omitted_fields,
+        }
+    }
+}
+
+impl Unsent for WatchedSpan {
+    fn take_unsent(&self) -> bool {
+        self.is_dirty.swap(false, AcqRel)
+    }
+
+    fn is_unsent(&self) -> bool {
+        self.is_dirty.load(Acquire)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::StartupBuffer;
+    use super::*;
+    use tracing_core::{
+        callsite::{Callsite, Identifier},
+        field::FieldSet,
+        metadata::Kind,
+        subscriber::Interest,
+        Level,
+    };
+
+    struct TestCallsite;
+    static CALLSITE: TestCallsite = TestCallsite;
+    static METADATA: Metadata<'static> = Metadata::new(
+        "runtime.spawn",
+        "tokio::task",
+        Level::TRACE,
+        None,
+        None,
+        None,
+        FieldSet::new(&[], Identifier(&CALLSITE)),
+        Kind::SPAN,
+    );
+
+    impl Callsite for TestCallsite {
+        fn set_interest(&self, _: Interest) {}
+
+        fn metadata(&self) -> &Metadata<'_> {
+            &METADATA
+        }
+    }
+
+    const RETENTION: Duration = Duration::from_secs(60);
+    const MAX_RETENTION: Duration = Duration::from_secs(60 * 10);
+
+    fn state() -> AggregatorState {
+        AggregatorState::new(
+            Duration::from_secs(1),
+            RETENTION,
+            MAX_RETENTION,
+            0,
+            stats::TimeAnchor::new(),
+        )
+    }
+
+    fn spawn(state: &mut AggregatorState, id: u64, at: Instant) -> Arc<stats::TaskStats> {
+        let (event, stats) = spawn_event(id, at);
+        state.update(event);
+        stats
+    }
+
+    fn spawn_event(id: u64, at: Instant) -> (Event, Arc<stats::TaskStats>) {
+        let max = Duration::from_secs(1).as_nanos() as u64;
+        let stats = Arc::new(stats::TaskStats::new(
+            max,
+            max,
+            crate::ConsoleLayer::DEFAULT_HISTOGRAM_SIGNIFICANT_FIGURES,
+            at,
+            proto::tasks::task::Scheduler::Unknown,
+            None,
+            Some(Default::default()),
+        ));
+        let event = Event::Spawn {
+            id: Id::from_u64(id),
+            metadata: &METADATA,
+            stats: stats.clone(),
+            fields: Vec::new(),
+            omitted_fields: 0,
+            location: None,
+            scheduler: proto::tasks::task::Scheduler::Unknown,
+        };
+        (event, stats)
+    }
+
+    fn task_count(update: &proto::instrument::Update) -> usize {
+        update.task_update.as_ref().unwrap().new_tasks.len()
+    }
+
+    #[test]
+    fn new_tasks_are_published_once() {
+        let mut state = state();
+        let now = Instant::now();
+        spawn(&mut state, 1, now);
+
+        assert_eq!(task_count(&state.publish_update(now)), 1);
+        assert_eq!(task_count(&state.publish_update(now)), 0);
+        // New clients are still sent every task.
+        let initial = state.initial_update(now, true).unwrap();
+        assert_eq!(task_count(&initial), 1);
+    }
+
+    #[test]
+    fn changed_extensions_are_published() {
+        let mut state = state();
+        let now = Instant::now();
+        spawn(&mut state, 1, now);
+        let extension = |value: u8| Event::TaskExtension {
+            id: Id::from_u64(1),
+            extension: prost_types::Any {
+                type_url: "type.example.com/app.Load".to_string(),
+                value: vec![value],
+            },
+        };
+        let extensions = |update: &proto::instrument::Update| {
+            update
+                .task_update
+                .as_ref()
+                .unwrap()
+                .extensions_update
+                .clone()
+        };
+
+        // A new task is sent with its extensions.
+        state.update(extension(1));
+        let update = state.publish_update(now);
+        let task = &update.task_update.as_ref().unwrap().new_tasks[0];
+        assert_eq!(task.extensions[0].value, vec![1]);
+        assert!(extensions(&update).is_empty());
+
+        // Replacing an extension sends the task's extensions again.
+        state.update(extension(2));
+        let update = state.publish_update(now);
+        assert_eq!(task_count(&update), 0);
+        assert_eq!(extensions(&update)[&1].extensions[0].value, vec![2]);
+        assert!(extensions(&state.publish_update(now)).is_empty());
+    }
+
+    #[test]
+    fn completed_tasks_are_dropped_after_retention() {
+        let mut state = state();
+        let spawned_at = Instant::now();
+        let stats = spawn(&mut state, 1, spawned_at);
+        stats.drop_task(spawned_at);
+
+        state.cleanup_closed(spawned_at + RETENTION / 2, false);
+        assert_eq!(
+            task_count(&state.initial_update(spawned_at, false).unwrap()),
+            1
+        );
+
+        state.cleanup_closed(spawned_at + RETENTION * 2, false);
+        assert_eq!(
+            task_count(&state.initial_update(spawned_at, false).unwrap()),
+            0
+        );
+    }
+
+    #[test]
+    fn dropping_unsent_tasks_while_unwatched_is_recorded() {
+        let mut state = state();
+        let spawned_at = Instant::now();
+        let sent = spawn(&mut state, 1, spawned_at);
+        let unsent = spawn(&mut state, 2, spawned_at);
+        // A client is sent both tasks, and task 1's completion.
+        sent.drop_task(spawned_at);
+        state.publish_update(spawned_at);
+        state.cleanup_closed(spawned_at, true);
+        assert!(!state.take_dropped_unsent());
+
+        // Once the client disconnects, task 2 completes without it being
+        // sent, and both tasks are dropped once the retention has elapsed.
+        unsent.drop_task(spawned_at + RETENTION / 2);
+        state.cleanup_closed(spawned_at + RETENTION / 2, false);
+        assert!(!state.take_dropped_unsent());
+        state.cleanup_closed(spawned_at + RETENTION * 2, false);
+        assert!(state.take_dropped_unsent());
+        assert!(!state.take_dropped_unsent());
+
+        // When the client reconnects, it must be sent the full state, which
+        // no longer has the task it thinks is running.
+        let initial = state.initial_update(spawned_at + RETENTION * 2, false);
+        assert_eq!(task_count(&initial.unwrap()), 0);
+    }
+
+    #[test]
+    fn recently_completed_tasks_are_backfilled() {
+        let mut state = AggregatorState::new(
+            Duration::from_secs(1),
+            RETENTION,
+            MAX_RETENTION,
+            2,
+            stats::TimeAnchor::new(),
+        );
+        let spawned_at = Instant::now();
+        for id in 1..=3 {
+            spawn(&mut state, id, spawned_at).drop_task(spawned_at);
+        }
+        spawn(&mut state, 4, spawned_at);
+
+        state.cleanup_closed(spawned_at + RETENTION * 2, false);
+        // Only the last two tasks to be dropped are kept.
+        let initial = state.initial_update(spawned_at, false).unwrap();
+        let task_update = initial.task_update.unwrap();
+        let mut historical = task_update
+            .new_tasks
+            .iter()
+            .filter(|task| task.historical)
+            .map(|task| task.id.as_ref().unwrap().id)
+            .collect::<Vec<_>>();
+        historical.sort_unstable();
+        assert_eq!(task_update.new_tasks.len(), 3);
+        assert_eq!(historical.len(), 2);
+        assert!(historical
+            .iter()
+            .all(|id| task_update.stats_update[id].dropped_at.is_some()));
+
+        // A historical task isn't sent if its ID has been reused.
+        spawn(&mut state, historical[0], spawned_at);
+        let initial = state.initial_update(spawned_at, false).unwrap();
+        let new_tasks = initial.task_update.unwrap().new_tasks;
+        assert_eq!(new_tasks.len(), 3);
+        assert_eq!(new_tasks.iter().filter(|task| task.historical).count(), 1);
+    }
+
+    #[test]
+    fn leaked_wakers_outlive_the_retention() {
+        let mut state = state();
+        let spawned_at = Instant::now();
+        let leaky = spawn(&mut state, 1, spawned_at);
+        leaky.record_wake_op(crate::WakeOp::Clone, spawned_at);
+        leaky.record_wake_op(crate::WakeOp::Clone, spawned_at);
+        leaky.drop_task(spawned_at);
+        spawn(&mut state, 2, spawned_at).drop_task(spawned_at);
+        state.publish_update(spawned_at);
+
+        let later = spawned_at + RETENTION * 2;
+        state.cleanup_closed(later, false);
+        let leaks = |update: proto::instrument::Update| update.task_update.unwrap().waker_leaks;
+        let leaked = leaks(state.publish_update(later)).unwrap().leaks;
+        assert_eq!(leaked.len(), 1);
+        assert_eq!(leaked[0].id, Some(proto::Id { id: 1 }));
+        assert_eq!(leaked[0].waker_clones - leaked[0].waker_drops, 2);
+        // Leaks are only sent again when they change, except to new clients.
+        assert_eq!(leaks(state.publish_update(later)), None);
+        assert!(leaks(state.initial_update(later, false).unwrap()).is_some());
+
+        leaky
+            .waker_counts()
+            .record(crate::WakeOp::Wake { self_wake: false });
+        let leaked = leaks(state.publish_update(later)).unwrap().leaks;
+        assert_eq!(leaked[0].waker_clones - leaked[0].waker_drops, 1);
+
+        // Once every waker is dropped, the task is no longer leaking.
+        leaky.waker_counts().record(crate::WakeOp::Drop);
+        assert_eq!(leaks(state.publish_update(later)).unwrap().leaks, []);
+        assert_eq!(leaks(state.initial_update(later, false).unwrap()), None);
+    }
+
+    #[test]
+    fn memory_usage_counts_retained_tasks() {
+        let mut state = state();
+        let spawned_at = Instant::now();
+        spawn(&mut state, 1, spawned_at);
+        spawn(&mut state, 2, spawned_at).drop_task(spawned_at);
+
+        let usage = state.memory_usage().to_proto(2);
+        assert_eq!(usage.tasks, 2);
+        // each task records poll, scheduled, and wake interval histograms.
+        assert_eq!(usage.histograms, 6);
+        assert!(usage.histogram_bytes > usage.task_bytes);
+        assert_eq!(usage.histogram_significant_figures, 2);
+
+        state.cleanup_closed(spawned_at + RETENTION * 2, false);
+        let usage = state.memory_usage().to_proto(2);
+        assert_eq!(usage.tasks, 1);
+        assert_eq!(usage.histograms, 3);
+    }
+
+    #[test]
+    fn unhealthy_tasks_are_reported_once() {
+        let mut state = state();
+        let threads = stats::Threads::new();
+        let spawned_at = Instant::now();
+        let mut lints = Lints::new(spawned_at);
+        // The first task yields without registering its waker, the second is
+        // still in its first poll, and the third is waiting to be woken.
+        for id in 1..=3 {
+            let stats = spawn(&mut state, id, spawned_at);
+            stats.start_poll(spawned_at, &Id::from_u64(id), &threads);
+            if id == 3 {
+                stats.record_wake_op(crate::WakeOp::Clone, spawned_at);
+            }
+            if id != 2 {
+                stats.end_poll(spawned_at, &Id::from_u64(id), &threads);
+            }
+        }
+
+        let found = |report: Report| {
+            let mut found: Vec<_> = report
+                .warnings
+                .iter()
+                .map(|warning| (warning.lint, warning.id.into_u64()))
+                .collect();
+            found.sort_by_key(|&(_, id)| id);
+            found
+        };
+        // Neither problem is reported until it has lasted for a while.
+        let now = spawned_at + Duration::from_millis(10);
+        assert_eq!(found(state.lint_tasks(&mut lints, now)), []);
+        let later = spawned_at + Duration::from_secs(5);
+        assert_eq!(
+            found(state.lint_tasks(&mut lints, later)),
+            [(Lint::LostWaker, 1), (Lint::NeverYielded, 2)]
+        );
+        assert_eq!(found(state.lint_tasks(&mut lints, later)), []);
+    }
+
+    #[test]
+    fn lint_warnings_are_rate_limited() {
+        let mut state = state();
+        let threads = stats::Threads::new();
+        let spawned_at = Instant::now();
+        let mut lints = Lints::new(spawned_at);
+        for id in 1..=15 {
+            spawn(&mut state, id, spawned_at).start_poll(spawned_at, &Id::from_u64(id), &threads);
+        }
+
+        let later = spawned_at + Duration::from_secs(5);
+        let report = state.lint_tasks(&mut lints, later);
+        assert_eq!(report.warnings.len(), 10);
+        assert_eq!(report.suppressed, 0);
+
+        // The suppressed warnings are counted once the period ends.
+        let report = state.lint_tasks(&mut lints, later + Duration::from_secs(60));
+        assert_eq!(report.warnings.len(), 0);
+        assert_eq!(report.suppressed, 5);
+    }
+
+    #[test]
+    fn unsent_tasks_are_retained_while_watched() {
+        let mut state = state();
+        let spawned_at = Instant::now();
+        let stats = spawn(&mut state, 1, spawned_at);
+        stats.drop_task(spawned_at);
+
+        state.cleanup_closed(spawned_at + RETENTION * 2, true);
+        assert_eq!(task_count(&state.publish_update(spawned_at)), 1);
+
+        state.cleanup_closed(spawned_at + RETENTION * 2, true);
+        assert_eq!(
+            task_count(&state.initial_update(spawned_at, true).unwrap()),
+            0
+        );
+    }
+
+    #[test]
+    fn startup_events_are_buffered_until_the_aggregator_starts() {
+        let mut state = state();
+        let now = Instant::now();
+        let buffer = StartupBuffer::new(2);
+        assert!(buffer.push(Event::Metadata(&METADATA)));
+        assert!(buffer.push(spawn_event(1, now).0));
+        // Events are dropped once the buffer is full...
+        assert!(!buffer.push(spawn_event(2, now).0));
+        // ...and events which don't register anything aren't buffered.
+        let timer_fired = Event::TimerFired {
+            resource_id: Id::from_u64(3),
+            overrun: Duration::ZERO,
+        };
+        assert!(!StartupBuffer::new(2).push(timer_fired));
+
+        for event in buffer.take() {
+            state.update(event);
+        }
+        assert_eq!(task_count(&state.publish_update(now)), 1);
+
+        // Once the aggregator has started, nothing more is buffered.
+        assert!(!buffer.push(spawn_event(4, now).0));
+        assert!(buffer.take().is_empty());
+    }
+
+    #[test]
+    fn requested_retention_is_bounded() {
+        let mut state = state();
+        let spawned_at = Instant::now();
+        let stats = spawn(&mut state, 1, spawned_at);
+        stats.drop_task(spawned_at);
+
+        state.set_requested_retention(Some(RETENTION * 4));
+        state.cleanup_closed(spawned_at + RETENTION * 2, false);
+        assert_eq!(
+            task_count(&state.initial_update(spawned_at, false).unwrap()),
+            1
+        );
+
+        // Clients can't ask for more than the maximum retention.
+        state.set_requested_retention(Some(MAX_RETENTION * 2));
+        state.cleanup_closed(spawned_at + MAX_RETENTION + RETENTION, false);
+        assert_eq!(
+            task_count(&state.initial_update(spawned_at, false).unwrap()),
+            0
+        );
+    }
+
+    #[test]
+    fn requested_retention_ends_when_clients_leave() {
+        let mut state = state();
+        let spawned_at = Instant::now();
+        let stats = spawn(&mut state, 1, spawned_at);
+        stats.drop_task(spawned_at);
+
+        state.set_requested_retention(Some(RETENTION * 4));
+        state.set_requested_retention(None);
+        state.cleanup_closed(spawned_at + RETENTION * 2, false);
+        assert_eq!(
+            task_count(&state.initial_update(spawned_at, false).unwrap()),
+            0
+        );
+    }
+
+    #[test]
+    fn pause_freezes_time() {
+        let mut state = state();
+        let paused_at = Instant::now();
+        state.pause(paused_at, true);
+        assert!(state.is_paused());
+        assert_eq!(state.current_time(paused_at + RETENTION), paused_at);
+
+        state.resume(paused_at + Duration::from_secs(3));
+        let now = paused_at + RETENTION;
+        assert_eq!(state.current_time(now), now);
+        let last_pause = state.state().last_pause.unwrap();
+        assert_eq!(last_pause.seconds, 3);
+    }
+
+    #[test]
+    fn repeated_pauses_keep_time_frozen() {
+        let mut state = state();
+        let paused_at = Instant::now();
+        state.pause(paused_at, true);
+        state.pause(paused_at + Duration::from_secs(1), false);
+        assert_eq!(state.current_time(paused_at + RETENTION), paused_at);
+
+        // a pause which doesn't freeze time can be made to freeze it.
+        state.resume(paused_at + Duration::from_secs(2));
+        let paused_at = paused_at + Duration::from_secs(3);
+        state.pause(paused_at, false);
+        assert_eq!(
+            state.current_time(paused_at + RETENTION),
+            paused_at + RETENTION
+        );
+        state.pause(paused_at + Duration::from_secs(1), true);
+        assert_eq!(state.current_time(paused_at + RETENTION), paused_at);
+    }
+
+    #[test]
+    fn task_groups_merge_first_poll_delays() {
+        let mut state = state();
+        let threads = stats::Threads::new();
+        let spawned_at = Instant::now();
+        let delay = Duration::from_millis(10);
+        for id in 1..=2 {
+            let (mut event, stats) = spawn_event(id, spawned_at);
+            if let Event::Spawn { location, .. } = &mut event {
+                *location = Some(proto::Location {
+                    file: Some("src/main.rs".to_string()),
+                    line: Some(id as u32),
+                    column: Some(1),
+                    ..Default::default()
+                });
+            }
+            state.update(event);
+            // Only the first task is polled.
+            if id == 1 {
+                stats.start_poll(spawned_at + delay, &Id::from_u64(id), &threads);
+                stats.end_poll(spawned_at + delay, &Id::from_u64(id), &threads);
+            }
+        }
+
+        let groups = state.task_groups(spawned_at + delay).groups;
+        assert_eq!(groups.len(), 2);
+        let delays = |line| {
+            let group = groups
+                .iter()
+                .find(|group| group.location.as_ref().unwrap().line == Some(line))
+                .unwrap();
+            group.first_poll_delay_histogram.as_ref().map(|histogram| {
+                hdrhistogram::serialization::Deserializer::new()
+                    .deserialize::<u64, _>(&mut &histogram.raw_histogram[..])
+                    .unwrap()
+            })
+        };
+        let polled = delays(1).expect("the polled task's delay should be recorded");
+        assert_eq!(polled.len(), 1);
+        assert!(polled.equivalent(polled.max(), delay.as_nanos() as u64));
+        assert!(delays(2).is_none());
+    }
+}
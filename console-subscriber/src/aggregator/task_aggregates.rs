@@ -0,0 +1,218 @@
+use super::id_data::{IdData, Include};
+use crate::stats::{DroppedAt, TaskStats};
+use console_api as proto;
+use std::{
+    cmp,
+    collections::VecDeque,
+    sync::{
+        atomic::{AtomicU64, Ordering::*},
+        Arc,
+    },
+    time::Duration,
+};
+
+/// The number of activity samples retained for clients which connect later.
+const MAX_ACTIVITY_SAMPLES: usize = 120;
+
+/// Aggregate statistics for all tasks spawned over the lifetime of the
+/// process.
+///
+/// Unlike the per-task stats, these are kept regardless of the retention
+/// period, so that clients which connect (or reconnect) later still receive
+/// the full history.
+#[derive(Debug, Default)]
+pub(super) struct TaskAggregates {
+    /// The total number of tasks spawned.
+    spawned: u64,
+    /// The highest number of tasks which have been live at once, as counted
+    /// by [`ConcurrentTasks`].
+    max_concurrent: u64,
+    /// The total busy time of tasks whose stats have already been dropped.
+    retired_busy_time: Duration,
+    /// The total wakes of tasks whose stats have already been dropped.
+    retired_wakes: u64,
+    /// Per-interval activity samples, oldest first.
+    samples: VecDeque<Sample>,
+    /// The total number of samples ever recorded.
+    recorded_samples: u64,
+    /// The number of samples at the back of `samples` which have not yet been
+    /// published.
+    unsent_samples: usize,
+    /// The totals as of the most recently recorded sample.
+    last_totals: Totals,
+}
+
+/// The number of live tasks, counted by the layer as tasks are spawned and
+/// closed, and the most that have ever been live at once.
+///
+/// Counting these as they happen, rather than from the task stats whenever
+/// an update is built, means the high-water mark doesn't depend on when (or
+/// whether) clients were watching.
+#[derive(Debug, Default)]
+pub(crate) struct ConcurrentTasks {
+    live: AtomicU64,
+    max: AtomicU64,
+}
+
+/// Counts of task activity during a single sampling interval.
+#[derive(Debug, Default, Clone, Copy)]
+struct Sample {
+    spawned: u64,
+    completed: u64,
+    wakes: u64,
+}
+
+/// A snapshot of the lifetime totals for all tasks.
+#[derive(Debug, Default, Clone, Copy)]
+struct Totals {
+    spawned: u64,
+    completed: u64,
+    wakes: u64,
+    busy_time: Duration,
+}
+
+// === impl TaskAggregates ===
+
+impl TaskAggregates {
+    /// Records that a new task was spawned.
+    pub(super) fn spawn(&mut self) {
+        self.spawned += 1;
+    }
+
+    /// Records the highest number of tasks which have been live at once.
+    pub(super) fn record_max_concurrent(&mut self, concurrent: &ConcurrentTasks) {
+        self.max_concurrent = cmp::max(self.max_concurrent, concurrent.max());
+    }
+
+    /// Records the final stats of a task whose stats are being dropped.
+    pub(super) fn retire(&mut self, stats: &TaskStats) {
+        self.retired_busy_time += stats.busy_time();
+        self.retired_wakes += stats.wakes();
+    }
+
+    /// Returns the aggregate stats for all tasks spawned over the lifetime of
+    /// the process, including tasks which are no longer retained.
+    pub(super) fn aggregates(
+        &mut self,
+        task_stats: &IdData<Arc<TaskStats>>,
+    ) -> proto::tasks::Aggregates {
+        let totals = self.totals(task_stats);
+        proto::tasks::Aggregates {
+            total_spawned: totals.spawned,
+            total_completed: totals.completed,
+            max_concurrent: self.max_concurrent,
+            total_busy_time: totals.busy_time.try_into().ok(),
+        }
+    }
+
+    /// Records a sample of the task activity since the previous sample.
+    pub(super) fn record_sample(&mut self, task_stats: &IdData<Arc<TaskStats>>) {
+        let totals = self.totals(task_stats);
+        let last = std::mem::replace(&mut self.last_totals, totals);
+        if self.samples.len() == MAX_ACTIVITY_SAMPLES {
+            self.samples.pop_front();
+        }
+        self.samples.push_back(Sample {
+            spawned: totals.spawned.saturating_sub(last.spawned),
+            completed: totals.completed.saturating_sub(last.completed),
+            wakes: totals.wakes.saturating_sub(last.wakes),
+        });
+        self.recorded_samples += 1;
+        self.unsent_samples = cmp::min(self.unsent_samples + 1, self.samples.len());
+    }
+
+    /// Returns the activity samples to send to clients.
+    ///
+    /// If `include` is [`Include::All`], this returns every retained sample;
+    /// otherwise, it returns only those recorded since the last update.
+    pub(super) fn activity(
+        &mut self,
+        include: Include,
+        interval: Duration,
+    ) -> proto::tasks::ActivitySamples {
+        let skip = match include {
+            Include::All => 0,
+            Include::UpdatedOnly => {
+                let skip = self.samples.len() - self.unsent_samples;
+                self.unsent_samples = 0;
+                skip
+            }
+        };
+        let samples = self.samples.iter().skip(skip);
+        let first_sample = self.recorded_samples - (self.samples.len() - skip) as u64;
+        proto::tasks::ActivitySamples {
+            first_sample,
+            interval: interval.try_into().ok(),
+            spawned: samples.clone().map(|s| s.spawned).collect(),
+            completed: samples.clone().map(|s| s.completed).collect(),
+            wakes: samples.map(|s| s.wakes).collect(),
+        }
+    }
+
+    fn totals(&mut self, task_stats: &IdData<Arc<TaskStats>>) -> Totals {
+        let mut live = 0;
+        let mut busy_time = self.retired_busy_time;
+        let mut wakes = self.retired_wakes;
+        for (_, stats) in task_stats.all() {
+            if stats.dropped_at().is_none() {
+                live += 1;
+            }
+            busy_time += stats.busy_time();
+            wakes += stats.wakes();
+        }
+
+        // tasks whose stats were sent are counted by `ConcurrentTasks` too,
+        // but it may not have been recorded since they were spawned.
+        self.max_concurrent = cmp::max(self.max_concurrent, live);
+        Totals {
+            spawned: self.spawned,
+            // Only completed tasks are ever dropped from the task stats, so any
+            // task which was spawned and is no longer live has completed.
+            completed: self.spawned.saturating_sub(live),
+            wakes,
+            busy_time,
+        }
+    }
+}
+
+// === impl ConcurrentTasks ===
+
+impl ConcurrentTasks {
+    /// Records that a task was spawned.
+    pub(crate) fn spawned(&self) {
+        let live = self.live.fetch_add(1, AcqRel) + 1;
+        self.max.fetch_max(live, AcqRel);
+    }
+
+    /// Records that a task which was counted as spawned has closed.
+    pub(crate) fn closed(&self) {
+        self.live.fetch_sub(1, AcqRel);
+    }
+
+    fn max(&self) -> u64 {
+        self.max.load(Acquire)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn max_concurrent_counts_tasks_between_samples() {
+        let concurrent = ConcurrentTasks::default();
+        let mut aggregates = TaskAggregates::default();
+        for _ in 0..3 {
+            concurrent.spawned();
+        }
+        concurrent.closed();
+        concurrent.closed();
+        concurrent.spawned();
+
+        // no task stats were ever sampled while three tasks were live.
+        let task_stats = IdData::default();
+        aggregates.record_sample(&task_stats);
+        aggregates.record_max_concurrent(&concurrent);
+        assert_eq!(aggregates.aggregates(&task_stats).max_concurrent, 3);
+    }
+}
@@ -0,0 +1,68 @@
+use super::{id_data::Include, intern};
+use crate::stats::{Histogram, RecordDuration};
+use console_api as proto;
+use std::{collections::HashMap, sync::Arc, time::Duration};
+
+/// The maximum value for timer overrun histograms.
+///
+/// Timers which fire later than this after their deadline are counted as
+/// outliers.
+const MAX_OVERRUN_NANOS: u64 = 60_000_000_000;
+
+/// Deadline overrun statistics for the timers created at each callsite, keyed
+/// by the callsite's file, line, and column.
+#[derive(Debug, Default)]
+pub(super) struct TimerStats {
+    callsites: HashMap<(Option<Arc<str>>, u32, u32), Callsite>,
+}
+
+#[derive(Debug)]
+struct Callsite {
+    location: proto::Location,
+    fires: u64,
+    overruns: Histogram,
+    is_dirty: bool,
+}
+
+// === impl TimerStats ===
+
+impl TimerStats {
+    /// Records that a timer created at `location` fired `overrun` after its
+    /// deadline.
+    pub(super) fn record(&mut self, location: &intern::Location, overrun: Duration) {
+        let key = (
+            location.file().cloned(),
+            location.line().unwrap_or_default(),
+            location.column().unwrap_or_default(),
+        );
+        let callsite = self.callsites.entry(key).or_insert_with(|| Callsite {
+            location: location.to_proto(),
+            fires: 0,
+            overruns: Histogram::new(MAX_OVERRUN_NANOS),
+            is_dirty: true,
+        });
+        callsite.fires += 1;
+        callsite.overruns.record_duration(overrun);
+        callsite.is_dirty = true;
+    }
+
+    /// Returns the stats for every callsite if `include` is [`Include::All`],
+    /// or only those which changed since the last update otherwise.
+    pub(super) fn updates(&mut self, include: Include) -> Vec<proto::resources::TimerStats> {
+        self.callsites
+            .values_mut()
+            .filter_map(|callsite| {
+                if include == Include::UpdatedOnly
+                    && !std::mem::replace(&mut callsite.is_dirty, false)
+                {
+                    return None;
+                }
+                Some(proto::resources::TimerStats {
+                    location: Some(callsite.location.clone()),
+                    fires: callsite.fires,
+                    overrun_histogram: Some(callsite.overruns.to_proto()),
+                })
+            })
+            .collect()
+    }
+}
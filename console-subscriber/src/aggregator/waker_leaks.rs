@@ -0,0 +1,184 @@
+use super::id_data::Include;
+use crate::{
+    stats::{TimeAnchor, Unsent, WakerCounts},
+    sync::Mutex,
+    WakeOp,
+};
+use console_api as proto;
+use std::{
+    collections::{HashMap, VecDeque},
+    sync::{
+        atomic::{AtomicUsize, Ordering::*},
+        Arc,
+    },
+    time::Instant,
+};
+use tracing_core::span::Id;
+
+/// The most completed tasks whose wakers are counted after they complete.
+///
+/// Wakers which outlive their task are unusual, so this is only reached if
+/// the application leaks wakers at a high rate, in which case the tasks
+/// which are already counted show the leak well enough.
+const MAX_LEAKED_TASKS: usize = 1024;
+
+/// The waker counts of completed tasks which had wakers alive when they
+/// completed, by their span IDs.
+///
+/// Once a task's span has closed, the layer can no longer find its stats
+/// from the span, so the waker operations of such tasks are counted here
+/// until all of their wakers have been dropped.
+#[derive(Debug, Default)]
+pub(crate) struct LingeringWakers {
+    tasks: Mutex<HashMap<u64, Arc<WakerCounts>>>,
+    /// The number of tasks in `tasks`, so that the lock isn't taken when a
+    /// task is spawned if there are none.
+    len: AtomicUsize,
+}
+
+/// The completed tasks which the aggregator no longer retains, but which
+/// still had wakers alive when they were dropped.
+///
+/// Only what's needed to report the leak is kept. A task is forgotten once
+/// all of its wakers have been dropped, or its span ID is reused.
+#[derive(Debug, Default)]
+pub(super) struct WakerLeaks {
+    leaks: VecDeque<Leak>,
+    /// Whether tasks were added or removed since the last update.
+    is_dirty: bool,
+}
+
+#[derive(Debug)]
+struct Leak {
+    task: LeakedTask,
+    counts: Arc<WakerCounts>,
+}
+
+/// What's known about a completed task whose wakers are leaking.
+#[derive(Debug)]
+pub(super) struct LeakedTask {
+    pub(super) id: u64,
+    pub(super) task_id: Option<u64>,
+    pub(super) name: Option<String>,
+    pub(super) location: Option<proto::Location>,
+    pub(super) dropped_at: Instant,
+}
+
+// === impl LingeringWakers ===
+
+impl LingeringWakers {
+    /// Starts counting the waker operations of the task with the span `id`,
+    /// which has just completed, if any of its wakers are still alive.
+    pub(crate) fn task_closed(&self, id: &Id, counts: &Arc<WakerCounts>) {
+        if counts.live() <= 0 {
+            return;
+        }
+        let mut tasks = self.tasks.lock();
+        if tasks.len() >= MAX_LEAKED_TASKS {
+            tracing::trace!(id = id.into_u64(), "too many tasks with lingering wakers");
+            return;
+        }
+        tasks.insert(id.into_u64(), counts.clone());
+        self.len.store(tasks.len(), Release);
+    }
+
+    /// Records a waker operation on the completed task with the span `id`,
+    /// if its wakers are being counted.
+    pub(crate) fn record(&self, id: &Id, op: WakeOp) {
+        if self.len.load(Acquire) == 0 {
+            return;
+        }
+        let mut tasks = self.tasks.lock();
+        let Some(counts) = tasks.get(&id.into_u64()) else {
+            return;
+        };
+        counts.record(op);
+        if counts.live() <= 0 {
+            tasks.remove(&id.into_u64());
+            self.len.store(tasks.len(), Release);
+        }
+    }
+
+    /// Stops counting the wakers of the completed task with the span `id`,
+    /// since a new task has been spawned with the same span ID.
+    pub(crate) fn task_spawned(&self, id: &Id) {
+        if self.len.load(Acquire) == 0 {
+            return;
+        }
+        let mut tasks = self.tasks.lock();
+        tasks.remove(&id.into_u64());
+        self.len.store(tasks.len(), Release);
+    }
+}
+
+// === impl WakerLeaks ===
+
+impl WakerLeaks {
+    /// Keeps a completed task which is being dropped, if any of its wakers
+    /// are still alive, forgetting the oldest task kept if there are already
+    /// too many.
+    pub(super) fn push(&mut self, task: LeakedTask, counts: &Arc<WakerCounts>) {
+        if counts.live() <= 0 {
+            return;
+        }
+        if self.leaks.len() == MAX_LEAKED_TASKS {
+            self.leaks.pop_front();
+        }
+        self.leaks.push_back(Leak {
+            task,
+            counts: counts.clone(),
+        });
+        self.is_dirty = true;
+    }
+
+    /// Forgets the task with the span `id`, since a new task has been spawned
+    /// with the same span ID.
+    pub(super) fn remove(&mut self, id: &Id) {
+        let len = self.leaks.len();
+        self.leaks.retain(|leak| leak.task.id != id.into_u64());
+        self.is_dirty |= self.leaks.len() != len;
+    }
+
+    /// Returns every task whose wakers are still alive if `include` is
+    /// [`Include::All`], or if any task was added, removed, or had its waker
+    /// counts change since the last update, and `None` otherwise.
+    ///
+    /// Tasks whose wakers have all been dropped are forgotten.
+    pub(super) fn update(
+        &mut self,
+        include: Include,
+        base_time: &TimeAnchor,
+    ) -> Option<proto::tasks::WakerLeaks> {
+        match include {
+            Include::All if self.leaks.is_empty() => return None,
+            Include::All => {}
+            Include::UpdatedOnly => {
+                let len = self.leaks.len();
+                self.leaks.retain(|leak| leak.counts.live() > 0);
+                let mut changed = std::mem::take(&mut self.is_dirty) || self.leaks.len() != len;
+                for leak in &self.leaks {
+                    changed |= leak.counts.take_unsent();
+                }
+                if !changed {
+                    return None;
+                }
+            }
+        }
+
+        let leaks = self
+            .leaks
+            .iter()
+            .filter(|leak| leak.counts.live() > 0)
+            .map(|leak| proto::tasks::WakerLeak {
+                id: Some(leak.task.id.into()),
+                task_id: leak.task.task_id,
+                name: leak.task.name.clone(),
+                location: leak.task.location.clone(),
+                dropped_at: Some(base_time.to_timestamp(leak.task.dropped_at)),
+                waker_clones: leak.counts.clones(),
+                waker_drops: leak.counts.drops(),
+            })
+            .collect();
+        Some(proto::tasks::WakerLeaks { leaks })
+    }
+}
@@ -0,0 +1,73 @@
+#[cfg(all(tokio_unstable, target_has_atomic = "64"))]
+use crate::stats::ThreadStats;
+use crate::stats::Threads;
+use console_api as proto;
+
+/// Reports the threads which poll tasks, along with the runtime's metrics for
+/// those which are its workers.
+#[derive(Debug, Default)]
+pub(super) struct Workers {
+    /// The metrics of the runtime whose workers are reported, if the
+    /// instrumented application provided them.
+    ///
+    /// The per-worker metrics are only available with 64-bit atomics.
+    #[cfg(all(tokio_unstable, target_has_atomic = "64"))]
+    metrics: Option<tokio::runtime::RuntimeMetrics>,
+}
+
+// === impl Workers ===
+
+impl Workers {
+    pub(super) fn new(_builder: &crate::Builder) -> Self {
+        Self {
+            #[cfg(all(tokio_unstable, target_has_atomic = "64"))]
+            metrics: _builder.runtime_metrics.clone(),
+        }
+    }
+
+    /// Returns the current stats of every one of `threads` which has polled a
+    /// task and is still running.
+    ///
+    /// The stats are cheap to collect and change on almost every poll, so
+    /// every thread is included in each update.
+    pub(super) fn update(&self, threads: &Threads) -> proto::runtime::RuntimeUpdate {
+        let workers = threads
+            .all()
+            .iter()
+            .map(|thread| {
+                #[allow(unused_mut)]
+                let mut worker = thread.to_proto();
+                #[cfg(all(tokio_unstable, target_has_atomic = "64"))]
+                self.record_metrics(thread, &mut worker);
+                worker
+            })
+            .collect();
+        proto::runtime::RuntimeUpdate { workers }
+    }
+
+    /// Adds the runtime's metrics for `thread` to `worker`, if the thread is
+    /// one of the runtime's workers.
+    #[cfg(all(tokio_unstable, target_has_atomic = "64"))]
+    fn record_metrics(&self, thread: &ThreadStats, worker: &mut proto::runtime::Worker) {
+        let Some(metrics) = &self.metrics else {
+            return;
+        };
+        let Some(index) = (0..metrics.num_workers())
+            .find(|&index| metrics.worker_thread_id(index) == Some(thread.std_id()))
+        else {
+            return;
+        };
+
+        let parks = metrics.worker_park_count(index);
+        worker.worker_index = Some(index as u64);
+        worker.steals = Some(metrics.worker_steal_count(index));
+        worker.parks = Some(parks);
+        // The runtime counts parks and unparks together, so this is one less
+        // than the parks while the worker is parked.
+        worker.unparks = Some(
+            metrics
+                .worker_park_unpark_count(index)
+                .saturating_sub(parks),
+        );
+    }
+}
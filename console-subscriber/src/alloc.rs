@@ -0,0 +1,215 @@
+//! Attributing allocations to the tasks which make them.
+//!
+//! The counts are kept per thread, and are only updated by the
+//! [`TrackingAllocator`], which must be installed as the application's global
+//! allocator. A task's stats snapshot the current thread's counts when the
+//! task starts being polled and add the difference when the poll ends.
+use std::cell::Cell;
+use std::sync::atomic::{AtomicBool, Ordering::*};
+
+use console_api as proto;
+
+/// Set once the [`TrackingAllocator`] has made an allocation, so that tasks
+/// don't report allocation stats when nothing is tracking them.
+static IS_TRACKING: AtomicBool = AtomicBool::new(false);
+
+std::thread_local! {
+    /// The allocations made on the current thread.
+    ///
+    /// This doesn't need dropping and is initialized without allocating, so
+    /// it can be used from within the allocator.
+    static CURRENT_THREAD: Cell<AllocCounts> = const { Cell::new(AllocCounts::ZERO) };
+}
+
+/// Counts of allocations and deallocations.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+struct AllocCounts {
+    allocations: u64,
+    deallocations: u64,
+    allocated_bytes: u64,
+    deallocated_bytes: u64,
+}
+
+/// The allocations made while a task was being polled.
+#[derive(Debug, Default)]
+pub(crate) struct TaskAllocs {
+    totals: AllocCounts,
+    /// The current thread's counts when the task's current poll started.
+    poll_started: Option<AllocCounts>,
+}
+
+/// A global allocator which counts the allocations made by each task.
+///
+/// This wraps another allocator, [`System`] by default, and counts the
+/// allocations and deallocations made through it on each thread. A
+/// [`ConsoleLayer`] attributes the ones made while a task is being polled to
+/// that task, and reports them in the task's stats, which the console shows
+/// in the tasks list.
+///
+/// Counting an allocation only updates a thread-local counter, but this is
+/// still a cost paid by every allocation in the program, so this is only
+/// available with the `alloc-tracking` feature.
+///
+/// # Examples
+///
+/// ```
+/// use console_subscriber::TrackingAllocator;
+/// use std::alloc::System;
+///
+/// #[global_allocator]
+/// static ALLOCATOR: TrackingAllocator = TrackingAllocator::new(System);
+/// ```
+///
+/// # Notes
+///
+/// A reallocation is counted as a deallocation of the old allocation and an
+/// allocation of the new one. If a task is polled from within the poll of
+/// another task on the same thread, such as by `block_on`, the allocations
+/// made while polling the inner task also count towards the outer one.
+///
+/// [`System`]: std::alloc::System
+/// [`ConsoleLayer`]: crate::ConsoleLayer
+#[cfg(feature = "alloc-tracking")]
+#[derive(Debug, Default)]
+pub struct TrackingAllocator<A = std::alloc::System> {
+    inner: A,
+}
+
+// === impl TrackingAllocator ===
+
+#[cfg(feature = "alloc-tracking")]
+impl<A> TrackingAllocator<A> {
+    /// Returns a `TrackingAllocator` which allocates with `inner`.
+    pub const fn new(inner: A) -> Self {
+        Self { inner }
+    }
+}
+
+// Safety: all allocation is delegated to the inner allocator. Recording the
+// allocation neither allocates nor panics.
+#[cfg(feature = "alloc-tracking")]
+unsafe impl<A: std::alloc::GlobalAlloc> std::alloc::GlobalAlloc for TrackingAllocator<A> {
+    unsafe fn alloc(&self, layout: std::alloc::Layout) -> *mut u8 {
+        let ptr = self.inner.alloc(layout);
+        if !ptr.is_null() {
+            record(|counts| counts.record_alloc(layout.size()));
+        }
+        ptr
+    }
+
+    unsafe fn alloc_zeroed(&self, layout: std::alloc::Layout) -> *mut u8 {
+        let ptr = self.inner.alloc_zeroed(layout);
+        if !ptr.is_null() {
+            record(|counts| counts.record_alloc(layout.size()));
+        }
+        ptr
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: std::alloc::Layout) {
+        self.inner.dealloc(ptr, layout);
+        record(|counts| counts.record_dealloc(layout.size()));
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: std::alloc::Layout, new_size: usize) -> *mut u8 {
+        let new_ptr = self.inner.realloc(ptr, layout, new_size);
+        if !new_ptr.is_null() {
+            record(|counts| {
+                counts.record_dealloc(layout.size());
+                counts.record_alloc(new_size);
+            });
+        }
+        new_ptr
+    }
+}
+
+/// Updates the current thread's counts.
+#[cfg(feature = "alloc-tracking")]
+fn record(f: impl FnOnce(&mut AllocCounts)) {
+    // The thread-local is never destroyed, so this only fails if the
+    // platform can't provide it at all.
+    let _ = CURRENT_THREAD.try_with(|current| {
+        let mut counts = current.get();
+        f(&mut counts);
+        current.set(counts);
+    });
+    if !IS_TRACKING.load(Relaxed) {
+        IS_TRACKING.store(true, Relaxed);
+    }
+}
+
+/// Returns the allocations made on the current thread so far, or `None` if
+/// allocations aren't being tracked.
+fn current() -> Option<AllocCounts> {
+    if !IS_TRACKING.load(Relaxed) {
+        return None;
+    }
+    CURRENT_THREAD.try_with(Cell::get).ok()
+}
+
+// === impl AllocCounts ===
+
+impl AllocCounts {
+    const ZERO: Self = Self {
+        allocations: 0,
+        deallocations: 0,
+        allocated_bytes: 0,
+        deallocated_bytes: 0,
+    };
+
+    #[cfg(feature = "alloc-tracking")]
+    fn record_alloc(&mut self, size: usize) {
+        self.allocations = self.allocations.wrapping_add(1);
+        self.allocated_bytes = self.allocated_bytes.wrapping_add(size as u64);
+    }
+
+    #[cfg(feature = "alloc-tracking")]
+    fn record_dealloc(&mut self, size: usize) {
+        self.deallocations = self.deallocations.wrapping_add(1);
+        self.deallocated_bytes = self.deallocated_bytes.wrapping_add(size as u64);
+    }
+
+    /// Adds the counts made between `start` and `end` to `self`.
+    fn add_since(&mut self, start: &Self, end: &Self) {
+        self.allocations += end.allocations.wrapping_sub(start.allocations);
+        self.deallocations += end.deallocations.wrapping_sub(start.deallocations);
+        self.allocated_bytes += end.allocated_bytes.wrapping_sub(start.allocated_bytes);
+        self.deallocated_bytes += end.deallocated_bytes.wrapping_sub(start.deallocated_bytes);
+    }
+}
+
+// === impl TaskAllocs ===
+
+impl TaskAllocs {
+    /// Records that a poll of the task started on the current thread.
+    pub(crate) fn start_poll(&mut self) {
+        self.poll_started = current();
+    }
+
+    /// Records that a poll of the task ended on the current thread, adding
+    /// the allocations made during it to the task's totals.
+    pub(crate) fn end_poll(&mut self) {
+        if let (Some(start), Some(end)) = (self.poll_started.take(), current()) {
+            self.totals.add_since(&start, &end);
+        }
+    }
+
+    /// Returns the task's allocation stats, or `None` if allocations aren't
+    /// being tracked.
+    pub(crate) fn to_proto(&self) -> Option<proto::tasks::AllocStats> {
+        if !IS_TRACKING.load(Relaxed) {
+            return None;
+        }
+        let AllocCounts {
+            allocations,
+            deallocations,
+            allocated_bytes,
+            deallocated_bytes,
+        } = self.totals;
+        Some(proto::tasks::AllocStats {
+            allocations,
+            deallocations,
+            allocated_bytes,
+            deallocated_bytes,
+        })
+    }
+}
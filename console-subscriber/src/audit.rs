@@ -0,0 +1,105 @@
+use console_api as proto;
+use std::{collections::VecDeque, net::SocketAddr, time::SystemTime};
+
+/// The most recent requests clients have made to the instrument server, so
+/// that operators can tell who has been using the console in a shared
+/// environment.
+///
+/// Once the log is full, the oldest entry is dropped for each new one.
+#[derive(Debug)]
+pub(crate) struct AuditLog {
+    entries: VecDeque<Entry>,
+    capacity: usize,
+    dropped_entries: u64,
+}
+
+#[derive(Debug)]
+struct Entry {
+    received_at: SystemTime,
+    client_addr: Option<SocketAddr>,
+    rpc: &'static str,
+}
+
+// === impl AuditLog ===
+
+impl AuditLog {
+    /// The number of requests kept in the log.
+    pub(crate) const CAPACITY: usize = 256;
+
+    pub(crate) fn new(capacity: usize) -> Self {
+        Self {
+            entries: VecDeque::with_capacity(capacity),
+            capacity,
+            dropped_entries: 0,
+        }
+    }
+
+    /// Logs a call to `rpc` by the client at `client_addr`, if it's known.
+    pub(crate) fn record(&mut self, client_addr: Option<SocketAddr>, rpc: &'static str) {
+        match client_addr {
+            Some(addr) => tracing::info!(client.addr = %addr, rpc, "console client request"),
+            None => tracing::info!(client.addr = %"<unknown>", rpc, "console client request"),
+        }
+        if self.capacity == 0 {
+            self.dropped_entries += 1;
+            return;
+        }
+        if self.entries.len() == self.capacity {
+            self.entries.pop_front();
+            self.dropped_entries += 1;
+        }
+        self.entries.push_back(Entry {
+            received_at: SystemTime::now(),
+            client_addr,
+            rpc,
+        });
+    }
+
+    pub(crate) fn to_proto(&self) -> proto::instrument::AuditLogResponse {
+        proto::instrument::AuditLogResponse {
+            entries: self
+                .entries
+                .iter()
+                .map(|entry| proto::instrument::AuditEntry {
+                    received_at: Some(entry.received_at.into()),
+                    client_addr: entry
+                        .client_addr
+                        .map(|addr| addr.to_string())
+                        .unwrap_or_default(),
+                    rpc: entry.rpc.to_owned(),
+                })
+                .collect(),
+            dropped_entries: self.dropped_entries,
+        }
+    }
+}
+
+impl Default for AuditLog {
+    fn default() -> Self {
+        Self::new(Self::CAPACITY)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn drops_the_oldest_entries_when_full() {
+        let mut log = AuditLog::new(2);
+        log.record(None, "WatchUpdates");
+        log.record(None, "Pause");
+        log.record(Some(([127, 0, 0, 1], 4242).into()), "Resume");
+
+        let log = log.to_proto();
+        let rpcs = log
+            .entries
+            .iter()
+            .map(|e| e.rpc.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(rpcs, ["Pause", "Resume"]);
+        assert_eq!(log.entries[0].client_addr, "");
+        assert_eq!(log.entries[1].client_addr, "127.0.0.1:4242");
+        assert_eq!(log.dropped_entries, 1);
+    }
+}
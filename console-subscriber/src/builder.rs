@@ -1,4 +1,7 @@
-use super::{ConsoleLayer, Server};
+use super::{
+    histograms::HistogramFilter, visitors::RedactFields, ConsoleFilter, ConsoleLayer,
+    FilteredConsoleLayer, Server, SpawnedTask,
+};
 #[cfg(unix)]
 use std::path::Path;
 use std::{
@@ -10,7 +13,7 @@ use std::{
 use tokio::runtime;
 use tracing::Subscriber;
 use tracing_subscriber::{
-    filter::{self, FilterFn},
+    filter,
     layer::{Layer, SubscriberExt},
     prelude::*,
     registry::LookupSpan,
@@ -23,19 +26,56 @@ pub struct Builder {
     /// the aggregator task.
     pub(super) event_buffer_capacity: usize,
 
+    /// The number of events in the channel from the subscriber to the
+    /// aggregator task at which the aggregator is woken to drain it, if not
+    /// the default of half its capacity.
+    pub(super) event_buffer_high_watermark: Option<usize>,
+
     /// The maximum number of updates to buffer per-client before the client is
     /// dropped.
     pub(super) client_buffer_capacity: usize,
 
+    /// The maximum number of events to buffer while the event channel is
+    /// full, before the aggregator starts draining it.
+    pub(super) startup_buffer_capacity: usize,
+
     /// The interval between publishing updates to clients.
     pub(crate) publish_interval: Duration,
 
+    /// The most tasks which an update may include before updates are
+    /// published less often, if the publish interval adapts to load.
+    pub(crate) max_updated_tasks: Option<usize>,
+
     /// How long to retain data for completed events.
     pub(crate) retention: Duration,
 
+    /// The longest retention a client may request while it is attached.
+    pub(crate) max_retention: Duration,
+
+    /// How many of the most recently completed tasks to keep after their
+    /// retention has elapsed, to send to new clients.
+    pub(crate) keep_completed_tasks: usize,
+
+    /// The maximum number of clients which may watch updates at once.
+    pub(crate) max_watchers: usize,
+
+    /// The most bytes of updates sent to each client per second, if this is
+    /// limited.
+    pub(crate) max_watcher_bandwidth: Option<u64>,
+
+    /// Whether clients are refused from pausing and resuming the aggregator.
+    pub(crate) read_only: bool,
+
+    /// Whether warnings about unhealthy tasks are logged in the application.
+    pub(crate) emit_warnings: bool,
+
     /// The address on which to serve the RPC server.
     pub(super) server_addr: ServerAddr,
 
+    /// Whether to serve the RPC server on a socket inherited from systemd.
+    #[cfg(unix)]
+    pub(super) socket_activation: bool,
+
     /// If and where to save a recording of the events.
     pub(super) recording_path: Option<PathBuf>,
 
@@ -57,26 +97,74 @@ pub struct Builder {
     /// value. Higher values will result in more memory usage.
     pub(super) scheduled_duration_max: Duration,
 
+    /// The precision of the task poll and scheduled duration histograms, in
+    /// significant figures.
+    pub(super) histogram_significant_figures: u8,
+
+    /// The most verbose level of task spans which are recorded as tasks.
+    pub(super) task_level: filter::LevelFilter,
+
+    /// The maximum number of fields recorded for each task.
+    pub(super) max_fields_per_task: usize,
+
+    /// The maximum length of the string values of task fields.
+    pub(super) max_field_value_len: usize,
+
+    /// Replaces the values of sensitive task and span fields, if set.
+    pub(super) redact_fields: Option<RedactFields>,
+
+    /// Chooses which tasks record poll and scheduled duration histograms,
+    /// if set.
+    pub(super) histogram_filter: Option<HistogramFilter>,
+
+    /// The maximum number of tasks which record histograms at once, if
+    /// limited.
+    pub(super) max_histogram_tasks: Option<usize>,
+
     /// Whether to enable the grpc-web support.
     #[cfg(feature = "grpc-web")]
     enable_grpc_web: bool,
+
+    /// The metrics of the runtime whose workers are reported to clients.
+    #[cfg(tokio_unstable)]
+    pub(crate) runtime_metrics: Option<tokio::runtime::RuntimeMetrics>,
 }
 
 impl Default for Builder {
     fn default() -> Self {
         Self {
             event_buffer_capacity: ConsoleLayer::DEFAULT_EVENT_BUFFER_CAPACITY,
+            event_buffer_high_watermark: None,
             client_buffer_capacity: ConsoleLayer::DEFAULT_CLIENT_BUFFER_CAPACITY,
+            startup_buffer_capacity: ConsoleLayer::DEFAULT_STARTUP_BUFFER_CAPACITY,
             publish_interval: ConsoleLayer::DEFAULT_PUBLISH_INTERVAL,
+            max_updated_tasks: None,
             retention: ConsoleLayer::DEFAULT_RETENTION,
+            max_retention: ConsoleLayer::DEFAULT_MAX_RETENTION,
+            keep_completed_tasks: 0,
+            max_watchers: ConsoleLayer::DEFAULT_MAX_WATCHERS,
+            max_watcher_bandwidth: None,
+            read_only: false,
+            emit_warnings: false,
             poll_duration_max: ConsoleLayer::DEFAULT_POLL_DURATION_MAX,
             scheduled_duration_max: ConsoleLayer::DEFAULT_SCHEDULED_DURATION_MAX,
+            histogram_significant_figures: ConsoleLayer::DEFAULT_HISTOGRAM_SIGNIFICANT_FIGURES,
+            task_level: filter::LevelFilter::TRACE,
+            max_fields_per_task: ConsoleLayer::DEFAULT_MAX_FIELDS_PER_TASK,
+            max_field_value_len: ConsoleLayer::DEFAULT_MAX_FIELD_VALUE_LEN,
+            redact_fields: None,
+            histogram_filter: None,
+            max_histogram_tasks: None,
             server_addr: ServerAddr::Tcp(SocketAddr::new(Server::DEFAULT_IP, Server::DEFAULT_PORT)),
+            #[cfg(unix)]
+            socket_activation: false,
             recording_path: None,
             filter_env_var: "RUST_LOG".to_string(),
             self_trace: false,
             #[cfg(feature = "grpc-web")]
             enable_grpc_web: false,
+            #[cfg(tokio_unstable)]
+            runtime_metrics: None,
         }
     }
 }
@@ -95,6 +183,28 @@ impl Builder {
         }
     }
 
+    /// Sets how many events may be waiting in the channel of events sent from
+    /// subscriber layers to the aggregator task before the aggregator is woken
+    /// to drain it.
+    ///
+    /// The aggregator otherwise only drains the channel when it publishes an
+    /// update, so a burst of events, such as from spawning many tasks at once,
+    /// could fill the channel in between. A lower watermark makes that less
+    /// likely, at the cost of waking the aggregator more often. If the
+    /// channel reaches the watermark in several publish intervals in a row,
+    /// the aggregator also publishes more often until the burst passes.
+    ///
+    /// The watermark is capped at the [event buffer capacity]. By default, it
+    /// is half of that capacity.
+    ///
+    /// [event buffer capacity]: Builder::event_buffer_capacity
+    pub fn event_buffer_high_watermark(self, event_buffer_high_watermark: usize) -> Self {
+        Self {
+            event_buffer_high_watermark: Some(event_buffer_high_watermark),
+            ..self
+        }
+    }
+
     /// Sets the maximum capacity of updates to buffer for each subscribed
     /// client, if that client is not reading from the RPC stream.
     ///
@@ -108,6 +218,23 @@ impl Builder {
         }
     }
 
+    /// Sets how many events to hold on to when the event channel fills up
+    /// before the aggregator task has started draining it.
+    ///
+    /// Applications can spawn many tasks before the runtime first polls the
+    /// aggregator task. Rather than dropping the events which register those
+    /// tasks (and the resources and async ops created alongside them), up to
+    /// this many of them are buffered, and handed to the aggregator once it
+    /// starts. After that, events which don't fit in the channel are dropped.
+    ///
+    /// By default, this is [`ConsoleLayer::DEFAULT_STARTUP_BUFFER_CAPACITY`].
+    pub fn startup_buffer_capacity(self, startup_buffer_capacity: usize) -> Self {
+        Self {
+            startup_buffer_capacity,
+            ..self
+        }
+    }
+
     /// Sets how frequently updates are published to clients.
     ///
     /// A shorter duration will allow clients to update more frequently, but may
@@ -126,6 +253,29 @@ impl Builder {
         }
     }
 
+    /// Publishes updates less often while they include the stats of more than
+    /// `max_updated_tasks` tasks.
+    ///
+    /// Each update includes the stats of every task which changed since the
+    /// previous one, so an application with many busy tasks can produce
+    /// large updates every [publish interval]. With this set, each time an
+    /// update includes more than `max_updated_tasks` tasks, the time until
+    /// the next update is doubled, up to eight publish intervals. Each time
+    /// an update includes no more than half that many, it is halved again,
+    /// down to the configured publish interval. Task activity is still
+    /// sampled once per publish interval.
+    ///
+    /// By default, updates are published once per publish interval,
+    /// regardless of their size.
+    ///
+    /// [publish interval]: Builder::publish_interval
+    pub fn adaptive_publish_interval(self, max_updated_tasks: usize) -> Self {
+        Self {
+            max_updated_tasks: Some(max_updated_tasks),
+            ..self
+        }
+    }
+
     /// Sets how long data is retained for completed tasks.
     ///
     /// A longer duration will allow more historical data to be replayed by
@@ -143,6 +293,139 @@ impl Builder {
         Self { retention, ..self }
     }
 
+    /// Sets the longest retention that clients may request.
+    ///
+    /// A client watching updates may ask for data from completed tasks to be
+    /// retained for longer than the configured [retention], so that it isn't
+    /// dropped before the client has seen it. While that client is attached,
+    /// completed data is retained for as long as it requested, up to this
+    /// maximum. Requests for a shorter retention than the configured one have
+    /// no effect.
+    ///
+    /// By default, this is [`ConsoleLayer::DEFAULT_MAX_RETENTION`]. Methods
+    /// like [`init`][`crate::init`] and [`spawn`][`crate::spawn`] will take the
+    /// value from the `TOKIO_CONSOLE_MAX_RETENTION` [environment variable]
+    /// before falling back on that default.
+    ///
+    /// [retention]: Builder::retention
+    /// [environment variable]: `Builder::with_default_env`
+    pub fn max_retention(self, max_retention: Duration) -> Self {
+        Self {
+            max_retention,
+            ..self
+        }
+    }
+
+    /// Keeps the `count` most recently completed tasks after their
+    /// [retention] has elapsed, and sends them to each new client.
+    ///
+    /// Completed tasks are normally dropped once they've been retained for
+    /// the retention period, so a console which attaches just after an
+    /// incident may not see the tasks which were involved in it. With this
+    /// set, the last `count` tasks to be dropped are kept regardless of their
+    /// age, and are sent in the first update to each new client, flagged as
+    /// historical. Only the tasks' final stats are kept, not their details.
+    ///
+    /// By default, no tasks are kept. Methods like [`init`][`crate::init`]
+    /// and [`spawn`][`crate::spawn`] will take the value from the
+    /// `TOKIO_CONSOLE_KEEP_COMPLETED` [environment variable] before falling
+    /// back on that default.
+    ///
+    /// [retention]: Builder::retention
+    /// [environment variable]: `Builder::with_default_env`
+    pub fn keep_completed_tasks(self, count: usize) -> Self {
+        Self {
+            keep_completed_tasks: count,
+            ..self
+        }
+    }
+
+    /// Sets the maximum number of clients which may watch updates at once.
+    ///
+    /// Once this many clients are watching, any further clients are refused
+    /// with a `RESOURCE_EXHAUSTED` status until one of them disconnects.
+    /// This keeps forgotten console sessions from piling up and adding to the
+    /// work of publishing each update.
+    ///
+    /// By default, this is [`ConsoleLayer::DEFAULT_MAX_WATCHERS`]. Methods
+    /// like [`init`][`crate::init`] and [`spawn`][`crate::spawn`] will take the
+    /// value from the `TOKIO_CONSOLE_MAX_WATCHERS` [environment variable]
+    /// before falling back on that default.
+    ///
+    /// [environment variable]: `Builder::with_default_env`
+    pub fn max_watchers(self, max_watchers: usize) -> Self {
+        Self {
+            max_watchers,
+            ..self
+        }
+    }
+
+    /// Sets the most bytes of updates which are sent to each client per
+    /// second.
+    ///
+    /// A client may be sent up to a second's worth of bytes at once. Once an
+    /// update is too large to be sent to a client within its limit, the
+    /// client is only sent summaries from then on: the task aggregates and
+    /// activity, and the runtime's workers, without any tasks, resources, or
+    /// async ops. The client can reconnect to receive the full state again.
+    /// The initial update sent to each client, which includes the full
+    /// state, isn't counted against its limit.
+    ///
+    /// By default, the bandwidth of updates isn't limited. Methods like
+    /// [`init`][`crate::init`] and [`spawn`][`crate::spawn`] will take the
+    /// value from the `TOKIO_CONSOLE_MAX_BANDWIDTH` [environment
+    /// variable] before falling back on that default.
+    ///
+    /// [environment variable]: `Builder::with_default_env`
+    pub fn max_watcher_bandwidth(self, bytes_per_sec: u64) -> Self {
+        Self {
+            max_watcher_bandwidth: Some(bytes_per_sec),
+            ..self
+        }
+    }
+
+    /// Sets whether the server is read-only.
+    ///
+    /// A read-only server still streams updates to clients, but refuses any
+    /// request to control the instrumented process, such as pausing or
+    /// resuming the aggregator, with a `PERMISSION_DENIED` status. Clients
+    /// are told that the server is read-only, so that the console can show
+    /// it. This allows a process in production to be observed without
+    /// letting whoever connects change how it behaves.
+    ///
+    /// By default, the server is not read-only.
+    pub fn read_only(self, read_only: bool) -> Self {
+        Self { read_only, ..self }
+    }
+
+    /// Sets whether warnings about unhealthy tasks are logged as `tracing`
+    /// events in the instrumented application.
+    ///
+    /// When enabled, the aggregator checks the tasks once per [publish
+    /// interval] for some of the problems which the console warns about,
+    /// such as tasks which have lost their wakers or never yielded, and
+    /// emits a `WARN` event with the `console_subscriber` target for each of
+    /// them. These are emitted to the subscriber which the [`ConsoleLayer`]
+    /// is part of, so the application's existing logging and alerting sees
+    /// them even when no console is connected.
+    ///
+    /// Each problem is logged at most once per task, and at most 10 warnings
+    /// are logged per minute, along with how many were suppressed.
+    ///
+    /// By default, warnings are not logged. Methods like
+    /// [`init`][`crate::init`] and [`spawn`][`crate::spawn`] will take the
+    /// value from the `TOKIO_CONSOLE_EMIT_WARNINGS` [environment variable]
+    /// before falling back on that default.
+    ///
+    /// [publish interval]: Self::publish_interval
+    /// [environment variable]: `Builder::with_default_env`
+    pub fn emit_warnings(self, emit_warnings: bool) -> Self {
+        Self {
+            emit_warnings,
+            ..self
+        }
+    }
+
     /// Sets the socket address on which to serve the RPC server.
     ///
     /// By default, the server is bound on the IP address [`Server::DEFAULT_IP`]
@@ -190,8 +473,42 @@ impl Builder {
         }
     }
 
+    /// Serves the instrument server on a listening socket passed to the
+    /// process by systemd, rather than binding [`Builder::server_addr`].
+    ///
+    /// With [socket activation], systemd binds the console's socket and
+    /// starts the service when a connection arrives, so an application
+    /// which is only run to be inspected consumes no resources until a
+    /// console actually connects. The socket is configured in the unit's
+    /// `.socket` file, for example:
+    ///
+    /// ```ini
+    /// [Socket]
+    /// ListenStream=127.0.0.1:6669
+    /// ```
+    ///
+    /// Both TCP and Unix domain sockets are supported. If the process was not
+    /// passed a socket, the server falls back on [`Builder::server_addr`].
+    ///
+    /// By default, socket activation is disabled.
+    ///
+    /// [socket activation]: https://www.freedesktop.org/software/systemd/man/latest/systemd.socket.html
+    #[cfg(unix)]
+    pub fn with_socket_activation(self) -> Self {
+        Self {
+            socket_activation: true,
+            ..self
+        }
+    }
+
     /// Sets the path to record the events to the file system.
     ///
+    /// Along with the events, the recording periodically includes a
+    /// checkpoint of the state of every live task, so that it can be replayed
+    /// from any point without applying every event before it. An index of
+    /// the checkpoints' byte offsets is written to the same path, with
+    /// `.index` appended.
+    ///
     /// By default, this is initially `None`. Methods like
     /// [`init`][`crate::init`] and [`spawn`][`crate::spawn`] will take the
     /// value from the `TOKIO_CONSOLE_RECORD_PATH` [environment variable] before
@@ -237,10 +554,17 @@ impl Builder {
     /// Any poll durations exceeding this value will be clamped down to this
     /// duration and recorded as an outlier.
     ///
+    /// Clients are sent how many polls were clamped, and the longest of them,
+    /// alongside the histogram, so long stalls are still visible.
+    ///
     /// By default, this is [one second]. Higher values will increase per-task
-    /// memory usage.
+    /// memory usage. Methods like [`init`][`crate::init`] and
+    /// [`spawn`][`crate::spawn`] will take the value from the
+    /// `TOKIO_CONSOLE_POLL_DURATION_MAX` [environment variable] before falling
+    /// back on that default.
     ///
     /// [one second]: ConsoleLayer::DEFAULT_POLL_DURATION_MAX
+    /// [environment variable]: `Builder::with_default_env`
     pub fn poll_duration_histogram_max(self, max: Duration) -> Self {
         Self {
             poll_duration_max: max,
@@ -265,6 +589,174 @@ impl Builder {
         }
     }
 
+    /// Sets the precision of task poll and scheduled duration histograms, in
+    /// significant figures.
+    ///
+    /// Durations are recorded to within this many significant figures of
+    /// their true value. Each additional figure increases the memory used by
+    /// each task's histograms roughly tenfold. Histograms can't record more
+    /// than five significant figures, so higher values are clamped to five.
+    /// The approximate memory used by the histograms is reported to clients
+    /// watching the aggregator's state, and shown in the console's header.
+    ///
+    /// By default, this is
+    /// [`ConsoleLayer::DEFAULT_HISTOGRAM_SIGNIFICANT_FIGURES`]. Methods like
+    /// [`init`][`crate::init`] and [`spawn`][`crate::spawn`] will take the
+    /// value from the `TOKIO_CONSOLE_HISTOGRAM_SIGFIG`
+    /// [environment variable] before falling back on that default.
+    ///
+    /// [environment variable]: `Builder::with_default_env`
+    pub fn histogram_significant_figures(self, significant_figures: u8) -> Self {
+        Self {
+            histogram_significant_figures: significant_figures.min(5),
+            ..self
+        }
+    }
+
+    /// Sets the most verbose level of task spans which are recorded as tasks.
+    ///
+    /// Task spans at a more verbose level than this are ignored entirely, as
+    /// with any other `tracing` filter: they aren't sent to clients, and
+    /// their polls and wakes aren't counted. This can be used to keep tasks
+    /// which an application instruments with a `DEBUG` or `TRACE` span out
+    /// of the console in production.
+    ///
+    /// The console can also hide tasks below a level itself, without the
+    /// application having to drop them.
+    ///
+    /// By default, this is [`LevelFilter::TRACE`], so every task is recorded.
+    /// Methods like [`init`][`crate::init`] and [`spawn`][`crate::spawn`] will
+    /// take the value from the `TOKIO_CONSOLE_TASK_LEVEL` [environment
+    /// variable] before falling back on that default.
+    ///
+    /// [`LevelFilter::TRACE`]: filter::LevelFilter::TRACE
+    /// [environment variable]: `Builder::with_default_env`
+    pub fn task_level(self, level: impl Into<filter::LevelFilter>) -> Self {
+        Self {
+            task_level: level.into(),
+            ..self
+        }
+    }
+
+    /// Sets the maximum number of fields recorded for each task.
+    ///
+    /// Any further fields are omitted when the task is spawned, and clients
+    /// are told how many fields were omitted. Lower values reduce the memory
+    /// used to store each task.
+    ///
+    /// By default, this is [`ConsoleLayer::DEFAULT_MAX_FIELDS_PER_TASK`].
+    /// Methods like [`init`][`crate::init`] and [`spawn`][`crate::spawn`] will
+    /// take the value from the `TOKIO_CONSOLE_MAX_FIELDS_PER_TASK`
+    /// [environment variable] before falling back on that default.
+    ///
+    /// [environment variable]: `Builder::with_default_env`
+    pub fn max_fields_per_task(self, max_fields_per_task: usize) -> Self {
+        Self {
+            max_fields_per_task,
+            ..self
+        }
+    }
+
+    /// Sets the maximum length, in bytes, of the string values of task fields.
+    ///
+    /// Longer values, such as the URL of a request a task is handling, are
+    /// truncated when the task is spawned, and are marked as truncated for
+    /// clients. Lower values reduce the memory used to store each task.
+    ///
+    /// By default, this is [`ConsoleLayer::DEFAULT_MAX_FIELD_VALUE_LEN`].
+    /// Methods like [`init`][`crate::init`] and [`spawn`][`crate::spawn`] will
+    /// take the value from the `TOKIO_CONSOLE_MAX_FIELD_VALUE_LEN`
+    /// [environment variable] before falling back on that default.
+    ///
+    /// [environment variable]: `Builder::with_default_env`
+    pub fn max_field_value_len(self, max_field_value_len: usize) -> Self {
+        Self {
+            max_field_value_len,
+            ..self
+        }
+    }
+
+    /// Sets a hook which redacts the values of sensitive fields.
+    ///
+    /// The hook is called with the name of each field of a task or watched
+    /// span, and its value formatted as a string. If it returns a
+    /// replacement, that is recorded in place of the field's value. This
+    /// happens when the task or span is created, before the value is sent to
+    /// the aggregator, so secrets which are accidentally included in spawn
+    /// fields are never sent to clients or written to a recording.
+    ///
+    /// ```
+    /// # use console_subscriber::Builder;
+    /// let builder = Builder::default().redact_fields(|name, _value| {
+    ///     (name.contains("token") || name.contains("password"))
+    ///         .then(|| "<redacted>".to_string())
+    /// });
+    /// ```
+    ///
+    /// By default, no fields are redacted.
+    pub fn redact_fields<F>(self, redact: F) -> Self
+    where
+        F: Fn(&str, &str) -> Option<String> + Send + Sync + 'static,
+    {
+        Self {
+            redact_fields: Some(RedactFields::new(redact)),
+            ..self
+        }
+    }
+
+    /// Sets a filter which chooses the tasks that record histograms of their
+    /// poll and scheduled durations.
+    ///
+    /// Every task records counters of its polls, wakes, and busy time, but
+    /// the duration histograms shown in a task's details take up much more
+    /// memory. Applications which spawn very many short-lived tasks can use
+    /// this to keep histograms only for the tasks they are interested in.
+    /// The filter is called once for each task, when it is spawned.
+    ///
+    /// ```
+    /// # use console_subscriber::Builder;
+    /// let builder = Builder::default().task_histograms(|task| {
+    ///     task.name().is_some() || task.file().map_or(false, |file| file.starts_with("src/"))
+    /// });
+    /// ```
+    ///
+    /// Tasks which don't record histograms have no poll or scheduled time
+    /// histograms in their details, and aren't counted in the poll duration
+    /// histograms of their task groups.
+    ///
+    /// By default, every task records histograms.
+    pub fn task_histograms<F>(self, filter: F) -> Self
+    where
+        F: Fn(&SpawnedTask<'_>) -> bool + Send + Sync + 'static,
+    {
+        Self {
+            histogram_filter: Some(HistogramFilter::new(filter)),
+            ..self
+        }
+    }
+
+    /// Sets the maximum number of tasks which record histograms of their
+    /// poll and scheduled durations at once.
+    ///
+    /// Histograms can't be filled in after the fact, so tasks are admitted
+    /// in the order they are spawned, rather than by how busy they turn out
+    /// to be. A task stops counting towards the limit once it has completed
+    /// and its data is no longer retained. Tasks spawned while the limit is
+    /// reached only record counters, as with [`Builder::task_histograms`].
+    ///
+    /// By default, there is no limit. Methods like [`init`][`crate::init`]
+    /// and [`spawn`][`crate::spawn`] will take the value from the
+    /// `TOKIO_CONSOLE_MAX_HISTOGRAM_TASKS` [environment variable] before
+    /// falling back on that default.
+    ///
+    /// [environment variable]: `Builder::with_default_env`
+    pub fn max_histogram_tasks(self, max: usize) -> Self {
+        Self {
+            max_histogram_tasks: Some(max),
+            ..self
+        }
+    }
+
     /// Sets whether tasks, resources, and async ops from the console
     /// subscriber thread are recorded.
     ///
@@ -296,24 +788,114 @@ impl Builder {
         }
     }
 
+    /// Sets the metrics of the runtime whose workers are shown in the
+    /// console's runtime view.
+    ///
+    /// Every thread which polls a task is always reported, along with the
+    /// task it is currently polling. With the runtime's metrics, the threads
+    /// which are that runtime's workers also report how many tasks they have
+    /// stolen, and how often they have parked and unparked.
+    ///
+    /// ```
+    /// # use console_subscriber::Builder;
+    /// # #[tokio::main]
+    /// # async fn main() {
+    /// let metrics = tokio::runtime::Handle::current().metrics();
+    /// let builder = Builder::default().runtime_metrics(metrics);
+    /// # }
+    /// ```
+    ///
+    /// By default, no runtime's metrics are reported.
+    #[cfg(tokio_unstable)]
+    pub fn runtime_metrics(self, metrics: tokio::runtime::RuntimeMetrics) -> Self {
+        Self {
+            runtime_metrics: Some(metrics),
+            ..self
+        }
+    }
+
     /// Completes the builder, returning a [`ConsoleLayer`] and [`Server`] task.
     pub fn build(self) -> (ConsoleLayer, Server) {
         ConsoleLayer::build(self)
     }
 
+    /// Completes the builder, returning a [`ConsoleLayer`] restricted by a
+    /// [`ConsoleFilter`] and [`Server`] task.
+    ///
+    /// The returned layer uses [per-layer filtering], so it only observes the
+    /// spans and events that the console records, while other layers in the
+    /// same subscriber are unaffected. This avoids the cost of recording
+    /// unrelated spans in subscribers with many of them, and allows the layer
+    /// to be used alongside layers with their own, more restrictive, filters.
+    ///
+    /// ```
+    /// use tracing_subscriber::{filter::LevelFilter, prelude::*};
+    ///
+    /// let (console_layer, server) = console_subscriber::ConsoleLayer::builder()
+    ///     .with_default_env()
+    ///     .build_filtered();
+    ///
+    /// tracing_subscriber::registry()
+    ///     .with(console_layer)
+    ///     .with(tracing_subscriber::fmt::layer().with_filter(LevelFilter::INFO))
+    /// //  .with(...)
+    ///     .init();
+    /// # drop(server);
+    /// ```
+    ///
+    /// [per-layer filtering]: https://docs.rs/tracing-subscriber/latest/tracing_subscriber/layer/index.html#per-layer-filtering
+    pub fn build_filtered<S>(self) -> (FilteredConsoleLayer<S>, Server)
+    where
+        S: Subscriber + for<'a> LookupSpan<'a>,
+    {
+        let (layer, server) = self.build();
+        (layer.with_filter(ConsoleFilter::new()), server)
+    }
+
     /// Configures this builder from a standard set of environment variables:
     ///
-    /// | **Environment Variable**         | **Purpose**                                                  | **Default Value** |
-    /// |----------------------------------|--------------------------------------------------------------|-------------------|
-    /// | `TOKIO_CONSOLE_RETENTION`        | The duration of seconds to accumulate completed tracing data | 3600s (1h)        |
-    /// | `TOKIO_CONSOLE_BIND`             | a HOST:PORT description, such as `localhost:1234`            | `127.0.0.1:6669`  |
-    /// | `TOKIO_CONSOLE_PUBLISH_INTERVAL` | The duration to wait between sending updates to the console  | 1000ms (1s)       |
-    /// | `TOKIO_CONSOLE_RECORD_PATH`      | The file path to save a recording                            | None              |
+    /// | **Environment Variable**            | **Purpose**                                                  | **Default Value** |
+    /// |-------------------------------------|--------------------------------------------------------------|-------------------|
+    /// | `TOKIO_CONSOLE_RETENTION`           | The duration of seconds to accumulate completed tracing data | 3600s (1h)        |
+    /// | `TOKIO_CONSOLE_MAX_RETENTION`       | The longest retention a client may request                   | 3600s (1h)        |
+    /// | `TOKIO_CONSOLE_KEEP_COMPLETED`      | How many completed tasks to keep after their retention       | 0                 |
+    /// | `TOKIO_CONSOLE_MAX_WATCHERS`        | The maximum number of clients watching updates at once       | 8                 |
+    /// | `TOKIO_CONSOLE_MAX_BANDWIDTH`       | The most bytes of updates sent to each client per second     | None              |
+    /// | `TOKIO_CONSOLE_EMIT_WARNINGS`       | Whether warnings about unhealthy tasks are logged            | false             |
+    /// | `TOKIO_CONSOLE_BIND`                | a HOST:PORT description, such as `localhost:1234`            | `127.0.0.1:6669`  |
+    /// | `TOKIO_CONSOLE_PUBLISH_INTERVAL`    | The duration to wait between sending updates to the console  | 1000ms (1s)       |
+    /// | `TOKIO_CONSOLE_RECORD_PATH`         | The file path to save a recording                            | None              |
+    /// | `TOKIO_CONSOLE_TASK_LEVEL`          | The most verbose level of task spans which are recorded      | trace             |
+    /// | `TOKIO_CONSOLE_MAX_FIELDS_PER_TASK` | The maximum number of fields recorded for each task          | 32                |
+    /// | `TOKIO_CONSOLE_MAX_FIELD_VALUE_LEN` | The maximum length of task field values, in bytes            | 1024              |
+    /// | `TOKIO_CONSOLE_POLL_DURATION_MAX`   | The longest poll duration recorded in histograms             | 1s                |
+    /// | `TOKIO_CONSOLE_HISTOGRAM_SIGFIG`    | The significant figures recorded in duration histograms      | 2                 |
+    /// | `TOKIO_CONSOLE_MAX_HISTOGRAM_TASKS` | The maximum number of tasks recording histograms at once     | None              |
     pub fn with_default_env(mut self) -> Self {
         if let Some(retention) = duration_from_env("TOKIO_CONSOLE_RETENTION") {
             self.retention = retention;
         }
 
+        if let Some(max_retention) = duration_from_env("TOKIO_CONSOLE_MAX_RETENTION") {
+            self.max_retention = max_retention;
+        }
+
+        if let Some(count) = usize_from_env("TOKIO_CONSOLE_KEEP_COMPLETED") {
+            self.keep_completed_tasks = count;
+        }
+
+        if let Some(max_watchers) = usize_from_env("TOKIO_CONSOLE_MAX_WATCHERS") {
+            self.max_watchers = max_watchers;
+        }
+
+        if let Some(bandwidth) = usize_from_env("TOKIO_CONSOLE_MAX_BANDWIDTH") {
+            self.max_watcher_bandwidth = Some(bandwidth as u64);
+        }
+
+        if let Some(emit_warnings) = bool_from_env("TOKIO_CONSOLE_EMIT_WARNINGS") {
+            self.emit_warnings = emit_warnings;
+        }
+
         if let Ok(bind) = std::env::var("TOKIO_CONSOLE_BIND") {
             self.server_addr = ServerAddr::Tcp(
                 bind.to_socket_addrs()
@@ -337,6 +919,36 @@ impl Builder {
             self.event_buffer_capacity = capacity;
         }
 
+        if let Ok(level) = std::env::var("TOKIO_CONSOLE_TASK_LEVEL") {
+            self.task_level = level.parse().unwrap_or_else(|e| {
+                panic!(
+                    "failed to parse a level from `TOKIO_CONSOLE_TASK_LEVEL={:?}`: {}",
+                    level, e
+                )
+            });
+        }
+
+        if let Some(max) = usize_from_env("TOKIO_CONSOLE_MAX_FIELDS_PER_TASK") {
+            self.max_fields_per_task = max;
+        }
+
+        if let Some(len) = usize_from_env("TOKIO_CONSOLE_MAX_FIELD_VALUE_LEN") {
+            self.max_field_value_len = len;
+        }
+
+        if let Some(max) = usize_from_env("TOKIO_CONSOLE_MAX_HISTOGRAM_TASKS") {
+            self.max_histogram_tasks = Some(max);
+        }
+
+        if let Some(max) = duration_from_env("TOKIO_CONSOLE_POLL_DURATION_MAX") {
+            self.poll_duration_max = max;
+        }
+
+        if let Some(sigfig) = usize_from_env("TOKIO_CONSOLE_HISTOGRAM_SIGFIG") {
+            let sigfig = u8::try_from(sigfig).unwrap_or(u8::MAX);
+            self = self.histogram_significant_figures(sigfig);
+        }
+
         self
     }
 
@@ -377,9 +989,21 @@ impl Builder {
     /// | **Environment Variable**            | **Purpose**                                                               | **Default Value** |
     /// |-------------------------------------|---------------------------------------------------------------------------|-------------------|
     /// | `TOKIO_CONSOLE_RETENTION`           | The number of seconds to accumulate completed tracing data                | 3600s (1h)        |
+    /// | `TOKIO_CONSOLE_MAX_RETENTION`       | The longest retention a client may request, in seconds                    | 3600s (1h)        |
+    /// | `TOKIO_CONSOLE_KEEP_COMPLETED`      | How many completed tasks to keep after their retention                    | 0                 |
+    /// | `TOKIO_CONSOLE_MAX_WATCHERS`        | The maximum number of clients watching updates at once                    | 8                 |
+    /// | `TOKIO_CONSOLE_MAX_BANDWIDTH`       | The most bytes of updates sent to each client per second                  | None              |
+    /// | `TOKIO_CONSOLE_EMIT_WARNINGS`       | Whether warnings about unhealthy tasks are logged                         | false             |
+    /// | `TOKIO_CONSOLE_EMIT_WARNINGS`       | Whether warnings about unhealthy tasks are logged                         | false             |
     /// | `TOKIO_CONSOLE_BIND`                | A HOST:PORT description, such as `localhost:1234`                         | `127.0.0.1:6669`  |
     /// | `TOKIO_CONSOLE_PUBLISH_INTERVAL`    | The number of milliseconds to wait between sending updates to the console | 1000ms (1s)       |
     /// | `TOKIO_CONSOLE_RECORD_PATH`         | The file path to save a recording                                         | None              |
+    /// | `TOKIO_CONSOLE_TASK_LEVEL`          | The most verbose level of task spans which are recorded                   | trace             |
+    /// | `TOKIO_CONSOLE_MAX_FIELDS_PER_TASK` | The maximum number of fields recorded for each task                       | 32                |
+    /// | `TOKIO_CONSOLE_MAX_FIELD_VALUE_LEN` | The maximum length of task field values, in bytes                         | 1024              |
+    /// | `TOKIO_CONSOLE_POLL_DURATION_MAX`   | The longest poll duration recorded in histograms                          | 1s                |
+    /// | `TOKIO_CONSOLE_HISTOGRAM_SIGFIG`    | The significant figures recorded in duration histograms                   | 2                 |
+    /// | `TOKIO_CONSOLE_MAX_HISTOGRAM_TASKS` | The maximum number of tasks recording histograms at once                  | None              |
     /// | `RUST_LOG`                          | Configures what events are logged events. See [`Targets`] for details.    | "error"           |
     ///
     /// If the "env-filter" crate feature flag is enabled, the `RUST_LOG`
@@ -450,7 +1074,7 @@ impl Builder {
     /// additional [`Layer`]s to be added.
     ///
     /// [subscriber]: https://docs.rs/tracing/latest/tracing/subscriber/trait.Subscriber.html
-    /// [filter]: https://docs.rs/tracing-subscriber/latest/tracing_subscriber/filter/targets/struct.Targets.html
+    /// [filter]: crate::ConsoleFilter
     /// [`Layer`]: https://docs.rs/tracing-subscriber/latest/tracing_subscriber/layer/trait.Layer.html
     /// [`Server`]: crate::Server
     ///
@@ -496,30 +1120,15 @@ impl Builder {
     /// [`fmt::Layer`]: https://docs.rs/tracing-subscriber/latest/tracing-subscriber/fmt/struct.Layer.html
     /// [`console_subscriber::init`]: crate::init()
     #[must_use = "a `Layer` must be added to a `tracing::Subscriber` in order to be used"]
-    pub fn spawn<S>(self) -> impl Layer<S>
+    pub fn spawn<S>(self) -> FilteredConsoleLayer<S>
     where
         S: Subscriber + for<'a> LookupSpan<'a>,
     {
-        fn console_filter(meta: &tracing::Metadata<'_>) -> bool {
-            // events will have *targets* beginning with "runtime"
-            if meta.is_event() {
-                return meta.target().starts_with("runtime") || meta.target().starts_with("tokio");
-            }
-
-            // spans will have *names* beginning with "runtime". for backwards
-            // compatibility with older Tokio versions, enable anything with the `tokio`
-            // target as well.
-            meta.name().starts_with("runtime.") || meta.target().starts_with("tokio")
-        }
-
         let self_trace = self.self_trace;
         #[cfg(feature = "grpc-web")]
         let enable_grpc_web = self.enable_grpc_web;
 
-        let (layer, server) = self.build();
-        let filter =
-            FilterFn::new(console_filter as for<'r, 's> fn(&'r tracing::Metadata<'s>) -> bool);
-        let layer = layer.with_filter(filter);
+        let (layer, server) = self.build_filtered();
 
         thread::Builder::new()
             .name("console_subscriber".into())
@@ -652,9 +1261,19 @@ impl<'a> From<&'a Path> for ServerAddr {
 /// | **Environment Variable**            | **Purpose**                                                               | **Default Value** |
 /// |-------------------------------------|---------------------------------------------------------------------------|-------------------|
 /// | `TOKIO_CONSOLE_RETENTION`           | The number of seconds to accumulate completed tracing data                | 3600s (1h)        |
+/// | `TOKIO_CONSOLE_MAX_RETENTION`       | The longest retention a client may request, in seconds                    | 3600s (1h)        |
+/// | `TOKIO_CONSOLE_KEEP_COMPLETED`      | How many completed tasks to keep after their retention                    | 0                 |
+/// | `TOKIO_CONSOLE_MAX_WATCHERS`        | The maximum number of clients watching updates at once                    | 8                 |
+/// | `TOKIO_CONSOLE_MAX_BANDWIDTH`       | The most bytes of updates sent to each client per second                  | None              |
 /// | `TOKIO_CONSOLE_BIND`                | A HOST:PORT description, such as `localhost:1234`                         | `127.0.0.1:6669`  |
 /// | `TOKIO_CONSOLE_PUBLISH_INTERVAL`    | The number of milliseconds to wait between sending updates to the console | 1000ms (1s)       |
 /// | `TOKIO_CONSOLE_RECORD_PATH`         | The file path to save a recording                                         | None              |
+/// | `TOKIO_CONSOLE_TASK_LEVEL`          | The most verbose level of task spans which are recorded                   | trace             |
+/// | `TOKIO_CONSOLE_MAX_FIELDS_PER_TASK` | The maximum number of fields recorded for each task                       | 32                |
+/// | `TOKIO_CONSOLE_MAX_FIELD_VALUE_LEN` | The maximum length of task field values, in bytes                         | 1024              |
+/// | `TOKIO_CONSOLE_POLL_DURATION_MAX`   | The longest poll duration recorded in histograms                          | 1s                |
+/// | `TOKIO_CONSOLE_HISTOGRAM_SIGFIG`    | The significant figures recorded in duration histograms                   | 2                 |
+/// | `TOKIO_CONSOLE_MAX_HISTOGRAM_TASKS` | The maximum number of tasks recording histograms at once                  | None              |
 /// | `RUST_LOG`                          | Configures what events are logged events. See [`Targets`] for details.    | "error"           |
 ///
 /// If the "env-filter" crate feature flag is enabled, the `RUST_LOG`
@@ -711,7 +1330,7 @@ pub fn init() {
 /// # use tracing_subscriber::prelude::*;
 /// # tracing_subscriber::registry().with(layer).init(); // to suppress must_use warnings
 /// ```
-/// [filter]: https://docs.rs/tracing-subscriber/latest/tracing_subscriber/filter/targets/struct.Targets.html
+/// [filter]: crate::ConsoleFilter
 /// [`Layer`]: https://docs.rs/tracing-subscriber/latest/tracing_subscriber/layer/trait.Layer.html
 /// [`Server`]: crate::Server
 ///
@@ -752,7 +1371,7 @@ pub fn init() {
 /// [`fmt::Layer`]: https://docs.rs/tracing-subscriber/latest/tracing-subscriber/fmt/struct.Layer.html
 /// [`console_subscriber::init`]: crate::init()
 #[must_use = "a `Layer` must be added to a `tracing::Subscriber`in order to be used"]
-pub fn spawn<S>() -> impl Layer<S>
+pub fn spawn<S>() -> FilteredConsoleLayer<S>
 where
     S: Subscriber + for<'a> LookupSpan<'a>,
 {
@@ -770,6 +1389,17 @@ fn duration_from_env(var_name: &str) -> Option<Duration> {
     }
 }
 
+fn bool_from_env(var_name: &str) -> Option<bool> {
+    let var = std::env::var(var_name).ok()?;
+    match var.parse::<bool>() {
+        Ok(value) => Some(value),
+        Err(e) => panic!(
+            "failed to parse a bool from `{}={:?}`: {}",
+            var_name, var, e
+        ),
+    }
+}
+
 fn usize_from_env(var_name: &str) -> Option<usize> {
     let var = std::env::var(var_name).ok()?;
     match var.parse::<usize>() {
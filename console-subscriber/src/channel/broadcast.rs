@@ -0,0 +1,148 @@
+//! An instrumented multi-producer, multi-consumer broadcast channel.
+//!
+//! See [`tokio::sync::broadcast`] for details on the channel's behavior.
+//!
+//! [`tokio::sync::broadcast`]: https://docs.rs/tokio/latest/tokio/sync/broadcast/index.html
+use super::Resource;
+use std::{fmt, sync::Arc, time::Instant};
+use tokio::sync::broadcast;
+
+pub use tokio::sync::broadcast::error::{RecvError, SendError, TryRecvError};
+
+/// Sends values to all associated [`Receiver`]s.
+///
+/// Instances are created by the [`channel`] function.
+pub struct Sender<T> {
+    inner: broadcast::Sender<(Instant, T)>,
+    resource: Arc<Resource>,
+}
+
+/// Receives values from the associated [`Sender`].
+///
+/// Instances are created by the [`channel`] function, or by calling
+/// [`Sender::subscribe`].
+pub struct Receiver<T> {
+    inner: broadcast::Receiver<(Instant, T)>,
+    resource: Arc<Resource>,
+}
+
+/// Creates a bounded, multi-producer, multi-consumer channel where each sent
+/// value is broadcasted to all active receivers, which is reported to the
+/// console as a resource.
+///
+/// The `depth` attribute of a broadcast channel is the number of values the
+/// most recently active receiver has yet to receive.
+///
+/// See [`tokio::sync::broadcast::channel`] for details.
+///
+/// # Panics
+///
+/// Panics if the capacity is 0, or too large.
+///
+/// [`tokio::sync::broadcast::channel`]: https://docs.rs/tokio/latest/tokio/sync/broadcast/fn.channel.html
+#[track_caller]
+pub fn channel<T: Clone>(capacity: usize) -> (Sender<T>, Receiver<T>) {
+    let (tx, rx) = broadcast::channel(capacity);
+    let resource = Arc::new(Resource::new("broadcast::Channel", Some(capacity), false));
+    let tx = Sender {
+        inner: tx,
+        resource: resource.clone(),
+    };
+    let rx = Receiver {
+        inner: rx,
+        resource,
+    };
+    (tx, rx)
+}
+
+// === impl Sender ===
+
+impl<T> Sender<T> {
+    /// Attempts to send a value to all active receivers, returning the number
+    /// of receivers that will receive it.
+    ///
+    /// See [`tokio::sync::broadcast::Sender::send`] for details.
+    ///
+    /// [`tokio::sync::broadcast::Sender::send`]: https://docs.rs/tokio/latest/tokio/sync/broadcast/struct.Sender.html#method.send
+    pub fn send(&self, value: T) -> Result<usize, SendError<T>> {
+        let receivers = self
+            .inner
+            .send((Instant::now(), value))
+            .map_err(|SendError((_, value))| SendError(value))?;
+        self.resource.sent();
+        Ok(receivers)
+    }
+
+    /// Creates a new [`Receiver`] which will receive values sent after this
+    /// call.
+    pub fn subscribe(&self) -> Receiver<T> {
+        Receiver {
+            inner: self.inner.subscribe(),
+            resource: self.resource.clone(),
+        }
+    }
+
+    /// Returns the number of active receivers.
+    pub fn receiver_count(&self) -> usize {
+        self.inner.receiver_count()
+    }
+}
+
+impl<T> Clone for Sender<T> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+            resource: self.resource.clone(),
+        }
+    }
+}
+
+impl<T> fmt::Debug for Sender<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Sender")
+            .field("inner", &self.inner)
+            .finish()
+    }
+}
+
+// === impl Receiver ===
+
+impl<T: Clone> Receiver<T> {
+    /// Receives the next value for this receiver.
+    ///
+    /// See [`tokio::sync::broadcast::Receiver::recv`] for details.
+    ///
+    /// [`tokio::sync::broadcast::Receiver::recv`]: https://docs.rs/tokio/latest/tokio/sync/broadcast/struct.Receiver.html#method.recv
+    pub async fn recv(&mut self) -> Result<T, RecvError> {
+        let (sent_at, value) = self.inner.recv().await?;
+        self.resource.received(sent_at, Some(self.inner.len()));
+        Ok(value)
+    }
+
+    /// Attempts to return a pending value on this receiver without waiting.
+    ///
+    /// See [`tokio::sync::broadcast::Receiver::try_recv`] for details.
+    ///
+    /// [`tokio::sync::broadcast::Receiver::try_recv`]: https://docs.rs/tokio/latest/tokio/sync/broadcast/struct.Receiver.html#method.try_recv
+    pub fn try_recv(&mut self) -> Result<T, TryRecvError> {
+        let (sent_at, value) = self.inner.try_recv()?;
+        self.resource.received(sent_at, Some(self.inner.len()));
+        Ok(value)
+    }
+
+    /// Re-subscribes to the channel starting from the current tail element.
+    pub fn resubscribe(&self) -> Self {
+        Self {
+            inner: self.inner.resubscribe(),
+            resource: self.resource.clone(),
+        }
+    }
+}
+
+impl<T> fmt::Debug for Receiver<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Receiver")
+            .field("inner", &self.inner)
+            .finish()
+    }
+}
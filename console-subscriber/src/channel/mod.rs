@@ -0,0 +1,128 @@
+//! Channels which are instrumented as resources.
+//!
+//! Tokio's channels do not emit any instrumentation of their own, so the
+//! channels in this module wrap the corresponding [`tokio::sync`] channels and
+//! report each channel to the console as a resource with the following
+//! attributes:
+//!
+//! - `capacity`: the channel's capacity, for bounded channels
+//! - `depth`: the number of values currently buffered in the channel
+//! - `sends`: the total number of values sent
+//! - `recvs`: the total number of values received
+//!
+//! The console also shows a histogram of how long values spent in the channel
+//! between being sent and received, which makes backpressure visible.
+//!
+//! The wrappers mirror the API of the Tokio channels they wrap, so switching to
+//! an instrumented channel is usually a matter of changing an import.
+//!
+//! [`tokio::sync`]: https://docs.rs/tokio/latest/tokio/sync/index.html
+use std::{
+    panic::Location,
+    sync::atomic::{AtomicIsize, Ordering::*},
+    time::Instant,
+};
+
+pub mod broadcast;
+pub mod mpsc;
+pub mod watch;
+
+/// The instrumentation shared by all halves of a channel.
+#[derive(Debug)]
+struct Resource {
+    span: tracing::Span,
+    /// The number of values sent but not yet received, if the channel's depth
+    /// is counted as values are sent and received.
+    ///
+    /// This is signed, because a value may be received before the sender has
+    /// recorded sending it.
+    depth: Option<AtomicIsize>,
+}
+
+impl Resource {
+    #[track_caller]
+    fn new(concrete_type: &'static str, capacity: Option<usize>, count_depth: bool) -> Self {
+        let location = Location::caller();
+        let span = tracing::trace_span!(
+            parent: None,
+            "runtime.resource",
+            concrete_type,
+            kind = "channel",
+            is_internal = false,
+            inherits_child_attrs = false,
+            loc.file = location.file(),
+            loc.line = location.line(),
+            loc.col = location.column(),
+        );
+        span.in_scope(|| {
+            if let Some(capacity) = capacity {
+                tracing::event!(
+                    target: "runtime::resource::state_update",
+                    tracing::Level::TRACE,
+                    capacity,
+                    capacity.op = "override",
+                );
+            }
+        });
+        Self {
+            span,
+            depth: count_depth.then(|| AtomicIsize::new(0)),
+        }
+    }
+
+    /// Records that a value was sent.
+    fn sent(&self) {
+        let depth = self
+            .depth
+            .as_ref()
+            .map(|depth| depth.fetch_add(1, AcqRel) + 1);
+        self.span.in_scope(|| {
+            tracing::event!(
+                target: "runtime::resource::state_update",
+                tracing::Level::TRACE,
+                sends = 1u64,
+                sends.op = "add",
+            );
+            if let Some(depth) = depth {
+                Self::update_depth(depth);
+            }
+        });
+    }
+
+    /// Records that a value which was sent at `sent_at` was received.
+    ///
+    /// If the channel's depth is not counted, `remaining` is the number of
+    /// values left to receive, if it is known.
+    fn received(&self, sent_at: Instant, remaining: Option<usize>) {
+        let latency_ns = sent_at.elapsed().as_nanos() as u64;
+        let depth = match self.depth {
+            Some(ref depth) => Some(depth.fetch_sub(1, AcqRel) - 1),
+            None => remaining.map(|remaining| remaining as isize),
+        };
+        self.span.in_scope(|| {
+            tracing::event!(
+                target: "runtime::resource::state_update",
+                tracing::Level::TRACE,
+                recvs = 1u64,
+                recvs.op = "add",
+            );
+            if let Some(depth) = depth {
+                Self::update_depth(depth);
+            }
+            tracing::event!(
+                target: "runtime::resource::channel::recv",
+                tracing::Level::TRACE,
+                latency_ns,
+            );
+        });
+    }
+
+    fn update_depth(depth: isize) {
+        tracing::event!(
+            target: "runtime::resource::state_update",
+            tracing::Level::TRACE,
+            depth = depth.max(0) as u64,
+            depth.op = "override",
+        );
+    }
+}
@@ -0,0 +1,161 @@
+//! An instrumented multi-producer, single-consumer channel.
+//!
+//! See [`tokio::sync::mpsc`] for details on the channel's behavior.
+//!
+//! [`tokio::sync::mpsc`]: https://docs.rs/tokio/latest/tokio/sync/mpsc/index.html
+use super::Resource;
+use std::{fmt, sync::Arc, time::Instant};
+use tokio::sync::mpsc;
+
+pub use tokio::sync::mpsc::error::{SendError, TryRecvError, TrySendError};
+
+/// Sends values to the associated [`Receiver`].
+///
+/// Instances are created by the [`channel`] function.
+pub struct Sender<T> {
+    inner: mpsc::Sender<(Instant, T)>,
+    resource: Arc<Resource>,
+}
+
+/// Receives values from the associated [`Sender`].
+///
+/// Instances are created by the [`channel`] function.
+pub struct Receiver<T> {
+    inner: mpsc::Receiver<(Instant, T)>,
+    resource: Arc<Resource>,
+}
+
+/// Creates a bounded mpsc channel for communicating between asynchronous
+/// tasks with backpressure, which is reported to the console as a resource.
+///
+/// See [`tokio::sync::mpsc::channel`] for details.
+///
+/// # Panics
+///
+/// Panics if the buffer capacity is 0.
+///
+/// [`tokio::sync::mpsc::channel`]: https://docs.rs/tokio/latest/tokio/sync/mpsc/fn.channel.html
+#[track_caller]
+pub fn channel<T>(buffer: usize) -> (Sender<T>, Receiver<T>) {
+    let (tx, rx) = mpsc::channel(buffer);
+    let resource = Arc::new(Resource::new("mpsc::Channel", Some(buffer), true));
+    let tx = Sender {
+        inner: tx,
+        resource: resource.clone(),
+    };
+    let rx = Receiver {
+        inner: rx,
+        resource,
+    };
+    (tx, rx)
+}
+
+// === impl Sender ===
+
+impl<T> Sender<T> {
+    /// Sends a value, waiting until there is capacity.
+    ///
+    /// See [`tokio::sync::mpsc::Sender::send`] for details.
+    ///
+    /// [`tokio::sync::mpsc::Sender::send`]: https://docs.rs/tokio/latest/tokio/sync/mpsc/struct.Sender.html#method.send
+    pub async fn send(&self, value: T) -> Result<(), SendError<T>> {
+        self.inner
+            .send((Instant::now(), value))
+            .await
+            .map_err(|SendError((_, value))| SendError(value))?;
+        self.resource.sent();
+        Ok(())
+    }
+
+    /// Attempts to immediately send a value on this channel.
+    ///
+    /// See [`tokio::sync::mpsc::Sender::try_send`] for details.
+    ///
+    /// [`tokio::sync::mpsc::Sender::try_send`]: https://docs.rs/tokio/latest/tokio/sync/mpsc/struct.Sender.html#method.try_send
+    pub fn try_send(&self, value: T) -> Result<(), TrySendError<T>> {
+        self.inner
+            .try_send((Instant::now(), value))
+            .map_err(|error| match error {
+                TrySendError::Full((_, value)) => TrySendError::Full(value),
+                TrySendError::Closed((_, value)) => TrySendError::Closed(value),
+            })?;
+        self.resource.sent();
+        Ok(())
+    }
+
+    /// Completes when the receiver has dropped.
+    pub async fn closed(&self) {
+        self.inner.closed().await
+    }
+
+    /// Checks if the channel has been closed.
+    pub fn is_closed(&self) -> bool {
+        self.inner.is_closed()
+    }
+
+    /// Returns the current capacity of the channel.
+    pub fn capacity(&self) -> usize {
+        self.inner.capacity()
+    }
+
+    /// Returns the maximum buffer capacity of the channel.
+    pub fn max_capacity(&self) -> usize {
+        self.inner.max_capacity()
+    }
+}
+
+impl<T> Clone for Sender<T> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+            resource: self.resource.clone(),
+        }
+    }
+}
+
+impl<T> fmt::Debug for Sender<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Sender")
+            .field("inner", &self.inner)
+            .finish()
+    }
+}
+
+// === impl Receiver ===
+
+impl<T> Receiver<T> {
+    /// Receives the next value for this receiver.
+    ///
+    /// See [`tokio::sync::mpsc::Receiver::recv`] for details.
+    ///
+    /// [`tokio::sync::mpsc::Receiver::recv`]: https://docs.rs/tokio/latest/tokio/sync/mpsc/struct.Receiver.html#method.recv
+    pub async fn recv(&mut self) -> Option<T> {
+        let (sent_at, value) = self.inner.recv().await?;
+        self.resource.received(sent_at, None);
+        Some(value)
+    }
+
+    /// Tries to receive the next value for this receiver.
+    ///
+    /// See [`tokio::sync::mpsc::Receiver::try_recv`] for details.
+    ///
+    /// [`tokio::sync::mpsc::Receiver::try_recv`]: https://docs.rs/tokio/latest/tokio/sync/mpsc/struct.Receiver.html#method.try_recv
+    pub fn try_recv(&mut self) -> Result<T, TryRecvError> {
+        let (sent_at, value) = self.inner.try_recv()?;
+        self.resource.received(sent_at, None);
+        Ok(value)
+    }
+
+    /// Closes the receiving half of a channel without dropping it.
+    pub fn close(&mut self) {
+        self.inner.close()
+    }
+}
+
+impl<T> fmt::Debug for Receiver<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Receiver")
+            .field("inner", &self.inner)
+            .finish()
+    }
+}
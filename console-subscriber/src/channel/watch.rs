@@ -0,0 +1,179 @@
+//! An instrumented single-producer, multi-consumer channel which only retains
+//! the last sent value.
+//!
+//! See [`tokio::sync::watch`] for details on the channel's behavior.
+//!
+//! Because a watch channel only ever holds one value, it has no `capacity` or
+//! `depth` attributes. Its receive latency is the time between a value being
+//! sent and a receiver observing the change.
+//!
+//! [`tokio::sync::watch`]: https://docs.rs/tokio/latest/tokio/sync/watch/index.html
+use super::Resource;
+use crate::sync::Mutex;
+use std::{fmt, sync::Arc, time::Instant};
+use tokio::sync::watch;
+
+pub use tokio::sync::watch::{
+    error::{RecvError, SendError},
+    Ref,
+};
+
+/// Sends values to the associated [`Receiver`]s.
+///
+/// Instances are created by the [`channel`] function.
+pub struct Sender<T> {
+    inner: watch::Sender<T>,
+    shared: Arc<Shared>,
+}
+
+/// Receives values from the associated [`Sender`].
+///
+/// Instances are created by the [`channel`] function, or by calling
+/// [`Sender::subscribe`].
+pub struct Receiver<T> {
+    inner: watch::Receiver<T>,
+    shared: Arc<Shared>,
+}
+
+#[derive(Debug)]
+struct Shared {
+    resource: Resource,
+    /// When the current value was sent.
+    sent_at: Mutex<Instant>,
+}
+
+/// Creates a new watch channel, returning the "send" and "receive" handles,
+/// which is reported to the console as a resource.
+///
+/// See [`tokio::sync::watch::channel`] for details.
+///
+/// [`tokio::sync::watch::channel`]: https://docs.rs/tokio/latest/tokio/sync/watch/fn.channel.html
+#[track_caller]
+pub fn channel<T>(init: T) -> (Sender<T>, Receiver<T>) {
+    let (tx, rx) = watch::channel(init);
+    let shared = Arc::new(Shared {
+        resource: Resource::new("watch::Channel", None, false),
+        sent_at: Mutex::new(Instant::now()),
+    });
+    let tx = Sender {
+        inner: tx,
+        shared: shared.clone(),
+    };
+    let rx = Receiver { inner: rx, shared };
+    (tx, rx)
+}
+
+// === impl Sender ===
+
+impl<T> Sender<T> {
+    /// Sends a new value via the channel, notifying all receivers.
+    ///
+    /// See [`tokio::sync::watch::Sender::send`] for details.
+    ///
+    /// [`tokio::sync::watch::Sender::send`]: https://docs.rs/tokio/latest/tokio/sync/watch/struct.Sender.html#method.send
+    pub fn send(&self, value: T) -> Result<(), SendError<T>> {
+        // Hold the lock while sending, so that a receiver which observes the
+        // new value also observes when it was sent.
+        let mut sent_at = self.shared.sent_at.lock();
+        self.inner.send(value)?;
+        *sent_at = Instant::now();
+        drop(sent_at);
+        self.shared.resource.sent();
+        Ok(())
+    }
+
+    /// Sends a new value via the channel, notifying all receivers and
+    /// returning the previous value.
+    ///
+    /// Unlike [`Sender::send`], this stores the value even if there are no
+    /// receivers.
+    pub fn send_replace(&self, value: T) -> T {
+        let mut sent_at = self.shared.sent_at.lock();
+        let prev = self.inner.send_replace(value);
+        *sent_at = Instant::now();
+        drop(sent_at);
+        self.shared.resource.sent();
+        prev
+    }
+
+    /// Returns a reference to the most recently sent value.
+    pub fn borrow(&self) -> Ref<'_, T> {
+        self.inner.borrow()
+    }
+
+    /// Creates a new [`Receiver`] connected to this `Sender`.
+    pub fn subscribe(&self) -> Receiver<T> {
+        Receiver {
+            inner: self.inner.subscribe(),
+            shared: self.shared.clone(),
+        }
+    }
+
+    /// Checks if the channel has been closed.
+    pub fn is_closed(&self) -> bool {
+        self.inner.is_closed()
+    }
+
+    /// Returns the number of receivers that currently exist.
+    pub fn receiver_count(&self) -> usize {
+        self.inner.receiver_count()
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for Sender<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Sender")
+            .field("inner", &self.inner)
+            .finish()
+    }
+}
+
+// === impl Receiver ===
+
+impl<T> Receiver<T> {
+    /// Waits for a change notification, then marks the newest value as seen.
+    ///
+    /// See [`tokio::sync::watch::Receiver::changed`] for details.
+    ///
+    /// [`tokio::sync::watch::Receiver::changed`]: https://docs.rs/tokio/latest/tokio/sync/watch/struct.Receiver.html#method.changed
+    pub async fn changed(&mut self) -> Result<(), RecvError> {
+        self.inner.changed().await?;
+        let sent_at = *self.shared.sent_at.lock();
+        self.shared.resource.received(sent_at, None);
+        Ok(())
+    }
+
+    /// Returns a reference to the most recently sent value.
+    pub fn borrow(&self) -> Ref<'_, T> {
+        self.inner.borrow()
+    }
+
+    /// Returns a reference to the most recently sent value and marks that
+    /// value as seen.
+    pub fn borrow_and_update(&mut self) -> Ref<'_, T> {
+        self.inner.borrow_and_update()
+    }
+
+    /// Checks if this channel contains a value that this receiver has not yet
+    /// seen.
+    pub fn has_changed(&self) -> Result<bool, RecvError> {
+        self.inner.has_changed()
+    }
+}
+
+impl<T> Clone for Receiver<T> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+            shared: self.shared.clone(),
+        }
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for Receiver<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Receiver")
+            .field("inner", &self.inner)
+            .finish()
+    }
+}
@@ -0,0 +1,43 @@
+/// Attaches an application-defined payload to the task this is called from.
+///
+/// The console protocol can't describe every statistic an application may
+/// want to show for its tasks, so applications can attach their own payloads,
+/// such as the number of requests a connection task has handled. Each payload
+/// is a serialized message identified by a type URL, like a protobuf
+/// [`Any`]. Consoles which don't know the type of a payload show it as raw
+/// data.
+///
+/// A task has at most one payload of each type: attaching a payload with the
+/// same `type_url` as one the task already has replaces it. Payloads are sent
+/// to clients with the next update after they're attached.
+///
+/// If this isn't called from within a task, the payload is discarded.
+///
+/// # Examples
+///
+/// ```
+/// # async fn doc() {
+/// tokio::spawn(async {
+///     let handled: u64 = 42;
+///     console_subscriber::set_task_extension(
+///         "type.example.com/app.Handled",
+///         &handled.to_le_bytes(),
+///     );
+/// });
+/// # }
+/// ```
+///
+/// [`Any`]: https://protobuf.dev/programming-guides/proto3/#any
+pub fn set_task_extension(type_url: &str, value: &[u8]) {
+    // `tracing` can't record byte slices, so the value is sent hex encoded.
+    let value = value
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect::<String>();
+    tracing::event!(
+        target: "runtime::task::extension",
+        tracing::Level::TRACE,
+        extension.type_url = type_url,
+        extension.value = value.as_str(),
+    );
+}
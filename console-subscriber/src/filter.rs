@@ -0,0 +1,73 @@
+use super::{visitors::WatchedSpanVisitor, ConsoleLayer};
+use tracing_core::{subscriber::Interest, Metadata};
+use tracing_subscriber::{filter::Filtered, layer};
+
+/// A [`ConsoleLayer`] restricted by a [`ConsoleFilter`], as returned by
+/// [`ConsoleLayer::filtered`] and [`Builder::build_filtered`].
+///
+/// [`Builder::build_filtered`]: crate::Builder::build_filtered
+pub type FilteredConsoleLayer<S> = Filtered<ConsoleLayer, ConsoleFilter, S>;
+
+/// A [per-layer filter] which enables only the spans and events that a
+/// [`ConsoleLayer`] records.
+///
+/// These are the spans for tasks, resources, and async operations, and the
+/// events emitted within them, by Tokio and by the instrumentation in this
+/// crate, as well as the spans the application has asked to watch with
+/// [`watch_span!`]. Everything else is disabled, so that a `ConsoleLayer` added to a
+/// busy subscriber doesn't observe spans which are of no use to the console.
+/// Because whether a span or event is enabled depends only on its
+/// [`Metadata`], the decision is cached for each callsite.
+///
+/// As this is a per-layer filter, it doesn't affect what other layers in the
+/// same subscriber see. It can also be combined with other filters using
+/// [`FilterExt`], for example to ignore the tasks spawned by a noisy
+/// dependency.
+///
+/// [per-layer filter]: https://docs.rs/tracing-subscriber/latest/tracing_subscriber/layer/index.html#per-layer-filtering
+/// [`FilterExt`]: https://docs.rs/tracing-subscriber/latest/tracing_subscriber/filter/trait.FilterExt.html
+/// [`watch_span!`]: crate::watch_span
+#[derive(Clone, Copy, Debug, Default)]
+pub struct ConsoleFilter {
+    _p: (),
+}
+
+// === impl ConsoleFilter ===
+
+impl ConsoleFilter {
+    /// Returns a new `ConsoleFilter`.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns `true` if a span or event with the given metadata is one that a
+    /// [`ConsoleLayer`] records.
+    pub fn is_enabled(metadata: &Metadata<'_>) -> bool {
+        // events will have *targets* beginning with "runtime"
+        if metadata.is_event() {
+            return metadata.target().starts_with("runtime")
+                || metadata.target().starts_with("tokio");
+        }
+
+        // spans will have *names* beginning with "runtime". for backwards
+        // compatibility with older Tokio versions, enable anything with the `tokio`
+        // target as well.
+        metadata.name().starts_with("runtime.")
+            || metadata.target().starts_with("tokio")
+            || WatchedSpanVisitor::is_watched(metadata)
+    }
+}
+
+impl<S> layer::Filter<S> for ConsoleFilter {
+    fn enabled(&self, metadata: &Metadata<'_>, _: &layer::Context<'_, S>) -> bool {
+        Self::is_enabled(metadata)
+    }
+
+    fn callsite_enabled(&self, metadata: &'static Metadata<'static>) -> Interest {
+        if Self::is_enabled(metadata) {
+            Interest::always()
+        } else {
+            Interest::never()
+        }
+    }
+}
@@ -0,0 +1,177 @@
+use pin_project_lite::pin_project;
+use std::{
+    future::Future,
+    mem::ManuallyDrop,
+    panic::Location,
+    pin::Pin,
+    sync::Arc,
+    task::{Context, Poll, RawWaker, RawWakerVTable, Waker},
+};
+
+pin_project! {
+    /// A future which is reported to the console as if it were a task.
+    ///
+    /// Only futures which are spawned are instrumented by Tokio, so futures
+    /// which are polled by hand, or are one of several running in a
+    /// `select!` or `join!`, are normally invisible to the console. Wrapping
+    /// such a future in an `InstrumentedFuture` lists it alongside the tasks,
+    /// with the kind `future`, and records its polls and wakes in the same
+    /// way.
+    ///
+    /// The future appears in the console when it is created, and is
+    /// completed when it is dropped.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # async fn doc(mut rx: tokio::sync::mpsc::Receiver<()>) {
+    /// use console_subscriber::InstrumentFutureExt;
+    /// use std::time::Duration;
+    ///
+    /// tokio::select! {
+    ///     _ = rx.recv().instrumented("recv") => {}
+    ///     _ = tokio::time::sleep(Duration::from_secs(1)).instrumented("timeout") => {}
+    /// }
+    /// # }
+    /// ```
+    #[derive(Debug)]
+    pub struct InstrumentedFuture<F> {
+        #[pin]
+        inner: F,
+        span: tracing::Span,
+        waker: Option<Arc<FutureWaker>>,
+    }
+}
+
+/// An extension trait for reporting futures to the console.
+pub trait InstrumentFutureExt: Future + Sized {
+    /// Wraps this future in an [`InstrumentedFuture`] with the given name,
+    /// reporting it to the console as if it were a task spawned at the
+    /// caller's location.
+    #[track_caller]
+    fn instrumented(self, name: &str) -> InstrumentedFuture<Self> {
+        InstrumentedFuture::new(self, name)
+    }
+}
+
+impl<F: Future> InstrumentFutureExt for F {}
+
+/// The waker which is passed to an instrumented future, which reports waker
+/// operations to the console before passing them on to the waker of the
+/// task polling it.
+#[derive(Debug)]
+struct FutureWaker {
+    waker: Waker,
+    id: u64,
+}
+
+// === impl InstrumentedFuture ===
+
+impl<F> InstrumentedFuture<F> {
+    /// Wraps `inner`, reporting it to the console with the given name, as if
+    /// it were a task spawned at the caller's location.
+    #[track_caller]
+    pub fn new(inner: F, name: &str) -> Self {
+        let location = Location::caller();
+        let span = tracing::trace_span!(
+            target: "runtime::future",
+            parent: None,
+            "runtime.spawn",
+            kind = "future",
+            task.name = name,
+            loc.file = location.file(),
+            loc.line = location.line(),
+            loc.col = location.column(),
+        );
+        Self {
+            inner,
+            span,
+            waker: None,
+        }
+    }
+
+    /// Returns a reference to the wrapped future.
+    pub fn get_ref(&self) -> &F {
+        &self.inner
+    }
+
+    /// Returns a mutable reference to the wrapped future.
+    pub fn get_mut(&mut self) -> &mut F {
+        &mut self.inner
+    }
+
+    /// Consumes this wrapper, returning the wrapped future.
+    pub fn into_inner(self) -> F {
+        self.inner
+    }
+}
+
+impl<F: Future> Future for InstrumentedFuture<F> {
+    type Output = F::Output;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.project();
+        let _entered = this.span.enter();
+        // If the console isn't recording this future, there's nothing to
+        // report waker operations to.
+        let Some(id) = this.span.id() else {
+            return this.inner.poll(cx);
+        };
+
+        // The waker is only replaced when the future is moved to another
+        // task, so that a future which is polled repeatedly by the same task
+        // doesn't allocate on every poll.
+        let waker = match this.waker {
+            Some(waker) if waker.waker.will_wake(cx.waker()) => waker,
+            waker => waker.insert(Arc::new(FutureWaker {
+                waker: cx.waker().clone(),
+                id: id.into_u64(),
+            })),
+        };
+        // Safety: the `Arc` outlives the borrowed waker, which is never
+        // dropped, so the reference count is only changed by clones of it.
+        let borrowed = ManuallyDrop::new(unsafe { Waker::from_raw(FutureWaker::raw(waker)) });
+        this.inner.poll(&mut Context::from_waker(&borrowed))
+    }
+}
+
+// === impl FutureWaker ===
+
+impl FutureWaker {
+    const VTABLE: RawWakerVTable =
+        RawWakerVTable::new(Self::clone, Self::wake, Self::wake_by_ref, Self::drop);
+
+    fn raw(this: &Arc<Self>) -> RawWaker {
+        RawWaker::new(Arc::as_ptr(this).cast(), &Self::VTABLE)
+    }
+
+    fn emit(&self, op: &'static str) {
+        tracing::trace!(target: "runtime::waker", op, task.id = self.id);
+    }
+
+    unsafe fn clone(data: *const ()) -> RawWaker {
+        let this = &*data.cast::<Self>();
+        this.emit("waker.clone");
+        Arc::increment_strong_count(data.cast::<Self>());
+        RawWaker::new(data, &Self::VTABLE)
+    }
+
+    unsafe fn wake(data: *const ()) {
+        // Waking by value consumes the waker without dropping it, which the
+        // console already counts as a drop.
+        let this = Arc::from_raw(data.cast::<Self>());
+        this.emit("waker.wake");
+        this.waker.wake_by_ref();
+    }
+
+    unsafe fn wake_by_ref(data: *const ()) {
+        let this = &*data.cast::<Self>();
+        this.emit("waker.wake_by_ref");
+        this.waker.wake_by_ref();
+    }
+
+    unsafe fn drop(data: *const ()) {
+        let this = Arc::from_raw(data.cast::<Self>());
+        this.emit("waker.drop");
+    }
+}
@@ -0,0 +1,191 @@
+//! Choosing which tasks record histograms of their poll and scheduled
+//! durations.
+//!
+//! Every task records counters of its polls, wakes, and busy time, but the
+//! histograms shown in a task's details are much larger. Applications which
+//! spawn hundreds of thousands of tiny tasks can limit which tasks record
+//! them with [`Builder::task_histograms`] and
+//! [`Builder::max_histogram_tasks`].
+//!
+//! [`Builder::task_histograms`]: crate::Builder::task_histograms
+//! [`Builder::max_histogram_tasks`]: crate::Builder::max_histogram_tasks
+use std::{
+    fmt,
+    sync::{
+        atomic::{AtomicUsize, Ordering::*},
+        Arc,
+    },
+};
+
+use console_api as proto;
+
+/// A newly spawned task, as seen by the filter set with
+/// [`Builder::task_histograms`].
+///
+/// [`Builder::task_histograms`]: crate::Builder::task_histograms
+#[derive(Debug)]
+pub struct SpawnedTask<'a> {
+    name: Option<&'a str>,
+    location: Option<&'a proto::Location>,
+}
+
+/// Decides whether newly spawned tasks record histograms.
+#[derive(Debug, Default)]
+pub(crate) struct HistogramTasks {
+    filter: Option<HistogramFilter>,
+    /// The maximum number of tasks which may record histograms at once, if
+    /// this is limited.
+    max: Option<usize>,
+    /// The number of tasks which currently record histograms.
+    live: Arc<AtomicUsize>,
+}
+
+/// A filter set with [`Builder::task_histograms`].
+///
+/// [`Builder::task_histograms`]: crate::Builder::task_histograms
+#[derive(Clone)]
+pub(crate) struct HistogramFilter(Arc<FilterFn>);
+
+type FilterFn = dyn Fn(&SpawnedTask<'_>) -> bool + Send + Sync;
+
+/// Allows a task to record histograms, counting it towards the maximum
+/// number of tasks which may do so until it is dropped.
+#[derive(Debug, Default)]
+pub(crate) struct HistogramPermit {
+    live: Option<Arc<AtomicUsize>>,
+}
+
+// === impl SpawnedTask ===
+
+impl<'a> SpawnedTask<'a> {
+    pub(crate) fn new(fields: &'a [proto::Field], location: Option<&'a proto::Location>) -> Self {
+        let name =
+            fields.iter().find_map(
+                |field| match (field.name.as_ref()?, field.value.as_ref()?) {
+                    (
+                        proto::field::Name::StrName(name),
+                        proto::field::Value::StrVal(value) | proto::field::Value::DebugVal(value),
+                    ) if name == "task.name" => Some(value.as_str()),
+                    _ => None,
+                },
+            );
+        Self { name, location }
+    }
+
+    /// Returns the task's name, if it was given one.
+    pub fn name(&self) -> Option<&'a str> {
+        self.name
+    }
+
+    /// Returns the path of the source file the task was spawned from, if it
+    /// is known.
+    pub fn file(&self) -> Option<&'a str> {
+        self.location?.file.as_deref()
+    }
+
+    /// Returns the line the task was spawned from, if it is known.
+    pub fn line(&self) -> Option<u32> {
+        self.location?.line
+    }
+
+    /// Returns the column the task was spawned from, if it is known.
+    pub fn column(&self) -> Option<u32> {
+        self.location?.column
+    }
+}
+
+// === impl HistogramTasks ===
+
+impl HistogramTasks {
+    pub(crate) fn new(filter: Option<HistogramFilter>, max: Option<usize>) -> Self {
+        Self {
+            filter,
+            max,
+            live: Arc::default(),
+        }
+    }
+
+    /// Returns a permit for `task` to record histograms, or `None` if it
+    /// doesn't match the filter or too many tasks already record them.
+    pub(crate) fn admit(&self, task: &SpawnedTask<'_>) -> Option<HistogramPermit> {
+        if let Some(HistogramFilter(filter)) = &self.filter {
+            if !filter(task) {
+                return None;
+            }
+        }
+        let Some(max) = self.max else {
+            return Some(HistogramPermit::default());
+        };
+        self.live
+            .fetch_update(AcqRel, Acquire, |live| (live < max).then_some(live + 1))
+            .ok()?;
+        Some(HistogramPermit {
+            live: Some(self.live.clone()),
+        })
+    }
+}
+
+// === impl HistogramFilter ===
+
+impl HistogramFilter {
+    pub(crate) fn new<F>(filter: F) -> Self
+    where
+        F: Fn(&SpawnedTask<'_>) -> bool + Send + Sync + 'static,
+    {
+        Self(Arc::new(filter))
+    }
+}
+
+impl fmt::Debug for HistogramFilter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("HistogramFilter").finish_non_exhaustive()
+    }
+}
+
+// === impl HistogramPermit ===
+
+impl Drop for HistogramPermit {
+    fn drop(&mut self) {
+        if let Some(live) = &self.live {
+            live.fetch_sub(1, Release);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn task(name: &str) -> Vec<proto::Field> {
+        vec![proto::Field {
+            name: Some(proto::field::Name::StrName("task.name".to_string())),
+            value: Some(proto::field::Value::StrVal(name.to_string())),
+            ..Default::default()
+        }]
+    }
+
+    #[test]
+    fn filters_by_name() {
+        let tasks = HistogramTasks::new(
+            Some(HistogramFilter::new(|task| task.name() == Some("big"))),
+            None,
+        );
+        assert!(tasks.admit(&SpawnedTask::new(&task("big"), None)).is_some());
+        assert!(tasks
+            .admit(&SpawnedTask::new(&task("tiny"), None))
+            .is_none());
+        assert!(tasks.admit(&SpawnedTask::new(&[], None)).is_none());
+    }
+
+    #[test]
+    fn permits_are_returned_when_dropped() {
+        let tasks = HistogramTasks::new(None, Some(2));
+        let first = tasks.admit(&SpawnedTask::new(&[], None));
+        let second = tasks.admit(&SpawnedTask::new(&[], None));
+        assert!(first.is_some() && second.is_some());
+        assert!(tasks.admit(&SpawnedTask::new(&[], None)).is_none());
+
+        drop(first);
+        assert!(tasks.admit(&SpawnedTask::new(&[], None)).is_some());
+    }
+}
@@ -0,0 +1,336 @@
+//! I/O resources which are instrumented for the console.
+//!
+//! Tokio's I/O resources do not emit any instrumentation of their own, so the
+//! types in this module wrap them and report each one to the console as a
+//! resource. For each direction (reading and writing), the console shows:
+//!
+//! - the number of bytes transferred
+//! - the number of completed operations, and how many times they were polled
+//! - how long operations spent waiting for the resource to become ready
+//!
+//! Long waits for write readiness usually mean that the peer is not reading
+//! fast enough, so the console warns about resources which spend a long time
+//! waiting to be written to.
+//!
+//! [`Instrumented`] wraps any stream implementing [`AsyncRead`] and
+//! [`AsyncWrite`], such as a `TcpStream` or `UnixStream`, and [`UdpSocket`]
+//! wraps a [`tokio::net::UdpSocket`].
+//!
+//! [`AsyncRead`]: tokio::io::AsyncRead
+//! [`AsyncWrite`]: tokio::io::AsyncWrite
+use crate::sync::Mutex;
+use std::{
+    future, io,
+    net::SocketAddr,
+    panic::Location,
+    pin::Pin,
+    task::{Context, Poll},
+    time::Instant,
+};
+use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
+
+/// An I/O stream which is reported to the console as a resource.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// # async fn docs() -> std::io::Result<()> {
+/// use console_subscriber::io::Instrumented;
+/// use tokio::{io::AsyncWriteExt, net::TcpStream};
+///
+/// let mut stream = Instrumented::new(TcpStream::connect("127.0.0.1:8080").await?);
+/// stream.write_all(b"hello world").await?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct Instrumented<T> {
+    inner: T,
+    io: Io,
+}
+
+/// A UDP socket which is reported to the console as a resource.
+///
+/// This wraps a [`tokio::net::UdpSocket`], and provides the subset of its
+/// API which sends and receives datagrams.
+#[derive(Debug)]
+pub struct UdpSocket {
+    inner: tokio::net::UdpSocket,
+    io: Io,
+}
+
+/// The instrumentation for an I/O resource.
+#[derive(Debug)]
+struct Io {
+    span: tracing::Span,
+    read: Mutex<Op>,
+    write: Mutex<Op>,
+}
+
+/// The in-progress operation in one direction.
+#[derive(Debug, Default)]
+struct Op {
+    polls: u64,
+    waiting_since: Option<Instant>,
+}
+
+#[derive(Debug, Copy, Clone)]
+enum Direction {
+    Read,
+    Write,
+}
+
+// === impl Instrumented ===
+
+impl<T> Instrumented<T> {
+    /// Wraps `inner`, reporting it to the console as a resource created at
+    /// the caller's location.
+    #[track_caller]
+    pub fn new(inner: T) -> Self {
+        Self {
+            inner,
+            io: Io::new(short_type_name::<T>()),
+        }
+    }
+
+    /// Returns a reference to the wrapped I/O resource.
+    pub fn get_ref(&self) -> &T {
+        &self.inner
+    }
+
+    /// Returns a mutable reference to the wrapped I/O resource.
+    pub fn get_mut(&mut self) -> &mut T {
+        &mut self.inner
+    }
+
+    /// Consumes this wrapper, returning the wrapped I/O resource.
+    pub fn into_inner(self) -> T {
+        self.inner
+    }
+}
+
+impl<T: AsyncRead + Unpin> AsyncRead for Instrumented<T> {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        let this = self.get_mut();
+        let filled = buf.filled().len();
+        let poll = Pin::new(&mut this.inner).poll_read(cx, buf);
+        let read = buf.filled().len() - filled;
+        this.io.record(Direction::Read, poll, |_| read)
+    }
+}
+
+impl<T: AsyncWrite + Unpin> AsyncWrite for Instrumented<T> {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        let this = self.get_mut();
+        let poll = Pin::new(&mut this.inner).poll_write(cx, buf);
+        this.io.record(Direction::Write, poll, |written| *written)
+    }
+
+    fn poll_write_vectored(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        bufs: &[io::IoSlice<'_>],
+    ) -> Poll<io::Result<usize>> {
+        let this = self.get_mut();
+        let poll = Pin::new(&mut this.inner).poll_write_vectored(cx, bufs);
+        this.io.record(Direction::Write, poll, |written| *written)
+    }
+
+    fn is_write_vectored(&self) -> bool {
+        self.inner.is_write_vectored()
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.get_mut().inner).poll_flush(cx)
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.get_mut().inner).poll_shutdown(cx)
+    }
+}
+
+// === impl UdpSocket ===
+
+impl UdpSocket {
+    /// Wraps `socket`, reporting it to the console as a resource created at
+    /// the caller's location.
+    #[track_caller]
+    pub fn new(socket: tokio::net::UdpSocket) -> Self {
+        Self {
+            inner: socket,
+            io: Io::new("UdpSocket"),
+        }
+    }
+
+    /// Returns a reference to the wrapped socket.
+    pub fn get_ref(&self) -> &tokio::net::UdpSocket {
+        &self.inner
+    }
+
+    /// Consumes this wrapper, returning the wrapped socket.
+    pub fn into_inner(self) -> tokio::net::UdpSocket {
+        self.inner
+    }
+
+    /// Returns the local address that this socket is bound to.
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.inner.local_addr()
+    }
+
+    /// Sends data on the socket to the remote address that the socket is
+    /// connected to.
+    ///
+    /// See [`tokio::net::UdpSocket::send`] for details.
+    pub async fn send(&self, buf: &[u8]) -> io::Result<usize> {
+        future::poll_fn(|cx| {
+            let poll = self.inner.poll_send(cx, buf);
+            self.io.record(Direction::Write, poll, |sent| *sent)
+        })
+        .await
+    }
+
+    /// Sends data on the socket to the given address.
+    ///
+    /// See [`tokio::net::UdpSocket::send_to`] for details.
+    pub async fn send_to(&self, buf: &[u8], target: SocketAddr) -> io::Result<usize> {
+        future::poll_fn(|cx| {
+            let poll = self.inner.poll_send_to(cx, buf, target);
+            self.io.record(Direction::Write, poll, |sent| *sent)
+        })
+        .await
+    }
+
+    /// Receives a single datagram message on the socket from the remote
+    /// address to which it is connected.
+    ///
+    /// See [`tokio::net::UdpSocket::recv`] for details.
+    pub async fn recv(&self, buf: &mut [u8]) -> io::Result<usize> {
+        let mut buf = ReadBuf::new(buf);
+        future::poll_fn(|cx| {
+            let poll = self.inner.poll_recv(cx, &mut buf);
+            let received = buf.filled().len();
+            self.io.record(Direction::Read, poll, |_| received)
+        })
+        .await?;
+        Ok(buf.filled().len())
+    }
+
+    /// Receives a single datagram message on the socket, returning the number
+    /// of bytes read and the address it came from.
+    ///
+    /// See [`tokio::net::UdpSocket::recv_from`] for details.
+    pub async fn recv_from(&self, buf: &mut [u8]) -> io::Result<(usize, SocketAddr)> {
+        let mut buf = ReadBuf::new(buf);
+        let addr = future::poll_fn(|cx| {
+            let poll = self.inner.poll_recv_from(cx, &mut buf);
+            let received = buf.filled().len();
+            self.io.record(Direction::Read, poll, |_| received)
+        })
+        .await?;
+        Ok((buf.filled().len(), addr))
+    }
+}
+
+// === impl Io ===
+
+impl Io {
+    #[track_caller]
+    fn new(concrete_type: &'static str) -> Self {
+        let location = Location::caller();
+        let span = tracing::trace_span!(
+            parent: None,
+            "runtime.resource",
+            concrete_type,
+            kind = "io",
+            is_internal = false,
+            inherits_child_attrs = false,
+            loc.file = location.file(),
+            loc.line = location.line(),
+            loc.col = location.column(),
+        );
+        Self {
+            span,
+            read: Mutex::new(Op::default()),
+            write: Mutex::new(Op::default()),
+        }
+    }
+
+    /// Records a poll of the resource in the given direction, returning the
+    /// result of the poll.
+    ///
+    /// If an operation is cancelled while it is waiting for the resource to
+    /// become ready, its wait is attributed to the next operation in the same
+    /// direction.
+    fn record<T>(
+        &self,
+        direction: Direction,
+        poll: Poll<io::Result<T>>,
+        bytes: impl FnOnce(&T) -> usize,
+    ) -> Poll<io::Result<T>> {
+        let mut op = match direction {
+            Direction::Read => self.read.lock(),
+            Direction::Write => self.write.lock(),
+        };
+        op.polls += 1;
+        match poll {
+            Poll::Pending => {
+                if op.waiting_since.is_none() {
+                    op.waiting_since = Some(Instant::now());
+                    drop(op);
+                    self.emit(direction, 0, 0, 0, true);
+                }
+            }
+            Poll::Ready(ref result) => {
+                let waited = op
+                    .waiting_since
+                    .take()
+                    .map(|since| since.elapsed())
+                    .unwrap_or_default();
+                let polls = std::mem::take(&mut op.polls);
+                drop(op);
+                let bytes = result.as_ref().map(bytes).unwrap_or(0);
+                self.emit(
+                    direction,
+                    bytes as u64,
+                    polls,
+                    waited.as_nanos() as u64,
+                    false,
+                );
+            }
+        }
+        poll
+    }
+
+    fn emit(&self, direction: Direction, bytes: u64, polls: u64, waited_ns: u64, pending: bool) {
+        let direction = match direction {
+            Direction::Read => "read",
+            Direction::Write => "write",
+        };
+        self.span.in_scope(|| {
+            tracing::event!(
+                target: "runtime::resource::io",
+                tracing::Level::TRACE,
+                io.direction = direction,
+                io.bytes = bytes,
+                io.polls = polls,
+                io.waited_ns = waited_ns,
+                io.pending = pending,
+            );
+        });
+    }
+}
+
+/// Returns the name of `T` without its module path or generic parameters.
+fn short_type_name<T>() -> &'static str {
+    let name = std::any::type_name::<T>();
+    let path = name.split('<').next().unwrap_or(name);
+    path.rsplit("::").next().unwrap_or(path)
+}
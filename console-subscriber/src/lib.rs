@@ -6,19 +6,26 @@ use std::{
     cell::RefCell,
     fmt,
     net::{IpAddr, Ipv4Addr},
+    panic::{self, AssertUnwindSafe},
     sync::{
-        atomic::{AtomicUsize, Ordering},
-        Arc,
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        Arc, OnceLock,
     },
     time::{Duration, Instant},
 };
 use thread_local::ThreadLocal;
 #[cfg(unix)]
 use tokio::net::UnixListener;
-use tokio::sync::{mpsc, oneshot};
+use tokio::{
+    io::{AsyncRead, AsyncWrite},
+    sync::{mpsc, oneshot},
+};
 #[cfg(unix)]
-use tokio_stream::wrappers::UnixListenerStream;
+use tokio_stream::wrappers::{TcpListenerStream, UnixListenerStream};
+use tokio_stream::Stream;
+use tonic::transport::server::Connected;
 use tracing_core::{
+    dispatcher::WeakDispatch,
     span::{self, Id},
     subscriber::{self, Subscriber},
     Metadata,
@@ -30,25 +37,56 @@ use tracing_subscriber::{
 };
 
 mod aggregator;
+mod alloc;
 mod attribute;
+mod audit;
 mod builder;
 mod callsites;
+pub mod channel;
+mod extension;
+mod filter;
+mod future;
+mod histograms;
+pub mod io;
 mod record;
+mod select;
 mod stack;
 mod stats;
 pub(crate) mod sync;
+#[cfg(unix)]
+mod systemd;
 mod visitors;
+mod watch;
 
 pub use aggregator::Aggregator;
+#[cfg(feature = "alloc-tracking")]
+pub use alloc::TrackingAllocator;
 pub use builder::{Builder, ServerAddr};
 use callsites::Callsites;
+pub use extension::set_task_extension;
+pub use filter::{ConsoleFilter, FilteredConsoleLayer};
+pub use future::{InstrumentFutureExt, InstrumentedFuture};
+use histograms::HistogramTasks;
+pub use histograms::SpawnedTask;
 use record::Recorder;
+pub use select::SelectTimer;
 use stack::SpanStack;
-use visitors::{AsyncOpVisitor, ResourceVisitor, ResourceVisitorResult, TaskVisitor, WakerVisitor};
+use visitors::{
+    AsyncOpVisitor, RedactFields, ResourceVisitor, ResourceVisitorResult, TaskVisitor,
+    WakerVisitor, WatchedSpanVisitor,
+};
 
 pub use builder::{init, spawn};
 
-use crate::visitors::{PollOpVisitor, StateUpdateVisitor};
+#[doc(hidden)]
+pub mod __private {
+    pub use tracing;
+}
+
+use crate::visitors::{
+    ChannelRecvVisitor, ExtensionVisitor, IoVisitor, PollOpVisitor, SelectVisitor,
+    StateUpdateVisitor,
+};
 
 /// A [`ConsoleLayer`] is a [`tracing_subscriber::Layer`] that records [`tracing`]
 /// spans and events emitted by the async runtime.
@@ -64,9 +102,9 @@ pub struct ConsoleLayer {
     current_spans: ThreadLocal<RefCell<SpanStack>>,
     tx: mpsc::Sender<Event>,
     shared: Arc<Shared>,
-    /// When the channel capacity goes under this number, a flush in the aggregator
-    /// will be triggered.
-    flush_under_capacity: usize,
+    /// When this many events are waiting in the channel, a flush in the
+    /// aggregator will be triggered.
+    flush_watermark: usize,
 
     /// Set of callsites for spans representing spawned tasks.
     ///
@@ -112,13 +150,32 @@ pub struct ConsoleLayer {
     /// TODO: Take some time to determine more reasonable numbers
     async_op_state_update_callsites: Callsites<32>,
 
+    /// Set of callsites for events representing completed `select!` branches.
+    ///
+    /// These are emitted by [`SelectTimer`], so there will usually only be
+    /// one of these.
+    select_callsites: Callsites<8>,
+
+    /// Set of callsites for events attaching payloads to tasks.
+    ///
+    /// These are emitted by [`set_task_extension`], so there will usually
+    /// only be one of these.
+    extension_callsites: Callsites<8>,
+
+    /// Set of callsites for events representing values received from channel
+    /// resources.
+    channel_recv_callsites: Callsites<8>,
+
+    /// Set of callsites for events representing polls of I/O resources.
+    io_callsites: Callsites<8>,
+
+    /// Set of callsites for spans the application has asked to watch with
+    /// [`watch_span!`].
+    watched_span_callsites: Callsites<32>,
+
     /// A sink to record all events to a file.
     recorder: Option<Recorder>,
 
-    /// Used to anchor monotonic timestamps to a base `SystemTime`, to produce a
-    /// timestamp that can be sent over the wire or recorded to JSON.
-    base_time: stats::TimeAnchor,
-
     /// Maximum value for the poll time histogram.
     ///
     /// By default, this is one second.
@@ -128,6 +185,26 @@ pub struct ConsoleLayer {
     ///
     /// By default, this is one second.
     max_scheduled_duration_nanos: u64,
+
+    /// Precision of the poll and scheduled time histograms.
+    ///
+    /// By default, this is two significant figures.
+    histogram_significant_figures: u8,
+
+    /// The most verbose level of task spans which are recorded as tasks.
+    task_level: tracing_subscriber::filter::LevelFilter,
+
+    /// Maximum number of fields recorded for each task.
+    max_fields_per_task: usize,
+
+    /// Maximum length of the string values of task fields.
+    max_field_value_len: usize,
+
+    /// Replaces the values of sensitive task and span fields, if set.
+    redact_fields: Option<RedactFields>,
+
+    /// Decides which tasks record poll and scheduled duration histograms.
+    histogram_tasks: HistogramTasks,
 }
 
 /// A gRPC [`Server`] that implements the [`tokio-console` wire format][wire].
@@ -144,8 +221,13 @@ pub struct ConsoleLayer {
 pub struct Server {
     subscribe: mpsc::Sender<Command>,
     addr: ServerAddr,
+    #[cfg(unix)]
+    socket_activation: bool,
     aggregator: Option<Aggregator>,
+    shared: Arc<Shared>,
     client_buffer: usize,
+    read_only: bool,
+    audit_log: sync::Mutex<audit::AuditLog>,
 }
 
 pub(crate) trait ToProto {
@@ -154,12 +236,29 @@ pub(crate) trait ToProto {
 }
 
 /// State shared between the `ConsoleLayer` and the `Aggregator` task.
-#[derive(Debug, Default)]
+#[derive(Debug)]
 struct Shared {
     /// Used to notify the aggregator task when the event buffer should be
     /// flushed.
     flush: aggregator::Flush,
 
+    /// Events which didn't fit in the event buffer before the aggregator task
+    /// started draining it.
+    startup: aggregator::StartupBuffer,
+
+    /// Whether the aggregator task is still running.
+    aggregator: aggregator::Liveness,
+
+    /// Set once the layer has warned that the aggregator task isn't running.
+    warned_inactive: AtomicBool,
+
+    /// Set once one of the layer's callbacks has panicked. From then on, the
+    /// layer ignores every callback, since its state may be inconsistent.
+    poisoned: AtomicBool,
+
+    /// A counter of how many times the layer's callbacks have panicked.
+    layer_panics: AtomicUsize,
+
     /// A counter of how many task events were dropped because the event buffer
     /// was at capacity.
     dropped_tasks: AtomicUsize,
@@ -171,22 +270,89 @@ struct Shared {
     /// A counter of how many resource events were dropped because the event buffer
     /// was at capacity.
     dropped_resources: AtomicUsize,
+
+    /// A counter of how many watched span events were dropped because the
+    /// event buffer was at capacity.
+    dropped_spans: AtomicUsize,
+
+    /// The threads which have polled the layer's tasks.
+    threads: stats::Threads,
+
+    /// The waker counts of completed tasks whose wakers outlived them.
+    lingering_wakers: aggregator::LingeringWakers,
+
+    /// The number of live tasks, and the most that have been live at once.
+    concurrent_tasks: aggregator::ConcurrentTasks,
+
+    /// The subscriber which the layer was registered with, which the
+    /// aggregator logs warnings about unhealthy tasks to.
+    dispatch: OnceLock<WeakDispatch>,
+}
+
+impl Shared {
+    fn inactive_reason(&self) -> &'static str {
+        self.aggregator
+            .inactive_reason()
+            .unwrap_or("the aggregator task is not running")
+    }
 }
 
 struct Watch<T>(mpsc::Sender<Result<T, tonic::Status>>);
 
 enum Command {
-    Instrument(Watch<proto::instrument::Update>),
+    Instrument(InstrumentRequest),
     WatchTaskDetail(WatchRequest<proto::tasks::TaskDetails>),
+    WatchTaskDetailsSet(WatchSetRequest),
+    WatchTaskGroups(Watch<proto::tasks::TaskGroups>),
     WatchState(Watch<proto::instrument::State>),
-    Pause,
+    Pause { freeze_time: bool },
     Resume,
+    ListTasks(ListTasksRequest),
+}
+
+/// A request to watch updates.
+struct InstrumentRequest {
+    watch: Watch<proto::instrument::Update>,
+    /// How long the client asked for closed data to be retained while it is
+    /// attached, if it asked.
+    retention: Option<Duration>,
+    /// The token of the last update the client received, if it is
+    /// reconnecting.
+    resume_token: Option<proto::instrument::ResumeToken>,
 }
 
 struct WatchRequest<T> {
     id: Id,
     stream_sender: oneshot::Sender<mpsc::Receiver<Result<T, tonic::Status>>>,
     buffer: usize,
+    options: DetailsOptions,
+}
+
+/// A request to watch the details of every task matching a set of IDs or a
+/// spawn location.
+struct WatchSetRequest {
+    ids: Vec<Id>,
+    location: Option<proto::Location>,
+    watch: Watch<proto::tasks::TaskDetailsSet>,
+    options: DetailsOptions,
+}
+
+/// A request for a single snapshot of the tasks.
+struct ListTasksRequest {
+    location: Option<proto::Location>,
+    include_completed: bool,
+    /// The maximum number of tasks to list, if limited.
+    limit: Option<usize>,
+    response: oneshot::Sender<proto::instrument::ListTasksResponse>,
+}
+
+/// How a client asked for task details to be sent.
+#[derive(Clone, Copy, Debug)]
+struct DetailsOptions {
+    /// How often to send updates, if not at the publish interval.
+    interval: Option<Duration>,
+    /// The precision to send histograms with, if not their full precision.
+    significant_figures: Option<u8>,
 }
 
 #[derive(Debug)]
@@ -197,7 +363,9 @@ enum Event {
         metadata: &'static Metadata<'static>,
         stats: Arc<stats::TaskStats>,
         fields: Vec<proto::Field>,
+        omitted_fields: u64,
         location: Option<proto::Location>,
+        scheduler: proto::tasks::task::Scheduler,
     },
     Resource {
         id: span::Id,
@@ -226,6 +394,28 @@ enum Event {
 
         stats: Arc<stats::AsyncOpStats>,
     },
+    TimerFired {
+        resource_id: span::Id,
+        overrun: Duration,
+    },
+    SelectBranch {
+        location: proto::Location,
+        branch: usize,
+        branches: usize,
+        waited: Duration,
+    },
+    TaskExtension {
+        id: span::Id,
+        extension: prost_types::Any,
+    },
+    WatchedSpan {
+        id: span::Id,
+        metadata: &'static Metadata<'static>,
+        fields: Vec<proto::Field>,
+        omitted_fields: u64,
+        task_id: Option<span::Id>,
+        stats: Arc<stats::WatchedSpanStats>,
+    },
 }
 
 #[derive(Clone, Debug, Copy, Serialize)]
@@ -247,6 +437,21 @@ impl ConsoleLayer {
         Self::builder().build()
     }
 
+    /// Returns a `ConsoleLayer` built with the default settings, restricted
+    /// by a [`ConsoleFilter`] so that it only observes the spans and events
+    /// it records.
+    ///
+    /// Note: these defaults do *not* include values provided via the
+    /// environment variables specified in [`Builder::with_default_env`].
+    ///
+    /// See also [`Builder::build_filtered`].
+    pub fn filtered<S>() -> (FilteredConsoleLayer<S>, Server)
+    where
+        S: Subscriber + for<'a> LookupSpan<'a>,
+    {
+        Self::builder().build_filtered()
+    }
+
     /// Returns a [`Builder`] for configuring a `ConsoleLayer`.
     ///
     /// Note that the returned builder does *not* include values provided via
@@ -268,40 +473,68 @@ impl ConsoleLayer {
         let base_time = stats::TimeAnchor::new();
         tracing::debug!(
             config.event_buffer_capacity,
+            ?config.event_buffer_high_watermark,
             config.client_buffer_capacity,
+            config.startup_buffer_capacity,
             ?config.publish_interval,
             ?config.retention,
+            ?config.max_retention,
             ?config.server_addr,
             ?config.recording_path,
             ?config.filter_env_var,
             ?config.poll_duration_max,
             ?config.scheduled_duration_max,
+            config.histogram_significant_figures,
             ?base_time,
             "configured console subscriber"
         );
 
         let (tx, events) = mpsc::channel(config.event_buffer_capacity);
         let (subscribe, rpcs) = mpsc::channel(256);
-        let shared = Arc::new(Shared::default());
+        let shared = Arc::new(Shared {
+            flush: aggregator::Flush::default(),
+            startup: aggregator::StartupBuffer::new(config.startup_buffer_capacity),
+            aggregator: aggregator::Liveness::default(),
+            warned_inactive: AtomicBool::new(false),
+            poisoned: AtomicBool::new(false),
+            layer_panics: AtomicUsize::new(0),
+            dropped_tasks: AtomicUsize::new(0),
+            dropped_async_ops: AtomicUsize::new(0),
+            dropped_resources: AtomicUsize::new(0),
+            dropped_spans: AtomicUsize::new(0),
+            threads: stats::Threads::new(),
+            lingering_wakers: aggregator::LingeringWakers::default(),
+            concurrent_tasks: aggregator::ConcurrentTasks::default(),
+            dispatch: OnceLock::new(),
+        });
         let aggregator = Aggregator::new(events, rpcs, &config, shared.clone(), base_time.clone());
-        // Conservatively, start to trigger a flush when half the channel is full.
-        // This tries to reduce the chance of losing events to a full channel.
-        let flush_under_capacity = config.event_buffer_capacity / 2;
+        // Conservatively, start to trigger a flush when half the channel is full,
+        // unless configured otherwise. This tries to reduce the chance of
+        // losing events to a full channel.
+        let flush_watermark = config
+            .event_buffer_high_watermark
+            .unwrap_or(config.event_buffer_capacity / 2)
+            .min(config.event_buffer_capacity);
         let recorder = config
             .recording_path
             .as_ref()
-            .map(|path| Recorder::new(path).expect("creating recorder"));
+            .map(|path| Recorder::new(path, base_time).expect("creating recorder"));
         let server = Server {
             aggregator: Some(aggregator),
+            shared: shared.clone(),
             addr: config.server_addr,
+            #[cfg(unix)]
+            socket_activation: config.socket_activation,
             subscribe,
             client_buffer: config.client_buffer_capacity,
+            read_only: config.read_only,
+            audit_log: Default::default(),
         };
         let layer = Self {
             current_spans: ThreadLocal::new(),
             tx,
             shared,
-            flush_under_capacity,
+            flush_watermark,
             spawn_callsites: Callsites::default(),
             waker_callsites: Callsites::default(),
             resource_callsites: Callsites::default(),
@@ -310,10 +543,23 @@ impl ConsoleLayer {
             poll_op_callsites: Callsites::default(),
             resource_state_update_callsites: Callsites::default(),
             async_op_state_update_callsites: Callsites::default(),
+            select_callsites: Callsites::default(),
+            extension_callsites: Callsites::default(),
+            channel_recv_callsites: Callsites::default(),
+            io_callsites: Callsites::default(),
+            watched_span_callsites: Callsites::default(),
             recorder,
-            base_time,
             max_poll_duration_nanos: config.poll_duration_max.as_nanos() as u64,
             max_scheduled_duration_nanos: config.scheduled_duration_max.as_nanos() as u64,
+            histogram_significant_figures: config.histogram_significant_figures,
+            task_level: config.task_level,
+            max_fields_per_task: config.max_fields_per_task,
+            max_field_value_len: config.max_field_value_len,
+            redact_fields: config.redact_fields,
+            histogram_tasks: HistogramTasks::new(
+                config.histogram_filter,
+                config.max_histogram_tasks,
+            ),
         };
         (layer, server)
     }
@@ -338,6 +584,12 @@ impl ConsoleLayer {
     /// See also [`Builder::client_buffer_capacity`].
     pub const DEFAULT_CLIENT_BUFFER_CAPACITY: usize = 1024 * 4;
 
+    /// Default maximum number of events which are buffered when the event
+    /// channel fills up before the aggregator task starts draining it.
+    ///
+    /// See also [`Builder::startup_buffer_capacity`].
+    pub const DEFAULT_STARTUP_BUFFER_CAPACITY: usize = 1024 * 10;
+
     /// Default frequency for publishing events to clients.
     ///
     /// Note that methods like [`init`][`crate::init`] and [`spawn`][`crate::spawn`] will take the value
@@ -361,12 +613,42 @@ impl ConsoleLayer {
     /// [environment variable]: `Builder::with_default_env`
     pub const DEFAULT_RETENTION: Duration = Duration::from_secs(60 * 60);
 
+    /// By default, clients may request that completed spans are retained for
+    /// up to one hour while they are attached.
+    ///
+    /// Note that methods like [`init`][`crate::init`] and
+    /// [`spawn`][`crate::spawn`] will take the value from the
+    /// `TOKIO_CONSOLE_MAX_RETENTION` [environment variable] before falling
+    /// back on this default.
+    ///
+    /// See also [`Builder::max_retention`].
+    ///
+    /// [environment variable]: `Builder::with_default_env`
+    pub const DEFAULT_MAX_RETENTION: Duration = Duration::from_secs(60 * 60);
+
+    /// By default, up to eight clients may watch updates at once.
+    ///
+    /// Note that methods like [`init`][`crate::init`] and
+    /// [`spawn`][`crate::spawn`] will take the value from the
+    /// `TOKIO_CONSOLE_MAX_WATCHERS` [environment variable] before falling
+    /// back on this default.
+    ///
+    /// See also [`Builder::max_watchers`].
+    ///
+    /// [environment variable]: `Builder::with_default_env`
+    pub const DEFAULT_MAX_WATCHERS: usize = 8;
+
     /// The default maximum value for task poll duration histograms.
     ///
     /// Any poll duration exceeding this will be clamped to this value. By
-    /// default, the maximum poll duration is one second.
+    /// default, the maximum poll duration is one second. Note that methods
+    /// like [`init`][`crate::init`] and [`spawn`][`crate::spawn`] will take
+    /// the value from the `TOKIO_CONSOLE_POLL_DURATION_MAX` [environment
+    /// variable] before falling back on this default.
     ///
     /// See also [`Builder::poll_duration_histogram_max`].
+    ///
+    /// [environment variable]: `Builder::with_default_env`
     pub const DEFAULT_POLL_DURATION_MAX: Duration = Duration::from_secs(1);
 
     /// The default maximum value for the task scheduled duration histogram.
@@ -378,6 +660,52 @@ impl ConsoleLayer {
     /// See also [`Builder::scheduled_duration_histogram_max`].
     pub const DEFAULT_SCHEDULED_DURATION_MAX: Duration = Duration::from_secs(1);
 
+    /// The default precision of task poll and scheduled duration histograms,
+    /// in significant figures.
+    ///
+    /// Note that methods like [`init`][`crate::init`] and
+    /// [`spawn`][`crate::spawn`] will take the value from the
+    /// `TOKIO_CONSOLE_HISTOGRAM_SIGFIG` [environment variable]
+    /// before falling back on this default.
+    ///
+    /// See also [`Builder::histogram_significant_figures`].
+    ///
+    /// [environment variable]: `Builder::with_default_env`
+    pub const DEFAULT_HISTOGRAM_SIGNIFICANT_FIGURES: u8 = 2;
+
+    /// The default maximum number of fields recorded for each task.
+    ///
+    /// Any further fields are omitted. Note that methods like
+    /// [`init`][`crate::init`] and [`spawn`][`crate::spawn`] will take the
+    /// value from the `TOKIO_CONSOLE_MAX_FIELDS_PER_TASK` [environment
+    /// variable] before falling back on this default.
+    ///
+    /// See also [`Builder::max_fields_per_task`].
+    ///
+    /// [environment variable]: `Builder::with_default_env`
+    pub const DEFAULT_MAX_FIELDS_PER_TASK: usize = 32;
+
+    /// The default maximum length, in bytes, of the string values of task
+    /// fields.
+    ///
+    /// Longer values are truncated. Note that methods like
+    /// [`init`][`crate::init`] and [`spawn`][`crate::spawn`] will take the
+    /// value from the `TOKIO_CONSOLE_MAX_FIELD_VALUE_LEN` [environment
+    /// variable] before falling back on this default.
+    ///
+    /// See also [`Builder::max_field_value_len`].
+    ///
+    /// [environment variable]: `Builder::with_default_env`
+    pub const DEFAULT_MAX_FIELD_VALUE_LEN: usize = 1024;
+
+    /// Returns `false` if the [`Aggregator`] task has stopped, or panicked.
+    ///
+    /// Once it has, nothing this layer records reaches the [`Server`], and
+    /// clients trying to connect get an `UNAVAILABLE` error saying why.
+    pub fn is_active(&self) -> bool {
+        self.shared.aggregator.is_running()
+    }
+
     fn is_spawn(&self, meta: &'static Metadata<'static>) -> bool {
         self.spawn_callsites.contains(meta)
     }
@@ -390,6 +718,10 @@ impl ConsoleLayer {
         self.async_op_callsites.contains(meta)
     }
 
+    fn is_watched_span(&self, meta: &'static Metadata<'static>) -> bool {
+        self.watched_span_callsites.contains(meta)
+    }
+
     fn is_id_spawned<S>(&self, id: &span::Id, cx: &Context<'_, S>) -> bool
     where
         S: Subscriber + for<'a> LookupSpan<'a>,
@@ -430,6 +762,35 @@ impl ConsoleLayer {
             .cloned()
     }
 
+    /// Runs one of the layer's callbacks, catching any panic in it, so that a
+    /// bug in the layer never unwinds into the instrumented application's
+    /// spans and polls.
+    ///
+    /// Once a callback has panicked, the layer's state may be inconsistent,
+    /// so every later callback is skipped, returning `default`. This can't
+    /// help if the application is built with `panic = "abort"`.
+    fn isolate<T>(&self, default: T, callback: impl FnOnce() -> T) -> T {
+        if self.shared.poisoned.load(Ordering::Acquire) {
+            return default;
+        }
+
+        match panic::catch_unwind(AssertUnwindSafe(callback)) {
+            Ok(value) => value,
+            Err(_) => {
+                self.shared.layer_panics.fetch_add(1, Ordering::AcqRel);
+                // we can't trigger tracing events from here, so warn on
+                // stderr instead, but only once...
+                if !self.shared.poisoned.swap(true, Ordering::AcqRel) {
+                    eprintln!(
+                        "console-subscriber: a layer callback panicked; \
+                         tasks, resources, and async ops will no longer be recorded"
+                    );
+                }
+                default
+            }
+        }
+    }
+
     fn send_metadata(&self, dropped: &AtomicUsize, event: Event) -> bool {
         self.send_stats(dropped, move || (event, ())).is_some()
     }
@@ -449,22 +810,39 @@ impl ConsoleLayer {
                 Some(stats)
             }
             Err(TrySendError::Closed(_)) => {
-                // we should warn here eventually, but nop for now because we
-                // can't trigger tracing events...
+                // the aggregator task is gone, so nothing will be recorded
+                // from now on. we can't trigger tracing events from here, so
+                // warn on stderr instead, but only once...
+                if !self.shared.warned_inactive.swap(true, Ordering::AcqRel) {
+                    eprintln!(
+                        "console-subscriber: instrumentation inactive, {}; \
+                         tasks, resources, and async ops will no longer be recorded",
+                        self.shared.inactive_reason(),
+                    );
+                }
                 None
             }
             Err(TrySendError::Full(_)) => {
                 // this shouldn't happen, since we trigger a flush when
                 // approaching the high water line...but if the executor wait
                 // time is very high, maybe the aggregator task hasn't been
-                // polled yet. so... eek?!
-                dropped.fetch_add(1, Ordering::Release);
-                None
+                // polled yet. in that case, hold on to the events which
+                // register new tasks, resources, and async ops until it is.
+                let (event, stats) = mk_event();
+                if self.shared.startup.push(event) {
+                    Some(stats)
+                } else {
+                    dropped.fetch_add(1, Ordering::Release);
+                    None
+                }
             }
         };
 
-        let capacity = self.tx.capacity();
-        if capacity <= self.flush_under_capacity {
+        // The channel's capacity is shared between the layers' threads, so
+        // this is only an approximation of how many events are waiting.
+        let depth = self.tx.max_capacity() - self.tx.capacity();
+        self.shared.flush.record_depth(depth);
+        if depth >= self.flush_watermark {
             self.shared.flush.trigger();
         }
 
@@ -477,6 +855,29 @@ impl ConsoleLayer {
         }
     }
 
+    /// Records how long after its deadline the timer resource with `id` was
+    /// observed to have fired.
+    fn timer_fired<S>(&self, id: &Id, ctx: &Context<'_, S>)
+    where
+        S: Subscriber + for<'a> LookupSpan<'a>,
+    {
+        let deadline = ctx.span(id).and_then(|span| {
+            span.extensions()
+                .get::<Arc<stats::ResourceStats>>()?
+                .take_deadline()
+        });
+        if let Some(deadline) = deadline {
+            let overrun = Instant::now().saturating_duration_since(deadline);
+            self.send_stats(&self.shared.dropped_resources, || {
+                let event = Event::TimerFired {
+                    resource_id: id.clone(),
+                    overrun,
+                };
+                (event, ())
+            });
+        }
+    }
+
     fn state_update<S>(
         &self,
         id: &Id,
@@ -510,6 +911,7 @@ impl ConsoleLayer {
         };
 
         stats.update_attribute(id, &update);
+        stats.update_deadline(&update);
 
         if let Some(parent) = stats.parent_id.as_ref().and_then(|parent| ctx.span(parent)) {
             let exts = parent.extensions();
@@ -526,112 +928,233 @@ impl<S> Layer<S> for ConsoleLayer
 where
     S: Subscriber + for<'a> LookupSpan<'a>,
 {
-    fn register_callsite(&self, meta: &'static Metadata<'static>) -> subscriber::Interest {
-        if !meta.is_span() && !meta.is_event() {
-            return subscriber::Interest::never();
-        }
+    fn on_register_dispatch(&self, subscriber: &tracing_core::Dispatch) {
+        // a weak reference, since the subscriber owns the layer.
+        let _ = self.shared.dispatch.set(subscriber.downgrade());
+    }
 
-        let dropped = match (meta.name(), meta.target()) {
-            ("runtime.spawn", _) | ("task", "tokio::task") => {
-                self.spawn_callsites.insert(meta);
-                &self.shared.dropped_tasks
-            }
-            (_, "runtime::waker") | (_, "tokio::task::waker") => {
-                self.waker_callsites.insert(meta);
-                &self.shared.dropped_tasks
-            }
-            (ResourceVisitor::RES_SPAN_NAME, _) => {
-                self.resource_callsites.insert(meta);
-                &self.shared.dropped_resources
-            }
-            (AsyncOpVisitor::ASYNC_OP_SPAN_NAME, _) => {
-                self.async_op_callsites.insert(meta);
-                &self.shared.dropped_async_ops
-            }
-            ("runtime.resource.async_op.poll", _) => {
-                self.async_op_poll_callsites.insert(meta);
-                &self.shared.dropped_async_ops
-            }
-            (_, PollOpVisitor::POLL_OP_EVENT_TARGET) => {
-                self.poll_op_callsites.insert(meta);
-                &self.shared.dropped_async_ops
-            }
-            (_, StateUpdateVisitor::RE_STATE_UPDATE_EVENT_TARGET) => {
-                self.resource_state_update_callsites.insert(meta);
-                &self.shared.dropped_resources
-            }
-            (_, StateUpdateVisitor::AO_STATE_UPDATE_EVENT_TARGET) => {
-                self.async_op_state_update_callsites.insert(meta);
-                &self.shared.dropped_async_ops
+    fn register_callsite(&self, meta: &'static Metadata<'static>) -> subscriber::Interest {
+        // once the layer is poisoned, it ignores every callback, so there's
+        // no need for callsites registered from then on to call it.
+        self.isolate(subscriber::Interest::never(), || {
+            if !meta.is_span() && !meta.is_event() {
+                return subscriber::Interest::never();
             }
-            (_, _) => &self.shared.dropped_tasks,
-        };
 
-        self.send_metadata(dropped, Event::Metadata(meta));
-        subscriber::Interest::always()
+            let dropped = match (meta.name(), meta.target()) {
+                ("runtime.spawn", _) | ("task", "tokio::task") => {
+                    // Tasks whose spans are too verbose are never recorded,
+                    // so their polls and wakes are ignored too.
+                    if self.task_level >= *meta.level() {
+                        self.spawn_callsites.insert(meta);
+                    }
+                    &self.shared.dropped_tasks
+                }
+                (_, "runtime::waker") | (_, "tokio::task::waker") => {
+                    self.waker_callsites.insert(meta);
+                    &self.shared.dropped_tasks
+                }
+                (ResourceVisitor::RES_SPAN_NAME, _) => {
+                    self.resource_callsites.insert(meta);
+                    &self.shared.dropped_resources
+                }
+                (AsyncOpVisitor::ASYNC_OP_SPAN_NAME, _) => {
+                    self.async_op_callsites.insert(meta);
+                    &self.shared.dropped_async_ops
+                }
+                ("runtime.resource.async_op.poll", _) => {
+                    self.async_op_poll_callsites.insert(meta);
+                    &self.shared.dropped_async_ops
+                }
+                (_, PollOpVisitor::POLL_OP_EVENT_TARGET) => {
+                    self.poll_op_callsites.insert(meta);
+                    &self.shared.dropped_async_ops
+                }
+                (_, StateUpdateVisitor::RE_STATE_UPDATE_EVENT_TARGET) => {
+                    self.resource_state_update_callsites.insert(meta);
+                    &self.shared.dropped_resources
+                }
+                (_, StateUpdateVisitor::AO_STATE_UPDATE_EVENT_TARGET) => {
+                    self.async_op_state_update_callsites.insert(meta);
+                    &self.shared.dropped_async_ops
+                }
+                (_, ChannelRecvVisitor::CHANNEL_RECV_EVENT_TARGET) => {
+                    self.channel_recv_callsites.insert(meta);
+                    &self.shared.dropped_resources
+                }
+                (_, IoVisitor::IO_EVENT_TARGET) => {
+                    self.io_callsites.insert(meta);
+                    &self.shared.dropped_resources
+                }
+                (_, SelectVisitor::SELECT_EVENT_TARGET) => {
+                    self.select_callsites.insert(meta);
+                    &self.shared.dropped_tasks
+                }
+                (_, ExtensionVisitor::EXTENSION_EVENT_TARGET) => {
+                    self.extension_callsites.insert(meta);
+                    &self.shared.dropped_tasks
+                }
+                _ if WatchedSpanVisitor::is_watched(meta) => {
+                    self.watched_span_callsites.insert(meta);
+                    &self.shared.dropped_spans
+                }
+                (_, _) => &self.shared.dropped_tasks,
+            };
+
+            self.send_metadata(dropped, Event::Metadata(meta));
+            subscriber::Interest::always()
+        })
     }
 
     fn on_new_span(&self, attrs: &span::Attributes<'_>, id: &span::Id, ctx: Context<'_, S>) {
-        let metadata = attrs.metadata();
-        if self.is_spawn(metadata) {
-            let at = Instant::now();
-            let mut task_visitor = TaskVisitor::new(metadata.into());
-            attrs.record(&mut task_visitor);
-            let (fields, location) = task_visitor.result();
-            self.record(|| record::Event::Spawn {
-                id: id.into_u64(),
-                at: self.base_time.to_system_time(at),
-                fields: record::SerializeFields(fields.clone()),
-            });
-            if let Some(stats) = self.send_stats(&self.shared.dropped_tasks, move || {
-                let stats = Arc::new(stats::TaskStats::new(
-                    self.max_poll_duration_nanos,
-                    self.max_scheduled_duration_nanos,
+        self.isolate((), || {
+            let metadata = attrs.metadata();
+            if self.is_spawn(metadata) {
+                let at = Instant::now();
+                let mut task_visitor = TaskVisitor::new(
+                    metadata.into(),
+                    self.max_fields_per_task,
+                    self.max_field_value_len,
+                    self.redact_fields.clone(),
+                );
+                attrs.record(&mut task_visitor);
+                let scheduler = current_scheduler(task_visitor.kind());
+                let (fields, omitted_fields, location) = task_visitor.result();
+                let histograms = self
+                    .histogram_tasks
+                    .admit(&SpawnedTask::new(&fields, location.as_ref()));
+                self.shared.lingering_wakers.task_spawned(id);
+                self.record(|| record::Event::Spawn {
+                    id: id.into_u64(),
                     at,
-                ));
-                let event = Event::Spawn {
-                    id: id.clone(),
-                    stats: stats.clone(),
-                    metadata,
-                    fields,
-                    location,
-                };
-                (event, stats)
-            }) {
-                ctx.span(id).expect("if `on_new_span` was called, the span must exist; this is a `tracing` bug!").extensions_mut().insert(stats);
-            }
-            return;
-        }
-
-        if self.is_resource(metadata) {
-            let at = Instant::now();
-            let mut resource_visitor = ResourceVisitor::default();
-            attrs.record(&mut resource_visitor);
-            if let Some(result) = resource_visitor.result() {
-                let ResourceVisitorResult {
-                    concrete_type,
-                    kind,
-                    location,
-                    is_internal,
-                    inherit_child_attrs,
-                } = result;
-                let parent_id = self.current_spans.get().and_then(|stack| {
-                    self.first_entered(&stack.borrow(), |id| self.is_id_resource(id, &ctx))
+                    fields: record::SerializeFields(fields.clone()),
                 });
-                if let Some(stats) = self.send_stats(&self.shared.dropped_resources, move || {
-                    let stats = Arc::new(stats::ResourceStats::new(
+                if let Some(stats) = self.send_stats(&self.shared.dropped_tasks, move || {
+                    let stats = Arc::new(stats::TaskStats::new(
+                        self.max_poll_duration_nanos,
+                        self.max_scheduled_duration_nanos,
+                        self.histogram_significant_figures,
                         at,
-                        inherit_child_attrs,
-                        parent_id.clone(),
+                        scheduler,
+                        location.clone(),
+                        histograms,
                     ));
-                    let event = Event::Resource {
+                    let event = Event::Spawn {
                         id: id.clone(),
-                        parent_id,
+                        stats: stats.clone(),
                         metadata,
+                        fields,
+                        omitted_fields,
+                        location,
+                        scheduler,
+                    };
+                    (event, stats)
+                }) {
+                    self.shared.concurrent_tasks.spawned();
+                    ctx.span(id).expect("if `on_new_span` was called, the span must exist; this is a `tracing` bug!").extensions_mut().insert(stats);
+                }
+                return;
+            }
+
+            if self.is_resource(metadata) {
+                let at = Instant::now();
+                let mut resource_visitor = ResourceVisitor::default();
+                attrs.record(&mut resource_visitor);
+                if let Some(result) = resource_visitor.result() {
+                    let ResourceVisitorResult {
                         concrete_type,
                         kind,
                         location,
                         is_internal,
+                        inherit_child_attrs,
+                    } = result;
+                    let parent_id = self.current_spans.get().and_then(|stack| {
+                        self.first_entered(&stack.borrow(), |id| self.is_id_resource(id, &ctx))
+                    });
+                    if let Some(stats) = self.send_stats(&self.shared.dropped_resources, move || {
+                        let stats = Arc::new(stats::ResourceStats::new(
+                            at,
+                            inherit_child_attrs,
+                            parent_id.clone(),
+                        ));
+                        let event = Event::Resource {
+                            id: id.clone(),
+                            parent_id,
+                            metadata,
+                            concrete_type,
+                            kind,
+                            location,
+                            is_internal,
+                            stats: stats.clone(),
+                        };
+                        (event, stats)
+                    }) {
+                        ctx.span(id).expect("if `on_new_span` was called, the span must exist; this is a `tracing` bug!").extensions_mut().insert(stats);
+                    }
+                }
+                return;
+            }
+
+            if self.is_async_op(metadata) {
+                let at = Instant::now();
+                let mut async_op_visitor = AsyncOpVisitor::default();
+                attrs.record(&mut async_op_visitor);
+                if let Some((source, inherit_child_attrs)) = async_op_visitor.result() {
+                    let resource_id = self.current_spans.get().and_then(|stack| {
+                        self.first_entered(&stack.borrow(), |id| self.is_id_resource(id, &ctx))
+                    });
+
+                    let parent_id = self.current_spans.get().and_then(|stack| {
+                        self.first_entered(&stack.borrow(), |id| self.is_id_async_op(id, &ctx))
+                    });
+
+                    if let Some(resource_id) = resource_id {
+                        if let Some(stats) =
+                            self.send_stats(&self.shared.dropped_async_ops, move || {
+                                let stats = Arc::new(stats::AsyncOpStats::new(
+                                    at,
+                                    inherit_child_attrs,
+                                    parent_id.clone(),
+                                ));
+                                let event = Event::AsyncResourceOp {
+                                    id: id.clone(),
+                                    parent_id,
+                                    resource_id,
+                                    metadata,
+                                    source,
+                                    stats: stats.clone(),
+                                };
+                                (event, stats)
+                            })
+                        {
+                            ctx.span(id).expect("if `on_new_span` was called, the span must exist; this is a `tracing` bug!").extensions_mut().insert(stats);
+                        }
+                    }
+                }
+                return;
+            }
+
+            if self.is_watched_span(metadata) {
+                let at = Instant::now();
+                let mut span_visitor = WatchedSpanVisitor::new(
+                    metadata.into(),
+                    self.max_fields_per_task,
+                    self.max_field_value_len,
+                    self.redact_fields.clone(),
+                );
+                attrs.record(&mut span_visitor);
+                let (fields, omitted_fields) = span_visitor.result();
+                let task_id = self.current_spans.get().and_then(|stack| {
+                    self.first_entered(&stack.borrow(), |id| self.is_id_spawned(id, &ctx))
+                });
+                if let Some(stats) = self.send_stats(&self.shared.dropped_spans, move || {
+                    let stats = Arc::new(stats::WatchedSpanStats::new(at));
+                    let event = Event::WatchedSpan {
+                        id: id.clone(),
+                        metadata,
+                        fields,
+                        omitted_fields,
+                        task_id,
                         stats: stats.clone(),
                     };
                     (event, stats)
@@ -639,229 +1162,293 @@ where
                     ctx.span(id).expect("if `on_new_span` was called, the span must exist; this is a `tracing` bug!").extensions_mut().insert(stats);
                 }
             }
-            return;
-        }
-
-        if self.is_async_op(metadata) {
-            let at = Instant::now();
-            let mut async_op_visitor = AsyncOpVisitor::default();
-            attrs.record(&mut async_op_visitor);
-            if let Some((source, inherit_child_attrs)) = async_op_visitor.result() {
-                let resource_id = self.current_spans.get().and_then(|stack| {
-                    self.first_entered(&stack.borrow(), |id| self.is_id_resource(id, &ctx))
-                });
+        })
+    }
 
-                let parent_id = self.current_spans.get().and_then(|stack| {
-                    self.first_entered(&stack.borrow(), |id| self.is_id_async_op(id, &ctx))
-                });
+    fn on_event(&self, event: &tracing::Event<'_>, ctx: Context<'_, S>) {
+        self.isolate((), || {
+            let metadata = event.metadata();
+            if self.waker_callsites.contains(metadata) {
+                let at = Instant::now();
+                let mut visitor = WakerVisitor::default();
+                event.record(&mut visitor);
+                // XXX (eliza): ew...
+                if let Some((id, mut op)) = visitor.result() {
+                    if let Some(span) = ctx.span(&id) {
+                        let exts = span.extensions();
+                        if let Some(stats) = exts.get::<Arc<stats::TaskStats>>() {
+                            if op.is_wake() {
+                                // Are we currently inside the task's span? If so, the task
+                                // has woken itself.
+
+                                let self_wake = self
+                                    .current_spans
+                                    .get()
+                                    .map(|spans| spans.borrow().iter().any(|span| span == &id))
+                                    .unwrap_or(false);
+                                op = op.self_wake(self_wake);
+
+                                // Otherwise, if we're inside another task's span,
+                                // that task woke this one.
+                                let waker = self.current_spans.get().and_then(|stack| {
+                                    self.first_entered(&stack.borrow(), |id| {
+                                        self.is_id_spawned(id, &ctx)
+                                    })
+                                });
+                                if let Some(waker) = waker.filter(|_| !self_wake) {
+                                    stats.record_waker(&waker);
+                                }
+                            }
 
-                if let Some(resource_id) = resource_id {
-                    if let Some(stats) =
-                        self.send_stats(&self.shared.dropped_async_ops, move || {
-                            let stats = Arc::new(stats::AsyncOpStats::new(
+                            stats.record_wake_op(op, at);
+                            self.record(|| record::Event::Waker {
+                                id: id.into_u64(),
                                 at,
-                                inherit_child_attrs,
-                                parent_id.clone(),
-                            ));
-                            let event = Event::AsyncResourceOp {
-                                id: id.clone(),
-                                parent_id,
-                                resource_id,
-                                metadata,
-                                source,
-                                stats: stats.clone(),
-                            };
-                            (event, stats)
-                        })
-                    {
-                        ctx.span(id).expect("if `on_new_span` was called, the span must exist; this is a `tracing` bug!").extensions_mut().insert(stats);
+                                op,
+                            });
+                            return;
+                        }
                     }
+                    // The task's span has closed, but it may have completed
+                    // with wakers which are still alive.
+                    self.shared.lingering_wakers.record(&id, op);
                 }
+                return;
             }
-        }
-    }
 
-    fn on_event(&self, event: &tracing::Event<'_>, ctx: Context<'_, S>) {
-        let metadata = event.metadata();
-        if self.waker_callsites.contains(metadata) {
-            let at = Instant::now();
-            let mut visitor = WakerVisitor::default();
-            event.record(&mut visitor);
-            // XXX (eliza): ew...
-            if let Some((id, mut op)) = visitor.result() {
-                if let Some(span) = ctx.span(&id) {
-                    let exts = span.extensions();
-                    if let Some(stats) = exts.get::<Arc<stats::TaskStats>>() {
-                        if op.is_wake() {
-                            // Are we currently inside the task's span? If so, the task
-                            // has woken itself.
-
-                            let self_wake = self
-                                .current_spans
-                                .get()
-                                .map(|spans| spans.borrow().iter().any(|span| span == &id))
-                                .unwrap_or(false);
-                            op = op.self_wake(self_wake);
+            if self.select_callsites.contains(metadata) {
+                let mut visitor = SelectVisitor::default();
+                event.record(&mut visitor);
+                if let Some((location, branch, branches, waited)) = visitor.result() {
+                    self.send_stats(&self.shared.dropped_tasks, || {
+                        let event = Event::SelectBranch {
+                            location,
+                            branch,
+                            branches,
+                            waited,
+                        };
+                        (event, ())
+                    });
+                }
+                return;
+            }
+
+            if self.extension_callsites.contains(metadata) {
+                // extensions are attached to the task they're set from.
+                let task_id = self.current_spans.get().and_then(|stack| {
+                    self.first_entered(&stack.borrow(), |id| self.is_id_spawned(id, &ctx))
+                });
+                let mut visitor = ExtensionVisitor::default();
+                event.record(&mut visitor);
+                if let (Some(id), Some(extension)) = (task_id, visitor.result()) {
+                    self.send_stats(&self.shared.dropped_tasks, || {
+                        (Event::TaskExtension { id, extension }, ())
+                    });
+                }
+                return;
+            }
+
+            if self.poll_op_callsites.contains(metadata) {
+                let resource_id = self.current_spans.get().and_then(|stack| {
+                    self.first_entered(&stack.borrow(), |id| self.is_id_resource(id, &ctx))
+                });
+                // poll op event should have a resource span parent
+                if let Some(resource_id) = resource_id {
+                    let mut poll_op_visitor = PollOpVisitor::default();
+                    event.record(&mut poll_op_visitor);
+                    if let Some((op_name, is_ready)) = poll_op_visitor.result() {
+                        if is_ready && op_name == "poll_elapsed" {
+                            self.timer_fired(&resource_id, &ctx);
                         }
 
-                        stats.record_wake_op(op, at);
-                        self.record(|| record::Event::Waker {
-                            id: id.into_u64(),
-                            at: self.base_time.to_system_time(at),
-                            op,
+                        let task_and_async_op_ids = self.current_spans.get().and_then(|stack| {
+                            let stack = stack.borrow();
+                            let task_id =
+                                self.first_entered(&stack, |id| self.is_id_spawned(id, &ctx))?;
+                            let async_op_id =
+                                self.first_entered(&stack, |id| self.is_id_async_op(id, &ctx))?;
+                            Some((task_id, async_op_id))
                         });
+                        // poll op event should be emitted in the context of an async op and task spans
+                        if let Some((task_id, async_op_id)) = task_and_async_op_ids {
+                            if let Some(span) = ctx.span(&async_op_id) {
+                                let exts = span.extensions();
+                                if let Some(stats) = exts.get::<Arc<stats::AsyncOpStats>>() {
+                                    stats.set_task_id(&task_id);
+                                }
+                            }
+
+                            self.send_stats(&self.shared.dropped_async_ops, || {
+                                let event = Event::PollOp {
+                                    metadata,
+                                    op_name,
+                                    resource_id,
+                                    async_op_id,
+                                    task_id,
+                                    is_ready,
+                                };
+                                (event, ())
+                            });
+
+                            // TODO: JSON recorder doesn't care about poll ops.
+                        }
                     }
                 }
+                return;
             }
-            return;
-        }
 
-        if self.poll_op_callsites.contains(metadata) {
-            let resource_id = self.current_spans.get().and_then(|stack| {
-                self.first_entered(&stack.borrow(), |id| self.is_id_resource(id, &ctx))
-            });
-            // poll op event should have a resource span parent
-            if let Some(resource_id) = resource_id {
-                let mut poll_op_visitor = PollOpVisitor::default();
-                event.record(&mut poll_op_visitor);
-                if let Some((op_name, is_ready)) = poll_op_visitor.result() {
-                    let task_and_async_op_ids = self.current_spans.get().and_then(|stack| {
-                        let stack = stack.borrow();
-                        let task_id =
-                            self.first_entered(&stack, |id| self.is_id_spawned(id, &ctx))?;
-                        let async_op_id =
-                            self.first_entered(&stack, |id| self.is_id_async_op(id, &ctx))?;
-                        Some((task_id, async_op_id))
+            if self.resource_state_update_callsites.contains(metadata) {
+                // state update event should have a resource span parent
+                let resource_id = self.current_spans.get().and_then(|stack| {
+                    self.first_entered(&stack.borrow(), |id| self.is_id_resource(id, &ctx))
+                });
+                if let Some(id) = resource_id {
+                    self.state_update(&id, event, &ctx, |exts| {
+                        exts.get::<Arc<stats::ResourceStats>>()
+                            .map(<Arc<stats::ResourceStats> as std::ops::Deref>::deref)
                     });
-                    // poll op event should be emitted in the context of an async op and task spans
-                    if let Some((task_id, async_op_id)) = task_and_async_op_ids {
-                        if let Some(span) = ctx.span(&async_op_id) {
-                            let exts = span.extensions();
-                            if let Some(stats) = exts.get::<Arc<stats::AsyncOpStats>>() {
-                                stats.set_task_id(&task_id);
-                            }
-                        }
+                }
 
-                        self.send_stats(&self.shared.dropped_async_ops, || {
-                            let event = Event::PollOp {
-                                metadata,
-                                op_name,
-                                resource_id,
-                                async_op_id,
-                                task_id,
-                                is_ready,
-                            };
-                            (event, ())
-                        });
+                return;
+            }
 
-                        // TODO: JSON recorder doesn't care about poll ops.
+            if self.channel_recv_callsites.contains(metadata) {
+                // receive events should have a channel resource span parent
+                let resource_id = self.current_spans.get().and_then(|stack| {
+                    self.first_entered(&stack.borrow(), |id| self.is_id_resource(id, &ctx))
+                });
+                let mut visitor = ChannelRecvVisitor::default();
+                event.record(&mut visitor);
+                if let (Some(id), Some(latency)) = (resource_id, visitor.result()) {
+                    if let Some(span) = ctx.span(&id) {
+                        if let Some(stats) = span.extensions().get::<Arc<stats::ResourceStats>>() {
+                            stats.record_recv_latency(latency);
+                        }
                     }
                 }
+                return;
             }
-            return;
-        }
 
-        if self.resource_state_update_callsites.contains(metadata) {
-            // state update event should have a resource span parent
-            let resource_id = self.current_spans.get().and_then(|stack| {
-                self.first_entered(&stack.borrow(), |id| self.is_id_resource(id, &ctx))
-            });
-            if let Some(id) = resource_id {
-                self.state_update(&id, event, &ctx, |exts| {
-                    exts.get::<Arc<stats::ResourceStats>>()
-                        .map(<Arc<stats::ResourceStats> as std::ops::Deref>::deref)
+            if self.io_callsites.contains(metadata) {
+                // I/O events should have an I/O resource span parent
+                let resource_id = self.current_spans.get().and_then(|stack| {
+                    self.first_entered(&stack.borrow(), |id| self.is_id_resource(id, &ctx))
                 });
+                let mut visitor = IoVisitor::default();
+                event.record(&mut visitor);
+                if let (Some(id), Some(io_event)) = (resource_id, visitor.result()) {
+                    if let Some(span) = ctx.span(&id) {
+                        if let Some(stats) = span.extensions().get::<Arc<stats::ResourceStats>>() {
+                            stats.record_io(&io_event, Instant::now());
+                        }
+                    }
+                }
+                return;
             }
 
-            return;
-        }
-
-        if self.async_op_state_update_callsites.contains(metadata) {
-            let async_op_id = self.current_spans.get().and_then(|stack| {
-                self.first_entered(&stack.borrow(), |id| self.is_id_async_op(id, &ctx))
-            });
-            if let Some(id) = async_op_id {
-                self.state_update(&id, event, &ctx, |exts| {
-                    let async_op = exts.get::<Arc<stats::AsyncOpStats>>()?;
-                    Some(&async_op.stats)
+            if self.async_op_state_update_callsites.contains(metadata) {
+                let async_op_id = self.current_spans.get().and_then(|stack| {
+                    self.first_entered(&stack.borrow(), |id| self.is_id_async_op(id, &ctx))
                 });
+                if let Some(id) = async_op_id {
+                    self.state_update(&id, event, &ctx, |exts| {
+                        let async_op = exts.get::<Arc<stats::AsyncOpStats>>()?;
+                        Some(&async_op.stats)
+                    });
+                }
             }
-        }
+        })
     }
 
     fn on_enter(&self, id: &span::Id, cx: Context<'_, S>) {
-        if let Some(span) = cx.span(id) {
-            let now = Instant::now();
-            let exts = span.extensions();
-            // if the span we are entering is a task or async op, record the
-            // poll stats.
-            if let Some(stats) = exts.get::<Arc<stats::TaskStats>>() {
-                stats.start_poll(now);
-            } else if let Some(stats) = exts.get::<Arc<stats::AsyncOpStats>>() {
-                stats.start_poll(now);
-            } else if exts.get::<Arc<stats::ResourceStats>>().is_some() {
-                // otherwise, is the span a resource? in that case, we also want
-                // to enter it, although we don't care about recording poll
-                // stats.
-            } else {
-                return;
-            };
+        self.isolate((), || {
+            if let Some(span) = cx.span(id) {
+                let now = Instant::now();
+                let exts = span.extensions();
+                // if the span is a task, async op, or watched span, record the
+                // poll stats.
+                if let Some(stats) = exts.get::<Arc<stats::TaskStats>>() {
+                    stats.start_poll(now, id, &self.shared.threads);
+                } else if let Some(stats) = exts.get::<Arc<stats::AsyncOpStats>>() {
+                    stats.start_poll(now);
+                } else if let Some(stats) = exts.get::<Arc<stats::WatchedSpanStats>>() {
+                    stats.enter(now);
+                } else if exts.get::<Arc<stats::ResourceStats>>().is_some() {
+                    // otherwise, is the span a resource? in that case, we also want
+                    // to enter it, although we don't care about recording poll
+                    // stats.
+                } else {
+                    return;
+                };
 
-            self.current_spans
-                .get_or_default()
-                .borrow_mut()
-                .push(id.clone());
+                self.current_spans
+                    .get_or_default()
+                    .borrow_mut()
+                    .push(id.clone());
 
-            self.record(|| record::Event::Enter {
-                id: id.into_u64(),
-                at: self.base_time.to_system_time(now),
-            });
-        }
+                self.record(|| record::Event::Enter {
+                    id: id.into_u64(),
+                    at: now,
+                });
+            }
+        })
     }
 
     fn on_exit(&self, id: &span::Id, cx: Context<'_, S>) {
-        if let Some(span) = cx.span(id) {
-            let exts = span.extensions();
-            let now = Instant::now();
-            // if the span we are entering is a task or async op, record the
-            // poll stats.
-            if let Some(stats) = exts.get::<Arc<stats::TaskStats>>() {
-                stats.end_poll(now);
-            } else if let Some(stats) = exts.get::<Arc<stats::AsyncOpStats>>() {
-                stats.end_poll(now);
-            } else if exts.get::<Arc<stats::ResourceStats>>().is_some() {
-                // otherwise, is the span a resource? in that case, we also want
-                // to enter it, although we don't care about recording poll
-                // stats.
-            } else {
-                return;
-            };
+        self.isolate((), || {
+            if let Some(span) = cx.span(id) {
+                let exts = span.extensions();
+                let now = Instant::now();
+                // if the span is a task, async op, or watched span, record the
+                // poll stats.
+                if let Some(stats) = exts.get::<Arc<stats::TaskStats>>() {
+                    stats.end_poll(now, id, &self.shared.threads);
+                } else if let Some(stats) = exts.get::<Arc<stats::AsyncOpStats>>() {
+                    stats.end_poll(now);
+                } else if let Some(stats) = exts.get::<Arc<stats::WatchedSpanStats>>() {
+                    stats.exit(now);
+                } else if exts.get::<Arc<stats::ResourceStats>>().is_some() {
+                    // otherwise, is the span a resource? in that case, we also want
+                    // to enter it, although we don't care about recording poll
+                    // stats.
+                } else {
+                    return;
+                };
 
-            self.current_spans.get_or_default().borrow_mut().pop(id);
+                self.current_spans.get_or_default().borrow_mut().pop(id);
 
-            self.record(|| record::Event::Exit {
-                id: id.into_u64(),
-                at: self.base_time.to_system_time(now),
-            });
-        }
+                self.record(|| record::Event::Exit {
+                    id: id.into_u64(),
+                    at: now,
+                });
+            }
+        })
     }
 
     fn on_close(&self, id: span::Id, cx: Context<'_, S>) {
-        if let Some(span) = cx.span(&id) {
-            let now = Instant::now();
-            let exts = span.extensions();
-            if let Some(stats) = exts.get::<Arc<stats::TaskStats>>() {
-                stats.drop_task(now);
-            } else if let Some(stats) = exts.get::<Arc<stats::AsyncOpStats>>() {
-                stats.drop_async_op(now);
-            } else if let Some(stats) = exts.get::<Arc<stats::ResourceStats>>() {
-                stats.drop_resource(now);
+        self.isolate((), || {
+            if let Some(span) = cx.span(&id) {
+                let now = Instant::now();
+                let exts = span.extensions();
+                if let Some(stats) = exts.get::<Arc<stats::TaskStats>>() {
+                    stats.drop_task(now);
+                    self.shared.concurrent_tasks.closed();
+                    self.shared
+                        .lingering_wakers
+                        .task_closed(&id, stats.waker_counts());
+                } else if let Some(stats) = exts.get::<Arc<stats::AsyncOpStats>>() {
+                    stats.drop_async_op(now);
+                } else if let Some(stats) = exts.get::<Arc<stats::ResourceStats>>() {
+                    stats.drop_resource(now);
+                } else if let Some(stats) = exts.get::<Arc<stats::WatchedSpanStats>>() {
+                    stats.drop_span(now);
+                }
+                self.record(|| record::Event::Close {
+                    id: id.into_u64(),
+                    at: now,
+                });
             }
-            self.record(|| record::Event::Close {
-                id: id.into_u64(),
-                at: self.base_time.to_system_time(now),
-            });
-        }
+        })
     }
 }
 
@@ -871,6 +1458,7 @@ impl fmt::Debug for ConsoleLayer {
             // mpsc::Sender debug impl is not very useful
             .field("tx", &format_args!("<...>"))
             .field("tx.capacity", &self.tx.capacity())
+            .field("flush_watermark", &self.flush_watermark)
             .field("shared", &self.shared)
             .field("spawn_callsites", &self.spawn_callsites)
             .field("waker_callsites", &self.waker_callsites)
@@ -938,24 +1526,63 @@ impl Server {
         mut builder: tonic::transport::Server,
     ) -> Result<(), Box<dyn std::error::Error + Send + Sync + 'static>> {
         let addr = self.addr.clone();
+        let socket_activation = self.socket_activation();
         let ServerParts {
             instrument_server,
             aggregator,
         } = self.into_parts();
         let aggregate = spawn_named(aggregator.run(), "console::aggregate");
         let router = builder.add_service(instrument_server);
-        let res = match addr {
-            ServerAddr::Tcp(addr) => {
-                let serve = router.serve(addr);
-                spawn_named(serve, "console::serve").await
-            }
-            #[cfg(unix)]
-            ServerAddr::Unix(path) => {
-                let incoming = UnixListener::bind(path)?;
-                let serve = router.serve_with_incoming(UnixListenerStream::new(incoming));
-                spawn_named(serve, "console::serve").await
-            }
-        };
+        let res = serve_router(router, addr, socket_activation).await;
+        aggregate.abort();
+        res
+    }
+
+    /// Starts the gRPC service with the default gRPC settings, serving
+    /// connections accepted from the `incoming` stream.
+    ///
+    /// This ignores any address set by [`Builder::server_addr`], and can be
+    /// used to serve the console over a custom transport, such as an
+    /// in-memory duplex stream, a TLS stream terminated by the application,
+    /// or a socket inherited through systemd socket activation.
+    ///
+    /// Like [`serve_with`], this spawns both the server task and the event
+    /// aggregation worker task on the current async runtime.
+    ///
+    /// # Examples
+    ///
+    /// Serving the console from an already bound listener:
+    ///
+    /// ```rust
+    /// # async fn docs() -> Result<(), Box<dyn std::error::Error + Send + Sync + 'static>> {
+    /// # let (_, server) = console_subscriber::ConsoleLayer::new();
+    /// use tokio_stream::wrappers::TcpListenerStream;
+    ///
+    /// let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await?;
+    /// server
+    ///     .serve_with_incoming(TcpListenerStream::new(listener))
+    ///     .await
+    /// # }
+    /// ```
+    /// [`serve_with`]: Server::serve_with
+    pub async fn serve_with_incoming<I, IO>(
+        self,
+        incoming: I,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync + 'static>>
+    where
+        I: Stream<Item = std::io::Result<IO>> + Send + 'static,
+        IO: Connected + AsyncRead + AsyncWrite + Unpin + Send + 'static,
+        IO::ConnectInfo: Clone + Send + Sync + 'static,
+    {
+        let ServerParts {
+            instrument_server,
+            aggregator,
+        } = self.into_parts();
+        let aggregate = spawn_named(aggregator.run(), "console::aggregate");
+        let serve = tonic::transport::Server::default()
+            .add_service(instrument_server)
+            .serve_with_incoming(incoming);
+        let res = spawn_named(serve, "console::serve").await;
         aggregate.abort();
         res?.map_err(Into::into)
     }
@@ -1063,6 +1690,7 @@ impl Server {
         builder: tonic::transport::Server,
     ) -> Result<(), Box<dyn std::error::Error + Send + Sync + 'static>> {
         let addr = self.addr.clone();
+        let socket_activation = self.socket_activation();
         let ServerParts {
             instrument_server,
             aggregator,
@@ -1071,20 +1699,16 @@ impl Server {
             .accept_http1(true)
             .add_service(tonic_web::enable(instrument_server));
         let aggregate = spawn_named(aggregator.run(), "console::aggregate");
-        let res = match addr {
-            ServerAddr::Tcp(addr) => {
-                let serve = router.serve(addr);
-                spawn_named(serve, "console::serve").await
-            }
-            #[cfg(unix)]
-            ServerAddr::Unix(path) => {
-                let incoming = UnixListener::bind(path)?;
-                let serve = router.serve_with_incoming(UnixListenerStream::new(incoming));
-                spawn_named(serve, "console::serve").await
-            }
-        };
+        let res = serve_router(router, addr, socket_activation).await;
         aggregate.abort();
-        res?.map_err(Into::into)
+        res
+    }
+
+    fn socket_activation(&self) -> bool {
+        #[cfg(unix)]
+        return self.socket_activation;
+        #[cfg(not(unix))]
+        false
     }
 
     /// Returns the parts needed to spawn a gRPC server and the aggregator that
@@ -1150,6 +1774,65 @@ impl Server {
             aggregator,
         }
     }
+
+    /// Returns the error for an RPC which can't be served because the
+    /// aggregator task isn't running.
+    fn inactive(&self, action: &str) -> tonic::Status {
+        tonic::Status::unavailable(format!(
+            "cannot {action}, instrumentation inactive: {}",
+            self.shared.inactive_reason()
+        ))
+    }
+
+    fn read_only_denied(&self, action: &str) -> tonic::Status {
+        tonic::Status::permission_denied(format!("cannot {action}, the server is read-only"))
+    }
+
+    /// Records `req` in the audit log.
+    fn audit<T>(&self, req: &tonic::Request<T>, rpc: &'static str) {
+        self.audit_log.lock().record(req.remote_addr(), rpc);
+    }
+}
+
+/// Serves `router` on the socket inherited from systemd, if
+/// `socket_activation` is enabled and one was passed to the process, or on
+/// `addr` otherwise.
+#[cfg_attr(not(unix), allow(unused_variables))]
+async fn serve_router(
+    router: tonic::transport::server::Router,
+    addr: ServerAddr,
+    socket_activation: bool,
+) -> Result<(), Box<dyn std::error::Error + Send + Sync + 'static>> {
+    #[cfg(unix)]
+    if socket_activation {
+        if let Some(listener) = systemd::Listener::take()? {
+            let res = match listener {
+                systemd::Listener::Tcp(listener) => {
+                    let serve = router.serve_with_incoming(TcpListenerStream::new(listener));
+                    spawn_named(serve, "console::serve").await
+                }
+                systemd::Listener::Unix(listener) => {
+                    let serve = router.serve_with_incoming(UnixListenerStream::new(listener));
+                    spawn_named(serve, "console::serve").await
+                }
+            };
+            return res?.map_err(Into::into);
+        }
+    }
+
+    let res = match addr {
+        ServerAddr::Tcp(addr) => {
+            let serve = router.serve(addr);
+            spawn_named(serve, "console::serve").await
+        }
+        #[cfg(unix)]
+        ServerAddr::Unix(path) => {
+            let incoming = UnixListener::bind(path)?;
+            let serve = router.serve_with_incoming(UnixListenerStream::new(incoming));
+            spawn_named(serve, "console::serve").await
+        }
+    };
+    res?.map_err(Into::into)
 }
 
 /// Server Parts
@@ -1191,21 +1874,34 @@ impl proto::instrument::instrument_server::Instrument for Server {
         tokio_stream::wrappers::ReceiverStream<Result<proto::instrument::Update, tonic::Status>>;
     type WatchTaskDetailsStream =
         tokio_stream::wrappers::ReceiverStream<Result<proto::tasks::TaskDetails, tonic::Status>>;
+    type WatchTaskDetailsSetStream =
+        tokio_stream::wrappers::ReceiverStream<Result<proto::tasks::TaskDetailsSet, tonic::Status>>;
+    type WatchTaskGroupsStream =
+        tokio_stream::wrappers::ReceiverStream<Result<proto::tasks::TaskGroups, tonic::Status>>;
     type WatchStateStream =
         tokio_stream::wrappers::ReceiverStream<Result<proto::instrument::State, tonic::Status>>;
     async fn watch_updates(
         &self,
         req: tonic::Request<proto::instrument::InstrumentRequest>,
     ) -> Result<tonic::Response<Self::WatchUpdatesStream>, tonic::Status> {
-        match req.remote_addr() {
-            Some(addr) => tracing::debug!(client.addr = %addr, "starting a new watch"),
-            None => tracing::debug!(client.addr = %"<unknown>", "starting a new watch"),
-        }
-        let permit = self.subscribe.reserve().await.map_err(|_| {
-            tonic::Status::internal("cannot start new watch, aggregation task is not running")
-        })?;
+        self.audit(&req, "WatchUpdates");
+        let req = req.into_inner();
+        let retention = req
+            .retention
+            .map(Duration::try_from)
+            .transpose()
+            .map_err(|_| tonic::Status::invalid_argument("retention cannot be negative"))?;
+        let permit = self
+            .subscribe
+            .reserve()
+            .await
+            .map_err(|_| self.inactive("start new watch"))?;
         let (tx, rx) = mpsc::channel(self.client_buffer);
-        permit.send(Command::Instrument(Watch(tx)));
+        permit.send(Command::Instrument(InstrumentRequest {
+            watch: Watch(tx),
+            retention,
+            resume_token: req.resume_token,
+        }));
         tracing::debug!("watch started");
         let stream = tokio_stream::wrappers::ReceiverStream::new(rx);
         Ok(tonic::Response::new(stream))
@@ -1215,20 +1911,25 @@ impl proto::instrument::instrument_server::Instrument for Server {
         &self,
         req: tonic::Request<proto::instrument::TaskDetailsRequest>,
     ) -> Result<tonic::Response<Self::WatchTaskDetailsStream>, tonic::Status> {
+        self.audit(&req, "WatchTaskDetails");
+        let req = req.into_inner();
         let task_id = req
-            .into_inner()
             .id
             .ok_or_else(|| tonic::Status::invalid_argument("missing task_id"))?
             .id;
+        let options = DetailsOptions::from_request(req.interval, req.histogram_significant_figures)
+            .map_err(tonic::Status::invalid_argument)?;
 
         // `tracing` reserves span ID 0 for niche optimization for `Option<Id>`.
         let id = std::num::NonZeroU64::new(task_id)
             .map(Id::from_non_zero_u64)
             .ok_or_else(|| tonic::Status::invalid_argument("task_id cannot be 0"))?;
 
-        let permit = self.subscribe.reserve().await.map_err(|_| {
-            tonic::Status::internal("cannot start new watch, aggregation task is not running")
-        })?;
+        let permit = self
+            .subscribe
+            .reserve()
+            .await
+            .map_err(|_| self.inactive("start new watch"))?;
 
         // Check with the aggregator task to request a stream if the task exists.
         let (stream_sender, stream_recv) = oneshot::channel();
@@ -1236,6 +1937,7 @@ impl proto::instrument::instrument_server::Instrument for Server {
             id,
             stream_sender,
             buffer: self.client_buffer,
+            options,
         }));
         // If the aggregator drops the sender, the task doesn't exist.
         let rx = stream_recv.await.map_err(|_| {
@@ -1248,40 +1950,164 @@ impl proto::instrument::instrument_server::Instrument for Server {
         Ok(tonic::Response::new(stream))
     }
 
+    async fn watch_task_details_set(
+        &self,
+        req: tonic::Request<proto::instrument::TaskDetailsSetRequest>,
+    ) -> Result<tonic::Response<Self::WatchTaskDetailsSetStream>, tonic::Status> {
+        self.audit(&req, "WatchTaskDetailsSet");
+        let req = req.into_inner();
+        if req.ids.is_empty() && req.location.is_none() {
+            return Err(tonic::Status::invalid_argument(
+                "either task IDs or a location must be requested",
+            ));
+        }
+        // `tracing` reserves span ID 0 for niche optimization for `Option<Id>`.
+        let ids = req
+            .ids
+            .iter()
+            .map(|id| std::num::NonZeroU64::new(id.id).map(Id::from_non_zero_u64))
+            .collect::<Option<Vec<_>>>()
+            .ok_or_else(|| tonic::Status::invalid_argument("task IDs cannot be 0"))?;
+        let options = DetailsOptions::from_request(req.interval, req.histogram_significant_figures)
+            .map_err(tonic::Status::invalid_argument)?;
+
+        let permit = self
+            .subscribe
+            .reserve()
+            .await
+            .map_err(|_| self.inactive("start new watch"))?;
+        let (tx, rx) = mpsc::channel(self.client_buffer);
+        permit.send(Command::WatchTaskDetailsSet(WatchSetRequest {
+            ids,
+            location: req.location,
+            watch: Watch(tx),
+            options,
+        }));
+        tracing::debug!(ids = ?req.ids, "task details set watch started");
+        let stream = tokio_stream::wrappers::ReceiverStream::new(rx);
+        Ok(tonic::Response::new(stream))
+    }
+
+    async fn watch_task_groups(
+        &self,
+        req: tonic::Request<proto::instrument::TaskGroupsRequest>,
+    ) -> Result<tonic::Response<Self::WatchTaskGroupsStream>, tonic::Status> {
+        self.audit(&req, "WatchTaskGroups");
+        let permit = self
+            .subscribe
+            .reserve()
+            .await
+            .map_err(|_| self.inactive("start new watch"))?;
+        let (tx, rx) = mpsc::channel(self.client_buffer);
+        permit.send(Command::WatchTaskGroups(Watch(tx)));
+        tracing::debug!("task groups watch started");
+        let stream = tokio_stream::wrappers::ReceiverStream::new(rx);
+        Ok(tonic::Response::new(stream))
+    }
+
     async fn watch_state(
         &self,
-        _req: tonic::Request<proto::instrument::StateRequest>,
+        req: tonic::Request<proto::instrument::StateRequest>,
     ) -> Result<tonic::Response<Self::WatchStateStream>, tonic::Status> {
+        self.audit(&req, "WatchState");
         let (stream_sender, stream_recv) = mpsc::channel(self.client_buffer);
         self.subscribe
             .send(Command::WatchState(Watch(stream_sender)))
             .await
-            .map_err(|_| {
-                tonic::Status::internal("cannot get state, aggregation task is not running")
-            })?;
+            .map_err(|_| self.inactive("get state"))?;
         let stream = tokio_stream::wrappers::ReceiverStream::new(stream_recv);
         Ok(tonic::Response::new(stream))
     }
 
     async fn pause(
         &self,
-        _req: tonic::Request<proto::instrument::PauseRequest>,
+        req: tonic::Request<proto::instrument::PauseRequest>,
     ) -> Result<tonic::Response<proto::instrument::PauseResponse>, tonic::Status> {
-        self.subscribe.send(Command::Pause).await.map_err(|_| {
-            tonic::Status::internal("cannot pause, aggregation task is not running")
-        })?;
+        self.audit(&req, "Pause");
+        if self.read_only {
+            return Err(self.read_only_denied("pause"));
+        }
+        let freeze_time = req.into_inner().freeze_time;
+        self.subscribe
+            .send(Command::Pause { freeze_time })
+            .await
+            .map_err(|_| self.inactive("pause"))?;
         Ok(tonic::Response::new(proto::instrument::PauseResponse {}))
     }
 
     async fn resume(
         &self,
-        _req: tonic::Request<proto::instrument::ResumeRequest>,
+        req: tonic::Request<proto::instrument::ResumeRequest>,
     ) -> Result<tonic::Response<proto::instrument::ResumeResponse>, tonic::Status> {
-        self.subscribe.send(Command::Resume).await.map_err(|_| {
-            tonic::Status::internal("cannot resume, aggregation task is not running")
-        })?;
+        self.audit(&req, "Resume");
+        if self.read_only {
+            return Err(self.read_only_denied("resume"));
+        }
+        self.subscribe
+            .send(Command::Resume)
+            .await
+            .map_err(|_| self.inactive("resume"))?;
         Ok(tonic::Response::new(proto::instrument::ResumeResponse {}))
     }
+
+    async fn audit_log(
+        &self,
+        req: tonic::Request<proto::instrument::AuditLogRequest>,
+    ) -> Result<tonic::Response<proto::instrument::AuditLogResponse>, tonic::Status> {
+        self.audit(&req, "AuditLog");
+        let log = self.audit_log.lock().to_proto();
+        Ok(tonic::Response::new(log))
+    }
+
+    async fn list_tasks(
+        &self,
+        req: tonic::Request<proto::instrument::ListTasksRequest>,
+    ) -> Result<tonic::Response<proto::instrument::ListTasksResponse>, tonic::Status> {
+        self.audit(&req, "ListTasks");
+        let req = req.into_inner();
+        let (response, rx) = oneshot::channel();
+        self.subscribe
+            .send(Command::ListTasks(ListTasksRequest {
+                location: req.location,
+                include_completed: req.include_completed,
+                limit: (req.limit > 0).then_some(req.limit as usize),
+                response,
+            }))
+            .await
+            .map_err(|_| self.inactive("list tasks"))?;
+        let tasks = rx.await.map_err(|_| self.inactive("list tasks"))?;
+        Ok(tonic::Response::new(tasks))
+    }
+}
+
+impl DetailsOptions {
+    /// The shortest interval task details are sent at, however short an
+    /// interval a client asks for.
+    ///
+    /// Building the details of a task means copying its histograms, so a
+    /// client asking for them in a busy loop mustn't be able to make the
+    /// aggregator do that in the instrumented process.
+    const MIN_INTERVAL: Duration = Duration::from_millis(50);
+
+    fn from_request(
+        interval: Option<prost_types::Duration>,
+        histogram_significant_figures: Option<u32>,
+    ) -> Result<Self, &'static str> {
+        let interval = interval
+            .map(Duration::try_from)
+            .transpose()
+            .map_err(|_| "interval cannot be negative")?;
+        if interval == Some(Duration::ZERO) {
+            return Err("interval cannot be zero");
+        }
+        let interval = interval.map(|interval| interval.max(Self::MIN_INTERVAL));
+        let significant_figures =
+            histogram_significant_figures.map(|sigfig| u8::try_from(sigfig).unwrap_or(u8::MAX));
+        Ok(Self {
+            interval,
+            significant_figures,
+        })
+    }
 }
 
 impl WakeOp {
@@ -1299,6 +2125,31 @@ impl WakeOp {
     }
 }
 
+/// Returns the kind of scheduler which runs a task of the given kind, spawned
+/// from the current thread.
+///
+/// Tasks spawned onto a `LocalSet` have the kind `local`. Otherwise, tasks
+/// are assumed to be spawned onto the runtime the current thread is in the
+/// context of, which is the case unless the task was spawned through a
+/// `Handle` to another runtime.
+fn current_scheduler(kind: Option<&str>) -> proto::tasks::task::Scheduler {
+    use proto::tasks::task::Scheduler;
+    use tokio::runtime::{Handle, RuntimeFlavor};
+
+    match kind {
+        Some("local") => return Scheduler::Local,
+        // Blocking tasks run on the blocking pool, and instrumented futures
+        // are polled by whichever task polls them.
+        Some("blocking" | "block_on" | "future") => return Scheduler::Unknown,
+        _ => {}
+    }
+    match Handle::try_current().map(|handle| handle.runtime_flavor()) {
+        Ok(RuntimeFlavor::CurrentThread) => Scheduler::CurrentThread,
+        Ok(RuntimeFlavor::MultiThread) => Scheduler::MultiThread,
+        _ => Scheduler::Unknown,
+    }
+}
+
 #[track_caller]
 pub(crate) fn spawn_named<T>(
     task: impl std::future::Future<Output = T> + Send + 'static,
@@ -1313,3 +2164,61 @@ where
     #[cfg(not(tokio_unstable))]
     tokio::spawn(task)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn details_intervals_are_clamped() {
+        let interval = |nanos| {
+            DetailsOptions::from_request(Some(prost_types::Duration { seconds: 0, nanos }), None)
+                .map(|options| options.interval)
+        };
+        assert_eq!(interval(1), Ok(Some(DetailsOptions::MIN_INTERVAL)));
+        assert_eq!(interval(200_000_000), Ok(Some(Duration::from_millis(200))));
+        assert!(interval(0).is_err());
+        assert!(interval(-1).is_err());
+    }
+
+    #[test]
+    fn panicking_callbacks_poison_the_layer() {
+        let (layer, _server) = ConsoleLayer::builder().build();
+
+        assert_eq!(layer.isolate(0, || 1), 1);
+        assert_eq!(layer.isolate(0, || panic!("a bug in the layer")), 0);
+        assert!(layer.shared.poisoned.load(Ordering::Acquire));
+        assert_eq!(layer.shared.layer_panics.load(Ordering::Acquire), 1);
+
+        // once poisoned, later callbacks are skipped.
+        let mut ran = false;
+        layer.isolate((), || ran = true);
+        assert!(!ran);
+
+        // ...and callsites registered from then on are never enabled.
+        let spawn = tracing::subscriber::with_default(tracing_subscriber::registry(), || {
+            tracing::trace_span!("runtime.spawn").metadata().unwrap()
+        });
+        let interest = Layer::<tracing_subscriber::Registry>::register_callsite(&layer, spawn);
+        assert!(interest.is_never());
+    }
+
+    #[test]
+    fn tasks_below_the_task_level_are_ignored() {
+        let (layer, _server) = ConsoleLayer::builder()
+            .task_level(tracing::Level::DEBUG)
+            .build();
+        let (debug, trace) =
+            tracing::subscriber::with_default(tracing_subscriber::registry(), || {
+                (
+                    tracing::debug_span!("runtime.spawn").metadata().unwrap(),
+                    tracing::trace_span!("runtime.spawn").metadata().unwrap(),
+                )
+            });
+
+        Layer::<tracing_subscriber::Registry>::register_callsite(&layer, debug);
+        Layer::<tracing_subscriber::Registry>::register_callsite(&layer, trace);
+        assert!(layer.is_spawn(debug));
+        assert!(!layer.is_spawn(trace));
+    }
+}
@@ -4,7 +4,16 @@ use serde::{
     ser::{SerializeSeq, SerializeStruct},
     Serialize,
 };
-use std::{fs::File, io, path::Path, time::SystemTime};
+use std::{
+    collections::BTreeMap,
+    ffi::OsString,
+    fs::File,
+    io::{self, BufWriter, Write},
+    path::{Path, PathBuf},
+    time::{Duration, Instant, SystemTime},
+};
+
+use crate::stats::TimeAnchor;
 
 /// This marks the currently understood version of the recording format. This
 /// should be increased whenever the format has a breaking change that we
@@ -12,7 +21,11 @@ use std::{fs::File, io, path::Path, time::SystemTime};
 /// older versions.
 ///
 /// But while this is in rapid development, we can move fast and break things.
-const DATA_FORMAT_VERSION: u8 = 1;
+const DATA_FORMAT_VERSION: u8 = 2;
+
+/// How often, in recorded time, a [`Checkpoint`] of every live task is
+/// written to the recording.
+const CHECKPOINT_INTERVAL: Duration = Duration::from_secs(10);
 
 pub(crate) struct Recorder {
     tx: Sender<Event>,
@@ -25,44 +38,110 @@ struct Header {
     v: u8,
 }
 
+/// The state of every live task at the time `at`, written to the recording
+/// every [`CHECKPOINT_INTERVAL`].
+///
+/// Together with the events that follow it, a checkpoint has everything
+/// needed to replay the recording from `at`, so a replay can seek to a time
+/// by starting from the last checkpoint before it, rather than by applying
+/// every event since the start of the recording. The byte offset of each
+/// checkpoint is written to the recording's index, as an [`IndexEntry`].
+///
+/// This is serialized like an [`Event`], tagged with its kind.
+#[derive(Serialize)]
+enum Checkpoint<'a> {
+    Checkpoint {
+        at: SystemTime,
+        tasks: Vec<&'a TaskSnapshot>,
+    },
+}
+
+/// The state of a live task, as of a [`Checkpoint`].
+#[derive(Serialize)]
+struct TaskSnapshot {
+    id: u64,
+    spawned_at: SystemTime,
+    fields: SerializeFields,
+    polls: u64,
+    /// When the task's current poll started, if it is being polled.
+    poll_started: Option<SystemTime>,
+    busy: Duration,
+    wakes: u64,
+    self_wakes: u64,
+    waker_clones: u64,
+    waker_drops: u64,
+}
+
+/// A line of the recording's index, which is written alongside the
+/// recording, to the same path with `.index` appended.
+#[derive(Serialize)]
+struct IndexEntry {
+    /// The time of the checkpoint.
+    at: SystemTime,
+    /// The byte offset of the checkpoint's line in the recording.
+    offset: u64,
+}
+
+/// Builds the [`Checkpoint`]s of a recording from its events.
+#[derive(Default)]
+struct Checkpoints {
+    tasks: BTreeMap<u64, TaskSnapshot>,
+    next_at: Option<SystemTime>,
+}
+
+/// Counts the bytes written to the recording, so that the offsets of its
+/// checkpoints can be indexed.
+struct CountingWriter<W> {
+    inner: W,
+    written: u64,
+}
+
+/// An event to record, which happened `at` the given time.
+///
+/// Events are recorded with the monotonic `Instant` they happened at, as
+/// taking the current `SystemTime` for each of them would be more costly, and
+/// would be affected by changes to the system clock. They are anchored to
+/// wall-clock time on the recorder's own thread, before they're written.
 #[derive(Serialize)]
-pub(crate) enum Event {
+pub(crate) enum Event<T = Instant> {
     Spawn {
         id: u64,
-        at: SystemTime,
+        at: T,
         fields: SerializeFields,
     },
     Enter {
         id: u64,
-        at: SystemTime,
+        at: T,
     },
     Exit {
         id: u64,
-        at: SystemTime,
+        at: T,
     },
     Close {
         id: u64,
-        at: SystemTime,
+        at: T,
     },
     Waker {
         id: u64,
         op: super::WakeOp,
-        at: SystemTime,
+        at: T,
     },
 }
 
+#[derive(Clone)]
 pub(crate) struct SerializeFields(pub(crate) Vec<proto::Field>);
 
 struct SerializeField<'a>(&'a proto::Field);
 
 impl Recorder {
-    pub(crate) fn new(path: &Path) -> io::Result<Self> {
-        let file = std::fs::File::create(path)?;
+    pub(crate) fn new(path: &Path, base_time: TimeAnchor) -> io::Result<Self> {
+        let file = File::create(path)?;
+        let index = File::create(index_path(path))?;
         let (tx, rx) = crossbeam_channel::bounded(4096);
         let _worker = std::thread::Builder::new()
             .name("console/subscriber/recorder/io".into())
             .spawn(move || {
-                if let Err(e) = record_io(file, rx) {
+                if let Err(e) = record_io(file, index, rx, base_time) {
                     eprintln!("event recorder failed: {}", e);
                 }
             })?;
@@ -79,10 +158,20 @@ impl Recorder {
     }
 }
 
-fn record_io(file: File, rx: Receiver<Event>) -> io::Result<()> {
-    use std::io::{BufWriter, Write};
+/// Returns the path of the index of the recording at `path`.
+fn index_path(path: &Path) -> PathBuf {
+    let mut index = OsString::from(path);
+    index.push(".index");
+    index.into()
+}
 
-    fn write<T: Serialize>(mut file: &mut BufWriter<File>, val: &T) -> io::Result<()> {
+fn record_io(
+    file: File,
+    index: File,
+    rx: Receiver<Event>,
+    base_time: TimeAnchor,
+) -> io::Result<()> {
+    fn write<T: Serialize>(mut file: &mut impl Write, val: &T) -> io::Result<()> {
         // Clippy throws a false positive here. We can't actually pass the owned `file` to
         // `to_writer` because we need it again in the line blow.
         #[allow(clippy::needless_borrows_for_generic_args)]
@@ -90,7 +179,21 @@ fn record_io(file: File, rx: Receiver<Event>) -> io::Result<()> {
         file.write_all(b"\n")
     }
 
-    let mut file = BufWriter::new(file);
+    let mut file = CountingWriter::new(BufWriter::new(file));
+    let mut index = BufWriter::new(index);
+    let mut checkpoints = Checkpoints::default();
+    let mut record = |file: &mut CountingWriter<_>, event: Event| {
+        let event = event.anchored(&base_time);
+        let at = event.at();
+        if checkpoints.is_due(at) {
+            let offset = file.written;
+            write(file, &checkpoints.checkpoint(at))?;
+            write(&mut index, &IndexEntry { at, offset })?;
+        }
+        checkpoints.apply(&event);
+        write(file, &event)
+    };
+
     write(
         &mut file,
         &Header {
@@ -101,20 +204,149 @@ fn record_io(file: File, rx: Receiver<Event>) -> io::Result<()> {
     // wait to receive an event...
     while let Ok(event) = rx.recv() {
         // TODO: what to do if file error?
-        write(&mut file, &event)?;
+        record(&mut file, event)?;
 
         // drain any additional events that are ready now
         while let Ok(event) = rx.try_recv() {
-            write(&mut file, &event)?;
+            record(&mut file, event)?;
         }
 
         file.flush()?;
     }
 
     tracing::debug!("event stream ended; flushing file");
+    index.flush()?;
     file.flush()
 }
 
+impl Event {
+    /// Converts the time the event happened at to a `SystemTime`.
+    fn anchored(self, base_time: &TimeAnchor) -> Event<SystemTime> {
+        let at = |at| base_time.to_system_time(at);
+        match self {
+            Self::Spawn { id, at: t, fields } => Event::Spawn {
+                id,
+                at: at(t),
+                fields,
+            },
+            Self::Enter { id, at: t } => Event::Enter { id, at: at(t) },
+            Self::Exit { id, at: t } => Event::Exit { id, at: at(t) },
+            Self::Close { id, at: t } => Event::Close { id, at: at(t) },
+            Self::Waker { id, op, at: t } => Event::Waker { id, op, at: at(t) },
+        }
+    }
+}
+
+impl<T: Copy> Event<T> {
+    fn at(&self) -> T {
+        match *self {
+            Self::Spawn { at, .. }
+            | Self::Enter { at, .. }
+            | Self::Exit { at, .. }
+            | Self::Close { at, .. }
+            | Self::Waker { at, .. } => at,
+        }
+    }
+}
+
+// === impl Checkpoints ===
+
+impl Checkpoints {
+    /// Returns `true` if a checkpoint should be written before an event which
+    /// happened `at` the given time.
+    fn is_due(&mut self, at: SystemTime) -> bool {
+        match self.next_at {
+            Some(next_at) if at < next_at => false,
+            next_at => {
+                self.next_at = Some(at + CHECKPOINT_INTERVAL);
+                // there's nothing to checkpoint before the first event.
+                next_at.is_some()
+            }
+        }
+    }
+
+    fn checkpoint(&self, at: SystemTime) -> Checkpoint<'_> {
+        Checkpoint::Checkpoint {
+            at,
+            tasks: self.tasks.values().collect(),
+        }
+    }
+
+    /// Updates the live tasks with an event.
+    ///
+    /// Only tasks are spawned, so events for other spans are ignored.
+    fn apply(&mut self, event: &Event<SystemTime>) {
+        match *event {
+            Event::Spawn { id, at, ref fields } => {
+                self.tasks.insert(
+                    id,
+                    TaskSnapshot {
+                        id,
+                        spawned_at: at,
+                        fields: fields.clone(),
+                        polls: 0,
+                        poll_started: None,
+                        busy: Duration::ZERO,
+                        wakes: 0,
+                        self_wakes: 0,
+                        waker_clones: 0,
+                        waker_drops: 0,
+                    },
+                );
+            }
+            Event::Enter { id, at } => {
+                if let Some(task) = self.tasks.get_mut(&id) {
+                    task.polls += 1;
+                    task.poll_started.get_or_insert(at);
+                }
+            }
+            Event::Exit { id, at } => {
+                if let Some(task) = self.tasks.get_mut(&id) {
+                    if let Some(started) = task.poll_started.take() {
+                        task.busy += at.duration_since(started).unwrap_or_default();
+                    }
+                }
+            }
+            Event::Close { id, .. } => {
+                self.tasks.remove(&id);
+            }
+            Event::Waker { id, op, .. } => {
+                if let Some(task) = self.tasks.get_mut(&id) {
+                    match op {
+                        super::WakeOp::Wake { self_wake }
+                        | super::WakeOp::WakeByRef { self_wake } => {
+                            task.wakes += 1;
+                            task.self_wakes += u64::from(self_wake);
+                        }
+                        super::WakeOp::Clone => task.waker_clones += 1,
+                        super::WakeOp::Drop => task.waker_drops += 1,
+                    }
+                }
+            }
+        }
+    }
+}
+
+// === impl CountingWriter ===
+
+impl<W> CountingWriter<W> {
+    fn new(inner: W) -> Self {
+        Self { inner, written: 0 }
+    }
+}
+
+impl<W: Write> Write for CountingWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let written = self.inner.write(buf)?;
+        self.written += written as u64;
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
 impl serde::Serialize for SerializeFields {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
     where
@@ -159,3 +391,91 @@ impl serde::Serialize for SerializeField<'_> {
         ser.end()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::WakeOp;
+    use serde_json::Value;
+
+    #[test]
+    fn index_points_at_checkpoints() {
+        let dir = std::env::temp_dir().join(format!("console-record-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("recording.json");
+
+        let base_time = TimeAnchor::new();
+        let start = Instant::now();
+        let secs = |secs| start + Duration::from_secs(secs);
+        let (tx, rx) = crossbeam_channel::unbounded();
+        for event in [
+            Event::Spawn {
+                id: 1,
+                at: secs(0),
+                fields: SerializeFields(Vec::new()),
+            },
+            Event::Spawn {
+                id: 2,
+                at: secs(1),
+                fields: SerializeFields(Vec::new()),
+            },
+            Event::Enter { id: 1, at: secs(2) },
+            Event::Exit { id: 1, at: secs(5) },
+            Event::Waker {
+                id: 2,
+                op: WakeOp::Wake { self_wake: false },
+                at: secs(6),
+            },
+            Event::Close { id: 2, at: secs(8) },
+            // the first checkpoint is written before this event.
+            Event::Enter {
+                id: 1,
+                at: secs(11),
+            },
+            // and the second one before this one, while task 1 is polled.
+            Event::Exit {
+                id: 1,
+                at: secs(22),
+            },
+        ] {
+            tx.send(event).unwrap();
+        }
+        drop(tx);
+        record_io(
+            File::create(&path).unwrap(),
+            File::create(index_path(&path)).unwrap(),
+            rx,
+            base_time,
+        )
+        .unwrap();
+
+        let recording = std::fs::read_to_string(&path).unwrap();
+        let index = std::fs::read_to_string(index_path(&path)).unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        let checkpoints = index
+            .lines()
+            .map(|entry| {
+                let entry: Value = serde_json::from_str(entry).unwrap();
+                let offset = entry["offset"].as_u64().unwrap() as usize;
+                let line = recording[offset..].lines().next().unwrap();
+                let checkpoint: Value = serde_json::from_str(line).unwrap();
+                let checkpoint = checkpoint["Checkpoint"].clone();
+                assert_eq!(checkpoint["at"], entry["at"]);
+                checkpoint
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(checkpoints.len(), 2, "checkpoints: {checkpoints:?}");
+
+        let tasks = checkpoints[0]["tasks"].as_array().unwrap();
+        assert_eq!(tasks.len(), 1, "task 2 was closed: {tasks:?}");
+        assert_eq!(tasks[0]["id"], 1);
+        assert_eq!(tasks[0]["polls"], 1);
+        assert_eq!(tasks[0]["busy"]["secs"], 3);
+        assert_eq!(tasks[0]["poll_started"], Value::Null);
+
+        let tasks = checkpoints[1]["tasks"].as_array().unwrap();
+        assert_eq!(tasks[0]["polls"], 2);
+        assert_ne!(tasks[0]["poll_started"], Value::Null);
+    }
+}
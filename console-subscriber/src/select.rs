@@ -0,0 +1,64 @@
+use std::{panic::Location, time::Instant};
+
+/// Records which branch of a [`tokio::select!`] completed, and how long the
+/// `select!` waited for it.
+///
+/// `tokio::select!` is not instrumented by Tokio itself, so this is opt-in:
+/// create a `SelectTimer` immediately before the `select!`, and call
+/// [`SelectTimer::complete`] at the start of each branch's handler with the
+/// index of that branch. The console aggregates these statistics for each
+/// `select!` callsite, which makes branches that rarely (or never) complete
+/// easy to spot.
+///
+/// # Examples
+///
+/// ```
+/// # async fn doc(mut rx: tokio::sync::mpsc::Receiver<()>) {
+/// use std::time::Duration;
+///
+/// let timer = console_subscriber::SelectTimer::new(2);
+/// tokio::select! {
+///     _ = rx.recv() => timer.complete(0),
+///     _ = tokio::time::sleep(Duration::from_secs(1)) => timer.complete(1),
+/// }
+/// # }
+/// ```
+///
+/// [`tokio::select!`]: https://docs.rs/tokio/latest/tokio/macro.select.html
+#[derive(Debug)]
+pub struct SelectTimer {
+    location: &'static Location<'static>,
+    branches: usize,
+    started: Instant,
+}
+
+impl SelectTimer {
+    /// Starts timing a `select!` with the given number of branches.
+    ///
+    /// The `select!` is identified by the location at which this is called.
+    #[track_caller]
+    pub fn new(branches: usize) -> Self {
+        Self {
+            location: Location::caller(),
+            branches,
+            started: Instant::now(),
+        }
+    }
+
+    /// Records that the branch with the given index completed the `select!`.
+    ///
+    /// Branches are numbered from zero, in the order in which they are
+    /// declared.
+    pub fn complete(&self, branch: usize) {
+        tracing::event!(
+            target: "runtime::select",
+            tracing::Level::TRACE,
+            loc.file = self.location.file(),
+            loc.line = self.location.line(),
+            loc.col = self.location.column(),
+            select.branch = branch,
+            select.branches = self.branches,
+            select.waited_ns = self.started.elapsed().as_nanos() as u64,
+        );
+    }
+}
@@ -1,19 +1,72 @@
-use crate::{attribute, sync::Mutex, ToProto};
+use crate::{alloc::TaskAllocs, attribute, histograms::HistogramPermit, sync::Mutex, ToProto};
 use crossbeam_utils::atomic::AtomicCell;
 use hdrhistogram::{
     self,
     serialization::{Serializer, V2Serializer},
 };
+use std::cell::RefCell;
 use std::cmp;
+use std::mem;
 use std::sync::{
-    atomic::{AtomicBool, AtomicUsize, Ordering::*},
-    Arc,
+    atomic::{AtomicBool, AtomicI32, AtomicU64, AtomicUsize, Ordering::*},
+    Arc, Weak,
 };
 use std::time::{Duration, Instant, SystemTime};
 use tracing::span::Id;
 
 use console_api as proto;
 
+/// The maximum value for channel receive latency histograms.
+///
+/// Values which wait longer than this in a channel are counted as outliers.
+const MAX_RECV_LATENCY_NANOS: u64 = 60_000_000_000;
+
+/// The maximum value for histograms of how long tasks waited to be polled
+/// for the first time.
+///
+/// Tasks which wait longer than this are counted as outliers.
+const MAX_FIRST_POLL_DELAY_NANOS: u64 = 60_000_000_000;
+
+/// The maximum value for histograms of the time between a task's wakes.
+///
+/// Intervals longer than this are counted as outliers.
+const MAX_WAKE_INTERVAL_NANOS: u64 = 60_000_000_000;
+
+/// The maximum number of threads whose polls of a task are counted
+/// separately.
+///
+/// Polls on any further threads are counted together, so that a task which
+/// is polled by many short-lived threads doesn't grow without bound.
+const MAX_POLL_THREADS: usize = 16;
+
+/// The maximum number of other tasks whose wakes of a task are counted
+/// separately.
+///
+/// Wakes by any further tasks are counted together, so that a task which is
+/// woken by many short-lived tasks doesn't grow without bound.
+const MAX_WAKERS: usize = 16;
+
+/// The maximum number of spawn locations whose tasks' busy time is counted
+/// separately for each thread.
+///
+/// The time spent polling tasks spawned anywhere else is counted together,
+/// so that a thread which polls tasks from many locations doesn't grow
+/// without bound.
+const MAX_BUSY_LOCATIONS: usize = 32;
+
+/// The ID of the next [`Threads`] to be created, which distinguishes the
+/// stats each of them keeps for the current thread.
+static NEXT_THREADS_ID: AtomicU64 = AtomicU64::new(0);
+
+std::thread_local! {
+    /// The stats of the current thread for each [`Threads`] it has polled
+    /// tasks for.
+    ///
+    /// These are dropped when the thread exits, which is how a [`Threads`]
+    /// knows which of its threads are still running.
+    static CURRENT_THREAD: RefCell<Vec<Arc<ThreadStats>>> = const { RefCell::new(Vec::new()) };
+}
+
 /// A type which records whether it has unsent updates.
 ///
 /// If something implementing this trait has been changed since the last time
@@ -60,12 +113,44 @@ pub(crate) struct TaskStats {
 
     // waker stats
     wakes: AtomicUsize,
-    waker_clones: AtomicUsize,
-    waker_drops: AtomicUsize,
+    waker_counts: Arc<WakerCounts>,
     self_wakes: AtomicUsize,
 
-    /// Poll durations and other stats.
-    poll_stats: PollStats<Histogram>,
+    /// Poll durations and other stats, with histograms if the task was
+    /// admitted to record them.
+    poll_stats: PollStats<Option<Histogram>>,
+
+    /// Counts this task towards the maximum number of tasks recording
+    /// histograms, until its stats are dropped.
+    _histograms: Option<HistogramPermit>,
+
+    /// The threads which have polled the task.
+    threads: Mutex<ThreadPolls>,
+
+    /// The other tasks which have woken the task.
+    wakers: Mutex<TaskWakers>,
+
+    /// The allocations made while polling the task.
+    allocs: Mutex<TaskAllocs>,
+
+    /// The kind of scheduler which runs the task.
+    scheduler: proto::tasks::task::Scheduler,
+
+    /// Where the task was spawned, which the threads that poll it attribute
+    /// the time they spend doing so to.
+    location: Option<Arc<proto::Location>>,
+}
+
+/// Counts of how many times a task's wakers have been cloned and dropped.
+///
+/// These are shared separately from the rest of the task's stats, so that
+/// the wakers which outlive a completed task can still be counted after its
+/// other stats are dropped.
+#[derive(Debug, Default)]
+pub(crate) struct WakerCounts {
+    is_dirty: AtomicBool,
+    clones: AtomicUsize,
+    drops: AtomicUsize,
 }
 
 /// Stats associated with an async operation.
@@ -88,6 +173,19 @@ pub(crate) struct AsyncOpStats {
     poll_stats: PollStats<()>,
 }
 
+/// Stats associated with a span the application has asked to watch.
+///
+/// Each time the span is entered and exited is recorded as a poll, so the
+/// [`PollStats`] count how many times the span was entered and for how long.
+#[derive(Debug)]
+pub(crate) struct WatchedSpanStats {
+    is_dirty: AtomicBool,
+    is_dropped: AtomicBool,
+    created_at: Instant,
+    dropped_at: Mutex<Option<Instant>>,
+    enter_stats: PollStats<()>,
+}
+
 /// Stats associated with a resource.
 #[derive(Debug)]
 pub(crate) struct ResourceStats {
@@ -96,19 +194,146 @@ pub(crate) struct ResourceStats {
     created_at: Instant,
     dropped_at: Mutex<Option<Instant>>,
     attributes: Mutex<attribute::Attributes>,
+    /// How long values waited between being sent and received, if this
+    /// resource is a channel which has received any values.
+    recv_latencies: Mutex<Option<Histogram>>,
+    /// When this resource is next due to fire, if it is a timer which has
+    /// been scheduled.
+    deadline: Mutex<Option<Instant>>,
+    /// Read and write statistics, if this resource is an I/O resource which
+    /// has been polled.
+    io: Mutex<Option<IoStats>>,
     pub(crate) inherit_child_attributes: bool,
     pub(crate) parent_id: Option<Id>,
 }
 
+/// A single poll of an I/O resource in one direction.
+#[derive(Debug)]
+pub(crate) struct IoEvent {
+    pub(crate) direction: IoDirection,
+    /// The number of bytes transferred, if the poll completed an operation.
+    pub(crate) bytes: u64,
+    /// The number of polls the completed operation took.
+    pub(crate) polls: u64,
+    /// How long the completed operation waited for the resource to become
+    /// ready.
+    pub(crate) waited: Duration,
+    /// Whether the operation has started waiting for the resource to become
+    /// ready, rather than completing.
+    pub(crate) pending: bool,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub(crate) enum IoDirection {
+    Read,
+    Write,
+}
+
+#[derive(Debug, Default)]
+struct IoStats {
+    read: IoDirectionStats,
+    write: IoDirectionStats,
+}
+
+#[derive(Debug, Default)]
+struct IoDirectionStats {
+    bytes: u64,
+    ops: u64,
+    polls: u64,
+    total_wait: Duration,
+    max_wait: Duration,
+    waiting_since: Option<Instant>,
+}
+
 #[derive(Debug, Default)]
 struct PollStats<H> {
     /// The number of polls in progress
     current_polls: AtomicUsize,
     /// The total number of polls
     polls: AtomicUsize,
+    /// The number of completed polls which returned `Poll::Pending`
+    pending_polls: AtomicUsize,
+    /// The number of completed polls which returned `Poll::Ready`
+    ready_polls: AtomicUsize,
     timestamps: Mutex<PollTimestamps<H>>,
 }
 
+/// How many times each thread has polled a task.
+#[derive(Debug, Default)]
+struct ThreadPolls {
+    threads: Vec<(PollThread, u64)>,
+    /// The number of polls on threads beyond the first `MAX_POLL_THREADS`.
+    other: u64,
+}
+
+/// The time a thread has spent polling tasks, by where they were spawned.
+#[derive(Debug, Default)]
+struct LocationBusy {
+    locations: Vec<(Arc<proto::Location>, Duration)>,
+    /// The time spent polling tasks spawned at locations beyond the first
+    /// `MAX_BUSY_LOCATIONS`, or at unknown locations.
+    other: Duration,
+}
+
+/// How many times each other task has woken a task.
+#[derive(Debug, Default)]
+struct TaskWakers {
+    /// The span IDs of the waking tasks, and how many times each one woke
+    /// the task.
+    wakers: Vec<(u64, u64)>,
+    /// The number of wakes by tasks beyond the first `MAX_WAKERS`.
+    other: u64,
+}
+
+/// The threads which have polled the tasks recorded by one `ConsoleLayer`.
+///
+/// Each layer keeps its own, so that several layers in the same process,
+/// such as one for each runtime, don't report each other's threads or count
+/// each other's polls.
+#[derive(Debug)]
+pub(crate) struct Threads {
+    /// Distinguishes these threads' stats from those kept by other layers.
+    id: u64,
+    /// The ID of the next thread to poll a task.
+    next_thread_id: AtomicU64,
+    /// Every thread which has polled a task, so that those which are still
+    /// running can be reported to clients.
+    all: std::sync::Mutex<Vec<Weak<ThreadStats>>>,
+}
+
+/// A thread which has polled a task.
+#[derive(Debug, Clone)]
+struct PollThread {
+    id: u64,
+    name: Option<String>,
+}
+
+/// Statistics for a thread which has polled a task.
+#[derive(Debug)]
+pub(crate) struct ThreadStats {
+    /// The ID of the [`Threads`] these stats belong to.
+    threads: u64,
+    thread: PollThread,
+    #[cfg(all(tokio_unstable, target_has_atomic = "64"))]
+    std_id: std::thread::ThreadId,
+    /// The span ID of the task currently being polled on this thread, or 0
+    /// if no task is being polled.
+    current_task: AtomicU64,
+    /// The number of task polls on this thread, other than polls of tasks
+    /// spawned onto a `LocalSet`.
+    polls: AtomicU64,
+    /// The number of polls of tasks spawned onto a `LocalSet` on this thread.
+    ///
+    /// These are counted separately so that the polls of a `LocalSet`
+    /// running within a task aren't attributed to the runtime's scheduler.
+    local_polls: AtomicU64,
+    /// The scheduler of the tasks most recently polled on this thread, other
+    /// than those spawned onto a `LocalSet`.
+    scheduler: AtomicI32,
+    /// The time spent polling tasks on this thread.
+    busy: Mutex<LocationBusy>,
+}
+
 #[derive(Debug, Default)]
 struct PollTimestamps<H> {
     first_poll: Option<Instant>,
@@ -117,19 +342,32 @@ struct PollTimestamps<H> {
     last_poll_ended: Option<Instant>,
     busy_time: Duration,
     scheduled_time: Duration,
+    /// The duration of the longest poll, and when it started.
+    longest_poll: Option<(Duration, Instant)>,
     poll_histogram: H,
     scheduled_histogram: H,
+    /// The time between each wake and the one before it.
+    wake_interval_histogram: H,
 }
 
-#[derive(Debug)]
-struct Histogram {
+#[derive(Debug, Clone)]
+pub(crate) struct Histogram {
     histogram: hdrhistogram::Histogram<u64>,
     max: u64,
     outliers: u64,
     max_outlier: Option<u64>,
 }
 
-trait RecordDuration {
+/// An approximation of the memory used by the stats the aggregator retains.
+#[derive(Debug, Default)]
+pub(crate) struct MemoryUsage {
+    histograms: u64,
+    histogram_bytes: u64,
+    tasks: u64,
+    task_bytes: u64,
+}
+
+pub(crate) trait RecordDuration {
     fn record_duration(&mut self, duration: Duration);
 }
 
@@ -157,8 +395,13 @@ impl TaskStats {
     pub(crate) fn new(
         poll_duration_max: u64,
         scheduled_duration_max: u64,
+        significant_figures: u8,
         created_at: Instant,
+        scheduler: proto::tasks::task::Scheduler,
+        location: Option<proto::Location>,
+        histograms: Option<HistogramPermit>,
     ) -> Self {
+        let has_histograms = histograms.is_some();
         Self {
             is_dirty: AtomicBool::new(true),
             is_dropped: AtomicBool::new(false),
@@ -166,52 +409,73 @@ impl TaskStats {
             dropped_at: Mutex::new(None),
             poll_stats: PollStats {
                 timestamps: Mutex::new(PollTimestamps {
-                    poll_histogram: Histogram::new(poll_duration_max),
-                    scheduled_histogram: Histogram::new(scheduled_duration_max),
+                    poll_histogram: has_histograms.then(|| {
+                        Histogram::with_significant_figures(poll_duration_max, significant_figures)
+                    }),
+                    scheduled_histogram: has_histograms.then(|| {
+                        Histogram::with_significant_figures(
+                            scheduled_duration_max,
+                            significant_figures,
+                        )
+                    }),
+                    wake_interval_histogram: has_histograms.then(|| {
+                        Histogram::with_significant_figures(
+                            MAX_WAKE_INTERVAL_NANOS,
+                            significant_figures,
+                        )
+                    }),
                     first_poll: None,
                     last_wake: None,
                     last_poll_started: None,
                     last_poll_ended: None,
                     busy_time: Duration::new(0, 0),
                     scheduled_time: Duration::new(0, 0),
+                    longest_poll: None,
                 }),
                 current_polls: AtomicUsize::new(0),
                 polls: AtomicUsize::new(0),
+                pending_polls: AtomicUsize::new(0),
+                ready_polls: AtomicUsize::new(0),
             },
             wakes: AtomicUsize::new(0),
-            waker_clones: AtomicUsize::new(0),
-            waker_drops: AtomicUsize::new(0),
+            waker_counts: Arc::new(WakerCounts::default()),
             self_wakes: AtomicUsize::new(0),
+            threads: Mutex::new(ThreadPolls::default()),
+            wakers: Mutex::new(TaskWakers::default()),
+            allocs: Mutex::new(TaskAllocs::default()),
+            scheduler,
+            location: location.map(Arc::new),
+            _histograms: histograms,
         }
     }
 
     pub(crate) fn record_wake_op(&self, op: crate::WakeOp, at: Instant) {
         use crate::WakeOp;
+        self.waker_counts.record(op);
         match op {
-            WakeOp::Clone => {
-                self.waker_clones.fetch_add(1, Release);
-            }
-            WakeOp::Drop => {
-                self.waker_drops.fetch_add(1, Release);
-            }
-            WakeOp::WakeByRef { self_wake } => self.wake(at, self_wake),
-            WakeOp::Wake { self_wake } => {
-                // Note: `Waker::wake` does *not* call the `drop`
-                // implementation, so waking by value doesn't
-                // trigger a drop event. so, count this as a `drop`
-                // to ensure the task's number of wakers can be
-                // calculated as `clones` - `drops`.
-                //
-                // see
-                // https://github.com/rust-lang/rust/blob/673d0db5e393e9c64897005b470bfeb6d5aec61b/library/core/src/task/wake.rs#L211-L212
-                self.waker_drops.fetch_add(1, Release);
-
+            WakeOp::WakeByRef { self_wake } | WakeOp::Wake { self_wake } => {
                 self.wake(at, self_wake)
             }
+            WakeOp::Clone | WakeOp::Drop => {}
         }
         self.make_dirty();
     }
 
+    /// Returns the counts of this task's waker clones and drops, which may
+    /// outlive the task.
+    pub(crate) fn waker_counts(&self) -> &Arc<WakerCounts> {
+        &self.waker_counts
+    }
+
+    /// Records that the task with the span ID `waker` woke this task.
+    ///
+    /// This is recorded in addition to the wake itself, which is recorded by
+    /// [`TaskStats::record_wake_op`].
+    pub(crate) fn record_waker(&self, waker: &Id) {
+        self.wakers.lock().record(waker.into_u64());
+        self.make_dirty();
+    }
+
     fn wake(&self, at: Instant, self_wake: bool) {
         self.poll_stats.wake(at);
 
@@ -223,13 +487,25 @@ impl TaskStats {
         self.make_dirty();
     }
 
-    pub(crate) fn start_poll(&self, at: Instant) {
+    pub(crate) fn start_poll(&self, at: Instant, id: &Id, threads: &Threads) {
         self.poll_stats.start_poll(at);
+        let current = threads.current();
+        self.threads.lock().record(current.as_deref());
+        if let Some(current) = current {
+            current.start_poll(id.into_u64(), self.scheduler);
+        }
         self.make_dirty();
+        // Done last, so that as little as possible of the work of recording
+        // the poll is counted as the task's allocations.
+        self.allocs.lock().start_poll();
     }
 
-    pub(crate) fn end_poll(&self, at: Instant) {
-        self.poll_stats.end_poll(at);
+    pub(crate) fn end_poll(&self, at: Instant, id: &Id, threads: &Threads) {
+        self.allocs.lock().end_poll();
+        let busy = self.poll_stats.end_poll(at);
+        if let Some(current) = threads.current() {
+            current.end_poll(id.into_u64(), busy, self.location.as_ref());
+        }
         self.make_dirty();
     }
 
@@ -242,20 +518,151 @@ impl TaskStats {
 
         let _prev = self.dropped_at.lock().replace(dropped_at);
         debug_assert_eq!(_prev, None, "tried to drop a task twice; this is a bug!");
+        self.poll_stats.finish();
         self.make_dirty();
     }
 
-    pub(crate) fn poll_duration_histogram(&self) -> proto::tasks::task_details::PollTimesHistogram {
-        let hist = self.poll_stats.timestamps.lock().poll_histogram.to_proto();
-        proto::tasks::task_details::PollTimesHistogram::Histogram(hist)
+    /// Returns the total number of times this task has been woken.
+    pub(crate) fn wakes(&self) -> u64 {
+        self.wakes.load(Acquire) as u64
     }
 
-    pub(crate) fn scheduled_duration_histogram(&self) -> proto::tasks::DurationHistogram {
-        self.poll_stats
+    /// Returns when the task's first poll started, if that poll is still in
+    /// progress.
+    pub(crate) fn first_poll_in_progress(&self) -> Option<Instant> {
+        if self.poll_stats.current_polls.load(Acquire) == 0
+            || self.poll_stats.polls.load(Acquire) != 1
+        {
+            return None;
+        }
+        self.poll_stats.timestamps.lock().first_poll
+    }
+
+    /// Returns when the task last yielded, if it is waiting to be woken: it
+    /// has been polled, isn't being polled, hasn't been woken since its last
+    /// poll started, and hasn't completed.
+    pub(crate) fn idle_since(&self) -> Option<Instant> {
+        if self.is_dropped.load(Acquire) || self.poll_stats.current_polls.load(Acquire) > 0 {
+            return None;
+        }
+        let timestamps = self.poll_stats.timestamps.lock();
+        if timestamps.last_wake > timestamps.last_poll_started {
+            return None;
+        }
+        timestamps.last_poll_ended
+    }
+
+    /// Returns the total time this task has spent being polled.
+    pub(crate) fn busy_time(&self) -> Duration {
+        self.poll_stats.timestamps.lock().busy_time
+    }
+
+    /// Returns the histogram of this task's poll durations, with at most
+    /// `significant_figures` of precision, if set, or `None` if the task
+    /// doesn't record histograms.
+    pub(crate) fn poll_duration_histogram(
+        &self,
+        significant_figures: Option<u8>,
+    ) -> Option<proto::tasks::task_details::PollTimesHistogram> {
+        let hist = self
+            .poll_stats
             .timestamps
             .lock()
-            .scheduled_histogram
-            .to_proto()
+            .poll_histogram
+            .as_ref()?
+            .to_proto_with_precision(significant_figures);
+        Some(proto::tasks::task_details::PollTimesHistogram::Histogram(
+            hist,
+        ))
+    }
+
+    /// Adds this task's poll durations to `merged`, a histogram of the poll
+    /// durations of several tasks.
+    ///
+    /// Nothing is added if the task doesn't record histograms.
+    pub(crate) fn merge_poll_durations(&self, merged: &mut Option<Histogram>) {
+        let timestamps = self.poll_stats.timestamps.lock();
+        let Some(poll_histogram) = &timestamps.poll_histogram else {
+            return;
+        };
+        match merged {
+            Some(merged) => merged.add(poll_histogram),
+            None => *merged = Some(poll_histogram.clone()),
+        }
+    }
+
+    /// Records how long this task waited between being spawned and its first
+    /// poll in `merged`, a histogram of the first poll delays of several
+    /// tasks.
+    ///
+    /// Nothing is recorded if the task hasn't been polled yet.
+    pub(crate) fn merge_first_poll_delay(&self, merged: &mut Option<Histogram>) {
+        let first_poll = self.poll_stats.timestamps.lock().first_poll;
+        if let Some(first_poll) = first_poll {
+            let delay = first_poll.saturating_duration_since(self.created_at);
+            merged
+                .get_or_insert_with(|| Histogram::new(MAX_FIRST_POLL_DELAY_NANOS))
+                .record_duration(delay);
+        }
+    }
+
+    /// Returns the histogram of this task's scheduled durations, with at most
+    /// `significant_figures` of precision, if set, or `None` if the task
+    /// doesn't record histograms.
+    pub(crate) fn scheduled_duration_histogram(
+        &self,
+        significant_figures: Option<u8>,
+    ) -> Option<proto::tasks::DurationHistogram> {
+        let timestamps = self.poll_stats.timestamps.lock();
+        let hist = timestamps.scheduled_histogram.as_ref()?;
+        Some(hist.to_proto_with_precision(significant_figures))
+    }
+
+    /// Returns the histogram of the time between this task's wakes, with at
+    /// most `significant_figures` of precision, if set, or `None` if the task
+    /// doesn't record histograms.
+    pub(crate) fn wake_interval_histogram(
+        &self,
+        significant_figures: Option<u8>,
+    ) -> Option<proto::tasks::DurationHistogram> {
+        let timestamps = self.poll_stats.timestamps.lock();
+        let hist = timestamps.wake_interval_histogram.as_ref()?;
+        Some(hist.to_proto_with_precision(significant_figures))
+    }
+
+    /// Returns how many times each thread has polled this task, and the
+    /// number of polls on threads which aren't tracked separately.
+    pub(crate) fn thread_polls(&self) -> (Vec<proto::tasks::ThreadPolls>, u64) {
+        let threads = self.threads.lock();
+        let polls = threads
+            .threads
+            .iter()
+            .map(|(thread, polls)| proto::tasks::ThreadPolls {
+                thread_id: thread.id,
+                name: thread.name.clone(),
+                polls: *polls,
+            })
+            .collect();
+        (polls, threads.other)
+    }
+
+    /// Adds the approximate memory used by this task's stats, and by its
+    /// histograms, to `usage`.
+    pub(crate) fn measure(&self, usage: &mut MemoryUsage) {
+        let mut bytes = mem::size_of::<Self>();
+        bytes += self.threads.lock().threads.capacity() * mem::size_of::<(PollThread, u64)>();
+        bytes += self.wakers.lock().wakers.capacity() * mem::size_of::<(u64, u64)>();
+        usage.task_bytes += bytes as u64;
+
+        let timestamps = self.poll_stats.timestamps.lock();
+        [
+            &timestamps.poll_histogram,
+            &timestamps.scheduled_histogram,
+            &timestamps.wake_interval_histogram,
+        ]
+        .into_iter()
+        .flatten()
+        .for_each(|histogram| usage.add_histogram(histogram));
     }
 
     #[inline]
@@ -270,14 +677,18 @@ impl ToProto for TaskStats {
     fn to_proto(&self, base_time: &TimeAnchor) -> Self::Output {
         let poll_stats = Some(self.poll_stats.to_proto(base_time));
         let timestamps = self.poll_stats.timestamps.lock();
+        let wakers = self.wakers.lock();
+        let waker_clones = self.waker_counts.clones();
+        let waker_drops = self.waker_counts.drops();
         proto::tasks::Stats {
             poll_stats,
             created_at: Some(base_time.to_timestamp(self.created_at)),
             dropped_at: self.dropped_at.lock().map(|at| base_time.to_timestamp(at)),
             wakes: self.wakes.load(Acquire) as u64,
-            waker_clones: self.waker_clones.load(Acquire) as u64,
+            waker_clones,
             self_wakes: self.self_wakes.load(Acquire) as u64,
-            waker_drops: self.waker_drops.load(Acquire) as u64,
+            waker_drops,
+            live_wakers: Some(waker_clones as i64 - waker_drops as i64),
             last_wake: timestamps.last_wake.map(|at| base_time.to_timestamp(at)),
             scheduled_time: Some(
                 timestamps
@@ -291,12 +702,83 @@ impl ToProto for TaskStats {
                         Default::default()
                     }),
             ),
+            max_poll_time: timestamps
+                .longest_poll
+                .and_then(|(longest, _)| longest.try_into().ok()),
+            max_poll_started_at: timestamps
+                .longest_poll
+                .map(|(_, started)| base_time.to_timestamp(started)),
+            woken_by: wakers
+                .wakers
+                .iter()
+                .map(|&(task_id, wakes)| proto::tasks::TaskWakes {
+                    task_id: Some(proto::Id { id: task_id }),
+                    wakes,
+                })
+                .collect(),
+            other_task_wakes: wakers.other,
+            alloc_stats: self.allocs.lock().to_proto(),
         }
     }
 }
 
 impl Unsent for TaskStats {
     #[inline]
+    fn take_unsent(&self) -> bool {
+        // The waker counts are changed without dirtying the rest of the
+        // stats once the task has completed, so both flags are cleared.
+        self.is_dirty.swap(false, AcqRel) | self.waker_counts.take_unsent()
+    }
+
+    fn is_unsent(&self) -> bool {
+        self.is_dirty.load(Acquire) || self.waker_counts.is_unsent()
+    }
+}
+
+// === impl WakerCounts ===
+
+impl WakerCounts {
+    /// Records a waker operation, counting how it changes the number of
+    /// wakers which are alive.
+    pub(crate) fn record(&self, op: crate::WakeOp) {
+        use crate::WakeOp;
+        match op {
+            WakeOp::Clone => {
+                self.clones.fetch_add(1, Release);
+            }
+            // Note: `Waker::wake` does *not* call the `drop`
+            // implementation, so waking by value doesn't
+            // trigger a drop event. so, count this as a `drop`
+            // to ensure the task's number of wakers can be
+            // calculated as `clones` - `drops`.
+            //
+            // see
+            // https://github.com/rust-lang/rust/blob/673d0db5e393e9c64897005b470bfeb6d5aec61b/library/core/src/task/wake.rs#L211-L212
+            WakeOp::Drop | WakeOp::Wake { .. } => {
+                self.drops.fetch_add(1, Release);
+            }
+            WakeOp::WakeByRef { .. } => return,
+        }
+        self.is_dirty.store(true, Release);
+    }
+
+    pub(crate) fn clones(&self) -> u64 {
+        self.clones.load(Acquire) as u64
+    }
+
+    pub(crate) fn drops(&self) -> u64 {
+        self.drops.load(Acquire) as u64
+    }
+
+    /// Returns the number of wakers which are currently alive.
+    ///
+    /// This is negative if the instrumentation missed some clones.
+    pub(crate) fn live(&self) -> i64 {
+        self.clones() as i64 - self.drops() as i64
+    }
+}
+
+impl Unsent for WakerCounts {
     fn take_unsent(&self) -> bool {
         self.is_dirty.swap(false, AcqRel)
     }
@@ -318,6 +800,210 @@ impl DroppedAt for TaskStats {
     }
 }
 
+// === impl ThreadPolls ===
+
+impl ThreadPolls {
+    /// Records a poll on the current thread, whose stats are `current`.
+    ///
+    /// If the thread is exiting, its stats may already have been destroyed,
+    /// so the poll is counted with those on untracked threads.
+    fn record(&mut self, current: Option<&ThreadStats>) {
+        let Some(current) = current else {
+            self.other += 1;
+            return;
+        };
+        if let Some((_, polls)) = self
+            .threads
+            .iter_mut()
+            .find(|(thread, _)| thread.id == current.thread.id)
+        {
+            *polls += 1;
+        } else if self.threads.len() < MAX_POLL_THREADS {
+            self.threads.push((current.thread.clone(), 1));
+        } else {
+            self.other += 1;
+        }
+    }
+}
+
+// === impl TaskWakers ===
+
+impl TaskWakers {
+    /// Records a wake by the task with the span ID `waker`.
+    fn record(&mut self, waker: u64) {
+        if let Some((_, wakes)) = self.wakers.iter_mut().find(|(id, _)| *id == waker) {
+            *wakes += 1;
+        } else if self.wakers.len() < MAX_WAKERS {
+            self.wakers.push((waker, 1));
+        } else {
+            self.other += 1;
+        }
+    }
+}
+
+// === impl Threads ===
+
+impl Threads {
+    pub(crate) fn new() -> Self {
+        Self {
+            id: NEXT_THREADS_ID.fetch_add(1, Relaxed),
+            next_thread_id: AtomicU64::new(0),
+            all: std::sync::Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Returns the stats of the current thread, registering it if it hasn't
+    /// polled any of these tasks before.
+    ///
+    /// Returns `None` if the thread is exiting, in which case its stats may
+    /// already have been destroyed.
+    fn current(&self) -> Option<Arc<ThreadStats>> {
+        CURRENT_THREAD
+            .try_with(|current| {
+                let mut current = current.borrow_mut();
+                if let Some(stats) = current.iter().find(|stats| stats.threads == self.id) {
+                    return stats.clone();
+                }
+                let stats = self.register();
+                current.push(stats.clone());
+                stats
+            })
+            .ok()
+    }
+
+    /// Creates the stats for the current thread, and adds them to the
+    /// threads reported to clients.
+    fn register(&self) -> Arc<ThreadStats> {
+        let thread = std::thread::current();
+        let stats = Arc::new(ThreadStats {
+            threads: self.id,
+            thread: PollThread {
+                id: self.next_thread_id.fetch_add(1, Relaxed),
+                name: thread.name().map(String::from),
+            },
+            #[cfg(all(tokio_unstable, target_has_atomic = "64"))]
+            std_id: thread.id(),
+            current_task: AtomicU64::new(0),
+            polls: AtomicU64::new(0),
+            local_polls: AtomicU64::new(0),
+            scheduler: AtomicI32::new(proto::tasks::task::Scheduler::Unknown as i32),
+            busy: Mutex::new(LocationBusy::default()),
+        });
+        self.all
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+            .push(Arc::downgrade(&stats));
+        stats
+    }
+
+    /// Returns the stats for every thread which has polled a task and is
+    /// still running.
+    pub(crate) fn all(&self) -> Vec<Arc<ThreadStats>> {
+        let mut threads = self
+            .all
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner);
+        threads.retain(|thread| thread.strong_count() > 0);
+        threads.iter().filter_map(Weak::upgrade).collect()
+    }
+}
+
+// === impl ThreadStats ===
+
+impl ThreadStats {
+    /// Records that the thread has started polling the task with the given
+    /// span ID, which is run by `scheduler`.
+    fn start_poll(&self, task: u64, scheduler: proto::tasks::task::Scheduler) {
+        use proto::tasks::task::Scheduler;
+        self.current_task.store(task, Release);
+        match scheduler {
+            Scheduler::Local => {
+                self.local_polls.fetch_add(1, Release);
+            }
+            Scheduler::Unknown => {
+                self.polls.fetch_add(1, Release);
+            }
+            scheduler => {
+                self.polls.fetch_add(1, Release);
+                self.scheduler.store(scheduler as i32, Release);
+            }
+        }
+    }
+
+    /// Records that the thread has finished polling the task with the given
+    /// span ID, which was spawned at `location`.
+    ///
+    /// `busy` is how long the poll took, if it was the task's outermost poll.
+    fn end_poll(&self, task: u64, busy: Option<Duration>, location: Option<&Arc<proto::Location>>) {
+        // Only clear the current task if another poll hasn't started on this
+        // thread since.
+        let _ = self.current_task.compare_exchange(task, 0, AcqRel, Acquire);
+        if let Some(busy) = busy {
+            self.busy.lock().record(busy, location);
+        }
+    }
+
+    /// Returns the ID the thread was given by the standard library, which
+    /// the runtime's metrics use to identify its workers.
+    #[cfg(all(tokio_unstable, target_has_atomic = "64"))]
+    pub(crate) fn std_id(&self) -> std::thread::ThreadId {
+        self.std_id
+    }
+
+    pub(crate) fn to_proto(&self) -> proto::runtime::Worker {
+        let current_task = match self.current_task.load(Acquire) {
+            0 => None,
+            id => Some(proto::Id { id }),
+        };
+        let (busy_by_location, other_busy_time) = {
+            let busy = self.busy.lock();
+            let locations = busy
+                .locations
+                .iter()
+                .map(|(location, busy)| proto::runtime::LocationBusyTime {
+                    location: Some(proto::Location::clone(location)),
+                    busy_time: (*busy).try_into().ok(),
+                })
+                .collect();
+            (locations, busy.other)
+        };
+        proto::runtime::Worker {
+            thread_id: self.thread.id,
+            thread_name: self.thread.name.clone(),
+            current_task,
+            polls: self.polls.load(Acquire),
+            local_polls: self.local_polls.load(Acquire),
+            scheduler: self.scheduler.load(Acquire),
+            busy_by_location,
+            other_busy_time: other_busy_time.try_into().ok(),
+            ..Default::default()
+        }
+    }
+}
+
+// === impl LocationBusy ===
+
+impl LocationBusy {
+    /// Records `busy` spent polling a task spawned at `location`.
+    fn record(&mut self, busy: Duration, location: Option<&Arc<proto::Location>>) {
+        let Some(location) = location else {
+            self.other += busy;
+            return;
+        };
+        if let Some((_, total)) = self
+            .locations
+            .iter_mut()
+            .find(|(known, _)| Arc::ptr_eq(known, location) || known == location)
+        {
+            *total += busy;
+        } else if self.locations.len() < MAX_BUSY_LOCATIONS {
+            self.locations.push((location.clone(), busy));
+        } else {
+            self.other += busy;
+        }
+    }
+}
+
 // === impl AsyncOpStats ===
 
 impl AsyncOpStats {
@@ -348,6 +1034,7 @@ impl AsyncOpStats {
     }
 
     pub(crate) fn drop_async_op(&self, dropped_at: Instant) {
+        self.poll_stats.finish();
         self.stats.drop_resource(dropped_at)
     }
 
@@ -404,6 +1091,83 @@ impl ToProto for AsyncOpStats {
     }
 }
 
+// === impl WatchedSpanStats ===
+
+impl WatchedSpanStats {
+    pub(crate) fn new(created_at: Instant) -> Self {
+        Self {
+            is_dirty: AtomicBool::new(true),
+            is_dropped: AtomicBool::new(false),
+            created_at,
+            dropped_at: Mutex::new(None),
+            enter_stats: PollStats::default(),
+        }
+    }
+
+    pub(crate) fn enter(&self, at: Instant) {
+        self.enter_stats.start_poll(at);
+        self.make_dirty();
+    }
+
+    pub(crate) fn exit(&self, at: Instant) {
+        self.enter_stats.end_poll(at);
+        self.make_dirty();
+    }
+
+    pub(crate) fn drop_span(&self, dropped_at: Instant) {
+        if self.is_dropped.swap(true, AcqRel) {
+            // The span was already closed.
+            return;
+        }
+
+        self.enter_stats.finish();
+        let _prev = self.dropped_at.lock().replace(dropped_at);
+        debug_assert_eq!(_prev, None, "tried to drop a span twice; this is a bug!");
+        self.make_dirty();
+    }
+
+    #[inline]
+    fn make_dirty(&self) {
+        self.is_dirty.swap(true, AcqRel);
+    }
+}
+
+impl Unsent for WatchedSpanStats {
+    #[inline]
+    fn take_unsent(&self) -> bool {
+        self.is_dirty.swap(false, AcqRel)
+    }
+
+    #[inline]
+    fn is_unsent(&self) -> bool {
+        self.is_dirty.load(Acquire)
+    }
+}
+
+impl DroppedAt for WatchedSpanStats {
+    fn dropped_at(&self) -> Option<Instant> {
+        // avoid acquiring the lock if we know we haven't tried to drop this
+        // thing yet
+        if self.is_dropped.load(Acquire) {
+            return *self.dropped_at.lock();
+        }
+
+        None
+    }
+}
+
+impl ToProto for WatchedSpanStats {
+    type Output = proto::spans::Stats;
+
+    fn to_proto(&self, base_time: &TimeAnchor) -> Self::Output {
+        proto::spans::Stats {
+            created_at: Some(base_time.to_timestamp(self.created_at)),
+            dropped_at: self.dropped_at.lock().map(|at| base_time.to_timestamp(at)),
+            enter_stats: Some(self.enter_stats.to_proto(base_time)),
+        }
+    }
+}
+
 // === impl ResourceStats ===
 
 impl ResourceStats {
@@ -418,6 +1182,9 @@ impl ResourceStats {
             created_at,
             dropped_at: Mutex::new(None),
             attributes: Default::default(),
+            recv_latencies: Mutex::new(None),
+            deadline: Mutex::new(None),
+            io: Mutex::new(None),
             inherit_child_attributes,
             parent_id,
         }
@@ -428,6 +1195,66 @@ impl ResourceStats {
         self.make_dirty();
     }
 
+    /// Adds the approximate memory used by this resource's histograms to
+    /// `usage`.
+    pub(crate) fn measure(&self, usage: &mut MemoryUsage) {
+        if let Some(ref histogram) = *self.recv_latencies.lock() {
+            usage.add_histogram(histogram);
+        }
+    }
+
+    /// Records how long a value waited in this channel before it was received.
+    pub(crate) fn record_recv_latency(&self, latency: Duration) {
+        self.recv_latencies
+            .lock()
+            .get_or_insert_with(|| Histogram::new(MAX_RECV_LATENCY_NANOS))
+            .record_duration(latency);
+        self.make_dirty();
+    }
+
+    /// Records when this resource is next due to fire, if `update` sets the
+    /// `duration` of a timer.
+    pub(crate) fn update_deadline(&self, update: &attribute::Update) {
+        use proto::field::{Name, Value};
+        if let (Some(Name::StrName(name)), Some(Value::U64Val(ms)), Some("ms")) = (
+            &update.field.name,
+            &update.field.value,
+            update.unit.as_deref(),
+        ) {
+            if name == "duration" {
+                *self.deadline.lock() = Some(Instant::now() + Duration::from_millis(*ms));
+            }
+        }
+    }
+
+    /// Returns when this resource was due to fire, if it is a timer, and
+    /// clears the deadline so that each firing is only observed once.
+    pub(crate) fn take_deadline(&self) -> Option<Instant> {
+        self.deadline.lock().take()
+    }
+
+    pub(crate) fn record_io(&self, event: &IoEvent, at: Instant) {
+        let mut io = self.io.lock();
+        let io = io.get_or_insert_with(IoStats::default);
+        let stats = match event.direction {
+            IoDirection::Read => &mut io.read,
+            IoDirection::Write => &mut io.write,
+        };
+        if event.pending {
+            // The wait started when the operation was first polled, so it
+            // may have been waiting for slightly longer than this.
+            stats.waiting_since.get_or_insert(at);
+        } else {
+            stats.bytes += event.bytes;
+            stats.ops += 1;
+            stats.polls += event.polls;
+            stats.total_wait += event.waited;
+            stats.max_wait = cmp::max(stats.max_wait, event.waited);
+            stats.waiting_since = None;
+        }
+        self.make_dirty();
+    }
+
     #[inline]
     pub(crate) fn drop_resource(&self, dropped_at: Instant) {
         if self.is_dropped.swap(true, AcqRel) {
@@ -483,6 +1310,26 @@ impl ToProto for ResourceStats {
             created_at: Some(base_time.to_timestamp(self.created_at)),
             dropped_at: self.dropped_at.lock().map(|at| base_time.to_timestamp(at)),
             attributes,
+            recv_latency_histogram: self.recv_latencies.lock().as_ref().map(Histogram::to_proto),
+            io_stats: self.io.lock().as_ref().map(|io| proto::resources::IoStats {
+                read: Some(io.read.to_proto(base_time)),
+                write: Some(io.write.to_proto(base_time)),
+            }),
+        }
+    }
+}
+
+impl ToProto for IoDirectionStats {
+    type Output = proto::resources::IoDirectionStats;
+
+    fn to_proto(&self, base_time: &TimeAnchor) -> Self::Output {
+        proto::resources::IoDirectionStats {
+            bytes: self.bytes,
+            ops: self.ops,
+            polls: self.polls,
+            total_wait: self.total_wait.try_into().ok(),
+            max_wait: self.max_wait.try_into().ok(),
+            waiting_since: self.waiting_since.map(|at| base_time.to_timestamp(at)),
         }
     }
 }
@@ -492,6 +1339,12 @@ impl ToProto for ResourceStats {
 impl<H: RecordDuration> PollStats<H> {
     fn wake(&self, at: Instant) {
         let mut timestamps = self.timestamps.lock();
+        if let Some(last_wake) = timestamps.last_wake {
+            // `at < last_wake` is possible when the task is woken from
+            // several threads at once.
+            let interval = at.saturating_duration_since(last_wake);
+            timestamps.wake_interval_histogram.record_duration(interval);
+        }
         timestamps.last_wake = cmp::max(timestamps.last_wake, Some(at));
     }
 
@@ -527,12 +1380,18 @@ impl<H: RecordDuration> PollStats<H> {
         timestamps.scheduled_time += elapsed;
     }
 
-    fn end_poll(&self, at: Instant) {
+    /// Records that a poll ended `at` the given time, returning how long it
+    /// took if it was the outermost of the object's current polls.
+    fn end_poll(&self, at: Instant) -> Option<Duration> {
         // Are we ending the last current poll?
         if self.current_polls.fetch_sub(1, AcqRel) > 1 {
-            return;
+            return None;
         }
 
+        // The runtime doesn't tell us what the poll returned, so assume that
+        // it was `Pending` until the object is dropped.
+        self.pending_polls.fetch_add(1, Release);
+
         let mut timestamps = self.timestamps.lock();
         let started = match timestamps.last_poll_started {
             Some(last_poll) => last_poll,
@@ -541,7 +1400,7 @@ impl<H: RecordDuration> PollStats<H> {
                     "a poll ended, but start timestamp was recorded. \
                      this is probably a `console-subscriber` bug"
                 );
-                return;
+                return None;
             }
         };
 
@@ -554,14 +1413,33 @@ impl<H: RecordDuration> PollStats<H> {
                     was before its start timestamp\nstart = {:?}\n  end = {:?}",
                     started, at
                 );
-                return;
+                return None;
             }
         };
 
         // if we have a poll time histogram, add the timestamp
         timestamps.poll_histogram.record_duration(elapsed);
 
+        if !matches!(timestamps.longest_poll, Some((longest, _)) if longest >= elapsed) {
+            timestamps.longest_poll = Some((elapsed, started));
+        }
+
         timestamps.busy_time += elapsed;
+        Some(elapsed)
+    }
+}
+
+impl<H> PollStats<H> {
+    /// Records that the polled object was dropped, so its final poll must
+    /// have returned `Ready`, if it was polled at all.
+    fn finish(&self) {
+        let was_pending = self
+            .pending_polls
+            .fetch_update(AcqRel, Acquire, |polls| polls.checked_sub(1))
+            .is_ok();
+        if was_pending {
+            self.ready_polls.fetch_add(1, Release);
+        }
     }
 }
 
@@ -572,6 +1450,8 @@ impl<H> ToProto for PollStats<H> {
         let timestamps = self.timestamps.lock();
         proto::PollStats {
             polls: self.polls.load(Acquire) as u64,
+            pending_polls: self.pending_polls.load(Acquire) as u64,
+            ready_polls: self.ready_polls.load(Acquire) as u64,
             first_poll: timestamps.first_poll.map(|at| base_time.to_timestamp(at)),
             last_poll_started: timestamps
                 .last_poll_started
@@ -615,13 +1495,48 @@ impl<T: ToProto> ToProto for Arc<T> {
     }
 }
 
+// === impl MemoryUsage ===
+
+impl MemoryUsage {
+    /// Adds a task whose static data takes `bytes`, not including its stats.
+    pub(crate) fn add_task(&mut self, bytes: usize) {
+        self.tasks += 1;
+        self.task_bytes += bytes as u64;
+    }
+
+    fn add_histogram(&mut self, histogram: &Histogram) {
+        self.histograms += 1;
+        self.histogram_bytes += histogram.memory_size() as u64;
+    }
+
+    pub(crate) fn to_proto(
+        &self,
+        histogram_significant_figures: u8,
+    ) -> proto::instrument::MemoryUsage {
+        proto::instrument::MemoryUsage {
+            histograms: self.histograms,
+            histogram_bytes: self.histogram_bytes,
+            histogram_significant_figures: histogram_significant_figures.into(),
+            tasks: self.tasks,
+            task_bytes: self.task_bytes,
+        }
+    }
+}
+
 // === impl Histogram ===
 
 impl Histogram {
-    fn new(max: u64) -> Self {
+    pub(crate) fn new(max: u64) -> Self {
+        Self::with_significant_figures(
+            max,
+            crate::ConsoleLayer::DEFAULT_HISTOGRAM_SIGNIFICANT_FIGURES,
+        )
+    }
+
+    pub(crate) fn with_significant_figures(max: u64, significant_figures: u8) -> Self {
         // significant figures should be in the [0-5] range and memory usage
         // grows exponentially with higher a sigfig
-        let histogram = hdrhistogram::Histogram::new_with_max(max, 2).unwrap();
+        let histogram = hdrhistogram::Histogram::new_with_max(max, significant_figures).unwrap();
         Self {
             histogram,
             max,
@@ -630,11 +1545,58 @@ impl Histogram {
         }
     }
 
-    fn to_proto(&self) -> proto::tasks::DurationHistogram {
+    /// Returns the approximate number of bytes this histogram uses, which
+    /// is mostly its array of counts.
+    ///
+    /// The array grows with the histogram's significant figures and
+    /// maximum value, but not with the number of values recorded.
+    fn memory_size(&self) -> usize {
+        mem::size_of::<Self>() + self.histogram.distinct_values() * mem::size_of::<u64>()
+    }
+
+    /// Adds the durations recorded by `other` to this histogram.
+    pub(crate) fn add(&mut self, other: &Histogram) {
+        self.histogram
+            .add(&other.histogram)
+            .expect("histograms with the same max value can always be added");
+        self.outliers += other.outliers;
+        self.max_outlier = cmp::max(self.max_outlier, other.max_outlier);
+    }
+
+    pub(crate) fn to_proto(&self) -> proto::tasks::DurationHistogram {
+        self.serialize(&self.histogram)
+    }
+
+    /// Serializes this histogram with at most `significant_figures` of
+    /// precision.
+    ///
+    /// Histograms can't be made more precise than they were recorded, so a
+    /// higher value serializes the histogram unchanged.
+    pub(crate) fn to_proto_with_precision(
+        &self,
+        significant_figures: Option<u8>,
+    ) -> proto::tasks::DurationHistogram {
+        match significant_figures {
+            Some(sigfig) if sigfig < self.histogram.sigfig() => {
+                let mut coarse = hdrhistogram::Histogram::new_with_max(self.max, sigfig)
+                    .expect("significant figures are less than the recorded histogram's");
+                coarse
+                    .add(&self.histogram)
+                    .expect("histograms with the same max value can always be added");
+                self.serialize(&coarse)
+            }
+            _ => self.to_proto(),
+        }
+    }
+
+    fn serialize(
+        &self,
+        histogram: &hdrhistogram::Histogram<u64>,
+    ) -> proto::tasks::DurationHistogram {
         let mut serializer = V2Serializer::new();
         let mut raw_histogram = Vec::new();
         serializer
-            .serialize(&self.histogram, &mut raw_histogram)
+            .serialize(histogram, &mut raw_histogram)
             .expect("histogram failed to serialize");
         proto::tasks::DurationHistogram {
             raw_histogram,
@@ -645,6 +1607,14 @@ impl Histogram {
     }
 }
 
+impl<H: RecordDuration> RecordDuration for Option<H> {
+    fn record_duration(&mut self, duration: Duration) {
+        if let Some(histogram) = self {
+            histogram.record_duration(duration);
+        }
+    }
+}
+
 impl RecordDuration for Histogram {
     fn record_duration(&mut self, duration: Duration) {
         let mut duration_ns = duration.as_nanos() as u64;
@@ -0,0 +1,100 @@
+//! Support for inheriting the server's listening socket from systemd.
+//!
+//! See [`sd_listen_fds(3)`] for a description of the protocol.
+//!
+//! [`sd_listen_fds(3)`]: https://www.freedesktop.org/software/systemd/man/latest/sd_listen_fds.html
+use std::{
+    env, io, net,
+    os::{
+        fd::{FromRawFd, IntoRawFd, RawFd},
+        unix::net as unix,
+    },
+    process,
+    sync::atomic::{AtomicBool, Ordering},
+};
+
+/// The first file descriptor passed by systemd. Any others follow it.
+const LISTEN_FDS_START: RawFd = 3;
+
+/// Whether the socket passed by systemd has been taken.
+static TAKEN: AtomicBool = AtomicBool::new(false);
+
+/// A listening socket inherited from systemd.
+#[derive(Debug)]
+pub(crate) enum Listener {
+    Tcp(tokio::net::TcpListener),
+    Unix(tokio::net::UnixListener),
+}
+
+impl Listener {
+    /// Takes the first socket passed to this process by systemd, if there is
+    /// one.
+    ///
+    /// The variables describing the passed sockets are left in the
+    /// environment, as removing them would race with the application's
+    /// threads reading it. Child processes still won't take the socket, as
+    /// `LISTEN_PID` names this process, and it's only ever taken once here.
+    pub(crate) fn take() -> io::Result<Option<Self>> {
+        let pid = env::var("LISTEN_PID").ok();
+        let fds = env::var("LISTEN_FDS").ok();
+        if !is_passed(pid.as_deref(), fds.as_deref(), process::id()) {
+            return Ok(None);
+        }
+        if TAKEN.swap(true, Ordering::AcqRel) {
+            return Ok(None);
+        }
+
+        // Safety: systemd passes ownership of the sockets to this process,
+        // and `TAKEN` ensures that nothing else can take the socket.
+        unsafe { Self::from_raw_fd(LISTEN_FDS_START) }.map(Some)
+    }
+
+    /// # Safety
+    ///
+    /// `fd` must be an open, listening socket which is owned by the caller.
+    unsafe fn from_raw_fd(fd: RawFd) -> io::Result<Self> {
+        // The standard library has no way to ask which family a socket
+        // belongs to, but getting the local address of a Unix socket as an
+        // IP address fails.
+        let listener = net::TcpListener::from_raw_fd(fd);
+        if listener.local_addr().is_ok() {
+            listener.set_nonblocking(true)?;
+            return tokio::net::TcpListener::from_std(listener).map(Self::Tcp);
+        }
+
+        let listener = unix::UnixListener::from_raw_fd(listener.into_raw_fd());
+        listener.local_addr()?;
+        listener.set_nonblocking(true)?;
+        tokio::net::UnixListener::from_std(listener).map(Self::Unix)
+    }
+}
+
+/// Returns whether systemd passed sockets to the process with the ID `this`,
+/// given the values of `LISTEN_PID` and `LISTEN_FDS`.
+fn is_passed(pid: Option<&str>, fds: Option<&str>, this: u32) -> bool {
+    // If the PID doesn't match, the sockets were passed to another process,
+    // which then spawned this one.
+    pid.and_then(|pid| pid.parse::<u32>().ok()) == Some(this)
+        && matches!(fds.and_then(|fds| fds.parse::<RawFd>().ok()), Some(fds) if fds > 0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sockets_must_be_passed_to_this_process() {
+        assert!(is_passed(Some("42"), Some("1"), 42));
+        assert!(is_passed(Some("42"), Some("2"), 42));
+
+        // passed to another process.
+        assert!(!is_passed(Some("41"), Some("1"), 42));
+        assert!(!is_passed(None, Some("1"), 42));
+        assert!(!is_passed(Some("not a pid"), Some("1"), 42));
+
+        // no sockets passed.
+        assert!(!is_passed(Some("42"), Some("0"), 42));
+        assert!(!is_passed(Some("42"), Some("-1"), 42));
+        assert!(!is_passed(Some("42"), None, 42));
+    }
+}
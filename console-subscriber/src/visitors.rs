@@ -2,9 +2,14 @@
 //! fields from tracing metadata and producing the parts
 //! needed to construct `Event` instances.
 
-use super::{attribute, WakeOp};
+use super::{
+    attribute,
+    stats::{IoDirection, IoEvent},
+    WakeOp,
+};
 use console_api as proto;
 use proto::resources::resource;
+use std::{fmt, sync::Arc, time::Duration};
 use tracing_core::{
     field::{self, Visit},
     span,
@@ -56,8 +61,26 @@ pub(crate) struct ResourceVisitorResult {
 pub(crate) struct FieldVisitor {
     fields: Vec<proto::Field>,
     meta_id: proto::MetaId,
+    /// Fields beyond this many are counted in `omitted`, rather than recorded.
+    max_fields: usize,
+    /// String values longer than this many bytes are truncated.
+    max_value_len: usize,
+    /// Replaces the values of sensitive fields, if set.
+    redact: Option<RedactFields>,
+    omitted: u64,
 }
 
+/// A hook which replaces the values of sensitive fields before they are
+/// recorded, as set by [`Builder::redact_fields`].
+///
+/// [`Builder::redact_fields`]: crate::Builder::redact_fields
+#[derive(Clone)]
+pub(crate) struct RedactFields(Arc<RedactFn>);
+
+/// Returns the replacement for the value of the named field, if it is
+/// redacted.
+type RedactFn = dyn Fn(&str, &str) -> Option<String> + Send + Sync;
+
 /// Used to extract the fields needed to construct
 /// an `Event::Spawn` from the metadata of a tracing span
 /// that has the following shape:
@@ -80,11 +103,37 @@ pub(crate) struct FieldVisitor {
 /// fields, which are interpreted as a Rust source code location where the task
 /// was spawned, if they are present. Other fields are recorded as arbitrary
 /// key-value pairs.
+///
+/// The `kind` field is also recorded as a key-value pair, but is kept so that
+/// the task's scheduler can be determined.
 pub(crate) struct TaskVisitor {
     field_visitor: FieldVisitor,
     line: Option<u32>,
     file: Option<String>,
     column: Option<u32>,
+    kind: Option<String>,
+}
+
+/// Used to extract the fields needed to construct an `Event::WatchedSpan`
+/// from a span which the application has asked the console to watch, such
+/// as one created by the [`watch_span!`] macro:
+///
+/// ```
+/// tracing::trace_span!(
+///     "load_config",
+///     console.watch = true,
+///     path = "config.toml",
+/// );
+/// ```
+///
+/// # Fields
+///
+/// The `console.watch` field marks the span as watched, and isn't recorded.
+/// Other fields are recorded as arbitrary key-value pairs.
+///
+/// [`watch_span!`]: crate::watch_span
+pub(crate) struct WatchedSpanVisitor {
+    field_visitor: FieldVisitor,
 }
 
 /// Used to extract the fields needed to construct
@@ -142,6 +191,95 @@ pub(crate) struct PollOpVisitor {
     is_ready: Option<bool>,
 }
 
+/// Used to extract the fields needed to construct
+/// an Event::SelectBranch from the metadata of a tracing event
+/// that has the following shape:
+///
+/// tracing::trace!(
+///     target: "runtime::select",
+///     loc.file = "some_file.rs",
+///     loc.line = 555,
+///     loc.col = 5,
+///     select.branch = 1,
+///     select.branches = 3,
+///     select.waited_ns = 1_000,
+/// );
+///
+/// Fields:
+/// loc.file, loc.line, loc.col - the location of the `select!`
+/// select.branch - the index of the branch which completed
+/// select.branches - the total number of branches in the `select!`
+/// select.waited_ns - how long the `select!` waited before the branch completed
+#[derive(Default)]
+pub(crate) struct SelectVisitor {
+    line: Option<u32>,
+    file: Option<String>,
+    column: Option<u32>,
+    branch: Option<u64>,
+    branches: Option<u64>,
+    waited_ns: Option<u64>,
+}
+
+/// Used to extract the receive latency from a tracing event
+/// that has the following shape:
+///
+/// tracing::trace!(
+///     target: "runtime::resource::channel::recv",
+///     latency_ns = 1_000,
+/// );
+///
+/// Fields:
+/// latency_ns - how long the received value waited in the channel
+#[derive(Default)]
+pub(crate) struct ChannelRecvVisitor {
+    latency_ns: Option<u64>,
+}
+
+/// Used to extract an application-defined task payload from a tracing event
+/// that has the following shape:
+///
+/// tracing::trace!(
+///     target: "runtime::task::extension",
+///     extension.type_url = "type.example.com/app.Handled",
+///     extension.value = "2a",
+/// );
+///
+/// Fields:
+/// extension.type_url - identifies the type of the payload
+/// extension.value - the serialized payload, hex encoded
+#[derive(Default)]
+pub(crate) struct ExtensionVisitor {
+    type_url: Option<String>,
+    value: Option<Vec<u8>>,
+}
+
+/// Used to extract a poll of an I/O resource from a tracing event
+/// that has the following shape:
+///
+/// tracing::trace!(
+///     target: "runtime::resource::io",
+///     io.direction = "write",
+///     io.bytes = 512,
+///     io.polls = 2,
+///     io.waited_ns = 1_000,
+///     io.pending = false,
+/// );
+///
+/// Fields:
+/// io.direction - either "read" or "write"
+/// io.bytes - the number of bytes transferred by the completed operation
+/// io.polls - the number of polls the completed operation took
+/// io.waited_ns - how long the completed operation waited for readiness
+/// io.pending - true if the operation started waiting for readiness instead
+#[derive(Default)]
+pub(crate) struct IoVisitor {
+    direction: Option<IoDirection>,
+    bytes: Option<u64>,
+    polls: Option<u64>,
+    waited_ns: Option<u64>,
+    pending: Option<bool>,
+}
+
 /// Used to extract the fields needed to construct
 /// an Event::StateUpdate from the metadata of a tracing event
 /// that has the following shape:
@@ -235,29 +373,94 @@ impl Visit for ResourceVisitor {
 }
 
 impl FieldVisitor {
-    pub(crate) fn new(meta_id: proto::MetaId) -> Self {
+    pub(crate) fn new(
+        meta_id: proto::MetaId,
+        max_fields: usize,
+        max_value_len: usize,
+        redact: Option<RedactFields>,
+    ) -> Self {
         FieldVisitor {
             fields: Vec::default(),
             meta_id,
+            max_fields,
+            max_value_len,
+            redact,
+            omitted: 0,
+        }
+    }
+
+    /// Returns the recorded fields, and the number of fields which were
+    /// omitted because there were more than the maximum number of fields.
+    pub(crate) fn result(self) -> (Vec<proto::Field>, u64) {
+        (self.fields, self.omitted)
+    }
+
+    /// Returns `true` if no more fields can be recorded, counting the field
+    /// as omitted if so.
+    fn is_full(&mut self) -> bool {
+        if self.fields.len() < self.max_fields {
+            return false;
         }
+        self.omitted += 1;
+        true
+    }
+
+    /// Returns the value to record in place of `value`, if `field` is
+    /// redacted.
+    fn redacted(&self, field: &field::Field, value: impl fmt::Display) -> Option<String> {
+        let redact = self.redact.as_ref()?;
+        (redact.0)(field.name(), &value.to_string())
+    }
+
+    /// Records a string value, truncating it if it is too long.
+    fn push_str(&mut self, field: &field::Field, value: &str) {
+        let truncated = value.len() > self.max_value_len;
+        let value = if truncated {
+            &value[..truncated_len(value, self.max_value_len)]
+        } else {
+            value
+        };
+        self.push(field, value.into(), truncated);
     }
-    pub(crate) fn result(self) -> Vec<proto::Field> {
-        self.fields
+
+    fn push(&mut self, field: &field::Field, value: proto::field::Value, truncated: bool) {
+        self.fields.push(proto::Field {
+            name: Some(field.name().into()),
+            value: Some(value),
+            metadata_id: Some(self.meta_id),
+            truncated,
+        });
     }
 }
 
 impl TaskVisitor {
-    pub(crate) fn new(meta_id: proto::MetaId) -> Self {
+    const KIND_FIELD_NAME: &'static str = "kind";
+
+    pub(crate) fn new(
+        meta_id: proto::MetaId,
+        max_fields: usize,
+        max_field_value_len: usize,
+        redact: Option<RedactFields>,
+    ) -> Self {
         TaskVisitor {
-            field_visitor: FieldVisitor::new(meta_id),
+            field_visitor: FieldVisitor::new(meta_id, max_fields, max_field_value_len, redact),
             line: None,
             file: None,
             column: None,
+            kind: None,
         }
     }
 
-    pub(crate) fn result(self) -> (Vec<proto::Field>, Option<proto::Location>) {
-        let fields = self.field_visitor.result();
+    /// Returns the kind of task, such as `task`, `local`, or `blocking`, if
+    /// the span recorded one.
+    pub(crate) fn kind(&self) -> Option<&str> {
+        self.kind.as_deref()
+    }
+
+    /// Returns the task's fields, the number of fields which were omitted, and
+    /// the location the task was spawned from.
+    pub(crate) fn result(self) -> (Vec<proto::Field>, u64, Option<proto::Location>) {
+        let (fields, omitted_fields) = self.field_visitor.result();
         let location = if self.file.is_some() && self.line.is_some() && self.column.is_some() {
             Some(proto::Location {
                 file: self.file,
@@ -269,12 +472,16 @@ impl TaskVisitor {
             None
         };
 
-        (fields, location)
+        (fields, omitted_fields, location)
     }
 }
 
 impl Visit for TaskVisitor {
     fn record_debug(&mut self, field: &field::Field, value: &dyn std::fmt::Debug) {
+        // Tokio records the kind with its `Display` implementation.
+        if field.name() == Self::KIND_FIELD_NAME {
+            self.kind = Some(format!("{:?}", value));
+        }
         self.field_visitor.record_debug(field, value);
     }
 
@@ -295,9 +502,70 @@ impl Visit for TaskVisitor {
     }
 
     fn record_str(&mut self, field: &tracing_core::Field, value: &str) {
-        if field.name() == LOCATION_FILE {
-            self.file = Some(value.to_string());
-        } else {
+        match field.name() {
+            LOCATION_FILE => self.file = Some(value.to_string()),
+            Self::KIND_FIELD_NAME => {
+                self.kind = Some(value.to_string());
+                self.field_visitor.record_str(field, value);
+            }
+            _ => self.field_visitor.record_str(field, value),
+        }
+    }
+}
+
+impl WatchedSpanVisitor {
+    pub(crate) const WATCH_FIELD_NAME: &'static str = "console.watch";
+
+    pub(crate) fn new(
+        meta_id: proto::MetaId,
+        max_fields: usize,
+        max_field_value_len: usize,
+        redact: Option<RedactFields>,
+    ) -> Self {
+        WatchedSpanVisitor {
+            field_visitor: FieldVisitor::new(meta_id, max_fields, max_field_value_len, redact),
+        }
+    }
+
+    /// Returns `true` if spans with the given metadata are ones the
+    /// application has asked the console to watch.
+    pub(crate) fn is_watched(meta: &tracing_core::Metadata<'_>) -> bool {
+        meta.is_span() && meta.fields().field(Self::WATCH_FIELD_NAME).is_some()
+    }
+
+    /// Returns the span's fields, and the number of fields which were omitted.
+    pub(crate) fn result(self) -> (Vec<proto::Field>, u64) {
+        self.field_visitor.result()
+    }
+}
+
+impl Visit for WatchedSpanVisitor {
+    fn record_debug(&mut self, field: &field::Field, value: &dyn std::fmt::Debug) {
+        if field.name() != Self::WATCH_FIELD_NAME {
+            self.field_visitor.record_debug(field, value);
+        }
+    }
+
+    fn record_i64(&mut self, field: &tracing_core::Field, value: i64) {
+        if field.name() != Self::WATCH_FIELD_NAME {
+            self.field_visitor.record_i64(field, value);
+        }
+    }
+
+    fn record_u64(&mut self, field: &tracing_core::Field, value: u64) {
+        if field.name() != Self::WATCH_FIELD_NAME {
+            self.field_visitor.record_u64(field, value);
+        }
+    }
+
+    fn record_bool(&mut self, field: &tracing_core::Field, value: bool) {
+        if field.name() != Self::WATCH_FIELD_NAME {
+            self.field_visitor.record_bool(field, value);
+        }
+    }
+
+    fn record_str(&mut self, field: &tracing_core::Field, value: &str) {
+        if field.name() != Self::WATCH_FIELD_NAME {
             self.field_visitor.record_str(field, value);
         }
     }
@@ -305,44 +573,84 @@ impl Visit for TaskVisitor {
 
 impl Visit for FieldVisitor {
     fn record_debug(&mut self, field: &field::Field, value: &dyn std::fmt::Debug) {
-        self.fields.push(proto::Field {
-            name: Some(field.name().into()),
-            value: Some(value.into()),
-            metadata_id: Some(self.meta_id),
-        });
+        if self.is_full() {
+            return;
+        }
+        let mut value = format!("{:?}", value);
+        if let Some(redacted) = self.redacted(field, &value) {
+            return self.push_str(field, &redacted);
+        }
+        let truncated = value.len() > self.max_value_len;
+        if truncated {
+            value.truncate(truncated_len(&value, self.max_value_len));
+        }
+        self.push(field, proto::field::Value::DebugVal(value), truncated);
     }
 
     fn record_i64(&mut self, field: &tracing_core::Field, value: i64) {
-        self.fields.push(proto::Field {
-            name: Some(field.name().into()),
-            value: Some(value.into()),
-            metadata_id: Some(self.meta_id),
-        });
+        if self.is_full() {
+            return;
+        }
+        match self.redacted(field, value) {
+            Some(redacted) => self.push_str(field, &redacted),
+            None => self.push(field, value.into(), false),
+        }
     }
 
     fn record_u64(&mut self, field: &tracing_core::Field, value: u64) {
-        self.fields.push(proto::Field {
-            name: Some(field.name().into()),
-            value: Some(value.into()),
-            metadata_id: Some(self.meta_id),
-        });
+        if self.is_full() {
+            return;
+        }
+        match self.redacted(field, value) {
+            Some(redacted) => self.push_str(field, &redacted),
+            None => self.push(field, value.into(), false),
+        }
     }
 
     fn record_bool(&mut self, field: &tracing_core::Field, value: bool) {
-        self.fields.push(proto::Field {
-            name: Some(field.name().into()),
-            value: Some(value.into()),
-            metadata_id: Some(self.meta_id),
-        });
+        if self.is_full() {
+            return;
+        }
+        match self.redacted(field, value) {
+            Some(redacted) => self.push_str(field, &redacted),
+            None => self.push(field, value.into(), false),
+        }
     }
 
     fn record_str(&mut self, field: &tracing_core::Field, value: &str) {
-        self.fields.push(proto::Field {
-            name: Some(field.name().into()),
-            value: Some(value.into()),
-            metadata_id: Some(self.meta_id),
-        });
+        if self.is_full() {
+            return;
+        }
+        match self.redacted(field, value) {
+            Some(redacted) => self.push_str(field, &redacted),
+            None => self.push_str(field, value),
+        }
+    }
+}
+
+impl RedactFields {
+    pub(crate) fn new<F>(redact: F) -> Self
+    where
+        F: Fn(&str, &str) -> Option<String> + Send + Sync + 'static,
+    {
+        Self(Arc::new(redact))
+    }
+}
+
+impl fmt::Debug for RedactFields {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RedactFields").finish_non_exhaustive()
+    }
+}
+
+/// Returns the length of the longest prefix of `value` which is at most
+/// `max_len` bytes long and doesn't split a character.
+fn truncated_len(value: &str, max_len: usize) -> usize {
+    let mut len = max_len;
+    while !value.is_char_boundary(len) {
+        len -= 1;
     }
+    len
 }
 
 impl AsyncOpVisitor {
@@ -435,6 +743,146 @@ impl Visit for PollOpVisitor {
     }
 }
 
+impl SelectVisitor {
+    pub(crate) const SELECT_EVENT_TARGET: &'static str = "runtime::select";
+    const BRANCH_FIELD_NAME: &'static str = "select.branch";
+    const BRANCHES_FIELD_NAME: &'static str = "select.branches";
+    const WAITED_FIELD_NAME: &'static str = "select.waited_ns";
+
+    /// Returns the location of the `select!`, the index of the branch that
+    /// completed, the total number of branches, and how long it waited.
+    pub(crate) fn result(self) -> Option<(proto::Location, usize, usize, Duration)> {
+        let location = proto::Location {
+            file: Some(self.file?),
+            line: Some(self.line?),
+            column: Some(self.column?),
+            ..Default::default()
+        };
+        let branch = self.branch? as usize;
+        let branches = self.branches.unwrap_or_default() as usize;
+        let waited = Duration::from_nanos(self.waited_ns.unwrap_or_default());
+        Some((location, branch, branches, waited))
+    }
+}
+
+impl Visit for SelectVisitor {
+    fn record_debug(&mut self, _: &field::Field, _: &dyn std::fmt::Debug) {}
+
+    fn record_str(&mut self, field: &tracing_core::Field, value: &str) {
+        if field.name() == LOCATION_FILE {
+            self.file = Some(value.to_string());
+        }
+    }
+
+    fn record_u64(&mut self, field: &tracing_core::Field, value: u64) {
+        match field.name() {
+            LOCATION_LINE => self.line = Some(value as u32),
+            LOCATION_COLUMN => self.column = Some(value as u32),
+            Self::BRANCH_FIELD_NAME => self.branch = Some(value),
+            Self::BRANCHES_FIELD_NAME => self.branches = Some(value),
+            Self::WAITED_FIELD_NAME => self.waited_ns = Some(value),
+            _ => {}
+        }
+    }
+}
+
+impl ChannelRecvVisitor {
+    pub(crate) const CHANNEL_RECV_EVENT_TARGET: &'static str = "runtime::resource::channel::recv";
+    const LATENCY_FIELD_NAME: &'static str = "latency_ns";
+
+    pub(crate) fn result(self) -> Option<Duration> {
+        self.latency_ns.map(Duration::from_nanos)
+    }
+}
+
+impl Visit for ChannelRecvVisitor {
+    fn record_debug(&mut self, _: &field::Field, _: &dyn std::fmt::Debug) {}
+
+    fn record_u64(&mut self, field: &tracing_core::Field, value: u64) {
+        if field.name() == Self::LATENCY_FIELD_NAME {
+            self.latency_ns = Some(value);
+        }
+    }
+}
+
+impl ExtensionVisitor {
+    pub(crate) const EXTENSION_EVENT_TARGET: &'static str = "runtime::task::extension";
+    const TYPE_URL_FIELD_NAME: &'static str = "extension.type_url";
+    const VALUE_FIELD_NAME: &'static str = "extension.value";
+
+    pub(crate) fn result(self) -> Option<prost_types::Any> {
+        Some(prost_types::Any {
+            type_url: self.type_url?,
+            value: self.value?,
+        })
+    }
+}
+
+impl Visit for ExtensionVisitor {
+    fn record_debug(&mut self, _: &field::Field, _: &dyn std::fmt::Debug) {}
+
+    fn record_str(&mut self, field: &tracing_core::Field, value: &str) {
+        match field.name() {
+            Self::TYPE_URL_FIELD_NAME => self.type_url = Some(value.to_string()),
+            Self::VALUE_FIELD_NAME => {
+                self.value = (0..value.len())
+                    .step_by(2)
+                    .map(|i| u8::from_str_radix(value.get(i..i + 2)?, 16).ok())
+                    .collect();
+            }
+            _ => {}
+        }
+    }
+}
+
+impl IoVisitor {
+    pub(crate) const IO_EVENT_TARGET: &'static str = "runtime::resource::io";
+    const DIRECTION_FIELD_NAME: &'static str = "io.direction";
+    const BYTES_FIELD_NAME: &'static str = "io.bytes";
+    const POLLS_FIELD_NAME: &'static str = "io.polls";
+    const WAITED_FIELD_NAME: &'static str = "io.waited_ns";
+    const PENDING_FIELD_NAME: &'static str = "io.pending";
+
+    pub(crate) fn result(self) -> Option<IoEvent> {
+        Some(IoEvent {
+            direction: self.direction?,
+            bytes: self.bytes.unwrap_or_default(),
+            polls: self.polls.unwrap_or_default(),
+            waited: Duration::from_nanos(self.waited_ns.unwrap_or_default()),
+            pending: self.pending.unwrap_or_default(),
+        })
+    }
+}
+
+impl Visit for IoVisitor {
+    fn record_debug(&mut self, _: &field::Field, _: &dyn std::fmt::Debug) {}
+
+    fn record_u64(&mut self, field: &tracing_core::Field, value: u64) {
+        match field.name() {
+            Self::BYTES_FIELD_NAME => self.bytes = Some(value),
+            Self::POLLS_FIELD_NAME => self.polls = Some(value),
+            Self::WAITED_FIELD_NAME => self.waited_ns = Some(value),
+            _ => {}
+        }
+    }
+
+    fn record_bool(&mut self, field: &tracing_core::Field, value: bool) {
+        if field.name() == Self::PENDING_FIELD_NAME {
+            self.pending = Some(value);
+        }
+    }
+
+    fn record_str(&mut self, field: &tracing_core::Field, value: &str) {
+        if field.name() == Self::DIRECTION_FIELD_NAME {
+            self.direction = match value {
+                "read" => Some(IoDirection::Read),
+                "write" => Some(IoDirection::Write),
+                _ => None,
+            };
+        }
+    }
+}
+
 impl StateUpdateVisitor {
     pub(crate) const RE_STATE_UPDATE_EVENT_TARGET: &'static str = "runtime::resource::state_update";
     pub(crate) const AO_STATE_UPDATE_EVENT_TARGET: &'static str =
@@ -474,6 +922,7 @@ impl Visit for StateUpdateVisitor {
                 name: Some(field.name().into()),
                 value: Some(value.into()),
                 metadata_id: Some(self.meta_id),
+                truncated: false,
             });
         }
     }
@@ -486,6 +935,7 @@ impl Visit for StateUpdateVisitor {
                 name: Some(field.name().into()),
                 value: Some(value.into()),
                 metadata_id: Some(self.meta_id),
+                truncated: false,
             });
         }
     }
@@ -498,6 +948,7 @@ impl Visit for StateUpdateVisitor {
                 name: Some(field.name().into()),
                 value: Some(value.into()),
                 metadata_id: Some(self.meta_id),
+                truncated: false,
             });
         }
     }
@@ -510,6 +961,7 @@ impl Visit for StateUpdateVisitor {
                 name: Some(field.name().into()),
                 value: Some(value.into()),
                 metadata_id: Some(self.meta_id),
+                truncated: false,
             });
         }
     }
@@ -529,6 +981,7 @@ impl Visit for StateUpdateVisitor {
                 name: Some(field.name().into()),
                 value: Some(value.into()),
                 metadata_id: Some(self.meta_id),
+                truncated: false,
             });
         }
     }
@@ -0,0 +1,44 @@
+/// Constructs a span which the console tracks, so that the time spent in a
+/// part of a task can be seen in the console's "user spans" view.
+///
+/// This takes the same arguments as [`tracing::trace_span!`], and creates a
+/// span at the `TRACE` level with an additional `console.watch` field. A
+/// [`ConsoleLayer`] records when any span with a `console.watch` field is
+/// created, entered, exited and closed, along with the task it was created
+/// in, so spans created with the [`tracing`] macros can be watched by adding
+/// the field to them instead.
+///
+/// Like any other span, a watched span is only timed while it is entered, so
+/// a span around an `.await` should be attached to the future it times with
+/// [`Instrument::instrument`].
+///
+/// # Examples
+///
+/// ```
+/// use tracing::Instrument;
+///
+/// # async fn load(_: &str) {}
+/// # async fn docs() {
+/// let path = "config.toml";
+/// load(path)
+///     .instrument(console_subscriber::watch_span!("load_config", path))
+///     .await;
+///
+/// let span = console_subscriber::watch_span!("parse_config", bytes = 512);
+/// let _guard = span.enter();
+/// # }
+/// ```
+///
+/// [`ConsoleLayer`]: crate::ConsoleLayer
+/// [`tracing`]: https://docs.rs/tracing
+/// [`tracing::trace_span!`]: https://docs.rs/tracing/latest/tracing/macro.trace_span.html
+/// [`Instrument::instrument`]: https://docs.rs/tracing/latest/tracing/trait.Instrument.html#method.instrument
+#[macro_export]
+macro_rules! watch_span {
+    ($name:expr) => {
+        $crate::watch_span!($name,)
+    };
+    ($name:expr, $($fields:tt)*) => {
+        $crate::__private::tracing::trace_span!($name, console.watch = true, $($fields)*)
+    };
+}
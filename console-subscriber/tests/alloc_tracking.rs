@@ -0,0 +1,55 @@
+mod support;
+use support::{record, spawn_named, ActualTask};
+
+#[global_allocator]
+static ALLOCATOR: console_subscriber::TrackingAllocator =
+    console_subscriber::TrackingAllocator::new(std::alloc::System);
+
+/// The size of the buffer allocated by the allocating task.
+const BUFFER_LEN: usize = 1 << 20;
+
+#[test]
+fn allocations_are_attributed_to_tasks() {
+    let (recording, ()) = record(async {
+        spawn_named("allocator", async {
+            let buffer = vec![0u8; BUFFER_LEN];
+            tokio::task::yield_now().await;
+            drop(std::hint::black_box(buffer));
+        })
+        .await
+        .expect("joining task failed");
+        spawn_named("non-allocator", async {})
+            .await
+            .expect("joining task failed");
+    });
+
+    let allocs = |name: &str| {
+        let task = recording
+            .tasks
+            .iter()
+            .find(|task| task.name.as_deref() == Some(name))
+            .unwrap_or_else(|| panic!("task {name:?} wasn't recorded"));
+        task_allocs(task)
+    };
+
+    let allocator = allocs("allocator");
+    assert!(
+        allocator.allocated_bytes >= BUFFER_LEN as u64,
+        "allocating task reported {allocator:?}"
+    );
+    assert!(
+        allocator.deallocated_bytes >= BUFFER_LEN as u64,
+        "allocating task reported {allocator:?}"
+    );
+
+    let non_allocator = allocs("non-allocator");
+    assert!(
+        non_allocator.allocated_bytes < BUFFER_LEN as u64,
+        "non-allocating task reported {non_allocator:?}"
+    );
+}
+
+fn task_allocs(task: &ActualTask) -> console_api::tasks::AllocStats {
+    task.allocs
+        .unwrap_or_else(|| panic!("task {:?} has no allocation stats", task.name))
+}
@@ -0,0 +1,41 @@
+mod support;
+use support::{record, spawn_named};
+
+#[test]
+fn extensions_are_attached_to_tasks() {
+    let (recording, ()) = record(async {
+        spawn_named("extended", async {
+            console_subscriber::set_task_extension("type.example.com/app.Load", &[1]);
+            tokio::task::yield_now().await;
+            // replaces the payload of the same type.
+            console_subscriber::set_task_extension("type.example.com/app.Load", &[2]);
+            console_subscriber::set_task_extension("type.example.com/app.Peer", b"db");
+        })
+        .await
+        .expect("joining task failed");
+        spawn_named("plain", async {})
+            .await
+            .expect("joining task failed");
+    });
+
+    let extensions = |name: &str| {
+        let task = recording
+            .tasks
+            .iter()
+            .find(|task| task.name.as_deref() == Some(name))
+            .unwrap_or_else(|| panic!("task {name:?} wasn't recorded"));
+        task.extensions
+            .iter()
+            .map(|extension| (extension.type_url.as_str(), extension.value.clone()))
+            .collect::<Vec<_>>()
+    };
+
+    assert_eq!(
+        extensions("extended"),
+        [
+            ("type.example.com/app.Load", vec![2]),
+            ("type.example.com/app.Peer", b"db".to_vec()),
+        ]
+    );
+    assert!(extensions("plain").is_empty());
+}
@@ -0,0 +1,50 @@
+use std::time::Duration;
+
+use tokio::time::sleep;
+
+mod support;
+use support::{assert_filtered_tasks, spawn_named, ExpectedTask};
+
+#[test]
+fn filtered_layer_records_tasks() {
+    // There is an extra poll because the span enters one more time upon drop (see
+    // tokio-rs/tracing#2562).
+    let expected_tasks = vec![
+        ExpectedTask::default().match_default_name().expect_polls(3),
+        ExpectedTask::default()
+            .match_name("sleeper".into())
+            .expect_polls(3),
+    ];
+
+    let future = async {
+        spawn_named("sleeper", async {
+            sleep(Duration::ZERO).await;
+        })
+        .await
+        .expect("joining task failed");
+    };
+
+    assert_filtered_tasks(expected_tasks, future);
+}
+
+#[test]
+fn filtered_layer_records_wakes() {
+    let expected_tasks = vec![ExpectedTask::default()
+        .match_name("waker".into())
+        .expect_wakes(1)
+        .expect_self_wakes(1)];
+
+    let future = async {
+        // Spans which aren't for the console are filtered out, without
+        // affecting the task spans around them.
+        let span = tracing::info_span!("unrelated");
+        spawn_named("waker", async move {
+            let _guard = span.enter();
+            support::self_wake().await;
+        })
+        .await
+        .expect("joining task failed");
+    };
+
+    assert_filtered_tasks(expected_tasks, future);
+}
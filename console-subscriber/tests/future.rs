@@ -0,0 +1,19 @@
+mod support;
+
+use console_subscriber::InstrumentFutureExt;
+use support::{assert_task, ExpectedTask};
+
+#[test]
+fn instrumented_future_is_recorded() {
+    let expected_task = ExpectedTask::default()
+        .match_name("instrumented".into())
+        .expect_polls(2)
+        .expect_wakes(1)
+        .expect_self_wakes(1);
+
+    let future = async {
+        support::self_wake().instrumented("instrumented").await;
+    };
+
+    assert_task(expected_task, future);
+}
@@ -0,0 +1,68 @@
+use std::{
+    sync::{Arc, Barrier},
+    thread,
+};
+
+mod support;
+use support::{record, spawn_named, Recording};
+
+/// Records a runtime running on a thread named `runtime`, which spawns a
+/// task named `task` and then waits at `barrier` until the other runtime has
+/// done the same.
+fn record_runtime(runtime: &str, task: &'static str, barrier: Arc<Barrier>) -> Recording {
+    thread::Builder::new()
+        .name(runtime.into())
+        .spawn(move || {
+            let (recording, ()) = record(async move {
+                spawn_named(task, async {})
+                    .await
+                    .expect("joining task failed");
+                // Both runtimes have now polled tasks on their threads, which
+                // are still running, so each console layer would report the
+                // other's thread if they shared any state.
+                barrier.wait();
+            });
+            recording
+        })
+        .expect("spawning runtime thread failed")
+        .join()
+        .expect("runtime thread panicked")
+}
+
+#[test]
+fn layers_for_different_runtimes_are_independent() {
+    let barrier = Arc::new(Barrier::new(2));
+    let first = {
+        let barrier = barrier.clone();
+        thread::spawn(move || record_runtime("runtime-a", "task-a", barrier))
+    };
+    let second = record_runtime("runtime-b", "task-b", barrier);
+    let first = first.join().expect("first runtime panicked");
+
+    for (recording, own, other) in [
+        (&first, ("runtime-a", "task-a"), ("runtime-b", "task-b")),
+        (&second, ("runtime-b", "task-b"), ("runtime-a", "task-a")),
+    ] {
+        let names = recording
+            .tasks
+            .iter()
+            .filter_map(|task| task.name.as_deref())
+            .collect::<Vec<_>>();
+        assert!(names.contains(&own.1), "{own:?} didn't record its task");
+        assert!(
+            !names.contains(&other.1),
+            "{own:?} recorded the other runtime's task"
+        );
+
+        assert!(
+            recording.threads.contains(own.0),
+            "{own:?} didn't report its thread: {threads:?}",
+            threads = recording.threads,
+        );
+        assert!(
+            !recording.threads.contains(other.0),
+            "{own:?} reported the other runtime's thread: {threads:?}",
+            threads = recording.threads,
+        );
+    }
+}
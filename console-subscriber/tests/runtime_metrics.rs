@@ -0,0 +1,157 @@
+//! Checks the poll counts and busy times reported by the console subscriber
+//! against the runtime's own metrics, so that a change in how Tokio
+//! instruments its tasks shows up as a test failure rather than as drift in
+//! the numbers shown by the console.
+//!
+//! The runtime metrics used here are only available with `tokio_unstable`.
+#![cfg(tokio_unstable)]
+
+use std::time::{Duration, Instant};
+
+use tokio::{
+    runtime::{Handle, RuntimeMetrics},
+    task,
+    time::sleep,
+};
+
+mod support;
+use support::{record, spawn_named, ActualTask, MAIN_TASK_NAME};
+
+const WORKLOAD_TASK_NAME: &str = "console-test::workload";
+
+/// The number of tasks the workload spawns.
+const WORKLOAD_TASKS: usize = 4;
+
+/// How many times each workload task yields before completing.
+const WORKLOAD_YIELDS: usize = 4;
+
+/// How long each poll of a workload task spins for.
+const WORKLOAD_POLL_BUSY: Duration = Duration::from_millis(10);
+
+/// What the runtime's metrics counted while the workload was running.
+struct RuntimeDelta {
+    polls: u64,
+    busy: Duration,
+}
+
+#[test]
+fn poll_counts_match_runtime_metrics() {
+    let (recording, delta) = record(run_workload());
+    let workload = workload_tasks(&recording.tasks);
+    let main = main_task(&recording.tasks);
+
+    // Each task is reported with an extra poll because its span enters one
+    // more time upon drop (see tokio-rs/tracing#2562).
+    let workload_polls = workload.iter().map(|task| task.polls - 1).sum::<u64>();
+    assert_eq!(
+        workload_polls,
+        (WORKLOAD_TASKS * (WORKLOAD_YIELDS + 1)) as u64,
+        "console-reported workload polls"
+    );
+
+    // The runtime also counts the polls of the main task which happen while
+    // the workload runs, of which there can't be more than the main task has
+    // in total.
+    let main_polls = main.polls - 1;
+    assert!(
+        (workload_polls..=workload_polls + main_polls).contains(&delta.polls),
+        "runtime counted {runtime} polls, console reported {workload_polls} workload polls \
+        and {main_polls} main task polls",
+        runtime = delta.polls,
+    );
+}
+
+#[test]
+fn busy_times_match_runtime_metrics() {
+    let (recording, delta) = record(run_workload());
+    let workload = workload_tasks(&recording.tasks);
+    let main = main_task(&recording.tasks);
+
+    let workload_busy = workload.iter().map(|task| task.busy).sum::<Duration>();
+    let spun = WORKLOAD_POLL_BUSY * (WORKLOAD_TASKS * (WORKLOAD_YIELDS + 1)) as u32;
+    assert!(
+        workload_busy >= spun,
+        "console reported {workload_busy:?} busy for the workload, which spun for {spun:?}"
+    );
+
+    // Every poll happens while the runtime is busy, so the runtime can't have
+    // been busy for less time than the console reports. It is also busy
+    // between polls, scheduling tasks, which the tolerance allows for.
+    let reported = workload_busy + main.busy;
+    let tolerance = reported / 5 + Duration::from_millis(10);
+    assert!(
+        delta.busy >= workload_busy && delta.busy <= reported + tolerance,
+        "runtime was busy for {runtime:?}, console reported {workload_busy:?} busy for the \
+        workload and {main_busy:?} for the main task",
+        runtime = delta.busy,
+        main_busy = main.busy,
+    );
+}
+
+/// Runs the workload, returning what the runtime's metrics counted while it
+/// was running.
+async fn run_workload() -> RuntimeDelta {
+    let metrics = Handle::current().metrics();
+
+    // Workers only publish their metrics when they park, so sleep before each
+    // reading to make sure it is up to date.
+    sleep(Duration::from_millis(1)).await;
+    let (polls_before, busy_before) = totals(&metrics);
+
+    let workload = (0..WORKLOAD_TASKS)
+        .map(|_| {
+            spawn_named(WORKLOAD_TASK_NAME, async {
+                spin(WORKLOAD_POLL_BUSY);
+                for _ in 0..WORKLOAD_YIELDS {
+                    task::yield_now().await;
+                    spin(WORKLOAD_POLL_BUSY);
+                }
+            })
+        })
+        .collect::<Vec<_>>();
+    for handle in workload {
+        handle.await.expect("workload task failed");
+    }
+
+    sleep(Duration::from_millis(1)).await;
+    let (polls_after, busy_after) = totals(&metrics);
+
+    RuntimeDelta {
+        polls: polls_after - polls_before,
+        busy: busy_after - busy_before,
+    }
+}
+
+/// Returns the polls and busy time of all of the runtime's workers.
+fn totals(metrics: &RuntimeMetrics) -> (u64, Duration) {
+    (0..metrics.num_workers()).fold((0, Duration::ZERO), |(polls, busy), worker| {
+        (
+            polls + metrics.worker_poll_count(worker),
+            busy + metrics.worker_total_busy_duration(worker),
+        )
+    })
+}
+
+/// Keeps the current thread busy, without yielding, for `duration`.
+fn spin(duration: Duration) {
+    let start = Instant::now();
+    while start.elapsed() < duration {
+        std::hint::spin_loop();
+    }
+}
+
+fn workload_tasks(tasks: &[ActualTask]) -> Vec<&ActualTask> {
+    let workload = tasks
+        .iter()
+        .filter(|task| task.name.as_deref() == Some(WORKLOAD_TASK_NAME))
+        .collect::<Vec<_>>();
+    assert_eq!(workload.len(), WORKLOAD_TASKS, "recorded workload tasks");
+    workload
+}
+
+fn main_task(tasks: &[ActualTask]) -> &ActualTask {
+    tasks
+        .iter()
+        .find(|task| task.name.as_deref() == Some(MAIN_TASK_NAME))
+        .expect("the main task wasn't recorded")
+}
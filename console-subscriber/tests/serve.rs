@@ -0,0 +1,732 @@
+use std::{future::Future, thread, time::Duration};
+
+use console_api::{
+    field::{Name, Value},
+    instrument::{
+        instrument_client::InstrumentClient, AuditLogRequest, InstrumentRequest, ListTasksRequest,
+        PauseRequest, ResumeRequest, StateRequest, TaskDetailsRequest, TaskDetailsSetRequest,
+    },
+    tasks::{task_details::PollTimesHistogram, Task},
+};
+use console_subscriber::ServerParts;
+use futures::stream::{self, StreamExt};
+use hdrhistogram::serialization::Deserializer;
+use hyper_util::rt::TokioIo;
+use tokio::{io::DuplexStream, sync::oneshot, task::JoinHandle};
+use tonic::transport::{Channel, Endpoint, Uri};
+use tower::service_fn;
+use tracing_subscriber::prelude::*;
+
+#[tokio::test]
+async fn serve_with_incoming_duplex() {
+    let (_console_layer, server) = console_subscriber::ConsoleLayer::builder()
+        .publish_interval(Duration::from_millis(10))
+        .build();
+    let (channel, serve) = connect(server).await;
+
+    let mut updates = InstrumentClient::new(channel)
+        .watch_updates(InstrumentRequest::default())
+        .await
+        .expect("client should watch updates")
+        .into_inner();
+    let update = tokio::time::timeout(Duration::from_secs(5), updates.next())
+        .await
+        .expect("an update should be received before the timeout");
+    assert!(
+        matches!(update, Some(Ok(_))),
+        "unexpected update: {update:?}"
+    );
+
+    serve.abort();
+}
+
+#[tokio::test]
+async fn watch_fails_once_the_aggregator_has_stopped() {
+    let (console_layer, server) = console_subscriber::ConsoleLayer::builder().build();
+    assert!(console_layer.is_active());
+    let ServerParts {
+        instrument_server,
+        aggregator,
+        ..
+    } = server.into_parts();
+    drop(aggregator);
+    assert!(!console_layer.is_active());
+
+    let (channel, serve) = connect_with(move |incoming| {
+        tonic::transport::Server::builder()
+            .add_service(instrument_server)
+            .serve_with_incoming(incoming)
+    })
+    .await;
+    let status = InstrumentClient::new(channel)
+        .watch_updates(InstrumentRequest::default())
+        .await
+        .expect_err("the watch should fail without an aggregator");
+    assert_eq!(status.code(), tonic::Code::Unavailable);
+    assert!(
+        status.message().contains("the aggregator task has stopped"),
+        "unexpected status: {status}"
+    );
+
+    serve.abort();
+}
+
+#[tokio::test]
+async fn watchers_over_the_limit_are_refused() {
+    let (_console_layer, server) = console_subscriber::ConsoleLayer::builder()
+        .publish_interval(Duration::from_millis(10))
+        .max_watchers(1)
+        .build();
+    let (channel, serve) = connect(server).await;
+    let mut client = InstrumentClient::new(channel);
+
+    let mut first = client
+        .watch_updates(InstrumentRequest::default())
+        .await
+        .expect("client should watch updates")
+        .into_inner();
+    assert!(matches!(first.next().await, Some(Ok(_))));
+
+    let mut second = client
+        .watch_updates(InstrumentRequest::default())
+        .await
+        .expect("the watch should start")
+        .into_inner();
+    let status = match second.next().await {
+        Some(Err(status)) => status,
+        update => panic!("a second watcher should be refused, got {update:?}"),
+    };
+    assert_eq!(status.code(), tonic::Code::ResourceExhausted);
+
+    // Once the first watcher goes away, there's room for another.
+    drop(first);
+    let mut third = client
+        .watch_updates(InstrumentRequest::default())
+        .await
+        .expect("client should watch updates")
+        .into_inner();
+    assert!(matches!(third.next().await, Some(Ok(_))));
+
+    serve.abort();
+}
+
+#[tokio::test]
+async fn watchers_over_their_bandwidth_are_sent_summaries() {
+    let (console_layer, server) = console_subscriber::ConsoleLayer::builder()
+        .publish_interval(Duration::from_millis(10))
+        .max_watcher_bandwidth(1)
+        .build();
+    let (channel, serve) = connect(server).await;
+    let (done_tx, instrumented) = run_instrumented(console_layer, 1);
+
+    let mut updates = InstrumentClient::new(channel)
+        .watch_updates(InstrumentRequest::default())
+        .await
+        .expect("client should watch updates")
+        .into_inner();
+    // The initial update is sent in full, but no later update fits in a
+    // single byte.
+    let initial = updates.next().await.unwrap().unwrap();
+    assert!(!initial.degraded);
+    let summary = tokio::time::timeout(Duration::from_secs(5), updates.next())
+        .await
+        .expect("a summary should be received before the timeout")
+        .unwrap()
+        .unwrap();
+    assert!(summary.degraded);
+    assert!(summary.resource_update.is_none());
+    let task_update = summary.task_update.expect("summary should include tasks");
+    assert!(task_update.new_tasks.is_empty());
+    assert!(task_update.stats_update.is_empty());
+    assert!(task_update.aggregates.is_some());
+
+    done_tx.send(()).unwrap();
+    instrumented.join().unwrap();
+    serve.abort();
+}
+
+#[tokio::test]
+async fn client_requests_are_audited() {
+    let (_console_layer, server) = console_subscriber::ConsoleLayer::builder().build();
+    let (channel, serve) = connect(server).await;
+    let mut client = InstrumentClient::new(channel);
+
+    client
+        .pause(PauseRequest::default())
+        .await
+        .expect("client should pause");
+    client
+        .resume(ResumeRequest::default())
+        .await
+        .expect("client should resume");
+    let log = client
+        .audit_log(AuditLogRequest::default())
+        .await
+        .expect("client should get the audit log")
+        .into_inner();
+
+    let rpcs = log
+        .entries
+        .iter()
+        .map(|entry| entry.rpc.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(rpcs, ["Pause", "Resume", "AuditLog"]);
+    assert!(log.entries.iter().all(|entry| entry.received_at.is_some()));
+    assert_eq!(log.dropped_entries, 0);
+
+    serve.abort();
+}
+
+#[tokio::test]
+async fn read_only_servers_refuse_to_pause() {
+    let (_console_layer, server) = console_subscriber::ConsoleLayer::builder()
+        .publish_interval(Duration::from_millis(10))
+        .read_only(true)
+        .build();
+    let (channel, serve) = connect(server).await;
+    let mut client = InstrumentClient::new(channel);
+
+    let status = client
+        .pause(PauseRequest::default())
+        .await
+        .expect_err("a read-only server should refuse to pause");
+    assert_eq!(status.code(), tonic::Code::PermissionDenied);
+    let status = client
+        .resume(ResumeRequest::default())
+        .await
+        .expect_err("a read-only server should refuse to resume");
+    assert_eq!(status.code(), tonic::Code::PermissionDenied);
+
+    let mut states = client
+        .watch_state(StateRequest::default())
+        .await
+        .expect("client should watch the state")
+        .into_inner();
+    let state = tokio::time::timeout(Duration::from_secs(5), states.next())
+        .await
+        .expect("a state should be received before the timeout")
+        .unwrap()
+        .unwrap();
+    assert!(state.read_only);
+    // the tests are built with `tokio_unstable`, by `.cargo/config.toml`.
+    assert_eq!(state.tokio_unstable, Some(true));
+
+    serve.abort();
+}
+
+#[tokio::test]
+async fn task_details_with_own_interval() {
+    // Use the default publish interval, which is much longer than the
+    // interval the task details are requested at.
+    let (console_layer, server) = console_subscriber::ConsoleLayer::builder().build();
+    let (channel, serve) = connect(server).await;
+
+    let (done_tx, instrumented) = run_instrumented(console_layer, 1);
+
+    let mut client = InstrumentClient::new(channel);
+    let task_id = recorded_tasks(&mut client, 1).await[0].id;
+
+    let mut details = client
+        .watch_task_details(TaskDetailsRequest {
+            id: task_id,
+            interval: Some(Duration::from_millis(20).try_into().unwrap()),
+            histogram_significant_figures: Some(1),
+        })
+        .await
+        .expect("client should watch task details")
+        .into_inner();
+    // At the publish interval, only the initial details would be received
+    // before the timeout.
+    let received = tokio::time::timeout(Duration::from_millis(900), async {
+        let mut received = Vec::new();
+        while received.len() < 5 {
+            let update = details
+                .next()
+                .await
+                .expect("details stream should not end")
+                .expect("details should not be an error");
+            received.push(update);
+        }
+        received
+    })
+    .await
+    .expect("details should be received at the requested interval");
+
+    let Some(PollTimesHistogram::Histogram(histogram)) = &received[4].poll_times_histogram else {
+        panic!("details should include a poll times histogram");
+    };
+    let histogram: hdrhistogram::Histogram<u64> = Deserializer::new()
+        .deserialize(&mut &histogram.raw_histogram[..])
+        .expect("histogram should deserialize");
+    assert_eq!(histogram.sigfig(), 1);
+
+    done_tx.send(()).unwrap();
+    instrumented.join().unwrap();
+    serve.abort();
+}
+
+#[tokio::test]
+async fn task_details_use_configured_histograms() {
+    // Every poll takes longer than this, so all of them are outliers.
+    let poll_duration_max = Duration::from_nanos(100);
+    let (console_layer, server) = console_subscriber::ConsoleLayer::builder()
+        .poll_duration_histogram_max(poll_duration_max)
+        .histogram_significant_figures(3)
+        .build();
+    let (channel, serve) = connect(server).await;
+    let (done_tx, instrumented) = run_instrumented(console_layer, 1);
+
+    let mut client = InstrumentClient::new(channel);
+    let task_id = recorded_tasks(&mut client, 1).await[0].id;
+
+    let mut details = client
+        .watch_task_details(TaskDetailsRequest {
+            id: task_id,
+            ..Default::default()
+        })
+        .await
+        .expect("client should watch task details")
+        .into_inner();
+    let details = tokio::time::timeout(Duration::from_secs(5), details.next())
+        .await
+        .expect("details should be received before the timeout")
+        .expect("details stream should not end")
+        .expect("details should not be an error");
+
+    let Some(PollTimesHistogram::Histogram(proto)) = &details.poll_times_histogram else {
+        panic!("details should include a poll times histogram");
+    };
+    let histogram: hdrhistogram::Histogram<u64> = Deserializer::new()
+        .deserialize(&mut &proto.raw_histogram[..])
+        .expect("histogram should deserialize");
+    assert_eq!(histogram.sigfig(), 3);
+    assert_eq!(proto.max_value, poll_duration_max.as_nanos() as u64);
+    assert!(proto.high_outliers > 0);
+    assert!(proto.highest_outlier > Some(proto.max_value));
+
+    done_tx.send(()).unwrap();
+    instrumented.join().unwrap();
+    serve.abort();
+}
+
+#[tokio::test]
+async fn filtered_tasks_have_no_histograms() {
+    let (console_layer, server) = console_subscriber::ConsoleLayer::builder()
+        .task_histograms(|task| task.name() == Some("interesting"))
+        .build();
+    let (channel, serve) = connect(server).await;
+    let (done_tx, instrumented) = run_instrumented(console_layer, 1);
+
+    let mut client = InstrumentClient::new(channel);
+    let task_id = recorded_tasks(&mut client, 1).await[0].id;
+
+    let mut details = client
+        .watch_task_details(TaskDetailsRequest {
+            id: task_id,
+            ..Default::default()
+        })
+        .await
+        .expect("client should watch task details")
+        .into_inner();
+    let details = tokio::time::timeout(Duration::from_secs(5), details.next())
+        .await
+        .expect("details should be received before the timeout")
+        .expect("details stream should not end")
+        .expect("details should not be an error");
+    assert_eq!(details.poll_times_histogram, None);
+    assert_eq!(details.scheduled_times_histogram, None);
+    assert_eq!(details.wake_intervals_histogram, None);
+
+    done_tx.send(()).unwrap();
+    instrumented.join().unwrap();
+    serve.abort();
+}
+
+#[tokio::test]
+async fn task_details_end_with_a_reason() {
+    let (console_layer, server) = console_subscriber::ConsoleLayer::builder()
+        .publish_interval(Duration::from_millis(10))
+        .retention(Duration::from_millis(10))
+        .build();
+    let (channel, serve) = connect(server).await;
+    // Spawn two tasks from the same location, so that they aren't mistaken
+    // for any other task spawned on the runtime.
+    let (stop_txs, stop_rxs): (Vec<_>, Vec<_>) = (0..2).map(|_| oneshot::channel::<()>()).unzip();
+    let (done_tx, done_rx) = oneshot::channel::<()>();
+    let instrumented = thread::spawn(move || {
+        let registry = tracing_subscriber::registry().with(console_layer);
+        tracing::subscriber::with_default(registry, || {
+            current_thread_runtime().block_on(async move {
+                for stop_rx in stop_rxs {
+                    tokio::spawn(async move {
+                        _ = stop_rx.await;
+                    });
+                }
+                _ = done_rx.await;
+            });
+        });
+    });
+
+    let mut client = InstrumentClient::new(channel);
+    let task_id = recorded_tasks(&mut client, 2).await[0].id;
+    // No client is watching updates, so the details need their own interval
+    // to be sent at all.
+    let mut details = client
+        .watch_task_details(TaskDetailsRequest {
+            id: task_id,
+            interval: Some(Duration::from_millis(10).try_into().unwrap()),
+            ..Default::default()
+        })
+        .await
+        .expect("client should watch task details")
+        .into_inner();
+    for stop_tx in stop_txs {
+        stop_tx.send(()).unwrap();
+    }
+
+    let status = tokio::time::timeout(Duration::from_secs(5), async {
+        loop {
+            match details.next().await {
+                Some(Ok(_)) => continue,
+                Some(Err(status)) => return status,
+                None => panic!("details stream should end with a status"),
+            }
+        }
+    })
+    .await
+    .expect("the watch should end once the task is no longer retained");
+    assert_eq!(status.code(), tonic::Code::NotFound);
+    assert!(
+        status.message().contains("no longer retained"),
+        "unexpected status: {status}"
+    );
+
+    done_tx.send(()).unwrap();
+    instrumented.join().unwrap();
+    serve.abort();
+}
+
+#[tokio::test]
+async fn task_details_include_polling_threads() {
+    let (console_layer, server) = console_subscriber::ConsoleLayer::builder().build();
+    let (channel, serve) = connect(server).await;
+    let (done_tx, instrumented) = run_instrumented(console_layer, 1);
+
+    let mut client = InstrumentClient::new(channel);
+    let task_id = recorded_tasks(&mut client, 1).await[0].id;
+
+    let mut details = client
+        .watch_task_details(TaskDetailsRequest {
+            id: task_id,
+            ..Default::default()
+        })
+        .await
+        .expect("client should watch task details")
+        .into_inner();
+    let details = tokio::time::timeout(Duration::from_secs(5), details.next())
+        .await
+        .expect("details should be received before the timeout")
+        .expect("details stream should not end")
+        .expect("details should not be an error");
+
+    // The task is only ever polled by the current-thread runtime's thread.
+    assert_eq!(details.thread_polls.len(), 1, "{:?}", details.thread_polls);
+    assert!(details.thread_polls[0].polls > 0);
+    assert_eq!(details.other_thread_polls, 0);
+
+    done_tx.send(()).unwrap();
+    instrumented.join().unwrap();
+    serve.abort();
+}
+
+#[tokio::test]
+async fn task_details_set_by_location() {
+    let (console_layer, server) = console_subscriber::ConsoleLayer::builder().build();
+    let (channel, serve) = connect(server).await;
+    let (done_tx, instrumented) = run_instrumented(console_layer, 3);
+
+    let mut client = InstrumentClient::new(channel);
+    let tasks = recorded_tasks(&mut client, 3).await;
+
+    let mut details = client
+        .watch_task_details_set(TaskDetailsSetRequest {
+            location: tasks[0].location.clone(),
+            ..Default::default()
+        })
+        .await
+        .expect("client should watch task details set")
+        .into_inner();
+    let set = tokio::time::timeout(Duration::from_secs(5), details.next())
+        .await
+        .expect("details should be received before the timeout")
+        .expect("details stream should not end")
+        .expect("details should not be an error");
+
+    let mut expected = tasks.iter().map(|task| task.id).collect::<Vec<_>>();
+    let mut actual = set
+        .details
+        .iter()
+        .map(|details| details.task_id)
+        .collect::<Vec<_>>();
+    expected.sort_by_key(|id| id.map(|id| id.id));
+    actual.sort_by_key(|id| id.map(|id| id.id));
+    assert_eq!(actual, expected);
+
+    done_tx.send(()).unwrap();
+    instrumented.join().unwrap();
+    serve.abort();
+}
+
+#[tokio::test]
+async fn list_tasks_by_location() {
+    let (console_layer, server) = console_subscriber::ConsoleLayer::builder().build();
+    let (channel, serve) = connect(server).await;
+    let (done_tx, instrumented) = run_instrumented(console_layer, 3);
+
+    let mut client = InstrumentClient::new(channel);
+    let tasks = recorded_tasks(&mut client, 3).await;
+    let mut expected = tasks
+        .iter()
+        .map(|task| task.id.expect("task should have an ID").id)
+        .collect::<Vec<_>>();
+    expected.sort_unstable();
+
+    let list = client
+        .list_tasks(ListTasksRequest {
+            location: tasks[0].location.clone(),
+            ..Default::default()
+        })
+        .await
+        .expect("client should list tasks")
+        .into_inner();
+    let listed = list
+        .tasks
+        .iter()
+        .map(|task| task.id.expect("task should have an ID").id)
+        .collect::<Vec<_>>();
+    assert_eq!(listed, expected);
+    assert!(listed.iter().all(|id| list.stats.contains_key(id)));
+    assert_eq!(list.omitted_tasks, 0);
+    assert!(list.now.is_some());
+
+    let limited = client
+        .list_tasks(ListTasksRequest {
+            location: tasks[0].location.clone(),
+            limit: 2,
+            ..Default::default()
+        })
+        .await
+        .expect("client should list tasks")
+        .into_inner();
+    let listed = limited
+        .tasks
+        .iter()
+        .map(|task| task.id.expect("task should have an ID").id)
+        .collect::<Vec<_>>();
+    assert_eq!(listed, expected[..2]);
+    assert_eq!(limited.stats.len(), 2);
+    assert_eq!(limited.omitted_tasks, 1);
+
+    done_tx.send(()).unwrap();
+    instrumented.join().unwrap();
+    serve.abort();
+}
+
+#[tokio::test]
+async fn task_fields_are_redacted() {
+    let (console_layer, server) = console_subscriber::ConsoleLayer::builder()
+        .redact_fields(|name, _| (name == "kind").then(|| "<redacted>".to_string()))
+        .build();
+    let (channel, serve) = connect(server).await;
+    let (done_tx, instrumented) = run_instrumented(console_layer, 1);
+
+    let mut client = InstrumentClient::new(channel);
+    let tasks = recorded_tasks(&mut client, 1).await;
+    let kind = tasks[0]
+        .fields
+        .iter()
+        .find(|field| field.name == Some(Name::StrName("kind".to_string())))
+        .expect("the task's kind should be recorded");
+    assert_eq!(kind.value, Some(Value::StrVal("<redacted>".to_string())));
+
+    done_tx.send(()).unwrap();
+    instrumented.join().unwrap();
+    serve.abort();
+}
+
+#[tokio::test]
+async fn runtime_update_includes_workers() {
+    let runtime = current_thread_runtime();
+    let (console_layer, server) = console_subscriber::ConsoleLayer::builder()
+        .publish_interval(Duration::from_millis(10))
+        .runtime_metrics(runtime.metrics())
+        .build();
+    let (channel, serve) = connect(server).await;
+    let (done_tx, instrumented) = run_instrumented_on(runtime, console_layer, 1);
+
+    let mut updates = InstrumentClient::new(channel)
+        .watch_updates(InstrumentRequest::default())
+        .await
+        .expect("client should watch updates")
+        .into_inner();
+    // Threads polling tasks for other tests may also be reported, but only
+    // this test's runtime has metrics to match its worker with.
+    let worker = tokio::time::timeout(Duration::from_secs(5), async {
+        loop {
+            let update = updates
+                .next()
+                .await
+                .expect("update stream should not end")
+                .expect("update should not be an error");
+            let workers = update.runtime_update.unwrap_or_default().workers;
+            if let Some(worker) = workers
+                .into_iter()
+                .find(|worker| worker.worker_index.is_some() && worker.polls > 0)
+            {
+                return worker;
+            }
+        }
+    })
+    .await
+    .expect("the runtime's worker should be reported before the timeout");
+    assert_eq!(worker.thread_name.as_deref(), Some("runtime-workers"));
+    assert_eq!(worker.worker_index, Some(0));
+    assert!(worker.parks.is_some());
+
+    done_tx.send(()).unwrap();
+    instrumented.join().unwrap();
+    serve.abort();
+}
+
+/// Runs `tasks` tasks, all spawned from the same location, on their own
+/// thread, so that only they are recorded by the console layer.
+///
+/// The tasks run until the returned sender is used.
+fn run_instrumented(
+    console_layer: console_subscriber::ConsoleLayer,
+    tasks: usize,
+) -> (oneshot::Sender<()>, thread::JoinHandle<()>) {
+    run_instrumented_on(current_thread_runtime(), console_layer, tasks)
+}
+
+/// Like [`run_instrumented`], but runs the tasks on the given runtime.
+///
+/// The runtime's thread is named `runtime-workers`.
+fn run_instrumented_on(
+    runtime: tokio::runtime::Runtime,
+    console_layer: console_subscriber::ConsoleLayer,
+    tasks: usize,
+) -> (oneshot::Sender<()>, thread::JoinHandle<()>) {
+    let (done_tx, done_rx) = oneshot::channel::<()>();
+    let instrumented = thread::Builder::new()
+        .name("runtime-workers".to_string())
+        .spawn(move || {
+            let registry = tracing_subscriber::registry().with(console_layer);
+            tracing::subscriber::with_default(registry, || {
+                runtime.block_on(async move {
+                    for _ in 0..tasks {
+                        tokio::spawn(async {
+                            loop {
+                                tokio::time::sleep(Duration::from_millis(10)).await;
+                            }
+                        });
+                    }
+                    _ = done_rx.await;
+                });
+            });
+        })
+        .unwrap();
+    (done_tx, instrumented)
+}
+
+fn current_thread_runtime() -> tokio::runtime::Runtime {
+    tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .unwrap()
+}
+
+/// Watches updates until `count` tasks spawned from the same location have
+/// been recorded, returning those tasks.
+///
+/// Tasks spawned from elsewhere, such as by the runtime, are ignored.
+async fn recorded_tasks(client: &mut InstrumentClient<Channel>, count: usize) -> Vec<Task> {
+    let mut updates = client
+        .watch_updates(InstrumentRequest::default())
+        .await
+        .expect("client should watch updates")
+        .into_inner();
+    tokio::time::timeout(Duration::from_secs(5), async {
+        let mut tasks = Vec::<Task>::new();
+        loop {
+            let update = updates
+                .next()
+                .await
+                .expect("update stream should not end")
+                .expect("update should not be an error");
+            if let Some(task_update) = update.task_update {
+                tasks.extend(task_update.new_tasks);
+            }
+            for task in &tasks {
+                let same_location = tasks
+                    .iter()
+                    .filter(|other| other.location == task.location)
+                    .cloned()
+                    .collect::<Vec<_>>();
+                if same_location.len() == count {
+                    return same_location;
+                }
+            }
+        }
+    })
+    .await
+    .expect("the tasks should be recorded before the timeout")
+}
+
+/// Serves `server` over an in-memory duplex stream, returning a channel
+/// connected to it.
+async fn connect(
+    server: console_subscriber::Server,
+) -> (
+    Channel,
+    JoinHandle<Result<(), Box<dyn std::error::Error + Send + Sync>>>,
+) {
+    connect_with(move |incoming| server.serve_with_incoming(incoming)).await
+}
+
+/// The connections served by [`connect_with`].
+type Incoming = stream::Chain<
+    stream::Iter<std::vec::IntoIter<std::io::Result<DuplexStream>>>,
+    stream::Pending<std::io::Result<DuplexStream>>,
+>;
+
+/// Spawns `serve` on a single in-memory connection, and connects a client to
+/// it.
+async fn connect_with<Fut>(
+    serve: impl FnOnce(Incoming) -> Fut,
+) -> (Channel, JoinHandle<Fut::Output>)
+where
+    Fut: Future + Send + 'static,
+    Fut::Output: Send + 'static,
+{
+    let (client_stream, server_stream) = tokio::io::duplex(1024);
+    // Keep the incoming stream open after the only connection, so that the
+    // server doesn't shut down while the client is connected.
+    let incoming = stream::iter(vec![Ok(server_stream)]).chain(stream::pending());
+    let serve = tokio::spawn(serve(incoming));
+
+    let mut client_stream = Some(client_stream);
+    let channel = Endpoint::try_from("http://[::]:6669")
+        .expect("Could not create endpoint")
+        .connect_with_connector(service_fn(move |_: Uri| {
+            let client = client_stream.take();
+            async move {
+                client
+                    .map(TokioIo::new)
+                    .ok_or_else(|| std::io::Error::other("client already taken"))
+            }
+        }))
+        .await
+        .expect("client should connect over the duplex stream");
+    (channel, serve)
+}
@@ -3,6 +3,7 @@ use std::time::Duration;
 use tokio::time::sleep;
 
 mod support;
+use console_api::tasks::task::Scheduler;
 use support::{assert_tasks, spawn_named, ExpectedTask};
 
 /// This test asserts the behavior that was fixed in #440. Before that fix,
@@ -39,3 +40,62 @@ fn child_polls_dont_count_towards_parent_polls() {
 
     assert_tasks(expected_tasks, future);
 }
+
+/// Field values longer than the maximum length are truncated when a task is
+/// spawned.
+#[test]
+fn long_field_values_are_truncated() {
+    let max_len = console_subscriber::ConsoleLayer::DEFAULT_MAX_FIELD_VALUE_LEN;
+    let name = "a".repeat(max_len * 2);
+    let expected_tasks = vec![ExpectedTask::default()
+        .match_name(name[..max_len].to_owned())
+        .expect_present()];
+
+    let future = async move {
+        spawn_named(&name, async {})
+            .await
+            .expect("joining task failed");
+    };
+
+    assert_tasks(expected_tasks, future);
+}
+
+/// Tasks are reported with the kind of scheduler they were spawned onto, so
+/// that tasks on a `LocalSet` can be told apart from those on the runtime.
+#[test]
+fn tasks_record_their_scheduler() {
+    let expected_tasks = vec![
+        ExpectedTask::default()
+            .match_name("runtime".into())
+            .expect_scheduler(Scheduler::CurrentThread),
+        ExpectedTask::default()
+            .match_name("local".into())
+            .expect_scheduler(Scheduler::Local),
+    ];
+
+    let future = async {
+        spawn_named("runtime", async {})
+            .await
+            .expect("joining runtime task failed");
+
+        // A `LocalSet` isn't `Send`, so it's run on a blocking thread, which
+        // doesn't inherit the test's subscriber.
+        let dispatch = tracing::dispatcher::get_default(Clone::clone);
+        tokio::task::spawn_blocking(move || {
+            let _dispatch = tracing::dispatcher::set_default(&dispatch);
+            let local = tokio::task::LocalSet::new();
+            tokio::runtime::Handle::current().block_on(local.run_until(async {
+                tokio::task::Builder::new()
+                    .name("local")
+                    .spawn_local(async {})
+                    .expect("spawning local task failed")
+                    .await
+                    .expect("joining local task failed");
+            }));
+        })
+        .await
+        .expect("joining blocking task failed");
+    };
+
+    assert_tasks(expected_tasks, future);
+}
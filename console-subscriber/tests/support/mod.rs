@@ -2,13 +2,18 @@ use std::{future::Future, task::Poll};
 
 use tokio::task::JoinHandle;
 
+mod span;
 mod state;
 mod subscriber;
 mod task;
 
-use subscriber::run_test;
-pub(crate) use subscriber::MAIN_TASK_NAME;
-pub(crate) use task::ExpectedTask;
+#[allow(unused_imports)]
+pub(crate) use span::ActualSpan;
+use subscriber::{record_test, run_test, Layering};
+#[allow(unused_imports)]
+pub(crate) use subscriber::{Recording, MAIN_TASK_NAME};
+#[allow(unused_imports)]
+pub(crate) use task::{ActualTask, ExpectedTask};
 
 /// Assert that an `expected_task` is recorded by a console-subscriber
 /// when driving the provided `future` to completion.
@@ -27,7 +32,7 @@ where
     Fut: Future + Send + 'static,
     Fut::Output: Send + 'static,
 {
-    run_test(vec![expected_task], future)
+    run_test(Layering::Unfiltered, vec![expected_task], future)
 }
 
 /// Assert that the `expected_tasks` are recorded by a console-subscriber
@@ -44,7 +49,74 @@ where
     Fut: Future + Send + 'static,
     Fut::Output: Send + 'static,
 {
-    run_test(expected_tasks, future)
+    run_test(Layering::Unfiltered, expected_tasks, future)
+}
+
+/// Assert that the `expected_tasks` are recorded by a console-subscriber
+/// restricted by its per-layer filter when driving the provided `future` to
+/// completion.
+///
+/// The subscriber also has a logging layer with its own per-layer filter,
+/// which only enables `INFO` and above, so this checks that the console layer
+/// composes with other filtered layers.
+///
+/// # Panics
+///
+/// This function will panic if the expectations on any of the expected tasks
+/// are not met or if matching tasks are not recorded for all expected tasks.
+#[track_caller]
+#[allow(dead_code)]
+pub(crate) fn assert_filtered_tasks<Fut>(expected_tasks: Vec<ExpectedTask>, future: Fut)
+where
+    Fut: Future + Send + 'static,
+    Fut::Output: Send + 'static,
+{
+    run_test(Layering::Filtered, expected_tasks, future)
+}
+
+/// Record what a console-subscriber sees when driving the provided `future` to
+/// completion.
+///
+/// Unlike [`assert_tasks`], no expectations are checked. All the recorded
+/// tasks (including the test's main task, named [`MAIN_TASK_NAME`]) and the
+/// threads which polled them are returned together with the output of the
+/// future, so that a test can compare them against values only known once the
+/// future has completed.
+///
+/// # Panics
+///
+/// This function will panic if the test's main task panics.
+#[track_caller]
+#[allow(dead_code)]
+pub(crate) fn record<Fut>(future: Fut) -> (Recording, Fut::Output)
+where
+    Fut: Future + Send + 'static,
+    Fut::Output: Send + 'static,
+{
+    let (recording, output) = record_test(Layering::Unfiltered, future);
+    let output = output.unwrap_or_else(|err| panic!("console-test error: test task failed: {err}"));
+    (recording, output)
+}
+
+/// Record what a console-subscriber restricted by its per-layer filter sees
+/// when driving the provided `future` to completion.
+///
+/// This is the same as [`record`], but the subscriber is set up as it is by
+/// [`assert_filtered_tasks`].
+///
+/// # Panics
+///
+/// This function will panic if the test's main task panics.
+#[track_caller]
+#[allow(dead_code)]
+pub(crate) fn record_filtered<Fut>(future: Fut) -> (Recording, Fut::Output)
+where
+    Fut: Future + Send + 'static,
+    Fut::Output: Send + 'static,
+{
+    let (recording, output) = record_test(Layering::Filtered, future);
+    let output = output.unwrap_or_else(|err| panic!("console-test error: test task failed: {err}"));
+    (recording, output)
 }
 
 /// Spawn a named task and unwrap.
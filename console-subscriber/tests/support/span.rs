@@ -0,0 +1,44 @@
+use std::time::Duration;
+
+use console_api::spans;
+
+/// An actual watched span
+///
+/// This struct contains the values recorded from the console subscriber
+/// client and represents what is known about a span which the test asked the
+/// console to watch.
+#[derive(Clone, Debug)]
+#[allow(dead_code)]
+pub(crate) struct ActualSpan {
+    pub(crate) id: u64,
+    pub(crate) metadata_id: Option<u64>,
+    pub(crate) name: Option<String>,
+    pub(crate) task_id: Option<u64>,
+    pub(crate) enters: u64,
+    pub(crate) busy: Duration,
+    pub(crate) closed: bool,
+}
+
+impl ActualSpan {
+    pub(super) fn new(span: &spans::Span) -> Option<Self> {
+        Some(Self {
+            id: span.id?.id,
+            metadata_id: span.metadata.map(|meta| meta.id),
+            name: None,
+            task_id: span.task_id.map(|id| id.id),
+            enters: 0,
+            busy: Duration::ZERO,
+            closed: false,
+        })
+    }
+
+    pub(super) fn update_from_stats(&mut self, stats: &spans::Stats) {
+        self.closed = stats.dropped_at.is_some();
+        if let Some(enter_stats) = &stats.enter_stats {
+            self.enters = enter_stats.polls;
+            if let Some(busy) = enter_stats.busy_time {
+                self.busy = busy.try_into().unwrap_or_default();
+            }
+        }
+    }
+}
@@ -1,4 +1,9 @@
-use std::{collections::HashMap, fmt, future::Future, thread};
+use std::{
+    collections::{HashMap, HashSet},
+    fmt,
+    future::Future,
+    thread,
+};
 
 use console_api::{
     field::Value,
@@ -7,16 +12,43 @@ use console_api::{
 use console_subscriber::ServerParts;
 use futures::stream::StreamExt;
 use hyper_util::rt::TokioIo;
-use tokio::{io::DuplexStream, task};
+use tokio::{
+    io::DuplexStream,
+    task::{self, JoinError},
+};
 use tonic::transport::{Channel, Endpoint, Server, Uri};
 use tower::service_fn;
 
+use super::span::ActualSpan;
 use super::state::{TestState, TestStep};
 use super::task::{ActualTask, ExpectedTask, TaskValidationFailure};
 
 pub(crate) const MAIN_TASK_NAME: &str = "console-test::main";
 const END_SIGNAL_TASK_NAME: &str = "console-test::signal";
 
+/// How the console layer is added to the subscriber under test.
+#[derive(Clone, Copy, Debug)]
+pub(super) enum Layering {
+    /// The console layer is the only layer, and observes every span.
+    Unfiltered,
+    /// The console layer is restricted by its per-layer filter, alongside a
+    /// logging layer with a per-layer filter of its own.
+    Filtered,
+}
+
+/// What the console client recorded during a test.
+#[derive(Debug)]
+pub(crate) struct Recording {
+    /// Every task which was recorded, other than the end signal task.
+    pub(crate) tasks: Vec<ActualTask>,
+    /// The names of the threads which were reported as polling tasks.
+    #[allow(dead_code)]
+    pub(crate) threads: HashSet<String>,
+    /// Every watched span which was recorded.
+    #[allow(dead_code)]
+    pub(crate) spans: Vec<ActualSpan>,
+}
+
 #[derive(Debug)]
 struct TestFailure {
     failures: Vec<TaskValidationFailure>,
@@ -42,16 +74,53 @@ impl fmt::Display for TestFailure {
 /// This function will panic if the expectations on any of the expected tasks
 /// are not met or if matching tasks are not recorded for all expected tasks.
 #[track_caller]
-pub(super) fn run_test<Fut>(expected_tasks: Vec<ExpectedTask>, future: Fut)
+pub(super) fn run_test<Fut>(layering: Layering, expected_tasks: Vec<ExpectedTask>, future: Fut)
+where
+    Fut: Future + Send + 'static,
+    Fut::Output: Send + 'static,
+{
+    let (recording, _) = record_test(layering, future);
+
+    if let Err(test_failure) = validate_expected_tasks(expected_tasks, recording.tasks) {
+        panic!("Test failed: {test_failure}")
+    }
+}
+
+/// Records the tasks of a test
+///
+/// This function sets up the `console-subscriber` layer, the gRPC server and
+/// the client in the same way as [`run_test()`], but instead of validating
+/// expected tasks it returns everything which was recorded while the provided
+/// future was driven to completion, together with the result of the test's
+/// main task.
+pub(super) fn record_test<Fut>(
+    layering: Layering,
+    future: Fut,
+) -> (Recording, Result<Fut::Output, JoinError>)
 where
     Fut: Future + Send + 'static,
     Fut::Output: Send + 'static,
 {
-    use tracing_subscriber::prelude::*;
+    use tracing_subscriber::{filter::LevelFilter, prelude::*};
 
     let (client_stream, server_stream) = tokio::io::duplex(1024);
-    let (console_layer, server) = console_subscriber::ConsoleLayer::builder().build();
-    let registry = tracing_subscriber::registry().with(console_layer);
+    let builder = console_subscriber::ConsoleLayer::builder();
+    let (console_layer, filtered_layers, server) = match layering {
+        Layering::Unfiltered => {
+            let (console_layer, server) = builder.build();
+            (Some(console_layer), None, server)
+        }
+        Layering::Filtered => {
+            let (console_layer, server) = builder.build_filtered();
+            let fmt_layer = tracing_subscriber::fmt::layer()
+                .with_writer(std::io::sink)
+                .with_filter(LevelFilter::INFO);
+            (None, Some(console_layer.and_then(fmt_layer)), server)
+        }
+    };
+    let registry = tracing_subscriber::registry()
+        .with(console_layer)
+        .with(filtered_layers);
 
     let mut test_state = TestState::new();
     let mut test_state_test = test_state.clone();
@@ -84,7 +153,7 @@ where
                     .spawn(console_server(server, server_stream, test_state.clone()))
                     .expect("console-test error: could not spawn 'console-server' task");
 
-                let actual_tasks = task::Builder::new()
+                let recording = task::Builder::new()
                     .name("console::client")
                     .spawn(console_client(client_stream, test_state.clone()))
                     .expect("console-test error: could not spawn 'console-client' task")
@@ -92,12 +161,12 @@ where
                     .expect("console-test error: failed to await 'console-client' task");
 
                 test_state.advance_to_step(TestStep::UpdatesRecorded);
-                actual_tasks
+                recording
             })
         })
         .expect("console-test error: console subscriber could not spawn thread");
 
-    tracing::subscriber::with_default(registry, || {
+    let output = tracing::subscriber::with_default(registry, || {
         let runtime = tokio::runtime::Builder::new_current_thread()
             .enable_all()
             .build()
@@ -109,7 +178,7 @@ where
                 .await;
 
             // Run the future that we are testing.
-            _ = task::Builder::new()
+            let output = task::Builder::new()
                 .name(MAIN_TASK_NAME)
                 .spawn(future)
                 .expect("console-test error: couldn't spawn test task")
@@ -124,16 +193,16 @@ where
             test_state_test
                 .wait_for_step(TestStep::UpdatesRecorded)
                 .await;
-        });
+
+            output
+        })
     });
 
-    let actual_tasks = join_handle
+    let recording = join_handle
         .join()
         .expect("console-test error: failed to join 'console-subscriber' thread");
 
-    if let Err(test_failure) = validate_expected_tasks(expected_tasks, actual_tasks) {
-        panic!("Test failed: {test_failure}")
-    }
+    (recording, output)
 }
 
 /// Starts the console server.
@@ -184,13 +253,13 @@ async fn console_server(
 /// the actual tasks.
 ///
 /// Once recording finishes (see [`record_actual_tasks()`] for details on the test
-/// state condition), the actual tasks returned.
+/// state condition), the recording is returned.
 ///
 /// # Test State
 ///
 /// 1. Waits for: `ServerStarted`
 /// 2. Advances to: `ClientConnected`
-async fn console_client(client_stream: DuplexStream, mut test_state: TestState) -> Vec<ActualTask> {
+async fn console_client(client_stream: DuplexStream, mut test_state: TestState) -> Recording {
     test_state.wait_for_step(TestStep::ServerStarted).await;
 
     let mut client_stream = Some(client_stream);
@@ -225,19 +294,18 @@ async fn console_client(client_stream: DuplexStream, mut test_state: TestState)
 ///
 /// Updates will be received until the test state reaches the `TestFinished` step
 /// (indicating that the test itself has finished running), at which point we wait
-/// for a final update before returning all the actual tasks which were recorded.
+/// for a final update before returning all the actual tasks which were recorded,
+/// along with the threads which were reported as polling them and the watched
+/// spans.
 ///
 /// # Test State
 ///
 /// 1. Waits for: `TestFinished`
-async fn record_actual_tasks(
-    client_channel: Channel,
-    mut test_state: TestState,
-) -> Vec<ActualTask> {
+async fn record_actual_tasks(client_channel: Channel, mut test_state: TestState) -> Recording {
     let mut client = InstrumentClient::new(client_channel);
 
     let mut stream = match client
-        .watch_updates(tonic::Request::new(InstrumentRequest {}))
+        .watch_updates(tonic::Request::new(InstrumentRequest::default()))
         .await
     {
         Ok(stream) => stream.into_inner(),
@@ -245,6 +313,9 @@ async fn record_actual_tasks(
     };
 
     let mut tasks = HashMap::new();
+    let mut threads = HashSet::new();
+    let mut spans = HashMap::new();
+    let mut span_names = HashMap::new();
 
     // The console-subscriber aggregator is a bit of an unknown entity for us,
     // especially with respect to its update loops. We can't guarantee that
@@ -262,6 +333,8 @@ async fn record_actual_tasks(
                     Some(id) => ActualTask::new(id.id),
                     None => continue,
                 };
+                actual_task.scheduler = new_task.scheduler();
+                actual_task.extensions = new_task.extensions.clone();
                 for field in &new_task.fields {
                     match field.name.as_ref() {
                         Some(console_api::field::Name::StrName(name)) if name == "task.name" => {
@@ -287,6 +360,43 @@ async fn record_actual_tasks(
                     task.update_from_stats(stats);
                 }
             }
+
+            for (id, extensions) in &task_update.extensions_update {
+                if let Some(task) = tasks.get_mut(id) {
+                    task.extensions = extensions.extensions.clone();
+                }
+            }
+        }
+
+        if let Some(new_metadata) = &update.new_metadata {
+            for new in &new_metadata.metadata {
+                if let (Some(id), Some(metadata)) = (new.id, &new.metadata) {
+                    span_names.insert(id.id, metadata.name.clone());
+                }
+            }
+        }
+
+        if let Some(span_update) = &update.span_update {
+            for new_span in &span_update.new_spans {
+                if let Some(actual_span) = ActualSpan::new(new_span) {
+                    spans.insert(actual_span.id, actual_span);
+                }
+            }
+
+            for (id, stats) in &span_update.stats_update {
+                if let Some(span) = spans.get_mut(id) {
+                    span.update_from_stats(stats);
+                }
+            }
+        }
+
+        if let Some(runtime_update) = &update.runtime_update {
+            threads.extend(
+                runtime_update
+                    .workers
+                    .iter()
+                    .filter_map(|worker| worker.thread_name.clone()),
+            );
         }
 
         if test_state.is_step(TestStep::TestFinished) && signal_task_read {
@@ -295,7 +405,19 @@ async fn record_actual_tasks(
         }
     }
 
-    tasks.into_values().collect()
+    let spans = spans
+        .into_values()
+        .map(|mut span: ActualSpan| {
+            span.name = span.metadata_id.and_then(|id| span_names.get(&id).cloned());
+            span
+        })
+        .collect();
+
+    Recording {
+        tasks: tasks.into_values().collect(),
+        threads,
+        spans,
+    }
 }
 
 /// Validate the expected tasks against the actual tasks.
@@ -1,6 +1,6 @@
-use std::{error, fmt};
+use std::{error, fmt, time::Duration};
 
-use console_api::tasks;
+use console_api::tasks::{self, task::Scheduler};
 
 use super::MAIN_TASK_NAME;
 
@@ -10,12 +10,17 @@ use super::MAIN_TASK_NAME;
 /// client and represents what is known about an actual task running on
 /// the test's runtime.
 #[derive(Clone, Debug)]
-pub(super) struct ActualTask {
-    pub(super) id: u64,
-    pub(super) name: Option<String>,
-    pub(super) wakes: u64,
-    pub(super) self_wakes: u64,
-    pub(super) polls: u64,
+pub(crate) struct ActualTask {
+    pub(crate) id: u64,
+    pub(crate) name: Option<String>,
+    pub(crate) wakes: u64,
+    pub(crate) self_wakes: u64,
+    pub(crate) task_wakes: u64,
+    pub(crate) polls: u64,
+    pub(crate) busy: Duration,
+    pub(crate) scheduler: Scheduler,
+    pub(crate) allocs: Option<tasks::AllocStats>,
+    pub(crate) extensions: Vec<prost_types::Any>,
 }
 
 impl ActualTask {
@@ -25,16 +30,27 @@ impl ActualTask {
             name: None,
             wakes: 0,
             self_wakes: 0,
+            task_wakes: 0,
             polls: 0,
+            busy: Duration::ZERO,
+            scheduler: Scheduler::Unknown,
+            allocs: None,
+            extensions: Vec::new(),
         }
     }
 
     pub(super) fn update_from_stats(&mut self, stats: &tasks::Stats) {
         self.wakes = stats.wakes;
         self.self_wakes = stats.self_wakes;
+        self.task_wakes =
+            stats.woken_by.iter().map(|waker| waker.wakes).sum::<u64>() + stats.other_task_wakes;
         if let Some(poll_stats) = &stats.poll_stats {
             self.polls = poll_stats.polls;
+            if let Some(busy) = poll_stats.busy_time {
+                self.busy = busy.try_into().unwrap_or_default();
+            }
         }
+        self.allocs = stats.alloc_stats;
     }
 }
 
@@ -90,7 +106,9 @@ pub(crate) struct ExpectedTask {
     expect_present: Option<bool>,
     expect_wakes: Option<u64>,
     expect_self_wakes: Option<u64>,
+    expect_task_wakes: Option<u64>,
     expect_polls: Option<u64>,
+    expect_scheduler: Option<Scheduler>,
 }
 
 #[allow(clippy::result_large_err)]
@@ -167,6 +185,22 @@ impl ExpectedTask {
             }
         }
 
+        if let Some(expected_task_wakes) = self.expect_task_wakes {
+            no_expectations = false;
+            if expected_task_wakes != actual_task.task_wakes {
+                return Err(TaskValidationFailure {
+                    expected: self.clone(),
+                    actual: Some(actual_task.clone()),
+                    failure: format!(
+                        "{self}: expected `task_wakes` to be \
+                        {expected_task_wakes}, but actual was \
+                        {actual_task_wakes}",
+                        actual_task_wakes = actual_task.task_wakes,
+                    ),
+                });
+            }
+        }
+
         if let Some(expected_polls) = self.expect_polls {
             no_expectations = false;
             if expected_polls != actual_task.polls {
@@ -182,6 +216,22 @@ impl ExpectedTask {
             }
         }
 
+        if let Some(expected_scheduler) = self.expect_scheduler {
+            no_expectations = false;
+            if expected_scheduler != actual_task.scheduler {
+                return Err(TaskValidationFailure {
+                    expected: self.clone(),
+                    actual: Some(actual_task.clone()),
+                    failure: format!(
+                        "{self}: expected `scheduler` to be \
+                        {expected_scheduler:?}, but actual was \
+                        {actual_scheduler:?}",
+                        actual_scheduler = actual_task.scheduler,
+                    ),
+                });
+            }
+        }
+
         if no_expectations {
             return Err(TaskValidationFailure {
                 expected: self.clone(),
@@ -248,6 +298,17 @@ impl ExpectedTask {
         self
     }
 
+    /// Expects that a task has been woken a specific number of times by
+    /// other tasks.
+    ///
+    /// To validate, the actual task matching this expected task must have
+    /// been woken `task_wakes` times by tasks other than itself, in total.
+    #[allow(dead_code)]
+    pub(crate) fn expect_task_wakes(mut self, task_wakes: u64) -> Self {
+        self.expect_task_wakes = Some(task_wakes);
+        self
+    }
+
     /// Expects that a task has a specific value for `polls`.
     ///
     /// To validate, the actual task must have a count of polls (on
@@ -257,6 +318,16 @@ impl ExpectedTask {
         self.expect_polls = Some(polls);
         self
     }
+
+    /// Expects that a task is run by a specific kind of scheduler.
+    ///
+    /// To validate, the actual task must have been reported with the
+    /// `scheduler`.
+    #[allow(dead_code)]
+    pub(crate) fn expect_scheduler(mut self, scheduler: Scheduler) -> Self {
+        self.expect_scheduler = Some(scheduler);
+        self
+    }
 }
 
 impl fmt::Display for ExpectedTask {
@@ -1,6 +1,6 @@
 mod support;
 
-use support::{assert_task, ExpectedTask};
+use support::{assert_task, assert_tasks, spawn_named, ExpectedTask};
 
 #[test]
 fn self_wake() {
@@ -15,3 +15,33 @@ fn self_wake() {
 
     assert_task(expected_task, future);
 }
+
+#[test]
+fn wakes_by_other_tasks() {
+    let expected_tasks = vec![
+        ExpectedTask::default()
+            .match_name("receiver".into())
+            .expect_self_wakes(0)
+            .expect_task_wakes(1),
+        ExpectedTask::default()
+            .match_name("sender".into())
+            .expect_task_wakes(0),
+    ];
+
+    let future = async {
+        let (tx, rx) = tokio::sync::oneshot::channel();
+        let receiver = spawn_named("receiver", async move {
+            rx.await.expect("sender dropped");
+        });
+        // Let the receiver start waiting, so that sending wakes it.
+        tokio::task::yield_now().await;
+        spawn_named("sender", async move {
+            tx.send(()).expect("receiver dropped");
+        })
+        .await
+        .expect("joining sender failed");
+        receiver.await.expect("joining receiver failed");
+    };
+
+    assert_tasks(expected_tasks, future);
+}
@@ -0,0 +1,62 @@
+use console_subscriber::watch_span;
+use tokio::task;
+
+mod support;
+use support::{record, record_filtered, spawn_named, ActualSpan, Recording};
+
+/// Spawns a task named `watcher` which enters a watched span named `phase`
+/// twice, along with a span which isn't watched.
+async fn watch_phase() {
+    let watcher = spawn_named("watcher", async {
+        let span = watch_span!("phase", step = 1);
+        for _ in 0..2 {
+            {
+                let _guard = span.enter();
+                let _unwatched = tracing::trace_span!("unwatched").entered();
+            }
+            task::yield_now().await;
+        }
+    });
+    watcher.await.expect("joining task failed")
+}
+
+fn phase_span(recording: &Recording) -> &ActualSpan {
+    assert_eq!(
+        recording.spans.len(),
+        1,
+        "expected only the watched span to be recorded: {spans:?}",
+        spans = recording.spans,
+    );
+    let span = &recording.spans[0];
+    assert_eq!(span.name.as_deref(), Some("phase"));
+    span
+}
+
+fn assert_phase_recorded(recording: &Recording) {
+    let span = phase_span(recording);
+    assert_eq!(span.enters, 2, "expected the span to be entered twice");
+    assert!(span.closed, "expected the span to be closed");
+
+    let watcher = recording
+        .tasks
+        .iter()
+        .find(|task| task.name.as_deref() == Some("watcher"))
+        .expect("the watcher task wasn't recorded");
+    assert_eq!(
+        span.task_id,
+        Some(watcher.id),
+        "expected the span to be created in the watcher task"
+    );
+}
+
+#[test]
+fn watched_spans_are_recorded() {
+    let (recording, _) = record(watch_phase());
+    assert_phase_recorded(&recording);
+}
+
+#[test]
+fn filtered_layer_records_watched_spans() {
+    let (recording, _) = record_filtered(watch_phase());
+    assert_phase_recorded(&recording);
+}
@@ -0,0 +1,368 @@
+//! A mock implementation of the console's `Instrument` gRPC service, for
+//! testing console clients without an instrumented process.
+//!
+//! A [`MockServer`] is given a [`Script`] for each time a client connects,
+//! which lists the [`Update`]s to send on that connection's `WatchUpdates`
+//! stream, and how the stream ends. This makes it possible to test how a
+//! client handles a particular sequence of updates, and how it retries when
+//! the remote goes away, deterministically.
+//!
+//! ```rust
+//! use console_api::instrument::Update;
+//! use console_test_support::{MockServer, Request, Script};
+//! use tonic::Status;
+//!
+//! # async fn docs() -> std::io::Result<()> {
+//! let server = MockServer::new()
+//!     // The first connection gets two updates, then fails...
+//!     .connection(
+//!         Script::new()
+//!             .update(Update::default())
+//!             .update(Update::default())
+//!             .fail(Status::unavailable("going away")),
+//!     )
+//!     // ...and the client gets one more update when it reconnects.
+//!     .connection(Script::new().update(Update::default()))
+//!     .spawn()
+//!     .await?;
+//!
+//! // Connect a client to `server.uri()`...
+//!
+//! for request in server.requests() {
+//!     if let Request::WatchUpdates(request) = request {
+//!         println!("the client asked for a retention of {:?}", request.retention);
+//!     }
+//! }
+//! # Ok(())
+//! # }
+//! ```
+use console_api::{
+    instrument::{
+        instrument_server::{Instrument, InstrumentServer},
+        AuditLogRequest, AuditLogResponse, InstrumentRequest, ListTasksRequest, ListTasksResponse,
+        PauseRequest, PauseResponse, ResumeRequest, ResumeResponse, State, StateRequest,
+        TaskDetailsRequest, TaskDetailsSetRequest, TaskGroupsRequest, Update,
+    },
+    tasks::{TaskDetails, TaskDetailsSet, TaskGroups},
+};
+use std::{
+    collections::VecDeque,
+    io,
+    net::SocketAddr,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+use tokio::{net::TcpListener, sync::mpsc, task::JoinHandle};
+use tokio_stream::wrappers::{ReceiverStream, TcpListenerStream};
+use tonic::{transport::Uri, Response, Status};
+
+/// A mock `Instrument` server, which sends scripted updates to the clients
+/// which connect to it.
+///
+/// Each [`Script`] added with [`MockServer::connection`] is played on one
+/// `WatchUpdates` stream, in the order they were added. Once every script has
+/// been played, further attempts to watch updates fail with
+/// [`Status::unavailable`], as if the remote had gone away.
+#[derive(Debug, Default)]
+pub struct MockServer {
+    scripts: VecDeque<Script>,
+}
+
+/// The updates a [`MockServer`] sends on one `WatchUpdates` stream, and how
+/// the stream ends.
+///
+/// Unless the script ends with [`Script::close`] or [`Script::fail`], the
+/// stream is left open once every update has been sent, as the stream from
+/// an instrumented process is until it exits.
+#[derive(Clone, Debug, Default)]
+pub struct Script {
+    steps: Vec<Step>,
+}
+
+/// A [`MockServer`] which is serving clients.
+///
+/// The server is shut down when this is dropped.
+#[derive(Debug)]
+pub struct RunningServer {
+    addr: SocketAddr,
+    requests: Arc<Mutex<Vec<Request>>>,
+    serve: JoinHandle<()>,
+}
+
+/// A request a [`MockServer`] has received.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Request {
+    WatchUpdates(InstrumentRequest),
+    WatchState,
+    WatchTaskDetails(TaskDetailsRequest),
+    WatchTaskDetailsSet(TaskDetailsSetRequest),
+    WatchTaskGroups,
+    Pause(PauseRequest),
+    Resume,
+    AuditLog,
+    ListTasks(ListTasksRequest),
+}
+
+#[derive(Clone, Debug)]
+enum Step {
+    Update(Box<Update>),
+    Wait(Duration),
+    Close,
+    Fail(Status),
+}
+
+/// The `Instrument` service a [`MockServer`] serves.
+#[derive(Debug)]
+struct Service {
+    scripts: Mutex<VecDeque<Script>>,
+    requests: Arc<Mutex<Vec<Request>>>,
+}
+
+/// How many messages each stream buffers before the script waits for the
+/// client to receive them.
+const STREAM_CAPACITY: usize = 16;
+
+// === impl MockServer ===
+
+impl MockServer {
+    /// Returns a server with no scripts, which refuses to stream updates.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds the script to play on the next client connection.
+    pub fn connection(mut self, script: Script) -> Self {
+        self.scripts.push_back(script);
+        self
+    }
+
+    /// Starts serving clients on an unused port on the loopback interface.
+    pub async fn spawn(self) -> io::Result<RunningServer> {
+        let listener = TcpListener::bind((std::net::Ipv4Addr::LOCALHOST, 0)).await?;
+        let addr = listener.local_addr()?;
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let service = Service {
+            scripts: Mutex::new(self.scripts),
+            requests: requests.clone(),
+        };
+        let serve = tokio::spawn(async move {
+            let result = tonic::transport::Server::builder()
+                .add_service(InstrumentServer::new(service))
+                .serve_with_incoming(TcpListenerStream::new(listener))
+                .await;
+            if let Err(error) = result {
+                panic!("mock instrument server failed: {error}");
+            }
+        });
+        Ok(RunningServer {
+            addr,
+            requests,
+            serve,
+        })
+    }
+}
+
+// === impl Script ===
+
+impl Script {
+    /// Returns an empty script, which sends no updates and leaves the stream
+    /// open.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sends `update`.
+    pub fn update(mut self, update: Update) -> Self {
+        self.steps.push(Step::Update(Box::new(update)));
+        self
+    }
+
+    /// Sends each of `updates`, in order.
+    pub fn updates(self, updates: impl IntoIterator<Item = Update>) -> Self {
+        updates.into_iter().fold(self, Self::update)
+    }
+
+    /// Waits for `duration` before playing the rest of the script.
+    pub fn wait(mut self, duration: Duration) -> Self {
+        self.steps.push(Step::Wait(duration));
+        self
+    }
+
+    /// Ends the stream cleanly, as if the instrumented process had closed
+    /// it.
+    pub fn close(mut self) -> Self {
+        self.steps.push(Step::Close);
+        self
+    }
+
+    /// Ends the stream with `status`.
+    pub fn fail(mut self, status: Status) -> Self {
+        self.steps.push(Step::Fail(status));
+        self
+    }
+
+    /// Plays the script on a stream, holding the stream open afterwards
+    /// unless the script ends it.
+    async fn play(self, tx: mpsc::Sender<Result<Update, Status>>) {
+        for step in self.steps {
+            let sent = match step {
+                Step::Update(update) => tx.send(Ok(*update)).await,
+                Step::Wait(duration) => {
+                    tokio::time::sleep(duration).await;
+                    Ok(())
+                }
+                Step::Close => return,
+                Step::Fail(status) => {
+                    let _ = tx.send(Err(status)).await;
+                    return;
+                }
+            };
+            if sent.is_err() {
+                // The client has stopped watching.
+                return;
+            }
+        }
+        tx.closed().await;
+    }
+}
+
+// === impl RunningServer ===
+
+impl RunningServer {
+    /// Returns the address the server is listening on.
+    pub fn addr(&self) -> SocketAddr {
+        self.addr
+    }
+
+    /// Returns the URI a console client can connect to the server with.
+    pub fn uri(&self) -> Uri {
+        format!("http://{}", self.addr)
+            .parse()
+            .expect("socket addresses are valid URIs")
+    }
+
+    /// Returns every request the server has received so far, in the order
+    /// they were received.
+    pub fn requests(&self) -> Vec<Request> {
+        self.requests.lock().unwrap().clone()
+    }
+
+    /// Returns how many times a client has started watching updates.
+    pub fn connections(&self) -> usize {
+        self.requests
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|request| matches!(request, Request::WatchUpdates(_)))
+            .count()
+    }
+}
+
+impl Drop for RunningServer {
+    fn drop(&mut self) {
+        self.serve.abort();
+    }
+}
+
+// === impl Service ===
+
+impl Service {
+    fn record(&self, request: Request) {
+        self.requests.lock().unwrap().push(request);
+    }
+}
+
+#[tonic::async_trait]
+impl Instrument for Service {
+    type WatchUpdatesStream = ReceiverStream<Result<Update, Status>>;
+    type WatchTaskDetailsStream = ReceiverStream<Result<TaskDetails, Status>>;
+    type WatchTaskDetailsSetStream = ReceiverStream<Result<TaskDetailsSet, Status>>;
+    type WatchTaskGroupsStream = ReceiverStream<Result<TaskGroups, Status>>;
+    type WatchStateStream = ReceiverStream<Result<State, Status>>;
+
+    async fn watch_updates(
+        &self,
+        request: tonic::Request<InstrumentRequest>,
+    ) -> Result<Response<Self::WatchUpdatesStream>, Status> {
+        self.record(Request::WatchUpdates(request.into_inner()));
+        let script = self
+            .scripts
+            .lock()
+            .unwrap()
+            .pop_front()
+            .ok_or_else(|| Status::unavailable("the mock server has no more scripts"))?;
+        let (tx, rx) = mpsc::channel(STREAM_CAPACITY);
+        tokio::spawn(script.play(tx));
+        Ok(Response::new(ReceiverStream::new(rx)))
+    }
+
+    async fn watch_state(
+        &self,
+        _: tonic::Request<StateRequest>,
+    ) -> Result<Response<Self::WatchStateStream>, Status> {
+        self.record(Request::WatchState);
+        // Scripts don't change the state, so the stream stays open without
+        // sending anything until the client stops watching.
+        let (tx, rx) = mpsc::channel(1);
+        tokio::spawn(async move { tx.closed().await });
+        Ok(Response::new(ReceiverStream::new(rx)))
+    }
+
+    async fn watch_task_details(
+        &self,
+        request: tonic::Request<TaskDetailsRequest>,
+    ) -> Result<Response<Self::WatchTaskDetailsStream>, Status> {
+        self.record(Request::WatchTaskDetails(request.into_inner()));
+        Err(Status::unimplemented("the mock server has no task details"))
+    }
+
+    async fn watch_task_details_set(
+        &self,
+        request: tonic::Request<TaskDetailsSetRequest>,
+    ) -> Result<Response<Self::WatchTaskDetailsSetStream>, Status> {
+        self.record(Request::WatchTaskDetailsSet(request.into_inner()));
+        Err(Status::unimplemented("the mock server has no task details"))
+    }
+
+    async fn watch_task_groups(
+        &self,
+        _: tonic::Request<TaskGroupsRequest>,
+    ) -> Result<Response<Self::WatchTaskGroupsStream>, Status> {
+        self.record(Request::WatchTaskGroups);
+        Err(Status::unimplemented("the mock server has no task groups"))
+    }
+
+    async fn pause(
+        &self,
+        request: tonic::Request<PauseRequest>,
+    ) -> Result<Response<PauseResponse>, Status> {
+        self.record(Request::Pause(request.into_inner()));
+        Ok(Response::new(PauseResponse {}))
+    }
+
+    async fn resume(
+        &self,
+        _: tonic::Request<ResumeRequest>,
+    ) -> Result<Response<ResumeResponse>, Status> {
+        self.record(Request::Resume);
+        Ok(Response::new(ResumeResponse {}))
+    }
+
+    async fn audit_log(
+        &self,
+        _: tonic::Request<AuditLogRequest>,
+    ) -> Result<Response<AuditLogResponse>, Status> {
+        self.record(Request::AuditLog);
+        Ok(Response::new(AuditLogResponse::default()))
+    }
+
+    async fn list_tasks(
+        &self,
+        request: tonic::Request<ListTasksRequest>,
+    ) -> Result<Response<ListTasksResponse>, Status> {
+        self.record(Request::ListTasks(request.into_inner()));
+        Err(Status::unimplemented(
+            "the mock server has no tasks to list",
+        ))
+    }
+}
@@ -1,4 +1,11 @@
-use crate::state::tasks::Task;
+use crate::conn;
+use crate::forward::Via;
+use crate::i18n::Lang;
+use crate::input::ScriptedKeys;
+use crate::log_file::RotatingLog;
+use crate::query::Filter;
+use crate::state::{resources::Resource, tasks::Task};
+use crate::term;
 use crate::view::Palette;
 use crate::warnings;
 use clap::builder::{PossibleValuesParser, TypedValueParser};
@@ -39,11 +46,21 @@ pub struct Config {
     #[clap(value_hint = ValueHint::Url)]
     pub(crate) target_addr: Option<Uri>,
 
+    /// Connect to a process in a container, through a port forward.
+    ///
+    /// This may be `docker:<CONTAINER>`, which runs `nc` in the container
+    /// with `docker exec` to relay each connection, or
+    /// `kube:[<NAMESPACE>/]<POD>`, which runs `kubectl port-forward` to the
+    /// pod. The port of the target address is the port in the container the
+    /// connection is forwarded to, and its host is ignored.
+    #[clap(long = "via", value_name = "HELPER")]
+    pub(crate) via: Option<Via>,
+
     /// Log level filter for the console's internal diagnostics.
     ///
-    /// Logs are written to a new file at the path given by the `--log-dir`
-    /// argument (or its default value), or to the system journal if
-    /// `systemd-journald` support is enabled.
+    /// Logs are written as JSON, one event per line, to a new file in the
+    /// directory given by the `--log-dir` argument (or its default value),
+    /// or to the system journal if `systemd-journald` support is enabled.
     ///
     /// If this is set to 'off' or is not set, no logs will be written.
     ///
@@ -62,13 +79,25 @@ pub struct Config {
     ///
     /// * `lost-waker` -- Warns when a task is dropped without being woken.
     ///
+    /// * `unbalanced-wakers` -- Warns when more of a task's wakers have been dropped than were
+    ///                          cloned, which means the instrumentation missed some clones.
+    ///
     /// * `never-yielded` -- Warns when a task has never yielded.
     ///
+    /// * `starved` -- Warns when a task has been woken, but not polled, for more than a second.
+    ///
+    /// * `slow-first-poll` -- Warns when a task waits more than 100ms after being spawned to be polled.
+    ///
+    /// * `wake-storm` -- Warns when a task is woken more than 1000 times for each time it's polled.
+    ///
     /// * `auto-boxed-future` -- Warnings when the future driving a task was automatically boxed by
     ///                          the runtime because it was large.
     ///
     /// * `large-future` -- Warnings when the future driving a task occupies a large amount of
     ///                     stack space.
+    ///
+    /// * `contention` -- Warns when a synchronization primitive, such as a mutex or semaphore,
+    ///                   has a long average wait time or many waiters.
     #[clap(long = "warn", short = 'W', value_delimiter = ',', num_args = 1..)]
     #[clap(default_values_t = KnownWarnings::default_enabled_warnings())]
     pub(crate) warnings: Vec<KnownWarnings>,
@@ -84,17 +113,29 @@ pub struct Config {
     ///
     /// * `lost-waker` -- Warns when a task is dropped without being woken.
     ///
+    /// * `unbalanced-wakers` -- Warns when more of a task's wakers have been dropped than were
+    ///                          cloned, which means the instrumentation missed some clones.
+    ///
     /// * `never-yielded` -- Warns when a task has never yielded.
     ///
+    /// * `starved` -- Warns when a task has been woken, but not polled, for more than a second.
+    ///
+    /// * `slow-first-poll` -- Warns when a task waits more than 100ms after being spawned to be polled.
+    ///
+    /// * `wake-storm` -- Warns when a task is woken more than 1000 times for each time it's polled.
+    ///
     /// * `auto-boxed-future` -- Warnings when the future driving a task was automatically boxed by
     ///                          the runtime because it was large.
     ///
     /// * `large-future` -- Warnings when the future driving a task occupies a large amount of
     ///                     stack space.
     ///
+    /// * `contention` -- Warns when a synchronization primitive, such as a mutex or semaphore,
+    ///                   has a long average wait time or many waiters.
+    ///
     /// If this is set to `all`, all warnings are allowed.
     ///
-    /// [possible values: all, self-wakes, lost-waker, never-yielded, large-future, auto-boxed-future]
+    /// [possible values: all, self-wakes, lost-waker, unbalanced-wakers, never-yielded, starved, slow-first-poll, wake-storm, large-future, auto-boxed-future, contention]
     #[clap(long = "allow", short = 'A', num_args = 1..)]
     pub(crate) allow_warnings: Option<AllowedWarnings>,
 
@@ -104,6 +145,16 @@ pub struct Config {
     #[clap(long = "log-dir", value_hint = ValueHint::DirPath)]
     pub(crate) log_directory: Option<PathBuf>,
 
+    /// The most log files to keep in the `--log-dir` directory.
+    ///
+    /// Each run of the console starts a new log file, and a new file is also
+    /// started whenever the current one grows past 10MiB. Once there are more
+    /// log files than this, the oldest ones are removed.
+    ///
+    /// [default: 10]
+    #[clap(long = "log-max-files", value_name = "COUNT")]
+    pub(crate) log_max_files: Option<usize>,
+
     #[clap(flatten)]
     pub(crate) view_options: ViewOptions,
 
@@ -114,6 +165,10 @@ pub struct Config {
     /// (such as `5days 2min 2s`), or `none` to disable removing completed tasks
     /// and dropped resources.
     ///
+    /// The instrumented application is asked to retain them for as long
+    /// while the console is attached, up to the maximum retention it allows,
+    /// so that they aren't lost if the console reconnects.
+    ///
     /// Each time span is an integer number followed by a suffix. Supported suffixes are:
     ///
     /// * `nsec`, `ns` -- nanoseconds
@@ -140,6 +195,163 @@ pub struct Config {
     #[clap(long = "retain-for")]
     retain_for: Option<RetainFor>,
 
+    /// The most completed tasks to keep, however recently they completed.
+    ///
+    /// Once more tasks than this have completed, the ones which completed
+    /// longest ago are removed first. This keeps the console's memory use
+    /// steady in long sessions with a long `--retain-for`, or with
+    /// `--retain-for none`.
+    ///
+    /// By default, completed tasks are only removed once they have been
+    /// retained for `--retain-for`.
+    #[clap(long = "max-completed-tasks", value_name = "COUNT")]
+    max_completed_tasks: Option<usize>,
+
+    /// Warn about a possible task leak when the number of live tasks has
+    /// only grown for this long, such as `5m` or `1h`.
+    ///
+    /// The live tasks are those spawned over the lifetime of the
+    /// instrumented application which haven't completed yet. A count which
+    /// keeps growing, without ever falling, usually means tasks are being
+    /// spawned which never complete.
+    ///
+    /// [default: 5m]
+    #[clap(
+        long = "leak-window",
+        value_name = "DURATION",
+        value_parser = parse_leak_window
+    )]
+    leak_window: Option<Duration>,
+
+    /// Only show tasks whose spans are at this level or a less verbose one.
+    ///
+    /// Tokio's own task spans are at the `TRACE` level, so more restrictive
+    /// levels only show tasks from runtimes and frameworks which emit task
+    /// spans at other levels. The subscriber can also be configured to not
+    /// record tasks below a level at all, with `TOKIO_CONSOLE_TASK_LEVEL`.
+    ///
+    /// [default: trace]
+    #[clap(long = "task-level", value_name = "LEVEL")]
+    task_level: Option<filter::LevelFilter>,
+
+    /// Open the details view for a task as soon as it appears.
+    ///
+    /// The task may be given by its ID or by its name. If no such task
+    /// exists yet, the console waits for one to be spawned.
+    #[clap(long = "task", value_name = "NAME_OR_ID")]
+    pub(crate) task: Option<String>,
+
+    /// Don't restore the view and target address from the last session, or
+    /// save them when the console exits.
+    ///
+    /// Sessions are saved in the `tokio-console` directory of the user's
+    /// state directory.
+    #[clap(long = "no-session", action = ArgAction::SetTrue)]
+    pub(crate) no_session: bool,
+
+    /// Print a single frame of the tasks view once the first update has been
+    /// received, and exit.
+    ///
+    /// The frame is rendered with ANSI colors and styles by default, or as
+    /// plain text with `--once=plain`. It is the size of the terminal, or
+    /// 120x40 if stdout is not a terminal.
+    ///
+    /// With `--once=status`, only a line with the target address, the number
+    /// of live tasks, and the number of warnings is printed, such as for
+    /// `#(tokio-console --once=status)` in a tmux status line.
+    #[clap(
+        long = "once",
+        value_name = "FORMAT",
+        value_enum,
+        num_args = 0..=1,
+        require_equals = true,
+        default_missing_value = "ansi"
+    )]
+    pub(crate) once: Option<SnapshotFormat>,
+
+    /// Print a plain text summary of the tasks, resources, and warnings
+    /// every few seconds, instead of the full-screen view.
+    ///
+    /// The summaries are written one line after another, without moving
+    /// the cursor or using colors, so they can be followed with a screen
+    /// reader or a braille display, or written to a log, such as in CI.
+    #[clap(
+        long = "plain",
+        visible_alias = "no-tui",
+        action = ArgAction::SetTrue,
+        conflicts_with = "once"
+    )]
+    pub(crate) plain: bool,
+
+    /// How often to print a summary with `--plain`, such as `5s` or `1m`.
+    ///
+    /// [default: 5s]
+    #[clap(
+        long = "summary-interval",
+        value_name = "DURATION",
+        value_parser = parse_summary_interval,
+        requires = "plain"
+    )]
+    pub(crate) summary_interval: Option<Duration>,
+
+    /// Set the terminal title to the target address, the number of live
+    /// tasks, and the number of warnings.
+    ///
+    /// This shows the state of each console in the tabs of a terminal, or
+    /// the window list of tmux, when several are being monitored at once.
+    /// The previous title is restored when the console exits, if the
+    /// terminal supports it.
+    #[clap(long = "title", action = ArgAction::SetTrue, conflicts_with_all = ["once", "plain"])]
+    pub(crate) title: bool,
+
+    /// Exit after this long, such as `30s` or `2m`, as if `q` had been
+    /// pressed.
+    ///
+    /// Along with `--keys`, this lets the console be run unattended, such as
+    /// for demos, smoke tests, or to reproduce a bug. With `--plain`, a last
+    /// summary is printed before exiting.
+    #[clap(
+        long = "duration",
+        value_name = "DURATION",
+        value_parser = parse_duration,
+        conflicts_with = "once"
+    )]
+    pub(crate) duration: Option<Duration>,
+
+    /// Press these keys, as if they were typed, once the first update has
+    /// been received.
+    ///
+    /// This is a comma-separated list, where each item is either the name of
+    /// a key, or text which is typed one character at a time. The names are
+    /// `Enter`, `Esc`, `Tab`, `Backspace`, `Delete`, `Up`, `Down`, `Left`,
+    /// `Right`, `Home`, `End`, `PageUp`, `PageDown`, `Space`, and `Comma`.
+    /// For example, `/,spinner,Enter,Enter` searches for a task named
+    /// `spinner`, and opens its details.
+    ///
+    /// A key is pressed every 200ms, so that each step can be seen.
+    #[clap(long = "keys", value_name = "KEYS", conflicts_with_all = ["once", "plain"])]
+    pub(crate) keys: Option<ScriptedKeys>,
+
+    /// Log the size and decode time of each message received from the
+    /// instrumented process, and how many tasks, resources, and async ops
+    /// each update includes.
+    ///
+    /// These are logged at the `DEBUG` level, with the
+    /// `tokio_console::traffic` target, wherever the console's other logs
+    /// are written. They are logged even if `--log` is not set.
+    #[clap(long = "trace-traffic", action = ArgAction::SetTrue)]
+    pub(crate) trace_traffic: bool,
+
+    /// Named filters and sorts for the tasks list, which are only set in
+    /// the config file.
+    #[clap(skip)]
+    presets: Vec<Preset>,
+
+    /// The most tasks which should be running at once with each target, or
+    /// from each spawn location, which are only set in the config file.
+    #[clap(skip)]
+    quotas: Vec<Quota>,
+
     /// An optional subcommand.
     ///
     /// If one of these is present, the console CLI will do something other than
@@ -154,9 +366,14 @@ pub struct Config {
 pub(crate) enum KnownWarnings {
     SelfWakes,
     LostWaker,
+    UnbalancedWakers,
     NeverYielded,
+    Starved,
+    SlowFirstPoll,
+    WakeStorm,
     AutoBoxedFuture,
     LargeFuture,
+    Contention,
 }
 
 impl FromStr for KnownWarnings {
@@ -166,22 +383,46 @@ impl FromStr for KnownWarnings {
         match s {
             "self-wakes" => Ok(KnownWarnings::SelfWakes),
             "lost-waker" => Ok(KnownWarnings::LostWaker),
+            "unbalanced-wakers" => Ok(KnownWarnings::UnbalancedWakers),
             "never-yielded" => Ok(KnownWarnings::NeverYielded),
+            "starved" => Ok(KnownWarnings::Starved),
+            "slow-first-poll" => Ok(KnownWarnings::SlowFirstPoll),
+            "wake-storm" => Ok(KnownWarnings::WakeStorm),
             "auto-boxed-future" => Ok(KnownWarnings::AutoBoxedFuture),
             "large-future" => Ok(KnownWarnings::LargeFuture),
+            "contention" => Ok(KnownWarnings::Contention),
             _ => Err(format!("unknown warning: {}", s)),
         }
     }
 }
 
-impl From<&KnownWarnings> for warnings::Linter<Task> {
-    fn from(warning: &KnownWarnings) -> Self {
-        match warning {
+impl KnownWarnings {
+    /// Returns the linter for this warning, if it is a warning about tasks.
+    pub(crate) fn task_linter(&self) -> Option<warnings::Linter<Task>> {
+        let linter = match self {
             KnownWarnings::SelfWakes => warnings::Linter::new(warnings::SelfWakePercent::default()),
             KnownWarnings::LostWaker => warnings::Linter::new(warnings::LostWaker),
+            KnownWarnings::UnbalancedWakers => warnings::Linter::new(warnings::UnbalancedWakers),
             KnownWarnings::NeverYielded => warnings::Linter::new(warnings::NeverYielded::default()),
+            KnownWarnings::Starved => warnings::Linter::new(warnings::Starved::default()),
+            KnownWarnings::SlowFirstPoll => {
+                warnings::Linter::new(warnings::SlowFirstPoll::default())
+            }
+            KnownWarnings::WakeStorm => warnings::Linter::new(warnings::WakeStorm::default()),
             KnownWarnings::AutoBoxedFuture => warnings::Linter::new(warnings::AutoBoxedFuture),
             KnownWarnings::LargeFuture => warnings::Linter::new(warnings::LargeFuture::default()),
+            KnownWarnings::Contention => return None,
+        };
+        Some(linter)
+    }
+
+    /// Returns the linter for this warning, if it is a warning about resources.
+    pub(crate) fn resource_linter(&self) -> Option<warnings::Linter<Resource>> {
+        match self {
+            KnownWarnings::Contention => {
+                Some(warnings::Linter::new(warnings::Contention::default()))
+            }
+            _ => None,
         }
     }
 }
@@ -191,9 +432,14 @@ impl fmt::Display for KnownWarnings {
         match self {
             KnownWarnings::SelfWakes => write!(f, "self-wakes"),
             KnownWarnings::LostWaker => write!(f, "lost-waker"),
+            KnownWarnings::UnbalancedWakers => write!(f, "unbalanced-wakers"),
             KnownWarnings::NeverYielded => write!(f, "never-yielded"),
+            KnownWarnings::Starved => write!(f, "starved"),
+            KnownWarnings::SlowFirstPoll => write!(f, "slow-first-poll"),
+            KnownWarnings::WakeStorm => write!(f, "wake-storm"),
             KnownWarnings::AutoBoxedFuture => write!(f, "auto-boxed-future"),
             KnownWarnings::LargeFuture => write!(f, "large-future"),
+            KnownWarnings::Contention => write!(f, "contention"),
         }
     }
 }
@@ -203,9 +449,14 @@ impl KnownWarnings {
         vec![
             KnownWarnings::SelfWakes,
             KnownWarnings::LostWaker,
+            KnownWarnings::UnbalancedWakers,
             KnownWarnings::NeverYielded,
+            KnownWarnings::Starved,
+            KnownWarnings::SlowFirstPoll,
+            KnownWarnings::WakeStorm,
             KnownWarnings::AutoBoxedFuture,
             KnownWarnings::LargeFuture,
+            KnownWarnings::Contention,
         ]
     }
 }
@@ -277,6 +528,149 @@ pub enum OptionalCmd {
         #[clap(value_enum)]
         shell: Shell,
     },
+
+    /// Run a query against the tasks of a console-enabled process, print the
+    /// results, and exit.
+    ///
+    /// A query names a source, followed by stages separated by `|`:
+    ///
+    ///
+    ///     $ tokio-console query 'tasks | where busy > 1s | top 10 by polls'
+    ///
+    ///
+    /// The supported stages are `where <expr>`, `sort by <field> [asc|desc]`,
+    /// `top <n> by <field>`, `limit <n>`, `select <field>, ...`, and the
+    /// aggregations `count`, `sum <field>`, `avg <field>`, `min <field>` and
+    /// `max <field>`.
+    ///
+    /// The same queries can be run in the console by typing `:query` followed
+    /// by the query.
+    Query {
+        /// The query to run.
+        query: String,
+
+        /// How to print the results.
+        ///
+        /// In JSON output, durations are given in nanoseconds.
+        #[clap(long = "format", value_enum, default_value_t = QueryFormat::Table)]
+        format: QueryFormat,
+    },
+
+    /// Print the graph of which tasks wake which other tasks, and which
+    /// resources each task waits on, and exit.
+    ///
+    /// The graph can be rendered with Graphviz:
+    ///
+    ///
+    ///     $ tokio-console export-graph | dot -Tsvg > wakes.svg
+    ///
+    ExportGraph {
+        /// The format to print the graph in.
+        #[clap(long = "format", value_enum, default_value_t = GraphFormat::Dot)]
+        format: GraphFormat,
+
+        /// Read the graph from a recording, rather than connecting to a
+        /// console-enabled process.
+        ///
+        /// A recording is a JSON array of updates, as serialized by
+        /// `console-api`'s `serde` feature.
+        #[clap(long = "recording", value_hint = ValueHint::FilePath)]
+        recording: Option<PathBuf>,
+    },
+}
+
+/// How to print the graph exported by `export-graph`.
+#[derive(clap::ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GraphFormat {
+    /// A Graphviz DOT digraph, with edges weighted by the number of wakes.
+    Dot,
+}
+
+/// How to print the results of a query.
+#[derive(clap::ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum QueryFormat {
+    /// A table with a column for each field.
+    Table,
+    /// A JSON array, with an object for each row.
+    Json,
+}
+
+/// How to print a frame rendered with `--once`.
+#[derive(clap::ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SnapshotFormat {
+    /// Text with ANSI escape codes for colors and styles.
+    Ansi,
+    /// Plain text.
+    Plain,
+    /// A single line with the target address, the number of live tasks,
+    /// and the number of warnings, such as for a tmux status line.
+    Status,
+}
+
+/// How to display points in time, such as when a task was spawned.
+#[derive(clap::ValueEnum, Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub(crate) enum TimeFormat {
+    /// How long ago, relative to the last update.
+    #[default]
+    Relative,
+    /// The time in the local time zone.
+    Local,
+    /// The time in UTC.
+    Utc,
+}
+
+/// A named filter and sort for the tasks list, applied by pressing its key.
+///
+/// Presets are set in the `[[presets]]` tables of the config file:
+///
+/// ```toml
+/// [[presets]]
+/// name = "hot"
+/// key = "1"
+/// filter = "busy > 100ms"
+/// sort = "Busy"
+/// ```
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct Preset {
+    pub(crate) name: String,
+    /// The key which applies the preset, which must be a digit from 1 to 9.
+    pub(crate) key: char,
+    /// Which tasks to list, as the expression of a query's `where` stage.
+    pub(crate) filter: Option<String>,
+    /// The header of the column to sort by.
+    pub(crate) sort: Option<String>,
+    /// Whether to sort with the largest values first.
+    ///
+    /// [default: true]
+    pub(crate) descending: Option<bool>,
+}
+
+/// The most tasks which should be running at once with a target, or spawned
+/// from a location.
+///
+/// The console highlights the targets and task groups which exceed their
+/// quota, and warns about them in the tasks list. Quotas are set in the
+/// `[[quotas]]` tables of the config file, each with either a `target` or a
+/// `location`:
+///
+/// ```toml
+/// [[quotas]]
+/// target = "app::conn"
+/// max_tasks = 100
+/// ```
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct Quota {
+    /// The `tracing` target of the tasks the quota applies to.
+    pub(crate) target: Option<String>,
+    /// The location the tasks the quota applies to were spawned from, as
+    /// shown in the `Location` column.
+    pub(crate) location: Option<String>,
+    /// The most tasks which should be running at once. Completed tasks
+    /// don't count towards this.
+    pub(crate) max_tasks: usize,
 }
 
 #[derive(Debug, Clone, Copy, Deserialize)]
@@ -318,6 +712,10 @@ pub struct ViewOptions {
     ascii_only: Option<bool>,
 
     /// Disable ANSI colors entirely.
+    ///
+    /// Colors are also disabled if the `NO_COLOR` environment variable is
+    /// set, or if `CLICOLOR` is set to `0`. Setting `CLICOLOR_FORCE` enables
+    /// colors even if the terminal doesn't advertise support for them.
     #[clap(name = "no-colors", long = "no-colors",action = ArgAction::SetTrue)]
     no_colors: bool,
 
@@ -343,6 +741,49 @@ pub struct ViewOptions {
 
     #[clap(flatten)]
     toggles: ColorToggles,
+
+    /// Redraw the console less often, for use over slow connections such as
+    /// SSH sessions.
+    ///
+    /// This caps the frame rate (see `--max-fps`) and hides the task
+    /// activity charts, which change on every update. It can be disabled
+    /// with `--low-bandwidth=false`.
+    ///
+    /// [default: enabled if the `SSH_CONNECTION` environment variable is set]
+    #[clap(
+        long = "low-bandwidth",
+        num_args = 0..=1,
+        require_equals = true,
+        default_missing_value = "true"
+    )]
+    low_bandwidth: Option<bool>,
+
+    /// The maximum number of times per second to redraw the console.
+    ///
+    /// [default: 2 in low-bandwidth mode, otherwise 30]
+    #[clap(long = "max-fps", value_name = "FPS", value_parser = clap::value_parser!(u32).range(1..))]
+    max_fps: Option<u32>,
+
+    /// How to display points in time, such as when a task was spawned or
+    /// last woken.
+    ///
+    /// This can be changed while the console is running by pressing `z`.
+    /// Local times are only supported on Unix, and are shown in UTC on other
+    /// platforms.
+    #[clap(long = "time-format", value_name = "FORMAT")]
+    time_format: Option<TimeFormat>,
+
+    /// Make a task's spawn location in its details view a link to the code
+    /// it was spawned from, in terminals which support OSC 8 hyperlinks.
+    ///
+    /// The template is a URL, in which `{file}`, `{line}`, and `{column}`
+    /// are replaced with those of the location. For example,
+    /// `vscode://file/{file}:{line}:{column}` opens it in VS Code, and
+    /// `https://github.com/OWNER/REPO/blob/main/{file}#L{line}` a permalink
+    /// to it. Files in the application's own crates are usually relative to
+    /// its workspace.
+    #[clap(long = "location-links", value_name = "TEMPLATE")]
+    location_links: Option<String>,
 }
 
 /// Toggles on and off color coding for individual UI elements.
@@ -394,13 +835,21 @@ impl FromStr for LogFilter {
 #[serde(deny_unknown_fields)]
 struct ConfigFile {
     default_target_addr: Option<String>,
+    via: Option<String>,
     log: Option<String>,
     warnings: Vec<KnownWarnings>,
     allow_warnings: Option<AllowedWarnings>,
     log_directory: Option<PathBuf>,
+    log_max_files: Option<usize>,
     retention: Option<RetainFor>,
+    max_completed_tasks: Option<usize>,
+    task_level: Option<String>,
+    session: Option<bool>,
     charset: Option<CharsetConfig>,
     colors: Option<ColorsConfig>,
+    rendering: Option<RenderingConfig>,
+    presets: Option<Vec<Preset>>,
+    quotas: Option<Vec<Quota>>,
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize)]
@@ -410,6 +859,15 @@ struct CharsetConfig {
     ascii_only: Option<bool>,
 }
 
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+struct RenderingConfig {
+    low_bandwidth: Option<bool>,
+    max_fps: Option<u32>,
+    time_format: Option<TimeFormat>,
+    location_links: Option<String>,
+}
+
 #[derive(Debug, Clone, Deserialize, Serialize)]
 #[serde(deny_unknown_fields)]
 struct ColorsConfig {
@@ -451,9 +909,15 @@ impl Config {
         let filter = match self.log_filter.clone() {
             // if logging is totally disabled, don't bother even constructing
             // the subscriber
-            None => return Ok(()),
+            None if !self.trace_traffic => return Ok(()),
+            None => filter::Targets::new(),
             Some(LogFilter(filter)) => filter,
         };
+        let filter = if self.trace_traffic {
+            filter.with_target(conn::TRAFFIC_TARGET, filter::LevelFilter::DEBUG)
+        } else {
+            filter
+        };
 
         // If we're on a Linux distro with journald, try logging to the system
         // journal so we don't interfere with text output.
@@ -473,34 +937,14 @@ impl Config {
                 .log_directory
                 .clone()
                 .unwrap_or_else(default_log_directory);
+            let max_files = self.log_max_files.unwrap_or(DEFAULT_LOG_MAX_FILES);
+            let log = RotatingLog::new(dir, max_files)?;
 
-            // first ensure that the log directory exists
-            fs::create_dir_all(&dir)
-                .with_context(|| format!("creating log directory '{}'", dir.display()))?;
-            color_eyre::eyre::ensure!(
-                dir.is_dir(),
-                "log directory path '{}' is not a directory",
-                dir.display()
-            );
-
-            // now, open a log file
-            let now = std::time::SystemTime::now();
-            // format the current time in a way that's appropriate for a
-            // filename (strip the `:` character, as it is an invalid filename
-            // char on windows)
-            let filename =
-                format!("{}.log", humantime::format_rfc3339_seconds(now)).replace(':', "");
-            let path = dir.join(filename);
-            let file = fs::File::options()
-                .create_new(true)
-                .write(true)
-                .open(&path)
-                .with_context(|| format!("creating log file '{}'", path.display()))?;
-
-            // finally, construct a `fmt` layer to write to that log file
+            // finally, construct a `fmt` layer to write JSON to that log file
             let fmt = tracing_subscriber::fmt::layer()
+                .json()
                 .with_ansi(false)
-                .with_writer(file);
+                .with_writer(std::sync::Mutex::new(log));
             Some(fmt)
         } else {
             None
@@ -520,6 +964,26 @@ impl Config {
         self.retain_for.unwrap_or_default().0
     }
 
+    pub(crate) fn max_completed_tasks(&self) -> Option<usize> {
+        self.max_completed_tasks
+    }
+
+    pub(crate) fn leak_window(&self) -> Duration {
+        self.leak_window.unwrap_or(DEFAULT_LEAK_WINDOW)
+    }
+
+    pub(crate) fn task_level(&self) -> filter::LevelFilter {
+        self.task_level.unwrap_or(filter::LevelFilter::TRACE)
+    }
+
+    pub(crate) fn presets(&self) -> &[Preset] {
+        &self.presets
+    }
+
+    pub(crate) fn quotas(&self) -> &[Quota] {
+        &self.quotas
+    }
+
     pub(crate) fn target_addr(&self) -> color_eyre::Result<Uri> {
         let target_addr = self
             .target_addr
@@ -538,6 +1002,19 @@ impl Config {
         Ok(target_addr)
     }
 
+    /// Returns a connection to the target address, through `--via` if it's
+    /// set.
+    pub(crate) fn connection(&self) -> color_eyre::Result<conn::Connection> {
+        let target = self.target_addr()?;
+        if self.via.is_some() && target.scheme_str() == Some("file") {
+            return Err(color_eyre::eyre::eyre!(
+                "`--via` forwards a TCP port, so it can't connect to the unix domain socket {:?}",
+                target
+            ));
+        }
+        Ok(conn::Connection::new(target).with_via(self.via.clone()))
+    }
+
     pub(crate) fn add_issue_metadata(
         &self,
         mut builder: color_eyre::config::HookBuilder,
@@ -559,9 +1036,25 @@ impl Config {
             self, builder =>
                 subcmd,
                 target_addr,
+                via,
                 log_filter,
                 log_directory,
+                log_max_files,
                 retain_for,
+                max_completed_tasks,
+                leak_window,
+                task_level,
+                task,
+                no_session,
+                once,
+                plain,
+                summary_interval,
+                title,
+                duration,
+                keys,
+                trace_traffic,
+                presets,
+                quotas,
                 view_options.no_colors,
                 view_options.lang,
                 view_options.ascii_only,
@@ -569,6 +1062,10 @@ impl Config {
                 view_options.palette,
                 view_options.toggles.color_durations,
                 view_options.toggles.color_terminated,
+                view_options.low_bandwidth,
+                view_options.max_fps,
+                view_options.time_format,
+                view_options.location_links,
         }
 
         builder
@@ -583,7 +1080,9 @@ impl Config {
     fn merge_with(self, other: Self) -> Self {
         Self {
             log_directory: other.log_directory.or(self.log_directory),
+            log_max_files: other.log_max_files.or(self.log_max_files),
             target_addr: other.target_addr.or(self.target_addr),
+            via: other.via.or(self.via),
             log_filter: other.log_filter.or(self.log_filter),
             warnings: {
                 let mut warns: Vec<KnownWarnings> = other.warnings;
@@ -599,7 +1098,31 @@ impl Config {
                 }
             },
             retain_for: other.retain_for.or(self.retain_for),
+            max_completed_tasks: other.max_completed_tasks.or(self.max_completed_tasks),
+            leak_window: other.leak_window.or(self.leak_window),
+            task_level: other.task_level.or(self.task_level),
             view_options: self.view_options.merge_with(other.view_options),
+            task: other.task.or(self.task),
+            no_session: other.no_session || self.no_session,
+            once: other.once.or(self.once),
+            plain: other.plain || self.plain,
+            summary_interval: other.summary_interval.or(self.summary_interval),
+            title: other.title || self.title,
+            duration: other.duration.or(self.duration),
+            keys: other.keys.or(self.keys),
+            trace_traffic: other.trace_traffic || self.trace_traffic,
+            // presets are replaced as a whole, rather than merged one by one,
+            // so that two presets are never bound to the same key.
+            presets: if other.presets.is_empty() {
+                self.presets
+            } else {
+                other.presets
+            },
+            quotas: if other.quotas.is_empty() {
+                self.quotas
+            } else {
+                other.quotas
+            },
             subcmd: other.subcmd.or(self.subcmd),
         }
     }
@@ -609,19 +1132,91 @@ impl Default for Config {
     fn default() -> Self {
         Self {
             target_addr: Some(default_target_addr()),
+            via: None,
             log_filter: Some(LogFilter(
                 filter::Targets::new().with_default(filter::LevelFilter::OFF),
             )),
             warnings: KnownWarnings::default_enabled_warnings(),
             allow_warnings: None,
             log_directory: Some(default_log_directory()),
+            log_max_files: Some(DEFAULT_LOG_MAX_FILES),
             retain_for: Some(RetainFor::default()),
+            max_completed_tasks: None,
+            leak_window: None,
+            task_level: None,
             view_options: ViewOptions::default(),
+            task: None,
+            no_session: false,
+            once: None,
+            plain: false,
+            summary_interval: None,
+            title: false,
+            duration: None,
+            keys: None,
+            trace_traffic: false,
+            presets: Vec::new(),
+            quotas: Vec::new(),
             subcmd: None,
         }
     }
 }
 
+/// The env vars which turn colors on or off, following the `NO_COLOR` and
+/// `CLICOLOR` conventions.
+#[derive(Debug, Default)]
+struct ColorEnv {
+    /// `NO_COLOR` is set to anything but an empty string.
+    no_color: bool,
+    /// `CLICOLOR_FORCE` is set to anything but an empty string or `0`.
+    clicolor_force: bool,
+    /// `CLICOLOR` is set to `0`.
+    clicolor_off: bool,
+}
+
+impl ColorEnv {
+    fn from_env() -> Self {
+        let var = |name| std::env::var_os(name).filter(|value| !value.is_empty());
+        Self {
+            no_color: var("NO_COLOR").is_some(),
+            clicolor_force: var("CLICOLOR_FORCE").is_some_and(|value| value != "0"),
+            clicolor_off: var("CLICOLOR").is_some_and(|value| value == "0"),
+        }
+    }
+}
+
+/// Asks the terminfo database how many colors the terminal supports.
+fn terminal_palette() -> Palette {
+    if let Some(term) = std::env::var("TERM").ok().filter(|term| !term.is_empty()) {
+        match term::terminfo_colors(&term) {
+            Some(colors) => {
+                tracing::debug!(?term, colors, "found terminfo entry");
+                return Palette::from_colors(colors);
+            }
+            None => tracing::debug!(?term, "no terminfo entry with colors"),
+        }
+    }
+
+    // Okay, try to use `tput`, which may know about terminfo databases we
+    // don't...
+    let tput = Command::new("tput").arg("colors").output();
+    tracing::debug!(?tput, "checking `tput colors`");
+    if let Ok(output) = tput {
+        let stdout = String::from_utf8(output.stdout);
+        tracing::debug!(?stdout, "`tput colors` succeeded");
+        return stdout
+            .map_err(|err| tracing::warn!(%err, "`tput colors` stdout was not utf-8 (this shouldn't happen)"))
+            .and_then(|s| {
+                let colors = s.trim().parse::<i32>();
+                tracing::debug!(?colors, "parsed `tput colors`");
+                colors.map_err(|_| tracing::warn!(colors = ?s, "invalid color count from `tput colors`"))
+            })
+            .map(Palette::from_colors)
+            .unwrap_or_default();
+    }
+
+    Palette::NoColors
+}
+
 fn default_target_addr() -> Uri {
     "http://127.0.0.1:6669"
         .parse::<Uri>()
@@ -632,9 +1227,44 @@ fn default_log_directory() -> PathBuf {
     ["/", "tmp", "tokio-console", "logs"].iter().collect()
 }
 
+const DEFAULT_LOG_MAX_FILES: usize = 10;
+const DEFAULT_LEAK_WINDOW: Duration = Duration::from_secs(5 * 60);
+
 // === impl ViewOptions ===
 
 impl ViewOptions {
+    /// Returns these options, with only ASCII characters allowed.
+    #[cfg(test)]
+    pub(crate) fn ascii_only(self) -> Self {
+        Self {
+            ascii_only: Some(true),
+            ..self
+        }
+    }
+
+    /// Returns these options, displaying points in time with `time_format`.
+    #[cfg(test)]
+    pub(crate) fn with_time_format(self, time_format: TimeFormat) -> Self {
+        Self {
+            time_format: Some(time_format),
+            ..self
+        }
+    }
+
+    /// Returns these options, linking spawn locations with `template`.
+    #[cfg(test)]
+    pub(crate) fn with_location_links(self, template: &str) -> Self {
+        Self {
+            location_links: Some(template.to_owned()),
+            ..self
+        }
+    }
+
+    /// Returns the language to display the console in, based on `lang`.
+    pub(crate) fn lang(&self) -> Lang {
+        Lang::from_locale(self.lang.as_deref().unwrap_or_default())
+    }
+
     pub fn is_utf8(&self) -> bool {
         if self.ascii_only.unwrap_or(false) {
             return false;
@@ -646,10 +1276,20 @@ impl ViewOptions {
     ///
     /// The color palette is determined based on the following (in order):
     /// - Any palette explicitly set via the command-line options
+    /// - The `NO_COLOR`, `CLICOLOR_FORCE`, and `CLICOLOR` env vars, in that
+    ///   order.
     /// - The terminal's advertised support for true colors via the `COLORTERM`
     ///   env var.
-    /// - Checking the `terminfo` database via `tput`
+    /// - Checking the `terminfo` database for the `TERM` env var, or via
+    ///   `tput` if there's no entry for it.
     pub(crate) fn determine_palette(&self) -> Palette {
+        self.palette_for(&ColorEnv::from_env(), terminal_palette)
+    }
+
+    /// Determines the color palette to use with the color env vars in
+    /// `env`, calling `terminal_palette` to ask the terminal if they don't
+    /// settle it.
+    fn palette_for(&self, env: &ColorEnv, terminal_palette: impl FnOnce() -> Palette) -> Palette {
         // Did the user explicitly disable colors?
         if self.no_colors {
             tracing::debug!("colors explicitly disabled by `--no-colors`");
@@ -662,36 +1302,65 @@ impl ViewOptions {
             return palette;
         }
 
+        if env.no_color {
+            tracing::debug!("colors disabled by `NO_COLOR`");
+            return Palette::NoColors;
+        }
+
+        if env.clicolor_off && !env.clicolor_force {
+            tracing::debug!("colors disabled by `CLICOLOR=0`");
+            return Palette::NoColors;
+        }
+
         // Does the terminal advertise truecolor support via the COLORTERM env var?
         if self.truecolor.unwrap_or(false) {
             tracing::debug!("millions of colors enabled via `COLORTERM=truecolor`");
             return Palette::All;
         }
 
-        // Okay, try to use `tput` to ask the terminfo database how many colors
-        // are supported...
-        let tput = Command::new("tput").arg("colors").output();
-        tracing::debug!(?tput, "checking `tput colors`");
-        if let Ok(output) = tput {
-            let stdout = String::from_utf8(output.stdout);
-            tracing::debug!(?stdout, "`tput colors` succeeded");
-            return stdout
-                .map_err(|err| tracing::warn!(%err, "`tput colors` stdout was not utf-8 (this shouldn't happen)"))
-                .and_then(|s| {
-                    let palette = s.parse::<Palette>();
-                    tracing::debug!(?palette, "parsed `tput colors`");
-                    palette.map_err(|_| tracing::warn!(palette = ?s, "invalid color palette from `tput colors`"))
-                })
-                .unwrap_or_default();
+        match terminal_palette() {
+            Palette::NoColors if env.clicolor_force => {
+                tracing::debug!("colors forced by `CLICOLOR_FORCE`");
+                Palette::Ansi8
+            }
+            palette => palette,
         }
-
-        Palette::NoColors
     }
 
     pub(crate) fn toggles(&self) -> ColorToggles {
         self.toggles
     }
 
+    /// Returns `true` if the console should redraw as little as possible,
+    /// because it was asked to or appears to be running over SSH.
+    pub(crate) fn is_low_bandwidth(&self) -> bool {
+        self.low_bandwidth
+            .unwrap_or_else(|| std::env::var_os("SSH_CONNECTION").is_some())
+    }
+
+    /// Returns the minimum time between redraws.
+    pub(crate) fn frame_interval(&self) -> Duration {
+        const LOW_BANDWIDTH_FPS: u32 = 2;
+        // Faster than updates arrive, and than most people scroll, but a cap
+        // on how often holding down a key redraws the console.
+        const DEFAULT_FPS: u32 = 30;
+
+        let fps = match self.max_fps {
+            Some(fps) => fps,
+            None if self.is_low_bandwidth() => LOW_BANDWIDTH_FPS,
+            None => DEFAULT_FPS,
+        };
+        Duration::from_secs(1) / fps
+    }
+
+    pub(crate) fn time_format(&self) -> TimeFormat {
+        self.time_format.unwrap_or_default()
+    }
+
+    pub(crate) fn location_links(&self) -> Option<String> {
+        self.location_links.clone()
+    }
+
     fn merge_with(self, command_line: ViewOptions) -> Self {
         Self {
             no_colors: command_line.no_colors || self.no_colors,
@@ -709,6 +1378,10 @@ impl ViewOptions {
                     .color_terminated
                     .or(self.toggles.color_terminated),
             },
+            low_bandwidth: command_line.low_bandwidth.or(self.low_bandwidth),
+            max_fps: command_line.max_fps.or(self.max_fps),
+            time_format: command_line.time_format.or(self.time_format),
+            location_links: command_line.location_links.or(self.location_links),
         }
     }
 }
@@ -725,6 +1398,10 @@ impl Default for ViewOptions {
                 color_durations: Some(true),
                 color_terminated: Some(true),
             },
+            low_bandwidth: None,
+            max_fps: None,
+            time_format: Some(TimeFormat::Relative),
+            location_links: None,
         }
     }
 }
@@ -740,6 +1417,26 @@ fn palette_parser() -> impl TypedValueParser<Value = Palette> {
     })
 }
 
+fn parse_duration(s: &str) -> Result<Duration, String> {
+    humantime::parse_duration(s).map_err(|error| error.to_string())
+}
+
+fn parse_summary_interval(s: &str) -> Result<Duration, String> {
+    let interval = parse_duration(s)?;
+    if interval.is_zero() {
+        return Err("the summary interval must be longer than zero".to_string());
+    }
+    Ok(interval)
+}
+
+fn parse_leak_window(s: &str) -> Result<Duration, String> {
+    let window = parse_duration(s)?;
+    if window.is_zero() {
+        return Err("the leak window must be longer than zero".to_string());
+    }
+    Ok(window)
+}
+
 fn parse_true_color(s: impl AsRef<str>) -> bool {
     let s = s.as_ref().trim();
     s.eq_ignore_ascii_case("truecolor") || s.eq_ignore_ascii_case("24bit")
@@ -805,6 +1502,22 @@ impl ConfigFile {
         Ok(uri)
     }
 
+    fn via(&self) -> color_eyre::Result<Option<Via>> {
+        self.via
+            .as_deref()
+            .map(Via::from_str)
+            .transpose()
+            .map_err(|error| color_eyre::eyre::eyre!("failed to parse `via`: {error}"))
+    }
+
+    fn task_level(&self) -> color_eyre::Result<Option<filter::LevelFilter>> {
+        self.task_level
+            .as_deref()
+            .map(filter::LevelFilter::from_str)
+            .transpose()
+            .wrap_err_with(|| format!("failed to parse task level {:?}", self.task_level))
+    }
+
     fn log_filter(&self) -> color_eyre::Result<Option<LogFilter>> {
         let filter_str = self.log.as_deref();
 
@@ -824,6 +1537,59 @@ impl ConfigFile {
         self.retention
     }
 
+    fn max_fps(&self) -> color_eyre::Result<Option<u32>> {
+        match self.rendering.as_ref().and_then(|config| config.max_fps) {
+            Some(0) => Err(color_eyre::eyre::eyre!(
+                "`max_fps` must be at least 1, as the console can't redraw zero times a second"
+            )),
+            max_fps => Ok(max_fps),
+        }
+    }
+
+    fn presets(&mut self) -> color_eyre::Result<Vec<Preset>> {
+        let presets = self.presets.take().unwrap_or_default();
+        let mut keys = BTreeSet::new();
+        for preset in &presets {
+            if !('1'..='9').contains(&preset.key) {
+                return Err(color_eyre::eyre::eyre!(
+                    "the key of preset {:?} must be a digit from 1 to 9, not {:?}",
+                    preset.name,
+                    preset.key
+                ));
+            }
+            if !keys.insert(preset.key) {
+                return Err(color_eyre::eyre::eyre!(
+                    "more than one preset is bound to the key {:?}",
+                    preset.key
+                ));
+            }
+            if let Some(ref filter) = preset.filter {
+                Filter::parse(filter).wrap_err_with(|| {
+                    format!("failed to parse the filter of preset {:?}", preset.name)
+                })?;
+            }
+        }
+        Ok(presets)
+    }
+
+    fn quotas(&mut self) -> color_eyre::Result<Vec<Quota>> {
+        let quotas = self.quotas.take().unwrap_or_default();
+        for quota in &quotas {
+            if quota.target.is_some() == quota.location.is_some() {
+                return Err(color_eyre::eyre::eyre!(
+                    "a quota must have either a target or a location, not {} (in {:?})",
+                    if quota.target.is_some() {
+                        "both"
+                    } else {
+                        "neither"
+                    },
+                    quota
+                ));
+            }
+        }
+        Ok(quotas)
+    }
+
     fn no_colors(&self) -> Option<bool> {
         self.colors
             .as_ref()
@@ -847,11 +1613,16 @@ impl From<Config> for ConfigFile {
     fn from(config: Config) -> Self {
         Self {
             default_target_addr: config.target_addr.map(|addr| addr.to_string()),
+            via: config.via.map(|via| via.to_string()),
             log: config.log_filter.map(|filter| filter.to_string()),
             log_directory: config.log_directory,
+            log_max_files: config.log_max_files,
             warnings: config.warnings,
             allow_warnings: config.allow_warnings,
             retention: config.retain_for,
+            max_completed_tasks: config.max_completed_tasks,
+            task_level: config.task_level.map(|level| level.to_string()),
+            session: Some(!config.no_session),
             charset: Some(CharsetConfig {
                 lang: config.view_options.lang,
                 ascii_only: config.view_options.ascii_only,
@@ -862,6 +1633,20 @@ impl From<Config> for ConfigFile {
                 palette: config.view_options.palette,
                 enable: Some(config.view_options.toggles),
             }),
+            // whether to use low-bandwidth mode is detected by default, so
+            // only write those settings if they were configured explicitly.
+            rendering: (config.view_options.low_bandwidth.is_some()
+                || config.view_options.max_fps.is_some()
+                || config.view_options.time_format.is_some()
+                || config.view_options.location_links.is_some())
+            .then_some(RenderingConfig {
+                low_bandwidth: config.view_options.low_bandwidth,
+                max_fps: config.view_options.max_fps,
+                time_format: config.view_options.time_format,
+                location_links: config.view_options.location_links,
+            }),
+            presets: (!config.presets.is_empty()).then_some(config.presets),
+            quotas: (!config.quotas.is_empty()).then_some(config.quotas),
         }
     }
 }
@@ -872,11 +1657,16 @@ impl TryFrom<ConfigFile> for Config {
     fn try_from(mut value: ConfigFile) -> Result<Self, Self::Error> {
         Ok(Config {
             target_addr: value.target_addr()?,
+            via: value.via()?,
             log_filter: value.log_filter()?,
             warnings: value.warnings.clone(),
             allow_warnings: value.allow_warnings.clone(),
             log_directory: value.log_directory.take(),
+            log_max_files: value.log_max_files,
             retain_for: value.retain_for(),
+            max_completed_tasks: value.max_completed_tasks,
+            leak_window: None,
+            task_level: value.task_level()?,
             view_options: ViewOptions {
                 no_colors: value.no_colors().unwrap_or(false),
                 lang: value
@@ -890,7 +1680,31 @@ impl TryFrom<ConfigFile> for Config {
                     color_durations: value.color_durations(),
                     color_terminated: value.color_terminated(),
                 },
+                low_bandwidth: value
+                    .rendering
+                    .as_ref()
+                    .and_then(|config| config.low_bandwidth),
+                max_fps: value.max_fps()?,
+                time_format: value
+                    .rendering
+                    .as_ref()
+                    .and_then(|config| config.time_format),
+                location_links: value
+                    .rendering
+                    .as_ref()
+                    .and_then(|config| config.location_links.clone()),
             },
+            task: None,
+            no_session: value.session.map(Not::not).unwrap_or(false),
+            once: None,
+            plain: false,
+            summary_interval: None,
+            title: false,
+            duration: None,
+            keys: None,
+            trace_traffic: false,
+            presets: value.presets()?,
+            quotas: value.quotas()?,
             subcmd: None,
         })
     }
@@ -954,6 +1768,229 @@ mod tests {
         Config::command().debug_assert()
     }
 
+    #[test]
+    fn parses_summary_interval() {
+        use clap::Parser;
+        let config = Config::try_parse_from(["tokio-console", "--no-tui", "--summary-interval=1m"])
+            .expect("valid arguments");
+        assert!(config.plain);
+        assert_eq!(config.summary_interval, Some(Duration::from_secs(60)));
+
+        Config::try_parse_from(["tokio-console", "--plain", "--summary-interval=0s"])
+            .expect_err("a zero interval is rejected");
+        Config::try_parse_from(["tokio-console", "--summary-interval=1m"])
+            .expect_err("the interval requires --plain");
+    }
+
+    #[test]
+    fn parses_leak_window() {
+        use clap::Parser;
+        let config =
+            Config::try_parse_from(["tokio-console", "--leak-window=1h"]).expect("valid arguments");
+        assert_eq!(config.leak_window(), Duration::from_secs(60 * 60));
+        assert_eq!(Config::default().leak_window(), DEFAULT_LEAK_WINDOW);
+
+        Config::try_parse_from(["tokio-console", "--leak-window=0s"])
+            .expect_err("a zero window is rejected");
+    }
+
+    #[test]
+    fn parses_scripted_runs() {
+        use clap::Parser;
+        let config = Config::try_parse_from(["tokio-console", "--duration=1m", "--keys=t,Enter"])
+            .expect("valid arguments");
+        assert_eq!(config.duration, Some(Duration::from_secs(60)));
+        assert_eq!(config.keys.map(|keys| keys.into_events().count()), Some(2));
+
+        Config::try_parse_from(["tokio-console", "--plain", "--keys=t"])
+            .expect_err("keys can't be pressed with --plain");
+        Config::try_parse_from(["tokio-console", "--once", "--duration=1m"])
+            .expect_err("--once exits after the first update");
+    }
+
+    #[test]
+    fn parses_presets() {
+        let parse = |toml: &str| {
+            Config::try_from(toml::from_str::<ConfigFile>(toml).expect("invalid config file"))
+        };
+        let config = parse(
+            r#"
+            warnings = []
+
+            [[presets]]
+            name = "hot"
+            key = "1"
+            filter = "busy > 100ms"
+            sort = "Busy"
+            "#,
+        )
+        .unwrap();
+        assert_eq!(config.presets().len(), 1);
+        assert_eq!(config.presets()[0].key, '1');
+
+        let error = parse(
+            r#"
+            warnings = []
+
+            [[presets]]
+            name = "hot"
+            key = "t"
+            "#,
+        )
+        .unwrap_err();
+        assert!(error.to_string().contains("digit"), "{error}");
+
+        let error = parse(
+            r#"
+            warnings = []
+
+            [[presets]]
+            name = "hot"
+            key = "1"
+            filter = "busy > 100"
+            "#,
+        )
+        .unwrap_err();
+        assert!(error.to_string().contains("preset \"hot\""), "{error}");
+    }
+
+    #[test]
+    fn rejects_zero_max_fps() {
+        let parse = |toml: &str| {
+            Config::try_from(toml::from_str::<ConfigFile>(toml).expect("invalid config file"))
+        };
+        let config = parse(
+            r#"
+            warnings = []
+
+            [rendering]
+            max_fps = 10
+            "#,
+        )
+        .unwrap();
+        assert_eq!(config.view_options.max_fps, Some(10));
+
+        let error = parse(
+            r#"
+            warnings = []
+
+            [rendering]
+            max_fps = 0
+            "#,
+        )
+        .unwrap_err();
+        assert!(error.to_string().contains("`max_fps`"), "{error}");
+    }
+
+    #[test]
+    fn parses_quotas() {
+        let parse = |toml: &str| {
+            Config::try_from(toml::from_str::<ConfigFile>(toml).expect("invalid config file"))
+        };
+        let config = parse(
+            r#"
+            warnings = []
+
+            [[quotas]]
+            target = "app::conn"
+            max_tasks = 100
+            "#,
+        )
+        .unwrap();
+        assert_eq!(config.quotas().len(), 1);
+        assert_eq!(config.quotas()[0].target.as_deref(), Some("app::conn"));
+        assert_eq!(config.quotas()[0].max_tasks, 100);
+
+        let error = parse(
+            r#"
+            warnings = []
+
+            [[quotas]]
+            target = "app::conn"
+            location = "src/main.rs:10:5"
+            max_tasks = 100
+            "#,
+        )
+        .unwrap_err();
+        assert!(error.to_string().contains("not both"), "{error}");
+    }
+
+    #[test]
+    fn color_env_vars_precedence() {
+        let options = ViewOptions {
+            truecolor: Some(false),
+            palette: None,
+            ..ViewOptions::default()
+        };
+        let palette = |options: &ViewOptions, env: ColorEnv, terminal: Palette| {
+            options.palette_for(&env, || terminal)
+        };
+
+        assert_eq!(
+            palette(&options, ColorEnv::default(), Palette::Ansi256),
+            Palette::Ansi256
+        );
+        // `NO_COLOR` wins over forcing colors...
+        let no_color = || ColorEnv {
+            no_color: true,
+            clicolor_force: true,
+            ..ColorEnv::default()
+        };
+        assert_eq!(
+            palette(&options, no_color(), Palette::Ansi256),
+            Palette::NoColors
+        );
+        // ...and over `COLORTERM`, but not over the command line.
+        let truecolor = ViewOptions {
+            truecolor: Some(true),
+            ..options.clone()
+        };
+        assert_eq!(
+            palette(&truecolor, no_color(), Palette::Ansi256),
+            Palette::NoColors
+        );
+        let explicit = ViewOptions {
+            palette: Some(Palette::Ansi16),
+            ..options.clone()
+        };
+        assert_eq!(
+            palette(&explicit, no_color(), Palette::Ansi256),
+            Palette::Ansi16
+        );
+
+        let clicolor_off = ColorEnv {
+            clicolor_off: true,
+            ..ColorEnv::default()
+        };
+        assert_eq!(
+            palette(&options, clicolor_off, Palette::Ansi256),
+            Palette::NoColors
+        );
+        // `CLICOLOR_FORCE` wins over `CLICOLOR=0`, and turns on colors the
+        // terminal doesn't advertise.
+        let forced = || ColorEnv {
+            clicolor_force: true,
+            clicolor_off: true,
+            ..ColorEnv::default()
+        };
+        assert_eq!(
+            palette(&options, forced(), Palette::Ansi256),
+            Palette::Ansi256
+        );
+        assert_eq!(
+            palette(&options, forced(), Palette::NoColors),
+            Palette::Ansi8
+        );
+        let no_colors = ViewOptions {
+            no_colors: true,
+            ..options.clone()
+        };
+        assert_eq!(
+            palette(&no_colors, forced(), Palette::Ansi256),
+            Palette::NoColors
+        );
+    }
+
     #[test]
     // The example output includes paths, so skip this test on windows. :/
     #[cfg_attr(windows, ignore)]
@@ -1,24 +1,50 @@
+use crate::forward::Via;
 use console_api::instrument::StateRequest;
 use console_api::instrument::{
     instrument_client::InstrumentClient, InstrumentRequest, PauseRequest, ResumeRequest,
-    State as InstrumentState, TaskDetailsRequest, Update,
+    ResumeToken, State as InstrumentState, TaskDetailsRequest, TaskGroupsRequest, Update,
 };
-use console_api::tasks::TaskDetails;
+use console_api::tasks::{TaskDetails, TaskGroups};
 use futures::stream::StreamExt;
 use futures::TryFutureExt;
 use hyper_util::rt::TokioIo;
-use std::{error::Error, time::Duration};
+use prost::bytes::Buf;
+use std::{
+    error::Error,
+    marker::PhantomData,
+    time::{Duration, Instant},
+};
 #[cfg(unix)]
 use tokio::net::UnixStream;
 use tonic::{
+    client::Grpc,
+    codec::{Codec, DecodeBuf, Decoder, ProstCodec},
+    codegen::http::uri::PathAndQuery,
     transport::{Channel, Endpoint, Uri},
-    Streaming,
+    Status, Streaming,
 };
 
+/// The target of the logs describing each message received from the remote,
+/// which are enabled by `--trace-traffic`.
+pub(crate) const TRAFFIC_TARGET: &str = "tokio_console::traffic";
+
+/// The port connections are forwarded to when the target doesn't have one,
+/// which is the port `console-subscriber` listens on by default.
+const DEFAULT_PORT: u16 = 6669;
+
 #[derive(Debug)]
 pub struct Connection {
     target: Uri,
     state: State,
+    /// How long the remote is asked to retain data for completed tasks,
+    /// resources and async ops while the console is attached, if at all.
+    retention: Option<prost_types::Duration>,
+    /// The helper which forwards connections to the target, if it's in a
+    /// container.
+    via: Option<Via>,
+    /// The token of the last update received, which is sent when
+    /// reconnecting so that the remote only sends the updates since then.
+    resume_token: Option<ResumeToken>,
 }
 
 // clippy doesn't like that the "connected" case is much larger than the
@@ -31,8 +57,8 @@ pub struct Connection {
 enum State {
     Connected {
         client: InstrumentClient<Channel>,
-        update_stream: Box<Streaming<Update>>,
-        state_stream: Box<Streaming<InstrumentState>>,
+        update_stream: Box<Streaming<Received<Update>>>,
+        state_stream: Box<Streaming<Received<InstrumentState>>>,
     },
     Disconnected(Duration),
 }
@@ -43,6 +69,22 @@ pub(crate) enum Message {
     State(InstrumentState),
 }
 
+/// A message received from the remote, along with how large it was on the
+/// wire and how long it took to decode.
+#[derive(Debug)]
+struct Received<T> {
+    message: T,
+    size: usize,
+    decode_time: Duration,
+}
+
+/// A `ProstCodec` which measures each message it decodes.
+struct TrafficCodec<T, U> {
+    inner: ProstCodec<T, U>,
+}
+
+struct TrafficDecoder<U>(PhantomData<fn() -> U>);
+
 macro_rules! with_client {
     ($me:ident, $client:ident, $block:expr) => ({
         loop {
@@ -79,9 +121,35 @@ impl Connection {
         Self {
             target,
             state: State::Disconnected(Duration::from_secs(0)),
+            retention: None,
+            via: None,
+            resume_token: None,
         }
     }
 
+    /// Asks the remote to retain completed data for as long as the console
+    /// shows it, so that it's still there if the console reconnects.
+    ///
+    /// If the console retains completed data forever, the remote is asked for
+    /// the longest retention it allows.
+    pub fn with_retain_for(self, retain_for: Option<Duration>) -> Self {
+        let retention = retain_for
+            .and_then(|retain_for| retain_for.try_into().ok())
+            .unwrap_or(prost_types::Duration {
+                seconds: i64::MAX,
+                nanos: 0,
+            });
+        Self {
+            retention: Some(retention),
+            ..self
+        }
+    }
+
+    /// Connects to the target through `via`, if set, rather than directly.
+    pub fn with_via(self, via: Option<Via>) -> Self {
+        Self { via, ..self }
+    }
+
     async fn connect(&mut self) {
         const MAX_BACKOFF: Duration = Duration::from_secs(5);
 
@@ -93,9 +161,19 @@ impl Connection {
                 tokio::time::sleep(backoff).await;
             }
             let try_connect = async {
-                let channel = match self.target.scheme_str() {
+                let channel = match (self.via.clone(), self.target.scheme_str()) {
+                    (Some(via), _) => {
+                        let port = self.target.port_u16().unwrap_or(DEFAULT_PORT);
+                        // Dummy endpoint is ignored by the connector.
+                        let endpoint = Endpoint::from_static("http://localhost");
+                        endpoint
+                            .connect_with_connector(tower::service_fn(move |_| {
+                                via.clone().connect(port).map_ok(TokioIo::new)
+                            }))
+                            .await?
+                    }
                     #[cfg(unix)]
-                    Some("file") => {
+                    (None, Some("file")) => {
                         if !matches!(self.target.host(), None | Some("localhost")) {
                             return Err("cannot connect to non-localhost unix domain socket".into());
                         }
@@ -109,20 +187,29 @@ impl Connection {
                             .await?
                     }
                     #[cfg(not(unix))]
-                    Some("file") => {
+                    (None, Some("file")) => {
                         return Err("unix domain sockets are not supported on this platform".into());
                     }
-                    _ => {
+                    (None, _) => {
                         let endpoint = Endpoint::from(self.target.clone());
                         endpoint.connect().await?
                     }
                 };
-                let mut client = InstrumentClient::new(channel);
-                let update_request = tonic::Request::new(InstrumentRequest {});
-                let update_stream =
-                    Box::new(client.watch_updates(update_request).await?.into_inner());
+                let client = InstrumentClient::new(channel.clone());
+                // The streams are watched with a codec which measures each
+                // message, so they don't use the generated client.
+                let mut grpc = Grpc::new(channel);
+                let update_request = tonic::Request::new(InstrumentRequest {
+                    retention: self.retention,
+                    resume_token: self.resume_token,
+                });
+                let update_stream = Box::new(
+                    watch(&mut grpc, update_request, "WatchUpdates")
+                        .await?
+                        .into_inner(),
+                );
                 let state_request = tonic::Request::new(StateRequest {});
-                let state_stream = match client.watch_state(state_request).await {
+                let state_stream = match watch(&mut grpc, state_request, "WatchState").await {
                     Ok(stream) => Box::new(stream.into_inner()),
                     Err(e) => {
                         if e.code() == tonic::Code::Unimplemented {
@@ -164,7 +251,13 @@ impl Connection {
                 } => {
                     tokio::select! { biased; // Always biased to update stream.
                         update = update_stream.next() => match update {
-                            Some(Ok(update)) => return Message::Update(update),
+                            Some(Ok(update)) => {
+                                trace_update(&update);
+                                // summaries don't have a token, since a
+                                // degraded stream can't be resumed.
+                                self.resume_token = update.message.resume_token;
+                                return Message::Update(update.message);
+                            }
                             Some(Err(status)) => {
                                 tracing::warn!(%status, "error from update stream");
                                 self.state = State::Disconnected(Self::BACKOFF);
@@ -175,7 +268,16 @@ impl Connection {
                             }
                         },
                         state = state_stream.next() => match state {
-                            Some(Ok(state)) => return Message::State(state),
+                            Some(Ok(state)) => {
+                                tracing::debug!(
+                                    target: TRAFFIC_TARGET,
+                                    size = state.size,
+                                    decode_time = ?state.decode_time,
+                                    temporality = ?state.message.temporality(),
+                                    "received state",
+                                );
+                                return Message::State(state.message);
+                            }
                             Some(Err(status)) => {
                                 tracing::warn!(%status, "error from state stream");
                                 self.state = State::Disconnected(Self::BACKOFF);
@@ -200,16 +302,28 @@ impl Connection {
         with_client!(self, client, {
             let request = tonic::Request::new(TaskDetailsRequest {
                 id: Some(task_id.into()),
+                ..Default::default()
             });
             client.watch_task_details(request).await
         })
         .map(|watch| watch.into_inner())
     }
 
+    #[tracing::instrument(skip(self))]
+    pub async fn watch_task_groups(&mut self) -> Result<Streaming<TaskGroups>, tonic::Status> {
+        with_client!(self, client, {
+            let request = tonic::Request::new(TaskGroupsRequest {});
+            client.watch_task_groups(request).await
+        })
+        .map(|watch| watch.into_inner())
+    }
+
     #[tracing::instrument(skip(self))]
     pub async fn pause(&mut self) {
         let res = with_client!(self, client, {
-            let request = tonic::Request::new(PauseRequest {});
+            // freeze the aggregator's clock while paused, so that the
+            // durations we display don't keep accruing while paused.
+            let request = tonic::Request::new(PauseRequest { freeze_time: true });
             client.pause(request).await
         });
 
@@ -230,6 +344,10 @@ impl Connection {
         }
     }
 
+    pub fn target(&self) -> &Uri {
+        &self.target
+    }
+
     pub fn render(&self, styles: &crate::view::Styles) -> ratatui::text::Line {
         use ratatui::{
             style::{Color, Modifier},
@@ -257,3 +375,236 @@ impl Connection {
         ])
     }
 }
+
+/// Starts watching one of the `Instrument` service's streams, decoding its
+/// messages with a [`TrafficCodec`].
+async fn watch<T, U>(
+    grpc: &mut Grpc<Channel>,
+    request: tonic::Request<T>,
+    method: &'static str,
+) -> Result<tonic::Response<Streaming<Received<U>>>, Status>
+where
+    T: prost::Message + Send + Sync + 'static,
+    U: prost::Message + Default + Send + Sync + 'static,
+{
+    grpc.ready()
+        .await
+        .map_err(|error| Status::unknown(format!("Service was not ready: {error}")))?;
+    let path = format!("/rs.tokio.console.instrument.Instrument/{method}");
+    let path = PathAndQuery::try_from(path).expect("method names are valid paths");
+    let codec = TrafficCodec {
+        inner: ProstCodec::default(),
+    };
+    grpc.server_streaming(request, path, codec).await
+}
+
+/// Logs the size of an update, and how many of each kind of entity it
+/// includes.
+fn trace_update(update: &Received<Update>) {
+    let Received {
+        message,
+        size,
+        decode_time,
+    } = update;
+    let tasks = message.task_update.as_ref();
+    let resources = message.resource_update.as_ref();
+    let async_ops = message.async_op_update.as_ref();
+    tracing::debug!(
+        target: TRAFFIC_TARGET,
+        size,
+        ?decode_time,
+        new_metadata = message
+            .new_metadata
+            .as_ref()
+            .map_or(0, |meta| meta.metadata.len()),
+        new_tasks = tasks.map_or(0, |update| update.new_tasks.len()),
+        task_stats = tasks.map_or(0, |update| update.stats_update.len()),
+        new_resources = resources.map_or(0, |update| update.new_resources.len()),
+        resource_stats = resources.map_or(0, |update| update.stats_update.len()),
+        new_async_ops = async_ops.map_or(0, |update| update.new_async_ops.len()),
+        async_op_stats = async_ops.map_or(0, |update| update.stats_update.len()),
+        workers = message
+            .runtime_update
+            .as_ref()
+            .map_or(0, |update| update.workers.len()),
+        dropped_events = tasks.map_or(0, |update| update.dropped_events)
+            + resources.map_or(0, |update| update.dropped_events)
+            + async_ops.map_or(0, |update| update.dropped_events),
+        "received update",
+    );
+}
+
+// === impl TrafficCodec ===
+
+impl<T, U> Codec for TrafficCodec<T, U>
+where
+    T: prost::Message + Send + 'static,
+    U: prost::Message + Default + Send + 'static,
+{
+    type Encode = T;
+    type Decode = Received<U>;
+    type Encoder = <ProstCodec<T, U> as Codec>::Encoder;
+    type Decoder = TrafficDecoder<U>;
+
+    fn encoder(&mut self) -> Self::Encoder {
+        self.inner.encoder()
+    }
+
+    fn decoder(&mut self) -> Self::Decoder {
+        TrafficDecoder(PhantomData)
+    }
+}
+
+impl<U: prost::Message + Default> Decoder for TrafficDecoder<U> {
+    type Item = Received<U>;
+    type Error = Status;
+
+    fn decode(&mut self, buf: &mut DecodeBuf<'_>) -> Result<Option<Self::Item>, Self::Error> {
+        let size = buf.remaining();
+        let start = Instant::now();
+        let message = U::decode(buf).map_err(|error| Status::internal(error.to_string()))?;
+        Ok(Some(Received {
+            message,
+            size,
+            decode_time: start.elapsed(),
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        config::ViewOptions,
+        state::recordings,
+        view::{Styles, ViewState},
+    };
+    use console_api::instrument::PauseRequest;
+    use console_test_support::{MockServer, Request, Script};
+
+    /// Returns the next update the connection receives, failing the test if
+    /// it doesn't receive one soon.
+    async fn next_update(conn: &mut Connection) -> Update {
+        let message = tokio::time::timeout(Duration::from_secs(10), conn.next_message())
+            .await
+            .expect("no update was received");
+        match message {
+            Message::Update(update) => update,
+            Message::State(state) => panic!("expected an update, got {state:?}"),
+        }
+    }
+
+    #[tokio::test]
+    async fn replays_updates_into_state() {
+        let server = MockServer::new()
+            .connection(Script::new().updates(recordings::load("tasks")))
+            .spawn()
+            .await
+            .unwrap();
+        let mut conn = Connection::new(server.uri()).with_retain_for(Some(Duration::from_secs(6)));
+
+        let styles = Styles::from_config(ViewOptions::default());
+        let mut state = recordings::state();
+        for _ in 0..2 {
+            let update = next_update(&mut conn).await;
+            state.update(&styles, &ViewState::TasksList, update);
+        }
+        let spinner = state.tasks_state().find("spinner").unwrap();
+        assert_eq!(spinner.borrow().total_polls(), 20);
+
+        assert_eq!(
+            server.requests(),
+            [
+                Request::WatchUpdates(InstrumentRequest {
+                    retention: Some(prost_types::Duration {
+                        seconds: 6,
+                        nanos: 0
+                    }),
+                    resume_token: None,
+                }),
+                Request::WatchState,
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn reconnects_when_the_stream_fails() {
+        let update = |id| Update {
+            task_update: Some(console_api::tasks::TaskUpdate {
+                dropped_events: id,
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        let server = MockServer::new()
+            .connection(
+                Script::new()
+                    .update(update(1))
+                    .fail(Status::unavailable("going away")),
+            )
+            .connection(Script::new().update(update(2)))
+            .spawn()
+            .await
+            .unwrap();
+        let mut conn = Connection::new(server.uri());
+
+        assert_eq!(next_update(&mut conn).await, update(1));
+        assert_eq!(next_update(&mut conn).await, update(2));
+        assert_eq!(server.connections(), 2);
+    }
+
+    #[tokio::test]
+    async fn resumes_from_the_last_update() {
+        let token = ResumeToken {
+            epoch: 1,
+            sequence: 5,
+        };
+        let server = MockServer::new()
+            .connection(
+                Script::new()
+                    .update(Update {
+                        resume_token: Some(token),
+                        ..Default::default()
+                    })
+                    .fail(Status::unavailable("going away")),
+            )
+            .connection(Script::new().update(Update::default()))
+            .spawn()
+            .await
+            .unwrap();
+        let mut conn = Connection::new(server.uri());
+
+        next_update(&mut conn).await;
+        next_update(&mut conn).await;
+        let watches: Vec<_> = server
+            .requests()
+            .into_iter()
+            .filter_map(|request| match request {
+                Request::WatchUpdates(request) => Some(request.resume_token),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(watches, [None, Some(token)]);
+    }
+
+    #[tokio::test]
+    async fn commands_reach_the_remote() {
+        let server = MockServer::new()
+            .connection(Script::new())
+            .spawn()
+            .await
+            .unwrap();
+        let mut conn = Connection::new(server.uri());
+
+        conn.pause().await;
+        conn.resume().await;
+        assert_eq!(
+            server.requests()[2..],
+            [
+                Request::Pause(PauseRequest { freeze_time: true }),
+                Request::Resume,
+            ]
+        );
+        assert!(conn.watch_task_groups().await.is_err());
+    }
+}
@@ -0,0 +1,343 @@
+//! Connections to processes in containers, for `--via`.
+//!
+//! A console-enabled process in a container usually only listens on the
+//! container's loopback interface, which can't be reached from the host
+//! without publishing the port. Instead, each connection starts a helper
+//! process which forwards it into the container: `kubectl port-forward` for
+//! pods, and `docker exec` running `nc` for Docker containers. The helper is
+//! killed when the connection is dropped, so nothing is left running once
+//! the console exits.
+use std::{
+    fmt, io,
+    net::Ipv4Addr,
+    pin::Pin,
+    process::Stdio,
+    str::FromStr,
+    task::{Context, Poll},
+};
+use tokio::{
+    io::{AsyncBufReadExt, AsyncRead, AsyncReadExt, AsyncWrite, BufReader, Lines, ReadBuf},
+    net::TcpStream,
+    process::{Child, ChildStderr, ChildStdin, ChildStdout, Command},
+};
+
+/// How to reach the process the console connects to.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) enum Via {
+    /// Through `docker exec`, in the named container.
+    Docker { container: String },
+    /// Through `kubectl port-forward`, to the named pod.
+    Kube {
+        namespace: Option<String>,
+        pod: String,
+    },
+}
+
+/// A connection forwarded by a helper process.
+#[derive(Debug)]
+pub(crate) struct Forwarded {
+    /// The helper process, which is killed when this is dropped.
+    _helper: Child,
+    io: Io,
+}
+
+#[derive(Debug)]
+enum Io {
+    /// The helper relays the connection over its stdio.
+    Stdio {
+        stdout: ChildStdout,
+        stdin: ChildStdin,
+    },
+    /// The helper listens on a local port.
+    Tcp(TcpStream),
+}
+
+// === impl Via ===
+
+impl Via {
+    /// Starts a helper process, and connects to `port` in the container
+    /// through it.
+    pub(crate) async fn connect(self, port: u16) -> io::Result<Forwarded> {
+        let mut helper = self
+            .command(port)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .kill_on_drop(true)
+            .spawn()
+            .map_err(|error| {
+                io::Error::new(
+                    error.kind(),
+                    format!("failed to run {}: {error}", self.program()),
+                )
+            })?;
+        let stdout = helper.stdout.take().expect("stdout is piped");
+        let io = match self {
+            Via::Docker { .. } => Io::Stdio {
+                stdout,
+                stdin: helper.stdin.take().expect("stdin is piped"),
+            },
+            Via::Kube { .. } => {
+                let mut stdout = BufReader::new(stdout).lines();
+                let Some(local_port) = forwarded_port(&mut stdout).await? else {
+                    return Err(helper_failed(&mut helper, "kubectl port-forward").await);
+                };
+                // kubectl logs each connection it handles, and exits if it
+                // can't, so its output has to be read for as long as it runs.
+                tokio::spawn(async move { while let Ok(Some(_)) = stdout.next_line().await {} });
+                Io::Tcp(TcpStream::connect((Ipv4Addr::LOCALHOST, local_port)).await?)
+            }
+        };
+        // Once the connection is forwarded, anything the helper prints is
+        // about it failing, such as `nc` not being installed.
+        let stderr = helper.stderr.take().expect("stderr is piped");
+        tokio::spawn(log_errors(self.program(), stderr));
+        Ok(Forwarded {
+            _helper: helper,
+            io,
+        })
+    }
+
+    /// Returns the command which forwards a connection to `port` in the
+    /// container.
+    fn command(&self, port: u16) -> Command {
+        let mut command = Command::new(self.program());
+        match self {
+            Via::Docker { container } => {
+                command.args(["exec", "-i", container, "nc", "127.0.0.1"]);
+                command.arg(port.to_string());
+            }
+            Via::Kube { namespace, pod } => {
+                command.arg("port-forward");
+                if let Some(namespace) = namespace {
+                    command.args(["--namespace", namespace]);
+                }
+                // An empty local port asks kubectl to pick an unused one.
+                command.args(["--address", "127.0.0.1", pod]);
+                command.arg(format!(":{port}"));
+            }
+        }
+        command
+    }
+
+    fn program(&self) -> &'static str {
+        match self {
+            Via::Docker { .. } => "docker",
+            Via::Kube { .. } => "kubectl",
+        }
+    }
+}
+
+impl FromStr for Via {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let Some((kind, name)) = s.split_once(':') else {
+            return Err(format!(
+                "expected `docker:<container>` or `kube:<pod>`, not {s:?}"
+            ));
+        };
+        match kind {
+            "docker" => Ok(Via::Docker {
+                container: parse_name(s, name, "container")?,
+            }),
+            "kube" => Ok(match name.split_once('/') {
+                Some((namespace, pod)) => Via::Kube {
+                    namespace: Some(parse_name(s, namespace, "namespace")?),
+                    pod: parse_name(s, pod, "pod")?,
+                },
+                None => Via::Kube {
+                    namespace: None,
+                    pod: parse_name(s, name, "pod")?,
+                },
+            }),
+            _ => Err(format!(
+                "unknown connection helper {kind:?}, expected `docker` or `kube`"
+            )),
+        }
+    }
+}
+
+/// Checks the name of a container, pod, or namespace in `via`, which is passed
+/// to the helper as an argument.
+///
+/// A name which starts with `-` would be taken as an option by the helper.
+fn parse_name(via: &str, name: &str, what: &str) -> Result<String, String> {
+    if name.is_empty() {
+        return Err(format!("{via:?} doesn't name a {what}"));
+    }
+    if name.starts_with('-') {
+        return Err(format!("the {what} in {via:?} can't start with `-`"));
+    }
+    Ok(name.to_owned())
+}
+
+impl fmt::Display for Via {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Via::Docker { container } => write!(f, "docker:{container}"),
+            Via::Kube {
+                namespace: Some(namespace),
+                pod,
+            } => write!(f, "kube:{namespace}/{pod}"),
+            Via::Kube {
+                namespace: None,
+                pod,
+            } => write!(f, "kube:{pod}"),
+        }
+    }
+}
+
+/// Reads the output of `kubectl port-forward` until it says which local port
+/// it's forwarding, or exits.
+async fn forwarded_port(stdout: &mut Lines<BufReader<ChildStdout>>) -> io::Result<Option<u16>> {
+    while let Some(line) = stdout.next_line().await? {
+        if let Some(port) = parse_forwarded_port(&line) {
+            return Ok(Some(port));
+        }
+    }
+    Ok(None)
+}
+
+/// Parses a line like `Forwarding from 127.0.0.1:41213 -> 6669`.
+fn parse_forwarded_port(line: &str) -> Option<u16> {
+    let (local, _) = line
+        .strip_prefix("Forwarding from 127.0.0.1:")?
+        .split_once(' ')?;
+    local.parse().ok()
+}
+
+/// Logs each line `program` prints to `stderr`.
+async fn log_errors(program: &'static str, stderr: ChildStderr) {
+    let mut lines = BufReader::new(stderr).lines();
+    while let Ok(Some(line)) = lines.next_line().await {
+        tracing::warn!(helper = program, "{line}");
+    }
+}
+
+/// Returns an error with what `helper` printed before it exited.
+async fn helper_failed(helper: &mut Child, what: &str) -> io::Error {
+    let mut stderr = String::new();
+    if let Some(mut pipe) = helper.stderr.take() {
+        let _ = pipe.read_to_string(&mut stderr).await;
+    }
+    let status = helper.wait().await;
+    let message = match stderr.trim() {
+        "" => format!("{what} exited without forwarding a port ({status:?})"),
+        stderr => format!("{what} failed: {stderr}"),
+    };
+    io::Error::new(io::ErrorKind::ConnectionRefused, message)
+}
+
+// === impl Forwarded ===
+
+impl AsyncRead for Forwarded {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        match &mut self.io {
+            Io::Stdio { stdout, .. } => Pin::new(stdout).poll_read(cx, buf),
+            Io::Tcp(stream) => Pin::new(stream).poll_read(cx, buf),
+        }
+    }
+}
+
+impl AsyncWrite for Forwarded {
+    fn poll_write(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        match &mut self.io {
+            Io::Stdio { stdin, .. } => Pin::new(stdin).poll_write(cx, buf),
+            Io::Tcp(stream) => Pin::new(stream).poll_write(cx, buf),
+        }
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        match &mut self.io {
+            Io::Stdio { stdin, .. } => Pin::new(stdin).poll_flush(cx),
+            Io::Tcp(stream) => Pin::new(stream).poll_flush(cx),
+        }
+    }
+
+    fn poll_shutdown(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        match &mut self.io {
+            Io::Stdio { stdin, .. } => Pin::new(stdin).poll_shutdown(cx),
+            Io::Tcp(stream) => Pin::new(stream).poll_shutdown(cx),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(via: &str) -> Vec<String> {
+        let command = via.parse::<Via>().unwrap().command(6669);
+        let command = command.as_std();
+        std::iter::once(command.get_program())
+            .chain(command.get_args())
+            .map(|arg| arg.to_string_lossy().into_owned())
+            .collect()
+    }
+
+    #[test]
+    fn parses_helpers() {
+        assert_eq!(
+            args("docker:app"),
+            ["docker", "exec", "-i", "app", "nc", "127.0.0.1", "6669"]
+        );
+        assert_eq!(
+            args("kube:api-0"),
+            [
+                "kubectl",
+                "port-forward",
+                "--address",
+                "127.0.0.1",
+                "api-0",
+                ":6669"
+            ]
+        );
+        assert_eq!(
+            args("kube:prod/api-0"),
+            [
+                "kubectl",
+                "port-forward",
+                "--namespace",
+                "prod",
+                "--address",
+                "127.0.0.1",
+                "api-0",
+                ":6669"
+            ]
+        );
+        assert_eq!(
+            "kube:prod/api-0".parse::<Via>().unwrap().to_string(),
+            "kube:prod/api-0"
+        );
+        assert!("ssh:host".parse::<Via>().is_err());
+        assert!("docker:".parse::<Via>().is_err());
+        assert!("docker:--privileged".parse::<Via>().is_err());
+        assert!("kube:-n".parse::<Via>().is_err());
+        assert!("kube:/api-0".parse::<Via>().is_err());
+        assert!("kube:prod/".parse::<Via>().is_err());
+        assert!("kube:-prod/api-0".parse::<Via>().is_err());
+        assert!("app".parse::<Via>().is_err());
+    }
+
+    #[test]
+    fn parses_forwarded_ports() {
+        assert_eq!(
+            parse_forwarded_port("Forwarding from 127.0.0.1:41213 -> 6669"),
+            Some(41213)
+        );
+        assert_eq!(
+            parse_forwarded_port("Forwarding from [::1]:41213 -> 6669"),
+            None
+        );
+        assert_eq!(parse_forwarded_port("Handling connection for 41213"), None);
+    }
+}
@@ -0,0 +1,195 @@
+//! Exports the graph of which tasks wake which other tasks, and which
+//! resources they wait on, for `export-graph`.
+use crate::{
+    config::GraphFormat,
+    conn,
+    state::{tasks::Task, State},
+    view,
+};
+use color_eyre::eyre::WrapErr;
+use console_api as proto;
+use std::{
+    collections::{BTreeSet, HashMap},
+    fs,
+    io::{self, Write},
+    path::Path,
+};
+
+/// Returns the state after the first update from the process `conn` is
+/// connected to.
+///
+/// Tasks report every task that has woken them in their stats, and the first
+/// update includes the stats of every task, so there's no need to wait for
+/// more.
+pub(crate) async fn connect(mut conn: conn::Connection, styles: &view::Styles) -> State {
+    let mut state = State::default();
+    loop {
+        if let conn::Message::Update(update) = conn.next_message().await {
+            state.update(styles, &view::ViewState::TasksList, update);
+            return state;
+        }
+    }
+}
+
+/// Returns the state after applying every update in the recording at `path`.
+///
+/// A recording is a JSON array of updates, as serialized by `console-api`'s
+/// `serde` feature.
+pub(crate) fn replay(path: &Path, styles: &view::Styles) -> color_eyre::Result<State> {
+    let json = fs::read_to_string(path)
+        .wrap_err_with(|| format!("failed to read recording {}", path.display()))?;
+    let updates: Vec<proto::instrument::Update> = serde_json::from_str(&json)
+        .wrap_err_with(|| format!("invalid recording {}", path.display()))?;
+    let mut state = State::default();
+    for update in updates {
+        state.update(styles, &view::ViewState::TasksList, update);
+    }
+    Ok(state)
+}
+
+/// Prints the graph in `state` to stdout.
+pub(crate) fn export(state: &State, format: GraphFormat) -> color_eyre::Result<()> {
+    let mut stdout = io::stdout().lock();
+    match format {
+        GraphFormat::Dot => write_dot(&mut stdout, state)?,
+    }
+    stdout.flush()?;
+    Ok(())
+}
+
+/// Writes a Graphviz digraph with an edge from each task to each task it has
+/// woken, labeled and weighted by the number of wakes, and a dashed edge from
+/// each task to each resource it has polled.
+///
+/// Only tasks and resources with at least one edge are included, so that
+/// tasks which never interact with anything else don't clutter the graph.
+fn write_dot(out: &mut impl Write, state: &State) -> io::Result<()> {
+    let tasks = state.tasks_state();
+    let mut task_ids = BTreeSet::new();
+    let mut wakes = tasks
+        .wake_graph()
+        .edges()
+        .filter_map(|edge| {
+            let edge = edge.borrow();
+            Some((edge.waker_id()?, edge.woken_id()?, edge.wakes()))
+        })
+        .collect::<Vec<_>>();
+    wakes.sort_unstable();
+
+    let resources = state
+        .resources_state()
+        .resources()
+        .map(|resource| (resource.borrow().id(), resource))
+        .collect::<HashMap<_, _>>();
+    let mut polls = BTreeSet::new();
+    for async_op in state.async_ops_state().async_ops() {
+        let Some(async_op) = async_op.upgrade() else {
+            continue;
+        };
+        let async_op = async_op.borrow();
+        if let Some(task) = async_op.task_id() {
+            if resources.contains_key(&async_op.resource_id()) {
+                polls.insert((task, async_op.resource_id()));
+            }
+        }
+    }
+
+    for &(waker, woken, _) in &wakes {
+        task_ids.insert(waker);
+        task_ids.insert(woken);
+    }
+    task_ids.extend(polls.iter().map(|&(task, _)| task));
+    let resource_ids = polls
+        .iter()
+        .map(|&(_, resource)| resource)
+        .collect::<BTreeSet<_>>();
+
+    writeln!(out, "digraph tasks {{")?;
+    for id in task_ids {
+        let label = match tasks.task(id).and_then(|task| task.upgrade()) {
+            Some(task) => task_label(&task.borrow()),
+            None => id.to_string(),
+        };
+        writeln!(out, "    task_{id} [label={}];", quote(&label))?;
+    }
+    for id in resource_ids {
+        let resource = resources[&id].borrow();
+        let label = format!("{} {}", resource.id(), resource.concrete_type());
+        writeln!(
+            out,
+            "    resource_{id} [label={}, shape=box];",
+            quote(&label)
+        )?;
+    }
+    for (waker, woken, wakes) in wakes {
+        writeln!(
+            out,
+            "    task_{waker} -> task_{woken} [label=\"{wakes}\", weight={wakes}];"
+        )?;
+    }
+    for (task, resource) in polls {
+        writeln!(
+            out,
+            "    task_{task} -> resource_{resource} [style=dashed];"
+        )?;
+    }
+    writeln!(out, "}}")
+}
+
+/// Labels a task with its Tokio task ID and name, or with the console's ID
+/// for it if it has neither.
+fn task_label(task: &Task) -> String {
+    match task.short_desc() {
+        "" => task.id().to_string(),
+        desc => desc.to_owned(),
+    }
+}
+
+/// Quotes `s` as a DOT string.
+fn quote(s: &str) -> String {
+    let mut quoted = String::with_capacity(s.len() + 2);
+    quoted.push('"');
+    for c in s.chars() {
+        if c == '"' || c == '\\' {
+            quoted.push('\\');
+        }
+        quoted.push(c);
+    }
+    quoted.push('"');
+    quoted
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{config::ViewOptions, state::recordings};
+
+    #[test]
+    fn writes_wake_edges_as_dot() {
+        let styles = view::Styles::from_config(ViewOptions::default());
+        let mut state = recordings::state();
+        for update in recordings::load("tasks") {
+            state.update(&styles, &view::ViewState::TasksList, update);
+        }
+        let mut out = Vec::new();
+        write_dot(&mut out, &state).unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            concat!(
+                "digraph tasks {\n",
+                "    task_1 [label=\"1 (listener)\"];\n",
+                "    task_2 [label=\"2 (conn)\"];\n",
+                "    task_3 [label=\"3 (spinner)\"];\n",
+                "    task_1 -> task_2 [label=\"1\", weight=1];\n",
+                "    task_1 -> task_3 [label=\"2\", weight=2];\n",
+                "    task_2 -> task_1 [label=\"1\", weight=1];\n",
+                "}\n",
+            )
+        );
+    }
+
+    #[test]
+    fn quotes_labels() {
+        assert_eq!(quote(r#"a "b" \c"#), r#""a \"b\" \\c""#);
+    }
+}
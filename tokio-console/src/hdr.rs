@@ -0,0 +1,181 @@
+//! Exports the poll times of a task in HdrHistogram's file formats, for the
+//! `:export` command, so that they can be analyzed with existing tools such
+//! as `HistogramLogProcessor` or hdr-plot.
+use crate::state::{histogram::DurationHistogram, tasks::Task};
+use color_eyre::eyre::WrapErr;
+use hdrhistogram::{
+    serialization::{
+        interval_log::{IntervalLogWriterBuilder, Tag},
+        V2DeflateSerializer,
+    },
+    Histogram,
+};
+use std::{
+    fmt::Write,
+    fs,
+    path::Path,
+    time::{Duration, SystemTime},
+};
+
+/// The extension of percentile distribution files; anything else is written
+/// as an interval log.
+const PERCENTILES_EXTENSION: &str = "hgrm";
+
+/// Durations are recorded in nanoseconds, but HdrHistogram's tools expect
+/// them to be scaled to milliseconds.
+const NANOS_PER_MILLI: f64 = 1_000_000.0;
+
+/// How many percentile steps to list in each half of the distance to 100%,
+/// as in the Java implementation's `outputPercentileDistribution`.
+const TICKS_PER_HALF_DISTANCE: u32 = 5;
+
+/// Writes `histogram`, the poll times of `task`, to `path`.
+///
+/// Files with the `.hgrm` extension get a percentile distribution, and any
+/// other file an interval log with a single interval, from when the task was
+/// spawned until `now`.
+pub(crate) fn export(
+    path: &Path,
+    task: &Task,
+    histogram: &DurationHistogram,
+    now: SystemTime,
+) -> color_eyre::Result<()> {
+    let contents = if path
+        .extension()
+        .is_some_and(|ext| ext == PERCENTILES_EXTENSION)
+    {
+        percentile_distribution(&histogram.histogram).into_bytes()
+    } else {
+        interval_log(task, &histogram.histogram, now)?
+    };
+    fs::write(path, contents).with_context(|| format!("writing '{}'", path.display()))
+}
+
+fn interval_log(
+    task: &Task,
+    histogram: &Histogram<u64>,
+    now: SystemTime,
+) -> color_eyre::Result<Vec<u8>> {
+    let mut out = Vec::new();
+    let mut serializer = V2DeflateSerializer::new();
+    let mut log = IntervalLogWriterBuilder::new()
+        .add_comment(&format!(
+            "poll times of task {} ({})",
+            task.id_str(),
+            task.short_desc()
+        ))
+        .with_start_time(task.created_at())
+        .with_base_time(task.created_at())
+        .with_max_value_divisor(NANOS_PER_MILLI)
+        .begin_log_with(&mut out, &mut serializer)?;
+    log.write_histogram(
+        histogram,
+        Duration::ZERO,
+        task.total(now),
+        Tag::new("poll_times"),
+    )
+    .wrap_err("serializing poll times histogram")?;
+    Ok(out)
+}
+
+/// Formats `histogram` like the Java implementation's
+/// `outputPercentileDistribution`, with values in milliseconds.
+fn percentile_distribution(histogram: &Histogram<u64>) -> String {
+    let mut out = format!(
+        "{:>12} {:>14} {:>10} {:>14}\n\n",
+        "Value", "Percentile", "TotalCount", "1/(1-Percentile)"
+    );
+
+    let mut total = 0;
+    for value in histogram.iter_quantiles(TICKS_PER_HALF_DISTANCE) {
+        total += value.count_since_last_iteration();
+        let millis = value.value_iterated_to() as f64 / NANOS_PER_MILLI;
+        let quantile = value.quantile_iterated_to();
+        // The last line is at 100%, where 1/(1-Percentile) is infinite.
+        if quantile < 1.0 {
+            let _ = writeln!(
+                out,
+                "{millis:12.3} {quantile:2.12} {total:10} {:14.2}",
+                1.0 / (1.0 - quantile)
+            );
+        } else {
+            let _ = writeln!(out, "{millis:12.3} {quantile:2.12} {total:10}");
+        }
+    }
+
+    let sub_buckets = (2 * 10_u64.pow(histogram.sigfig().into())).next_power_of_two();
+    let _ = write!(
+        out,
+        "#[Mean    = {:12.3}, StdDeviation   = {:12.3}]\n\
+         #[Max     = {:12.3}, Total count    = {:12}]\n\
+         #[Buckets = {:12}, SubBuckets     = {:12}]\n",
+        histogram.mean() / NANOS_PER_MILLI,
+        histogram.stdev() / NANOS_PER_MILLI,
+        histogram.max() as f64 / NANOS_PER_MILLI,
+        histogram.len(),
+        histogram.buckets(),
+        sub_buckets,
+    );
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        config::ViewOptions,
+        state::recordings,
+        view::{Styles, ViewState},
+    };
+    use hdrhistogram::serialization::interval_log::{IntervalLogIterator, LogEntry};
+
+    fn poll_times() -> Histogram<u64> {
+        let mut histogram = Histogram::<u64>::new(2).unwrap();
+        for millis in 1..=100 {
+            histogram.record(millis * 1_000_000).unwrap();
+        }
+        histogram
+    }
+
+    #[test]
+    fn percentile_distribution_ends_at_max() {
+        let out = percentile_distribution(&poll_times());
+        let mut lines = out.lines();
+        assert_eq!(
+            lines.next(),
+            Some("       Value     Percentile TotalCount 1/(1-Percentile)")
+        );
+        assert_eq!(lines.next(), Some(""));
+        assert!(out.contains("\n     100.139 1.000000000000        100\n"));
+        assert!(out.ends_with(
+            "#[Max     =      100.139, Total count    =          100]\n\
+             #[Buckets =           20, SubBuckets     =          256]\n"
+        ));
+    }
+
+    #[test]
+    fn interval_log_covers_task_lifetime() {
+        let styles = Styles::from_config(ViewOptions::default());
+        let mut state = recordings::state();
+        for update in recordings::load("tasks") {
+            state.update(&styles, &ViewState::TasksList, update);
+        }
+        let task = state.tasks_state().find("spinner").unwrap();
+        let task = task.borrow();
+        let now = task.created_at() + Duration::from_secs(2);
+        let log = interval_log(&task, &poll_times(), now).unwrap();
+
+        let intervals = IntervalLogIterator::new(&log)
+            .filter_map(|entry| match entry.unwrap() {
+                LogEntry::Interval(interval) => Some(interval),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(intervals.len(), 1);
+        let interval = &intervals[0];
+        assert_eq!(interval.tag().map(|tag| tag.as_str()), Some("poll_times"));
+        assert_eq!(interval.start_timestamp(), Duration::ZERO);
+        assert_eq!(interval.duration(), Duration::from_secs(2));
+        assert_eq!(interval.max(), 100.139);
+    }
+}
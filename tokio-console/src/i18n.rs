@@ -0,0 +1,413 @@
+//! Localization of the console's user interface.
+//!
+//! Every label, title, and warning message the view renders is listed in
+//! [`Msg`], and looked up in the table for the [`Lang`] selected by the
+//! `lang` setting. Messages may contain `{name}` placeholders, which are
+//! filled in by [`Lang::fill`] (or `Styles::fill_spans`, when the arguments
+//! are styled).
+//!
+//! Table column headers are not localized: they double as the names of
+//! query fields and of the sort columns saved in session files, so they must
+//! stay the same whatever the language.
+//!
+//! Adding a language means adding a [`Lang`] variant with its ISO 639-1 code
+//! in `Lang::code`, listing it in `Lang::ALL`, and giving it a table like
+//! [`english`]. Messages missing from that table fall back to English.
+use std::fmt::{self, Write};
+
+/// A language the console's user interface can be displayed in.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub(crate) enum Lang {
+    #[default]
+    English,
+}
+
+/// A piece of a message's text.
+#[derive(Debug, PartialEq, Eq)]
+pub(crate) enum Piece<'a> {
+    /// Text to display as-is.
+    Text(&'a str),
+    /// The name of a `{name}` placeholder.
+    Arg(&'a str),
+}
+
+macro_rules! messages {
+    ($($(#[$meta:meta])* $name:ident => $text:literal,)+) => {
+        /// A user interface message.
+        #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+        pub(crate) enum Msg {
+            $($(#[$meta])* $name,)+
+        }
+
+        /// The English text of each message.
+        fn english(msg: Msg) -> &'static str {
+            match msg {
+                $(Msg::$name => $text,)+
+            }
+        }
+
+        #[cfg(test)]
+        const ALL_MSGS: &[Msg] = &[$(Msg::$name,)+];
+    };
+}
+
+messages! {
+    // Controls
+    Controls => "controls:",
+    Or => "or",
+    TogglePause => "toggle pause",
+    TimeFormat => "time format",
+    Query => "query",
+    Quit => "quit",
+    SelectColumn => "select column (sort)",
+    Scroll => "scroll",
+    ViewDetails => "view details",
+    InvertSort => "invert sort (highest/lowest)",
+    ScrollToTop => "scroll to top",
+    ScrollToBottom => "scroll to bottom",
+    ExitQuery => "exit query",
+    ReturnToTaskList => "return to task list",
+    CycleHistogramMode => "histogram/percentiles/counts",
+    CollapseGroup => "collapse group",
+    ExpandCollapse => "expand/collapse",
+    Acknowledge => "acknowledge",
+    ShowAcknowledged => "show acknowledged",
+    Search => "search",
+
+    // Common labels and titles
+    Id => "ID: ",
+    Target => "Target: ",
+    Location => "Location: ",
+    Warnings => "Warnings",
+    Percentiles => "Percentiles",
+    Histogram => "Histogram",
+    HistogramMax => "max",
+    HistogramOutliers => "outliers: ",
+    Help => "Help",
+    Ago => "{duration} ago",
+    Utc => "UTC",
+    UnknownCommand => "unknown command `{command}`, expected `query` or `export`",
+    ExportNeedsTask => "`export` writes the poll times of the task being inspected",
+    ExportNeedsPath => "expected a file to export to, such as `poll-times.hgrm`",
+    NoPollTimes => "no poll times have been recorded for this task yet",
+    ReadOnly => " READ ONLY",
+
+    // Query
+    QueryTitle => "Query: ",
+    QueryMatchesTitle => "Query ({count}): ",
+    WaitingForUpdate => "Waiting for an update from the remote...",
+
+    // Task list
+    TasksTitle => "Tasks ({count}) ",
+    GroupTasksTitle => "Tasks in {location} ({count}) ",
+    RunningCount => " Running ({count}) ",
+    IdleCount => " Idle ({count})",
+    Spawned => "Spawned",
+    Completed => "Completed",
+    Wakes => "Wakes",
+    ActivityTitle => "{series} ({count} per {interval})",
+    SearchMatches => "{count} matches",
+    SearchMatch => "match {current} of {count}",
+    NoSearchMatches => "no matches",
+    SearchNavigation => "n/N = next/previous match",
+    CompletedTasksTitle => "Completed Tasks ({count}) ",
+    CancelledCount => "Cancelled ({count})",
+    HistoricalCount => " Historical ({count})",
+    TaskGroupsTitle => "Task Groups ({count}) ",
+    TaskGroupsHelp => "Tasks are grouped by the location they were spawned from. \
+        The remote doesn't report which JoinSet, or which parent task, spawned \
+        a task, so tasks spawned into separate JoinSets from the same location \
+        share a group.",
+
+    // Task details
+    Task => "Task",
+    Name => "Name: ",
+    Scheduler => "Scheduler: ",
+    Level => "Level: ",
+    TotalTime => "Total Time: ",
+    Historical => " (completed before the console attached)",
+    Busy => "Busy: ",
+    Scheduled => "Scheduled: ",
+    Idle => "Idle: ",
+    FirstPollDelay => "Time to First Poll: ",
+    LongestPoll => "Longest Poll: ",
+    LongestPollValue => "{duration}, started {at}",
+    ExecutorShare => "Executor Share: ",
+    ExecutorShareValue => "{percent}% of busy time in the last update",
+    Waker => "Waker",
+    CurrentWakers => "Current wakers: ",
+    WakerCounts => "{count} ({clones}{clone_count}, {drops}{drop_count})",
+    WakerClones => "clones: ",
+    WakerDrops => "drops: ",
+    Woken => "Woken: ",
+    WokenTimes => "{count} times",
+    LastWoken => "last woken: ",
+    SelfWakes => "Self Wakes: ",
+    SelfWakesValue => "{count} times ({percent}%)",
+    Threads => "Threads",
+    ThreadPolls => "{polls} polls ({percent}%)",
+    UnnamedThread => "<unnamed>",
+    OtherThreads => "Other threads: ",
+    Fields => "Fields",
+    FieldsOmitted => "+{count} fields omitted",
+    DetailsAgedOut => "Details stopped updating: the task completed and was aged out by the remote",
+    DetailsFellBehind => "Details stopped updating: the console fell too far behind the remote",
+    DetailsDisconnected => "Details stopped updating: connection problem ({error})",
+    DetailsClosed => "Details stopped updating: the remote ended the stream",
+    Awaiting => "Awaiting",
+    AwaitingOp => " on {resource} for {duration} ({polls} polls)",
+    AwaitingMore => "+{count} more async ops",
+    PollTimesPercentiles => "Poll Times Percentiles",
+    SchedTimesPercentiles => "Sched Times Percentiles",
+    PollTimesHistogram => "Poll Times Histogram",
+    ScheduledTimesHistogram => "Scheduled Times Histogram",
+    WakeIntervalsPercentiles => "Wake Interval Percentiles",
+    WakeIntervalsHistogram => "Wake Intervals Histogram",
+
+    // Resources
+    ResourcesTitle => "Resources ({count}) ",
+    BackpressureCount => " Backpressure ({count})",
+    Resource => "Resource",
+    ParentId => "Parent ID: ",
+    Kind => "Kind: ",
+    Type => "Type: ",
+    Attributes => "Attributes",
+    Io => "I/O",
+    IoRead => "Read: ",
+    IoWrite => "Write: ",
+    IoStats => "{bytes} bytes in {ops} ops ({polls} polls), waited {waited}, longest {longest}",
+    WriteBackpressure => "waited {waited} to become writable; the peer may not be reading fast enough",
+    RecvLatencyPercentiles => "Recv Latency Percentiles",
+    RecvLatencyHistogram => "Recv Latency Histogram",
+    AsyncOpsTitle => "Async Ops ({count}) ",
+
+    // Timers
+    TimerLag => "Timer Lag",
+    TimerLagTitle => "Timer Lag ({count}) ",
+    LaggingCount => " Lagging ({count})",
+    NoTimers => "No timers have fired yet.",
+    TimerLagUnstable => "Timer deadlines are only reported when Tokio is built with {cfg}.",
+    NoTasksUnstable => "The instrumented process was built without {cfg}, so its tasks can't be instrumented.",
+    TasksUnstableRebuild => "Rebuild it with {rustflags} to see its tasks here.",
+    OverrunPercentiles => "Overrun Percentiles",
+    OverrunHistogram => "Overrun Histogram",
+
+    // `select!` hotspots
+    SelectHotspots => "Select Hotspots",
+    SelectHotspotsTitle => "Select Hotspots ({count}) ",
+    StarvedCount => " Starved ({count})",
+    BranchCompletions => "#{index}: {count} ({percent}%, avg {wait}) ",
+    NoSelects => "No instrumented `select!` callsites have been reported.",
+    SelectTimerHint => "Use {timer} to record which branches of a `select!` complete.",
+
+    // Wake graph
+    WakeGraph => "Task Wakes",
+    WakeGraphTitle => "Task Wakes ({count}) ",
+    RecentWakesCount => "Recent ({count})",
+    NoWakes => "No task has woken another task yet.",
+    WakeGraphHint => "A wake is attributed to the task which was being polled when it woke the other task.",
+
+    // Waker leaks
+    WakerLeaks => "Waker Leaks",
+    WakerLeaksTitle => "Waker Leaks ({count}) ",
+    LeakedCount => "Leaked ({count})",
+    NoWakerLeaks => "No completed task has wakers which are still alive.",
+    WakerLeaksHint => "A task's wakers are leaked if they are still alive {after} after it completes.",
+
+    // Targets
+    Targets => "Targets",
+    TargetsTitle => "Targets ({count}) ",
+    OtherTargets => "others",
+    NoBusyTargets => "No task has been polled yet.",
+
+    // Runtime workers
+    RuntimeWorkers => "Runtime Workers",
+    RuntimeWorkersTitle => "Runtime Workers ({count}) ",
+    PollingCount => "Polling ({count})",
+    CurrentThreadWorker => "current",
+    RuntimeMetrics => "Runtime Metrics",
+    WorkerMetricsUnstable => "Steals, parks, and unparks are only reported when Tokio is built with {cfg},",
+    WorkerMetricsBuilder => "and the runtime's metrics are passed to {builder}.",
+    NoPolls => "No threads have polled a task yet.",
+
+    // User spans
+    UserSpans => "User Spans",
+    UserSpansTitle => "User Spans ({count}) ",
+    EnteredCount => "Entered ({count})",
+    NoUserSpans => "No watched spans have been created yet.",
+    WatchSpanHint => "Use {macro} to time a part of a task, or add a {field} field to a span.",
+
+    // Warnings
+    WarningsTitle => "Warnings ({count})",
+    AcknowledgedWarningsTitle => "Warnings ({count}, {acknowledged} acknowledged)",
+    AcknowledgedWarning => " (acknowledged)",
+    NoWarnings => "No warnings have been detected.",
+    AllWarningsAcknowledged => "All of the warnings detected have been acknowledged.",
+    TaskWarning => "Task {id}: ",
+    NamedTaskWarning => "Task {id} ({name}): ",
+    ResourceWarning => "Resource {id} ({type}): ",
+    SelfWakePercentSummary => "tasks have woken themselves over {percent}% of the time",
+    SelfWakePercentWarning => "This task has woken itself for more than {min}% of its total wakeups ({percent}%)",
+    LostWakerSummary => "tasks have lost their wakers",
+    LostWakerWarning => "This task has lost its waker, and will never be woken again.",
+    UnbalancedWakersSummary => "tasks have had more wakers dropped than cloned",
+    UnbalancedWakersWarning => "This task's wakers have been dropped {drops} times but only cloned {clones} times, so the instrumentation has missed some clones and its waker counts are inaccurate",
+    NeverYieldedSummary => "tasks have never yielded (threshold {threshold}ms)",
+    NeverYieldedWarning => "This task has never yielded ({busy})",
+    StarvedSummary => "tasks have been woken but not polled for over {threshold}ms",
+    StarvedWarning => "This task was woken {wait} ago, but hasn't been polled since",
+    SlowFirstPollSummary => "tasks waited over {threshold}ms to be polled after being spawned",
+    SlowFirstPollWarning => "This task waited {delay} after being spawned to be polled for the first time",
+    WakeStormSummary => "tasks have been woken over {threshold} times per poll",
+    WakeStormWarning => "This task has been woken {wakes} times per poll, which suggests that whatever wakes it is notifying it far more often than needed",
+    AutoBoxedFutureSummary => "tasks have been boxed by the runtime due to their size",
+    AutoBoxedFutureWarning => "This task's future was auto-boxed by the runtime when spawning, due to its size (originally {original} bytes, boxed size {boxed} bytes)",
+    LargeFutureSummary => "tasks are {size} bytes or larger",
+    LargeFutureWarning => "This task occupies a large amount of stack space ({size} bytes)",
+    ContentionSummary => "synchronization primitives are contended (average wait over {wait}ms or more than {waiters} waiters)",
+    ContentionWarning => "This {type} created at {location} is contended: {waiters} operations are waiting{tasks} and operations waited {wait} on average",
+    ContentionWaitingTasks => " (tasks {tasks})",
+    TargetQuotaSummary => "tasks with the target {target} are running, over its quota of {max}",
+    LocationQuotaSummary => "tasks spawned at {location} are running, over their quota of {max}",
+    WakerLeaksSummary => "completed tasks have leaked their wakers",
+    TaskLeakSummary => "more tasks are live than {window} ago, without ever falling since: tasks may be leaking",
+
+    // Plain output
+    PlainTasks => "Tasks: {total} ({running} running, {scheduled} scheduled, {idle} idle, {completed} completed).",
+    PlainBusiestTasks => "Busiest tasks:",
+    PlainTaskStats => "{state}, busy {busy}, {polls} polls, spawned at {location}.",
+    PlainResources => "Resources: {count}.",
+    PlainWarnings => "Warnings: {count}.",
+    PlainWarning => "{count} {summary}.",
+    RunningState => "running",
+    ScheduledState => "scheduled",
+    IdleState => "idle",
+    CompletedState => "completed",
+
+    // Terminal title and status line
+    StatusLine => "{target}: {live} live, {warnings} warnings",
+    StatusConnecting => "{target}: connecting",
+}
+
+// === impl Lang ===
+
+impl Lang {
+    /// Returns the language for a POSIX locale name such as `en_US.UTF-8`.
+    ///
+    /// Locales in languages the console hasn't been translated to (and the
+    /// `C` and `POSIX` locales) are displayed in English.
+    pub(crate) fn from_locale(locale: &str) -> Self {
+        let language = locale
+            .split(['_', '-', '.', '@'])
+            .next()
+            .unwrap_or_default();
+        Self::ALL
+            .iter()
+            .copied()
+            .find(|lang| lang.code().eq_ignore_ascii_case(language))
+            .unwrap_or_default()
+    }
+
+    /// Every language the console can be displayed in.
+    const ALL: &'static [Lang] = &[Lang::English];
+
+    /// Returns the ISO 639-1 code of this language.
+    fn code(self) -> &'static str {
+        match self {
+            Lang::English => "en",
+        }
+    }
+
+    /// Returns the text of `msg` in this language.
+    pub(crate) fn text(self, msg: Msg) -> &'static str {
+        match self {
+            Lang::English => english(msg),
+        }
+    }
+
+    /// Returns the text of `msg` in this language, with its placeholders
+    /// replaced by the matching `args`.
+    ///
+    /// Placeholders without a matching argument are left as-is.
+    pub(crate) fn fill(self, msg: Msg, args: &[(&str, &dyn fmt::Display)]) -> String {
+        let mut out = String::new();
+        for piece in pieces(self.text(msg)) {
+            match piece {
+                Piece::Text(text) => out.push_str(text),
+                Piece::Arg(name) => match args.iter().find(|(arg, _)| *arg == name) {
+                    Some((_, value)) => {
+                        write!(out, "{value}").expect("writing to a `String` never fails")
+                    }
+                    None => write!(out, "{{{name}}}").expect("writing to a `String` never fails"),
+                },
+            }
+        }
+        out
+    }
+}
+
+/// Splits a message's text into literal text and `{name}` placeholders.
+pub(crate) fn pieces(mut text: &str) -> impl Iterator<Item = Piece<'_>> {
+    std::iter::from_fn(move || {
+        if text.is_empty() {
+            return None;
+        }
+        if let Some(rest) = text.strip_prefix('{') {
+            if let Some(end) = rest.find('}') {
+                text = &rest[end + 1..];
+                return Some(Piece::Arg(&rest[..end]));
+            }
+        }
+        // An opening brace without a closing one is displayed as-is.
+        let end = text[1..].find('{').map_or(text.len(), |i| i + 1);
+        let (piece, rest) = text.split_at(end);
+        text = rest;
+        Some(Piece::Text(piece))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unknown_locales_fall_back_to_english() {
+        for locale in ["", "C", "POSIX", "en_US.UTF-8", "en-GB", "xx_XX@euro"] {
+            assert_eq!(Lang::from_locale(locale), Lang::English, "{locale:?}");
+        }
+    }
+
+    #[test]
+    fn every_message_has_text() {
+        for &msg in ALL_MSGS {
+            assert!(!Lang::English.text(msg).is_empty(), "{msg:?}");
+        }
+    }
+
+    #[test]
+    fn splits_placeholders() {
+        let pieces = pieces("Tasks in {location} ({count}) {").collect::<Vec<_>>();
+        assert_eq!(
+            pieces,
+            [
+                Piece::Text("Tasks in "),
+                Piece::Arg("location"),
+                Piece::Text(" ("),
+                Piece::Arg("count"),
+                Piece::Text(") "),
+                Piece::Text("{"),
+            ]
+        );
+    }
+
+    #[test]
+    fn fills_placeholders() {
+        let text = Lang::English.fill(
+            Msg::ActivityTitle,
+            &[("series", &"Wakes"), ("count", &3), ("interval", &"1s")],
+        );
+        assert_eq!(text, "Wakes (3 per 1s)");
+        let text = Lang::English.fill(Msg::TasksTitle, &[]);
+        assert_eq!(text, "Tasks ({count}) ");
+    }
+}
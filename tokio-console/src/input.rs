@@ -66,10 +66,94 @@ pub(crate) fn is_esc(event: &Event) -> bool {
     )
 }
 
+/// Keys to press as if they were typed, for `--keys`.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub(crate) struct ScriptedKeys(Vec<KeyEvent>);
+
+/// The names of the keys which can be given to `--keys`, other than
+/// characters.
+const KEY_NAMES: &[(&str, KeyCode)] = &[
+    ("Enter", KeyCode::Enter),
+    ("Esc", KeyCode::Esc),
+    ("Tab", KeyCode::Tab),
+    ("Backspace", KeyCode::Backspace),
+    ("Delete", KeyCode::Delete),
+    ("Up", KeyCode::Up),
+    ("Down", KeyCode::Down),
+    ("Left", KeyCode::Left),
+    ("Right", KeyCode::Right),
+    ("Home", KeyCode::Home),
+    ("End", KeyCode::End),
+    ("PageUp", KeyCode::PageUp),
+    ("PageDown", KeyCode::PageDown),
+    ("Space", KeyCode::Char(' ')),
+    ("Comma", KeyCode::Char(',')),
+];
+
+impl ScriptedKeys {
+    pub(crate) fn into_events(self) -> impl Iterator<Item = Event> {
+        self.0.into_iter().map(Event::Key)
+    }
+}
+
+impl std::str::FromStr for ScriptedKeys {
+    type Err = String;
+
+    /// Parses a comma-separated list of keys, where each item is either the
+    /// name of a key, such as `Enter`, or text which is typed one character
+    /// at a time.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut keys = Vec::new();
+        for item in s.split(',') {
+            if item.is_empty() {
+                return Err(format!(
+                    "{s:?} has an empty key; use `Comma` to press the comma key"
+                ));
+            }
+            match KEY_NAMES.iter().find(|(name, _)| *name == item) {
+                Some(&(_, code)) => keys.push(KeyEvent::new(code, KeyModifiers::NONE)),
+                None => keys.extend(
+                    item.chars()
+                        .map(|c| KeyEvent::new(KeyCode::Char(c), KeyModifiers::NONE)),
+                ),
+            }
+        }
+        Ok(Self(keys))
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn parses_scripted_keys() {
+        let keys = "s,busy,Enter,Comma,up"
+            .parse::<ScriptedKeys>()
+            .unwrap()
+            .into_events()
+            .map(|event| match event {
+                Event::Key(key) => key.code,
+                event => panic!("unexpected event {event:?}"),
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            keys,
+            [
+                KeyCode::Char('s'),
+                KeyCode::Char('b'),
+                KeyCode::Char('u'),
+                KeyCode::Char('s'),
+                KeyCode::Char('y'),
+                KeyCode::Enter,
+                KeyCode::Char(','),
+                KeyCode::Char('u'),
+                KeyCode::Char('p'),
+            ]
+        );
+        assert!("s,,Enter".parse::<ScriptedKeys>().is_err());
+    }
+
     #[test]
     fn ignore_key_repeat_and_release_events() {
         let event = Event::Key(KeyEvent {
@@ -0,0 +1,185 @@
+use color_eyre::eyre::WrapErr;
+use std::{
+    fs,
+    io::{self, Write},
+    path::{Path, PathBuf},
+    time::SystemTime,
+};
+
+/// How large a log file may grow before the console starts a new one.
+const MAX_FILE_BYTES: u64 = 10 * 1024 * 1024;
+
+/// The extension of the log files written by the console.
+///
+/// Only files with this extension are removed when old logs are pruned, so
+/// that pointing `--log-dir` at a directory with other files in it never
+/// deletes them.
+const EXTENSION: &str = "json";
+
+/// A log file in the `--log-dir` directory which is replaced by a new file
+/// once it grows too large.
+///
+/// Each file is named after the time it was created, and only the newest
+/// `max_files` files are kept.
+#[derive(Debug)]
+pub(crate) struct RotatingLog {
+    dir: PathBuf,
+    max_files: usize,
+    max_file_bytes: u64,
+    file: fs::File,
+    written: u64,
+}
+
+impl RotatingLog {
+    pub(crate) fn new(dir: PathBuf, max_files: usize) -> color_eyre::Result<Self> {
+        Self::with_max_file_bytes(dir, max_files, MAX_FILE_BYTES)
+    }
+
+    fn with_max_file_bytes(
+        dir: PathBuf,
+        max_files: usize,
+        max_file_bytes: u64,
+    ) -> color_eyre::Result<Self> {
+        // first ensure that the log directory exists
+        fs::create_dir_all(&dir)
+            .with_context(|| format!("creating log directory '{}'", dir.display()))?;
+        color_eyre::eyre::ensure!(
+            dir.is_dir(),
+            "log directory path '{}' is not a directory",
+            dir.display()
+        );
+
+        let file = create_file(&dir)?;
+        let log = Self {
+            dir,
+            max_files: max_files.max(1),
+            max_file_bytes,
+            file,
+            written: 0,
+        };
+        log.prune();
+        Ok(log)
+    }
+
+    fn rotate(&mut self) -> io::Result<()> {
+        self.file.flush()?;
+        self.file = create_file(&self.dir).map_err(io::Error::other)?;
+        self.written = 0;
+        self.prune();
+        Ok(())
+    }
+
+    /// Removes the oldest log files, so that at most `max_files` remain.
+    ///
+    /// This is best-effort: a log file which can't be removed is left behind,
+    /// rather than failing to log.
+    fn prune(&self) {
+        let Ok(entries) = fs::read_dir(&self.dir) else {
+            return;
+        };
+        let mut logs = entries
+            .filter_map(|entry| entry.ok())
+            .map(|entry| entry.path())
+            .filter(|path| is_log_file(path))
+            .collect::<Vec<_>>();
+        if logs.len() <= self.max_files {
+            return;
+        }
+
+        // file names start with the time they were created, so sorting them
+        // by name sorts them from oldest to newest.
+        logs.sort_unstable();
+        let excess = logs.len() - self.max_files;
+        for path in &logs[..excess] {
+            if let Err(error) = fs::remove_file(path) {
+                // there's nowhere to log this but the log itself...
+                let _ = writeln!(
+                    &self.file,
+                    "failed to remove old log file '{}': {error}",
+                    path.display()
+                );
+            }
+        }
+    }
+}
+
+impl Write for RotatingLog {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        // only rotate between writes, so that a single event is never split
+        // across two files.
+        if self.written > 0 && self.written + buf.len() as u64 > self.max_file_bytes {
+            self.rotate()?;
+        }
+        let len = self.file.write(buf)?;
+        self.written += len as u64;
+        Ok(len)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.file.flush()
+    }
+}
+
+fn create_file(dir: &Path) -> color_eyre::Result<fs::File> {
+    // format the current time in a way that's appropriate for a filename
+    // (strip the `:` character, as it is an invalid filename char on
+    // windows)
+    let now = humantime::format_rfc3339_millis(SystemTime::now())
+        .to_string()
+        .replace(':', "");
+    let path = dir.join(format!("{now}.{EXTENSION}"));
+    fs::File::options()
+        .create_new(true)
+        .write(true)
+        .open(&path)
+        .with_context(|| format!("creating log file '{}'", path.display()))
+}
+
+fn is_log_file(path: &Path) -> bool {
+    path.is_file()
+        && path.extension().is_some_and(|ext| ext == EXTENSION)
+        && path
+            .file_stem()
+            .and_then(|stem| stem.to_str())
+            .is_some_and(|stem| stem.ends_with('Z'))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn log_files(dir: &Path) -> Vec<PathBuf> {
+        let mut files = fs::read_dir(dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().path())
+            .collect::<Vec<_>>();
+        files.sort_unstable();
+        files
+    }
+
+    #[test]
+    fn rotates_and_prunes_old_files() {
+        let dir = std::env::temp_dir().join(format!("tokio-console-log-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let unrelated = dir.join("notes.txt");
+        fs::write(&unrelated, "not a log").unwrap();
+
+        let mut log = RotatingLog::with_max_file_bytes(dir.clone(), 2, 16).unwrap();
+        for i in 0..4 {
+            log.write_all(format!("{{\"event\":{i}}}\n").as_bytes())
+                .unwrap();
+            // file names have millisecond precision
+            std::thread::sleep(std::time::Duration::from_millis(2));
+        }
+        log.flush().unwrap();
+
+        let files = log_files(&dir);
+        assert_eq!(files.len(), 3, "{files:?}");
+        assert!(files.contains(&unrelated));
+        let newest = files.iter().rfind(|path| is_log_file(path)).unwrap();
+        assert_eq!(fs::read_to_string(newest).unwrap(), "{\"event\":3}\n");
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -1,34 +1,50 @@
 use color_eyre::{eyre::eyre, Help, SectionExt};
-use console_api::tasks::TaskDetails;
-use state::{State, Temporality};
+use console_api::tasks::{TaskDetails, TaskGroups};
+use state::{tasks::DetailsEnded, State, Temporality};
 
+use crossterm::terminal::SetTitle;
 use futures::stream::StreamExt;
 use ratatui::{
     layout::{Constraint, Direction, Layout},
-    style::Color,
+    style::{Color, Modifier, Style},
     text::{Line, Span},
     widgets::{Paragraph, Wrap},
 };
+use std::{collections::VecDeque, time::Duration};
 use tokio::sync::{mpsc, watch};
 
 use crate::{
     config::AllowedWarnings,
-    view::{bold, UpdateKind},
+    i18n::Msg,
+    view::{bold, UpdateKind, ViewState},
 };
 
 mod config;
 mod conn;
+mod forward;
+mod graph;
+mod hdr;
+mod i18n;
 mod input;
 mod intern;
+mod log_file;
+mod plain;
+mod query;
+mod session;
+mod snapshot;
 mod state;
+mod status;
 mod term;
 mod util;
 mod view;
 mod warnings;
 
+/// How long to wait between each of the keys given with `--keys`.
+const SCRIPTED_KEY_INTERVAL: Duration = Duration::from_millis(200);
+
 #[tokio::main]
 async fn main() -> color_eyre::Result<()> {
-    let args = config::Config::parse()?;
+    let mut args = config::Config::parse()?;
     // initialize error handling first, in case panics occur while setting up
     // other stuff.
     let styles = view::Styles::from_config(args.view_options.clone());
@@ -47,20 +63,43 @@ async fn main() -> color_eyre::Result<()> {
         Some(config::OptionalCmd::GenCompletion { install, shell }) => {
             return config::gen_completion(install, shell);
         }
+        Some(config::OptionalCmd::Query { ref query, format }) => {
+            return query::run(args.connection()?, query, format, &styles).await;
+        }
+        Some(config::OptionalCmd::ExportGraph {
+            format,
+            ref recording,
+        }) => {
+            let state = match recording {
+                Some(path) => graph::replay(path, &styles)?,
+                None => graph::connect(args.connection()?, &styles).await,
+            };
+            return graph::export(&state, format);
+        }
         None => {}
     }
 
+    let mut session = if args.no_session {
+        None
+    } else {
+        Some(session::Session::load().unwrap_or_default())
+    };
+    // an address given on the command line or in a config file takes
+    // precedence over the one the last session was connected to.
+    if args.target_addr.is_none() {
+        args.target_addr = session.as_ref().and_then(session::Session::target_addr);
+    }
+
     let target = args.target_addr()?;
-    tracing::info!(?target, "using target addr");
+    tracing::info!(?target, via = ?args.via, "using target addr");
 
     let retain_for = args.retain_for();
-    let (mut terminal, _cleanup) = term::init_crossterm()?;
-    terminal.clear()?;
-    let mut conn = conn::Connection::new(target);
-    // A channel to send the outcome of `View::update_input` to the watch_details_stream task.
-    let (update_tx, update_rx) = watch::channel(UpdateKind::Other);
-    // A channel to send the task details update stream (no need to keep outdated details in the memory)
-    let (details_tx, mut details_rx) = mpsc::channel::<TaskDetails>(2);
+    let conn = args.connection()?.with_retain_for(retain_for);
+    let max_completed_tasks = args.max_completed_tasks();
+    let task_level = args.task_level();
+    let presets = args.presets().to_vec();
+    let quotas = args.quotas().to_vec();
+    let leak_window = args.leak_window();
     let warnings = match args.allow_warnings {
         Some(AllowedWarnings::All) => vec![],
         Some(AllowedWarnings::Explicit(allow_warnings)) => args
@@ -72,13 +111,74 @@ async fn main() -> color_eyre::Result<()> {
     };
 
     let mut state = State::default()
-        .with_task_linters(warnings.into_iter().map(|lint| lint.into()))
-        .with_retain_for(retain_for);
+        .with_task_linters(warnings.iter().filter_map(|lint| lint.task_linter()))
+        .with_resource_linters(warnings.iter().filter_map(|lint| lint.resource_linter()))
+        .with_retain_for(retain_for)
+        .with_max_completed_tasks(max_completed_tasks)
+        .with_task_level(task_level)
+        .with_quotas(quotas)
+        .with_leak_window(leak_window);
+
+    if let Some(format) = args.once {
+        return snapshot::run(conn, format, state, styles).await;
+    }
+    if args.plain {
+        return plain::run(conn, args.summary_interval, args.duration, state, styles).await;
+    }
+
+    let (mut terminal, _cleanup) = term::init_crossterm()?;
+    terminal.clear()?;
+    // With `--title`, the terminal title is only set when the status changes.
+    let _restore_title = args.title.then(term::save_title).transpose()?;
+    let target_addr = target.to_string();
+    let mut title = None;
+    let mut conn = conn;
+    // A channel to send the outcome of `View::update_input` to the watch_details_stream task.
+    let (update_tx, update_rx) = watch::channel(UpdateKind::Other);
+    // A channel to send the task details update stream (no need to keep outdated details in the memory),
+    // and why it ended, if the remote stopped sending it.
+    let (details_tx, mut details_rx) = mpsc::channel::<DetailsUpdate>(2);
+    // A channel to send task groups updates, and the task forwarding them
+    // from the remote while the task groups view is open.
+    let (groups_tx, mut groups_rx) = mpsc::channel::<TaskGroups>(2);
+    let mut groups_watch: Option<tokio::task::JoinHandle<()>> = None;
     let mut input = Box::pin(input::EventStream::new());
-    let mut view = view::View::new(styles);
+    let mut view = view::View::new(styles)
+        .with_presets(&presets)
+        .with_plugins(view::plugin::registered());
+    if let Some(ref session) = session {
+        view.restore_session(session);
+    }
+    // The task to open the details view for once it appears, if one was
+    // given on the command line.
+    let mut open_task = args.task.clone();
+    // The frame rate is capped, so updates received too soon after the last
+    // frame are drawn once the next frame is due.
+    let frame_interval = args.view_options.frame_interval();
+    let mut next_frame = tokio::time::Instant::now();
+    let mut frame_pending = false;
+    // `--duration` counts from when the console starts, rather than from the
+    // first update, so that it exits even if it can't connect.
+    let deadline = args
+        .duration
+        .map(|duration| tokio::time::Instant::now() + duration);
+    // The keys given with `--keys` are pressed once the first update has been
+    // received, so that they have tasks to act on.
+    let mut scripted_keys = args
+        .keys
+        .take()
+        .map(|keys| keys.into_events().collect::<VecDeque<_>>())
+        .unwrap_or_default();
+    let mut next_key = None;
 
     loop {
-        tokio::select! { biased;
+        let mut update_kind = None;
+        let mut quit = false;
+        let input = tokio::select! { biased;
+            _ = util::sleep_until(deadline) => {
+                quit = true;
+                None
+            },
             input = input.next() => {
                 let input = input
                     .ok_or_else(|| eyre!("keyboard input stream ended early"))
@@ -87,12 +187,61 @@ async fn main() -> color_eyre::Result<()> {
                 if input::should_ignore_key_event(&input) {
                     continue;
                 }
-
-                if input::should_quit(&input) {
-                    return Ok(());
+                Some(input)
+            },
+            _ = util::sleep_until(next_key), if !scripted_keys.is_empty() => {
+                next_key = Some(tokio::time::Instant::now() + SCRIPTED_KEY_INTERVAL);
+                scripted_keys.pop_front()
+            }
+            instrument_message = conn.next_message() => {
+                match instrument_message {
+                    conn::Message::Update(update) => {
+                        next_key.get_or_insert_with(tokio::time::Instant::now);
+                        state.update(&view.styles, view.current_view(), update);
+                        view.update_plugins(&state);
+                        let task = open_task
+                            .as_deref()
+                            .and_then(|id_or_name| state.tasks_state().find(id_or_name));
+                        if let Some(task) = task {
+                            update_kind = Some(view.open_task(task, &state));
+                            open_task = None;
+                        }
+                    },
+                    conn::Message::State(state_update) => {
+                        state.update_state(state_update);
+                        view.update_plugins(&state);
+                    }
+                }
+                None
+            }
+            details_update = details_rx.recv() => {
+                match details_update {
+                    Some(DetailsUpdate::Details(details)) => state.update_task_details(*details),
+                    Some(DetailsUpdate::Ended { task_id, ended }) => {
+                        tracing::info!(task_id, ?ended, "task details watch ended");
+                        state.end_task_details(task_id, ended);
+                    }
+                    None => {}
                 }
+                None
+            },
+            groups_update = groups_rx.recv() => {
+                if let Some(groups_update) = groups_update {
+                    state.update_task_groups(groups_update);
+                }
+                None
+            },
+            _ = tokio::time::sleep_until(next_frame), if frame_pending => None,
+        };
+
+        if let Some(input) = input {
+            // while a command or search is being typed, keys such as `q`
+            // and space are part of it.
+            if !view.is_prompting() {
+                quit = input::should_quit(&input);
 
-                if input::is_space(&input) {
+                // a read-only remote would refuse to pause or resume.
+                if input::is_space(&input) && !state.is_read_only() {
                     if state.is_paused() {
                         conn.resume().await;
                         state.start_unpausing();
@@ -101,46 +250,85 @@ async fn main() -> color_eyre::Result<()> {
                         state.start_pausing();
                     }
                 }
+            }
+
+            if !quit {
+                update_kind = Some(view.update_input(input, &mut state));
+            }
+        }
 
-                let update_kind = view.update_input(input, &state);
-                // Using the result of update_input to manage the details watcher task
-                let _ = update_tx.send(update_kind);
-                match update_kind {
-                    UpdateKind::SelectTask(task_id) => {
-                        tracing::info!(task_id, "starting details watch");
-                        match conn.watch_details(task_id).await {
-                            Ok(stream) => {
-                                tokio::spawn(watch_details_stream(task_id, stream, update_rx.clone(), details_tx.clone()));
-                            },
-                            Err(error) => {
-                                tracing::warn!(%error, "error watching task details");
-                                state.unset_task_details();
+        if quit {
+            if let Some(ref mut session) = session {
+                session.target_addr = Some(target_addr);
+                view.save_session(session);
+                if let Err(error) = session.save() {
+                    tracing::warn!(%error, "failed to save session");
+                }
+            }
+            return Ok(());
+        }
+
+        if let Some(update_kind) = update_kind {
+            // Using the result of update_input to manage the details watcher task
+            let _ = update_tx.send(update_kind);
+            match update_kind {
+                UpdateKind::SelectTask(task_id) => {
+                    tracing::info!(task_id, "starting details watch");
+                    match conn.watch_details(task_id).await {
+                        Ok(stream) => {
+                            tokio::spawn(watch_details_stream(
+                                task_id,
+                                stream,
+                                update_rx.clone(),
+                                details_tx.clone(),
+                            ));
                         }
+                        Err(error) => {
+                            tracing::warn!(%error, "error watching task details");
+                            state.unset_task_details();
                         }
-                    },
-                    UpdateKind::ExitTaskView => {
-                        state.unset_task_details();
                     }
-                    _ => {}
                 }
-            },
-            instrument_message = conn.next_message() => {
-                match instrument_message {
-                    conn::Message::Update(update) => {
-                        state.update(&view.styles, view.current_view(), update);
-                    },
-                    conn::Message::State(state_update) => {
-                        state.update_state(state_update);
-                    }
+                UpdateKind::ExitTaskView => {
+                    state.unset_task_details();
                 }
+                _ => {}
             }
-            details_update = details_rx.recv() => {
-                if let Some(details_update) = details_update {
-                    state.update_task_details(details_update);
+        }
+
+        // Merging the poll time histograms of every group of tasks is
+        // expensive for the remote, so only watch them while they are shown.
+        let showing_groups = matches!(view.current_view(), ViewState::TaskGroupsList);
+        match groups_watch {
+            Some(ref watch) if !showing_groups || watch.is_finished() => {
+                watch.abort();
+                groups_watch = None;
+                state.unset_task_groups();
+            }
+            None if showing_groups => match conn.watch_task_groups().await {
+                Ok(stream) => {
+                    tracing::info!("starting task groups watch");
+                    groups_watch = Some(tokio::spawn(watch_task_groups_stream(
+                        stream,
+                        groups_tx.clone(),
+                    )));
+                }
+                Err(error) => {
+                    tracing::warn!(%error, "error watching task groups");
                 }
             },
+            _ => {}
         }
-        terminal.draw(|f| {
+
+        let now = tokio::time::Instant::now();
+        if now < next_frame {
+            frame_pending = true;
+            continue;
+        }
+        next_frame = now + frame_interval;
+        frame_pending = false;
+
+        let frame = terminal.draw(|f| {
             let chunks = Layout::default()
                 .direction(Direction::Vertical)
                 .margin(0)
@@ -165,12 +353,61 @@ async fn main() -> color_eyre::Result<()> {
                 Temporality::Unpausing => {
                     header_text.push_span(Span::styled(" UNPAUSING", view.styles.fg(Color::Green)));
                 }
-                Temporality::Live => {}
+                Temporality::Live => {
+                    if let Some(paused_for) = state.last_pause() {
+                        header_text.push_span(Span::styled(
+                            " RESUMED (paused for ",
+                            view.styles.fg(Color::Green),
+                        ));
+                        header_text.push_span(view.styles.time_units(
+                            paused_for,
+                            view::DUR_LIST_PRECISION,
+                            None,
+                        ));
+                        header_text.push_span(Span::styled(")", view.styles.fg(Color::Green)));
+                    }
+                }
+            }
+            if state.is_read_only() {
+                header_text.push_span(Span::styled(
+                    view.styles.text(Msg::ReadOnly),
+                    view.styles.fg(Color::Yellow),
+                ));
+            }
+            if let Some(preset) = view.active_preset() {
+                header_text.push_span(Span::styled(
+                    format!(" preset: {preset}"),
+                    view.styles.fg(Color::Cyan),
+                ));
+            }
+            if let Some(id_or_name) = open_task.as_deref() {
+                header_text.push_span(Span::styled(
+                    format!(" waiting for task {}", id_or_name),
+                    view.styles.fg(Color::Yellow),
+                ));
+            }
+            if state.is_degraded() {
+                header_text.push_span(Span::styled(
+                    " summaries only: updates exceeded the bandwidth limit",
+                    view.styles.fg(Color::Red),
+                ));
+            }
+            if state.has_layer_panicked() {
+                header_text.push_span(Span::styled(
+                    " instrumentation stopped: the console-subscriber layer panicked",
+                    view.styles.fg(Color::Red),
+                ));
             }
             let dropped_async_ops_state = state.async_ops_state().dropped_events();
             let dropped_tasks_state = state.tasks_state().dropped_events();
             let dropped_resources_state = state.resources_state().dropped_events();
-            if (dropped_async_ops_state + dropped_tasks_state + dropped_resources_state) > 0 {
+            let dropped_spans_state = state.spans_state().dropped_events();
+            if (dropped_async_ops_state
+                + dropped_tasks_state
+                + dropped_resources_state
+                + dropped_spans_state)
+                > 0
+            {
                 let mut dropped_texts = vec![];
                 if dropped_async_ops_state > 0 {
                     dropped_texts.push(format!("{} async_ops", dropped_async_ops_state))
@@ -181,18 +418,72 @@ async fn main() -> color_eyre::Result<()> {
                 if dropped_resources_state > 0 {
                     dropped_texts.push(format!("{} resources", dropped_resources_state))
                 }
+                if dropped_spans_state > 0 {
+                    dropped_texts.push(format!("{} spans", dropped_spans_state))
+                }
                 header_text.push_span(Span::styled(
                     format!(" dropped: {}", dropped_texts.join(", ")),
                     view.styles.fg(Color::Red),
                 ));
             }
+            if let Some(aggregates) = state.tasks_state().aggregates() {
+                header_text.push_span(Span::raw(format!(
+                    " tasks: {} spawned, {} completed, {} max concurrent, busy ",
+                    aggregates.total_spawned, aggregates.total_completed, aggregates.max_concurrent,
+                )));
+                header_text.push_span(view.styles.time_units(
+                    aggregates.total_busy,
+                    view::DUR_LIST_PRECISION,
+                    None,
+                ));
+            }
+            let live_tasks = state.tasks_state().live_tasks();
+            if let Some(live) = live_tasks.live() {
+                let style = if live_tasks.leaked().is_some() {
+                    view.styles.fg(Color::Red).add_modifier(Modifier::BOLD)
+                } else {
+                    Style::default().add_modifier(Modifier::BOLD)
+                };
+                header_text.push_span(Span::styled(
+                    format!(" live: {} {}", live, live_tasks.trend().arrow(&view.styles)),
+                    style,
+                ));
+            }
+            if let Some(memory) = state.server_memory() {
+                header_text.push_span(Span::raw(format!(
+                    " server memory: {} ({} in histograms at {} significant figures)",
+                    util::format_bytes(memory.task_bytes + memory.histogram_bytes),
+                    util::format_bytes(memory.histogram_bytes),
+                    memory.histogram_significant_figures,
+                )));
+            }
             let header = Paragraph::new(header_text).wrap(Wrap { trim: true });
             let view_controls = Paragraph::new(Line::from(vec![
                 Span::raw("views: "),
                 bold("t"),
                 Span::raw(" = tasks, "),
+                bold("c"),
+                Span::raw(" = completed tasks, "),
                 bold("r"),
-                Span::raw(" = resources"),
+                Span::raw(" = resources, "),
+                bold("s"),
+                Span::raw(" = task groups, "),
+                bold("b"),
+                Span::raw(" = select hotspots, "),
+                bold("w"),
+                Span::raw(" = timer lag, "),
+                bold("R"),
+                Span::raw(" = runtime workers, "),
+                bold("u"),
+                Span::raw(" = user spans, "),
+                bold("e"),
+                Span::raw(" = task wakes, "),
+                bold("L"),
+                Span::raw(" = waker leaks, "),
+                bold("T"),
+                Span::raw(" = targets, "),
+                bold("W"),
+                Span::raw(" = warnings"),
             ]))
             .wrap(Wrap { trim: true });
 
@@ -200,11 +491,34 @@ async fn main() -> color_eyre::Result<()> {
             f.render_widget(view_controls, chunks[1]);
             view.render(f, chunks[2], &mut state);
         })?;
+        let links = view.hyperlinks();
+        if !links.is_empty() {
+            let buffer = frame.buffer.clone();
+            view::hyperlink::draw(terminal.backend_mut(), &buffer, links)?;
+        }
+        if args.title {
+            let status = status::line(&target_addr, &state, &view.styles);
+            if title.as_ref() != Some(&status) {
+                crossterm::execute!(terminal.backend_mut(), SetTitle(&status))?;
+                title = Some(status);
+            }
+        }
     }
 }
 
+/// An update from the task details stream of the currently-viewed task.
+enum DetailsUpdate {
+    Details(Box<TaskDetails>),
+    /// The remote stopped sending the task's details.
+    Ended {
+        task_id: u64,
+        ended: DetailsEnded,
+    },
+}
+
 /// Given the task details stream for the given task id, sends the updates
-/// to the `details_tx` channel until the currently-viewed task changes.
+/// to the `details_tx` channel until the currently-viewed task changes, or
+/// the remote ends the stream, in which case the reason is sent last.
 ///
 /// This is a separate task from the main program loop mainly because there isn't
 /// always a details stream to poll and we need to react to user inputs to
@@ -213,21 +527,24 @@ async fn watch_details_stream(
     task_id: u64,
     mut details_stream: tonic::Streaming<TaskDetails>,
     mut watch_rx: watch::Receiver<UpdateKind>,
-    details_tx: mpsc::Sender<TaskDetails>,
+    details_tx: mpsc::Sender<DetailsUpdate>,
 ) {
     loop {
         tokio::select! { biased;
             details = details_stream.next() => {
-                match details {
+                let status = match details {
                     Some(Ok(details)) => {
-                        if details_tx.send(details).await.is_err() {
+                        if details_tx.send(DetailsUpdate::Details(Box::new(details))).await.is_err() {
                             break;
                         }
+                        continue;
                     },
-                    _ => {
-                        break;
-                    }
-                }
+                    Some(Err(status)) => Some(status),
+                    None => None,
+                };
+                let ended = DetailsEnded::from_status(status.as_ref());
+                let _ = details_tx.send(DetailsUpdate::Ended { task_id, ended }).await;
+                break;
             },
             update = watch_rx.changed() => {
                 if update.is_ok() {
@@ -247,3 +564,16 @@ async fn watch_details_stream(
         }
     }
 }
+
+/// Sends the updates from the task groups stream to the `groups_tx` channel,
+/// until the stream ends or the task is aborted.
+async fn watch_task_groups_stream(
+    mut groups_stream: tonic::Streaming<TaskGroups>,
+    groups_tx: mpsc::Sender<TaskGroups>,
+) {
+    while let Some(Ok(groups)) = groups_stream.next().await {
+        if groups_tx.send(groups).await.is_err() {
+            break;
+        }
+    }
+}
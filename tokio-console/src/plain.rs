@@ -0,0 +1,241 @@
+//! Prints linear text summaries of the remote's state, for `--plain`.
+//!
+//! Unlike the full-screen view, the summaries never move the cursor or rely
+//! on colors, so they can be followed with a screen reader or a braille
+//! display.
+use crate::{
+    conn,
+    i18n::Msg,
+    state::{tasks::TaskState, State},
+    util, view,
+};
+use std::{
+    cmp::Reverse,
+    io::{self, Write},
+    time::Duration,
+};
+
+/// How often a summary is printed, unless `--summary-interval` is set.
+const DEFAULT_SUMMARY_INTERVAL: Duration = Duration::from_secs(5);
+
+/// How many of the busiest tasks each summary lists.
+const BUSIEST_TASKS: usize = 5;
+
+/// Prints a summary of the tasks, resources, and warnings of the process
+/// `conn` is connected to, every `interval` until the console is killed, or
+/// `duration` has elapsed.
+pub(crate) async fn run(
+    mut conn: conn::Connection,
+    interval: Option<Duration>,
+    duration: Option<Duration>,
+    mut state: State,
+    styles: view::Styles,
+) -> color_eyre::Result<()> {
+    let mut summaries = tokio::time::interval(interval.unwrap_or(DEFAULT_SUMMARY_INTERVAL));
+    summaries.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+    let deadline = duration.map(|duration| tokio::time::Instant::now() + duration);
+    loop {
+        let last = tokio::select! {
+            message = conn.next_message() => {
+                match message {
+                    conn::Message::Update(update) => {
+                        state.update(&styles, &view::ViewState::TasksList, update);
+                        // There are no tables to add new items to, so drop
+                        // them rather than letting them pile up.
+                        state.tasks_state_mut().take_new_tasks().for_each(drop);
+                        state.resources_state_mut().take_new_resources().for_each(drop);
+                        state.async_ops_state_mut().take_new_async_ops().for_each(drop);
+                        state.workers_state_mut().take_new_workers().for_each(drop);
+                    }
+                    conn::Message::State(update) => state.update_state(update),
+                }
+                continue;
+            },
+            _ = summaries.tick() => false,
+            // A last summary is printed once the duration has elapsed,
+            // however long it's been since the one before it.
+            _ = util::sleep_until(deadline) => true,
+        };
+
+        state.retain_active();
+        let connection = conn
+            .render(&styles)
+            .spans
+            .iter()
+            .map(|span| span.content.as_ref())
+            .collect::<String>();
+        let mut stdout = io::stdout().lock();
+        write_summary(&mut stdout, &state, &styles, &connection)?;
+        stdout.flush()?;
+        if last {
+            return Ok(());
+        }
+    }
+}
+
+/// Writes a summary of `state`, after the `connection` status, followed by a
+/// blank line.
+fn write_summary(
+    out: &mut impl Write,
+    state: &State,
+    styles: &view::Styles,
+    connection: &str,
+) -> io::Result<()> {
+    writeln!(out, "{connection}")?;
+    let Some(now) = state.last_updated_at() else {
+        writeln!(out, "{}", styles.text(Msg::WaitingForUpdate))?;
+        return writeln!(out);
+    };
+
+    let tasks = state.tasks_state();
+    let (mut running, mut scheduled, mut idle, mut completed) = (0, 0, 0, 0);
+    for task in tasks.tasks() {
+        match task.borrow().state() {
+            TaskState::Running => running += 1,
+            TaskState::Scheduled => scheduled += 1,
+            TaskState::Idle => idle += 1,
+            TaskState::Completed => completed += 1,
+        }
+    }
+    let total: usize = running + scheduled + idle + completed;
+    writeln!(
+        out,
+        "{}",
+        styles.fill(
+            Msg::PlainTasks,
+            &[
+                ("total", &total),
+                ("running", &running),
+                ("scheduled", &scheduled),
+                ("idle", &idle),
+                ("completed", &completed),
+            ],
+        )
+    )?;
+
+    let mut busiest = tasks
+        .tasks()
+        .filter(|task| !task.borrow().is_completed())
+        .collect::<Vec<_>>();
+    busiest.sort_by_key(|task| {
+        let task = task.borrow();
+        (Reverse(task.busy(now)), task.id())
+    });
+    if !busiest.is_empty() {
+        writeln!(out, "{}", styles.text(Msg::PlainBusiestTasks))?;
+    }
+    for task in busiest.into_iter().take(BUSIEST_TASKS) {
+        let task = task.borrow();
+        let title = match task.name() {
+            Some(name) => styles.fill(
+                Msg::NamedTaskWarning,
+                &[("id", &task.id_str()), ("name", &name)],
+            ),
+            None => styles.fill(Msg::TaskWarning, &[("id", &task.id_str())]),
+        };
+        let busy = styles.time_units(task.busy(now), view::DUR_LIST_PRECISION, None);
+        let stats = styles.fill(
+            Msg::PlainTaskStats,
+            &[
+                ("state", &task_state(styles, task.state())),
+                ("busy", &busy.content),
+                ("polls", &task.total_polls()),
+                ("location", &task.location()),
+            ],
+        );
+        writeln!(out, "  {title}{stats}")?;
+    }
+
+    let resources = state.resources_state().resources().count();
+    writeln!(
+        out,
+        "{}",
+        styles.fill(Msg::PlainResources, &[("count", &resources)])
+    )?;
+
+    let lang = styles.lang();
+    let warnings = tasks
+        .warnings()
+        .map(|linter| (linter.count(), linter.summary(lang)))
+        .chain(
+            state
+                .resources_state()
+                .warnings()
+                .map(|linter| (linter.count(), linter.summary(lang))),
+        )
+        .chain(
+            tasks
+                .task_leak(styles)
+                .map(|(summary, count)| (count, summary)),
+        )
+        .collect::<Vec<_>>();
+    writeln!(
+        out,
+        "{}",
+        styles.fill(Msg::PlainWarnings, &[("count", &warnings.len())])
+    )?;
+    for (count, summary) in warnings {
+        let warning = styles.fill(
+            Msg::PlainWarning,
+            &[("count", &count), ("summary", &summary)],
+        );
+        writeln!(out, "  {warning}")?;
+    }
+    writeln!(out)
+}
+
+/// Describes a task's state in words, rather than with the tasks table's
+/// symbols.
+fn task_state(styles: &view::Styles, state: TaskState) -> &'static str {
+    styles.text(match state {
+        TaskState::Running => Msg::RunningState,
+        TaskState::Scheduled => Msg::ScheduledState,
+        TaskState::Idle => Msg::IdleState,
+        TaskState::Completed => Msg::CompletedState,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{config::ViewOptions, state::recordings};
+
+    fn summary(state: &State) -> String {
+        let styles = view::Styles::from_config(ViewOptions::default());
+        let mut out = Vec::new();
+        write_summary(&mut out, state, &styles, "connection: test (CONNECTED)").unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn waits_for_the_first_update() {
+        assert_eq!(
+            summary(&recordings::state()),
+            "connection: test (CONNECTED)\nWaiting for an update from the remote...\n\n"
+        );
+    }
+
+    #[test]
+    fn summarizes_tasks_and_warnings() {
+        let styles = view::Styles::from_config(ViewOptions::default());
+        let mut state = recordings::state();
+        let update = recordings::load("tasks").pop_front().unwrap();
+        state.update(&styles, &view::ViewState::TasksList, update);
+        assert_eq!(
+            summary(&state),
+            concat!(
+                "connection: test (CONNECTED)\n",
+                "Tasks: 3 (0 running, 1 scheduled, 2 idle, 0 completed).\n",
+                "Busiest tasks:\n",
+                "  Task 3 (spinner): scheduled, busy 600.00ms, 20 polls, spawned at <unknown location>.\n",
+                "  Task 1 (listener): idle, busy 200.00ms, 5 polls, spawned at <unknown location>.\n",
+                "  Task 2 (conn): idle, busy 50.00ms, 2 polls, spawned at <unknown location>.\n",
+                "Resources: 0.\n",
+                "Warnings: 2.\n",
+                "  1 tasks have woken themselves over 50% of the time.\n",
+                "  1 tasks have lost their wakers.\n",
+                "\n",
+            )
+        );
+    }
+}
@@ -0,0 +1,574 @@
+//! A small query language for selecting and aggregating tasks.
+//!
+//! A query names a source of rows, followed by any number of stages separated
+//! by `|`, each of which transforms the rows produced by the stage before it:
+//!
+//! ```text
+//! tasks | where busy > 1s && target =~ "hyper" | top 10 by polls
+//! ```
+//!
+//! The supported stages are:
+//!
+//! * `where <expr>` keeps the rows for which `<expr>` is true. Expressions
+//!   compare a field with a literal using `==`, `!=`, `<`, `<=`, `>` or `>=`,
+//!   or match a field against a regular expression using `=~` or `!~`, and
+//!   may be combined using `&&`, `||`, `!` and parentheses.
+//! * `sort by <field> [asc|desc]` sorts the rows by a field.
+//! * `top <n> by <field>` keeps the `n` rows with the largest values of a
+//!   field.
+//! * `limit <n>` keeps the first `n` rows.
+//! * `select <field>, ...` chooses which fields are output.
+//! * `count`, or `sum`, `avg`, `min` or `max` followed by a field, aggregates
+//!   the rows into a single value. This must be the last stage.
+//!
+//! Literals are strings (`"hyper"`), numbers (`100`), or durations (`1s`,
+//! `1.5ms`, `250us`).
+//!
+//! The expression of a `where` stage can also be parsed on its own, as a
+//! [`Filter`], which is how searches of the tasks list select tasks.
+use crate::{
+    config::QueryFormat,
+    conn,
+    state::{
+        tasks::{Task, TaskState, TasksState},
+        State,
+    },
+    view,
+};
+use color_eyre::{eyre::eyre, Help, SectionExt};
+use std::{
+    cell::RefCell,
+    cmp::Ordering,
+    fmt,
+    rc::Rc,
+    time::{Duration, SystemTime},
+};
+
+mod parse;
+
+pub(crate) use self::parse::Error;
+
+/// A parsed query.
+#[derive(Debug)]
+pub(crate) struct Query {
+    stages: Vec<Stage>,
+}
+
+/// A parsed filter expression, such as `busy > 500ms && name =~ "^conn"`.
+///
+/// This is the same expression a `where` stage takes.
+#[derive(Debug)]
+pub(crate) struct Filter {
+    expr: Expr,
+}
+
+/// The result of evaluating a query.
+#[derive(Debug, Default)]
+pub(crate) struct Table {
+    pub(crate) columns: Vec<String>,
+    pub(crate) rows: Vec<Vec<Value>>,
+}
+
+/// A single value in a query result.
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) enum Value {
+    Str(String),
+    Num(u64),
+    Duration(Duration),
+    /// The field has no value for this row, such as an unnamed task's name.
+    None,
+}
+
+#[derive(Debug)]
+enum Stage {
+    Where(Expr),
+    Sort { field: Field, descending: bool },
+    Limit(usize),
+    Select(Vec<Field>),
+    Aggregate(Aggregate),
+}
+
+#[derive(Debug)]
+enum Expr {
+    And(Box<Expr>, Box<Expr>),
+    Or(Box<Expr>, Box<Expr>),
+    Not(Box<Expr>),
+    Compare {
+        field: Field,
+        op: CmpOp,
+        value: Value,
+    },
+    Matches {
+        field: Field,
+        regex: regex::Regex,
+    },
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum CmpOp {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+#[derive(Clone, Copy, Debug)]
+enum Aggregate {
+    Count,
+    Sum(Field),
+    Avg(Field),
+    Min(Field),
+    Max(Field),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Field {
+    Id,
+    Name,
+    Target,
+    Kind,
+    Scheduler,
+    Level,
+    State,
+    Location,
+    Total,
+    Busy,
+    Sched,
+    Idle,
+    Polls,
+    MaxPoll,
+    Wakes,
+    SelfWakes,
+    Wakers,
+    Warnings,
+}
+
+/// The type of a field's values.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Type {
+    Str,
+    Num,
+    Duration,
+}
+
+/// Runs `query` against the tasks of the process `conn` is connected to, and
+/// prints the results.
+pub(crate) async fn run(
+    mut conn: conn::Connection,
+    query: &str,
+    format: QueryFormat,
+    styles: &view::Styles,
+) -> color_eyre::Result<()> {
+    let parsed = Query::parse(query).map_err(|error| {
+        eyre!("invalid query: {error}").with_section(|| query.to_owned().header("Query:"))
+    })?;
+
+    // The first update from the remote includes every task it knows about, so
+    // there's no need to wait for more.
+    let mut state = State::default();
+    loop {
+        if let conn::Message::Update(update) = conn.next_message().await {
+            state.update(styles, &view::ViewState::TasksList, update);
+            break;
+        }
+    }
+
+    let now = state.last_updated_at().unwrap_or_else(SystemTime::now);
+    let table = parsed.eval(state.tasks_state(), now);
+    match format {
+        QueryFormat::Table => print!("{table}"),
+        QueryFormat::Json => println!("{}", serde_json::to_string_pretty(&table.to_json())?),
+    }
+    Ok(())
+}
+
+// === impl Query ===
+
+impl Query {
+    pub(crate) fn parse(query: &str) -> Result<Self, Error> {
+        parse::Parser::new(query)?.query()
+    }
+
+    /// Evaluates the query against the current set of tasks.
+    pub(crate) fn eval(&self, tasks: &TasksState, now: SystemTime) -> Table {
+        let mut rows: Vec<Rc<RefCell<Task>>> = tasks.tasks().cloned().collect();
+        rows.sort_by_key(|task| task.borrow().id());
+        let mut columns = Field::DEFAULT_COLUMNS.to_vec();
+
+        for stage in &self.stages {
+            match stage {
+                Stage::Where(expr) => rows.retain(|task| expr.eval(&task.borrow(), now)),
+                Stage::Sort { field, descending } => rows.sort_by(|a, b| {
+                    let a = field.value(&a.borrow(), now);
+                    let b = field.value(&b.borrow(), now);
+                    let ordering = a.sort_cmp(&b);
+                    if *descending {
+                        ordering.reverse()
+                    } else {
+                        ordering
+                    }
+                }),
+                Stage::Limit(limit) => rows.truncate(*limit),
+                Stage::Select(fields) => columns.clone_from(fields),
+                // the parser ensures that aggregations are the last stage.
+                Stage::Aggregate(aggregate) => return aggregate.eval(&rows, now),
+            }
+        }
+
+        Table {
+            columns: columns
+                .iter()
+                .map(|field| field.name().to_owned())
+                .collect(),
+            rows: rows
+                .iter()
+                .map(|task| {
+                    let task = task.borrow();
+                    columns
+                        .iter()
+                        .map(|field| field.value(&task, now))
+                        .collect()
+                })
+                .collect(),
+        }
+    }
+}
+
+// === impl Filter ===
+
+impl Filter {
+    pub(crate) fn parse(filter: &str) -> Result<Self, Error> {
+        parse::Parser::new(filter)?.filter()
+    }
+
+    /// Returns `true` if `task` is selected by the filter.
+    pub(crate) fn matches(&self, task: &Task, now: SystemTime) -> bool {
+        self.expr.eval(task, now)
+    }
+}
+
+// === impl Table ===
+
+impl Table {
+    /// Returns the rows of the table as a JSON array of objects.
+    ///
+    /// Durations are given in nanoseconds.
+    pub(crate) fn to_json(&self) -> serde_json::Value {
+        self.rows
+            .iter()
+            .map(|row| {
+                let object = self
+                    .columns
+                    .iter()
+                    .zip(row)
+                    .map(|(column, value)| (column.clone(), value.to_json()))
+                    .collect::<serde_json::Map<_, _>>();
+                serde_json::Value::Object(object)
+            })
+            .collect()
+    }
+}
+
+impl fmt::Display for Table {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let rows = self
+            .rows
+            .iter()
+            .map(|row| row.iter().map(Value::to_string).collect::<Vec<_>>())
+            .collect::<Vec<_>>();
+        let widths = self
+            .columns
+            .iter()
+            .enumerate()
+            .map(|(idx, column)| {
+                rows.iter()
+                    .map(|row| row[idx].len())
+                    .chain(Some(column.len()))
+                    .max()
+                    .unwrap_or_default()
+            })
+            .collect::<Vec<_>>();
+
+        let mut write_row = |cells: &mut dyn Iterator<Item = &str>| -> fmt::Result {
+            let line = cells
+                .zip(&widths)
+                .map(|(cell, &width)| format!("{cell:width$}"))
+                .collect::<Vec<_>>()
+                .join("  ");
+            writeln!(f, "{}", line.trim_end())
+        };
+        write_row(&mut self.columns.iter().map(String::as_str))?;
+        for row in &rows {
+            write_row(&mut row.iter().map(String::as_str))?;
+        }
+        Ok(())
+    }
+}
+
+// === impl Value ===
+
+impl Value {
+    /// Compares two values of the same type, returning `None` if they can't
+    /// be compared.
+    fn compare(&self, other: &Value) -> Option<Ordering> {
+        match (self, other) {
+            (Value::Str(a), Value::Str(b)) => Some(a.cmp(b)),
+            (Value::Num(a), Value::Num(b)) => Some(a.cmp(b)),
+            (Value::Duration(a), Value::Duration(b)) => Some(a.cmp(b)),
+            _ => None,
+        }
+    }
+
+    /// Compares two values for sorting, with missing values first.
+    fn sort_cmp(&self, other: &Value) -> Ordering {
+        match (self, other) {
+            (Value::None, Value::None) => Ordering::Equal,
+            (Value::None, _) => Ordering::Less,
+            (_, Value::None) => Ordering::Greater,
+            (a, b) => a.compare(b).unwrap_or(Ordering::Equal),
+        }
+    }
+
+    fn to_json(&self) -> serde_json::Value {
+        match self {
+            Value::Str(s) => s.clone().into(),
+            Value::Num(n) => (*n).into(),
+            Value::Duration(d) => (d.as_nanos() as u64).into(),
+            Value::None => serde_json::Value::Null,
+        }
+    }
+}
+
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Value::Str(s) => f.write_str(s),
+            Value::Num(n) => write!(f, "{n}"),
+            Value::Duration(d) => write!(f, "{d:.2?}"),
+            Value::None => Ok(()),
+        }
+    }
+}
+
+// === impl Expr ===
+
+impl Expr {
+    fn eval(&self, task: &Task, now: SystemTime) -> bool {
+        match self {
+            Expr::And(a, b) => a.eval(task, now) && b.eval(task, now),
+            Expr::Or(a, b) => a.eval(task, now) || b.eval(task, now),
+            Expr::Not(expr) => !expr.eval(task, now),
+            Expr::Compare { field, op, value } => {
+                let ordering = field.value(task, now).compare(value);
+                match op {
+                    CmpOp::Eq => ordering == Some(Ordering::Equal),
+                    CmpOp::Ne => ordering != Some(Ordering::Equal),
+                    CmpOp::Lt => ordering == Some(Ordering::Less),
+                    CmpOp::Le => matches!(ordering, Some(Ordering::Less | Ordering::Equal)),
+                    CmpOp::Gt => ordering == Some(Ordering::Greater),
+                    CmpOp::Ge => matches!(ordering, Some(Ordering::Greater | Ordering::Equal)),
+                }
+            }
+            Expr::Matches { field, regex } => match field.value(task, now) {
+                Value::Str(s) => regex.is_match(&s),
+                _ => false,
+            },
+        }
+    }
+}
+
+// === impl Aggregate ===
+
+impl Aggregate {
+    fn eval(self, rows: &[Rc<RefCell<Task>>], now: SystemTime) -> Table {
+        let (column, field) = match self {
+            Aggregate::Count => {
+                return Table {
+                    columns: vec!["count".to_owned()],
+                    rows: vec![vec![Value::Num(rows.len() as u64)]],
+                }
+            }
+            Aggregate::Sum(field) => ("sum", field),
+            Aggregate::Avg(field) => ("avg", field),
+            Aggregate::Min(field) => ("min", field),
+            Aggregate::Max(field) => ("max", field),
+        };
+        let values = rows
+            .iter()
+            .map(|task| field.value(&task.borrow(), now))
+            .filter(|value| *value != Value::None);
+
+        let value = match self {
+            Aggregate::Count => unreachable!("counts are returned above"),
+            Aggregate::Min(_) => values.min_by(Value::sort_cmp).unwrap_or(Value::None),
+            Aggregate::Max(_) => values.max_by(Value::sort_cmp).unwrap_or(Value::None),
+            // the parser ensures that only numbers and durations are summed or
+            // averaged.
+            Aggregate::Sum(_) | Aggregate::Avg(_) => {
+                let (count, total) = values.fold((0u128, 0u128), |(count, total), value| {
+                    let value = match value {
+                        Value::Num(n) => n as u128,
+                        Value::Duration(d) => d.as_nanos(),
+                        _ => 0,
+                    };
+                    (count + 1, total + value)
+                });
+                let total = match self {
+                    Aggregate::Avg(_) if count == 0 => None,
+                    Aggregate::Avg(_) => Some(total / count),
+                    _ => Some(total),
+                };
+                match (total, field.ty()) {
+                    (None, _) => Value::None,
+                    (Some(total), Type::Duration) => {
+                        Value::Duration(Duration::from_nanos(total.try_into().unwrap_or(u64::MAX)))
+                    }
+                    (Some(total), _) => Value::Num(total.try_into().unwrap_or(u64::MAX)),
+                }
+            }
+        };
+
+        Table {
+            columns: vec![format!("{column}({})", field.name())],
+            rows: vec![vec![value]],
+        }
+    }
+}
+
+// === impl Field ===
+
+impl Field {
+    const ALL: &'static [Field] = &[
+        Field::Id,
+        Field::Name,
+        Field::Target,
+        Field::Kind,
+        Field::Scheduler,
+        Field::Level,
+        Field::State,
+        Field::Location,
+        Field::Total,
+        Field::Busy,
+        Field::Sched,
+        Field::Idle,
+        Field::Polls,
+        Field::MaxPoll,
+        Field::Wakes,
+        Field::SelfWakes,
+        Field::Wakers,
+        Field::Warnings,
+    ];
+
+    /// The fields which are output if a query has no `select` stage.
+    const DEFAULT_COLUMNS: &'static [Field] = &[
+        Field::Id,
+        Field::Name,
+        Field::State,
+        Field::Total,
+        Field::Busy,
+        Field::Polls,
+        Field::Location,
+    ];
+
+    fn name(self) -> &'static str {
+        match self {
+            Field::Id => "id",
+            Field::Name => "name",
+            Field::Target => "target",
+            Field::Kind => "kind",
+            Field::Scheduler => "scheduler",
+            Field::Level => "level",
+            Field::State => "state",
+            Field::Location => "location",
+            Field::Total => "total",
+            Field::Busy => "busy",
+            Field::Sched => "sched",
+            Field::Idle => "idle",
+            Field::Polls => "polls",
+            Field::MaxPoll => "max_poll",
+            Field::Wakes => "wakes",
+            Field::SelfWakes => "self_wakes",
+            Field::Wakers => "wakers",
+            Field::Warnings => "warnings",
+        }
+    }
+
+    fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.iter().copied().find(|field| field.name() == name)
+    }
+
+    fn ty(self) -> Type {
+        match self {
+            Field::Name
+            | Field::Target
+            | Field::Kind
+            | Field::Scheduler
+            | Field::Level
+            | Field::State
+            | Field::Location => Type::Str,
+            Field::Total | Field::Busy | Field::Sched | Field::Idle | Field::MaxPoll => {
+                Type::Duration
+            }
+            Field::Id
+            | Field::Polls
+            | Field::Wakes
+            | Field::SelfWakes
+            | Field::Wakers
+            | Field::Warnings => Type::Num,
+        }
+    }
+
+    fn value(self, task: &Task, now: SystemTime) -> Value {
+        match self {
+            Field::Id => task.id_str().parse().map(Value::Num).unwrap_or(Value::None),
+            Field::Name => task
+                .name()
+                .map(|name| Value::Str(name.to_owned()))
+                .unwrap_or(Value::None),
+            Field::Target => Value::Str(task.target().to_owned()),
+            Field::Kind => Value::Str(task.kind().to_owned()),
+            Field::Scheduler => task
+                .scheduler()
+                .map(|scheduler| Value::Str(scheduler.to_owned()))
+                .unwrap_or(Value::None),
+            Field::Level => Value::Str(task.level().as_str().to_ascii_lowercase()),
+            Field::State => Value::Str(
+                match task.state() {
+                    TaskState::Running => "running",
+                    TaskState::Scheduled => "scheduled",
+                    TaskState::Idle => "idle",
+                    TaskState::Completed => "completed",
+                }
+                .to_owned(),
+            ),
+            Field::Location => Value::Str(task.location().to_owned()),
+            Field::Total => Value::Duration(task.total(now)),
+            Field::Busy => Value::Duration(task.busy(now)),
+            Field::Sched => Value::Duration(task.scheduled(now)),
+            Field::Idle => Value::Duration(task.idle(now)),
+            Field::Polls => Value::Num(task.total_polls()),
+            Field::MaxPoll => task
+                .longest_poll(now)
+                .map(|(longest, _)| Value::Duration(longest))
+                .unwrap_or(Value::None),
+            Field::Wakes => Value::Num(task.wakes()),
+            Field::SelfWakes => Value::Num(task.self_wakes()),
+            // A negative count means the instrumentation is inconsistent, so
+            // it isn't a meaningful number of wakers.
+            Field::Wakers => u64::try_from(task.waker_count()).map_or(Value::None, Value::Num),
+            Field::Warnings => Value::Num(task.warnings().len() as u64),
+        }
+    }
+}
+
+impl fmt::Display for Type {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Type::Str => f.write_str("a string"),
+            Type::Num => f.write_str("a number"),
+            Type::Duration => f.write_str("a duration"),
+        }
+    }
+}
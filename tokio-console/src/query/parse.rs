@@ -0,0 +1,492 @@
+use super::{Aggregate, CmpOp, Expr, Field, Filter, Query, Stage, Type, Value};
+use std::{fmt, time::Duration};
+
+/// An error parsing a query.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Error {
+    message: String,
+    /// The offset in the query at which the error occurred.
+    position: usize,
+}
+
+pub(super) struct Parser {
+    tokens: Vec<(usize, Token)>,
+    next: usize,
+    /// The length of the query, used as the position of errors at its end.
+    end: usize,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Token {
+    Ident(String),
+    Str(String),
+    Num(u64),
+    Duration(Duration),
+    Pipe,
+    Comma,
+    And,
+    Or,
+    Not,
+    LParen,
+    RParen,
+    Cmp(CmpOp),
+    Match,
+    NotMatch,
+}
+
+// === impl Error ===
+
+impl Error {
+    fn new(position: usize, message: impl Into<String>) -> Self {
+        Self {
+            message: message.into(),
+            position,
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} (at column {})", self.message, self.position + 1)
+    }
+}
+
+impl std::error::Error for Error {}
+
+// === impl Parser ===
+
+impl Parser {
+    pub(super) fn new(query: &str) -> Result<Self, Error> {
+        Ok(Self {
+            tokens: tokenize(query)?,
+            next: 0,
+            end: query.len(),
+        })
+    }
+
+    pub(super) fn query(mut self) -> Result<Query, Error> {
+        let (position, source) = self.ident("a source, such as `tasks`")?;
+        if source != "tasks" {
+            return Err(Error::new(
+                position,
+                format!("unknown source `{source}`, expected `tasks`"),
+            ));
+        }
+
+        let mut stages = Vec::new();
+        while self.eat(&Token::Pipe) {
+            if let Some(Stage::Aggregate(_)) = stages.last() {
+                return Err(self.error("aggregations must be the last stage of a query"));
+            }
+            self.stage(&mut stages)?;
+        }
+
+        if self.next < self.tokens.len() {
+            return Err(self.error("expected `|` or the end of the query"));
+        }
+        Ok(Query { stages })
+    }
+
+    pub(super) fn filter(mut self) -> Result<Filter, Error> {
+        let expr = self.expr()?;
+        if self.next < self.tokens.len() {
+            return Err(self.error("expected `&&`, `||` or the end of the filter"));
+        }
+        Ok(Filter { expr })
+    }
+
+    fn stage(&mut self, stages: &mut Vec<Stage>) -> Result<(), Error> {
+        let (position, stage) = self.ident("a stage, such as `where`")?;
+        match stage.as_str() {
+            "where" => stages.push(Stage::Where(self.expr()?)),
+            "sort" => {
+                self.keyword("by")?;
+                let field = self.field()?;
+                let descending = if self.eat_keyword("desc") {
+                    true
+                } else {
+                    self.eat_keyword("asc");
+                    false
+                };
+                stages.push(Stage::Sort { field, descending });
+            }
+            "top" => {
+                let limit = self.limit()?;
+                self.keyword("by")?;
+                let field = self.field()?;
+                stages.push(Stage::Sort {
+                    field,
+                    descending: true,
+                });
+                stages.push(Stage::Limit(limit));
+            }
+            "limit" => stages.push(Stage::Limit(self.limit()?)),
+            "select" => {
+                let mut fields = vec![self.field()?];
+                while self.eat(&Token::Comma) {
+                    fields.push(self.field()?);
+                }
+                stages.push(Stage::Select(fields));
+            }
+            "count" => stages.push(Stage::Aggregate(Aggregate::Count)),
+            "min" => stages.push(Stage::Aggregate(Aggregate::Min(self.field()?))),
+            "max" => stages.push(Stage::Aggregate(Aggregate::Max(self.field()?))),
+            "sum" | "avg" => {
+                let position = self.position();
+                let field = self.field()?;
+                if field.ty() == Type::Str {
+                    return Err(Error::new(
+                        position,
+                        format!("cannot {stage} `{}`, which is a string", field.name()),
+                    ));
+                }
+                let aggregate = if stage == "sum" {
+                    Aggregate::Sum(field)
+                } else {
+                    Aggregate::Avg(field)
+                };
+                stages.push(Stage::Aggregate(aggregate));
+            }
+            _ => return Err(Error::new(position, format!("unknown stage `{stage}`"))),
+        }
+        Ok(())
+    }
+
+    fn expr(&mut self) -> Result<Expr, Error> {
+        let mut expr = self.and()?;
+        while self.eat(&Token::Or) {
+            expr = Expr::Or(Box::new(expr), Box::new(self.and()?));
+        }
+        Ok(expr)
+    }
+
+    fn and(&mut self) -> Result<Expr, Error> {
+        let mut expr = self.unary()?;
+        while self.eat(&Token::And) {
+            expr = Expr::And(Box::new(expr), Box::new(self.unary()?));
+        }
+        Ok(expr)
+    }
+
+    fn unary(&mut self) -> Result<Expr, Error> {
+        if self.eat(&Token::Not) {
+            return Ok(Expr::Not(Box::new(self.unary()?)));
+        }
+        if self.eat(&Token::LParen) {
+            let expr = self.expr()?;
+            if !self.eat(&Token::RParen) {
+                return Err(self.error("expected `)`"));
+            }
+            return Ok(expr);
+        }
+        self.comparison()
+    }
+
+    fn comparison(&mut self) -> Result<Expr, Error> {
+        let field = self.field()?;
+        let op = match self.advance() {
+            Some((_, Token::Cmp(op))) => op,
+            Some((position, token @ (Token::Match | Token::NotMatch))) => {
+                let regex = match self.advance() {
+                    Some((position, Token::Str(pattern))) => regex::Regex::new(&pattern)
+                        .map_err(|error| Error::new(position, format!("invalid regex: {error}")))?,
+                    _ => return Err(self.error("expected a regex, such as `\"hyper\"`")),
+                };
+                if field.ty() != Type::Str {
+                    return Err(Error::new(
+                        position,
+                        format!("cannot match `{}`, which is not a string", field.name()),
+                    ));
+                }
+                let expr = Expr::Matches { field, regex };
+                return Ok(if token == Token::NotMatch {
+                    Expr::Not(Box::new(expr))
+                } else {
+                    expr
+                });
+            }
+            _ => {
+                self.back();
+                return Err(self.error("expected a comparison, such as `>` or `=~`"));
+            }
+        };
+
+        let position = self.position();
+        let value = match self.advance() {
+            Some((_, Token::Str(s))) if field.ty() == Type::Str => Value::Str(s),
+            Some((_, Token::Num(n))) if field.ty() == Type::Num => Value::Num(n),
+            Some((_, Token::Duration(d))) if field.ty() == Type::Duration => Value::Duration(d),
+            _ => {
+                return Err(Error::new(
+                    position,
+                    format!("expected {} to compare `{}` with", field.ty(), field.name()),
+                ))
+            }
+        };
+        Ok(Expr::Compare { field, op, value })
+    }
+
+    fn field(&mut self) -> Result<Field, Error> {
+        let (position, name) = self.ident("a field, such as `busy`")?;
+        Field::from_name(&name).ok_or_else(|| {
+            let fields = Field::ALL
+                .iter()
+                .map(|field| field.name())
+                .collect::<Vec<_>>()
+                .join(", ");
+            Error::new(
+                position,
+                format!("unknown field `{name}`, expected one of: {fields}"),
+            )
+        })
+    }
+
+    fn limit(&mut self) -> Result<usize, Error> {
+        match self.advance() {
+            Some((_, Token::Num(n))) => Ok(n as usize),
+            _ => {
+                self.back();
+                Err(self.error("expected a number of rows"))
+            }
+        }
+    }
+
+    fn ident(&mut self, expected: &str) -> Result<(usize, String), Error> {
+        match self.advance() {
+            Some((position, Token::Ident(ident))) => Ok((position, ident)),
+            _ => {
+                self.back();
+                Err(self.error(format!("expected {expected}")))
+            }
+        }
+    }
+
+    fn keyword(&mut self, keyword: &str) -> Result<(), Error> {
+        if self.eat_keyword(keyword) {
+            Ok(())
+        } else {
+            Err(self.error(format!("expected `{keyword}`")))
+        }
+    }
+
+    fn eat_keyword(&mut self, keyword: &str) -> bool {
+        match self.tokens.get(self.next) {
+            Some((_, Token::Ident(ident))) if ident == keyword => {
+                self.next += 1;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    fn eat(&mut self, token: &Token) -> bool {
+        match self.tokens.get(self.next) {
+            Some((_, next)) if next == token => {
+                self.next += 1;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    fn advance(&mut self) -> Option<(usize, Token)> {
+        let token = self.tokens.get(self.next).cloned();
+        self.next += 1;
+        token
+    }
+
+    fn back(&mut self) {
+        self.next -= 1;
+    }
+
+    fn position(&self) -> usize {
+        self.tokens
+            .get(self.next)
+            .map(|&(position, _)| position)
+            .unwrap_or(self.end)
+    }
+
+    fn error(&self, message: impl Into<String>) -> Error {
+        Error::new(self.position(), message)
+    }
+}
+
+fn tokenize(query: &str) -> Result<Vec<(usize, Token)>, Error> {
+    let mut tokens = Vec::new();
+    let mut chars = query.char_indices().peekable();
+    while let Some((start, c)) = chars.next() {
+        let mut next_is = |expected: char| {
+            chars
+                .next_if(|&(_, c)| c == expected)
+                .map(|_| true)
+                .unwrap_or(false)
+        };
+        let token = match c {
+            c if c.is_whitespace() => continue,
+            '|' if next_is('|') => Token::Or,
+            '|' => Token::Pipe,
+            '&' if next_is('&') => Token::And,
+            ',' => Token::Comma,
+            '(' => Token::LParen,
+            ')' => Token::RParen,
+            '=' if next_is('=') => Token::Cmp(CmpOp::Eq),
+            '=' if next_is('~') => Token::Match,
+            '!' if next_is('=') => Token::Cmp(CmpOp::Ne),
+            '!' if next_is('~') => Token::NotMatch,
+            '!' => Token::Not,
+            '<' if next_is('=') => Token::Cmp(CmpOp::Le),
+            '<' => Token::Cmp(CmpOp::Lt),
+            '>' if next_is('=') => Token::Cmp(CmpOp::Ge),
+            '>' => Token::Cmp(CmpOp::Gt),
+            '"' => {
+                let mut s = String::new();
+                loop {
+                    match chars.next() {
+                        Some((_, '"')) => break,
+                        Some((_, '\\')) => match chars.next() {
+                            Some((_, c)) => s.push(c),
+                            None => return Err(Error::new(start, "unterminated string")),
+                        },
+                        Some((_, c)) => s.push(c),
+                        None => return Err(Error::new(start, "unterminated string")),
+                    }
+                }
+                Token::Str(s)
+            }
+            c if c.is_ascii_digit() => {
+                let mut end = start + 1;
+                while let Some((idx, _)) = chars.next_if(|&(_, c)| c.is_ascii_digit() || c == '.') {
+                    end = idx + 1;
+                }
+                let number = &query[start..end];
+                let unit_start = end;
+                while let Some((idx, c)) = chars.next_if(|&(_, c)| c.is_alphabetic()) {
+                    end = idx + c.len_utf8();
+                }
+                number_or_duration(start, number, &query[unit_start..end])?
+            }
+            c if c.is_alphabetic() || c == '_' => {
+                let mut end = start + c.len_utf8();
+                while let Some((idx, c)) = chars.next_if(|&(_, c)| c.is_alphanumeric() || c == '_')
+                {
+                    end = idx + c.len_utf8();
+                }
+                Token::Ident(query[start..end].to_owned())
+            }
+            c => return Err(Error::new(start, format!("unexpected `{c}`"))),
+        };
+        tokens.push((start, token));
+    }
+    Ok(tokens)
+}
+
+fn number_or_duration(position: usize, number: &str, unit: &str) -> Result<Token, Error> {
+    let invalid = || Error::new(position, format!("invalid number `{number}{unit}`"));
+    if unit.is_empty() {
+        return number.parse().map(Token::Num).map_err(|_| invalid());
+    }
+
+    let nanos_per_unit = match unit {
+        "ns" => 1.0,
+        "us" | "µs" => 1e3,
+        "ms" => 1e6,
+        "s" => 1e9,
+        "m" => 60e9,
+        "h" => 3600e9,
+        _ => {
+            return Err(Error::new(
+                position,
+                format!("unknown duration unit `{unit}`, expected one of: ns, us, ms, s, m, h"),
+            ))
+        }
+    };
+    let number: f64 = number.parse().map_err(|_| invalid())?;
+    Ok(Token::Duration(Duration::from_nanos(
+        (number * nanos_per_unit) as u64,
+    )))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_pipeline() {
+        let query =
+            Query::parse(r#"tasks | where busy > 1s && target =~ "hyper" | top 10 by polls"#)
+                .unwrap();
+        assert!(matches!(
+            query.stages.as_slice(),
+            [
+                Stage::Where(Expr::And(..)),
+                Stage::Sort {
+                    field: Field::Polls,
+                    descending: true,
+                },
+                Stage::Limit(10),
+            ]
+        ));
+    }
+
+    #[test]
+    fn parses_filters() {
+        let filter =
+            Filter::parse(r#"polls >= 1000 || (busy > 500ms && name !~ "^conn")"#).unwrap();
+        assert!(matches!(filter.expr, Expr::Or(..)));
+
+        let error = Filter::parse("polls >= 1000 | count").unwrap_err();
+        assert_eq!(error.position, 14);
+        assert!(Filter::parse("listener").is_err());
+    }
+
+    #[test]
+    fn parses_non_ascii_text() {
+        let error = Filter::parse(r#"名前 == "conn""#).unwrap_err();
+        assert_eq!(error.position, 0);
+        assert!(error.message.starts_with("unknown field `名前`"), "{error}");
+        let error = Filter::parse("café").unwrap_err();
+        assert!(error.message.starts_with("unknown field `café`"), "{error}");
+
+        let tokens = tokenize(r#"name == "café名""#).unwrap();
+        assert_eq!(tokens[2], (8, Token::Str("café名".to_owned())));
+        Filter::parse(r#"name == "café名""#).unwrap();
+    }
+
+    #[test]
+    fn parses_durations() {
+        let tokens = tokenize("1.5ms 250us 2s 3").unwrap();
+        let tokens = tokens.into_iter().map(|(_, t)| t).collect::<Vec<_>>();
+        assert_eq!(
+            tokens,
+            vec![
+                Token::Duration(Duration::from_micros(1500)),
+                Token::Duration(Duration::from_micros(250)),
+                Token::Duration(Duration::from_secs(2)),
+                Token::Num(3),
+            ]
+        );
+    }
+
+    #[test]
+    fn rejects_mismatched_types() {
+        let error = Query::parse("tasks | where busy > 10").unwrap_err();
+        assert_eq!(error.position, 21);
+        assert!(error.message.contains("a duration"), "{error}");
+
+        let error = Query::parse("tasks | where polls =~ \"1\"").unwrap_err();
+        assert!(error.message.contains("not a string"), "{error}");
+    }
+
+    #[test]
+    fn rejects_stages_after_aggregations() {
+        let error = Query::parse("tasks | count | limit 1").unwrap_err();
+        assert_eq!(error.position, 16);
+    }
+
+    #[test]
+    fn rejects_unknown_fields() {
+        let error = Query::parse("tasks | sort by nope").unwrap_err();
+        assert_eq!(error.position, 16);
+        assert!(error.message.starts_with("unknown field `nope`"), "{error}");
+    }
+}
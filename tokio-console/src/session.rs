@@ -0,0 +1,146 @@
+use color_eyre::eyre::WrapErr;
+use serde::{Deserialize, Serialize};
+use std::{fs, path::PathBuf};
+use tonic::transport::Uri;
+
+/// The state of the console's UI, which is saved when the console exits and
+/// restored the next time it is launched.
+///
+/// This is stored separately from the config file, so that the config file
+/// is never rewritten by the console.
+#[derive(Debug, Default, Deserialize, Serialize)]
+#[serde(default)]
+pub(crate) struct Session {
+    /// The address of the process the console was last connected to.
+    pub(crate) target_addr: Option<String>,
+    /// The view which was shown when the console exited.
+    pub(crate) view: SessionView,
+    /// The query whose results were shown, if the view was a query.
+    pub(crate) query: Option<String>,
+    pub(crate) tasks: Option<TableSession>,
+    pub(crate) completed_tasks: Option<TableSession>,
+    pub(crate) resources: Option<TableSession>,
+    pub(crate) task_groups: Option<TableSession>,
+    pub(crate) select_stats: Option<TableSession>,
+    pub(crate) timer_lag: Option<TableSession>,
+    pub(crate) workers: Option<TableSession>,
+    pub(crate) spans: Option<TableSession>,
+    pub(crate) wake_graph: Option<TableSession>,
+    pub(crate) waker_leaks: Option<TableSession>,
+    pub(crate) targets: Option<TableSession>,
+}
+
+/// The views which can be restored by a session.
+///
+/// Views which show a single task or resource are restored as the list they
+/// were opened from, since the task or resource is unlikely to still exist.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub(crate) enum SessionView {
+    #[default]
+    Tasks,
+    CompletedTasks,
+    Resources,
+    TaskGroups,
+    SelectStats,
+    TimerLag,
+    Workers,
+    Spans,
+    WakeGraph,
+    WakerLeaks,
+    Targets,
+    Warnings,
+    Query,
+}
+
+/// How a table was sorted.
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Eq)]
+pub(crate) struct TableSession {
+    /// The header of the column the table was sorted by.
+    ///
+    /// This is stored by name rather than by index, so that it still refers
+    /// to the same column if columns are added in a later version.
+    pub(crate) sort_column: String,
+    pub(crate) sort_descending: bool,
+}
+
+// === impl Session ===
+
+impl Session {
+    /// Loads the session saved by the last console to exit, if there is one.
+    ///
+    /// A session file which can't be read is ignored, since it only affects
+    /// how the console looks when it starts.
+    pub(crate) fn load() -> Option<Self> {
+        let path = Self::path()?;
+        let raw = fs::read_to_string(&path).ok()?;
+        match toml::from_str(&raw) {
+            Ok(session) => Some(session),
+            Err(error) => {
+                tracing::warn!(path = %path.display(), %error, "ignoring invalid session file");
+                None
+            }
+        }
+    }
+
+    pub(crate) fn save(&self) -> color_eyre::Result<()> {
+        let Some(path) = Self::path() else {
+            return Ok(());
+        };
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)
+                .with_context(|| format!("creating session directory '{}'", dir.display()))?;
+        }
+        let raw = toml::to_string_pretty(self)?;
+        fs::write(&path, raw)
+            .with_context(|| format!("writing session file '{}'", path.display()))?;
+        Ok(())
+    }
+
+    /// Returns the address of the process the console was last connected
+    /// to, if it is valid.
+    pub(crate) fn target_addr(&self) -> Option<Uri> {
+        self.target_addr.as_ref()?.parse().ok()
+    }
+
+    fn path() -> Option<PathBuf> {
+        let mut path = dirs::state_dir().or_else(dirs::data_local_dir)?;
+        path.push("tokio-console/session.toml");
+        Some(path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trips_through_toml() {
+        let session = Session {
+            target_addr: Some("http://127.0.0.1:6669/".to_owned()),
+            view: SessionView::Query,
+            query: Some("tasks | where polls > 10".to_owned()),
+            tasks: Some(TableSession {
+                sort_column: "Busy".to_owned(),
+                sort_descending: true,
+            }),
+            ..Default::default()
+        };
+
+        let raw = toml::to_string_pretty(&session).expect("session should serialize");
+        let parsed: Session = toml::from_str(&raw).expect("session should parse");
+        assert_eq!(parsed.target_addr, session.target_addr);
+        assert_eq!(parsed.view, session.view);
+        assert_eq!(parsed.query, session.query);
+        assert_eq!(parsed.tasks, session.tasks);
+        assert_eq!(parsed.resources, None);
+    }
+
+    #[test]
+    fn missing_fields_use_defaults() {
+        let parsed: Session = toml::from_str("view = 'resources'").expect("session should parse");
+        assert_eq!(parsed.view, SessionView::Resources);
+        assert_eq!(parsed.target_addr, None);
+        assert_eq!(parsed.tasks, None);
+    }
+}
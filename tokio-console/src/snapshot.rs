@@ -0,0 +1,154 @@
+//! Renders a single frame of the console to stdout, for `--once`.
+use crate::{config::SnapshotFormat, conn, state::State, status, view};
+use crossterm::{
+    queue,
+    style::{Attribute, Colors, Print, SetAttribute, SetColors},
+};
+use ratatui::{
+    backend::TestBackend,
+    buffer::{Buffer, Cell},
+    style::{Color, Modifier},
+    text::Span,
+    Terminal,
+};
+use std::io::{self, Write};
+
+/// The size of the frame to render if stdout is not a terminal.
+const DEFAULT_SIZE: (u16, u16) = (120, 40);
+
+/// Waits for the first update from the process `conn` is connected to, and
+/// prints the tasks view as it would be displayed.
+pub(crate) async fn run(
+    mut conn: conn::Connection,
+    format: SnapshotFormat,
+    mut state: State,
+    styles: view::Styles,
+) -> color_eyre::Result<()> {
+    // The first update from the remote includes every task it knows about, so
+    // there's no need to wait for more.
+    let mut view = view::View::new(styles);
+    loop {
+        if let conn::Message::Update(update) = conn.next_message().await {
+            state.update(&view.styles, view.current_view(), update);
+            break;
+        }
+    }
+
+    if format == SnapshotFormat::Status {
+        let status = status::line(&conn.target().to_string(), &state, &view.styles);
+        println!("{status}");
+        return Ok(());
+    }
+
+    let (width, height) = crossterm::terminal::size().unwrap_or(DEFAULT_SIZE);
+    let mut terminal = Terminal::new(TestBackend::new(width, height))?;
+    terminal.draw(|frame| view.render(frame, frame.size(), &mut state))?;
+
+    let mut stdout = io::stdout().lock();
+    write_buffer(&mut stdout, terminal.backend().buffer(), format)?;
+    stdout.flush()?;
+    Ok(())
+}
+
+/// Writes the contents of `buffer` as lines of text, with trailing blank
+/// cells removed.
+pub(crate) fn write_buffer(
+    out: &mut impl Write,
+    buffer: &Buffer,
+    format: SnapshotFormat,
+) -> io::Result<()> {
+    let width = buffer.area.width as usize;
+    for line in buffer.content().chunks(width) {
+        let len = line
+            .iter()
+            .rposition(|cell| *cell != Cell::default())
+            .map_or(0, |idx| idx + 1);
+
+        let mut style = None;
+        // The number of cells covered by the last wide character.
+        let mut skip = 0;
+        for cell in &line[..len] {
+            if skip > 0 {
+                skip -= 1;
+                continue;
+            }
+            skip = Span::raw(cell.symbol()).width().saturating_sub(1);
+
+            if format == SnapshotFormat::Ansi {
+                let cell_style = (cell.fg, cell.bg, cell.modifier);
+                if style != Some(cell_style) {
+                    write_style(out, cell.fg, cell.bg, cell.modifier)?;
+                    style = Some(cell_style);
+                }
+            }
+            queue!(out, Print(cell.symbol()))?;
+        }
+
+        if style.is_some() {
+            queue!(out, SetAttribute(Attribute::Reset))?;
+        }
+        queue!(out, Print("\n"))?;
+    }
+    Ok(())
+}
+
+fn write_style(out: &mut impl Write, fg: Color, bg: Color, modifier: Modifier) -> io::Result<()> {
+    const ATTRIBUTES: &[(Modifier, Attribute)] = &[
+        (Modifier::BOLD, Attribute::Bold),
+        (Modifier::DIM, Attribute::Dim),
+        (Modifier::ITALIC, Attribute::Italic),
+        (Modifier::UNDERLINED, Attribute::Underlined),
+        (Modifier::REVERSED, Attribute::Reverse),
+        (Modifier::CROSSED_OUT, Attribute::CrossedOut),
+    ];
+
+    // Resetting first means that each style can be written without
+    // tracking which attributes need to be turned off.
+    queue!(
+        out,
+        SetAttribute(Attribute::Reset),
+        SetColors(Colors::new(fg.into(), bg.into()))
+    )?;
+    for (flag, attribute) in ATTRIBUTES {
+        if modifier.contains(*flag) {
+            queue!(out, SetAttribute(*attribute))?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ratatui::{layout::Rect, style::Style};
+
+    fn render(buffer: &Buffer, format: SnapshotFormat) -> String {
+        let mut out = Vec::new();
+        write_buffer(&mut out, buffer, format).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn plain_trims_trailing_blanks() {
+        let mut buffer = Buffer::empty(Rect::new(0, 0, 8, 2));
+        buffer.set_string(0, 0, "tasks", Style::default().fg(Color::Green));
+        assert_eq!(render(&buffer, SnapshotFormat::Plain), "tasks\n\n");
+    }
+
+    #[test]
+    fn plain_skips_cells_covered_by_wide_chars() {
+        let mut buffer = Buffer::empty(Rect::new(0, 0, 8, 1));
+        buffer.set_string(0, 0, "\u{4E16}ok", Style::default());
+        assert_eq!(render(&buffer, SnapshotFormat::Plain), "\u{4E16}ok\n");
+    }
+
+    #[test]
+    fn ansi_resets_style_at_end_of_line() {
+        let mut buffer = Buffer::empty(Rect::new(0, 0, 4, 1));
+        buffer.set_string(0, 0, "ok", Style::default().add_modifier(Modifier::BOLD));
+        assert_eq!(
+            render(&buffer, SnapshotFormat::Ansi),
+            "\u{1b}[0m\u{1b}[39m\u{1b}[49m\u{1b}[1mok\u{1b}[0m\n"
+        );
+    }
+}
@@ -124,6 +124,18 @@ impl AsyncOpsState {
         self.async_ops.values().map(Rc::downgrade)
     }
 
+    /// Returns the async ops which the task with the given ID is currently
+    /// awaiting.
+    pub(crate) fn awaited_by(
+        &self,
+        task_id: Id<Task>,
+    ) -> impl Iterator<Item = &Rc<RefCell<AsyncOp>>> + '_ {
+        self.async_ops.values().filter(move |async_op| {
+            let async_op = async_op.borrow();
+            !async_op.dropped() && async_op.task_id() == Some(task_id)
+        })
+    }
+
     // Clippy warns us that having too many arguments is bad style. In this case, however
     // it does not make much sense to group any of them.
     #[allow(clippy::too_many_arguments)]
@@ -171,7 +183,7 @@ impl AsyncOpsState {
                     task_ids,
                 );
 
-                let id = ids.id_for(span_id);
+                let id = ids.id_for_new(span_id, stats.created_at);
                 let resource_id = resource_ids.id_for(async_op.resource_id?.id);
                 let parent_id = match async_op.parent_async_op_id {
                     Some(id) => strings.string(format!("{}", ids.id_for(id.id))),
@@ -0,0 +1,134 @@
+use crate::{state::tasks::Aggregates, view};
+use std::{
+    cmp,
+    collections::VecDeque,
+    time::{Duration, SystemTime},
+};
+
+/// The number of tasks which the remote has spawned over its lifetime but
+/// which haven't completed yet, and how it has changed recently.
+///
+/// Unlike the tasks the console knows about, this includes tasks spawned
+/// before the console connected, and tasks filtered out by `--task-level`,
+/// so it shows whether the remote is accumulating tasks as a whole.
+#[derive(Debug, Default)]
+pub(crate) struct LiveTasks {
+    /// How long the live tasks must have only grown for before they're
+    /// considered to be leaking, if leaks are warned about.
+    window: Option<Duration>,
+    /// The number of live tasks at each update, oldest first.
+    ///
+    /// Samples are kept for the window, or for the trend if that's longer,
+    /// along with the latest one from before then.
+    samples: VecDeque<(SystemTime, u64)>,
+}
+
+/// Which way the number of live tasks has gone over the last
+/// [`LiveTasks::TREND_OVER`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub(crate) enum Trend {
+    Rising,
+    Falling,
+    Steady,
+}
+
+// === impl LiveTasks ===
+
+impl LiveTasks {
+    /// How far back the live tasks are compared to for their trend.
+    pub(crate) const TREND_OVER: Duration = Duration::from_secs(10);
+
+    pub(crate) fn new(window: Option<Duration>) -> Self {
+        Self {
+            window,
+            samples: VecDeque::new(),
+        }
+    }
+
+    /// Records the number of live tasks according to the remote's lifetime
+    /// task counters at `now`.
+    pub(crate) fn record(&mut self, now: SystemTime, aggregates: &Aggregates) {
+        let live = aggregates
+            .total_spawned
+            .saturating_sub(aggregates.total_completed);
+        // the remote's clock is used, so a sample may be from before the
+        // last one if the remote restarted.
+        if matches!(self.samples.back(), Some((last, _)) if *last > now) {
+            self.samples.clear();
+        }
+        self.samples.push_back((now, live));
+
+        let kept_for = self.window.unwrap_or_default().max(Self::TREND_OVER);
+        let Some(start) = now.checked_sub(kept_for) else {
+            return;
+        };
+        while matches!(self.samples.get(1), Some((at, _)) if *at <= start) {
+            self.samples.pop_front();
+        }
+    }
+
+    /// Returns the number of live tasks as of the last update, if the remote
+    /// reports its lifetime task counters.
+    pub(crate) fn live(&self) -> Option<u64> {
+        self.samples.back().map(|&(_, live)| live)
+    }
+
+    pub(crate) fn trend(&self) -> Trend {
+        let Some(&(now, live)) = self.samples.back() else {
+            return Trend::Steady;
+        };
+        let before = self
+            .samples
+            .iter()
+            .rev()
+            .find(|(at, _)| now.duration_since(*at).unwrap_or_default() >= Self::TREND_OVER)
+            .or(self.samples.front())
+            .map_or(live, |&(_, live)| live);
+        match live.cmp(&before) {
+            cmp::Ordering::Greater => Trend::Rising,
+            cmp::Ordering::Less => Trend::Falling,
+            cmp::Ordering::Equal => Trend::Steady,
+        }
+    }
+
+    /// Returns how many more tasks are live than at the start of the leak
+    /// window, if the live tasks have grown without ever falling for all
+    /// of it.
+    ///
+    /// A task leak looks like this: tasks are spawned which never complete,
+    /// so the live tasks keep growing however the load on the remote varies.
+    pub(crate) fn leaked(&self) -> Option<u64> {
+        let window = self.window?;
+        let &(now, live) = self.samples.back()?;
+        let start = now.checked_sub(window)?;
+        // the latest sample from before the window is where it starts.
+        let first = self.samples.iter().rposition(|(at, _)| *at <= start)?;
+        let mut samples = self.samples.range(first..).map(|&(_, live)| live);
+        let started_with = samples.next()?;
+        let mut prev = started_with;
+        for live in samples {
+            if live < prev {
+                return None;
+            }
+            prev = live;
+        }
+        (live > started_with).then_some(live - started_with)
+    }
+
+    pub(crate) fn window(&self) -> Option<Duration> {
+        self.window
+    }
+}
+
+// === impl Trend ===
+
+impl Trend {
+    /// Returns an arrow pointing the way the live tasks are going.
+    pub(crate) fn arrow(self, styles: &view::Styles) -> &'static str {
+        match self {
+            Trend::Rising => styles.if_utf8("↑", "^"),
+            Trend::Falling => styles.if_utf8("↓", "v"),
+            Trend::Steady => styles.if_utf8("→", "-"),
+        }
+    }
+}
@@ -1,5 +1,13 @@
-use self::{async_ops::AsyncOpsState, resources::ResourcesState};
+use self::{
+    async_ops::AsyncOpsState,
+    live_tasks::LiveTasks,
+    quotas::Quotas,
+    resources::{Resource, ResourcesState},
+    spans::SpansState,
+    workers::WorkersState,
+};
 use crate::{
+    config::Quota,
     intern::{self, InternedStr},
     view,
     warnings::Linter,
@@ -16,15 +24,29 @@ use std::{
     convert::{TryFrom, TryInto},
     fmt,
     rc::Rc,
-    time::{Duration, SystemTime},
+    time::{Duration, Instant, SystemTime},
 };
-use tasks::{Details, Task, TasksState};
+use tasks::{Details, DetailsEnded, Task, TasksState, ThreadPolls};
+use tracing_subscriber::filter::LevelFilter;
 
 pub mod async_ops;
 pub mod histogram;
+pub mod live_tasks;
+pub mod quotas;
 pub mod resources;
+pub mod select_stats;
+pub mod spans;
 pub mod store;
+pub mod targets;
+pub mod task_groups;
 pub mod tasks;
+pub mod timer_stats;
+pub mod wake_graph;
+pub mod waker_leaks;
+pub mod workers;
+
+#[cfg(test)]
+pub(crate) mod recordings;
 
 pub(crate) use self::store::Id;
 
@@ -35,11 +57,29 @@ pub(crate) struct State {
     metas: HashMap<u64, Metadata>,
     last_updated_at: Option<SystemTime>,
     temporality: Temporality,
+    /// How long the aggregator was paused for, and when we learned that it
+    /// had resumed.
+    last_pause: Option<(Duration, Instant)>,
+    /// Whether the remote has stopped sending the full state, because the
+    /// updates exceeded its bandwidth limit for each client.
+    degraded: bool,
+    /// Whether the remote refuses to be paused or resumed.
+    read_only: bool,
+    /// Whether the remote was built without `tokio_unstable`, so its tasks
+    /// can't be instrumented.
+    without_tokio_unstable: bool,
+    /// How many times the remote's tracing layer has panicked.
+    layer_panics: u64,
+    /// The memory the remote last reported using, if it reports it.
+    memory: Option<proto::instrument::MemoryUsage>,
     tasks_state: TasksState,
     resources_state: ResourcesState,
     async_ops_state: AsyncOpsState,
+    workers_state: WorkersState,
+    spans_state: SpansState,
     current_task_details: DetailsRef,
     retain_for: Option<Duration>,
+    max_completed_tasks: Option<usize>,
     strings: intern::Strings,
 }
 
@@ -50,16 +90,32 @@ pub(crate) enum Visibility {
 
 #[derive(Debug)]
 pub(crate) struct Metadata {
+    name: InternedStr,
     field_names: Vec<InternedStr>,
     target: InternedStr,
+    level: tracing::Level,
     id: u64,
     //TODO: add more metadata as needed
 }
 
+/// Where something was spawned or created, as the compiler reported it.
+///
+/// Unlike the formatted location, the file is never shortened, so that it
+/// can be linked to.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub(crate) struct SourceLocation {
+    pub(crate) file: String,
+    pub(crate) line: Option<u32>,
+    pub(crate) column: Option<u32>,
+}
+
 #[derive(Debug, Eq, PartialEq)]
 pub(crate) struct Field {
     pub(crate) name: InternedStr,
     pub(crate) value: FieldValue,
+    /// Whether the subscriber truncated this field's value because it was
+    /// longer than its configured maximum.
+    pub(crate) truncated: bool,
 }
 
 #[derive(Debug, Eq, PartialEq, Ord, PartialOrd)]
@@ -101,11 +157,35 @@ pub(crate) struct Attribute {
 }
 
 impl State {
+    /// How long to show how long the aggregator was paused for, after it
+    /// resumes.
+    const LAST_PAUSE_SHOWN_FOR: Duration = Duration::from_secs(5);
+
     pub(crate) fn with_retain_for(mut self, retain_for: Option<Duration>) -> Self {
         self.retain_for = retain_for;
         self
     }
 
+    pub(crate) fn with_max_completed_tasks(mut self, max_completed_tasks: Option<usize>) -> Self {
+        self.max_completed_tasks = max_completed_tasks;
+        self
+    }
+
+    pub(crate) fn with_task_level(mut self, level: LevelFilter) -> Self {
+        self.tasks_state.level = Some(level);
+        self
+    }
+
+    pub(crate) fn with_quotas(mut self, quotas: Vec<Quota>) -> Self {
+        self.tasks_state.quotas = Quotas::new(quotas);
+        self
+    }
+
+    pub(crate) fn with_leak_window(mut self, window: Duration) -> Self {
+        self.tasks_state.live_tasks = LiveTasks::new(Some(window));
+        self
+    }
+
     pub(crate) fn with_task_linters(
         mut self,
         linters: impl IntoIterator<Item = Linter<Task>>,
@@ -114,6 +194,14 @@ impl State {
         self
     }
 
+    pub(crate) fn with_resource_linters(
+        mut self,
+        linters: impl IntoIterator<Item = Linter<Resource>>,
+    ) -> Self {
+        self.resources_state.linters.extend(linters);
+        self
+    }
+
     pub(crate) fn last_updated_at(&self) -> Option<SystemTime> {
         self.last_updated_at
     }
@@ -127,6 +215,7 @@ impl State {
         if let Some(now) = update.now.map(|v| v.try_into().unwrap()) {
             self.last_updated_at = Some(now);
         }
+        self.degraded = update.degraded;
 
         let strings = &mut self.strings;
         if let Some(new_metadata) = update.new_metadata {
@@ -150,7 +239,12 @@ impl State {
                 &self.metas,
                 tasks_update,
                 visibility,
-            )
+            );
+            if let (Some(now), Some(aggregates)) =
+                (self.last_updated_at, self.tasks_state.aggregates())
+            {
+                self.tasks_state.live_tasks.record(now, &aggregates);
+            }
         }
 
         if let Some(resources_update) = update.resource_update {
@@ -182,7 +276,36 @@ impl State {
                 self.resources_state.ids_mut(),
                 self.tasks_state.ids_mut(),
                 visibility,
-            )
+            );
+
+            if let Some(now) = self.last_updated_at {
+                self.resources_state
+                    .update_contention(&self.async_ops_state, now);
+            }
+        }
+
+        if let Some(span_update) = update.span_update {
+            let visibility = if matches!(current_view, view::ViewState::SpansList) {
+                Visibility::Show
+            } else {
+                Visibility::Hide
+            };
+            self.spans_state.update_spans(
+                styles,
+                &mut self.strings,
+                &self.metas,
+                span_update,
+                self.tasks_state.ids_mut(),
+                visibility,
+            );
+        }
+
+        if let Some(runtime_update) = update.runtime_update {
+            self.workers_state.update(
+                runtime_update,
+                self.tasks_state.ids_mut(),
+                self.last_updated_at,
+            );
         }
     }
 
@@ -195,6 +318,10 @@ impl State {
             self.tasks_state.retain_active(now, retain_for);
             self.resources_state.retain_active(now, retain_for);
             self.async_ops_state.retain_active(now, retain_for);
+            self.spans_state.retain_active(now, retain_for);
+        }
+        if let Some(max) = self.max_completed_tasks {
+            self.tasks_state.retain_recently_completed(max);
         }
 
         // After dropping idle tasks & resources, prune any interned strings
@@ -206,7 +333,7 @@ impl State {
         self.current_task_details.clone()
     }
 
-    pub(crate) fn tasks_state(&mut self) -> &TasksState {
+    pub(crate) fn tasks_state(&self) -> &TasksState {
         &self.tasks_state
     }
 
@@ -214,7 +341,7 @@ impl State {
         &mut self.tasks_state
     }
 
-    pub(crate) fn resources_state(&mut self) -> &ResourcesState {
+    pub(crate) fn resources_state(&self) -> &ResourcesState {
         &self.resources_state
     }
 
@@ -230,8 +357,30 @@ impl State {
         &mut self.async_ops_state
     }
 
+    pub(crate) fn workers_state(&self) -> &WorkersState {
+        &self.workers_state
+    }
+
+    pub(crate) fn workers_state_mut(&mut self) -> &mut WorkersState {
+        &mut self.workers_state
+    }
+
+    pub(crate) fn spans_state(&self) -> &SpansState {
+        &self.spans_state
+    }
+
+    pub(crate) fn spans_state_mut(&mut self) -> &mut SpansState {
+        &mut self.spans_state
+    }
+
     pub(crate) fn update_task_details(&mut self, update: proto::tasks::TaskDetails) {
         if let Some(id) = update.task_id {
+            let mut thread_polls = update
+                .thread_polls
+                .into_iter()
+                .map(ThreadPolls::from)
+                .collect::<Vec<_>>();
+            thread_polls.sort_unstable_by_key(|thread| std::cmp::Reverse(thread.polls));
             let details = Details {
                 span_id: id.id,
                 poll_times_histogram: update
@@ -242,16 +391,51 @@ impl State {
                     .scheduled_times_histogram
                     .as_ref()
                     .and_then(histogram::DurationHistogram::from_proto),
+                wake_intervals_histogram: update
+                    .wake_intervals_histogram
+                    .as_ref()
+                    .and_then(histogram::DurationHistogram::from_proto),
+                thread_polls,
+                other_thread_polls: update.other_thread_polls,
+                ended: None,
             };
 
             *self.current_task_details.borrow_mut() = Some(details);
         }
     }
 
+    /// Records why the remote stopped sending the details of the task with
+    /// the given span ID.
+    pub(crate) fn end_task_details(&mut self, span_id: u64, ended: DetailsEnded) {
+        let mut details = self.current_task_details.borrow_mut();
+        match details.as_mut() {
+            Some(details) if details.span_id == span_id => details.ended = Some(ended),
+            _ => {
+                *details = Some(Details {
+                    span_id,
+                    poll_times_histogram: None,
+                    scheduled_times_histogram: None,
+                    wake_intervals_histogram: None,
+                    thread_polls: Vec::new(),
+                    other_thread_polls: 0,
+                    ended: Some(ended),
+                })
+            }
+        }
+    }
+
     pub(crate) fn unset_task_details(&mut self) {
         *self.current_task_details.borrow_mut() = None;
     }
 
+    pub(crate) fn update_task_groups(&mut self, update: proto::tasks::TaskGroups) {
+        self.tasks_state.groups_mut().update_poll_times(update);
+    }
+
+    pub(crate) fn unset_task_groups(&mut self) {
+        self.tasks_state.groups_mut().unset_poll_times();
+    }
+
     // temporality methods
     pub(crate) fn temporality(&self) -> &Temporality {
         &self.temporality
@@ -266,9 +450,59 @@ impl State {
     }
 
     pub(crate) fn update_state(&mut self, state: proto::instrument::State) {
+        let was_paused = matches!(
+            self.temporality,
+            Temporality::Paused | Temporality::Unpausing
+        );
+        self.read_only = state.read_only;
+        self.without_tokio_unstable = state.tokio_unstable == Some(false);
+        self.layer_panics = state.layer_panics;
+        self.memory = state.memory;
         self.temporality = proto::instrument::Temporality::try_from(state.temporality)
             .expect("invalid temporality")
             .into();
+        if was_paused && matches!(self.temporality, Temporality::Live) {
+            self.last_pause = state
+                .last_pause
+                .and_then(|pause| pause.try_into().ok())
+                .map(|pause| (pause, Instant::now()));
+        }
+    }
+
+    /// Returns how long the aggregator was paused for, if it resumed
+    /// recently.
+    pub(crate) fn last_pause(&self) -> Option<Duration> {
+        let (paused_for, resumed_at) = self.last_pause?;
+        (resumed_at.elapsed() < Self::LAST_PAUSE_SHOWN_FOR).then_some(paused_for)
+    }
+
+    /// Returns whether the remote is only sending summaries, rather than
+    /// the tasks, resources, and async ops themselves.
+    pub(crate) fn is_degraded(&self) -> bool {
+        self.degraded
+    }
+
+    /// Returns whether the remote refuses to be paused or resumed.
+    pub(crate) fn is_read_only(&self) -> bool {
+        self.read_only
+    }
+
+    /// Returns whether the remote was built without `tokio_unstable`, which
+    /// Tokio requires to instrument tasks.
+    pub(crate) fn is_without_tokio_unstable(&self) -> bool {
+        self.without_tokio_unstable
+    }
+
+    /// Returns whether the remote's tracing layer has panicked, after which
+    /// nothing it sends is updated any more.
+    pub(crate) fn has_layer_panicked(&self) -> bool {
+        self.layer_panics > 0
+    }
+
+    /// Returns the memory the remote last reported using for the tasks and
+    /// histograms it records, if it reports it.
+    pub(crate) fn server_memory(&self) -> Option<&proto::instrument::MemoryUsage> {
+        self.memory.as_ref()
     }
 
     pub(crate) fn is_paused(&self) -> bool {
@@ -279,6 +513,8 @@ impl State {
 impl Metadata {
     fn from_proto(pb: proto::Metadata, id: u64, strings: &mut intern::Strings) -> Self {
         Self {
+            level: pb.level().into(),
+            name: strings.string(pb.name),
             field_names: pb
                 .field_names
                 .into_iter()
@@ -302,7 +538,11 @@ impl Field {
 
     /// Creates a new Field with a pre-interned `name` and a `FieldValue`.
     fn new(name: InternedStr, value: FieldValue) -> Self {
-        Field { name, value }
+        Field {
+            name,
+            value,
+            truncated: false,
+        }
     }
 
     /// Converts a wire-format `Field` into an internal `Field` representation,
@@ -316,6 +556,7 @@ impl Field {
             name,
             metadata_id,
             value,
+            truncated,
         }: proto::Field,
         meta: &Metadata,
         strings: &mut intern::Strings,
@@ -372,34 +613,40 @@ impl Field {
             value = value.truncate_registry_path();
         }
 
-        Some(Self { name, value })
+        Some(Self {
+            name,
+            value,
+            truncated,
+        })
     }
 
     fn make_formatted(styles: &view::Styles, fields: &mut [Field]) -> Vec<Vec<Span<'static>>> {
         let key_style = styles.fg(Color::LightBlue).add_modifier(Modifier::BOLD);
         let delim_style = styles.fg(Color::LightBlue).add_modifier(Modifier::DIM);
         let val_style = styles.fg(Color::Yellow);
+        let truncated_style = styles.fg(Color::Yellow).add_modifier(Modifier::DIM);
 
         fields.sort_unstable();
 
-        let mut formatted = Vec::with_capacity(fields.len());
-        let mut fields = fields.iter();
-        if let Some(field) = fields.next() {
-            formatted.push(vec![
-                Span::styled(field.name.to_string(), key_style),
-                Span::styled("=", delim_style),
-                Span::styled(format!("{} ", field.value), val_style),
-            ]);
-            for field in fields {
-                formatted.push(vec![
-                    // Span::styled(", ", delim_style),
+        fields
+            .iter()
+            .map(|field| {
+                let mut elems = vec![
                     Span::styled(field.name.to_string(), key_style),
                     Span::styled("=", delim_style),
-                    Span::styled(format!("{} ", field.value), val_style),
-                ])
-            }
-        }
-        formatted
+                ];
+                if field.truncated {
+                    elems.push(Span::styled(field.value.to_string(), val_style));
+                    elems.push(Span::styled(
+                        format!("{}truncated ", styles.if_utf8("\u{2026}", "...")),
+                        truncated_style,
+                    ));
+                } else {
+                    elems.push(Span::styled(format!("{} ", field.value), val_style));
+                }
+                elems
+            })
+            .collect()
     }
 }
 
@@ -575,6 +822,19 @@ fn format_location(loc: Option<proto::Location>) -> String {
     .unwrap_or_else(|| "<unknown location>".to_string())
 }
 
+// === impl SourceLocation ===
+
+impl SourceLocation {
+    /// Returns the location `loc` describes, if it includes a file.
+    fn from_proto(loc: &proto::Location) -> Option<Self> {
+        Some(Self {
+            file: loc.file.clone()?,
+            line: loc.line,
+            column: loc.column,
+        })
+    }
+}
+
 fn pb_duration(dur: prost_types::Duration) -> Duration {
     let secs = u64::try_from(dur.seconds).expect("duration should not be negative!");
     let nanos = u64::try_from(dur.nanos).expect("duration should not be negative!");
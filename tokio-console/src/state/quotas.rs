@@ -0,0 +1,70 @@
+use crate::{config::Quota, state::tasks::Task};
+use std::{cell::RefCell, rc::Rc};
+
+/// The quotas set in the config file, and how many of the tasks each one
+/// applies to were running as of the last update.
+#[derive(Debug, Default)]
+pub(crate) struct Quotas {
+    quotas: Vec<Quota>,
+    running: Vec<usize>,
+}
+
+// === impl Quotas ===
+
+impl Quotas {
+    pub(crate) fn new(quotas: Vec<Quota>) -> Self {
+        let running = vec![0; quotas.len()];
+        Self { quotas, running }
+    }
+
+    /// Counts how many of the tasks each quota applies to are running.
+    pub(crate) fn count<'a>(&mut self, tasks: impl Iterator<Item = &'a Rc<RefCell<Task>>>) {
+        if self.quotas.is_empty() {
+            return;
+        }
+
+        self.running.iter_mut().for_each(|running| *running = 0);
+        for task in tasks {
+            let task = task.borrow();
+            if task.is_completed() {
+                continue;
+            }
+            for (quota, running) in self.quotas.iter().zip(&mut self.running) {
+                if applies_to(quota, &task) {
+                    *running += 1;
+                }
+            }
+        }
+    }
+
+    /// Returns each quota which more tasks are running than allowed, with
+    /// how many are running.
+    pub(crate) fn exceeded(&self) -> impl Iterator<Item = (&Quota, usize)> + '_ {
+        self.quotas
+            .iter()
+            .zip(self.running.iter().copied())
+            .filter(|(quota, running)| *running > quota.max_tasks)
+    }
+
+    /// Returns whether more tasks with `target` are running than its quota
+    /// allows.
+    pub(crate) fn is_target_exceeded(&self, target: &str) -> bool {
+        self.exceeded()
+            .any(|(quota, _)| quota.target.as_deref() == Some(target))
+    }
+
+    /// Returns whether more tasks spawned from `location` are running than
+    /// its quota allows.
+    pub(crate) fn is_location_exceeded(&self, location: &str) -> bool {
+        self.exceeded()
+            .any(|(quota, _)| quota.location.as_deref() == Some(location))
+    }
+}
+
+fn applies_to(quota: &Quota, task: &Task) -> bool {
+    match (&quota.target, &quota.location) {
+        (Some(target), _) => task.target() == target,
+        (None, Some(location)) => task.location() == location,
+        (None, None) => false,
+    }
+}
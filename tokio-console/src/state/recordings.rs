@@ -0,0 +1,720 @@
+//! Tests which replay recorded updates into the console's state, and check
+//! what its tables would show.
+//!
+//! Each recording in `tests/recordings` is a JSON array of
+//! [`proto::instrument::Update`]s, as serialized by `console-api`'s `serde`
+//! feature. Fields which are missing from a message have their default
+//! values, so recordings only need to include the fields a test depends on.
+
+use super::{
+    live_tasks::Trend,
+    targets::{self, Target, TargetRef},
+    task_groups::{self, TaskGroupRef},
+    tasks::{self, Task, TaskRef, TaskState, Termination},
+    wake_graph::{self, WakeEdge, WakeEdgeRef},
+    waker_leaks::{self, WakerLeakRef},
+    workers::{self, Worker},
+    State,
+};
+use crate::{
+    config::{Quota, ViewOptions},
+    i18n::Lang,
+    view,
+    warnings::{Linter, LostWaker, NeverYielded, SelfWakePercent, UnbalancedWakers},
+};
+use console_api as proto;
+use std::{
+    cell::RefCell,
+    collections::VecDeque,
+    fs,
+    path::PathBuf,
+    rc::Rc,
+    time::{Duration, SystemTime},
+};
+use tracing_subscriber::filter::LevelFilter;
+
+/// Loads the updates in `tests/recordings/{recording}.json`.
+pub(crate) fn load(recording: &str) -> VecDeque<proto::instrument::Update> {
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join("recordings")
+        .join(format!("{recording}.json"));
+    let json = fs::read_to_string(&path)
+        .unwrap_or_else(|error| panic!("failed to read {}: {error}", path.display()));
+    serde_json::from_str(&json)
+        .unwrap_or_else(|error| panic!("invalid recording {}: {error}", path.display()))
+}
+
+/// Returns an empty state, which checks tasks for the warnings the recordings
+/// exercise.
+pub(crate) fn state() -> State {
+    State::default().with_task_linters([
+        Linter::new(SelfWakePercent::default()),
+        Linter::new(LostWaker),
+        Linter::new(NeverYielded::default()),
+    ])
+}
+
+/// Replays the updates in a recording, one at a time.
+struct Replay {
+    state: State,
+    styles: view::Styles,
+    updates: VecDeque<proto::instrument::Update>,
+    /// The tasks in the tasks table, in the order they were added.
+    tasks: Vec<TaskRef>,
+    /// The threads in the workers table, in the order they were added.
+    workers: Vec<workers::WorkerRef>,
+    /// The edges in the task wakes table, in the order they were added.
+    wake_edges: Vec<WakeEdgeRef>,
+    /// The targets in the targets table, in the order they were added.
+    targets: Vec<TargetRef>,
+    /// The tasks in the waker leaks table, in the order they were added.
+    waker_leaks: Vec<WakerLeakRef>,
+}
+
+impl Replay {
+    fn new(recording: &str) -> Self {
+        Self {
+            state: state(),
+            styles: view::Styles::from_config(ViewOptions::default()),
+            updates: load(recording),
+            tasks: Vec::new(),
+            workers: Vec::new(),
+            wake_edges: Vec::new(),
+            targets: Vec::new(),
+            waker_leaks: Vec::new(),
+        }
+    }
+
+    /// Applies the next update in the recording, as the tasks list would.
+    fn next(&mut self) {
+        let update = self
+            .updates
+            .pop_front()
+            .expect("the recording has no more updates");
+        self.state
+            .update(&self.styles, &view::ViewState::TasksList, update);
+        self.tasks
+            .extend(self.state.tasks_state_mut().take_new_tasks());
+        self.workers
+            .extend(self.state.workers_state_mut().take_new_workers());
+        self.wake_edges.extend(
+            self.state
+                .tasks_state_mut()
+                .wake_graph_mut()
+                .take_new_edges(),
+        );
+        self.targets.extend(
+            self.state
+                .tasks_state_mut()
+                .targets_mut()
+                .take_new_targets(),
+        );
+        self.waker_leaks.extend(
+            self.state
+                .tasks_state_mut()
+                .waker_leaks_mut()
+                .take_new_leaks(),
+        );
+    }
+
+    /// Returns the names of the tasks in the tasks table, sorted by `sort_by`.
+    fn task_names(&mut self, sort_by: tasks::SortBy) -> Vec<String> {
+        let now = self.state.last_updated_at().expect("no update has a time");
+        sort_by.sort(now, &mut self.tasks);
+        self.tasks
+            .iter()
+            .filter_map(|task| task.upgrade())
+            .map(|task| task.borrow().name().unwrap_or_default().to_owned())
+            .collect()
+    }
+
+    /// Returns the task named `name`.
+    fn task<T>(&self, name: &str, f: impl FnOnce(&Task) -> T) -> T {
+        let task = self
+            .state
+            .tasks_state()
+            .find(name)
+            .unwrap_or_else(|| panic!("no task named {name}"));
+        let task = task.borrow();
+        f(&task)
+    }
+
+    /// Returns the summaries of the warnings on the warnings view, and how
+    /// many tasks each applies to.
+    fn task_warnings(&self) -> Vec<(String, usize)> {
+        self.state
+            .tasks_state()
+            .warnings()
+            .map(|linter| (linter.summary(Lang::default()), linter.count()))
+            .collect()
+    }
+
+    /// Returns the workers table, sorted by `sort_by`, after removing the
+    /// threads which have exited.
+    fn workers<T>(&mut self, sort_by: workers::SortBy, f: impl Fn(&Worker) -> T) -> Vec<T> {
+        self.workers.retain(|worker| worker.upgrade().is_some());
+        sort_by.sort(&mut self.workers);
+        self.workers
+            .iter()
+            .filter_map(|worker| worker.upgrade())
+            .map(|worker| f(&worker.borrow()))
+            .collect()
+    }
+
+    /// Returns the task wakes table as `(waker, woken, ...)`, sorted by
+    /// `sort_by`.
+    fn wake_edges<T>(
+        &mut self,
+        sort_by: wake_graph::SortBy,
+        f: impl Fn(&WakeEdge) -> T,
+    ) -> Vec<(String, String, T)> {
+        let name = |task: Option<Rc<RefCell<Task>>>| {
+            let task = task.expect("the edge's task was removed");
+            let name = task.borrow().name().unwrap_or_default().to_owned();
+            name
+        };
+        sort_by.sort(&mut self.wake_edges);
+        self.wake_edges
+            .iter()
+            .filter_map(|edge| edge.upgrade())
+            .map(|edge| {
+                let edge = edge.borrow();
+                (name(edge.waker()), name(edge.woken()), f(&edge))
+            })
+            .collect()
+    }
+
+    /// Returns the targets table, sorted by `sort_by`.
+    fn targets<T>(&mut self, sort_by: targets::SortBy, f: impl Fn(&Target) -> T) -> Vec<T> {
+        let now = self.state.last_updated_at().expect("no update has a time");
+        sort_by.sort(now, &mut self.targets);
+        self.targets
+            .iter()
+            .filter_map(|target| target.upgrade())
+            .map(|target| f(&target.borrow()))
+            .collect()
+    }
+}
+
+#[test]
+fn tasks_table_sorts_tasks() {
+    let mut replay = Replay::new("tasks");
+    replay.next();
+    assert_eq!(
+        replay.task_names(tasks::SortBy::Polls),
+        ["conn", "listener", "spinner"]
+    );
+    assert_eq!(
+        replay.task_names(tasks::SortBy::Name),
+        ["conn", "listener", "spinner"]
+    );
+    assert_eq!(
+        replay.task_names(tasks::SortBy::Busy),
+        ["conn", "listener", "spinner"]
+    );
+    assert_eq!(
+        replay.task_names(tasks::SortBy::MaxPoll),
+        ["conn", "spinner", "listener"]
+    );
+    assert_eq!(
+        replay.task_names(tasks::SortBy::LastWake),
+        ["listener", "conn", "spinner"]
+    );
+
+    // The listener was busy for most of the second interval.
+    replay.next();
+    assert_eq!(
+        replay.task_names(tasks::SortBy::Busy),
+        ["conn", "spinner", "listener"]
+    );
+    assert_eq!(
+        replay.task("listener", |task| task.longest_poll(SystemTime::UNIX_EPOCH)),
+        Some((
+            Duration::from_millis(400),
+            SystemTime::UNIX_EPOCH + Duration::from_millis(1_704_067_201_500)
+        ))
+    );
+    assert_eq!(
+        replay.task_names(tasks::SortBy::State),
+        ["conn", "listener", "spinner"]
+    );
+}
+
+#[test]
+fn tasks_are_found_by_name_or_id() {
+    let mut replay = Replay::new("tasks");
+    replay.next();
+    let name = |id_or_name: &str| {
+        let task = replay.state.tasks_state().find(id_or_name)?;
+        let name = task.borrow().name().map(str::to_owned);
+        name
+    };
+    assert_eq!(name("conn").as_deref(), Some("conn"));
+    assert_eq!(name("3").as_deref(), Some("spinner"));
+    assert_eq!(name("4"), None);
+    assert_eq!(name("listen"), None);
+}
+
+#[test]
+fn tasks_table_shows_task_state() {
+    let mut replay = Replay::new("tasks");
+    replay.next();
+    assert_eq!(replay.task("listener", Task::state), TaskState::Idle);
+    assert_eq!(replay.task("conn", Task::state), TaskState::Idle);
+    assert_eq!(replay.task("spinner", Task::state), TaskState::Scheduled);
+    assert_eq!(
+        replay.task("listener", |task| task.id_str().to_owned()),
+        "1"
+    );
+    assert_eq!(replay.task("listener", Task::total_polls), 5);
+    assert_eq!(replay.task("spinner", Task::self_wake_percent), 80);
+
+    replay.next();
+    assert_eq!(replay.task("conn", Task::state), TaskState::Completed);
+    assert_eq!(
+        replay.task("conn", Task::termination),
+        Some(Termination::Returned)
+    );
+    assert_eq!(replay.task("listener", Task::termination), None);
+    assert_eq!(replay.task("listener", Task::total_polls), 8);
+    assert_eq!(replay.task("listener", Task::wakes_per_poll), 1.0);
+    let now = replay.state.last_updated_at().unwrap();
+    assert_eq!(
+        replay.task("listener", |task| task.busy(now)),
+        Duration::from_millis(1100)
+    );
+    assert_eq!(
+        replay.task("conn", |task| task.total(now)),
+        Duration::from_secs(1)
+    );
+}
+
+const SELF_WAKES: &str = "tasks have woken themselves over 50% of the time";
+const LOST_WAKER: &str = "tasks have lost their wakers";
+
+#[test]
+fn warnings_follow_task_stats() {
+    let mut replay = Replay::new("tasks");
+    replay.next();
+    assert_eq!(
+        replay.task_warnings(),
+        [(SELF_WAKES.to_owned(), 1), (LOST_WAKER.to_owned(), 1)]
+    );
+    assert_eq!(replay.task("conn", |task| task.warnings().len()), 1);
+    assert_eq!(replay.task("spinner", |task| task.warnings().len()), 1);
+    assert_eq!(replay.task("listener", |task| task.warnings().len()), 0);
+
+    // Once `conn` completes, it's no longer waiting for a waker.
+    replay.next();
+    assert_eq!(replay.task_warnings(), [(SELF_WAKES.to_owned(), 1)]);
+    assert_eq!(replay.task("conn", |task| task.warnings().len()), 0);
+}
+
+#[test]
+fn acknowledged_warnings_are_not_reported() {
+    let mut replay = Replay::new("tasks");
+    replay.next();
+    let unacknowledged = |replay: &Replay| {
+        replay
+            .state
+            .tasks_state()
+            .unacknowledged_warnings()
+            .map(|(linter, count)| (linter.summary(Lang::default()), count))
+            .collect::<Vec<_>>()
+    };
+
+    // Acknowledging `spinner`'s warning leaves the rest of its lint.
+    let spinner = replay.task("spinner", Task::id);
+    let tasks = replay.state.tasks_state_mut();
+    tasks.acknowledged.toggle_warning(0, spinner);
+    assert_eq!(unacknowledged(&replay), [(LOST_WAKER.to_owned(), 1)]);
+
+    // Acknowledging a whole lint hides all of its warnings.
+    let tasks = replay.state.tasks_state_mut();
+    tasks.acknowledged.toggle_warning(0, spinner);
+    tasks.acknowledged.toggle_lint(1);
+    assert_eq!(unacknowledged(&replay), [(SELF_WAKES.to_owned(), 1)]);
+
+    // The warnings themselves are still tracked.
+    assert_eq!(
+        replay.task_warnings(),
+        [(SELF_WAKES.to_owned(), 1), (LOST_WAKER.to_owned(), 1)]
+    );
+}
+
+#[test]
+fn wake_graph_tracks_wakes_between_tasks() {
+    let mut replay = Replay::new("tasks");
+    replay.next();
+    assert_eq!(
+        replay.wake_edges(wake_graph::SortBy::Wakes, |edge| (
+            edge.wakes(),
+            edge.recent_wakes(),
+            edge.share()
+        )),
+        [
+            ("listener".to_owned(), "conn".to_owned(), (1, 1, 100)),
+            ("listener".to_owned(), "spinner".to_owned(), (2, 2, 20)),
+        ]
+    );
+
+    // `spinner` wasn't updated, so its edge has no recent wakes, and `conn`
+    // has now woken `listener`.
+    replay.next();
+    assert_eq!(
+        replay.wake_edges(wake_graph::SortBy::Woken, WakeEdge::recent_wakes),
+        [
+            ("conn".to_owned(), "listener".to_owned(), 1),
+            ("listener".to_owned(), "conn".to_owned(), 0),
+            ("listener".to_owned(), "spinner".to_owned(), 0),
+        ]
+    );
+}
+
+#[test]
+fn workers_table_tracks_threads() {
+    let mut replay = Replay::new("workers");
+    replay.next();
+    assert_eq!(
+        replay.workers(workers::SortBy::Worker, |worker| (
+            worker.name().to_owned(),
+            worker.index()
+        )),
+        [
+            ("blocking".to_owned(), None),
+            ("tokio-runtime-worker".to_owned(), Some(1)),
+            ("tokio-runtime-worker".to_owned(), Some(0)),
+        ]
+    );
+    assert!(replay.state.workers_state().has_runtime_metrics());
+    assert_eq!(
+        replay.workers(workers::SortBy::Worker, |worker| worker.scheduler()),
+        [None, Some("multi_thread"), Some("multi_thread")]
+    );
+    // Rates need two updates.
+    assert_eq!(
+        replay.workers(workers::SortBy::Worker, Worker::park_rate),
+        [None, None, None]
+    );
+
+    // The blocking thread has exited, and the first worker is polling the
+    // listener.
+    replay.next();
+    assert_eq!(
+        replay.workers(workers::SortBy::Polls, |worker| (
+            worker.index(),
+            worker.recent_polls(),
+            worker.park_rate(),
+            worker.unpark_rate(),
+        )),
+        [
+            (Some(1), 5, Some(1.0), Some(1.0)),
+            (Some(0), 30, Some(2.0), Some(3.0)),
+        ]
+    );
+    let listener = replay.task("listener", Task::id);
+    assert_eq!(
+        replay.workers(workers::SortBy::Task, Worker::current_task),
+        [None, Some(listener)]
+    );
+}
+
+#[test]
+fn targets_table_groups_tasks_by_target() {
+    let mut replay = Replay::new("targets");
+    replay.next();
+    let now = replay.state.last_updated_at().unwrap();
+    let summary = |target: &Target| {
+        (
+            target.target().to_owned(),
+            target.len(),
+            target.busy(now),
+            target.total_polls(),
+        )
+    };
+    assert_eq!(
+        replay.targets(targets::SortBy::Busy, summary),
+        [
+            ("app::net".to_owned(), 2, Duration::from_millis(300), 15),
+            ("app::jobs".to_owned(), 1, Duration::from_millis(700), 20),
+        ]
+    );
+    assert_eq!(
+        replay.targets(targets::SortBy::Target, |target| target.target().to_owned()),
+        ["app::jobs", "app::net"]
+    );
+}
+
+#[test]
+fn task_groups_group_tasks_by_spawn_location() {
+    let mut replay = Replay::new("groups");
+    replay.state = state().with_max_completed_tasks(Some(0));
+    replay.next();
+    let now = replay.state.last_updated_at().unwrap();
+    let mut groups = replay
+        .state
+        .tasks_state_mut()
+        .groups_mut()
+        .take_new_groups()
+        .collect::<Vec<_>>();
+    let summaries = |groups: &mut Vec<TaskGroupRef>| {
+        groups.retain(|group| group.upgrade().is_some());
+        task_groups::SortBy::Location.sort(now, groups);
+        groups
+            .iter()
+            .filter_map(|group| group.upgrade())
+            .map(|group| {
+                let group = group.borrow();
+                (
+                    group.location().to_owned(),
+                    group.len(),
+                    group.busy(now),
+                    group.total_polls(),
+                )
+            })
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(
+        summaries(&mut groups),
+        [
+            (
+                "src/main.rs:10:5".to_owned(),
+                1,
+                Duration::from_millis(100),
+                5
+            ),
+            (
+                "src/server.rs:20:9".to_owned(),
+                2,
+                Duration::from_millis(900),
+                30
+            ),
+        ]
+    );
+
+    // Once all of a group's tasks are gone, so is the group.
+    replay.next();
+    replay.state.retain_active();
+    assert_eq!(
+        summaries(&mut groups),
+        [(
+            "src/main.rs:10:5".to_owned(),
+            1,
+            Duration::from_millis(100),
+            5
+        )]
+    );
+}
+
+#[test]
+fn only_the_most_recently_completed_tasks_are_kept() {
+    let mut replay = Replay::new("tasks");
+    replay.state = state().with_max_completed_tasks(Some(1));
+    replay.next();
+    replay.next();
+    replay.state.retain_active();
+    assert_eq!(
+        replay.task_names(tasks::SortBy::Name),
+        ["conn", "listener", "spinner"]
+    );
+
+    let mut replay = Replay::new("tasks");
+    replay.state = state().with_max_completed_tasks(Some(0));
+    replay.next();
+    replay.next();
+    replay.state.retain_active();
+    // Tasks which are still running are kept, however many there are.
+    assert_eq!(
+        replay.task_names(tasks::SortBy::Name),
+        ["listener", "spinner"]
+    );
+    assert!(replay.state.tasks_state().find("conn").is_none());
+}
+
+#[test]
+fn historical_tasks_outlast_the_retention() {
+    let mut replay = Replay::new("tasks");
+    replay.state = state().with_retain_for(Some(Duration::ZERO));
+    replay.next();
+    replay.next();
+    replay.state.retain_active();
+    assert!(replay.state.tasks_state().find("conn").is_none());
+
+    // The subscriber only sends completed tasks it kept past their
+    // retention to new clients, so they're kept however long ago they ended.
+    let mut replay = Replay::new("tasks");
+    replay.state = state().with_retain_for(Some(Duration::ZERO));
+    let new_tasks = &mut replay.updates[0].task_update.as_mut().unwrap().new_tasks;
+    new_tasks
+        .iter_mut()
+        .filter(|task| task.id.as_ref().is_some_and(|id| id.id == 102))
+        .for_each(|task| task.historical = true);
+    replay.next();
+    replay.next();
+    replay.state.retain_active();
+    assert!(replay.task("conn", Task::is_historical));
+    assert!(!replay.task("spinner", Task::is_historical));
+}
+
+#[test]
+fn negative_waker_counts_are_flagged() {
+    let mut replay = Replay::new("tasks");
+    replay.state = State::default().with_task_linters([Linter::new(UnbalancedWakers)]);
+    let stats_update = &mut replay.updates[0].task_update.as_mut().unwrap().stats_update;
+    stats_update.get_mut(&103).unwrap().live_wakers = Some(-2);
+    replay.next();
+
+    // Older subscribers don't send the live waker count, so it's computed
+    // from the clones and drops.
+    assert_eq!(replay.task("listener", Task::waker_count), 1);
+    assert_eq!(replay.task("conn", Task::waker_count), 0);
+    assert_eq!(replay.task("spinner", Task::waker_count), -2);
+    assert_eq!(
+        replay.task_names(tasks::SortBy::Wakers),
+        ["spinner", "conn", "listener"]
+    );
+    assert!(replay.task("listener", |task| task.warnings().is_empty()));
+    assert_eq!(replay.task("spinner", |task| task.warnings().len()), 1);
+}
+
+#[test]
+fn tasks_below_the_task_level_are_hidden() {
+    let mut replay = Replay::new("tasks");
+    replay.state = state().with_task_level(LevelFilter::DEBUG);
+    let new_tasks = &mut replay.updates[0].task_update.as_mut().unwrap().new_tasks;
+    new_tasks[2].level = Some(proto::metadata::Level::Info as i32);
+    replay.next();
+
+    // Tasks without a level of their own have their metadata's.
+    assert_eq!(replay.task_names(tasks::SortBy::Tid), ["spinner"]);
+    assert_eq!(replay.task("spinner", Task::level), tracing::Level::INFO);
+
+    let mut replay = Replay::new("tasks");
+    replay.next();
+    assert_eq!(replay.task("listener", Task::level), tracing::Level::TRACE);
+}
+
+#[test]
+fn quotas_count_running_tasks() {
+    let quota = |max_tasks| Quota {
+        target: Some("tokio::task".to_owned()),
+        location: None,
+        max_tasks,
+    };
+    let mut replay = Replay::new("tasks");
+    replay.state = state().with_quotas(vec![quota(2), quota(3)]);
+    let exceeded = |replay: &Replay| {
+        replay
+            .state
+            .tasks_state()
+            .quotas()
+            .exceeded()
+            .map(|(quota, running)| (quota.max_tasks, running))
+            .collect::<Vec<_>>()
+    };
+
+    replay.next();
+    assert_eq!(exceeded(&replay), [(2, 3)]);
+    assert!(replay
+        .state
+        .tasks_state()
+        .quotas()
+        .is_target_exceeded("tokio::task"));
+
+    // completed tasks don't count towards the quota.
+    replay.next();
+    assert_eq!(exceeded(&replay), []);
+}
+
+#[test]
+fn waker_leaks_are_replaced_by_each_snapshot() {
+    let mut replay = Replay::new("tasks");
+    let now: SystemTime = replay.updates[0].now.unwrap().try_into().unwrap();
+    let leak = |id, completed_secs_ago, waker_clones, waker_drops| proto::tasks::WakerLeak {
+        id: Some(proto::Id { id }),
+        name: Some(format!("leak-{id}")),
+        dropped_at: Some((now - Duration::from_secs(completed_secs_ago)).into()),
+        waker_clones,
+        waker_drops,
+        ..Default::default()
+    };
+    replay.updates[0].task_update.as_mut().unwrap().waker_leaks = Some(proto::tasks::WakerLeaks {
+        leaks: vec![leak(200, 120, 3, 1), leak(201, 5, 2, 1)],
+    });
+    replay.updates[1].task_update.as_mut().unwrap().waker_leaks = Some(proto::tasks::WakerLeaks {
+        leaks: vec![leak(201, 6, 4, 1)],
+    });
+    let leaks = |replay: &mut Replay| {
+        let leaks = &mut replay.waker_leaks;
+        leaks.retain(|leak| leak.upgrade().is_some());
+        waker_leaks::SortBy::Live.sort(leaks);
+        leaks
+            .iter()
+            .filter_map(|leak| {
+                let leak = leak.upgrade()?;
+                let leak = leak.borrow();
+                Some((leak.task_desc(), leak.live()))
+            })
+            .collect::<Vec<_>>()
+    };
+
+    replay.next();
+    assert_eq!(
+        leaks(&mut replay),
+        [("leak-201".to_owned(), 1), ("leak-200".to_owned(), 2)]
+    );
+    // Wakers which outlive their task only briefly aren't leaked.
+    assert_eq!(replay.state.tasks_state().waker_leaks().leaked(now), 1);
+
+    // Tasks left out of a snapshot have had all of their wakers dropped.
+    replay.next();
+    assert_eq!(leaks(&mut replay), [("leak-201".to_owned(), 3)]);
+    assert_eq!(replay.state.tasks_state().waker_leaks().leaked(now), 0);
+}
+
+#[test]
+fn live_tasks_growing_for_the_leak_window_are_flagged() {
+    let mut state = state().with_leak_window(Duration::from_secs(60));
+    let styles = view::Styles::from_config(ViewOptions::default());
+    let start = SystemTime::UNIX_EPOCH + Duration::from_secs(1_000_000);
+    let mut secs = 0;
+    let mut update = |state: &mut State, total_spawned, total_completed| {
+        let update = proto::instrument::Update {
+            now: Some((start + Duration::from_secs(secs)).into()),
+            task_update: Some(proto::tasks::TaskUpdate {
+                aggregates: Some(proto::tasks::Aggregates {
+                    total_spawned,
+                    total_completed,
+                    ..Default::default()
+                }),
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        secs += 10;
+        state.update(&styles, &view::ViewState::TasksList, update);
+    };
+    let live_tasks = |state: &State| {
+        let live_tasks = state.tasks_state().live_tasks();
+        (live_tasks.live(), live_tasks.trend(), live_tasks.leaked())
+    };
+
+    // the live tasks grow, though some tasks complete, for almost the window.
+    for (spawned, completed) in [(5, 0), (7, 1), (8, 2), (10, 3), (11, 3), (13, 4)] {
+        update(&mut state, spawned, completed);
+    }
+    assert_eq!(live_tasks(&state), (Some(9), Trend::Rising, None));
+
+    update(&mut state, 14, 4);
+    assert_eq!(live_tasks(&state), (Some(10), Trend::Rising, Some(5)));
+    let (summary, count) = state.tasks_state().task_leak(&styles).unwrap();
+    assert_eq!(count, 5);
+    assert!(summary.contains("1m"), "{summary}");
+
+    // any fall in the live tasks means they aren't only growing.
+    update(&mut state, 14, 5);
+    assert_eq!(live_tasks(&state), (Some(9), Trend::Falling, None));
+    update(&mut state, 14, 5);
+    assert_eq!(live_tasks(&state), (Some(9), Trend::Steady, None));
+}
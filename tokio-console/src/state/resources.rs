@@ -1,13 +1,21 @@
 use crate::intern::{self, InternedStr};
 use crate::state::{
+    async_ops::AsyncOpsState,
     format_location,
+    histogram::DurationHistogram,
+    pb_duration,
     store::{self, Id, SpanId, Store},
+    tasks::Task,
+    timer_stats::TimerStats,
     Attribute, Field, Metadata, Visibility,
 };
 use crate::view;
+use crate::warnings::{Acknowledged, Lint, Linter};
 use console_api as proto;
 use ratatui::{style::Color, text::Span};
 use std::{
+    cell::RefCell,
+    cmp,
     collections::HashMap,
     convert::{TryFrom, TryInto},
     rc::Rc,
@@ -17,7 +25,11 @@ use std::{
 #[derive(Default, Debug)]
 pub(crate) struct ResourcesState {
     resources: Store<Resource>,
+    timer_stats: TimerStats,
     dropped_events: u64,
+    pub(crate) linters: Vec<Linter<Resource>>,
+    /// The warnings acknowledged in the warnings view.
+    pub(crate) acknowledged: Acknowledged<Resource>,
 }
 
 #[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd)]
@@ -59,8 +71,23 @@ pub(crate) struct Resource {
     stats: ResourceStats,
     target: InternedStr,
     concrete_type: InternedStr,
-    location: String,
+    location: InternedStr,
     visibility: TypeVisibility,
+    contention: Contention,
+    warnings: Vec<Linter<Resource>>,
+}
+
+/// How the operations on a resource are waiting for it, used to detect
+/// contended synchronization primitives.
+#[derive(Debug, Default)]
+pub(crate) struct Contention {
+    /// The number of operations currently waiting on the resource.
+    pub(crate) waiters: usize,
+    /// The average time operations on the resource have spent waiting,
+    /// including those which are still waiting.
+    pub(crate) avg_wait: Duration,
+    /// The IDs of the tasks which are currently waiting on the resource.
+    pub(crate) waiting_tasks: Vec<Id<Task>>,
 }
 
 pub(crate) type ResourceRef = store::Ref<Resource>;
@@ -71,8 +98,35 @@ struct ResourceStats {
     dropped_at: Option<SystemTime>,
     total: Option<Duration>,
     formatted_attributes: Vec<Vec<Span<'static>>>,
+    /// The time values spent in the resource between being sent and received,
+    /// for channel resources.
+    recv_latency_histogram: Option<DurationHistogram>,
+    io: Option<IoStats>,
+}
+
+/// Read and write statistics for an I/O resource.
+#[derive(Debug, Default, Clone, Copy)]
+pub(crate) struct IoStats {
+    pub(crate) read: IoDirectionStats,
+    pub(crate) write: IoDirectionStats,
+}
+
+#[derive(Debug, Default, Clone, Copy)]
+pub(crate) struct IoDirectionStats {
+    pub(crate) bytes: u64,
+    pub(crate) ops: u64,
+    pub(crate) polls: u64,
+    pub(crate) total_wait: Duration,
+    pub(crate) max_wait: Duration,
+    /// When the current operation started waiting for readiness, if it is
+    /// waiting.
+    waiting_since: Option<SystemTime>,
 }
 
+/// I/O resources which have waited at least this long for write readiness are
+/// considered to be experiencing backpressure.
+const BACKPRESSURE_THRESHOLD: Duration = Duration::from_secs(1);
+
 impl Default for SortBy {
     fn default() -> Self {
         Self::Id
@@ -154,6 +208,18 @@ impl ResourcesState {
         self.resources.ids_mut()
     }
 
+    pub(crate) fn resources(&self) -> impl Iterator<Item = &Rc<RefCell<Resource>>> {
+        self.resources.values()
+    }
+
+    pub(crate) fn resource(&self, id: Id<Resource>) -> Option<&Rc<RefCell<Resource>>> {
+        self.resources.get(id)
+    }
+
+    pub(crate) fn timer_stats_mut(&mut self) -> &mut TimerStats {
+        &mut self.timer_stats
+    }
+
     pub(crate) fn update_resources(
         &mut self,
         styles: &view::Styles,
@@ -212,7 +278,7 @@ impl ResourcesState {
                     strings,
                 );
 
-                let id = ids.id_for(span_id);
+                let id = ids.id_for_new(span_id, stats.created_at);
                 let parent_id = resource.parent_resource_id.map(|id| ids.id_for(id.id));
 
                 let parent = strings.string(match parent_id {
@@ -234,7 +300,7 @@ impl ResourcesState {
                         .unwrap_or_else(|| "n/a".to_string()),
                 );
 
-                let location = format_location(resource.location);
+                let location = strings.string(format_location(resource.location));
                 let visibility = if resource.is_internal {
                     TypeVisibility::Internal
                 } else {
@@ -254,11 +320,14 @@ impl ResourcesState {
                     meta_id,
                     location,
                     visibility,
+                    contention: Contention::default(),
+                    warnings: Vec::new(),
                 };
                 Some((id, resource))
             });
 
         self.dropped_events += update.dropped_events;
+        self.timer_stats.update(update.timer_stats);
 
         for (stats, mut resource) in self.resources.updated(stats_update) {
             if let Some(meta) = metas.get(&resource.meta_id) {
@@ -268,6 +337,70 @@ impl ResourcesState {
         }
     }
 
+    /// Recomputes how contended each resource is from the async ops waiting
+    /// on it, and lints the resources with the new values.
+    pub(crate) fn update_contention(&mut self, async_ops: &AsyncOpsState, now: SystemTime) {
+        let mut contention: HashMap<Id<Resource>, (Contention, Duration, u32)> = HashMap::new();
+        for async_op in async_ops.async_ops() {
+            let Some(async_op) = async_op.upgrade() else {
+                continue;
+            };
+            let async_op = async_op.borrow();
+            let (contention, total_wait, ops) =
+                contention.entry(async_op.resource_id()).or_default();
+            if !async_op.dropped() {
+                contention.waiters += 1;
+                if let Some(task_id) = async_op.task_id() {
+                    contention.waiting_tasks.push(task_id);
+                }
+            }
+            *total_wait += async_op.total(now);
+            *ops += 1;
+        }
+
+        let linters = &self.linters;
+        for (id, resource) in self.resources.iter() {
+            let mut resource = resource.borrow_mut();
+            resource.contention = match contention.remove(id) {
+                Some((mut contention, total_wait, ops)) => {
+                    contention.avg_wait = total_wait / ops;
+                    contention.waiting_tasks.sort_unstable();
+                    contention.waiting_tasks.dedup();
+                    contention
+                }
+                None => Contention::default(),
+            };
+            resource.lint(linters);
+        }
+    }
+
+    pub(crate) fn warnings(&self) -> impl Iterator<Item = &Linter<Resource>> {
+        self.linters.iter().filter(|linter| linter.count() > 0)
+    }
+
+    /// Returns the linters with warnings which haven't been acknowledged,
+    /// along with how many resources have them.
+    pub(crate) fn unacknowledged_warnings(
+        &self,
+    ) -> impl Iterator<Item = (&Linter<Resource>, usize)> {
+        self.linters
+            .iter()
+            .enumerate()
+            .filter_map(|(lint, linter)| {
+                let count = self.acknowledged.unacknowledged(lint, linter, || {
+                    self.resources().filter_map(|resource| {
+                        let resource = resource.borrow();
+                        resource
+                            .warnings()
+                            .iter()
+                            .any(|warning| warning.is(linter))
+                            .then(|| resource.id())
+                    })
+                });
+                (count > 0).then_some((linter, count))
+            })
+    }
+
     pub(crate) fn retain_active(&mut self, now: SystemTime, retain_for: Duration) {
         self.resources.retain(|_, resource| {
             let resource = resource.borrow();
@@ -329,6 +462,39 @@ impl Resource {
         &self.stats.formatted_attributes
     }
 
+    pub(crate) fn recv_latency_histogram(&self) -> Option<&DurationHistogram> {
+        self.stats.recv_latency_histogram.as_ref()
+    }
+
+    pub(crate) fn contention(&self) -> &Contention {
+        &self.contention
+    }
+
+    pub(crate) fn warnings(&self) -> &[Linter<Resource>] {
+        &self.warnings
+    }
+
+    fn lint(&mut self, linters: &[Linter<Resource>]) {
+        self.warnings.clear();
+        for lint in linters {
+            if let Lint::Warning(warning) = lint.check(self) {
+                self.warnings.push(warning);
+            }
+        }
+    }
+
+    pub(crate) fn io_stats(&self) -> Option<&IoStats> {
+        self.stats.io.as_ref()
+    }
+
+    /// Returns the longest time this resource has waited to become writable,
+    /// if it is long enough to indicate that the resource is experiencing
+    /// backpressure.
+    pub(crate) fn write_backpressure(&self, now: SystemTime) -> Option<Duration> {
+        let wait = self.stats.io.as_ref()?.write.longest_wait(now);
+        (wait >= BACKPRESSURE_THRESHOLD).then_some(wait)
+    }
+
     pub(crate) fn total(&self, since: SystemTime) -> Duration {
         self.stats.total.unwrap_or_else(|| {
             since
@@ -375,16 +541,55 @@ impl ResourceStats {
             .unwrap();
         let dropped_at: Option<SystemTime> = pb.dropped_at.map(|v| v.try_into().unwrap());
         let total = dropped_at.map(|d| d.duration_since(created_at).unwrap_or_default());
+        let recv_latency_histogram = pb
+            .recv_latency_histogram
+            .as_ref()
+            .and_then(DurationHistogram::from_proto);
+        let io = pb.io_stats.map(|io| IoStats {
+            read: io
+                .read
+                .map(IoDirectionStats::from_proto)
+                .unwrap_or_default(),
+            write: io
+                .write
+                .map(IoDirectionStats::from_proto)
+                .unwrap_or_default(),
+        });
 
         Self {
             created_at,
             dropped_at,
             total,
             formatted_attributes,
+            recv_latency_histogram,
+            io,
         }
     }
 }
 
+impl IoDirectionStats {
+    fn from_proto(pb: proto::resources::IoDirectionStats) -> Self {
+        Self {
+            bytes: pb.bytes,
+            ops: pb.ops,
+            polls: pb.polls,
+            total_wait: pb.total_wait.map(pb_duration).unwrap_or_default(),
+            max_wait: pb.max_wait.map(pb_duration).unwrap_or_default(),
+            waiting_since: pb.waiting_since.and_then(|at| at.try_into().ok()),
+        }
+    }
+
+    /// Returns the longest time a single operation has waited for readiness,
+    /// including the current operation if it is still waiting.
+    pub(crate) fn longest_wait(&self, now: SystemTime) -> Duration {
+        let current = self
+            .waiting_since
+            .and_then(|since| now.duration_since(since).ok())
+            .unwrap_or_default();
+        cmp::max(self.max_wait, current)
+    }
+}
+
 fn kind_from_proto(
     pb: proto::resources::resource::Kind,
     strings: &mut intern::Strings,
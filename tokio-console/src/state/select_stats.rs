@@ -0,0 +1,188 @@
+use crate::{
+    state::{format_location, pb_duration},
+    view,
+};
+use console_api as proto;
+use std::{
+    cell::RefCell,
+    collections::HashMap,
+    convert::TryFrom,
+    rc::{Rc, Weak},
+    time::Duration,
+};
+
+/// Branch statistics for each instrumented `select!` callsite in the remote.
+#[derive(Debug, Default)]
+pub(crate) struct SelectStats {
+    callsites: HashMap<String, Rc<RefCell<SelectCallsite>>>,
+    new_callsites: Vec<SelectCallsiteRef>,
+}
+
+/// The statistics for a single `select!` callsite.
+#[derive(Debug)]
+pub(crate) struct SelectCallsite {
+    location: String,
+    branches: Vec<Branch>,
+}
+
+#[derive(Debug, Default, Clone, Copy)]
+pub(crate) struct Branch {
+    completions: u64,
+    total_wait: Duration,
+    max_wait: Duration,
+}
+
+pub(crate) type SelectCallsiteRef = Weak<RefCell<SelectCallsite>>;
+
+#[derive(Debug, Default, Copy, Clone)]
+#[repr(usize)]
+pub(crate) enum SortBy {
+    Selects = 0,
+    Branches = 1,
+    #[default]
+    Starved = 2,
+    AvgWait = 3,
+    MaxWait = 4,
+    Location = 5,
+}
+
+// === impl SelectStats ===
+
+impl SelectStats {
+    pub(crate) fn update(&mut self, update: Vec<proto::tasks::SelectStats>) {
+        for stats in update {
+            let location = format_location(stats.location);
+            let branches = stats.branches.into_iter().map(Branch::from).collect();
+            match self.callsites.get(&location) {
+                Some(callsite) => callsite.borrow_mut().branches = branches,
+                None => {
+                    let callsite = Rc::new(RefCell::new(SelectCallsite {
+                        location: location.clone(),
+                        branches,
+                    }));
+                    self.new_callsites.push(Rc::downgrade(&callsite));
+                    self.callsites.insert(location, callsite);
+                }
+            }
+        }
+    }
+
+    /// Returns any new callsites that were added since the last call.
+    pub(crate) fn take_new_callsites(&mut self) -> impl Iterator<Item = SelectCallsiteRef> + '_ {
+        self.new_callsites.drain(..)
+    }
+}
+
+// === impl SelectCallsite ===
+
+impl SelectCallsite {
+    pub(crate) fn location(&self) -> &str {
+        &self.location
+    }
+
+    pub(crate) fn branches(&self) -> &[Branch] {
+        &self.branches
+    }
+
+    /// Returns the total number of times this `select!` completed.
+    pub(crate) fn completions(&self) -> u64 {
+        self.branches.iter().map(|b| b.completions).sum()
+    }
+
+    /// Returns the number of branches which have never completed.
+    pub(crate) fn starved(&self) -> usize {
+        self.branches.iter().filter(|b| b.completions == 0).count()
+    }
+
+    /// Returns the average time this `select!` waited before completing.
+    pub(crate) fn avg_wait(&self) -> Duration {
+        let completions = self.completions();
+        if completions == 0 {
+            return Duration::ZERO;
+        }
+        let total: Duration = self.branches.iter().map(|b| b.total_wait).sum();
+        total.div_f64(completions as f64)
+    }
+
+    /// Returns the longest time this `select!` waited before completing.
+    pub(crate) fn max_wait(&self) -> Duration {
+        self.branches
+            .iter()
+            .map(|b| b.max_wait)
+            .max()
+            .unwrap_or_default()
+    }
+}
+
+// === impl Branch ===
+
+impl Branch {
+    pub(crate) fn completions(&self) -> u64 {
+        self.completions
+    }
+
+    pub(crate) fn avg_wait(&self) -> Duration {
+        if self.completions == 0 {
+            return Duration::ZERO;
+        }
+        self.total_wait.div_f64(self.completions as f64)
+    }
+}
+
+impl From<proto::tasks::SelectBranchStats> for Branch {
+    fn from(pb: proto::tasks::SelectBranchStats) -> Self {
+        Self {
+            completions: pb.completions,
+            total_wait: pb.total_wait.map(pb_duration).unwrap_or_default(),
+            max_wait: pb.max_wait.map(pb_duration).unwrap_or_default(),
+        }
+    }
+}
+
+// === impl SortBy ===
+
+impl SortBy {
+    pub fn sort(&self, callsites: &mut [SelectCallsiteRef]) {
+        match self {
+            Self::Selects => {
+                callsites.sort_unstable_by_key(|c| c.upgrade().map(|c| c.borrow().completions()))
+            }
+            Self::Branches => {
+                callsites.sort_unstable_by_key(|c| c.upgrade().map(|c| c.borrow().branches().len()))
+            }
+            Self::Starved => {
+                callsites.sort_unstable_by_key(|c| c.upgrade().map(|c| c.borrow().starved()))
+            }
+            Self::AvgWait => {
+                callsites.sort_unstable_by_key(|c| c.upgrade().map(|c| c.borrow().avg_wait()))
+            }
+            Self::MaxWait => {
+                callsites.sort_unstable_by_key(|c| c.upgrade().map(|c| c.borrow().max_wait()))
+            }
+            Self::Location => {
+                callsites.sort_unstable_by_key(|c| c.upgrade().map(|c| c.borrow().location.clone()))
+            }
+        }
+    }
+}
+
+impl view::SortBy for SortBy {
+    fn as_column(&self) -> usize {
+        *self as usize
+    }
+}
+
+impl TryFrom<usize> for SortBy {
+    type Error = ();
+    fn try_from(idx: usize) -> Result<Self, Self::Error> {
+        match idx {
+            idx if idx == Self::Selects as usize => Ok(Self::Selects),
+            idx if idx == Self::Branches as usize => Ok(Self::Branches),
+            idx if idx == Self::Starved as usize => Ok(Self::Starved),
+            idx if idx == Self::AvgWait as usize => Ok(Self::AvgWait),
+            idx if idx == Self::MaxWait as usize => Ok(Self::MaxWait),
+            idx if idx == Self::Location as usize => Ok(Self::Location),
+            _ => Err(()),
+        }
+    }
+}
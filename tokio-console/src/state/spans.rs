@@ -0,0 +1,291 @@
+use crate::{
+    intern::{self, InternedStr},
+    state::{
+        format_location, pb_duration,
+        store::{self, Id, Store},
+        tasks::Task,
+        Field, Metadata, Visibility,
+    },
+    view,
+};
+use console_api as proto;
+use ratatui::text::Span;
+use std::{
+    collections::HashMap,
+    convert::{TryFrom, TryInto},
+    time::{Duration, SystemTime},
+};
+
+/// The spans which the remote's application has asked the console to watch.
+#[derive(Default, Debug)]
+pub(crate) struct SpansState {
+    spans: Store<WatchedSpan>,
+    dropped_events: u64,
+}
+
+#[derive(Debug, Default, Copy, Clone)]
+#[repr(usize)]
+pub(crate) enum SortBy {
+    #[default]
+    Id = 0,
+    Name = 1,
+    Task = 2,
+    Total = 3,
+    Busy = 4,
+    Enters = 5,
+    Location = 6,
+}
+
+/// A span which the remote's application has asked the console to watch,
+/// such as one created with `console_subscriber::watch_span!`.
+#[derive(Debug)]
+pub(crate) struct WatchedSpan {
+    id: Id<WatchedSpan>,
+    name: InternedStr,
+    task_id: Option<Id<Task>>,
+    task_id_str: InternedStr,
+    location: InternedStr,
+    formatted_fields: Vec<Vec<Span<'static>>>,
+    stats: SpanStats,
+}
+
+pub(crate) type SpanRef = store::Ref<WatchedSpan>;
+
+#[derive(Debug)]
+struct SpanStats {
+    created_at: SystemTime,
+    dropped_at: Option<SystemTime>,
+    enters: u64,
+    busy: Duration,
+    last_enter_started: Option<SystemTime>,
+    last_enter_ended: Option<SystemTime>,
+}
+
+// === impl SortBy ===
+
+impl SortBy {
+    pub fn sort(&self, now: SystemTime, spans: &mut [SpanRef]) {
+        fn key<T>(span: &SpanRef, f: impl Fn(&WatchedSpan) -> T) -> Option<T> {
+            span.upgrade().map(|span| f(&span.borrow()))
+        }
+        match self {
+            Self::Id => spans.sort_unstable_by_key(|s| key(s, |s| s.id)),
+            Self::Name => spans.sort_unstable_by_key(|s| key(s, |s| s.name.clone())),
+            Self::Task => spans.sort_unstable_by_key(|s| key(s, |s| s.task_id)),
+            Self::Total => spans.sort_unstable_by_key(|s| key(s, |s| s.total(now))),
+            Self::Busy => spans.sort_unstable_by_key(|s| key(s, |s| s.busy(now))),
+            Self::Enters => spans.sort_unstable_by_key(|s| key(s, |s| s.stats.enters)),
+            Self::Location => spans.sort_unstable_by_key(|s| key(s, |s| s.location.clone())),
+        }
+    }
+}
+
+impl TryFrom<usize> for SortBy {
+    type Error = ();
+    fn try_from(idx: usize) -> Result<Self, Self::Error> {
+        match idx {
+            idx if idx == Self::Id as usize => Ok(Self::Id),
+            idx if idx == Self::Name as usize => Ok(Self::Name),
+            idx if idx == Self::Task as usize => Ok(Self::Task),
+            idx if idx == Self::Total as usize => Ok(Self::Total),
+            idx if idx == Self::Busy as usize => Ok(Self::Busy),
+            idx if idx == Self::Enters as usize => Ok(Self::Enters),
+            idx if idx == Self::Location as usize => Ok(Self::Location),
+            _ => Err(()),
+        }
+    }
+}
+
+impl view::SortBy for SortBy {
+    fn as_column(&self) -> usize {
+        *self as usize
+    }
+}
+
+// === impl SpansState ===
+
+impl SpansState {
+    /// Returns any new watched spans that were added since the last call.
+    pub(crate) fn take_new_spans(&mut self) -> impl Iterator<Item = SpanRef> + '_ {
+        self.spans.take_new_items()
+    }
+
+    pub(crate) fn update_spans(
+        &mut self,
+        styles: &view::Styles,
+        strings: &mut intern::Strings,
+        metas: &HashMap<u64, Metadata>,
+        update: proto::spans::SpanUpdate,
+        task_ids: &mut store::Ids<Task>,
+        visibility: Visibility,
+    ) {
+        let mut stats_update = update.stats_update;
+
+        self.spans
+            .insert_with(visibility, update.new_spans, |ids, mut span| {
+                let span_id = match span.id.as_ref() {
+                    Some(id) => id.id,
+                    None => {
+                        tracing::warn!(?span, "skipping watched span with no id");
+                        return None;
+                    }
+                };
+                let meta_id = match span.metadata.as_ref() {
+                    Some(id) => id.id,
+                    None => {
+                        tracing::warn!(?span, "watched span has no metadata id, skipping");
+                        return None;
+                    }
+                };
+                let meta = match metas.get(&meta_id) {
+                    Some(meta) => meta,
+                    None => {
+                        tracing::warn!(?span, meta_id, "no metadata for watched span, skipping");
+                        return None;
+                    }
+                };
+
+                let mut fields = span
+                    .fields
+                    .drain(..)
+                    .filter_map(|pb| Field::from_proto(pb, meta, strings))
+                    .collect::<Vec<_>>();
+                let formatted_fields = Field::make_formatted(styles, &mut fields);
+
+                let stats = SpanStats::from_proto(stats_update.remove(&span_id)?);
+                let id = ids.id_for_new(span_id, stats.created_at);
+                let task_id = span.task_id.map(|id| task_ids.id_for(id.id));
+                let task_id_str = strings.string(
+                    task_id
+                        .as_ref()
+                        .map(Id::<Task>::to_string)
+                        .unwrap_or_else(|| "n/a".to_string()),
+                );
+
+                let span = WatchedSpan {
+                    id,
+                    name: meta.name.clone(),
+                    task_id,
+                    task_id_str,
+                    location: strings.string(format_location(span.location)),
+                    formatted_fields,
+                    stats,
+                };
+                Some((id, span))
+            });
+
+        for (stats, mut span) in self.spans.updated(stats_update) {
+            tracing::trace!(?span, ?stats, "processing stats update for");
+            span.stats = SpanStats::from_proto(stats);
+        }
+
+        self.dropped_events += update.dropped_events;
+    }
+
+    pub(crate) fn retain_active(&mut self, now: SystemTime, retain_for: Duration) {
+        self.spans.retain(|_, span| {
+            let span = span.borrow();
+
+            span.stats
+                .dropped_at
+                .map(|d| {
+                    let dropped_for = now.duration_since(d).unwrap_or_default();
+                    retain_for > dropped_for
+                })
+                .unwrap_or(true)
+        })
+    }
+
+    pub(crate) fn dropped_events(&self) -> u64 {
+        self.dropped_events
+    }
+}
+
+// === impl WatchedSpan ===
+
+impl WatchedSpan {
+    pub(crate) fn id(&self) -> Id<WatchedSpan> {
+        self.id
+    }
+
+    pub(crate) fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns the task the span was created in, if it was created in one.
+    pub(crate) fn task_id(&self) -> Option<Id<Task>> {
+        self.task_id
+    }
+
+    pub(crate) fn task_id_str(&self) -> &str {
+        &self.task_id_str
+    }
+
+    pub(crate) fn location(&self) -> &str {
+        &self.location
+    }
+
+    pub(crate) fn formatted_fields(&self) -> &[Vec<Span<'static>>] {
+        &self.formatted_fields
+    }
+
+    /// Returns how long the span has existed for, or existed for before it
+    /// was closed.
+    pub(crate) fn total(&self, since: SystemTime) -> Duration {
+        self.stats
+            .dropped_at
+            .unwrap_or(since)
+            .duration_since(self.stats.created_at)
+            .unwrap_or_default()
+    }
+
+    /// Returns how long the span has been entered for, including the time
+    /// spent in it so far if it is currently entered.
+    pub(crate) fn busy(&self, since: SystemTime) -> Duration {
+        match self.stats.last_enter_started {
+            Some(started) if self.is_entered() => {
+                self.stats.busy + since.duration_since(started).unwrap_or_default()
+            }
+            _ => self.stats.busy,
+        }
+    }
+
+    /// Returns `true` if the span is currently entered.
+    pub(crate) fn is_entered(&self) -> bool {
+        match (self.stats.last_enter_started, self.stats.last_enter_ended) {
+            (Some(started), Some(ended)) => started > ended,
+            (Some(_), None) => true,
+            (None, _) => false,
+        }
+    }
+
+    /// Returns how many times the span has been entered.
+    pub(crate) fn enters(&self) -> u64 {
+        self.stats.enters
+    }
+
+    pub(crate) fn dropped(&self) -> bool {
+        self.stats.dropped_at.is_some()
+    }
+}
+
+// === impl SpanStats ===
+
+impl SpanStats {
+    fn from_proto(pb: proto::spans::Stats) -> Self {
+        let created_at = pb
+            .created_at
+            .expect("watched span was never created")
+            .try_into()
+            .unwrap();
+        let enter_stats = pb.enter_stats.unwrap_or_default();
+        Self {
+            created_at,
+            dropped_at: pb.dropped_at.map(|v| v.try_into().unwrap()),
+            enters: enter_stats.polls,
+            busy: enter_stats.busy_time.map(pb_duration).unwrap_or_default(),
+            last_enter_started: enter_stats.last_poll_started.map(|v| v.try_into().unwrap()),
+            last_enter_ended: enter_stats.last_poll_ended.map(|v| v.try_into().unwrap()),
+        }
+    }
+}
@@ -2,11 +2,15 @@ use std::{
     any,
     cell::{self, RefCell},
     cmp,
-    collections::hash_map::{self, Entry, HashMap},
+    collections::{
+        hash_map::{self, Entry, HashMap},
+        HashSet,
+    },
     fmt,
     hash::{Hash, Hasher},
     marker::PhantomData,
     rc::{Rc, Weak},
+    time::SystemTime,
     vec,
 };
 
@@ -28,7 +32,8 @@ pub(crate) type SpanId = u64;
 /// A rewritten sequential ID.
 ///
 /// This is distinct from the remote server's span ID, which may be reused and
-/// is not sequential.
+/// is not sequential. IDs are never reused, so an item which is given the span
+/// ID of an item which was dropped still gets an ID of its own.
 pub(crate) struct Id<T> {
     id: u64,
     _ty: PhantomData<fn(T)>,
@@ -37,7 +42,9 @@ pub(crate) struct Id<T> {
 /// Stores the rewritten sequential IDs of items in a [`Store`].
 pub(crate) struct Ids<T> {
     next: u64,
-    map: HashMap<u64, Id<T>>,
+    /// The ID each span ID is mapped to, and when the item with that span ID
+    /// was created, once it has been stored.
+    map: HashMap<SpanId, (Id<T>, Option<SystemTime>)>,
 }
 
 // === impl Store ===
@@ -48,7 +55,7 @@ impl<T> Store<T> {
     }
 
     pub fn get_by_span(&self, span_id: SpanId) -> Option<&Stored<T>> {
-        let id = self.ids.map.get(&span_id)?;
+        let (id, _) = self.ids.map.get(&span_id)?;
         self.get(*id)
     }
 
@@ -92,7 +99,7 @@ impl<T> Store<T> {
         I::IntoIter: 'store,
     {
         update.into_iter().filter_map(|(span_id, update)| {
-            let id = self.ids.map.get(&span_id)?;
+            let (id, _) = self.ids.map.get(&span_id)?;
             let item = self.store.get(id)?;
             Some((update, item.borrow_mut()))
         })
@@ -100,11 +107,23 @@ impl<T> Store<T> {
 
     /// Applies a predicate to each element in the [`Store`], removing the item
     /// if the predicate returns `false`.
-    pub fn retain(&mut self, f: impl FnMut(&Id<T>, &mut Stored<T>) -> bool) {
-        self.store.retain(f);
+    pub fn retain(&mut self, mut f: impl FnMut(&Id<T>, &mut Stored<T>) -> bool) {
+        let mut removed = HashSet::new();
+        self.store.retain(|id, item| {
+            let retain = f(id, item);
+            if !retain {
+                removed.insert(*id);
+            }
+            retain
+        });
+        if removed.is_empty() {
+            return;
+        }
         // If a removed element was in `new_items`, remove it.
         self.new_items.retain(|item| item.upgrade().is_some());
-        // TODO(eliza): remove from `ids` if it's no longer in `store`?
+        // Forget the span IDs of removed items, so that the map of IDs doesn't
+        // grow forever.
+        self.ids.map.retain(|_, (id, _)| !removed.contains(id));
     }
 
     /// Returns an iterator over all of the items which have been added to this
@@ -151,20 +170,63 @@ impl<'store, T> IntoIterator for &'store Store<T> {
 // === impl Ids ===
 
 impl<T> Ids<T> {
+    /// Returns the ID of the item with the span ID `span_id`, which may not
+    /// have been stored yet.
     pub(crate) fn id_for(&mut self, span_id: SpanId) -> Id<T> {
         match self.map.entry(span_id) {
-            Entry::Occupied(entry) => *entry.get(),
+            Entry::Occupied(entry) => entry.get().0,
             Entry::Vacant(entry) => {
-                let id = Id {
-                    id: self.next,
-                    _ty: PhantomData,
-                };
-                entry.insert(id);
-                self.next = self.next.wrapping_add(1);
+                let id = Self::next_id(&mut self.next);
+                entry.insert((id, None));
                 id
             }
         }
     }
+
+    /// Returns the ID for a new item with the span ID `span_id`, which was
+    /// created at `created_at`.
+    ///
+    /// The remote reuses the span IDs of items which have been dropped. If
+    /// `span_id` already belongs to an item which was created at a different
+    /// time, that item was dropped, so the new item gets a new ID rather than
+    /// being merged with it. An item which is sent again, such as when the
+    /// console reconnects, keeps its ID.
+    pub(crate) fn id_for_new(&mut self, span_id: SpanId, created_at: SystemTime) -> Id<T> {
+        match self.map.entry(span_id) {
+            Entry::Occupied(mut entry) => {
+                let (id, stored_at) = entry.get_mut();
+                match *stored_at {
+                    Some(stored_at) if stored_at != created_at => {
+                        tracing::debug!(
+                            span_id,
+                            old_id = %id,
+                            "span ID reused by a new item, assigning it a new ID"
+                        );
+                        *id = Self::next_id(&mut self.next);
+                    }
+                    _ => {}
+                }
+                *stored_at = Some(created_at);
+                *id
+            }
+            Entry::Vacant(entry) => {
+                let id = Self::next_id(&mut self.next);
+                entry.insert((id, Some(created_at)));
+                id
+            }
+        }
+    }
+
+    fn next_id(next: &mut u64) -> Id<T> {
+        let id = Id {
+            id: *next,
+            _ty: PhantomData,
+        };
+        // Even a new ID every nanosecond wouldn't run out for centuries, so
+        // IDs never wrap around and are never reused.
+        *next += 1;
+        id
+    }
 }
 
 impl<T> Default for Ids<T> {
@@ -241,3 +303,50 @@ impl<T> cmp::PartialOrd for Id<T> {
         Some(self.cmp(other))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    fn at(secs: u64) -> SystemTime {
+        SystemTime::UNIX_EPOCH + Duration::from_secs(secs)
+    }
+
+    #[test]
+    fn resent_items_keep_their_ids() {
+        let mut ids = Ids::<()>::default();
+        let id = ids.id_for_new(1, at(10));
+        assert_eq!(ids.id_for_new(1, at(10)), id);
+        assert_eq!(ids.id_for(1), id);
+    }
+
+    #[test]
+    fn reused_span_ids_get_new_ids() {
+        let mut ids = Ids::<()>::default();
+        let old = ids.id_for_new(1, at(10));
+        let new = ids.id_for_new(1, at(20));
+        assert_ne!(old, new);
+        assert!(new > old);
+        assert_eq!(ids.id_for(1), new);
+    }
+
+    #[test]
+    fn referenced_items_keep_their_ids_once_stored() {
+        let mut ids = Ids::<()>::default();
+        let id = ids.id_for(1);
+        assert_eq!(ids.id_for_new(1, at(10)), id);
+    }
+
+    #[test]
+    fn removed_items_are_forgotten() {
+        let mut store = Store::<u64>::default();
+        store.insert_with(Visibility::Show, [1, 2], |ids, span_id| {
+            Some((ids.id_for_new(span_id, at(span_id)), span_id))
+        });
+        store.retain(|_, item| *item.borrow() != 1);
+        assert!(store.get_by_span(1).is_none());
+        assert!(store.get_by_span(2).is_some());
+        assert_eq!(store.ids.map.len(), 1);
+    }
+}
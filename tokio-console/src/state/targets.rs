@@ -0,0 +1,153 @@
+use crate::{
+    intern::InternedStr,
+    state::tasks::{Task, TaskRef},
+    view,
+};
+use std::{
+    cell::RefCell,
+    collections::HashMap,
+    convert::TryFrom,
+    rc::{Rc, Weak},
+    time::{Duration, SystemTime},
+};
+
+/// Tasks grouped by the `tracing` target of their spans.
+///
+/// The target is usually the module path the task was spawned from, so this
+/// shows which subsystems of the remote are keeping the executor busy,
+/// without scanning through their tasks one at a time.
+#[derive(Debug, Default)]
+pub(crate) struct Targets {
+    targets: HashMap<InternedStr, Rc<RefCell<Target>>>,
+    new_targets: Vec<TargetRef>,
+}
+
+/// The tasks whose spans have the same target.
+#[derive(Debug)]
+pub(crate) struct Target {
+    target: InternedStr,
+    tasks: Vec<TaskRef>,
+}
+
+pub(crate) type TargetRef = Weak<RefCell<Target>>;
+
+#[derive(Debug, Default, Copy, Clone)]
+#[repr(usize)]
+pub(crate) enum SortBy {
+    Tasks = 0,
+    #[default]
+    Busy = 1,
+    Polls = 2,
+    Share = 3,
+    Target = 4,
+}
+
+// === impl Targets ===
+
+impl Targets {
+    /// Adds a newly spawned task to the group for its target.
+    pub(crate) fn insert(&mut self, task: &Rc<RefCell<Task>>) {
+        let task_ref = Rc::downgrade(task);
+        let task = task.borrow();
+        let new_targets = &mut self.new_targets;
+        self.targets
+            .entry(task.interned_target().clone())
+            .or_insert_with(|| {
+                let target = Rc::new(RefCell::new(Target {
+                    target: task.interned_target().clone(),
+                    tasks: Vec::new(),
+                }));
+                new_targets.push(Rc::downgrade(&target));
+                target
+            })
+            .borrow_mut()
+            .tasks
+            .push(task_ref);
+    }
+
+    /// Returns any new targets that were added since the last call.
+    pub(crate) fn take_new_targets(&mut self) -> impl Iterator<Item = TargetRef> + '_ {
+        self.new_targets.drain(..)
+    }
+
+    /// Removes tasks which are no longer stored, and any targets left empty.
+    pub(crate) fn retain_active(&mut self) {
+        self.targets.retain(|_, target| {
+            let mut target = target.borrow_mut();
+            target.tasks.retain(|task| task.upgrade().is_some());
+            !target.tasks.is_empty()
+        });
+        self.new_targets.retain(|target| target.upgrade().is_some());
+    }
+}
+
+// === impl Target ===
+
+impl Target {
+    pub(crate) fn target(&self) -> &str {
+        &self.target
+    }
+
+    /// Returns the number of tasks with this target.
+    pub(crate) fn len(&self) -> usize {
+        self.tasks.len()
+    }
+
+    /// Returns the total time tasks with this target have spent being polled.
+    pub(crate) fn busy(&self, since: SystemTime) -> Duration {
+        self.fold(Duration::ZERO, |busy, task| busy + task.busy(since))
+    }
+
+    /// Returns the total number of times tasks with this target have been
+    /// polled.
+    pub(crate) fn total_polls(&self) -> u64 {
+        self.fold(0, |polls, task| polls + task.total_polls())
+    }
+
+    fn fold<B>(&self, init: B, mut f: impl FnMut(B, &Task) -> B) -> B {
+        self.tasks
+            .iter()
+            .filter_map(Weak::upgrade)
+            .fold(init, |acc, task| f(acc, &task.borrow()))
+    }
+}
+
+// === impl SortBy ===
+
+impl SortBy {
+    pub fn sort(&self, now: SystemTime, targets: &mut [TargetRef]) {
+        match self {
+            Self::Tasks => {
+                targets.sort_unstable_by_key(|target| target.upgrade().map(|t| t.borrow().len()))
+            }
+            // A target's share is of the same total busy time as every other
+            // target's, so sorting by it is sorting by busy time.
+            Self::Busy | Self::Share => targets
+                .sort_unstable_by_key(|target| target.upgrade().map(|t| t.borrow().busy(now))),
+            Self::Polls => targets
+                .sort_unstable_by_key(|target| target.upgrade().map(|t| t.borrow().total_polls())),
+            Self::Target => targets
+                .sort_unstable_by_key(|target| target.upgrade().map(|t| t.borrow().target.clone())),
+        }
+    }
+}
+
+impl view::SortBy for SortBy {
+    fn as_column(&self) -> usize {
+        *self as usize
+    }
+}
+
+impl TryFrom<usize> for SortBy {
+    type Error = ();
+    fn try_from(idx: usize) -> Result<Self, Self::Error> {
+        match idx {
+            idx if idx == Self::Tasks as usize => Ok(Self::Tasks),
+            idx if idx == Self::Busy as usize => Ok(Self::Busy),
+            idx if idx == Self::Polls as usize => Ok(Self::Polls),
+            idx if idx == Self::Share as usize => Ok(Self::Share),
+            idx if idx == Self::Target as usize => Ok(Self::Target),
+            _ => Err(()),
+        }
+    }
+}
@@ -0,0 +1,297 @@
+use crate::{
+    intern::InternedStr,
+    state::{
+        format_location,
+        histogram::DurationHistogram,
+        tasks::{Task, TaskRef, TaskState},
+    },
+    view,
+};
+use console_api as proto;
+use std::{
+    cell::RefCell,
+    collections::HashMap,
+    convert::TryFrom,
+    rc::{Rc, Weak},
+    time::{Duration, SystemTime},
+};
+
+/// Groups of tasks which were spawned from the same location.
+///
+/// Tasks spawned into a `JoinSet`, or pushed into a `FuturesUnordered` of
+/// `JoinHandle`s in a loop, all share the location of the call to `spawn`.
+/// Grouping them keeps applications which fan out thousands of short-lived
+/// tasks navigable.
+///
+/// Tasks aren't grouped by the `JoinSet` they were spawned into, as the
+/// remote doesn't report it, nor the span context tasks were spawned in. The
+/// remote's merged histograms are also keyed by location, so separate
+/// `JoinSet`s filled from the same location share a group.
+#[derive(Debug, Default)]
+pub(crate) struct TaskGroups {
+    groups: HashMap<InternedStr, Rc<RefCell<TaskGroup>>>,
+    new_groups: Vec<TaskGroupRef>,
+}
+
+/// A set of tasks spawned from the same location.
+#[derive(Debug)]
+pub(crate) struct TaskGroup {
+    location: InternedStr,
+    kind: String,
+    tasks: Vec<TaskRef>,
+    /// The poll durations of every task in the group, merged by the remote.
+    ///
+    /// This is only present while the remote is sending task group updates,
+    /// which the console only requests while the task groups view is open.
+    poll_times: Option<DurationHistogram>,
+    /// How long each task in the group waited to be polled for the first
+    /// time after being spawned, merged by the remote.
+    ///
+    /// Like `poll_times`, this is only present while the remote is sending
+    /// task group updates.
+    first_poll_delays: Option<DurationHistogram>,
+}
+
+pub(crate) type TaskGroupRef = Weak<RefCell<TaskGroup>>;
+
+#[derive(Debug, Default, Copy, Clone)]
+#[repr(usize)]
+pub(crate) enum SortBy {
+    #[default]
+    Tasks = 0,
+    Running = 1,
+    Idle = 2,
+    Completed = 3,
+    Busy = 4,
+    Scheduled = 5,
+    Polls = 6,
+    P50 = 7,
+    P95 = 8,
+    P99 = 9,
+    FirstPollP99 = 10,
+    Kind = 11,
+    Location = 12,
+}
+
+// === impl TaskGroups ===
+
+impl TaskGroups {
+    /// Adds a newly spawned task to the group for its spawn location.
+    pub(crate) fn insert(&mut self, task: &Rc<RefCell<Task>>) {
+        let task_ref = Rc::downgrade(task);
+        let task = task.borrow();
+        let new_groups = &mut self.new_groups;
+        self.groups
+            .entry(task.interned_location().clone())
+            .or_insert_with(|| {
+                let group = Rc::new(RefCell::new(TaskGroup {
+                    location: task.interned_location().clone(),
+                    kind: task.kind().to_owned(),
+                    tasks: Vec::new(),
+                    poll_times: None,
+                    first_poll_delays: None,
+                }));
+                new_groups.push(Rc::downgrade(&group));
+                group
+            })
+            .borrow_mut()
+            .tasks
+            .push(task_ref);
+    }
+
+    /// Returns any new groups that were added since the last call.
+    pub(crate) fn take_new_groups(&mut self) -> impl Iterator<Item = TaskGroupRef> + '_ {
+        self.new_groups.drain(..)
+    }
+
+    /// Updates the merged poll duration and first poll delay histograms of
+    /// each group.
+    pub(crate) fn update_poll_times(&mut self, update: proto::tasks::TaskGroups) {
+        for group in update.groups {
+            let location = format_location(group.location);
+            if let Some(stored) = self.groups.get(location.as_str()) {
+                let mut stored = stored.borrow_mut();
+                stored.poll_times = group
+                    .poll_times_histogram
+                    .as_ref()
+                    .and_then(DurationHistogram::from_proto);
+                stored.first_poll_delays = group
+                    .first_poll_delay_histogram
+                    .as_ref()
+                    .and_then(DurationHistogram::from_proto);
+            }
+        }
+    }
+
+    /// Forgets the merged histograms of every group, once the remote is no
+    /// longer sending updates for them.
+    pub(crate) fn unset_poll_times(&mut self) {
+        for group in self.groups.values() {
+            let mut group = group.borrow_mut();
+            group.poll_times = None;
+            group.first_poll_delays = None;
+        }
+    }
+
+    /// Removes tasks which are no longer stored, and any groups left empty.
+    pub(crate) fn retain_active(&mut self) {
+        self.groups.retain(|_, group| {
+            let mut group = group.borrow_mut();
+            group.tasks.retain(|task| task.upgrade().is_some());
+            !group.tasks.is_empty()
+        });
+        self.new_groups.retain(|group| group.upgrade().is_some());
+    }
+}
+
+// === impl TaskGroup ===
+
+impl TaskGroup {
+    pub(crate) fn location(&self) -> &str {
+        &self.location
+    }
+
+    pub(crate) fn kind(&self) -> &str {
+        &self.kind
+    }
+
+    /// Returns the tasks in this group.
+    pub(crate) fn tasks(&self) -> &[TaskRef] {
+        &self.tasks
+    }
+
+    /// Returns the number of tasks in this group.
+    pub(crate) fn len(&self) -> usize {
+        self.tasks.len()
+    }
+
+    /// Returns the number of tasks in this group in the given state.
+    pub(crate) fn count(&self, state: TaskState) -> usize {
+        self.fold(0, |count, task| {
+            if task.state() == state {
+                count + 1
+            } else {
+                count
+            }
+        })
+    }
+
+    /// Returns the total time tasks in this group have spent being polled.
+    pub(crate) fn busy(&self, since: SystemTime) -> Duration {
+        self.fold(Duration::ZERO, |busy, task| busy + task.busy(since))
+    }
+
+    /// Returns the total time tasks in this group have spent scheduled.
+    pub(crate) fn scheduled(&self, since: SystemTime) -> Duration {
+        self.fold(Duration::ZERO, |scheduled, task| {
+            scheduled + task.scheduled(since)
+        })
+    }
+
+    /// Returns the total number of times tasks in this group have been polled.
+    pub(crate) fn total_polls(&self) -> u64 {
+        self.fold(0, |polls, task| polls + task.total_polls())
+    }
+
+    /// Returns the given percentile of the poll durations of all the tasks in
+    /// this group, if the remote has sent a merged histogram for the group.
+    pub(crate) fn poll_percentile(&self, percentile: f64) -> Option<Duration> {
+        self.poll_times.as_ref().map(|poll_times| {
+            Duration::from_nanos(poll_times.histogram.value_at_percentile(percentile))
+        })
+    }
+
+    /// Returns the given percentile of how long the tasks in this group
+    /// waited to be polled for the first time, if the remote has sent a
+    /// merged histogram for the group.
+    pub(crate) fn first_poll_percentile(&self, percentile: f64) -> Option<Duration> {
+        self.first_poll_delays
+            .as_ref()
+            .map(|delays| Duration::from_nanos(delays.histogram.value_at_percentile(percentile)))
+    }
+
+    fn fold<B>(&self, init: B, mut f: impl FnMut(B, &Task) -> B) -> B {
+        self.tasks
+            .iter()
+            .filter_map(Weak::upgrade)
+            .fold(init, |acc, task| f(acc, &task.borrow()))
+    }
+}
+
+// === impl SortBy ===
+
+impl SortBy {
+    pub fn sort(&self, now: SystemTime, groups: &mut [TaskGroupRef]) {
+        match self {
+            Self::Tasks => {
+                groups.sort_unstable_by_key(|group| group.upgrade().map(|g| g.borrow().len()))
+            }
+            Self::Running => groups.sort_unstable_by_key(|group| {
+                group
+                    .upgrade()
+                    .map(|g| g.borrow().count(TaskState::Running))
+            }),
+            Self::Idle => groups.sort_unstable_by_key(|group| {
+                group.upgrade().map(|g| g.borrow().count(TaskState::Idle))
+            }),
+            Self::Completed => groups.sort_unstable_by_key(|group| {
+                group
+                    .upgrade()
+                    .map(|g| g.borrow().count(TaskState::Completed))
+            }),
+            Self::Busy => {
+                groups.sort_unstable_by_key(|group| group.upgrade().map(|g| g.borrow().busy(now)))
+            }
+            Self::Scheduled => groups
+                .sort_unstable_by_key(|group| group.upgrade().map(|g| g.borrow().scheduled(now))),
+            Self::Polls => groups
+                .sort_unstable_by_key(|group| group.upgrade().map(|g| g.borrow().total_polls())),
+            Self::P50 => groups.sort_unstable_by_key(|group| {
+                group.upgrade().map(|g| g.borrow().poll_percentile(50.0))
+            }),
+            Self::P95 => groups.sort_unstable_by_key(|group| {
+                group.upgrade().map(|g| g.borrow().poll_percentile(95.0))
+            }),
+            Self::P99 => groups.sort_unstable_by_key(|group| {
+                group.upgrade().map(|g| g.borrow().poll_percentile(99.0))
+            }),
+            Self::FirstPollP99 => groups.sort_unstable_by_key(|group| {
+                group
+                    .upgrade()
+                    .map(|g| g.borrow().first_poll_percentile(99.0))
+            }),
+            Self::Kind => groups
+                .sort_unstable_by_key(|group| group.upgrade().map(|g| g.borrow().kind.clone())),
+            Self::Location => groups
+                .sort_unstable_by_key(|group| group.upgrade().map(|g| g.borrow().location.clone())),
+        }
+    }
+}
+
+impl view::SortBy for SortBy {
+    fn as_column(&self) -> usize {
+        *self as usize
+    }
+}
+
+impl TryFrom<usize> for SortBy {
+    type Error = ();
+    fn try_from(idx: usize) -> Result<Self, Self::Error> {
+        match idx {
+            idx if idx == Self::Tasks as usize => Ok(Self::Tasks),
+            idx if idx == Self::Running as usize => Ok(Self::Running),
+            idx if idx == Self::Idle as usize => Ok(Self::Idle),
+            idx if idx == Self::Completed as usize => Ok(Self::Completed),
+            idx if idx == Self::Busy as usize => Ok(Self::Busy),
+            idx if idx == Self::Scheduled as usize => Ok(Self::Scheduled),
+            idx if idx == Self::Polls as usize => Ok(Self::Polls),
+            idx if idx == Self::P50 as usize => Ok(Self::P50),
+            idx if idx == Self::P95 as usize => Ok(Self::P95),
+            idx if idx == Self::P99 as usize => Ok(Self::P99),
+            idx if idx == Self::FirstPollP99 as usize => Ok(Self::FirstPollP99),
+            idx if idx == Self::Kind as usize => Ok(Self::Kind),
+            idx if idx == Self::Location as usize => Ok(Self::Location),
+            _ => Err(()),
+        }
+    }
+}
@@ -1,32 +1,88 @@
 use crate::{
+    i18n::{Lang, Msg},
     intern::{self, InternedStr},
     state::{
         format_location,
         histogram::DurationHistogram,
+        live_tasks::LiveTasks,
         pb_duration,
+        quotas::Quotas,
+        select_stats::SelectStats,
         store::{self, Id, SpanId, Store},
-        Field, FieldValue, Metadata, Visibility,
+        targets::Targets,
+        task_groups::TaskGroups,
+        wake_graph::WakeGraph,
+        waker_leaks::WakerLeaks,
+        Field, FieldValue, Metadata, SourceLocation, Visibility,
     },
     util::Percentage,
     view,
-    warnings::{Lint, Linter},
+    warnings::{Acknowledged, Lint, Linter},
 };
 use console_api as proto;
-use ratatui::{style::Color, text::Span};
+use ratatui::{
+    style::{Color, Modifier},
+    text::Span,
+};
 use std::{
     cell::RefCell,
-    collections::{HashMap, HashSet},
+    cmp,
+    collections::{HashMap, HashSet, VecDeque},
     convert::{TryFrom, TryInto},
     rc::{Rc, Weak},
     time::{Duration, SystemTime},
 };
+use tracing_subscriber::filter::LevelFilter;
 
 #[derive(Default, Debug)]
 pub(crate) struct TasksState {
     tasks: Store<Task>,
     pending_lint: HashSet<Id<Task>>,
     pub(crate) linters: Vec<Linter<Task>>,
+    /// The warnings acknowledged in the warnings view.
+    pub(crate) acknowledged: Acknowledged<Task>,
     dropped_events: u64,
+    aggregates: Option<Aggregates>,
+    activity: Activity,
+    groups: TaskGroups,
+    targets: Targets,
+    select_stats: SelectStats,
+    wake_graph: WakeGraph,
+    waker_leaks: WakerLeaks,
+    pub(crate) quotas: Quotas,
+    pub(crate) live_tasks: LiveTasks,
+    /// The tasks which were busy during the last update interval, so that
+    /// their executor share can be reset on the next update.
+    busy_last_interval: Vec<Id<Task>>,
+    /// The most verbose level of the tasks to keep, if any are filtered out.
+    pub(crate) level: Option<LevelFilter>,
+}
+
+/// Aggregate stats for all tasks spawned over the lifetime of the remote
+/// process.
+///
+/// These are reported by the remote, so they include tasks that completed
+/// before the console connected.
+#[derive(Debug, Default, Copy, Clone)]
+pub(crate) struct Aggregates {
+    pub(crate) total_spawned: u64,
+    pub(crate) total_completed: u64,
+    pub(crate) max_concurrent: u64,
+    pub(crate) total_busy: Duration,
+}
+
+/// Recent per-interval counts of task activity, oldest first.
+///
+/// The remote sends its retained history when the console connects, so this
+/// is populated immediately rather than only accumulating while connected.
+#[derive(Debug, Default)]
+pub(crate) struct Activity {
+    interval: Option<Duration>,
+    /// The sequence number of the next sample expected from the remote.
+    next_sample: u64,
+    spawned: VecDeque<u64>,
+    completed: VecDeque<u64>,
+    wakes: VecDeque<u64>,
 }
 
 #[derive(Debug, Default)]
@@ -34,6 +90,35 @@ pub(crate) struct Details {
     pub(crate) span_id: SpanId,
     pub(crate) poll_times_histogram: Option<DurationHistogram>,
     pub(crate) scheduled_times_histogram: Option<DurationHistogram>,
+    pub(crate) wake_intervals_histogram: Option<DurationHistogram>,
+    /// The threads which have polled the task, most frequent first.
+    pub(crate) thread_polls: Vec<ThreadPolls>,
+    /// The number of polls on threads which aren't in `thread_polls`.
+    pub(crate) other_thread_polls: u64,
+    /// Why the remote stopped sending the task's details, if it has.
+    pub(crate) ended: Option<DetailsEnded>,
+}
+
+/// Why the remote stopped sending a task's details.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum DetailsEnded {
+    /// The task completed, and the remote no longer retains it.
+    AgedOut,
+    /// The console fell too far behind in reading the details.
+    FellBehind,
+    /// The connection to the remote failed.
+    Disconnected(String),
+    /// The remote ended the stream without saying why, as older versions of
+    /// the subscriber do.
+    Closed,
+}
+
+/// The number of times a thread has polled a task.
+#[derive(Debug)]
+pub(crate) struct ThreadPolls {
+    pub(crate) id: u64,
+    pub(crate) name: Option<String>,
+    pub(crate) polls: u64,
 }
 
 #[derive(Debug, Copy, Clone)]
@@ -45,11 +130,35 @@ pub(crate) enum SortBy {
     Name = 3,
     Total = 4,
     Busy = 5,
-    Scheduled = 6,
-    Idle = 7,
-    Polls = 8,
-    Target = 9,
-    Location = 10,
+    BusyRatio = 6,
+    Share = 7,
+    Scheduled = 8,
+    Idle = 9,
+    Polls = 10,
+    MaxPoll = 11,
+    Pending = 12,
+    WakesPerPoll = 13,
+    Wakers = 14,
+    Memory = 15,
+    Created = 16,
+    LastWake = 17,
+    Target = 18,
+    Location = 19,
+}
+
+/// How the completed tasks table is sorted.
+#[derive(Debug, Copy, Clone, Default)]
+#[repr(usize)]
+pub(crate) enum CompletedSortBy {
+    Tid = 0,
+    Name = 1,
+    #[default]
+    Ended = 2,
+    Total = 3,
+    Busy = 4,
+    Polls = 5,
+    Termination = 6,
+    Location = 7,
 }
 
 #[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd)]
@@ -60,6 +169,31 @@ pub(crate) enum TaskState {
     Scheduled,
 }
 
+/// How a completed task ended, as far as the console can tell.
+///
+/// The remote doesn't report why a task's future was dropped, so this is
+/// inferred from when it was dropped. The runtime drops a future which
+/// returned `Ready` as soon as that poll ends, while a task which is aborted,
+/// or whose runtime shuts down, is dropped between polls. A task which
+/// panicked is also dropped as its last poll ends, so it looks like it
+/// returned.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd)]
+pub(crate) enum Termination {
+    /// The task was dropped before it was ever polled.
+    NeverPolled,
+    /// The task was dropped between polls, before it completed.
+    Cancelled,
+    /// The task's future returned from its last poll.
+    Returned,
+}
+
+/// A task dropped more than this long after its last poll ended is assumed
+/// to have been cancelled.
+const RETURNED_WITHIN: Duration = Duration::from_millis(1);
+
+/// The most bytes of an extension's value which are shown.
+const MAX_EXTENSION_LEN: usize = 32;
+
 pub(crate) type TaskRef = store::Ref<Task>;
 
 /// The Id for a Tokio task.
@@ -92,6 +226,8 @@ pub(crate) struct Task {
     short_desc: InternedStr,
     /// Fields that don't have their own column, pre-formatted
     formatted_fields: Vec<Vec<Span<'static>>>,
+    /// Payloads attached to the task by the application, pre-formatted
+    formatted_extensions: Vec<Vec<Span<'static>>>,
     /// The task statistics that are updated over the lifetime of the task
     stats: TaskStats,
     /// The target of the span representing the task
@@ -101,24 +237,49 @@ pub(crate) struct Task {
     /// Currently active warnings for this task.
     warnings: Vec<Linter<Task>>,
     /// The source file and line number the task was spawned from
-    location: String,
+    location: InternedStr,
+    /// The unshortened location the task was spawned from, if it has a file
+    source_location: Option<SourceLocation>,
+    /// The level of the span representing the task
+    level: tracing::Level,
     /// The kind of task, currently one of task, blocking, block_on, local
     kind: InternedStr,
+    /// The scheduler which runs the task, if the subscriber knows it
+    scheduler: Option<&'static str>,
     /// The size of the future driving the task
     size_bytes: Option<usize>,
     /// The original size of the future (before runtime auto-boxing)
     original_size_bytes: Option<usize>,
+    /// The number of fields the subscriber omitted because the task had more
+    /// than its configured maximum
+    omitted_fields: u64,
+    /// The time this task was busy during the last update interval
+    interval_busy: Duration,
+    /// The fraction of the time all tasks were busy during the last update
+    /// interval that was spent polling this task
+    executor_share: f64,
+    /// Whether the task had completed long enough ago that the subscriber
+    /// only kept it to send to new clients
+    historical: bool,
 }
 
 #[derive(Debug)]
 struct TaskStats {
     polls: u64,
+    /// The number of polls which returned `Pending`.
+    pending_polls: u64,
+    /// The number of polls which returned `Ready`.
+    ready_polls: u64,
     created_at: SystemTime,
     dropped_at: Option<SystemTime>,
     busy: Duration,
     scheduled: Duration,
+    /// When the task was first polled, if it has been polled.
+    first_poll: Option<SystemTime>,
     last_poll_started: Option<SystemTime>,
     last_poll_ended: Option<SystemTime>,
+    /// The duration of the longest completed poll, and when it started.
+    longest_poll: Option<(Duration, SystemTime)>,
     idle: Option<Duration>,
     total: Option<Duration>,
 
@@ -130,11 +291,21 @@ struct TaskStats {
 
     /// Total number of times the task's waker has been dropped.
     waker_drops: u64,
+    /// The number of the task's wakers which are currently alive. This is
+    /// negative if the instrumentation missed some clones.
+    live_wakers: i64,
 
     /// The timestamp of when the task was last woken.
     last_wake: Option<SystemTime>,
     /// Total number of times the task has woken itself.
     self_wakes: u64,
+    /// The span IDs of the other tasks which have woken the task, and how
+    /// many times each one woke it.
+    woken_by: Vec<(SpanId, u64)>,
+
+    /// The number of bytes allocated while polling the task, if the remote
+    /// tracks allocations.
+    allocated_bytes: Option<u64>,
 }
 
 impl TasksState {
@@ -157,9 +328,22 @@ impl TasksState {
     ) {
         let mut stats_update = update.stats_update;
         let linters = &self.linters;
+        let level_filter = self.level;
 
         // Gathers the tasks that need to be linted again on the next update cycle
         let mut next_pending_lint = HashSet::new();
+        // Gathers the tasks that were spawned in this update, to add them to
+        // their groups once they are stored.
+        let mut new_ids = Vec::new();
+
+        // Tasks which aren't updated in this interval weren't busy during it.
+        for id in self.busy_last_interval.drain(..) {
+            if let Some(task) = self.tasks.get(id) {
+                let mut task = task.borrow_mut();
+                task.interval_busy = Duration::ZERO;
+                task.executor_share = 0.0;
+            }
+        }
 
         self.tasks
             .insert_with(visibility, update.new_tasks, |ids, mut task| {
@@ -185,6 +369,16 @@ impl TasksState {
                         return None;
                     }
                 };
+                // the span's level was only sent with the task by newer
+                // subscribers, so fall back on its metadata's.
+                let level = task
+                    .level
+                    .and_then(|level| proto::metadata::Level::try_from(level).ok())
+                    .map_or(meta.level, Into::into);
+                if level_filter.is_some_and(|filter| filter < level) {
+                    return None;
+                }
+                let scheduler = scheduler_name(task.scheduler());
                 let mut name = None;
                 let mut task_id = None;
                 let mut kind = strings.string(String::new());
@@ -202,7 +396,10 @@ impl TasksState {
                         // the `task.name` field gets its own column, if it's present.
                         match &*field.name {
                             Field::NAME => {
-                                name = Some(strings.string(field.value.to_string()));
+                                // mark names which the subscriber truncated, so
+                                // they aren't mistaken for the full name.
+                                let marker = if field.truncated { "\u{2026}" } else { "" };
+                                name = Some(strings.string(format!("{}{marker}", field.value)));
                                 None
                             }
                             Field::TASK_ID => {
@@ -242,12 +439,15 @@ impl TasksState {
                     .collect::<Vec<_>>();
 
                 let formatted_fields = Field::make_formatted(styles, &mut fields);
+                let formatted_extensions = format_extensions(styles, &task.extensions);
 
-                let stats = stats_update.remove(&span_id)?.into();
-                let location = format_location(task.location);
+                let stats: TaskStats = stats_update.remove(&span_id)?.into();
+                let busy = stats.busy;
+                let source_location = task.location.as_ref().and_then(SourceLocation::from_proto);
+                let location = strings.string(format_location(task.location));
 
                 // remap the server's ID to a pretty, sequential task ID
-                let id = ids.id_for(span_id);
+                let id = ids.id_for_new(span_id, stats.created_at);
 
                 let short_desc = strings.string(match (task_id, name.as_ref()) {
                     (Some(task_id), Some(name)) => format!("{task_id} ({name})"),
@@ -264,23 +464,59 @@ impl TasksState {
                     id_str: task_id.map(|id| id.to_string()).unwrap_or_default(),
                     short_desc,
                     formatted_fields,
+                    formatted_extensions,
                     stats,
                     target: meta.target.clone(),
                     warnings: Vec::new(),
                     location,
+                    source_location,
+                    level,
                     kind,
+                    scheduler,
                     size_bytes,
                     original_size_bytes,
+                    omitted_fields: task.omitted_fields,
+                    // a new task's busy time was all spent during the
+                    // interval it was spawned in, unless it completed before
+                    // the console attached.
+                    interval_busy: if task.historical {
+                        Duration::ZERO
+                    } else {
+                        busy
+                    },
+                    executor_share: 0.0,
+                    historical: task.historical,
                 };
                 if let TaskLintResult::RequiresRecheck = task.lint(linters) {
                     next_pending_lint.insert(task.id);
                 }
+                new_ids.push(id);
                 Some((id, task))
             });
 
+        for &id in &new_ids {
+            if let Some(task) = self.tasks.get(id) {
+                self.groups.insert(task);
+                self.targets.insert(task);
+                if task.borrow().interval_busy > Duration::ZERO {
+                    self.busy_last_interval.push(id);
+                }
+            }
+        }
+
+        // The tasks whose stats changed, so that their wakes by other tasks
+        // can be added to the wake graph once every task's stats are updated.
+        let mut woken_ids = new_ids;
+
         for (stats, mut task) in self.tasks.updated(stats_update) {
             tracing::trace!(?task, ?stats, "processing stats update for");
+            let busy = task.stats.busy;
             task.stats = stats.into();
+            woken_ids.push(task.id);
+            task.interval_busy = task.stats.busy.saturating_sub(busy);
+            if task.interval_busy > Duration::ZERO {
+                self.busy_last_interval.push(task.id);
+            }
             match task.lint(linters) {
                 TaskLintResult::RequiresRecheck => next_pending_lint.insert(task.id),
                 // Avoid linting this task again this cycle
@@ -288,6 +524,17 @@ impl TasksState {
             };
         }
 
+        for (extensions, mut task) in self.tasks.updated(update.extensions_update) {
+            task.formatted_extensions = format_extensions(styles, &extensions.extensions);
+        }
+
+        self.wake_graph.start_interval();
+        for id in woken_ids {
+            if let Some(task) = self.tasks.get(id) {
+                self.wake_graph.update(task, &self.tasks);
+            }
+        }
+
         for id in &self.pending_lint {
             if let Some(task) = self.tasks.get(*id) {
                 if let TaskLintResult::RequiresRecheck = task.borrow_mut().lint(linters) {
@@ -297,12 +544,45 @@ impl TasksState {
         }
         self.pending_lint = next_pending_lint;
 
+        let interval_busy = self
+            .busy_last_interval
+            .iter()
+            .filter_map(|id| self.tasks.get(*id))
+            .map(|task| task.borrow().interval_busy)
+            .sum::<Duration>();
+        for id in &self.busy_last_interval {
+            if let Some(task) = self.tasks.get(*id) {
+                let mut task = task.borrow_mut();
+                task.executor_share =
+                    task.interval_busy.as_secs_f64() / interval_busy.as_secs_f64();
+            }
+        }
+
         self.dropped_events += update.dropped_events;
+
+        if let Some(aggregates) = update.aggregates {
+            self.aggregates = Some(aggregates.into());
+        }
+
+        if let Some(activity) = update.activity {
+            self.activity.update(activity);
+        }
+
+        self.select_stats.update(update.select_stats);
+        if let Some(waker_leaks) = update.waker_leaks {
+            self.waker_leaks.update(waker_leaks);
+        }
+        self.quotas.count(self.tasks.values());
     }
 
     pub(crate) fn retain_active(&mut self, now: SystemTime, retain_for: Duration) {
         self.tasks.retain(|_, task| {
             let task = task.borrow();
+            // historical tasks already completed longer ago than any
+            // retention, and the subscriber sends them only once.
+            if task.historical {
+                return true;
+            }
 
             task.stats
                 .dropped_at
@@ -311,20 +591,203 @@ impl TasksState {
                     retain_for > dropped_for
                 })
                 .unwrap_or(true)
-        })
+        });
+        self.retain_related();
+    }
+
+    /// Removes the tasks which completed longest ago, until at most `max`
+    /// completed tasks are left.
+    pub(crate) fn retain_recently_completed(&mut self, max: usize) {
+        let mut completed = self
+            .tasks
+            .values()
+            .filter_map(|task| {
+                let task = task.borrow();
+                Some((task.stats.dropped_at?, task.id))
+            })
+            .collect::<Vec<_>>();
+        if completed.len() <= max {
+            return;
+        }
+        completed.sort_unstable();
+        let removed = completed[..completed.len() - max]
+            .iter()
+            .map(|&(_, id)| id)
+            .collect::<HashSet<_>>();
+        self.tasks.retain(|id, _| !removed.contains(id));
+        self.retain_related();
+    }
+
+    /// Removes the groups, targets, and wake edges of tasks which have been
+    /// removed.
+    fn retain_related(&mut self) {
+        self.groups.retain_active();
+        self.targets.retain_active();
+        self.wake_graph.retain_active();
     }
 
     pub(crate) fn warnings(&self) -> impl Iterator<Item = &Linter<Task>> {
         self.linters.iter().filter(|linter| linter.count() > 0)
     }
 
+    /// Returns the linters with warnings which haven't been acknowledged,
+    /// along with how many tasks have them.
+    pub(crate) fn unacknowledged_warnings(&self) -> impl Iterator<Item = (&Linter<Task>, usize)> {
+        self.linters
+            .iter()
+            .enumerate()
+            .filter_map(|(lint, linter)| {
+                let count = self.acknowledged.unacknowledged(lint, linter, || {
+                    self.tasks().filter_map(|task| {
+                        let task = task.borrow();
+                        task.warnings()
+                            .iter()
+                            .any(|warning| warning.is(linter))
+                            .then(|| task.id())
+                    })
+                });
+                (count > 0).then_some((linter, count))
+            })
+    }
+
     pub(crate) fn task(&self, id: Id<Task>) -> Option<TaskRef> {
         self.tasks.get(id).map(Rc::downgrade)
     }
 
+    pub(crate) fn tasks(&self) -> impl Iterator<Item = &Rc<RefCell<Task>>> {
+        self.tasks.values()
+    }
+
+    /// Returns the task whose ID or name is `id_or_name`, if there is one.
+    ///
+    /// If several tasks match, tasks which are still running are preferred
+    /// over completed ones, and older tasks over newer ones.
+    pub(crate) fn find(&self, id_or_name: &str) -> Option<Rc<RefCell<Task>>> {
+        self.tasks
+            .values()
+            .filter(|task| {
+                let task = task.borrow();
+                task.id_str() == id_or_name || task.name() == Some(id_or_name)
+            })
+            .min_by_key(|task| {
+                let task = task.borrow();
+                (task.is_completed(), task.id())
+            })
+            .cloned()
+    }
+
     pub(crate) fn dropped_events(&self) -> u64 {
         self.dropped_events
     }
+
+    /// Returns the process-lifetime task aggregates, if the remote reports
+    /// them.
+    pub(crate) fn aggregates(&self) -> Option<Aggregates> {
+        self.aggregates
+    }
+
+    pub(crate) fn activity(&self) -> &Activity {
+        &self.activity
+    }
+
+    pub(crate) fn groups_mut(&mut self) -> &mut TaskGroups {
+        &mut self.groups
+    }
+
+    pub(crate) fn targets_mut(&mut self) -> &mut Targets {
+        &mut self.targets
+    }
+
+    pub(crate) fn select_stats_mut(&mut self) -> &mut SelectStats {
+        &mut self.select_stats
+    }
+
+    pub(crate) fn quotas(&self) -> &Quotas {
+        &self.quotas
+    }
+
+    pub(crate) fn live_tasks(&self) -> &LiveTasks {
+        &self.live_tasks
+    }
+
+    /// Returns the summary of the possible task leak, with how many more
+    /// tasks are live than at the start of the leak window, if the live
+    /// tasks have only grown during it.
+    pub(crate) fn task_leak(&self, styles: &view::Styles) -> Option<(String, usize)> {
+        let leaked = self.live_tasks.leaked()?;
+        let window = humantime::format_duration(self.live_tasks.window()?);
+        let summary = styles.fill(Msg::TaskLeakSummary, &[("window", &window)]);
+        Some((summary, leaked as usize))
+    }
+
+    pub(crate) fn wake_graph(&self) -> &WakeGraph {
+        &self.wake_graph
+    }
+
+    pub(crate) fn wake_graph_mut(&mut self) -> &mut WakeGraph {
+        &mut self.wake_graph
+    }
+
+    pub(crate) fn waker_leaks(&self) -> &WakerLeaks {
+        &self.waker_leaks
+    }
+
+    pub(crate) fn waker_leaks_mut(&mut self) -> &mut WakerLeaks {
+        &mut self.waker_leaks
+    }
+}
+
+impl Activity {
+    /// The maximum number of samples kept for each series.
+    const MAX_SAMPLES: usize = 240;
+
+    /// Returns the length of each sampled interval, if any samples have been
+    /// received.
+    pub(crate) fn interval(&self) -> Option<Duration> {
+        self.interval
+    }
+
+    pub(crate) fn spawned(&self) -> &VecDeque<u64> {
+        &self.spawned
+    }
+
+    pub(crate) fn completed(&self) -> &VecDeque<u64> {
+        &self.completed
+    }
+
+    pub(crate) fn wakes(&self) -> &VecDeque<u64> {
+        &self.wakes
+    }
+
+    fn update(&mut self, pb: proto::tasks::ActivitySamples) {
+        if let Some(interval) = pb.interval {
+            self.interval = Some(pb_duration(interval));
+        }
+
+        let len = pb.spawned.len() as u64;
+        let end = pb.first_sample + len;
+        if end < self.next_sample {
+            // The remote's samples end before ones we have already seen, so
+            // it must have restarted. Start over from its history.
+            self.spawned.clear();
+            self.completed.clear();
+            self.wakes.clear();
+            self.next_sample = pb.first_sample;
+        }
+        // After reconnecting, skip any samples that we already have.
+        let skip = self.next_sample.saturating_sub(pb.first_sample) as usize;
+        self.next_sample = cmp::max(self.next_sample, end);
+
+        fn extend(series: &mut VecDeque<u64>, samples: Vec<u64>, skip: usize) {
+            series.extend(samples.into_iter().skip(skip));
+            let excess = series.len().saturating_sub(Activity::MAX_SAMPLES);
+            series.drain(..excess);
+        }
+
+        extend(&mut self.spawned, pb.spawned, skip);
+        extend(&mut self.completed, pb.completed, skip);
+        extend(&mut self.wakes, pb.wakes, skip);
+    }
 }
 
 impl Details {
@@ -339,6 +802,45 @@ impl Details {
     pub(crate) fn scheduled_times_histogram(&self) -> Option<&DurationHistogram> {
         self.scheduled_times_histogram.as_ref()
     }
+
+    pub(crate) fn wake_intervals_histogram(&self) -> Option<&DurationHistogram> {
+        self.wake_intervals_histogram.as_ref()
+    }
+
+    pub(crate) fn thread_polls(&self) -> &[ThreadPolls] {
+        &self.thread_polls
+    }
+
+    pub(crate) fn other_thread_polls(&self) -> u64 {
+        self.other_thread_polls
+    }
+
+    pub(crate) fn ended(&self) -> Option<&DetailsEnded> {
+        self.ended.as_ref()
+    }
+}
+
+// === impl DetailsEnded ===
+
+impl DetailsEnded {
+    /// Returns why a details stream ended with `status`, or without one.
+    pub(crate) fn from_status(status: Option<&tonic::Status>) -> Self {
+        match status {
+            Some(status) if status.code() == tonic::Code::NotFound => Self::AgedOut,
+            Some(status) if status.code() == tonic::Code::ResourceExhausted => Self::FellBehind,
+            Some(status) => Self::Disconnected(status.message().to_string()),
+            None => Self::Closed,
+        }
+    }
+
+    pub(crate) fn describe(&self, lang: Lang) -> String {
+        match self {
+            Self::AgedOut => lang.text(Msg::DetailsAgedOut).to_string(),
+            Self::FellBehind => lang.text(Msg::DetailsFellBehind).to_string(),
+            Self::Disconnected(error) => lang.fill(Msg::DetailsDisconnected, &[("error", error)]),
+            Self::Closed => lang.text(Msg::DetailsClosed).to_string(),
+        }
+    }
 }
 
 impl Task {
@@ -362,6 +864,16 @@ impl Task {
         &self.kind
     }
 
+    /// Returns the scheduler which runs the task: `multi_thread`,
+    /// `current_thread`, or `local` for tasks spawned on a `LocalSet`.
+    pub(crate) fn level(&self) -> tracing::Level {
+        self.level
+    }
+
+    pub(crate) fn scheduler(&self) -> Option<&'static str> {
+        self.scheduler
+    }
+
     pub(crate) fn short_desc(&self) -> &str {
         &self.short_desc
     }
@@ -374,6 +886,22 @@ impl Task {
         &self.formatted_fields
     }
 
+    pub(crate) fn formatted_extensions(&self) -> &[Vec<Span<'static>>] {
+        &self.formatted_extensions
+    }
+
+    /// Returns the number of fields which were not recorded for this task
+    /// because it had more than the subscriber's maximum number of fields.
+    pub(crate) fn omitted_fields(&self) -> u64 {
+        self.omitted_fields
+    }
+
+    /// Returns `true` if this task completed before the console attached,
+    /// and was only kept by the subscriber to show what recently finished.
+    pub(crate) fn is_historical(&self) -> bool {
+        self.historical
+    }
+
     /// Returns `true` if this task is currently being polled.
     pub(crate) fn is_running(&self) -> bool {
         self.stats.last_poll_started > self.stats.last_poll_ended
@@ -407,6 +935,20 @@ impl Task {
         TaskState::Idle
     }
 
+    pub(crate) fn created_at(&self) -> SystemTime {
+        self.stats.created_at
+    }
+
+    /// Returns when the task completed, if it has.
+    pub(crate) fn dropped_at(&self) -> Option<SystemTime> {
+        self.stats.dropped_at
+    }
+
+    /// Returns how the task ended, if it has completed.
+    pub(crate) fn termination(&self) -> Option<Termination> {
+        self.stats.termination()
+    }
+
     pub(crate) fn total(&self, since: SystemTime) -> Duration {
         self.stats
             .total
@@ -425,6 +967,26 @@ impl Task {
         self.stats.busy
     }
 
+    /// Returns the fraction of this task's lifetime that it has spent being
+    /// polled, relative to the given `since` timestamp.
+    pub(crate) fn busy_ratio(&self, since: SystemTime) -> f64 {
+        let total = self.total(since).as_secs_f64();
+        if total == 0.0 {
+            return 0.0;
+        }
+        // a task which is currently being polled may appear to have been busy
+        // for slightly longer than it has existed.
+        (self.busy(since).as_secs_f64() / total).min(1.0)
+    }
+
+    /// Returns the fraction of the time all tasks spent being polled during
+    /// the last update interval that was spent polling this task.
+    ///
+    /// This is used to find the tasks which dominate the executor.
+    pub(crate) fn executor_share(&self) -> f64 {
+        self.executor_share
+    }
+
     pub(crate) fn scheduled(&self, since: SystemTime) -> Duration {
         if let Some(wake) = self.stats.last_wake {
             if self.stats.last_wake > self.stats.last_poll_started {
@@ -436,6 +998,34 @@ impl Task {
         self.stats.scheduled
     }
 
+    /// Returns how long the task has been waiting to be polled since it was
+    /// last woken, or `None` if it isn't waiting to be polled.
+    pub(crate) fn wait_since_wake(&self, since: SystemTime) -> Option<Duration> {
+        if self.is_completed() || !self.is_scheduled() {
+            return None;
+        }
+        let wake = self.stats.last_wake?;
+        Some(since.duration_since(wake).unwrap_or_default())
+    }
+
+    /// Returns how long the task waited between being spawned and its first
+    /// poll.
+    ///
+    /// If the task hasn't been polled yet, this is how long it has waited so
+    /// far, or `None` if it completed without ever being polled.
+    pub(crate) fn first_poll_delay(&self, since: SystemTime) -> Option<Duration> {
+        let first_poll = match self.stats.first_poll {
+            Some(first_poll) => first_poll,
+            None if self.is_completed() => return None,
+            None => since,
+        };
+        Some(
+            first_poll
+                .duration_since(self.stats.created_at)
+                .unwrap_or_default(),
+        )
+    }
+
     pub(crate) fn idle(&self, since: SystemTime) -> Duration {
         self.stats
             .idle
@@ -451,14 +1041,40 @@ impl Task {
         self.stats.polls
     }
 
-    /// Returns the elapsed time since the task was last woken, relative to
-    /// given `now` timestamp.
+    /// Returns the duration of this task's longest poll, and when it started.
+    ///
+    /// If the task is being polled, and this poll has already taken longer
+    /// than any before it, that's the longest poll.
+    pub(crate) fn longest_poll(&self, since: SystemTime) -> Option<(Duration, SystemTime)> {
+        let current = self
+            .stats
+            .last_poll_started
+            .filter(|_| self.stats.last_poll_started > self.stats.last_poll_ended)
+            .map(|started| (since.duration_since(started).unwrap_or_default(), started));
+        cmp::max(self.stats.longest_poll, current)
+    }
+
+    /// Returns the fraction of this task's completed polls which returned
+    /// `Pending`.
+    ///
+    /// Returns 0 if none of the task's polls have completed.
+    pub(crate) fn pending_ratio(&self) -> f64 {
+        let pending = self.stats.pending_polls as f64;
+        let total = pending + self.stats.ready_polls as f64;
+        if total == 0.0 {
+            return 0.0;
+        }
+        pending / total
+    }
+
+    /// Returns how many times this task has been woken for each time it has
+    /// been polled.
     ///
-    /// Returns `None` if the task has never been woken, or if it was last woken
-    /// more recently than `now` (which *shouldn't* happen as long as `now` is the
-    /// timestamp of the last stats update...)
-    pub(crate) fn since_wake(&self, now: SystemTime) -> Option<Duration> {
-        now.duration_since(self.last_wake()?).ok()
+    /// A task which hasn't been polled yet is treated as if it had been
+    /// polled once, so that a task which is woken many times before its
+    /// first poll still stands out.
+    pub(crate) fn wakes_per_poll(&self) -> f64 {
+        self.stats.wakes as f64 / cmp::max(self.stats.polls, 1) as f64
     }
 
     pub(crate) fn last_wake(&self) -> Option<SystemTime> {
@@ -466,8 +1082,11 @@ impl Task {
     }
 
     /// Returns the current number of wakers for this task.
-    pub(crate) fn waker_count(&self) -> u64 {
-        self.waker_clones().saturating_sub(self.waker_drops())
+    ///
+    /// This is negative if more wakers have been dropped than were cloned,
+    /// which means that the instrumentation missed some of the clones.
+    pub(crate) fn waker_count(&self) -> i64 {
+        self.stats.live_wakers
     }
 
     /// Returns the total number of times this task's waker has been cloned.
@@ -490,6 +1109,18 @@ impl Task {
         self.stats.self_wakes
     }
 
+    /// Returns the span IDs of the other tasks which have woken this task,
+    /// and how many times each one woke it.
+    pub(crate) fn woken_by(&self) -> &[(SpanId, u64)] {
+        &self.stats.woken_by
+    }
+
+    /// Returns the number of bytes allocated while polling this task, or
+    /// `None` if the remote doesn't track allocations.
+    pub(crate) fn allocated_bytes(&self) -> Option<u64> {
+        self.stats.allocated_bytes
+    }
+
     /// Returns the percentage of this task's total wakeups that were self-wakes.
     pub(crate) fn self_wake_percent(&self) -> u64 {
         self.self_wakes().percent_of(self.wakes())
@@ -533,6 +1164,20 @@ impl Task {
         &self.location
     }
 
+    pub(crate) fn source_location(&self) -> Option<&SourceLocation> {
+        self.source_location.as_ref()
+    }
+
+    /// Returns the location the task was spawned from, shared with every
+    /// other task spawned from the same place.
+    pub(super) fn interned_location(&self) -> &InternedStr {
+        &self.location
+    }
+
+    pub(super) fn interned_target(&self) -> &InternedStr {
+        &self.target
+    }
+
     pub(crate) fn size_bytes(&self) -> Option<usize> {
         self.size_bytes
     }
@@ -547,6 +1192,72 @@ enum TaskLintResult {
     RequiresRecheck,
 }
 
+/// Returns the name displayed for a scheduler, or `None` if the subscriber
+/// didn't know which scheduler it was.
+pub(crate) fn scheduler_name(scheduler: proto::tasks::task::Scheduler) -> Option<&'static str> {
+    use proto::tasks::task::Scheduler;
+    match scheduler {
+        Scheduler::Unknown => None,
+        Scheduler::MultiThread => Some("multi_thread"),
+        Scheduler::CurrentThread => Some("current_thread"),
+        Scheduler::Local => Some("local"),
+    }
+}
+
+/// Formats the payloads attached to a task by the application as raw data,
+/// since the console doesn't know their types.
+///
+/// Each is shown as the name of its type, which is the last segment of its
+/// type URL, and its value as a string if it's printable, or in hex.
+fn format_extensions(
+    styles: &view::Styles,
+    extensions: &[prost_types::Any],
+) -> Vec<Vec<Span<'static>>> {
+    let key_style = styles.fg(Color::LightBlue).add_modifier(Modifier::BOLD);
+    let delim_style = styles.fg(Color::LightBlue).add_modifier(Modifier::DIM);
+    let val_style = styles.fg(Color::Yellow);
+    let truncated_style = styles.fg(Color::Yellow).add_modifier(Modifier::DIM);
+
+    extensions
+        .iter()
+        .map(|extension| {
+            let name = extension.type_url.rsplit('/').next().unwrap_or_default();
+            let value = &extension.value[..cmp::min(extension.value.len(), MAX_EXTENSION_LEN)];
+            let value = match std::str::from_utf8(value) {
+                Ok(value) if !value.chars().any(char::is_control) => format!("{value:?}"),
+                _ => value.iter().map(|byte| format!("{byte:02x}")).collect(),
+            };
+            let mut elems = vec![
+                Span::styled(name.to_owned(), key_style),
+                Span::styled("=", delim_style),
+            ];
+            if extension.value.len() > MAX_EXTENSION_LEN {
+                elems.push(Span::styled(value, val_style));
+                elems.push(Span::styled(
+                    format!("{}truncated ", styles.if_utf8("\u{2026}", "...")),
+                    truncated_style,
+                ));
+            } else {
+                elems.push(Span::styled(format!("{value} "), val_style));
+            }
+            elems
+        })
+        .collect()
+}
+
+impl TaskStats {
+    fn termination(&self) -> Option<Termination> {
+        let dropped_at = self.dropped_at?;
+        let Some(last_poll_ended) = self.last_poll_ended else {
+            return Some(Termination::NeverPolled);
+        };
+        match dropped_at.duration_since(last_poll_ended) {
+            Ok(since_poll) if since_poll > RETURNED_WITHIN => Some(Termination::Cancelled),
+            _ => Some(Termination::Returned),
+        }
+    }
+}
+
 impl From<proto::tasks::Stats> for TaskStats {
     fn from(pb: proto::tasks::Stats) -> Self {
         let created_at = pb
@@ -568,15 +1279,53 @@ impl From<proto::tasks::Stats> for TaskStats {
             scheduled,
             busy,
             last_wake: pb.last_wake.map(|v| v.try_into().unwrap()),
+            first_poll: poll_stats.first_poll.map(|v| v.try_into().unwrap()),
             last_poll_started: poll_stats.last_poll_started.map(|v| v.try_into().unwrap()),
             last_poll_ended: poll_stats.last_poll_ended.map(|v| v.try_into().unwrap()),
+            longest_poll: pb.max_poll_time.map(pb_duration).zip(
+                pb.max_poll_started_at
+                    .and_then(|started| started.try_into().ok()),
+            ),
             polls: poll_stats.polls,
+            pending_polls: poll_stats.pending_polls,
+            ready_polls: poll_stats.ready_polls,
             created_at,
             dropped_at,
             wakes: pb.wakes,
             waker_clones: pb.waker_clones,
             waker_drops: pb.waker_drops,
+            // Older subscribers don't send the live waker count.
+            live_wakers: pb
+                .live_wakers
+                .unwrap_or_else(|| pb.waker_clones as i64 - pb.waker_drops as i64),
             self_wakes: pb.self_wakes,
+            woken_by: pb
+                .woken_by
+                .into_iter()
+                .filter_map(|waker| Some((waker.task_id?.id, waker.wakes)))
+                .collect(),
+            allocated_bytes: pb.alloc_stats.map(|allocs| allocs.allocated_bytes),
+        }
+    }
+}
+
+impl From<proto::tasks::ThreadPolls> for ThreadPolls {
+    fn from(pb: proto::tasks::ThreadPolls) -> Self {
+        Self {
+            id: pb.thread_id,
+            name: pb.name,
+            polls: pb.polls,
+        }
+    }
+}
+
+impl From<proto::tasks::Aggregates> for Aggregates {
+    fn from(pb: proto::tasks::Aggregates) -> Self {
+        Self {
+            total_spawned: pb.total_spawned,
+            total_completed: pb.total_completed,
+            max_concurrent: pb.max_concurrent,
+            total_busy: pb.total_busy_time.map(pb_duration).unwrap_or_default(),
         }
     }
 }
@@ -613,9 +1362,44 @@ impl SortBy {
             Self::Busy => {
                 tasks.sort_unstable_by_key(|task| task.upgrade().map(|t| t.borrow().busy(now)))
             }
+            Self::BusyRatio => tasks.sort_unstable_by(|a, b| {
+                let a = a.upgrade().map(|t| t.borrow().busy_ratio(now));
+                let b = b.upgrade().map(|t| t.borrow().busy_ratio(now));
+                a.partial_cmp(&b).unwrap_or(cmp::Ordering::Equal)
+            }),
+            Self::Share => tasks.sort_unstable_by(|a, b| {
+                let a = a.upgrade().map(|t| t.borrow().executor_share);
+                let b = b.upgrade().map(|t| t.borrow().executor_share);
+                a.partial_cmp(&b).unwrap_or(cmp::Ordering::Equal)
+            }),
             Self::Polls => {
                 tasks.sort_unstable_by_key(|task| task.upgrade().map(|t| t.borrow().stats.polls))
             }
+            Self::MaxPoll => tasks.sort_unstable_by_key(|task| {
+                task.upgrade()
+                    .and_then(|t| t.borrow().longest_poll(now))
+                    .map(|(longest, _)| longest)
+            }),
+            Self::Pending => tasks.sort_unstable_by(|a, b| {
+                let a = a.upgrade().map(|t| t.borrow().pending_ratio());
+                let b = b.upgrade().map(|t| t.borrow().pending_ratio());
+                a.partial_cmp(&b).unwrap_or(cmp::Ordering::Equal)
+            }),
+            Self::WakesPerPoll => tasks.sort_unstable_by(|a, b| {
+                let a = a.upgrade().map(|t| t.borrow().wakes_per_poll());
+                let b = b.upgrade().map(|t| t.borrow().wakes_per_poll());
+                a.partial_cmp(&b).unwrap_or(cmp::Ordering::Equal)
+            }),
+            Self::Wakers => {
+                tasks.sort_unstable_by_key(|task| task.upgrade().map(|t| t.borrow().waker_count()))
+            }
+            Self::Memory => tasks.sort_unstable_by_key(|task| {
+                task.upgrade().and_then(|t| t.borrow().allocated_bytes())
+            }),
+            Self::Created => tasks
+                .sort_unstable_by_key(|task| task.upgrade().map(|t| t.borrow().stats.created_at)),
+            Self::LastWake => tasks
+                .sort_unstable_by_key(|task| task.upgrade().and_then(|t| t.borrow().last_wake())),
             Self::Target => {
                 tasks.sort_unstable_by_key(|task| task.upgrade().map(|t| t.borrow().target.clone()))
             }
@@ -641,9 +1425,18 @@ impl TryFrom<usize> for SortBy {
             idx if idx == Self::Name as usize => Ok(Self::Name),
             idx if idx == Self::Total as usize => Ok(Self::Total),
             idx if idx == Self::Busy as usize => Ok(Self::Busy),
+            idx if idx == Self::BusyRatio as usize => Ok(Self::BusyRatio),
+            idx if idx == Self::Share as usize => Ok(Self::Share),
             idx if idx == Self::Scheduled as usize => Ok(Self::Scheduled),
             idx if idx == Self::Idle as usize => Ok(Self::Idle),
             idx if idx == Self::Polls as usize => Ok(Self::Polls),
+            idx if idx == Self::MaxPoll as usize => Ok(Self::MaxPoll),
+            idx if idx == Self::Pending as usize => Ok(Self::Pending),
+            idx if idx == Self::WakesPerPoll as usize => Ok(Self::WakesPerPoll),
+            idx if idx == Self::Wakers as usize => Ok(Self::Wakers),
+            idx if idx == Self::Memory as usize => Ok(Self::Memory),
+            idx if idx == Self::Created as usize => Ok(Self::Created),
+            idx if idx == Self::LastWake as usize => Ok(Self::LastWake),
             idx if idx == Self::Target as usize => Ok(Self::Target),
             idx if idx == Self::Location as usize => Ok(Self::Location),
             _ => Err(()),
@@ -651,6 +1444,69 @@ impl TryFrom<usize> for SortBy {
     }
 }
 
+impl CompletedSortBy {
+    pub fn sort(&self, now: SystemTime, tasks: &mut [Weak<RefCell<Task>>]) {
+        match self {
+            Self::Tid => {
+                tasks.sort_unstable_by_key(|task| task.upgrade().map(|t| t.borrow().task_id))
+            }
+            Self::Name => {
+                tasks.sort_unstable_by_key(|task| task.upgrade().map(|t| t.borrow().name.clone()))
+            }
+            Self::Ended => {
+                tasks.sort_unstable_by_key(|task| task.upgrade().map(|t| t.borrow().dropped_at()))
+            }
+            Self::Total => {
+                tasks.sort_unstable_by_key(|task| task.upgrade().map(|t| t.borrow().total(now)))
+            }
+            Self::Busy => {
+                tasks.sort_unstable_by_key(|task| task.upgrade().map(|t| t.borrow().busy(now)))
+            }
+            Self::Polls => {
+                tasks.sort_unstable_by_key(|task| task.upgrade().map(|t| t.borrow().stats.polls))
+            }
+            Self::Termination => {
+                tasks.sort_unstable_by_key(|task| task.upgrade().map(|t| t.borrow().termination()))
+            }
+            Self::Location => tasks
+                .sort_unstable_by_key(|task| task.upgrade().map(|t| t.borrow().location.clone())),
+        }
+    }
+}
+
+impl view::SortBy for CompletedSortBy {
+    fn as_column(&self) -> usize {
+        *self as usize
+    }
+}
+
+impl TryFrom<usize> for CompletedSortBy {
+    type Error = ();
+    fn try_from(idx: usize) -> Result<Self, Self::Error> {
+        match idx {
+            idx if idx == Self::Tid as usize => Ok(Self::Tid),
+            idx if idx == Self::Name as usize => Ok(Self::Name),
+            idx if idx == Self::Ended as usize => Ok(Self::Ended),
+            idx if idx == Self::Total as usize => Ok(Self::Total),
+            idx if idx == Self::Busy as usize => Ok(Self::Busy),
+            idx if idx == Self::Polls as usize => Ok(Self::Polls),
+            idx if idx == Self::Termination as usize => Ok(Self::Termination),
+            idx if idx == Self::Location as usize => Ok(Self::Location),
+            _ => Err(()),
+        }
+    }
+}
+
+impl Termination {
+    pub(crate) fn render(self, styles: &crate::view::Styles) -> Span<'static> {
+        match self {
+            Self::NeverPolled => Span::raw("never polled"),
+            Self::Cancelled => Span::styled("cancelled", styles.fg(Color::Yellow)),
+            Self::Returned => Span::raw("returned"),
+        }
+    }
+}
+
 impl TaskState {
     pub(crate) fn render(self, styles: &crate::view::Styles) -> Span<'static> {
         const RUNNING_UTF8: &str = "\u{25B6}";
@@ -668,3 +1524,73 @@ impl TaskState {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn samples(first_sample: u64, spawned: &[u64]) -> proto::tasks::ActivitySamples {
+        proto::tasks::ActivitySamples {
+            interval: Some(Duration::from_secs(1).try_into().unwrap()),
+            spawned: spawned.to_vec(),
+            completed: vec![0; spawned.len()],
+            wakes: vec![0; spawned.len()],
+            first_sample,
+        }
+    }
+
+    #[test]
+    fn activity_skips_samples_replayed_on_reconnect() {
+        let mut activity = Activity::default();
+        activity.update(samples(0, &[1, 2, 3]));
+        activity.update(samples(3, &[4]));
+        // After reconnecting, the remote sends all of its retained samples.
+        activity.update(samples(1, &[2, 3, 4, 5]));
+        assert_eq!(activity.spawned(), &[1, 2, 3, 4, 5]);
+    }
+
+    /// Returns the stats of a task dropped `dropped_at` milliseconds after
+    /// it was spawned, whose last poll ended at `last_poll_ended`, if it was
+    /// polled.
+    fn completed_stats(last_poll_ended: Option<i32>, dropped_at: i32) -> TaskStats {
+        let at = |millis: i32| prost_types::Timestamp {
+            seconds: 1_700_000_000,
+            nanos: millis * 1_000_000,
+        };
+        proto::tasks::Stats {
+            created_at: Some(at(0)),
+            dropped_at: Some(at(dropped_at)),
+            poll_stats: Some(proto::PollStats {
+                polls: last_poll_ended.is_some().into(),
+                last_poll_ended: last_poll_ended.map(at),
+                ..Default::default()
+            }),
+            ..Default::default()
+        }
+        .into()
+    }
+
+    #[test]
+    fn termination_is_inferred_from_when_the_task_was_dropped() {
+        assert_eq!(
+            completed_stats(Some(10), 10).termination(),
+            Some(Termination::Returned)
+        );
+        assert_eq!(
+            completed_stats(Some(10), 500).termination(),
+            Some(Termination::Cancelled)
+        );
+        assert_eq!(
+            completed_stats(None, 500).termination(),
+            Some(Termination::NeverPolled)
+        );
+    }
+
+    #[test]
+    fn activity_resets_when_remote_restarts() {
+        let mut activity = Activity::default();
+        activity.update(samples(0, &[1, 2, 3, 4]));
+        activity.update(samples(0, &[7, 8]));
+        assert_eq!(activity.spawned(), &[7, 8]);
+    }
+}
@@ -0,0 +1,155 @@
+use crate::{
+    state::{format_location, histogram::DurationHistogram},
+    view,
+};
+use console_api as proto;
+use std::{
+    cell::RefCell,
+    cmp,
+    collections::HashMap,
+    convert::TryFrom,
+    rc::{Rc, Weak},
+    time::Duration,
+};
+
+/// Deadline overrun statistics for each timer callsite in the remote.
+#[derive(Debug, Default)]
+pub(crate) struct TimerStats {
+    callsites: HashMap<String, Rc<RefCell<TimerCallsite>>>,
+    new_callsites: Vec<TimerCallsiteRef>,
+}
+
+/// The deadline overruns of the timers created at a single callsite.
+#[derive(Debug)]
+pub(crate) struct TimerCallsite {
+    location: String,
+    fires: u64,
+    overruns: Option<DurationHistogram>,
+}
+
+pub(crate) type TimerCallsiteRef = Weak<RefCell<TimerCallsite>>;
+
+#[derive(Debug, Default, Copy, Clone)]
+#[repr(usize)]
+pub(crate) enum SortBy {
+    Fires = 0,
+    P50 = 1,
+    #[default]
+    P99 = 2,
+    Max = 3,
+    Location = 4,
+}
+
+// === impl TimerStats ===
+
+impl TimerStats {
+    pub(crate) fn update(&mut self, update: Vec<proto::resources::TimerStats>) {
+        for stats in update {
+            let location = format_location(stats.location);
+            let overruns = stats
+                .overrun_histogram
+                .as_ref()
+                .and_then(DurationHistogram::from_proto);
+            match self.callsites.get(&location) {
+                Some(callsite) => {
+                    let mut callsite = callsite.borrow_mut();
+                    callsite.fires = stats.fires;
+                    callsite.overruns = overruns;
+                }
+                None => {
+                    let callsite = Rc::new(RefCell::new(TimerCallsite {
+                        location: location.clone(),
+                        fires: stats.fires,
+                        overruns,
+                    }));
+                    self.new_callsites.push(Rc::downgrade(&callsite));
+                    self.callsites.insert(location, callsite);
+                }
+            }
+        }
+    }
+
+    /// Returns any new callsites that were added since the last call.
+    pub(crate) fn take_new_callsites(&mut self) -> impl Iterator<Item = TimerCallsiteRef> + '_ {
+        self.new_callsites.drain(..)
+    }
+}
+
+// === impl TimerCallsite ===
+
+impl TimerCallsite {
+    pub(crate) fn location(&self) -> &str {
+        &self.location
+    }
+
+    /// Returns the number of times a timer created at this callsite fired.
+    pub(crate) fn fires(&self) -> u64 {
+        self.fires
+    }
+
+    pub(crate) fn overruns(&self) -> Option<&DurationHistogram> {
+        self.overruns.as_ref()
+    }
+
+    /// Returns the deadline overrun at the given percentile.
+    pub(crate) fn percentile(&self, percentile: f64) -> Duration {
+        self.overruns
+            .as_ref()
+            .map(|h| Duration::from_nanos(h.histogram.value_at_percentile(percentile)))
+            .unwrap_or_default()
+    }
+
+    /// Returns the longest deadline overrun, including any outliers which
+    /// were too large for the histogram.
+    pub(crate) fn max(&self) -> Duration {
+        self.overruns
+            .as_ref()
+            .map(|h| {
+                let max = Duration::from_nanos(h.histogram.max());
+                cmp::max(max, h.highest_outlier.unwrap_or_default())
+            })
+            .unwrap_or_default()
+    }
+}
+
+// === impl SortBy ===
+
+impl SortBy {
+    pub fn sort(&self, callsites: &mut [TimerCallsiteRef]) {
+        match self {
+            Self::Fires => {
+                callsites.sort_unstable_by_key(|c| c.upgrade().map(|c| c.borrow().fires()))
+            }
+            Self::P50 => {
+                callsites.sort_unstable_by_key(|c| c.upgrade().map(|c| c.borrow().percentile(50.0)))
+            }
+            Self::P99 => {
+                callsites.sort_unstable_by_key(|c| c.upgrade().map(|c| c.borrow().percentile(99.0)))
+            }
+            Self::Max => callsites.sort_unstable_by_key(|c| c.upgrade().map(|c| c.borrow().max())),
+            Self::Location => {
+                callsites.sort_unstable_by_key(|c| c.upgrade().map(|c| c.borrow().location.clone()))
+            }
+        }
+    }
+}
+
+impl view::SortBy for SortBy {
+    fn as_column(&self) -> usize {
+        *self as usize
+    }
+}
+
+impl TryFrom<usize> for SortBy {
+    type Error = ();
+    fn try_from(idx: usize) -> Result<Self, Self::Error> {
+        match idx {
+            idx if idx == Self::Fires as usize => Ok(Self::Fires),
+            idx if idx == Self::P50 as usize => Ok(Self::P50),
+            idx if idx == Self::P99 as usize => Ok(Self::P99),
+            idx if idx == Self::Max as usize => Ok(Self::Max),
+            idx if idx == Self::Location as usize => Ok(Self::Location),
+            _ => Err(()),
+        }
+    }
+}
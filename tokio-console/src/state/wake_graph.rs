@@ -0,0 +1,195 @@
+use crate::{
+    state::{
+        store::{Id, Store},
+        tasks::{Task, TaskRef},
+    },
+    util::Percentage,
+    view,
+};
+use std::{
+    cell::RefCell,
+    collections::HashMap,
+    convert::TryFrom,
+    rc::{Rc, Weak},
+};
+
+/// How many times each task in the remote has been woken by each other task.
+///
+/// Only wakes between tasks which are both in the tasks table are included,
+/// so an edge is removed once either of its tasks is.
+#[derive(Debug, Default)]
+pub(crate) struct WakeGraph {
+    edges: HashMap<EdgeKey, Rc<RefCell<WakeEdge>>>,
+    new_edges: Vec<WakeEdgeRef>,
+}
+
+/// The wakes of one task by another.
+#[derive(Debug)]
+pub(crate) struct WakeEdge {
+    waker: TaskRef,
+    woken: TaskRef,
+    wakes: u64,
+    /// The number of wakes during the last update interval.
+    recent_wakes: u64,
+}
+
+pub(crate) type WakeEdgeRef = Weak<RefCell<WakeEdge>>;
+
+/// The IDs of the waking task and the woken task.
+type EdgeKey = (Id<Task>, Id<Task>);
+
+#[derive(Debug, Default, Copy, Clone)]
+#[repr(usize)]
+pub(crate) enum SortBy {
+    #[default]
+    Wakes = 0,
+    Recent = 1,
+    Share = 2,
+    Waker = 3,
+    Woken = 4,
+}
+
+// === impl WakeGraph ===
+
+impl WakeGraph {
+    /// Starts a new update interval, in which no edges have had any wakes
+    /// yet.
+    pub(crate) fn start_interval(&mut self) {
+        for edge in self.edges.values() {
+            edge.borrow_mut().recent_wakes = 0;
+        }
+    }
+
+    /// Updates the edges into `woken` from its latest stats, looking up the
+    /// tasks which woke it in `tasks`.
+    ///
+    /// Wakes by tasks which aren't in `tasks` are left out.
+    pub(crate) fn update(&mut self, woken: &Rc<RefCell<Task>>, tasks: &Store<Task>) {
+        let task = woken.borrow();
+        for &(span_id, wakes) in task.woken_by() {
+            let Some(waker) = tasks.get_by_span(span_id) else {
+                continue;
+            };
+            let key = (waker.borrow().id(), task.id());
+            match self.edges.get(&key) {
+                Some(edge) => {
+                    let mut edge = edge.borrow_mut();
+                    edge.recent_wakes = wakes.saturating_sub(edge.wakes);
+                    edge.wakes = wakes;
+                }
+                None => {
+                    // As with a new task's busy time, a new edge's wakes are
+                    // all counted as recent.
+                    let edge = Rc::new(RefCell::new(WakeEdge {
+                        waker: Rc::downgrade(waker),
+                        woken: Rc::downgrade(woken),
+                        wakes,
+                        recent_wakes: wakes,
+                    }));
+                    self.new_edges.push(Rc::downgrade(&edge));
+                    self.edges.insert(key, edge);
+                }
+            }
+        }
+    }
+
+    /// Removes the edges to or from tasks which are no longer in the tasks
+    /// table.
+    pub(crate) fn retain_active(&mut self) {
+        self.edges.retain(|_, edge| {
+            let edge = edge.borrow();
+            edge.waker.strong_count() > 0 && edge.woken.strong_count() > 0
+        });
+    }
+
+    /// Returns all of the edges, in no particular order.
+    pub(crate) fn edges(&self) -> impl Iterator<Item = &Rc<RefCell<WakeEdge>>> {
+        self.edges.values()
+    }
+
+    /// Returns any new edges that were added since the last call.
+    pub(crate) fn take_new_edges(&mut self) -> impl Iterator<Item = WakeEdgeRef> + '_ {
+        self.new_edges.drain(..)
+    }
+}
+
+// === impl WakeEdge ===
+
+impl WakeEdge {
+    pub(crate) fn waker(&self) -> Option<Rc<RefCell<Task>>> {
+        self.waker.upgrade()
+    }
+
+    pub(crate) fn woken(&self) -> Option<Rc<RefCell<Task>>> {
+        self.woken.upgrade()
+    }
+
+    /// Returns the total number of times the waking task has woken the woken
+    /// task.
+    pub(crate) fn wakes(&self) -> u64 {
+        self.wakes
+    }
+
+    /// Returns the number of wakes during the last update interval.
+    pub(crate) fn recent_wakes(&self) -> u64 {
+        self.recent_wakes
+    }
+
+    /// Returns the percentage of all of the woken task's wakes which were by
+    /// the waking task.
+    pub(crate) fn share(&self) -> u64 {
+        let wakes = self
+            .woken()
+            .map(|woken| woken.borrow().wakes())
+            .unwrap_or_default();
+        self.wakes.percent_of(wakes)
+    }
+
+    pub(crate) fn waker_id(&self) -> Option<Id<Task>> {
+        self.waker().map(|task| task.borrow().id())
+    }
+
+    pub(crate) fn woken_id(&self) -> Option<Id<Task>> {
+        self.woken().map(|task| task.borrow().id())
+    }
+}
+
+// === impl SortBy ===
+
+impl SortBy {
+    pub fn sort(&self, edges: &mut [WakeEdgeRef]) {
+        match self {
+            Self::Wakes => edges.sort_unstable_by_key(|e| e.upgrade().map(|e| e.borrow().wakes())),
+            Self::Recent => {
+                edges.sort_unstable_by_key(|e| e.upgrade().map(|e| e.borrow().recent_wakes()))
+            }
+            Self::Share => edges.sort_unstable_by_key(|e| e.upgrade().map(|e| e.borrow().share())),
+            Self::Waker => {
+                edges.sort_unstable_by_key(|e| e.upgrade().and_then(|e| e.borrow().waker_id()))
+            }
+            Self::Woken => {
+                edges.sort_unstable_by_key(|e| e.upgrade().and_then(|e| e.borrow().woken_id()))
+            }
+        }
+    }
+}
+
+impl view::SortBy for SortBy {
+    fn as_column(&self) -> usize {
+        *self as usize
+    }
+}
+
+impl TryFrom<usize> for SortBy {
+    type Error = ();
+    fn try_from(idx: usize) -> Result<Self, Self::Error> {
+        match idx {
+            idx if idx == Self::Wakes as usize => Ok(Self::Wakes),
+            idx if idx == Self::Recent as usize => Ok(Self::Recent),
+            idx if idx == Self::Share as usize => Ok(Self::Share),
+            idx if idx == Self::Waker as usize => Ok(Self::Waker),
+            idx if idx == Self::Woken as usize => Ok(Self::Woken),
+            _ => Err(()),
+        }
+    }
+}
@@ -0,0 +1,196 @@
+use crate::{state::format_location, view};
+use console_api as proto;
+use std::{
+    cell::RefCell,
+    cmp,
+    collections::HashMap,
+    convert::{TryFrom, TryInto},
+    rc::{Rc, Weak},
+    time::{Duration, SystemTime},
+};
+
+/// Completed tasks in the remote which still had wakers alive when they were
+/// dropped from its retained tasks.
+///
+/// The remote sends every such task whenever one changes, so the leaks are
+/// replaced by each update which includes them.
+#[derive(Debug, Default)]
+pub(crate) struct WakerLeaks {
+    leaks: HashMap<u64, Rc<RefCell<WakerLeak>>>,
+    new_leaks: Vec<WakerLeakRef>,
+}
+
+/// A completed task with wakers which are still alive.
+#[derive(Debug)]
+pub(crate) struct WakerLeak {
+    span_id: u64,
+    task_id: Option<u64>,
+    name: Option<String>,
+    location: String,
+    dropped_at: SystemTime,
+    clones: u64,
+    drops: u64,
+}
+
+pub(crate) type WakerLeakRef = Weak<RefCell<WakerLeak>>;
+
+#[derive(Debug, Default, Copy, Clone)]
+#[repr(usize)]
+pub(crate) enum SortBy {
+    #[default]
+    Live = 0,
+    Clones = 1,
+    Drops = 2,
+    Completed = 3,
+    Task = 4,
+    Location = 5,
+}
+
+// === impl WakerLeaks ===
+
+impl WakerLeaks {
+    /// How long after a task completes its wakers must still be alive for
+    /// them to be considered leaked.
+    ///
+    /// Wakers are often dropped shortly after their task completes, such as
+    /// when a timer or I/O resource which was registered with them is
+    /// cleaned up, so they aren't leaked just because they outlive the task.
+    pub(crate) const LEAKED_AFTER: Duration = Duration::from_secs(60);
+
+    pub(crate) fn update(&mut self, update: proto::tasks::WakerLeaks) {
+        let mut leaks = HashMap::with_capacity(update.leaks.len());
+        for pb in update.leaks {
+            let Some(span_id) = pb.id.map(|id| id.id) else {
+                tracing::warn!(leak = ?pb, "waker leak has no task id, skipping");
+                continue;
+            };
+            let Some(dropped_at) = pb.dropped_at.and_then(|t| t.try_into().ok()) else {
+                tracing::warn!(leak = ?pb, "waker leak has no completion time, skipping");
+                continue;
+            };
+            let leak = WakerLeak {
+                span_id,
+                task_id: pb.task_id,
+                name: pb.name,
+                location: format_location(pb.location),
+                dropped_at,
+                clones: pb.waker_clones,
+                drops: pb.waker_drops,
+            };
+            // keep the rows of tasks which are still leaking, so that the
+            // view doesn't lose its selection.
+            let leak = match self.leaks.remove(&span_id) {
+                Some(existing) => {
+                    *existing.borrow_mut() = leak;
+                    existing
+                }
+                None => {
+                    let leak = Rc::new(RefCell::new(leak));
+                    self.new_leaks.push(Rc::downgrade(&leak));
+                    leak
+                }
+            };
+            leaks.insert(span_id, leak);
+        }
+        self.leaks = leaks;
+    }
+
+    /// Returns any new leaks that were added since the last call.
+    pub(crate) fn take_new_leaks(&mut self) -> impl Iterator<Item = WakerLeakRef> + '_ {
+        self.new_leaks.drain(..)
+    }
+
+    /// Returns how many tasks have had wakers alive for longer than
+    /// [`Self::LEAKED_AFTER`] since they completed.
+    pub(crate) fn leaked(&self, now: SystemTime) -> usize {
+        self.leaks
+            .values()
+            .filter(|leak| leak.borrow().is_leaked(now))
+            .count()
+    }
+}
+
+// === impl WakerLeak ===
+
+impl WakerLeak {
+    /// Returns the task's Tokio task ID and name, or its span ID if it has
+    /// neither.
+    pub(crate) fn task_desc(&self) -> String {
+        match (self.task_id, self.name.as_ref()) {
+            (Some(task_id), Some(name)) => format!("{task_id} ({name})"),
+            (Some(task_id), None) => task_id.to_string(),
+            (None, Some(name)) => name.clone(),
+            (None, None) => format!("span {}", self.span_id),
+        }
+    }
+
+    pub(crate) fn location(&self) -> &str {
+        &self.location
+    }
+
+    pub(crate) fn clones(&self) -> u64 {
+        self.clones
+    }
+
+    pub(crate) fn drops(&self) -> u64 {
+        self.drops
+    }
+
+    /// Returns the number of the task's wakers which are still alive.
+    pub(crate) fn live(&self) -> u64 {
+        self.clones.saturating_sub(self.drops)
+    }
+
+    /// Returns how long ago the task completed.
+    pub(crate) fn completed_for(&self, now: SystemTime) -> Duration {
+        now.duration_since(self.dropped_at).unwrap_or_default()
+    }
+
+    /// Returns whether the task's wakers have been alive for long enough
+    /// after it completed to be considered leaked.
+    pub(crate) fn is_leaked(&self, now: SystemTime) -> bool {
+        self.live() > 0 && self.completed_for(now) >= WakerLeaks::LEAKED_AFTER
+    }
+}
+
+// === impl SortBy ===
+
+impl SortBy {
+    pub fn sort(&self, leaks: &mut [WakerLeakRef]) {
+        match self {
+            Self::Live => leaks.sort_unstable_by_key(|l| l.upgrade().map(|l| l.borrow().live())),
+            Self::Clones => leaks.sort_unstable_by_key(|l| l.upgrade().map(|l| l.borrow().clones)),
+            Self::Drops => leaks.sort_unstable_by_key(|l| l.upgrade().map(|l| l.borrow().drops)),
+            // the tasks which completed longest ago sort as the largest.
+            Self::Completed => leaks
+                .sort_unstable_by_key(|l| l.upgrade().map(|l| cmp::Reverse(l.borrow().dropped_at))),
+            Self::Task => {
+                leaks.sort_unstable_by_key(|l| l.upgrade().map(|l| l.borrow().task_desc()))
+            }
+            Self::Location => {
+                leaks.sort_unstable_by_key(|l| l.upgrade().map(|l| l.borrow().location.clone()))
+            }
+        }
+    }
+}
+
+impl view::SortBy for SortBy {
+    fn as_column(&self) -> usize {
+        *self as usize
+    }
+}
+
+impl TryFrom<usize> for SortBy {
+    type Error = ();
+    fn try_from(idx: usize) -> Result<Self, Self::Error> {
+        match idx {
+            idx if idx == Self::Live as usize => Ok(Self::Live),
+            idx if idx == Self::Clones as usize => Ok(Self::Clones),
+            idx if idx == Self::Drops as usize => Ok(Self::Drops),
+            idx if idx == Self::Completed as usize => Ok(Self::Completed),
+            idx if idx == Self::Task as usize => Ok(Self::Task),
+            idx if idx == Self::Location as usize => Ok(Self::Location),
+            _ => Err(()),
+        }
+    }
+}
@@ -0,0 +1,361 @@
+use crate::{
+    state::{
+        format_location, pb_duration, store,
+        tasks::{scheduler_name, Task},
+        Id,
+    },
+    view,
+};
+use console_api as proto;
+use std::{
+    cell::RefCell,
+    collections::HashMap,
+    convert::TryFrom,
+    rc::{Rc, Weak},
+    time::{Duration, SystemTime},
+};
+
+/// The threads in the remote which poll tasks.
+#[derive(Debug, Default)]
+pub(crate) struct WorkersState {
+    workers: HashMap<u64, Rc<RefCell<Worker>>>,
+    new_workers: Vec<WorkerRef>,
+    /// When the last update was received, so that the runtime's counters can
+    /// be turned into rates.
+    last_update: Option<SystemTime>,
+}
+
+/// A thread in the remote which polls tasks.
+#[derive(Debug)]
+pub(crate) struct Worker {
+    thread_id: u64,
+    name: String,
+    /// The index of the worker in the runtime, if the remote reports its
+    /// runtime's metrics and this is one of its workers.
+    index: Option<u64>,
+    /// The scheduler whose tasks the thread polls, if it polls any.
+    scheduler: Option<&'static str>,
+    current_task: Option<Id<Task>>,
+    polls: u64,
+    /// The number of polls of tasks spawned on a `LocalSet`, which aren't
+    /// included in `polls`.
+    local_polls: u64,
+    /// The number of polls since the previous update.
+    recent_polls: u64,
+    steals: Option<u64>,
+    parks: Option<u64>,
+    unparks: Option<u64>,
+    /// Parks per second since the previous update.
+    park_rate: Option<f64>,
+    /// Unparks per second since the previous update.
+    unpark_rate: Option<f64>,
+    /// The total time spent polling tasks, by the location they were spawned
+    /// at, and the time spent polling tasks from untracked locations.
+    busy: (Vec<(String, Duration)>, Duration),
+    /// The percentage of the time since the previous update spent polling
+    /// tasks.
+    recent_busy: Option<f64>,
+    /// The percentage of the time since the previous update spent polling
+    /// tasks spawned at each location, from the most to the least.
+    recent_busy_by_location: Vec<(String, f64)>,
+}
+
+pub(crate) type WorkerRef = Weak<RefCell<Worker>>;
+
+#[derive(Debug, Default, Copy, Clone)]
+#[repr(usize)]
+pub(crate) enum SortBy {
+    Thread = 0,
+    #[default]
+    Worker = 1,
+    Task = 2,
+    RecentPolls = 3,
+    Busy = 4,
+    Polls = 5,
+    LocalPolls = 6,
+    Steals = 7,
+    Parks = 8,
+    Unparks = 9,
+    Locations = 10,
+}
+
+// === impl WorkersState ===
+
+impl WorkersState {
+    /// Updates the workers from a runtime update received at `now`.
+    ///
+    /// Each update includes every thread which is still running, so any
+    /// which are missing from it have exited and are removed.
+    pub(crate) fn update(
+        &mut self,
+        update: proto::runtime::RuntimeUpdate,
+        task_ids: &mut store::Ids<Task>,
+        now: Option<SystemTime>,
+    ) {
+        let elapsed = match (self.last_update, now) {
+            (Some(last), Some(now)) => now
+                .duration_since(last)
+                .ok()
+                .filter(|elapsed| !elapsed.is_zero()),
+            _ => None,
+        };
+        self.last_update = now;
+
+        let mut workers = HashMap::with_capacity(update.workers.len());
+        for worker in update.workers {
+            let current_task = worker.current_task.map(|id| task_ids.id_for(id.id));
+            let scheduler = scheduler_name(worker.scheduler());
+            let busy = (
+                worker
+                    .busy_by_location
+                    .into_iter()
+                    .map(|busy| {
+                        (
+                            format_location(busy.location),
+                            busy.busy_time.map(pb_duration).unwrap_or_default(),
+                        )
+                    })
+                    .collect::<Vec<_>>(),
+                worker.other_busy_time.map(pb_duration).unwrap_or_default(),
+            );
+            let updated = match self.workers.remove(&worker.thread_id) {
+                Some(existing) => {
+                    {
+                        let mut existing = existing.borrow_mut();
+                        let rate = |prev: Option<u64>, curr: Option<u64>| {
+                            let delta = curr?.checked_sub(prev?)?;
+                            Some(delta as f64 / elapsed?.as_secs_f64())
+                        };
+                        existing.recent_polls = worker.polls.saturating_sub(existing.polls);
+                        existing.park_rate = rate(existing.parks, worker.parks);
+                        existing.unpark_rate = rate(existing.unparks, worker.unparks);
+                        existing.update_busy(busy, elapsed);
+                        existing.polls = worker.polls;
+                        existing.local_polls = worker.local_polls;
+                        existing.current_task = current_task;
+                        existing.index = worker.worker_index;
+                        existing.scheduler = scheduler;
+                        existing.steals = worker.steals;
+                        existing.parks = worker.parks;
+                        existing.unparks = worker.unparks;
+                    }
+                    existing
+                }
+                None => {
+                    let new = Rc::new(RefCell::new(Worker {
+                        thread_id: worker.thread_id,
+                        name: worker
+                            .thread_name
+                            .unwrap_or_else(|| format!("#{}", worker.thread_id)),
+                        index: worker.worker_index,
+                        scheduler,
+                        current_task,
+                        polls: worker.polls,
+                        local_polls: worker.local_polls,
+                        recent_polls: 0,
+                        steals: worker.steals,
+                        parks: worker.parks,
+                        unparks: worker.unparks,
+                        park_rate: None,
+                        unpark_rate: None,
+                        busy,
+                        recent_busy: None,
+                        recent_busy_by_location: Vec::new(),
+                    }));
+                    self.new_workers.push(Rc::downgrade(&new));
+                    new
+                }
+            };
+            workers.insert(worker.thread_id, updated);
+        }
+        self.workers = workers;
+    }
+
+    /// Returns any new workers that were added since the last call.
+    pub(crate) fn take_new_workers(&mut self) -> impl Iterator<Item = WorkerRef> + '_ {
+        self.new_workers.drain(..)
+    }
+
+    /// Returns `true` if any thread is one of the runtime's workers, which
+    /// is only known if the remote reports its runtime's metrics.
+    pub(crate) fn has_runtime_metrics(&self) -> bool {
+        self.workers
+            .values()
+            .any(|worker| worker.borrow().index.is_some())
+    }
+}
+
+// === impl Worker ===
+
+impl Worker {
+    /// Returns the name of the thread, or its ID if it has no name.
+    pub(crate) fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub(crate) fn index(&self) -> Option<u64> {
+        self.index
+    }
+
+    /// Returns the scheduler whose tasks the thread polls, if it has polled
+    /// any tasks spawned on a runtime.
+    pub(crate) fn scheduler(&self) -> Option<&'static str> {
+        self.scheduler
+    }
+
+    /// Returns the task currently being polled on this thread, if any.
+    pub(crate) fn current_task(&self) -> Option<Id<Task>> {
+        self.current_task
+    }
+
+    pub(crate) fn polls(&self) -> u64 {
+        self.polls
+    }
+
+    /// Returns the number of polls of tasks spawned on a `LocalSet`.
+    pub(crate) fn local_polls(&self) -> u64 {
+        self.local_polls
+    }
+
+    /// Returns the number of polls since the previous update.
+    pub(crate) fn recent_polls(&self) -> u64 {
+        self.recent_polls
+    }
+
+    pub(crate) fn steals(&self) -> Option<u64> {
+        self.steals
+    }
+
+    /// Returns how many times per second the worker parked since the
+    /// previous update.
+    pub(crate) fn park_rate(&self) -> Option<f64> {
+        self.park_rate
+    }
+
+    /// Returns how many times per second the worker was unparked since the
+    /// previous update.
+    pub(crate) fn unpark_rate(&self) -> Option<f64> {
+        self.unpark_rate
+    }
+
+    /// Returns the percentage of the time since the previous update which
+    /// the thread spent polling tasks.
+    pub(crate) fn recent_busy(&self) -> Option<f64> {
+        self.recent_busy
+    }
+
+    /// Returns the percentage of the time since the previous update which
+    /// the thread spent polling the tasks spawned at each location, from the
+    /// most to the least.
+    ///
+    /// Time spent polling tasks from locations the remote doesn't track
+    /// separately isn't included.
+    pub(crate) fn recent_busy_by_location(&self) -> &[(String, f64)] {
+        &self.recent_busy_by_location
+    }
+
+    /// Updates the total time spent polling tasks, and works out how much of
+    /// the `elapsed` time since the previous update was spent doing so.
+    fn update_busy(
+        &mut self,
+        busy: (Vec<(String, Duration)>, Duration),
+        elapsed: Option<Duration>,
+    ) {
+        let (prev_locations, prev_other) = std::mem::replace(&mut self.busy, busy);
+        let Some(elapsed) = elapsed else {
+            self.recent_busy = None;
+            self.recent_busy_by_location.clear();
+            return;
+        };
+        let percent = |busy: Duration| busy.as_secs_f64() / elapsed.as_secs_f64() * 100.0;
+
+        let (locations, other) = &self.busy;
+        let mut total = other.saturating_sub(prev_other);
+        self.recent_busy_by_location = locations
+            .iter()
+            .filter_map(|(location, busy)| {
+                let prev = prev_locations
+                    .iter()
+                    .find(|(prev, _)| prev == location)
+                    .map(|&(_, prev)| prev)
+                    .unwrap_or_default();
+                let recent = busy.saturating_sub(prev);
+                total += recent;
+                (!recent.is_zero()).then(|| (location.clone(), percent(recent)))
+            })
+            .collect();
+        self.recent_busy_by_location
+            .sort_by(|(_, a), (_, b)| b.total_cmp(a));
+        self.recent_busy = Some(percent(total));
+    }
+}
+
+// === impl SortBy ===
+
+impl SortBy {
+    pub fn sort(&self, workers: &mut [WorkerRef]) {
+        fn key<T>(worker: &WorkerRef, f: impl Fn(&Worker) -> T) -> Option<T> {
+            worker.upgrade().map(|worker| f(&worker.borrow()))
+        }
+        match self {
+            Self::Thread => workers.sort_unstable_by_key(|w| key(w, |w| w.name.clone())),
+            // Threads which aren't the runtime's workers sort before all of
+            // its workers, so that they're listed after them by default.
+            Self::Worker => workers.sort_unstable_by_key(|w| {
+                key(w, |w| (w.index.map(std::cmp::Reverse), w.thread_id))
+            }),
+            Self::Task => workers.sort_unstable_by_key(|w| key(w, |w| w.current_task)),
+            Self::RecentPolls => workers.sort_unstable_by_key(|w| key(w, |w| w.recent_polls)),
+            Self::Busy => workers.sort_unstable_by(|a, b| {
+                key(a, |w| w.recent_busy)
+                    .partial_cmp(&key(b, |w| w.recent_busy))
+                    .unwrap()
+            }),
+            Self::Polls => workers.sort_unstable_by_key(|w| key(w, |w| w.polls)),
+            Self::LocalPolls => workers.sort_unstable_by_key(|w| key(w, |w| w.local_polls)),
+            Self::Steals => workers.sort_unstable_by_key(|w| key(w, |w| w.steals)),
+            Self::Parks => workers.sort_unstable_by(|a, b| {
+                key(a, |w| w.park_rate)
+                    .partial_cmp(&key(b, |w| w.park_rate))
+                    .unwrap()
+            }),
+            Self::Unparks => workers.sort_unstable_by(|a, b| {
+                key(a, |w| w.unpark_rate)
+                    .partial_cmp(&key(b, |w| w.unpark_rate))
+                    .unwrap()
+            }),
+            // Workers are sorted by the share of their time taken by the
+            // location which takes the most of it.
+            Self::Locations => workers.sort_unstable_by(|a, b| {
+                let top = |w: &Worker| w.recent_busy_by_location.first().map(|&(_, busy)| busy);
+                key(a, top).partial_cmp(&key(b, top)).unwrap()
+            }),
+        }
+    }
+}
+
+impl view::SortBy for SortBy {
+    fn as_column(&self) -> usize {
+        *self as usize
+    }
+}
+
+impl TryFrom<usize> for SortBy {
+    type Error = ();
+    fn try_from(idx: usize) -> Result<Self, Self::Error> {
+        match idx {
+            idx if idx == Self::Thread as usize => Ok(Self::Thread),
+            idx if idx == Self::Worker as usize => Ok(Self::Worker),
+            idx if idx == Self::Task as usize => Ok(Self::Task),
+            idx if idx == Self::RecentPolls as usize => Ok(Self::RecentPolls),
+            idx if idx == Self::Busy as usize => Ok(Self::Busy),
+            idx if idx == Self::Polls as usize => Ok(Self::Polls),
+            idx if idx == Self::LocalPolls as usize => Ok(Self::LocalPolls),
+            idx if idx == Self::Steals as usize => Ok(Self::Steals),
+            idx if idx == Self::Parks as usize => Ok(Self::Parks),
+            idx if idx == Self::Unparks as usize => Ok(Self::Unparks),
+            idx if idx == Self::Locations as usize => Ok(Self::Locations),
+            _ => Err(()),
+        }
+    }
+}
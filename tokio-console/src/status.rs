@@ -0,0 +1,66 @@
+//! A one-line status of the remote, for the terminal title with `--title`,
+//! and for tmux status lines with `--once=status`.
+//!
+//! The status is short enough to fit in a terminal tab or a status line, so
+//! that several consoles can be monitored at a glance.
+use crate::{i18n::Msg, state::State, view};
+
+/// Returns the status of the remote at `target`: how many of its tasks are
+/// live, and how many warnings there are.
+///
+/// Until the first update is received, only the target is included.
+pub(crate) fn line(target: &str, state: &State, styles: &view::Styles) -> String {
+    let Some(now) = state.last_updated_at() else {
+        return styles.fill(Msg::StatusConnecting, &[("target", &target)]);
+    };
+
+    let tasks = state.tasks_state();
+    // the remote's lifetime counters include tasks which the console
+    // doesn't know about, but older remotes don't send them.
+    let live = tasks.live_tasks().live().unwrap_or_else(|| {
+        tasks
+            .tasks()
+            .filter(|task| !task.borrow().is_completed())
+            .count() as u64
+    });
+    // each warned about task or resource counts as a warning, along with each
+    // exceeded quota and each leak, as they're counted in the warnings list.
+    let warnings = tasks
+        .unacknowledged_warnings()
+        .map(|(_, count)| count)
+        .chain(
+            state
+                .resources_state()
+                .unacknowledged_warnings()
+                .map(|(_, count)| count),
+        )
+        .sum::<usize>()
+        + tasks.quotas().exceeded().count()
+        + tasks.waker_leaks().leaked(now)
+        + usize::from(tasks.live_tasks().leaked().is_some());
+    styles.fill(
+        Msg::StatusLine,
+        &[
+            ("target", &target),
+            ("live", &live),
+            ("warnings", &warnings),
+        ],
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{config::ViewOptions, state::recordings};
+
+    #[test]
+    fn counts_live_tasks_and_warnings() {
+        let styles = view::Styles::from_config(ViewOptions::default());
+        let mut state = recordings::state();
+        assert_eq!(line("test", &state, &styles), "test: connecting");
+
+        let update = recordings::load("tasks").pop_front().unwrap();
+        state.update(&styles, &view::ViewState::TasksList, update);
+        assert_eq!(line("test", &state, &styles), "test: 3 live, 2 warnings");
+    }
+}
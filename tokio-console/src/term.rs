@@ -1,16 +1,37 @@
 pub use color_eyre::eyre::WrapErr;
 pub use ratatui::{backend::CrosstermBackend, Terminal};
-use std::io;
+use std::{
+    env, fs, io,
+    path::{Path, PathBuf},
+};
 
-pub fn init_crossterm() -> color_eyre::Result<(Terminal<CrosstermBackend<io::Stdout>>, OnShutdown)>
-{
+/// The index of the `colors` capability among a terminfo entry's numbers.
+const TERMINFO_COLORS: usize = 13;
+
+/// The size of the buffer frames are written to before being written to the
+/// terminal, which is large enough for a typical frame to be written at once.
+const OUTPUT_BUFFER_SIZE: usize = 64 * 1024;
+
+/// The terminal the console is drawn to.
+///
+/// `Terminal` only writes the cells which changed since the previous frame,
+/// and these are buffered so that each frame is written to the terminal in as
+/// few writes as possible. `Stdout` alone flushes every kilobyte or so, which
+/// over a remote shell means many small packets, and a frame which is visibly
+/// drawn in pieces.
+pub type ConsoleTerminal = Terminal<CrosstermBackend<io::BufWriter<io::Stdout>>>;
+
+pub fn init_crossterm() -> color_eyre::Result<(ConsoleTerminal, OnShutdown)> {
     use crossterm::terminal::{self, EnterAlternateScreen};
     terminal::enable_raw_mode().wrap_err("Failed to enable crossterm raw mode")?;
 
     let mut stdout = std::io::stdout();
     crossterm::execute!(stdout, EnterAlternateScreen)
         .wrap_err("Failed to enable crossterm alternate screen")?;
-    let backend = CrosstermBackend::new(io::stdout());
+    let backend = CrosstermBackend::new(io::BufWriter::with_capacity(
+        OUTPUT_BUFFER_SIZE,
+        io::stdout(),
+    ));
     let term = Terminal::new(backend).wrap_err("Failed to create crossterm terminal")?;
 
     let cleanup = OnShutdown::new(exit_crossterm);
@@ -28,6 +49,29 @@ pub(crate) fn exit_crossterm() -> color_eyre::Result<()> {
     Ok(())
 }
 
+/// Saves the terminal's title, so that it's restored when the returned guard
+/// is dropped, after the console has set its own.
+///
+/// This uses xterm's title stack, which most terminals and tmux support.
+/// Terminals which don't support it ignore it, and keep the console's title.
+pub(crate) fn save_title() -> color_eyre::Result<OnShutdown> {
+    use std::io::Write;
+    let mut stdout = std::io::stdout();
+    write!(stdout, "\x1b[22;0t")
+        .and_then(|()| stdout.flush())
+        .wrap_err("Failed to save the terminal title")?;
+    Ok(OnShutdown::new(restore_title))
+}
+
+fn restore_title() -> color_eyre::Result<()> {
+    use std::io::Write;
+    let mut stdout = std::io::stdout();
+    write!(stdout, "\x1b[23;0t")
+        .and_then(|()| stdout.flush())
+        .wrap_err("Failed to restore the terminal title")?;
+    Ok(())
+}
+
 pub struct OnShutdown {
     action: fn() -> color_eyre::Result<()>,
 }
@@ -45,3 +89,125 @@ impl Drop for OnShutdown {
         }
     }
 }
+
+/// Returns how many colors the terminfo database says `term` supports, if it
+/// has an entry for `term` which includes the `colors` capability.
+///
+/// Entries are looked up in the same directories as ncurses searches, so
+/// this agrees with `tput colors` without having to run it.
+pub(crate) fn terminfo_colors(term: &str) -> Option<i32> {
+    // Entry names are file names, so don't let them escape the database.
+    if term.is_empty() || term.contains(['/', '\\']) {
+        return None;
+    }
+    let entry = terminfo_dirs()
+        .iter()
+        .find_map(|dir| read_terminfo_entry(dir, term))?;
+    parse_terminfo_colors(&entry)
+}
+
+fn terminfo_dirs() -> Vec<PathBuf> {
+    let mut dirs = Vec::new();
+    if let Some(dir) = env::var_os("TERMINFO") {
+        dirs.push(dir.into());
+    }
+    if let Some(home) = dirs::home_dir() {
+        dirs.push(home.join(".terminfo"));
+    }
+    if let Some(list) = env::var_os("TERMINFO_DIRS") {
+        dirs.extend(env::split_paths(&list).filter(|dir| !dir.as_os_str().is_empty()));
+    }
+    dirs.extend(
+        [
+            "/etc/terminfo",
+            "/lib/terminfo",
+            "/usr/share/terminfo",
+            "/usr/lib/terminfo",
+        ]
+        .map(PathBuf::from),
+    );
+    dirs
+}
+
+fn read_terminfo_entry(dir: &Path, term: &str) -> Option<Vec<u8>> {
+    let first = term.chars().next()?;
+    // Entries are usually grouped by their first character, but macOS groups
+    // them by its hex code instead.
+    [first.to_string(), format!("{:x}", first as u32)]
+        .iter()
+        .find_map(|group| fs::read(dir.join(group).join(term)).ok())
+}
+
+/// Parses the `colors` capability out of a compiled terminfo entry, in either
+/// the legacy format or the extended number format of ncurses 6.1.
+fn parse_terminfo_colors(entry: &[u8]) -> Option<i32> {
+    let header = |idx: usize| {
+        let bytes = entry.get(idx * 2..idx * 2 + 2)?;
+        Some(u16::from_le_bytes([bytes[0], bytes[1]]) as usize)
+    };
+    let number_size = match header(0)? {
+        0o432 => 2,
+        0o1036 => 4,
+        _ => return None,
+    };
+    let (names_size, bools) = (header(1)?, header(2)?);
+    if header(3)? <= TERMINFO_COLORS {
+        return None;
+    }
+    // The numbers section starts at an even offset, after the header, names,
+    // and booleans.
+    let numbers = (12 + names_size + bools + 1) & !1;
+    let start = numbers + TERMINFO_COLORS * number_size;
+    let bytes = entry.get(start..start + number_size)?;
+    let colors = match *bytes {
+        [lo, hi] => i16::from_le_bytes([lo, hi]) as i32,
+        [a, b, c, d] => i32::from_le_bytes([a, b, c, d]),
+        _ => unreachable!("numbers are 2 or 4 bytes"),
+    };
+    // Absent and cancelled capabilities are negative.
+    (colors >= 0).then_some(colors)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Compiles a terminfo entry whose only number is `colors`.
+    fn entry(magic: u16, colors: i32) -> Vec<u8> {
+        let number_size = if magic == 0o432 { 2 } else { 4 };
+        let names = b"test|a test\0";
+        let numbers = TERMINFO_COLORS + 1;
+        let mut entry = Vec::new();
+        for value in [magic, names.len() as u16, 1, numbers as u16, 0, 0] {
+            entry.extend(value.to_le_bytes());
+        }
+        entry.extend(names);
+        // One boolean, then padding to an even offset.
+        entry.extend([1, 0]);
+        for idx in 0..numbers {
+            let value = if idx == TERMINFO_COLORS { colors } else { -1 };
+            entry.extend(&value.to_le_bytes()[..number_size]);
+        }
+        entry
+    }
+
+    #[test]
+    fn parses_legacy_entries() {
+        assert_eq!(parse_terminfo_colors(&entry(0o432, 256)), Some(256));
+        assert_eq!(parse_terminfo_colors(&entry(0o432, -1)), None);
+    }
+
+    #[test]
+    fn parses_extended_number_entries() {
+        assert_eq!(
+            parse_terminfo_colors(&entry(0o1036, 0x100_0000)),
+            Some(0x100_0000)
+        );
+    }
+
+    #[test]
+    fn rejects_other_files() {
+        assert_eq!(parse_terminfo_colors(b"not a terminfo entry"), None);
+        assert_eq!(parse_terminfo_colors(&entry(0o432, 8)[..30]), None);
+    }
+}
@@ -1,5 +1,6 @@
 pub(crate) use crate::view::table::view_controls;
 use crate::{
+    i18n::Msg,
     state::{
         async_ops::{AsyncOp, SortBy},
         resources::Resource,
@@ -189,10 +190,9 @@ impl TableList<9> for AsyncOpsTable {
             Table::default().rows(rows.rev())
         };
 
-        let block = styles.border_block().title(vec![bold(format!(
-            "Async Ops ({}) ",
-            table_list_state.len()
-        ))]);
+        let block = styles.border_block().title(vec![bold(
+            styles.fill(Msg::AsyncOpsTitle, &[("count", &table_list_state.len())]),
+        )]);
 
         let attributes_width = layout::Constraint::Percentage(100);
         let widths = &[
@@ -0,0 +1,216 @@
+use crate::{
+    i18n::Msg,
+    state::{
+        tasks::{CompletedSortBy, Task, Termination},
+        State,
+    },
+    view::{
+        self, bold,
+        controls::Controls,
+        table::{view_controls, TableList, TableListState},
+        DUR_LEN, DUR_TABLE_PRECISION,
+    },
+};
+use ratatui::{
+    layout,
+    style::{self, Color, Style},
+    text::Span,
+    widgets::{Cell, Row, Table},
+};
+use std::rc::Rc;
+
+/// The tasks which have completed, with the stats they had when they ended.
+///
+/// Completed tasks are also listed in the tasks table, but there they're
+/// mixed in with the tasks which are still running.
+#[derive(Debug, Default)]
+pub(crate) struct CompletedTasksTable {}
+
+impl TableList<8> for CompletedTasksTable {
+    type Row = Task;
+    type Sort = CompletedSortBy;
+    type Context = ();
+
+    const HEADER: &'static [&'static str; 8] = &[
+        "ID", "Name", "Ended", "Total", "Busy", "Polls", "Cause", "Location",
+    ];
+
+    const WIDTHS: &'static [usize; 8] = &[
+        Self::HEADER[0].len() + 1,
+        Self::HEADER[1].len() + 1,
+        DUR_LEN + 1,
+        DUR_LEN + 1,
+        DUR_LEN + 1,
+        Self::HEADER[5].len() + 1,
+        "never polled".len() + 1,
+        Self::HEADER[7].len() + 1,
+    ];
+
+    fn render(
+        table_list_state: &mut TableListState<Self, 8>,
+        styles: &view::Styles,
+        frame: &mut ratatui::terminal::Frame,
+        area: layout::Rect,
+        state: &mut State,
+        _: Self::Context,
+    ) {
+        let now = if let Some(now) = state.last_updated_at() {
+            now
+        } else {
+            // If we have never gotten an update yet, skip...
+            return;
+        };
+
+        // Tasks complete after they're added to the tasks table, so refresh
+        // the list from all of the tasks rather than taking new tasks.
+        table_list_state.sorted_items.clear();
+        table_list_state.sorted_items.extend(
+            state
+                .tasks_state()
+                .tasks()
+                .filter(|task| task.borrow().is_completed())
+                .map(Rc::downgrade),
+        );
+        table_list_state
+            .sort_by
+            .sort(now, &mut table_list_state.sorted_items);
+
+        let dur_cell = |dur: std::time::Duration| -> Cell<'static> {
+            Cell::from(styles.time_units(dur, DUR_TABLE_PRECISION, Some(DUR_LEN)))
+        };
+
+        // Start out wide enough to display the column headers...
+        let mut id_width = view::Width::new(Self::WIDTHS[0] as u16);
+        let mut name_width = view::Width::new(Self::WIDTHS[1] as u16);
+        let mut polls_width = view::Width::new(Self::WIDTHS[5] as u16);
+        let mut location_width = view::Width::new(Self::WIDTHS[7] as u16);
+
+        let mut num_cancelled = 0;
+        let mut num_historical = 0;
+
+        let rows = {
+            let id_width = &mut id_width;
+            let name_width = &mut name_width;
+            let polls_width = &mut polls_width;
+            let location_width = &mut location_width;
+            let num_cancelled = &mut num_cancelled;
+            let num_historical = &mut num_historical;
+
+            table_list_state
+                .sorted_items
+                .iter()
+                .filter_map(move |task| {
+                    let task = task.upgrade()?;
+                    let task = task.borrow();
+                    let termination = task.termination()?;
+                    if termination == Termination::Cancelled {
+                        *num_cancelled += 1;
+                    }
+                    let ended = task
+                        .dropped_at()
+                        .and_then(|dropped_at| now.duration_since(dropped_at).ok())
+                        .unwrap_or_default();
+
+                    // tasks which completed before the console attached are
+                    // dimmed, as they may have ended long before the others.
+                    let style = if task.is_historical() {
+                        *num_historical += 1;
+                        Style::default().add_modifier(style::Modifier::DIM)
+                    } else {
+                        Style::default()
+                    };
+                    let row = Row::new(vec![
+                        Cell::from(id_width.update_str(format!(
+                            "{:>width$}",
+                            task.id_str(),
+                            width = id_width.chars() as usize
+                        ))),
+                        Cell::from(name_width.update_str(task.name().unwrap_or("")).to_string()),
+                        dur_cell(ended),
+                        dur_cell(task.total(now)),
+                        dur_cell(task.busy(now)),
+                        Cell::from(polls_width.update_str(task.total_polls().to_string())),
+                        Cell::from(termination.render(styles)),
+                        Cell::from(location_width.update_str(task.location()).to_owned()),
+                    ]);
+                    Some(row.style(style))
+                })
+        };
+
+        let header_style = if styles.color(Color::Cyan).is_some() {
+            Style::default()
+        } else {
+            Style::default().add_modifier(style::Modifier::REVERSED)
+        };
+        let header_style = header_style.add_modifier(style::Modifier::BOLD);
+
+        let header = Row::new(Self::HEADER.iter().enumerate().map(|(idx, &value)| {
+            if idx == table_list_state.selected_column {
+                if table_list_state.sort_descending {
+                    Cell::from(styles.ascending(value))
+                } else {
+                    Cell::from(styles.descending(value))
+                }
+            } else {
+                Cell::from(value)
+            }
+        }))
+        .height(1)
+        .style(header_style);
+
+        let table = if table_list_state.sort_descending {
+            Table::default().rows(rows)
+        } else {
+            Table::default().rows(rows.rev())
+        };
+
+        let mut title = vec![
+            bold(styles.fill(
+                Msg::CompletedTasksTitle,
+                &[("count", &table_list_state.len())],
+            )),
+            Span::from(styles.fill(Msg::CancelledCount, &[("count", &num_cancelled)])),
+        ];
+        if num_historical > 0 {
+            title.push(Span::from(
+                styles.fill(Msg::HistoricalCount, &[("count", &num_historical)]),
+            ));
+        }
+        let block = styles.border_block().title(title);
+
+        let controls = Controls::new(view_controls(), &area, styles);
+        let chunks = layout::Layout::default()
+            .direction(layout::Direction::Vertical)
+            .margin(0)
+            .constraints(
+                [
+                    layout::Constraint::Length(controls.height()),
+                    layout::Constraint::Max(area.height),
+                ]
+                .as_ref(),
+            )
+            .split(area);
+        let (controls_area, tasks_area) = (chunks[0], chunks[1]);
+
+        let widths = &[
+            id_width.constraint(),
+            name_width.constraint(),
+            layout::Constraint::Length(Self::WIDTHS[2] as u16),
+            layout::Constraint::Length(Self::WIDTHS[3] as u16),
+            layout::Constraint::Length(Self::WIDTHS[4] as u16),
+            polls_width.constraint(),
+            layout::Constraint::Length(Self::WIDTHS[6] as u16),
+            location_width.constraint(),
+        ];
+
+        let table = table
+            .header(header)
+            .block(block)
+            .widths(widths)
+            .highlight_symbol(view::TABLE_HIGHLIGHT_SYMBOL)
+            .highlight_style(Style::default().add_modifier(style::Modifier::BOLD));
+
+        frame.render_stateful_widget(table, tasks_area, &mut table_list_state.table_state);
+        frame.render_widget(controls.into_widget(), controls_area);
+    }
+}
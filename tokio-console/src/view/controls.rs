@@ -1,22 +1,39 @@
-use crate::view::{self, bold};
+use crate::{
+    i18n::Msg,
+    view::{self, bold},
+};
 
 use ratatui::{
     layout,
     text::{Line, Span, Text},
-    widgets::{Paragraph, Widget},
+    widgets::{Paragraph, Widget, Wrap},
 };
 
 /// A list of controls which are available in all views.
 const UNIVERSAL_CONTROLS: &[ControlDisplay] = &[
     ControlDisplay {
-        action: "toggle pause",
+        action: Msg::TogglePause,
         keys: &[KeyDisplay {
             base: "space",
             utf8: None,
         }],
     },
     ControlDisplay {
-        action: "quit",
+        action: Msg::TimeFormat,
+        keys: &[KeyDisplay {
+            base: "z",
+            utf8: None,
+        }],
+    },
+    ControlDisplay {
+        action: Msg::Query,
+        keys: &[KeyDisplay {
+            base: ":query",
+            utf8: None,
+        }],
+    },
+    ControlDisplay {
+        action: Msg::Quit,
         keys: &[KeyDisplay {
             base: "q",
             utf8: None,
@@ -33,7 +50,7 @@ pub(crate) struct Controls {
 
 impl Controls {
     pub(in crate::view) fn new(
-        view_controls: &'static [ControlDisplay],
+        view_controls: &[ControlDisplay],
         area: &layout::Rect,
         styles: &view::Styles,
     ) -> Self {
@@ -41,7 +58,10 @@ impl Controls {
         line_controls.extend(view_controls.iter().map(|c| c.to_spans(styles, 0)));
         line_controls.extend(UNIVERSAL_CONTROLS.iter().map(|c| c.to_spans(styles, 0)));
 
-        let mut lines = vec![Line::from(vec![Span::from("controls: ")])];
+        let mut lines = vec![Line::from(vec![
+            Span::from(styles.text(Msg::Controls)),
+            Span::from(" "),
+        ])];
         let mut current_line = lines.last_mut().expect("This vector is never empty");
         let separator = Span::from(", ");
 
@@ -105,12 +125,27 @@ pub(crate) fn controls_paragraph<'a>(
     view_controls: &[ControlDisplay],
     styles: &view::Styles,
 ) -> Paragraph<'a> {
+    Paragraph::new(controls_lines(view_controls, styles))
+}
+
+/// Construct a help paragraph which explains `note` about the view, followed
+/// by the view's controls.
+pub(crate) fn noted_controls_paragraph<'a>(
+    note: Msg,
+    view_controls: &[ControlDisplay],
+    styles: &view::Styles,
+) -> Paragraph<'a> {
+    let mut lines = vec![Line::from(styles.text(note)), Line::default()];
+    lines.extend(controls_lines(view_controls, styles));
+    Paragraph::new(lines).wrap(Wrap { trim: false })
+}
+
+fn controls_lines<'a>(view_controls: &[ControlDisplay], styles: &view::Styles) -> Vec<Line<'a>> {
     let mut spans = Vec::with_capacity(1 + view_controls.len() + UNIVERSAL_CONTROLS.len());
-    spans.push(Line::from(vec![Span::raw("controls:")]));
+    spans.push(Line::from(vec![Span::raw(styles.text(Msg::Controls))]));
     spans.extend(view_controls.iter().map(|c| c.to_spans(styles, 2)));
     spans.extend(UNIVERSAL_CONTROLS.iter().map(|c| c.to_spans(styles, 2)));
-
-    Paragraph::new(spans)
+    spans
 }
 
 /// Construct span to display a control.
@@ -119,7 +154,7 @@ pub(crate) fn controls_paragraph<'a>(
 /// that action.
 #[derive(Clone)]
 pub(crate) struct ControlDisplay {
-    pub(crate) action: &'static str,
+    pub(crate) action: Msg,
     pub(crate) keys: &'static [KeyDisplay],
 }
 
@@ -141,11 +176,11 @@ impl ControlDisplay {
         let mut spans = Vec::new();
 
         spans.push(Span::from(" ".repeat(indent)));
-        spans.push(Span::from(self.action));
+        spans.push(Span::from(styles.text(self.action)));
         spans.push(Span::from(" = "));
         for (idx, key_display) in self.keys.iter().enumerate() {
             if idx > 0 {
-                spans.push(Span::from(" or "))
+                spans.push(Span::from(format!(" {} ", styles.text(Msg::Or))))
             }
             spans.push(bold(match key_display.utf8 {
                 Some(utf8) => styles.if_utf8(utf8, key_display.base),
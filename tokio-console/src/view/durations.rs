@@ -1,13 +1,15 @@
-use std::cmp;
+use std::{cmp, time::Duration};
 
 use ratatui::{
     layout::{self},
-    widgets::Widget,
+    text::{Line, Span, Text},
+    widgets::{Paragraph, Widget},
 };
 
 use crate::{
+    i18n::Msg,
     state::histogram::DurationHistogram,
-    view::{self, mini_histogram::MiniHistogram, percentiles::Percentiles},
+    view::{self, bold, mini_histogram::MiniHistogram, percentiles::Percentiles},
 };
 
 // This is calculated so that a legend like the below generally fits:
@@ -15,6 +17,22 @@ use crate::{
 // This also gives at characters for the sparkline itself.
 const MIN_HISTOGRAM_BLOCK_WIDTH: u16 = 22;
 
+/// The percentiles listed when a histogram is shown as a percentile table.
+const TABLE_PERCENTILES: [(&str, f64); 4] =
+    [("p50", 50.0), ("p90", 90.0), ("p99", 99.0), ("p99.9", 99.9)];
+
+/// How the histogram next to the percentiles list is displayed.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub(crate) enum HistogramMode {
+    /// A sparkline of the bucketized durations.
+    #[default]
+    Chart,
+    /// A table of the tail percentiles and the maximum.
+    Percentiles,
+    /// The number of durations in each of a series of linear buckets.
+    Counts,
+}
+
 /// This is a Ratatui widget to visualize durations as a list of percentiles
 /// and if possible, a mini-histogram too.
 ///
@@ -24,7 +42,8 @@ const MIN_HISTOGRAM_BLOCK_WIDTH: u16 = 22;
 ///   b) There is at least a minimum width (22 characters to display the full
 ///      bottom legend) left after drawing the percentiles
 ///
-/// This
+/// The [`HistogramMode`] replaces the sparkline with a percentile table or a
+/// list of bucket counts instead, neither of which needs UTF-8.
 ///
 /// [`Styles`]: crate::view::Styles
 pub(crate) struct Durations<'a> {
@@ -38,53 +57,76 @@ pub(crate) struct Durations<'a> {
     histogram_title: &'a str,
     /// Fixed width for percentiles block
     percentiles_width: u16,
+    /// How to display the histogram
+    mode: HistogramMode,
 }
 
 impl Widget for Durations<'_> {
     fn render(self, area: ratatui::layout::Rect, buf: &mut ratatui::buffer::Buffer) {
         // Only split the durations area in half if we're also drawing a
         // sparkline. We require UTF-8 to draw the sparkline and also enough width.
-        let (percentiles_area, histogram_area) = if self.styles.utf8 {
-            let percentiles_width = match self.percentiles_width {
-                // Fixed width
-                width if width > 0 => width,
-                // Long enough for the title or for a single line
-                // like "p99: 544.77µs" (13) (and borders on the sides).
-                _ => cmp::max(self.percentiles_title.len() as u16, 13_u16) + 2,
-            };
+        // The percentile table and bucket counts are plain text, so only the
+        // sparkline needs UTF-8.
+        let (percentiles_area, histogram_area) =
+            if self.styles.utf8 || self.mode != HistogramMode::Chart {
+                let percentiles_width = match self.percentiles_width {
+                    // Fixed width
+                    width if width > 0 => width,
+                    // Long enough for the title or for a single line
+                    // like "p99: 544.77µs" (13) (and borders on the sides).
+                    _ => cmp::max(self.percentiles_title.len() as u16, 13_u16) + 2,
+                };
 
-            // If there isn't enough width left after drawing the percentiles
-            // then we won't draw the sparkline at all.
-            if area.width < percentiles_width + MIN_HISTOGRAM_BLOCK_WIDTH {
-                (area, None)
+                // If there isn't enough width left after drawing the percentiles
+                // then we won't draw the sparkline at all.
+                if area.width < percentiles_width + MIN_HISTOGRAM_BLOCK_WIDTH {
+                    (area, None)
+                } else {
+                    let areas = layout::Layout::default()
+                        .direction(layout::Direction::Horizontal)
+                        .constraints(
+                            [
+                                layout::Constraint::Length(percentiles_width),
+                                layout::Constraint::Min(MIN_HISTOGRAM_BLOCK_WIDTH),
+                            ]
+                            .as_ref(),
+                        )
+                        .split(area);
+                    (areas[0], Some(areas[1]))
+                }
             } else {
-                let areas = layout::Layout::default()
-                    .direction(layout::Direction::Horizontal)
-                    .constraints(
-                        [
-                            layout::Constraint::Length(percentiles_width),
-                            layout::Constraint::Min(MIN_HISTOGRAM_BLOCK_WIDTH),
-                        ]
-                        .as_ref(),
-                    )
-                    .split(area);
-                (areas[0], Some(areas[1]))
-            }
-        } else {
-            (area, None)
-        };
+                (area, None)
+            };
 
         let percentiles_widget = Percentiles::new(self.styles)
             .title(self.percentiles_title)
             .histogram(self.histogram);
         percentiles_widget.render(percentiles_area, buf);
 
-        if let Some(histogram_area) = histogram_area {
-            let histogram_widget = MiniHistogram::default()
-                .block(self.styles.border_block().title(self.histogram_title))
-                .histogram(self.histogram)
-                .duration_precision(2);
-            histogram_widget.render(histogram_area, buf);
+        let Some(histogram_area) = histogram_area else {
+            return;
+        };
+        let block = self.styles.border_block().title(self.histogram_title);
+        match self.mode {
+            HistogramMode::Chart => {
+                let histogram_widget = MiniHistogram::default()
+                    .block(block)
+                    .histogram(self.histogram)
+                    .duration_precision(2);
+                histogram_widget.render(histogram_area, buf);
+            }
+            HistogramMode::Percentiles => {
+                Paragraph::new(self.percentile_table())
+                    .block(block)
+                    .render(histogram_area, buf);
+            }
+            HistogramMode::Counts => {
+                // Subtract 2 for the top and bottom borders.
+                let rows = histogram_area.height.saturating_sub(2) as usize;
+                Paragraph::new(self.bucket_counts(rows))
+                    .block(block)
+                    .render(histogram_area, buf);
+            }
         }
     }
 }
@@ -94,9 +136,10 @@ impl<'a> Durations<'a> {
         Self {
             styles,
             histogram: None,
-            percentiles_title: "Percentiles",
-            histogram_title: "Histogram",
+            percentiles_title: styles.text(Msg::Percentiles),
+            histogram_title: styles.text(Msg::Histogram),
             percentiles_width: 0,
+            mode: HistogramMode::default(),
         }
     }
 
@@ -119,4 +162,98 @@ impl<'a> Durations<'a> {
         self.percentiles_width = width;
         self
     }
+
+    pub(crate) fn mode(mut self, mode: HistogramMode) -> Self {
+        self.mode = mode;
+        self
+    }
+
+    fn duration(&self, nanos: u64) -> Span<'static> {
+        self.styles
+            .time_units(Duration::from_nanos(nanos), view::DUR_LIST_PRECISION, None)
+    }
+
+    fn percentile_table(&self) -> Text<'static> {
+        let Some(DurationHistogram { histogram, .. }) = self.histogram else {
+            return Text::default();
+        };
+
+        let width = TABLE_PERCENTILES
+            .iter()
+            .map(|(label, _)| label.len())
+            .max()
+            .unwrap_or(0);
+        let mut lines = TABLE_PERCENTILES
+            .iter()
+            .map(|&(label, percentile)| {
+                Line::from(vec![
+                    bold(format!("{label:>width$}: ")),
+                    self.duration(histogram.value_at_percentile(percentile)),
+                ])
+            })
+            .collect::<Vec<_>>();
+        let max = self.styles.text(Msg::HistogramMax);
+        lines.push(Line::from(vec![
+            bold(format!("{max:>width$}: ")),
+            self.duration(histogram.max()),
+        ]));
+        Text::from(lines)
+    }
+
+    /// Splits the recorded durations into at most `rows` equally sized
+    /// buckets, and lists how many durations fell into each.
+    fn bucket_counts(&self, rows: usize) -> Text<'static> {
+        let Some(DurationHistogram {
+            histogram,
+            high_outliers,
+            ..
+        }) = self.histogram
+        else {
+            return Text::default();
+        };
+        if histogram.is_empty() || rows == 0 {
+            return Text::default();
+        }
+
+        // Leave a row for the outliers, which aren't in the histogram.
+        let rows = if *high_outliers > 0 && rows > 1 {
+            rows - 1
+        } else {
+            rows
+        };
+        let min = histogram.min();
+        let max = histogram.max();
+        let step = ((max - min) / rows as u64).max(1);
+        let buckets = cmp::min(rows as u64, (max - min) / step + 1) as usize;
+
+        let mut counts = vec![0; buckets];
+        for value in histogram.iter_recorded() {
+            let bucket = (value.value_iterated_to().saturating_sub(min) / step) as usize;
+            counts[bucket.min(buckets - 1)] += value.count_at_value();
+        }
+
+        let mut lines = Vec::with_capacity(buckets + 1);
+        for (i, count) in counts.into_iter().enumerate() {
+            let low = min + i as u64 * step;
+            let high = if i + 1 == buckets {
+                max
+            } else {
+                low + step - 1
+            };
+            lines.push(Line::from(vec![
+                self.duration(low),
+                Span::raw(" - "),
+                self.duration(high),
+                Span::raw(": "),
+                bold(count.to_string()),
+            ]));
+        }
+        if *high_outliers > 0 {
+            lines.push(Line::from(vec![
+                Span::raw(self.styles.text(Msg::HistogramOutliers)),
+                bold(high_outliers.to_string()),
+            ]));
+        }
+        Text::from(lines)
+    }
 }
@@ -3,7 +3,7 @@ use ratatui::{
     widgets::{Clear, Paragraph},
 };
 
-use crate::{state::State, view};
+use crate::{i18n::Msg, state::State, view};
 
 pub(crate) trait HelpText {
     fn render_help_content(&self, styles: &view::Styles) -> Paragraph<'static>;
@@ -58,7 +58,7 @@ impl<'a> HelpView<'a> {
             )
             .split(popup_layout[1])[1];
 
-        let display_text = content.block(styles.border_block().title("Help"));
+        let display_text = content.block(styles.border_block().title(styles.text(Msg::Help)));
 
         // Clear the help block area and render the popup
         frame.render_widget(Clear, popup_area);
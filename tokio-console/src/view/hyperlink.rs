@@ -0,0 +1,120 @@
+//! OSC 8 hyperlinks, which terminals that support them open when the text
+//! they are drawn over is clicked.
+//!
+//! `ratatui` works out how wide a cell is from its symbol, so the escape
+//! sequences can't be written into the buffer along with the text. Instead,
+//! views note which areas should be links as they render, and once a frame
+//! has been drawn, the text in those areas is drawn again inside the escape
+//! sequences. Cells which are unchanged in later frames aren't redrawn, so
+//! the terminal keeps them linked.
+use crate::state::SourceLocation;
+use ratatui::{backend::Backend, buffer::Buffer, layout::Rect};
+use std::{fmt::Write as _, io};
+
+/// An area of the screen which links to a URL.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) struct Hyperlink {
+    pub(crate) area: Rect,
+    pub(crate) url: String,
+}
+
+/// The characters which are left as they are in the file paths filled into
+/// templates, besides ASCII letters and digits.
+const UNRESERVED: &str = "-._~/:@!$&'()*+,;=";
+
+/// Fills the `{file}`, `{line}`, and `{column}` placeholders in `template`
+/// with those of `location`.
+///
+/// A location without a line or column links to the first one. Characters in
+/// the file which can't appear in URLs are percent-encoded, and control
+/// characters are removed from the template, so that the URL can't end the
+/// escape sequence it's written in early.
+pub(crate) fn location_url(template: &str, location: &SourceLocation) -> String {
+    let mut file = String::with_capacity(location.file.len());
+    for byte in location.file.bytes() {
+        if byte.is_ascii_alphanumeric() || UNRESERVED.as_bytes().contains(&byte) {
+            file.push(byte as char);
+        } else {
+            let _ = write!(file, "%{byte:02X}");
+        }
+    }
+    template
+        .chars()
+        .filter(|c| !c.is_control())
+        .collect::<String>()
+        .replace("{file}", &file)
+        .replace("{line}", &location.line.unwrap_or(1).to_string())
+        .replace("{column}", &location.column.unwrap_or(1).to_string())
+}
+
+/// Draws the cells of `buffer` which are covered by `links` again, inside
+/// the escape sequences which make them links.
+pub(crate) fn draw<B>(backend: &mut B, buffer: &Buffer, links: &[Hyperlink]) -> io::Result<()>
+where
+    B: Backend + io::Write,
+{
+    for link in links {
+        let area = link.area.intersection(buffer.area);
+        if area.is_empty() {
+            continue;
+        }
+        let cells = (area.top()..area.bottom())
+            .flat_map(|y| (area.left()..area.right()).map(move |x| (x, y)))
+            .map(|(x, y)| (x, y, buffer.get(x, y)));
+        write!(backend, "\x1b]8;;{}\x1b\\", link.url)?;
+        Backend::draw(backend, cells)?;
+        write!(backend, "\x1b]8;;\x1b\\")?;
+    }
+    Backend::flush(backend)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ratatui::backend::CrosstermBackend;
+
+    fn location(file: &str) -> SourceLocation {
+        SourceLocation {
+            file: file.to_owned(),
+            line: Some(12),
+            column: None,
+        }
+    }
+
+    #[test]
+    fn fills_location_templates() {
+        assert_eq!(
+            location_url(
+                "vscode://file/{file}:{line}:{column}",
+                &location("/src/my app/main.rs")
+            ),
+            "vscode://file//src/my%20app/main.rs:12:1"
+        );
+        assert_eq!(
+            location_url(
+                "https://example.com/blob/main/{file}#L{line}\x07",
+                &location("src/main.rs\x1b\\")
+            ),
+            "https://example.com/blob/main/src/main.rs%1B%5C#L12"
+        );
+    }
+
+    #[test]
+    fn draws_links_around_their_cells() {
+        let mut buffer = Buffer::empty(Rect::new(0, 0, 10, 2));
+        buffer.set_string(0, 1, "main.rs:12", ratatui::style::Style::default());
+        let mut output = Vec::new();
+        let link = Hyperlink {
+            area: Rect::new(0, 1, 7, 1),
+            url: "file:///main.rs".to_owned(),
+        };
+        draw(&mut CrosstermBackend::new(&mut output), &buffer, &[link]).unwrap();
+
+        let output = String::from_utf8(output).unwrap();
+        let start = output.find("\x1b]8;;file:///main.rs\x1b\\").unwrap();
+        let end = output.rfind("\x1b]8;;\x1b\\").unwrap();
+        let text = &output[start..end];
+        assert!(text.contains("main.rs"));
+        assert!(!text.contains(":12"));
+    }
+}
@@ -1,27 +1,67 @@
 use crate::view::help::HelpView;
 use crate::view::{
-    help::HelpText, resources::ResourcesTable, table::TableListState, tasks::TasksTable,
+    completed_tasks::CompletedTasksTable,
+    help::HelpText,
+    plugin::ViewPlugin,
+    resources::ResourcesTable,
+    search::Search,
+    select_stats::SelectStatsTable,
+    spans::SpansTable,
+    table::{Seek, TableListState},
+    targets::TargetsTable,
+    task_groups::TaskGroupsTable,
+    tasks::{TasksContext, TasksTable},
+    timer_lag::TimerLagTable,
+    wake_graph::WakeGraphTable,
+    waker_leaks::WakerLeaksTable,
+    workers::WorkersTable,
+};
+use crate::{
+    config,
+    i18n::Msg,
+    input,
+    query::{Filter, Query},
+    session::{Session, SessionView, TableSession},
+    state::{tasks::Task, State},
 };
-use crate::{input, state::State};
 use ratatui::{
     layout,
-    style::{self, Style},
-    text::Span,
+    style::{self, Color, Style},
+    text::{Line, Span},
+    widgets::Paragraph,
 };
-use std::{borrow::Cow, cmp};
+use std::{borrow::Cow, cell::RefCell, cmp, rc::Rc};
 
 mod async_ops;
+mod completed_tasks;
 mod controls;
 mod durations;
 mod help;
+pub(crate) mod hyperlink;
 mod mini_histogram;
 mod percentiles;
+pub(crate) mod plugin;
+mod query;
 mod resource;
 mod resources;
+mod search;
+mod select_stats;
+mod spans;
 mod styles;
 mod table;
+mod targets;
 mod task;
+mod task_group;
+mod task_groups;
 mod tasks;
+mod timer_lag;
+mod wake_graph;
+mod waker_leaks;
+mod warnings;
+mod workers;
+
+#[cfg(test)]
+mod tests;
 pub(crate) use self::styles::{Palette, Styles};
 pub(crate) use self::table::SortBy;
 
@@ -31,9 +71,12 @@ pub(crate) use self::table::SortBy;
 const DUR_LEN: usize = 6;
 // Precision (after decimal point) for durations displayed in a list
 // (detail view)
-const DUR_LIST_PRECISION: usize = 2;
+pub(crate) const DUR_LIST_PRECISION: usize = 2;
 // Precision (after decimal point) for durations displayed in a table
 const DUR_TABLE_PRECISION: usize = 0;
+// Width of a point in time displayed in a table, which is long enough for a
+// time of day (`HH:MM:SS`). Like durations, these don't need much precision.
+const TIMESTAMP_LEN: usize = 8;
 const TABLE_HIGHLIGHT_SYMBOL: &str = ">> ";
 
 pub struct View {
@@ -44,8 +87,36 @@ pub struct View {
     /// details view), we want to leave the task list's state the way we left it
     /// --- e.g., if the user previously selected a particular sorting, we want
     /// it to remain sorted that way when we return to it.
-    tasks_list: TableListState<TasksTable, 12>,
+    tasks_list: TableListState<TasksTable, 21>,
+    completed_tasks_list: TableListState<CompletedTasksTable, 8>,
     resources_list: TableListState<ResourcesTable, 9>,
+    task_groups_list: TableListState<TaskGroupsTable, 13>,
+    select_stats_list: TableListState<SelectStatsTable, 7>,
+    timer_lag_list: TableListState<TimerLagTable, 5>,
+    workers_list: TableListState<WorkersTable, 11>,
+    spans_list: TableListState<SpansTable, 8>,
+    wake_graph_list: TableListState<WakeGraphTable, 5>,
+    waker_leaks_list: TableListState<WakerLeaksTable, 6>,
+    targets_list: TableListState<TargetsTable, 5>,
+    /// The expanded task group a task instance was opened from, if any, so
+    /// that exiting the task returns to the group.
+    expanded_group: Option<self::task_group::TaskGroupView>,
+    /// The warnings view a task or resource was opened from, if any, so that
+    /// exiting its details returns to the warnings.
+    expanded_warnings: Option<self::warnings::WarningsView>,
+    /// Whether the task being inspected was opened from the completed tasks
+    /// list, so that exiting its details returns to that list.
+    expanded_completed: bool,
+    /// The command being typed after pressing `:`, if any.
+    prompt: Option<Prompt>,
+    /// The search of the tasks list started by pressing `/`, if any.
+    search: Option<Search>,
+    /// The filters and sorts for the tasks list set in the config file.
+    presets: Vec<Preset>,
+    /// The index of the preset applied to the tasks list, if any.
+    active_preset: Option<usize>,
+    /// The custom views the console was built with.
+    plugins: Vec<Box<dyn ViewPlugin>>,
     state: ViewState,
     show_help_modal: bool,
     pub(crate) styles: Styles,
@@ -54,12 +125,55 @@ pub struct View {
 pub(crate) enum ViewState {
     /// The table list of all tasks.
     TasksList,
+    /// The table list of tasks which have completed.
+    CompletedTasksList,
     /// The table list of all resources.
     ResourcesList,
+    /// The table list of tasks grouped by spawn location.
+    TaskGroupsList,
+    /// Inspecting the tasks in a single task group.
+    TaskGroupInstance(self::task_group::TaskGroupView),
+    /// The table list of instrumented `select!` callsites.
+    SelectStatsList,
+    /// The table list of timer callsites and their deadline overruns.
+    TimerLagList,
+    /// The table list of threads which poll tasks, and the runtime's workers.
+    WorkersList,
+    /// The table list of the spans the application has asked to watch.
+    SpansList,
+    /// The table list of which tasks wake which other tasks.
+    WakeGraphList,
+    /// The table list of completed tasks whose wakers are still alive.
+    WakerLeaksList,
+    /// The table list of tasks grouped by the `tracing` target of their
+    /// spans.
+    TargetsList,
     /// Inspecting a single task instance.
     TaskInstance(self::task::TaskView),
     /// Inspecting a single resource instance.
     ResourceInstance(self::resource::ResourceView),
+    /// The results of a query entered with `:query`.
+    Query(self::query::QueryView),
+    /// All of the currently detected warnings, grouped by lint.
+    WarningsList(self::warnings::WarningsView),
+    /// The custom view at this index in the view's plugins.
+    Plugin(usize),
+}
+
+/// A preset from the config file, ready to be applied to the tasks list.
+struct Preset {
+    name: String,
+    key: char,
+    filter: Option<Rc<Filter>>,
+    sort: Option<TableSession>,
+}
+
+/// A command being typed at the `:` prompt.
+#[derive(Default)]
+struct Prompt {
+    text: String,
+    /// The error from the last attempt to run the command, if it failed.
+    error: Option<String>,
 }
 
 /// The outcome of the update_input method
@@ -100,32 +214,283 @@ impl View {
     pub fn new(styles: Styles) -> Self {
         Self {
             state: ViewState::TasksList,
-            tasks_list: TableListState::<TasksTable, 12>::default(),
+            tasks_list: TableListState::<TasksTable, 21>::default(),
+            completed_tasks_list: TableListState::<CompletedTasksTable, 8>::default(),
             resources_list: TableListState::<ResourcesTable, 9>::default(),
+            task_groups_list: TableListState::<TaskGroupsTable, 13>::default(),
+            select_stats_list: TableListState::<SelectStatsTable, 7>::default(),
+            timer_lag_list: TableListState::<TimerLagTable, 5>::default(),
+            workers_list: TableListState::<WorkersTable, 11>::default(),
+            spans_list: TableListState::<SpansTable, 8>::default(),
+            wake_graph_list: TableListState::<WakeGraphTable, 5>::default(),
+            waker_leaks_list: TableListState::<WakerLeaksTable, 6>::default(),
+            targets_list: TableListState::<TargetsTable, 5>::default(),
+            expanded_group: None,
+            expanded_warnings: None,
+            expanded_completed: false,
+            prompt: None,
+            search: None,
+            presets: Vec::new(),
+            active_preset: None,
+            plugins: Vec::new(),
             show_help_modal: false,
             styles,
         }
     }
 
-    pub(crate) fn update_input(&mut self, event: input::Event, state: &State) -> UpdateKind {
+    /// Binds the presets from the config file to their keys.
+    pub(crate) fn with_presets(mut self, presets: &[config::Preset]) -> Self {
+        self.presets = presets
+            .iter()
+            .map(|preset| Preset {
+                name: preset.name.clone(),
+                key: preset.key,
+                // filters are checked when the config file is loaded.
+                filter: preset
+                    .filter
+                    .as_deref()
+                    .and_then(|filter| Filter::parse(filter).ok())
+                    .map(Rc::new),
+                sort: preset.sort.clone().map(|sort_column| TableSession {
+                    sort_column,
+                    // The tasks list lists the largest values first when
+                    // it's *not* sorted in descending order.
+                    sort_descending: !preset.descending.unwrap_or(true),
+                }),
+            })
+            .collect();
+        self
+    }
+
+    /// Adds custom views, each opened by pressing its key.
+    pub(crate) fn with_plugins(mut self, plugins: Vec<Box<dyn ViewPlugin>>) -> Self {
+        self.plugins = plugins;
+        self
+    }
+
+    /// Passes the console's latest state to every custom view.
+    pub(crate) fn update_plugins(&mut self, state: &State) {
+        for plugin in &mut self.plugins {
+            plugin.update(state);
+        }
+    }
+
+    /// Returns the areas rendered as links in the last frame.
+    pub(crate) fn hyperlinks(&self) -> &[hyperlink::Hyperlink] {
+        match self.state {
+            // the help modal is drawn over the view, links and all.
+            ViewState::TaskInstance(ref view) if !self.show_help_modal => view.links(),
+            _ => &[],
+        }
+    }
+
+    /// Returns the name of the preset applied to the tasks list, if any.
+    pub(crate) fn active_preset(&self) -> Option<&str> {
+        let preset = &self.presets[self.active_preset?];
+        Some(&preset.name)
+    }
+
+    /// Switches to the tasks list and applies the preset at `idx`, or removes
+    /// it if it's already applied to the tasks list.
+    fn toggle_preset(&mut self, idx: usize, state: &mut State) {
+        let reapplied =
+            matches!(self.state, ViewState::TasksList) && self.active_preset == Some(idx);
+        self.state = ViewState::TasksList;
+        self.expanded_group = None;
+        self.expanded_warnings = None;
+        self.expanded_completed = false;
+        self.active_preset = if reapplied { None } else { Some(idx) };
+        if !reapplied {
+            if let Some(ref sort) = self.presets[idx].sort {
+                self.tasks_list.restore_session(sort);
+            }
+        }
+
+        // A filter leaves out tasks which are no longer taken from the state
+        // as new tasks, so start again from every task.
+        state.tasks_state_mut().take_new_tasks().for_each(drop);
+        self.tasks_list.sorted_items = state.tasks_state().tasks().map(Rc::downgrade).collect();
+    }
+
+    /// Switches to the details view for `task`.
+    pub(crate) fn open_task(&mut self, task: Rc<RefCell<Task>>, state: &State) -> UpdateKind {
+        let span_id = task.borrow().span_id();
+        self.state =
+            ViewState::TaskInstance(self::task::TaskView::new(task, state.task_details_ref()));
+        self.expanded_group = None;
+        self.expanded_warnings = None;
+        self.expanded_completed = false;
+        UpdateKind::SelectTask(span_id)
+    }
+
+    /// Switches to the details view for a task or resource selected in the
+    /// warnings view, which is returned to when the details view is exited.
+    fn open_warning(&mut self, selection: self::warnings::Selection, state: &State) -> UpdateKind {
+        let warnings = match std::mem::replace(&mut self.state, ViewState::TasksList) {
+            ViewState::WarningsList(warnings) => Some(warnings),
+            _ => None,
+        };
+        let update_kind = match selection {
+            self::warnings::Selection::Task(task) => self.open_task(task, state),
+            self::warnings::Selection::Resource(resource) => {
+                let span_id = resource.borrow().span_id();
+                self.state =
+                    ViewState::ResourceInstance(self::resource::ResourceView::new(resource));
+                UpdateKind::SelectResource(span_id)
+            }
+        };
+        self.expanded_warnings = warnings;
+        update_kind
+    }
+
+    pub(crate) fn update_input(&mut self, event: input::Event, state: &mut State) -> UpdateKind {
         use ViewState::*;
         let mut update_kind = UpdateKind::Other;
 
+        if self.prompt.is_some() {
+            return self.update_prompt(event, state);
+        }
+
+        if self.search.as_ref().is_some_and(Search::is_editing) {
+            return self.update_search(event, state);
+        }
+
+        if !self.show_help_modal && matches!(event, key!(Char(':'))) {
+            // when viewing a query's results, start from that query so that
+            // it can be refined.
+            let text = match self.state {
+                Query(ref view) => format!("query {}", view.text()),
+                _ => String::new(),
+            };
+            self.prompt = Some(Prompt { text, error: None });
+            return update_kind;
+        }
+
         if self.should_toggle_help_modal(&event) {
             self.show_help_modal = !self.show_help_modal;
             return update_kind;
         }
 
+        if matches!(event, key!(Char('z'))) {
+            self.styles.cycle_time_format();
+            return update_kind;
+        }
+
         if matches!(event, key!(Char('t'))) {
             self.state = TasksList;
+            self.expanded_group = None;
+            self.expanded_warnings = None;
+            self.expanded_completed = false;
+            return update_kind;
+        }
+
+        if matches!(event, key!(Char('c'))) {
+            self.state = CompletedTasksList;
+            self.expanded_group = None;
+            self.expanded_warnings = None;
+            self.expanded_completed = false;
             return update_kind;
         }
 
         if matches!(event, key!(Char('r'))) {
             self.state = ResourcesList;
+            self.expanded_group = None;
+            self.expanded_warnings = None;
+            self.expanded_completed = false;
             return update_kind;
         }
 
+        if matches!(event, key!(Char('s'))) {
+            self.state = TaskGroupsList;
+            self.expanded_group = None;
+            self.expanded_warnings = None;
+            self.expanded_completed = false;
+            return update_kind;
+        }
+
+        if matches!(event, key!(Char('b'))) {
+            self.state = SelectStatsList;
+            self.expanded_group = None;
+            self.expanded_warnings = None;
+            self.expanded_completed = false;
+            return update_kind;
+        }
+
+        if matches!(event, key!(Char('w'))) {
+            self.state = TimerLagList;
+            self.expanded_group = None;
+            self.expanded_warnings = None;
+            self.expanded_completed = false;
+            return update_kind;
+        }
+
+        if matches!(event, key!(Char('R'))) {
+            self.state = WorkersList;
+            self.expanded_group = None;
+            self.expanded_warnings = None;
+            self.expanded_completed = false;
+            return update_kind;
+        }
+
+        if matches!(event, key!(Char('u'))) {
+            self.state = SpansList;
+            self.expanded_group = None;
+            self.expanded_warnings = None;
+            self.expanded_completed = false;
+            return update_kind;
+        }
+
+        if matches!(event, key!(Char('e'))) {
+            self.state = WakeGraphList;
+            self.expanded_group = None;
+            self.expanded_warnings = None;
+            self.expanded_completed = false;
+            return update_kind;
+        }
+
+        if matches!(event, key!(Char('L'))) {
+            self.state = WakerLeaksList;
+            self.expanded_group = None;
+            self.expanded_warnings = None;
+            self.expanded_completed = false;
+            return update_kind;
+        }
+
+        if matches!(event, key!(Char('T'))) {
+            self.state = TargetsList;
+            self.expanded_group = None;
+            self.expanded_warnings = None;
+            self.expanded_completed = false;
+            return update_kind;
+        }
+
+        if matches!(event, key!(Char('W'))) {
+            self.state = WarningsList(self::warnings::WarningsView::default());
+            self.expanded_group = None;
+            self.expanded_warnings = None;
+            self.expanded_completed = false;
+            return update_kind;
+        }
+
+        if let input::Event::Key(input::KeyEvent {
+            code: input::KeyCode::Char(c),
+            ..
+        }) = event
+        {
+            if let Some(idx) = self.presets.iter().position(|preset| preset.key == c) {
+                self.toggle_preset(idx, state);
+                return update_kind;
+            }
+            if let Some(idx) = self.plugins.iter().position(|plugin| plugin.key() == c) {
+                tracing::debug!(plugin = self.plugins[idx].name(), "opening plugin view");
+                self.state = Plugin(idx);
+                self.expanded_group = None;
+                self.expanded_warnings = None;
+                self.expanded_completed = false;
+                return update_kind;
+            }
+        }
+
         match self.state {
             TasksList => {
                 // The enter key changes views, so handle here since we can
@@ -133,19 +498,37 @@ impl View {
                 match event {
                     key!(Enter) => {
                         if let Some(task) = self.tasks_list.selected_item() {
-                            update_kind = UpdateKind::SelectTask(task.borrow().span_id());
-                            self.state = TaskInstance(self::task::TaskView::new(
-                                task,
-                                state.task_details_ref(),
-                            ));
+                            update_kind = self.open_task(task, state);
                         }
                     }
+                    key!(Char('/')) => self.search = Some(Search::new()),
+                    key!(Esc) => self.search = None,
+                    key!(Char('n')) | key!(Char('N')) if self.search.is_some() => {
+                        let seek = if matches!(event, key!(Char('n'))) {
+                            Seek::Next
+                        } else {
+                            Seek::Prev
+                        };
+                        self.select_search_match(seek, state);
+                    }
                     _ => {
                         // otherwise pass on to view
                         self.tasks_list.update_input(event);
                     }
                 }
             }
+            CompletedTasksList => match event {
+                key!(Enter) => {
+                    if let Some(task) = self.completed_tasks_list.selected_item() {
+                        update_kind = self.open_task(task, state);
+                        self.expanded_completed = true;
+                    }
+                }
+                _ => {
+                    // otherwise pass on to view
+                    self.completed_tasks_list.update_input(event);
+                }
+            },
             ResourcesList => {
                 match event {
                     key!(Enter) => {
@@ -160,12 +543,54 @@ impl View {
                     }
                 }
             }
+            TaskGroupsList => match event {
+                key!(Enter) => {
+                    if let Some(group) = self.task_groups_list.selected_item() {
+                        self.state = TaskGroupInstance(self::task_group::TaskGroupView::new(group));
+                    }
+                }
+                _ => {
+                    // otherwise pass on to view
+                    self.task_groups_list.update_input(event);
+                }
+            },
+            TaskGroupInstance(ref mut view) => match event {
+                key!(Esc) => {
+                    self.state = TaskGroupsList;
+                }
+                key!(Enter) => {
+                    if let Some(task) = view.tasks_table.selected_item() {
+                        update_kind = UpdateKind::SelectTask(task.borrow().span_id());
+                        let task_view =
+                            TaskInstance(self::task::TaskView::new(task, state.task_details_ref()));
+                        if let TaskGroupInstance(group) =
+                            std::mem::replace(&mut self.state, task_view)
+                        {
+                            self.expanded_group = Some(group);
+                        }
+                    }
+                }
+                _ => {
+                    // otherwise pass on to view
+                    view.update_input(event);
+                }
+            },
+            SelectStatsList => self.select_stats_list.update_input(event),
+            TimerLagList => self.timer_lag_list.update_input(event),
+            WorkersList => self.workers_list.update_input(event),
+            SpansList => self.spans_list.update_input(event),
+            WakeGraphList => self.wake_graph_list.update_input(event),
+            WakerLeaksList => self.waker_leaks_list.update_input(event),
+            TargetsList => self.targets_list.update_input(event),
             ResourceInstance(ref mut view) => {
                 // The escape key changes views, so handle here since we can
                 // mutate the currently selected view.
                 match event {
                     key!(Esc) => {
-                        self.state = ResourcesList;
+                        self.state = match self.expanded_warnings.take() {
+                            Some(warnings) => WarningsList(warnings),
+                            None => ResourcesList,
+                        };
                         update_kind = UpdateKind::Other;
                     }
                     key!(Enter) => {
@@ -199,7 +624,14 @@ impl View {
                 // mutate the currently selected view.
                 match event {
                     key!(Esc) => {
-                        self.state = TasksList;
+                        let expanded_completed = std::mem::take(&mut self.expanded_completed);
+                        self.state =
+                            match (self.expanded_group.take(), self.expanded_warnings.take()) {
+                                (Some(group), _) => TaskGroupInstance(group),
+                                (None, Some(warnings)) => WarningsList(warnings),
+                                (None, None) if expanded_completed => CompletedTasksList,
+                                (None, None) => TasksList,
+                            };
                         update_kind = UpdateKind::ExitTaskView;
                     }
                     _ => {
@@ -208,10 +640,137 @@ impl View {
                     }
                 }
             }
+            Query(ref mut view) => match event {
+                key!(Esc) => {
+                    self.state = TasksList;
+                }
+                _ => view.update_input(event),
+            },
+            WarningsList(ref mut view) => match event {
+                key!(Esc) => {
+                    self.state = TasksList;
+                }
+                _ => {
+                    if let Some(selection) = view.update_input(event, state, self.styles.lang()) {
+                        update_kind = self.open_warning(selection, state);
+                    }
+                }
+            },
+            Plugin(idx) => match event {
+                key!(Esc) => {
+                    self.state = TasksList;
+                }
+                _ => self.plugins[idx].update_input(event),
+            },
         }
         update_kind
     }
 
+    /// Returns `true` if a command is being typed at the `:` prompt, or a
+    /// search after `/`, in which case all key presses are part of the
+    /// command or search.
+    pub(crate) fn is_prompting(&self) -> bool {
+        self.prompt.is_some() || self.search.as_ref().is_some_and(Search::is_editing)
+    }
+
+    fn update_search(&mut self, event: input::Event, state: &State) -> UpdateKind {
+        let Some(search) = self.search.as_mut() else {
+            return UpdateKind::Other;
+        };
+        match event {
+            key!(Esc) => self.search = None,
+            key!(Backspace) => {
+                if search.pop().is_none() {
+                    self.search = None;
+                } else {
+                    self.select_search_match(Seek::Here, state);
+                }
+            }
+            input::Event::Key(input::KeyEvent {
+                code: input::KeyCode::Char(c),
+                ..
+            }) => {
+                search.push(c);
+                self.select_search_match(Seek::Here, state);
+            }
+            key!(Enter) if search.text().is_empty() => self.search = None,
+            key!(Enter) => search.enter(),
+            _ => {}
+        }
+        UpdateKind::Other
+    }
+
+    /// Selects a task matching the search of the tasks list, if there is one.
+    fn select_search_match(&mut self, seek: Seek, state: &State) {
+        if let (Some(search), Some(now)) = (&self.search, state.last_updated_at()) {
+            self.tasks_list
+                .select_matching(seek, |task| search.matches(task, now));
+        }
+    }
+
+    fn update_prompt(&mut self, event: input::Event, state: &State) -> UpdateKind {
+        let Some(prompt) = self.prompt.as_mut() else {
+            return UpdateKind::Other;
+        };
+        match event {
+            key!(Esc) => self.prompt = None,
+            key!(Backspace) => {
+                prompt.error = None;
+                if prompt.text.pop().is_none() {
+                    self.prompt = None;
+                }
+            }
+            input::Event::Key(input::KeyEvent {
+                code: input::KeyCode::Char(c),
+                ..
+            }) => {
+                prompt.error = None;
+                prompt.text.push(c);
+            }
+            key!(Enter) => {
+                let text = prompt.text.trim();
+                let (command, args) = text.split_once(' ').unwrap_or((text, ""));
+                match command {
+                    "query" | "q" => match Query::parse(args) {
+                        Ok(query) => {
+                            let view = self::query::QueryView::new(args.trim().to_owned(), query);
+                            let prev = std::mem::replace(&mut self.state, ViewState::Query(view));
+                            self.prompt = None;
+                            self.expanded_group = None;
+                            self.expanded_warnings = None;
+                            self.expanded_completed = false;
+                            if let ViewState::TaskInstance(_) = prev {
+                                return UpdateKind::ExitTaskView;
+                            }
+                        }
+                        Err(error) => prompt.error = Some(error.to_string()),
+                    },
+                    "export" => {
+                        let result = match self.state {
+                            ViewState::TaskInstance(ref view) => {
+                                view.export_poll_times(args.trim(), state, &self.styles)
+                            }
+                            _ => Err(self.styles.text(Msg::ExportNeedsTask).to_owned()),
+                        };
+                        match result {
+                            Ok(()) => self.prompt = None,
+                            Err(error) => prompt.error = Some(error),
+                        }
+                    }
+                    "" => self.prompt = None,
+                    command => {
+                        prompt.error = Some(
+                            self.styles
+                                .fill(Msg::UnknownCommand, &[("command", &command)]),
+                        )
+                    }
+                }
+            }
+            _ => {}
+        }
+        UpdateKind::Other
+    }
+
     /// The help modal should toggle on the `?` key and should exit on `Esc`
     fn should_toggle_help_modal(&mut self, event: &crossterm::event::Event) -> bool {
         input::is_help_toggle(event) || (self.show_help_modal && input::is_esc(event))
@@ -223,29 +782,138 @@ impl View {
         area: layout::Rect,
         state: &mut State,
     ) {
+        // The search is only shown with the tasks list it searches, and the
+        // prompt is shown in its place while a command is typed.
+        let search = match self.state {
+            ViewState::TasksList if self.prompt.is_none() => self.search.as_ref(),
+            _ => None,
+        };
+        let (area, line_area) = if self.prompt.is_some() || search.is_some() {
+            let chunks = layout::Layout::default()
+                .direction(layout::Direction::Vertical)
+                .constraints([layout::Constraint::Min(0), layout::Constraint::Length(1)])
+                .split(area);
+            (chunks[0], chunks[1])
+        } else {
+            (area, layout::Rect::default())
+        };
+        if let Some(ref prompt) = self.prompt {
+            let line = match prompt.error {
+                Some(ref error) => Line::from(vec![
+                    Span::raw(format!(":{} ", prompt.text)),
+                    Span::styled(error.clone(), self.styles.fg(Color::Red)),
+                ]),
+                None => Line::from(format!(":{}", prompt.text)),
+            };
+            frame.render_widget(Paragraph::new(line), line_area);
+        }
+
         let help_text: &dyn HelpText = match self.state {
             ViewState::TasksList => {
-                self.tasks_list.render(&self.styles, frame, area, state, ());
+                let filter = self
+                    .active_preset
+                    .and_then(|idx| self.presets[idx].filter.clone());
+                self.tasks_list.render(
+                    &self.styles,
+                    frame,
+                    area,
+                    state,
+                    TasksContext {
+                        group: None,
+                        filter,
+                    },
+                );
                 &self.tasks_list
             }
+            ViewState::CompletedTasksList => {
+                self.completed_tasks_list
+                    .render(&self.styles, frame, area, state, ());
+                &self.completed_tasks_list
+            }
             ViewState::ResourcesList => {
                 self.resources_list
                     .render(&self.styles, frame, area, state, ());
                 &self.resources_list
             }
+            ViewState::TaskGroupsList => {
+                self.task_groups_list
+                    .render(&self.styles, frame, area, state, ());
+                &self.task_groups_list
+            }
+            ViewState::TaskGroupInstance(ref mut view) => {
+                view.render(&self.styles, frame, area, state);
+                view
+            }
+            ViewState::SelectStatsList => {
+                self.select_stats_list
+                    .render(&self.styles, frame, area, state, ());
+                &self.select_stats_list
+            }
+            ViewState::TimerLagList => {
+                self.timer_lag_list
+                    .render(&self.styles, frame, area, state, ());
+                &self.timer_lag_list
+            }
+            ViewState::WorkersList => {
+                self.workers_list
+                    .render(&self.styles, frame, area, state, ());
+                &self.workers_list
+            }
+            ViewState::SpansList => {
+                self.spans_list.render(&self.styles, frame, area, state, ());
+                &self.spans_list
+            }
+            ViewState::WakeGraphList => {
+                self.wake_graph_list
+                    .render(&self.styles, frame, area, state, ());
+                &self.wake_graph_list
+            }
+            ViewState::WakerLeaksList => {
+                self.waker_leaks_list
+                    .render(&self.styles, frame, area, state, ());
+                &self.waker_leaks_list
+            }
+            ViewState::TargetsList => {
+                self.targets_list
+                    .render(&self.styles, frame, area, state, ());
+                &self.targets_list
+            }
             ViewState::TaskInstance(ref mut view) => {
                 let now = state
                     .last_updated_at()
                     .expect("task view implies we've received an update");
-                view.render(&self.styles, frame, area, now);
+                view.render(&self.styles, frame, area, state, now);
                 view
             }
             ViewState::ResourceInstance(ref mut view) => {
                 view.render(&self.styles, frame, area, state);
                 view
             }
+            ViewState::Query(ref mut view) => {
+                view.render(&self.styles, frame, area, state);
+                view
+            }
+            ViewState::WarningsList(ref mut view) => {
+                view.render(&self.styles, frame, area, state);
+                view
+            }
+            ViewState::Plugin(idx) => {
+                let plugin = &mut self.plugins[idx];
+                plugin.render(&self.styles, frame, area, state);
+                plugin
+            }
         };
 
+        if let (Some(search), Some(now)) = (search, state.last_updated_at()) {
+            // Count the matches after rendering the tasks list, which sorts
+            // it and adds any new tasks.
+            let (matches, current) = self
+                .tasks_list
+                .count_matching(|task| search.matches(task, now));
+            let line = search.render(&self.styles, matches, current);
+            frame.render_widget(Paragraph::new(line), line_area);
+        }
+
         state.retain_active();
 
         if self.show_help_modal {
@@ -257,6 +925,108 @@ impl View {
     pub(crate) fn current_view(&self) -> &ViewState {
         &self.state
     }
+
+    /// Records the current view and how each table is sorted in `session`.
+    pub(crate) fn save_session(&self, session: &mut Session) {
+        session.view = match self.state {
+            // custom views may not exist when the session is restored.
+            ViewState::TasksList | ViewState::TaskInstance(_) | ViewState::Plugin(_) => {
+                SessionView::Tasks
+            }
+            ViewState::CompletedTasksList => SessionView::CompletedTasks,
+            ViewState::ResourcesList | ViewState::ResourceInstance(_) => SessionView::Resources,
+            ViewState::TaskGroupsList | ViewState::TaskGroupInstance(_) => SessionView::TaskGroups,
+            ViewState::SelectStatsList => SessionView::SelectStats,
+            ViewState::TimerLagList => SessionView::TimerLag,
+            ViewState::WorkersList => SessionView::Workers,
+            ViewState::SpansList => SessionView::Spans,
+            ViewState::WakeGraphList => SessionView::WakeGraph,
+            ViewState::WakerLeaksList => SessionView::WakerLeaks,
+            ViewState::TargetsList => SessionView::Targets,
+            ViewState::WarningsList(_) => SessionView::Warnings,
+            ViewState::Query(_) => SessionView::Query,
+        };
+        session.query = match self.state {
+            ViewState::Query(ref view) => Some(view.text().to_owned()),
+            _ => None,
+        };
+        session.tasks = Some(self.tasks_list.save_session());
+        session.completed_tasks = Some(self.completed_tasks_list.save_session());
+        session.resources = Some(self.resources_list.save_session());
+        session.task_groups = Some(self.task_groups_list.save_session());
+        session.select_stats = Some(self.select_stats_list.save_session());
+        session.timer_lag = Some(self.timer_lag_list.save_session());
+        session.workers = Some(self.workers_list.save_session());
+        session.spans = Some(self.spans_list.save_session());
+        session.wake_graph = Some(self.wake_graph_list.save_session());
+        session.waker_leaks = Some(self.waker_leaks_list.save_session());
+        session.targets = Some(self.targets_list.save_session());
+    }
+
+    /// Restores the view and table sorting saved by a previous session.
+    pub(crate) fn restore_session(&mut self, session: &Session) {
+        if let Some(ref tasks) = session.tasks {
+            self.tasks_list.restore_session(tasks);
+        }
+        if let Some(ref completed_tasks) = session.completed_tasks {
+            self.completed_tasks_list.restore_session(completed_tasks);
+        }
+        if let Some(ref resources) = session.resources {
+            self.resources_list.restore_session(resources);
+        }
+        if let Some(ref task_groups) = session.task_groups {
+            self.task_groups_list.restore_session(task_groups);
+        }
+        if let Some(ref select_stats) = session.select_stats {
+            self.select_stats_list.restore_session(select_stats);
+        }
+        if let Some(ref timer_lag) = session.timer_lag {
+            self.timer_lag_list.restore_session(timer_lag);
+        }
+        if let Some(ref workers) = session.workers {
+            self.workers_list.restore_session(workers);
+        }
+        if let Some(ref spans) = session.spans {
+            self.spans_list.restore_session(spans);
+        }
+        if let Some(ref wake_graph) = session.wake_graph {
+            self.wake_graph_list.restore_session(wake_graph);
+        }
+        if let Some(ref waker_leaks) = session.waker_leaks {
+            self.waker_leaks_list.restore_session(waker_leaks);
+        }
+        if let Some(ref targets) = session.targets {
+            self.targets_list.restore_session(targets);
+        }
+
+        self.state = match session.view {
+            SessionView::Tasks => ViewState::TasksList,
+            SessionView::CompletedTasks => ViewState::CompletedTasksList,
+            SessionView::Resources => ViewState::ResourcesList,
+            SessionView::TaskGroups => ViewState::TaskGroupsList,
+            SessionView::SelectStats => ViewState::SelectStatsList,
+            SessionView::TimerLag => ViewState::TimerLagList,
+            SessionView::Workers => ViewState::WorkersList,
+            SessionView::Spans => ViewState::SpansList,
+            SessionView::WakeGraph => ViewState::WakeGraphList,
+            SessionView::WakerLeaks => ViewState::WakerLeaksList,
+            SessionView::Targets => ViewState::TargetsList,
+            SessionView::Warnings => ViewState::WarningsList(Default::default()),
+            SessionView::Query => {
+                // the query syntax may have changed since it was saved.
+                let query = session
+                    .query
+                    .as_deref()
+                    .and_then(|text| Some((text, Query::parse(text).ok()?)));
+                match query {
+                    Some((text, query)) => {
+                        ViewState::Query(self::query::QueryView::new(text.to_owned(), query))
+                    }
+                    None => ViewState::TasksList,
+                }
+            }
+        };
+    }
 }
 
 pub(crate) fn bold<'a>(text: impl Into<Cow<'a, str>>) -> Span<'a> {
@@ -6,6 +6,7 @@ use ratatui::{
 };
 
 use crate::{
+    i18n::Msg,
     state::histogram::DurationHistogram,
     view::{self, bold},
 };
@@ -35,7 +36,7 @@ impl<'a> Percentiles<'a> {
         Self {
             styles,
             histogram: None,
-            title: "Percentiles",
+            title: styles.text(Msg::Percentiles),
         }
     }
 
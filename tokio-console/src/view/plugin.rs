@@ -0,0 +1,63 @@
+//! Custom views added to the console without changing its own views.
+//!
+//! A fork or extension of the console can show its own views, such as
+//! metrics specific to one application, by implementing [`ViewPlugin`] and
+//! adding it to the list returned by [`registered`]. Each plugin is opened by
+//! pressing its key, and while it's shown it's given the keys the console
+//! doesn't handle itself.
+use crate::{
+    input,
+    state::State,
+    view::{
+        self,
+        controls::{controls_paragraph, ControlDisplay},
+        help::HelpText,
+    },
+};
+use ratatui::{layout, widgets::Paragraph};
+
+/// A custom view shown by the console.
+pub(crate) trait ViewPlugin {
+    /// The name of the view, used in logs.
+    fn name(&self) -> &str;
+
+    /// The key which opens the view.
+    ///
+    /// Keys bound by the console's own views take precedence, so a plugin
+    /// must use a key they don't, such as a digit not bound to a preset.
+    fn key(&self) -> char;
+
+    /// Called whenever the console receives new data from the remote, whether
+    /// or not the view is shown.
+    fn update(&mut self, _state: &State) {}
+
+    /// Handles a key the console doesn't handle itself while the view is
+    /// shown.
+    fn update_input(&mut self, _event: input::Event) {}
+
+    /// The keys the view handles, listed in its controls and help.
+    fn controls(&self) -> &'static [ControlDisplay] {
+        &[]
+    }
+
+    fn render(
+        &mut self,
+        styles: &view::Styles,
+        frame: &mut ratatui::terminal::Frame,
+        area: layout::Rect,
+        state: &State,
+    );
+}
+
+/// Returns the plugins the console is built with.
+///
+/// The console has no plugins of its own; forks add theirs here.
+pub(crate) fn registered() -> Vec<Box<dyn ViewPlugin>> {
+    Vec::new()
+}
+
+impl HelpText for Box<dyn ViewPlugin> {
+    fn render_help_content(&self, styles: &view::Styles) -> Paragraph<'static> {
+        controls_paragraph(self.controls(), styles)
+    }
+}
@@ -0,0 +1,188 @@
+use crate::{
+    i18n::Msg,
+    input,
+    query::{Query, Value},
+    state::State,
+    view::{
+        self, bold,
+        controls::{controls_paragraph, ControlDisplay, Controls, KeyDisplay},
+        help::HelpText,
+        DUR_LEN, DUR_TABLE_PRECISION,
+    },
+};
+use ratatui::{
+    layout,
+    style::{self, Color, Style},
+    text::Span,
+    widgets::{Cell, Paragraph, Row, Table, TableState},
+};
+
+/// The results of a query, which are updated as the console receives new
+/// data.
+pub(crate) struct QueryView {
+    text: String,
+    query: Query,
+    table_state: TableState,
+}
+
+impl QueryView {
+    pub(super) fn new(text: String, query: Query) -> Self {
+        QueryView {
+            text,
+            query,
+            table_state: TableState::default(),
+        }
+    }
+
+    pub(crate) fn text(&self) -> &str {
+        &self.text
+    }
+
+    pub(crate) fn update_input(&mut self, event: input::Event) {
+        use input::KeyCode::*;
+
+        if let input::Event::Key(event) = event {
+            let selected = self.table_state.selected();
+            match event.code {
+                Down | Char('j') => {
+                    self.table_state
+                        .select(Some(selected.map(|i| i + 1).unwrap_or(0)));
+                }
+                Up | Char('k') => {
+                    self.table_state
+                        .select(Some(selected.unwrap_or(0).saturating_sub(1)));
+                }
+                _ => {}
+            }
+        }
+    }
+
+    pub(crate) fn render(
+        &mut self,
+        styles: &view::Styles,
+        frame: &mut ratatui::terminal::Frame,
+        area: layout::Rect,
+        state: &mut State,
+    ) {
+        let controls = Controls::new(view_controls(), &area, styles);
+        let chunks = layout::Layout::default()
+            .direction(layout::Direction::Vertical)
+            .margin(0)
+            .constraints(
+                [
+                    layout::Constraint::Length(controls.height()),
+                    layout::Constraint::Min(0),
+                ]
+                .as_ref(),
+            )
+            .split(area);
+        let (controls_area, table_area) = (chunks[0], chunks[1]);
+        frame.render_widget(controls.into_widget(), controls_area);
+
+        let title = |len: Option<usize>| {
+            let title = match len {
+                Some(len) => styles.fill(Msg::QueryMatchesTitle, &[("count", &len)]),
+                None => styles.text(Msg::QueryTitle).to_owned(),
+            };
+            vec![bold(title), Span::from(self.text.clone())]
+        };
+
+        let Some(now) = state.last_updated_at() else {
+            let waiting = Paragraph::new(styles.text(Msg::WaitingForUpdate))
+                .block(styles.border_block().title(title(None)));
+            frame.render_widget(waiting, table_area);
+            return;
+        };
+
+        let results = self.query.eval(state.tasks_state(), now);
+        if let Some(selected) = self.table_state.selected() {
+            if selected >= results.rows.len() {
+                self.table_state.select(results.rows.len().checked_sub(1));
+            }
+        }
+
+        let mut widths = results
+            .columns
+            .iter()
+            .map(|column| view::Width::new(column.len() as u16 + 1))
+            .collect::<Vec<_>>();
+        let rows = results
+            .rows
+            .iter()
+            .map(|row| {
+                Row::new(
+                    row.iter()
+                        .zip(widths.iter_mut())
+                        .map(|(value, width)| match value {
+                            Value::Duration(dur) => {
+                                width.update_len(DUR_LEN);
+                                Cell::from(styles.time_units(
+                                    *dur,
+                                    DUR_TABLE_PRECISION,
+                                    Some(DUR_LEN),
+                                ))
+                            }
+                            value => Cell::from(width.update_str(value.to_string())),
+                        }),
+                )
+            })
+            .collect::<Vec<_>>();
+
+        let header_style = if styles.color(Color::Cyan).is_some() {
+            Style::default()
+        } else {
+            Style::default().add_modifier(style::Modifier::REVERSED)
+        };
+        let header = Row::new(
+            results
+                .columns
+                .iter()
+                .map(|column| Cell::from(column.clone())),
+        )
+        .height(1)
+        .style(header_style.add_modifier(style::Modifier::BOLD));
+
+        let widths = widths
+            .iter()
+            .map(view::Width::constraint)
+            .collect::<Vec<_>>();
+        let table = Table::new(rows, widths)
+            .header(header)
+            .block(styles.border_block().title(title(Some(results.rows.len()))))
+            .highlight_symbol(view::TABLE_HIGHLIGHT_SYMBOL)
+            .highlight_style(Style::default().add_modifier(style::Modifier::BOLD));
+
+        frame.render_stateful_widget(table, table_area, &mut self.table_state);
+    }
+}
+
+impl HelpText for QueryView {
+    fn render_help_content(&self, styles: &view::Styles) -> Paragraph<'static> {
+        controls_paragraph(view_controls(), styles)
+    }
+}
+
+const fn view_controls() -> &'static [ControlDisplay] {
+    &[
+        ControlDisplay {
+            action: Msg::Scroll,
+            keys: &[
+                KeyDisplay {
+                    base: "up, down",
+                    utf8: Some("\u{2191}\u{2193}"),
+                },
+                KeyDisplay {
+                    base: "k, j",
+                    utf8: None,
+                },
+            ],
+        },
+        ControlDisplay {
+            action: Msg::ExitQuery,
+            keys: &[KeyDisplay {
+                base: "esc",
+                utf8: Some("\u{238B} esc"),
+            }],
+        },
+    ]
+}
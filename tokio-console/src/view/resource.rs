@@ -1,12 +1,14 @@
 use crate::{
+    i18n::Msg,
     input,
-    state::resources::Resource,
+    state::resources::{IoDirectionStats, Resource},
     state::State,
     view::{
         self,
         async_ops::{self, AsyncOpsTable, AsyncOpsTableCtx},
         bold,
         controls::{controls_paragraph, ControlDisplay, Controls, KeyDisplay},
+        durations::Durations,
         help::HelpText,
         TableListState,
     },
@@ -15,7 +17,7 @@ use once_cell::sync::OnceCell;
 use ratatui::{
     layout::{self, Layout},
     text::{Line, Span, Text},
-    widgets::Paragraph,
+    widgets::{List, ListItem, Paragraph},
 };
 use std::{cell::RefCell, rc::Rc};
 
@@ -48,22 +50,52 @@ impl ResourceView {
         let resource = &*self.resource.borrow();
         let controls = Controls::new(view_controls(), &area, styles);
 
-        let (controls_area, stats_area, async_ops_area) = {
+        let recv_latency_histogram = resource.recv_latency_histogram();
+        let io_stats = resource.io_stats();
+
+        let warnings: Vec<_> = resource
+            .warnings()
+            .iter()
+            .map(|linter| {
+                ListItem::new(Text::from(Line::from(vec![
+                    styles.warning_wide(),
+                    Span::from(linter.format(resource, styles.lang())),
+                ])))
+            })
+            .collect();
+
+        let (controls_area, warnings_area, stats_area, io_area, recv_latency_area, async_ops_area) = {
             let chunks = Layout::default()
                 .direction(layout::Direction::Vertical)
                 .constraints(
                     [
                         // controls
                         layout::Constraint::Length(controls.height()),
+                        // warnings (add 2 for top and bottom borders)
+                        layout::Constraint::Length(if warnings.is_empty() {
+                            0
+                        } else {
+                            warnings.len() as u16 + 2
+                        }),
                         // resource stats
                         layout::Constraint::Length(8),
+                        // reads and writes, only reported for I/O resources
+                        layout::Constraint::Length(if io_stats.is_some() { 5 } else { 0 }),
+                        // receive latencies, only reported for channels
+                        layout::Constraint::Length(if recv_latency_histogram.is_some() {
+                            9
+                        } else {
+                            0
+                        }),
                         // async ops
                         layout::Constraint::Percentage(60),
                     ]
                     .as_ref(),
                 )
                 .split(area);
-            (chunks[0], chunks[1], chunks[2])
+            (
+                chunks[0], chunks[1], chunks[2], chunks[3], chunks[4], chunks[5],
+            )
         };
 
         let stats_area = Layout::default()
@@ -78,17 +110,32 @@ impl ResourceView {
             .split(stats_area);
 
         let overview = vec![
-            Line::from(vec![bold("ID: "), Span::raw(resource.id_str())]),
-            Line::from(vec![bold("Parent ID: "), Span::raw(resource.parent())]),
-            Line::from(vec![bold("Kind: "), Span::raw(resource.kind())]),
-            Line::from(vec![bold("Target: "), Span::raw(resource.target())]),
             Line::from(vec![
-                bold("Type: "),
+                bold(styles.text(Msg::Id)),
+                Span::raw(resource.id_str()),
+            ]),
+            Line::from(vec![
+                bold(styles.text(Msg::ParentId)),
+                Span::raw(resource.parent()),
+            ]),
+            Line::from(vec![
+                bold(styles.text(Msg::Kind)),
+                Span::raw(resource.kind()),
+            ]),
+            Line::from(vec![
+                bold(styles.text(Msg::Target)),
+                Span::raw(resource.target()),
+            ]),
+            Line::from(vec![
+                bold(styles.text(Msg::Type)),
                 Span::raw(resource.concrete_type()),
                 Span::raw(" "),
                 resource.type_visibility().render(styles),
             ]),
-            Line::from(vec![bold("Location: "), Span::raw(resource.location())]),
+            Line::from(vec![
+                bold(styles.text(Msg::Location)),
+                Span::raw(resource.location()),
+            ]),
         ];
 
         let mut fields = Text::default();
@@ -101,12 +148,72 @@ impl ResourceView {
         );
 
         let resource_widget =
-            Paragraph::new(overview).block(styles.border_block().title("Resource"));
-        let fields_widget = Paragraph::new(fields).block(styles.border_block().title("Attributes"));
+            Paragraph::new(overview).block(styles.border_block().title(styles.text(Msg::Resource)));
+        let fields_widget =
+            Paragraph::new(fields).block(styles.border_block().title(styles.text(Msg::Attributes)));
 
         frame.render_widget(controls.into_widget(), controls_area);
+        if !warnings.is_empty() {
+            let warnings =
+                List::new(warnings).block(styles.border_block().title(styles.text(Msg::Warnings)));
+            frame.render_widget(warnings, warnings_area);
+        }
         frame.render_widget(resource_widget, stats_area[0]);
         frame.render_widget(fields_widget, stats_area[1]);
+        if let Some(io_stats) = io_stats {
+            let now = state
+                .last_updated_at()
+                .expect("resource view implies we've received an update");
+            let direction = |name: Msg, stats: &IoDirectionStats| {
+                let mut spans = vec![bold(styles.text(name))];
+                spans.extend(styles.fill_spans(
+                    Msg::IoStats,
+                    &[
+                        ("bytes", Span::from(stats.bytes.to_string())),
+                        ("ops", Span::from(stats.ops.to_string())),
+                        ("polls", Span::from(stats.polls.to_string())),
+                        (
+                            "waited",
+                            styles.time_units(stats.total_wait, view::DUR_LIST_PRECISION, None),
+                        ),
+                        (
+                            "longest",
+                            styles.time_units(
+                                stats.longest_wait(now),
+                                view::DUR_LIST_PRECISION,
+                                None,
+                            ),
+                        ),
+                    ],
+                ));
+                Line::from(spans)
+            };
+            let mut lines = vec![
+                direction(Msg::IoRead, &io_stats.read),
+                direction(Msg::IoWrite, &io_stats.write),
+            ];
+            if let Some(wait) = resource.write_backpressure(now) {
+                let mut spans = vec![styles.warning_wide()];
+                spans.extend(styles.fill_spans(
+                    Msg::WriteBackpressure,
+                    &[(
+                        "waited",
+                        styles.time_units(wait, view::DUR_LIST_PRECISION, None),
+                    )],
+                ));
+                lines.push(Line::from(spans));
+            }
+            let io_widget =
+                Paragraph::new(lines).block(styles.border_block().title(styles.text(Msg::Io)));
+            frame.render_widget(io_widget, io_area);
+        }
+        if recv_latency_histogram.is_some() {
+            let recv_latency_widget = Durations::new(styles)
+                .histogram(recv_latency_histogram)
+                .percentiles_title(styles.text(Msg::RecvLatencyPercentiles))
+                .histogram_title(styles.text(Msg::RecvLatencyHistogram));
+            frame.render_widget(recv_latency_widget, recv_latency_area);
+        }
         let ctx = AsyncOpsTableCtx {
             initial_render: self.initial_render,
             resource_id: resource.id(),
@@ -128,7 +235,7 @@ fn view_controls() -> &'static [ControlDisplay] {
 
     VIEW_CONTROLS.get_or_init(|| {
         let resource_controls = &[ControlDisplay {
-            action: "return to task list",
+            action: Msg::ReturnToTaskList,
             keys: &[KeyDisplay {
                 base: "esc",
                 utf8: Some("\u{238B} esc"),
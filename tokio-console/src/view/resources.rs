@@ -1,4 +1,5 @@
 use crate::{
+    i18n::Msg,
     state::{
         resources::{Resource, SortBy},
         State,
@@ -14,8 +15,8 @@ use crate::{
 use ratatui::{
     layout,
     style::{self, Color, Style},
-    text::Line,
-    widgets::{Cell, Row, Table},
+    text::{Line, Span, Text},
+    widgets::{Cell, List, ListItem, Row, Table},
 };
 
 #[derive(Debug, Default)]
@@ -81,6 +82,7 @@ impl TableList<9> for ResourcesTable {
         let mut target_width = view::Width::new(Self::WIDTHS[4] as u16);
         let mut type_width = view::Width::new(Self::WIDTHS[5] as u16);
         let mut location_width = view::Width::new(Self::WIDTHS[7] as u16);
+        let mut num_backpressured = 0;
 
         let rows = {
             let id_width = &mut id_width;
@@ -89,6 +91,7 @@ impl TableList<9> for ResourcesTable {
             let target_width = &mut target_width;
             let type_width = &mut type_width;
             let location_width = &mut location_width;
+            let num_backpressured = &mut num_backpressured;
 
             table_list_state
                 .sorted_items
@@ -97,6 +100,16 @@ impl TableList<9> for ResourcesTable {
                     let resource = resource.upgrade()?;
                     let resource = resource.borrow();
 
+                    let mut attributes = Vec::new();
+                    let backpressured = resource.write_backpressure(now).is_some();
+                    if backpressured {
+                        *num_backpressured += 1;
+                    }
+                    if backpressured || !resource.warnings().is_empty() {
+                        attributes.push(styles.warning_narrow());
+                    }
+                    attributes.extend(resource.formatted_attributes().iter().flatten().cloned());
+
                     let mut row = Row::new(vec![
                         Cell::from(id_width.update_str(format!(
                             "{:>width$}",
@@ -114,14 +127,7 @@ impl TableList<9> for ResourcesTable {
                         Cell::from(type_width.update_str(resource.concrete_type()).to_owned()),
                         Cell::from(resource.type_visibility().render(styles)),
                         Cell::from(location_width.update_str(resource.location()).to_owned()),
-                        Cell::from(
-                            resource
-                                .formatted_attributes()
-                                .iter()
-                                .flatten()
-                                .cloned()
-                                .collect::<Line>(),
-                        ),
+                        Cell::from(Line::from(attributes)),
                     ]);
 
                     if resource.dropped() {
@@ -159,10 +165,33 @@ impl TableList<9> for ResourcesTable {
             Table::default().rows(rows.rev())
         };
 
-        let block = styles.border_block().title(vec![bold(format!(
-            "Resources ({}) ",
-            table_list_state.len()
-        ))]);
+        let mut title = vec![bold(
+            styles.fill(Msg::ResourcesTitle, &[("count", &table_list_state.len())]),
+        )];
+        if num_backpressured > 0 {
+            title.push(styles.warning_narrow());
+            title.push(Span::from(
+                styles.fill(Msg::BackpressureCount, &[("count", &num_backpressured)]),
+            ));
+        }
+        let block = styles.border_block().title(title);
+
+        let warnings = state
+            .resources_state()
+            .unacknowledged_warnings()
+            .map(|(warning, count)| {
+                ListItem::new(Text::from(Line::from(vec![
+                    styles.warning_wide(),
+                    Span::from(format!("{} {}", count, warning.summary(styles.lang()))),
+                ])))
+            })
+            .collect::<Vec<_>>();
+        let warnings_height = if warnings.is_empty() {
+            0
+        } else {
+            // add 2 for top and bottom borders
+            warnings.len() as u16 + 2
+        };
 
         let controls = Controls::new(view_controls(), &area, styles);
 
@@ -174,13 +203,15 @@ impl TableList<9> for ResourcesTable {
             .constraints(
                 [
                     layout::Constraint::Length(controls.height()),
+                    layout::Constraint::Length(warnings_height),
                     layout::Constraint::Max(area.height),
                 ]
                 .as_ref(),
             )
             .split(area);
         let controls_area = chunks[0];
-        let tasks_area = chunks[1];
+        let warnings_area = chunks[1];
+        let tasks_area = chunks[2];
 
         let attributes_width = layout::Constraint::Percentage(100);
         let widths = &[
@@ -204,6 +235,12 @@ impl TableList<9> for ResourcesTable {
 
         frame.render_stateful_widget(table, tasks_area, &mut table_list_state.table_state);
         frame.render_widget(controls.into_widget(), controls_area);
+        if warnings_height > 0 {
+            let block = styles
+                .border_block()
+                .title(Line::from(vec![bold(styles.text(Msg::Warnings))]));
+            frame.render_widget(List::new(warnings).block(block), warnings_area);
+        }
 
         table_list_state
             .sorted_items
@@ -0,0 +1,110 @@
+use crate::{i18n::Msg, query::Filter, state::tasks::Task, view};
+use ratatui::{
+    style::Color,
+    text::{Line, Span},
+};
+use std::time::SystemTime;
+
+/// A search of the tasks list, typed after pressing `/`.
+///
+/// The search is run again on every key press, so the selection moves to the
+/// first matching task as the search is typed. Once the search is entered,
+/// `n` and `N` move between the matching tasks.
+///
+/// A search which is a filter expression, such as `polls >= 1000` or
+/// `name =~ "^conn"`, selects the tasks the filter does, as a `where` stage of
+/// a query would. Any other search selects the tasks with the text in their
+/// ID, name, location, or target.
+#[derive(Debug)]
+pub(crate) struct Search {
+    text: String,
+    /// The search parsed as a filter, if it is one.
+    filter: Option<Filter>,
+    /// Whether the search is still being typed, in which case all key presses
+    /// are part of the search.
+    editing: bool,
+}
+
+// === impl Search ===
+
+impl Search {
+    pub(super) fn new() -> Self {
+        Self {
+            text: String::new(),
+            filter: None,
+            editing: true,
+        }
+    }
+
+    pub(super) fn is_editing(&self) -> bool {
+        self.editing
+    }
+
+    pub(super) fn text(&self) -> &str {
+        &self.text
+    }
+
+    pub(super) fn push(&mut self, c: char) {
+        self.text.push(c);
+        self.filter = Filter::parse(&self.text).ok();
+    }
+
+    pub(super) fn pop(&mut self) -> Option<char> {
+        let c = self.text.pop();
+        self.filter = Filter::parse(&self.text).ok();
+        c
+    }
+
+    /// Stops typing the search, so that keys navigate between its matches.
+    pub(super) fn enter(&mut self) {
+        self.editing = false;
+    }
+
+    /// Returns `true` if the search's filter selects the task or, if the
+    /// search isn't a filter, if the task's ID, name, location, or target
+    /// contains the search text, ignoring case.
+    pub(super) fn matches(&self, task: &Task, now: SystemTime) -> bool {
+        if let Some(ref filter) = self.filter {
+            return filter.matches(task, now);
+        }
+        if self.text.is_empty() {
+            return false;
+        }
+        let text = self.text.to_lowercase();
+        [
+            task.id_str(),
+            task.name().unwrap_or_default(),
+            task.location(),
+            task.target(),
+        ]
+        .iter()
+        .any(|field| field.to_lowercase().contains(&text))
+    }
+
+    /// Renders the search as it's typed, followed by how many tasks match it
+    /// and which of them is selected, if any.
+    pub(super) fn render(
+        &self,
+        styles: &view::Styles,
+        matches: usize,
+        current: Option<usize>,
+    ) -> Line<'static> {
+        let mut spans = vec![Span::raw(format!("/{}  ", self.text))];
+        if self.text.is_empty() {
+            return Line::from(spans);
+        }
+        spans.push(match (matches, current) {
+            (0, _) => Span::styled(styles.text(Msg::NoSearchMatches), styles.fg(Color::Red)),
+            (count, Some(current)) => Span::from(styles.fill(
+                Msg::SearchMatch,
+                &[("current", &current), ("count", &count)],
+            )),
+            (count, None) => Span::from(styles.fill(Msg::SearchMatches, &[("count", &count)])),
+        });
+        if !self.editing && matches > 0 {
+            spans.push(Span::raw("  "));
+            spans.push(Span::from(styles.text(Msg::SearchNavigation)));
+        }
+        Line::from(spans)
+    }
+}
@@ -0,0 +1,239 @@
+use crate::{
+    i18n::Msg,
+    state::{
+        select_stats::{SelectCallsite, SortBy},
+        State,
+    },
+    view::{
+        self, bold,
+        controls::Controls,
+        table::{view_controls, TableList, TableListState},
+        DUR_LEN, DUR_TABLE_PRECISION,
+    },
+};
+use ratatui::{
+    layout,
+    style::{self, Color, Style},
+    text::{Line, Span},
+    widgets::{Cell, Paragraph, Row, Table},
+};
+
+#[derive(Debug, Default)]
+pub(crate) struct SelectStatsTable {}
+
+impl TableList<7> for SelectStatsTable {
+    type Row = SelectCallsite;
+    type Sort = SortBy;
+    type Context = ();
+
+    const HEADER: &'static [&'static str; 7] = &[
+        "Selects",
+        "Branches",
+        "Starved",
+        "Avg Wait",
+        "Max Wait",
+        "Location",
+        "Completions",
+    ];
+
+    const WIDTHS: &'static [usize; 7] = &[
+        Self::HEADER[0].len() + 1,
+        Self::HEADER[1].len() + 1,
+        Self::HEADER[2].len() + 1,
+        Self::HEADER[3].len() + 1,
+        Self::HEADER[4].len() + 1,
+        Self::HEADER[5].len() + 1,
+        Self::HEADER[6].len() + 1,
+    ];
+
+    fn render(
+        table_list_state: &mut TableListState<Self, 7>,
+        styles: &view::Styles,
+        frame: &mut ratatui::terminal::Frame,
+        area: layout::Rect,
+        state: &mut State,
+        _: Self::Context,
+    ) {
+        table_list_state.sorted_items.extend(
+            state
+                .tasks_state_mut()
+                .select_stats_mut()
+                .take_new_callsites(),
+        );
+        table_list_state
+            .sort_by
+            .sort(&mut table_list_state.sorted_items);
+
+        let dur_cell = |dur: std::time::Duration| -> Cell<'static> {
+            Cell::from(styles.time_units(dur, DUR_TABLE_PRECISION, Some(DUR_LEN)))
+        };
+
+        let mut selects_width = view::Width::new(Self::WIDTHS[0] as u16);
+        let mut location_width = view::Width::new(Self::WIDTHS[5] as u16);
+        let mut num_starved = 0;
+
+        let rows = {
+            let selects_width = &mut selects_width;
+            let location_width = &mut location_width;
+            let num_starved = &mut num_starved;
+
+            table_list_state
+                .sorted_items
+                .iter()
+                .filter_map(move |callsite| {
+                    let callsite = callsite.upgrade()?;
+                    let callsite = callsite.borrow();
+                    let completions = callsite.completions();
+                    let starved = callsite.starved();
+                    if starved > 0 {
+                        *num_starved += 1;
+                    }
+
+                    // Show how often each branch won, so that starved branches
+                    // stand out.
+                    let branches = callsite
+                        .branches()
+                        .iter()
+                        .enumerate()
+                        .map(|(idx, branch)| {
+                            let percent = if completions == 0 {
+                                0.0
+                            } else {
+                                branch.completions() as f64 / completions as f64 * 100.0
+                            };
+                            let text = styles.fill(
+                                Msg::BranchCompletions,
+                                &[
+                                    ("index", &idx),
+                                    ("count", &branch.completions()),
+                                    ("percent", &format!("{percent:.0}")),
+                                    (
+                                        "wait",
+                                        &styles.time_units(
+                                            branch.avg_wait(),
+                                            DUR_TABLE_PRECISION,
+                                            None,
+                                        ),
+                                    ),
+                                ],
+                            );
+                            if branch.completions() == 0 {
+                                Span::styled(text, styles.fg(Color::Red))
+                            } else {
+                                Span::from(text)
+                            }
+                        })
+                        .collect::<Vec<_>>();
+
+                    let starved = if starved > 0 {
+                        Cell::from(Line::from(vec![
+                            styles.warning_narrow(),
+                            Span::from(starved.to_string()),
+                        ]))
+                    } else {
+                        Cell::from("0")
+                    };
+
+                    Some(Row::new(vec![
+                        Cell::from(selects_width.update_str(completions.to_string())),
+                        Cell::from(callsite.branches().len().to_string()),
+                        starved,
+                        dur_cell(callsite.avg_wait()),
+                        dur_cell(callsite.max_wait()),
+                        Cell::from(location_width.update_str(callsite.location()).to_owned()),
+                        Cell::from(Line::from(branches)),
+                    ]))
+                })
+        };
+
+        let header_style = if styles.color(Color::Cyan).is_some() {
+            Style::default()
+        } else {
+            Style::default().add_modifier(style::Modifier::REVERSED)
+        };
+        let header_style = header_style.add_modifier(style::Modifier::BOLD);
+
+        let header = Row::new(Self::HEADER.iter().enumerate().map(|(idx, &value)| {
+            if idx == table_list_state.selected_column {
+                if table_list_state.sort_descending {
+                    Cell::from(styles.ascending(value))
+                } else {
+                    Cell::from(styles.descending(value))
+                }
+            } else {
+                Cell::from(value)
+            }
+        }))
+        .height(1)
+        .style(header_style);
+
+        let table = if table_list_state.sort_descending {
+            Table::default().rows(rows)
+        } else {
+            Table::default().rows(rows.rev())
+        };
+
+        let block = styles.border_block().title(vec![
+            bold(styles.fill(
+                Msg::SelectHotspotsTitle,
+                &[("count", &table_list_state.len())],
+            )),
+            styles.warning_narrow(),
+            Span::from(styles.fill(Msg::StarvedCount, &[("count", &num_starved)])),
+        ]);
+
+        let controls = Controls::new(view_controls(), &area, styles);
+        let chunks = layout::Layout::default()
+            .direction(layout::Direction::Vertical)
+            .margin(0)
+            .constraints(
+                [
+                    layout::Constraint::Length(controls.height()),
+                    layout::Constraint::Max(area.height),
+                ]
+                .as_ref(),
+            )
+            .split(area);
+        let (controls_area, table_area) = (chunks[0], chunks[1]);
+        frame.render_widget(controls.into_widget(), controls_area);
+
+        if table_list_state.sorted_items.is_empty() {
+            // Unlike tasks, `select!` statistics are opt-in, so explain how to
+            // get some rather than showing an empty table.
+            let help = Paragraph::new(vec![
+                Line::from(styles.text(Msg::NoSelects)),
+                Line::from(styles.fill_spans(
+                    Msg::SelectTimerHint,
+                    &[("timer", bold("console_subscriber::SelectTimer"))],
+                )),
+            ])
+            .block(
+                styles
+                    .border_block()
+                    .title(vec![bold(styles.text(Msg::SelectHotspots))]),
+            );
+            frame.render_widget(help, table_area);
+            return;
+        }
+
+        let widths = &[
+            selects_width.constraint(),
+            layout::Constraint::Length(Self::WIDTHS[1] as u16),
+            layout::Constraint::Length(Self::WIDTHS[2] as u16),
+            layout::Constraint::Length(Self::WIDTHS[3] as u16),
+            layout::Constraint::Length(Self::WIDTHS[4] as u16),
+            location_width.constraint(),
+            // Fill the rest of the row with the per-branch completions.
+            layout::Constraint::Percentage(100),
+        ];
+
+        let table = table
+            .header(header)
+            .block(block)
+            .widths(widths)
+            .highlight_symbol(view::TABLE_HIGHLIGHT_SYMBOL)
+            .highlight_style(Style::default().add_modifier(style::Modifier::BOLD));
+
+        frame.render_stateful_widget(table, table_area, &mut table_list_state.table_state);
+    }
+}
@@ -0,0 +1,224 @@
+use crate::{
+    i18n::Msg,
+    state::{
+        spans::{SortBy, WatchedSpan},
+        State,
+    },
+    view::{
+        self, bold,
+        controls::Controls,
+        table::{view_controls, TableList, TableListState},
+        DUR_LEN, DUR_TABLE_PRECISION,
+    },
+};
+use ratatui::{
+    layout,
+    style::{self, Color, Style},
+    text::{Line, Span},
+    widgets::{Cell, Paragraph, Row, Table},
+};
+
+#[derive(Debug, Default)]
+pub(crate) struct SpansTable {}
+
+impl TableList<8> for SpansTable {
+    type Row = WatchedSpan;
+    type Sort = SortBy;
+    type Context = ();
+
+    const HEADER: &'static [&'static str; 8] = &[
+        "ID", "Name", "Task", "Total", "Busy", "Enters", "Location", "Fields",
+    ];
+
+    const WIDTHS: &'static [usize; 8] = &[
+        Self::HEADER[0].len() + 1,
+        Self::HEADER[1].len() + 1,
+        Self::HEADER[2].len() + 1,
+        Self::HEADER[3].len() + 1,
+        Self::HEADER[4].len() + 1,
+        Self::HEADER[5].len() + 1,
+        Self::HEADER[6].len() + 1,
+        Self::HEADER[7].len() + 1,
+    ];
+
+    fn render(
+        table_list_state: &mut TableListState<Self, 8>,
+        styles: &view::Styles,
+        frame: &mut ratatui::terminal::Frame,
+        area: layout::Rect,
+        state: &mut State,
+        _: Self::Context,
+    ) {
+        let now = if let Some(now) = state.last_updated_at() {
+            now
+        } else {
+            // If we have never gotten an update yet, skip...
+            return;
+        };
+
+        table_list_state
+            .sorted_items
+            .extend(state.spans_state_mut().take_new_spans());
+        // Spans which were closed longer ago than the retention are removed
+        // from the state.
+        table_list_state
+            .sorted_items
+            .retain(|span| span.upgrade().is_some());
+        table_list_state
+            .sort_by
+            .sort(now, &mut table_list_state.sorted_items);
+
+        let tasks_state = state.tasks_state();
+        let dur_cell = |dur: std::time::Duration| -> Cell<'static> {
+            Cell::from(styles.time_units(dur, DUR_TABLE_PRECISION, Some(DUR_LEN)))
+        };
+
+        let mut id_width = view::Width::new(Self::WIDTHS[0] as u16);
+        let mut name_width = view::Width::new(Self::WIDTHS[1] as u16);
+        let mut task_width = view::Width::new(Self::WIDTHS[2] as u16);
+        let mut enters_width = view::Width::new(Self::WIDTHS[5] as u16);
+        let mut location_width = view::Width::new(Self::WIDTHS[6] as u16);
+        let mut num_entered = 0;
+
+        let rows = {
+            let id_width = &mut id_width;
+            let name_width = &mut name_width;
+            let task_width = &mut task_width;
+            let enters_width = &mut enters_width;
+            let location_width = &mut location_width;
+            let num_entered = &mut num_entered;
+
+            table_list_state
+                .sorted_items
+                .iter()
+                .filter_map(move |span| {
+                    let span = span.upgrade()?;
+                    let span = span.borrow();
+                    // The task may have been removed from the state already,
+                    // if it completed before the span was closed.
+                    let task = span
+                        .task_id()
+                        .and_then(|id| tasks_state.task(id))
+                        .and_then(|task| task.upgrade())
+                        .map(|task| task.borrow().short_desc().to_owned())
+                        .unwrap_or_else(|| span.task_id_str().to_owned());
+
+                    let mut row = Row::new(vec![
+                        Cell::from(id_width.update_str(format!(
+                            "{:>width$}",
+                            span.id(),
+                            width = id_width.chars() as usize
+                        ))),
+                        Cell::from(name_width.update_str(span.name()).to_owned()),
+                        Cell::from(task_width.update_str(task)),
+                        dur_cell(span.total(now)),
+                        dur_cell(span.busy(now)),
+                        Cell::from(enters_width.update_str(span.enters().to_string())),
+                        Cell::from(location_width.update_str(span.location()).to_owned()),
+                        Cell::from(Line::from(
+                            span.formatted_fields()
+                                .iter()
+                                .flatten()
+                                .cloned()
+                                .collect::<Vec<_>>(),
+                        )),
+                    ]);
+
+                    if span.dropped() {
+                        row = row.style(styles.terminated());
+                    } else if span.is_entered() {
+                        *num_entered += 1;
+                    }
+
+                    Some(row)
+                })
+        };
+
+        let header_style = if styles.color(Color::Cyan).is_some() {
+            Style::default()
+        } else {
+            Style::default().add_modifier(style::Modifier::REVERSED)
+        };
+        let header_style = header_style.add_modifier(style::Modifier::BOLD);
+
+        let header = Row::new(Self::HEADER.iter().enumerate().map(|(idx, &value)| {
+            if idx == table_list_state.selected_column {
+                if table_list_state.sort_descending {
+                    Cell::from(styles.ascending(value))
+                } else {
+                    Cell::from(styles.descending(value))
+                }
+            } else {
+                Cell::from(value)
+            }
+        }))
+        .height(1)
+        .style(header_style);
+
+        let table = if table_list_state.sort_descending {
+            Table::default().rows(rows)
+        } else {
+            Table::default().rows(rows.rev())
+        };
+
+        let block = styles.border_block().title(vec![
+            bold(styles.fill(Msg::UserSpansTitle, &[("count", &table_list_state.len())])),
+            Span::from(styles.fill(Msg::EnteredCount, &[("count", &num_entered)])),
+        ]);
+
+        let controls = Controls::new(view_controls(), &area, styles);
+        let chunks = layout::Layout::default()
+            .direction(layout::Direction::Vertical)
+            .margin(0)
+            .constraints(
+                [
+                    layout::Constraint::Length(controls.height()),
+                    layout::Constraint::Min(0),
+                ]
+                .as_ref(),
+            )
+            .split(area);
+        let (controls_area, table_area) = (chunks[0], chunks[1]);
+        frame.render_widget(controls.into_widget(), controls_area);
+
+        if table_list_state.sorted_items.is_empty() {
+            let help = Paragraph::new(vec![
+                Line::from(styles.text(Msg::NoUserSpans)),
+                Line::from(styles.fill_spans(
+                    Msg::WatchSpanHint,
+                    &[
+                        ("macro", bold("console_subscriber::watch_span!")),
+                        ("field", bold("console.watch")),
+                    ],
+                )),
+            ])
+            .block(
+                styles
+                    .border_block()
+                    .title(vec![bold(styles.text(Msg::UserSpans))]),
+            );
+            frame.render_widget(help, table_area);
+            return;
+        }
+
+        let widths = &[
+            id_width.constraint(),
+            name_width.constraint(),
+            task_width.constraint(),
+            layout::Constraint::Length(DUR_LEN as u16),
+            layout::Constraint::Length(DUR_LEN as u16),
+            enters_width.constraint(),
+            location_width.constraint(),
+            layout::Constraint::Percentage(100),
+        ];
+
+        let table = table
+            .header(header)
+            .block(block)
+            .widths(widths)
+            .highlight_symbol(view::TABLE_HIGHLIGHT_SYMBOL)
+            .highlight_style(Style::default().add_modifier(style::Modifier::BOLD));
+
+        frame.render_stateful_widget(table, table_area, &mut table_list_state.table_state);
+    }
+}
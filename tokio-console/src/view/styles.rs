@@ -1,16 +1,30 @@
-use crate::config;
+use crate::{
+    config::{self, TimeFormat},
+    i18n::{self, Lang, Msg},
+    state::SourceLocation,
+};
 use ratatui::{
     style::{Color, Modifier, Style},
     text::Span,
 };
 use serde::{Deserialize, Serialize};
-use std::{str::FromStr, time::Duration};
+use std::{
+    fmt,
+    str::FromStr,
+    time::{Duration, SystemTime},
+};
 
 #[derive(Debug, Clone)]
 pub struct Styles {
     palette: Palette,
     toggles: config::ColorToggles,
     pub(crate) utf8: bool,
+    /// Whether to avoid rendering things which change on every update.
+    pub(crate) low_bandwidth: bool,
+    time_format: TimeFormat,
+    lang: Lang,
+    /// The template for the URLs spawn locations link to, if they are links.
+    location_links: Option<String>,
 }
 
 #[derive(Debug, PartialEq, Eq, Copy, Clone, Deserialize, Serialize)]
@@ -74,6 +88,10 @@ impl Styles {
             palette: config.determine_palette(),
             toggles: config.toggles(),
             utf8: config.is_utf8(),
+            low_bandwidth: config.is_low_bandwidth(),
+            time_format: config.time_format(),
+            lang: config.lang(),
+            location_links: config.location_links(),
         }
     }
 
@@ -252,6 +270,128 @@ impl Styles {
         }
     }
 
+    /// Returns the text of `msg` in the configured language.
+    pub(crate) fn text(&self, msg: Msg) -> &'static str {
+        self.lang.text(msg)
+    }
+
+    /// Returns the text of `msg` in the configured language, with its
+    /// placeholders replaced by the matching `args`.
+    pub(crate) fn fill(&self, msg: Msg, args: &[(&str, &dyn fmt::Display)]) -> String {
+        self.lang.fill(msg, args)
+    }
+
+    /// Returns the text of `msg` in the configured language as spans, with
+    /// its placeholders replaced by the matching (styled) `args`.
+    pub(crate) fn fill_spans<'a>(&self, msg: Msg, args: &[(&str, Span<'a>)]) -> Vec<Span<'a>> {
+        i18n::pieces(self.text(msg))
+            .map(|piece| match piece {
+                i18n::Piece::Text(text) => Span::raw(text),
+                i18n::Piece::Arg(name) => args
+                    .iter()
+                    .find(|(arg, _)| *arg == name)
+                    .map(|(_, span)| span.clone())
+                    .unwrap_or_else(|| Span::raw(format!("{{{name}}}"))),
+            })
+            .collect()
+    }
+
+    /// Returns the URL `location` links to, if spawn locations are links.
+    pub(crate) fn location_url(&self, location: &SourceLocation) -> Option<String> {
+        let template = self.location_links.as_deref()?;
+        Some(super::hyperlink::location_url(template, location))
+    }
+
+    /// Returns the configured language.
+    pub(crate) fn lang(&self) -> Lang {
+        self.lang
+    }
+
+    /// Switches to the next way of displaying points in time: from relative
+    /// times, to local times, to UTC, and back again.
+    pub(crate) fn cycle_time_format(&mut self) {
+        self.time_format = match self.time_format {
+            TimeFormat::Relative => TimeFormat::Local,
+            TimeFormat::Local => TimeFormat::Utc,
+            TimeFormat::Utc => TimeFormat::Relative,
+        };
+    }
+
+    /// Creates a span with the time `at`, including its date and time zone,
+    /// formatted with the current time format.
+    ///
+    /// Relative times are how long before `now` the time was.
+    pub(crate) fn timestamp<'a>(&self, at: SystemTime, now: SystemTime) -> Span<'a> {
+        match self.time_format {
+            TimeFormat::Relative => {
+                let since = now.duration_since(at).unwrap_or_default();
+                let since = self.time_units(since, super::DUR_LIST_PRECISION, None);
+                Span::styled(
+                    self.fill(Msg::Ago, &[("duration", &since.content)]),
+                    since.style,
+                )
+            }
+            TimeFormat::Local | TimeFormat::Utc => {
+                let (date, time, zone) = self.rfc3339(at);
+                Span::raw(format!("{date} {time} {zone}"))
+            }
+        }
+    }
+
+    /// Creates a span with the time `at`, formatted with the current time
+    /// format, for a table column.
+    ///
+    /// Relative times are shown as a negative offset from `now`, and absolute
+    /// times only include the time of day, to save space. The text is right
+    /// aligned to [`TIMESTAMP_LEN`](super::TIMESTAMP_LEN).
+    pub(crate) fn timestamp_cell<'a>(&self, at: SystemTime, now: SystemTime) -> Span<'a> {
+        let width = super::TIMESTAMP_LEN;
+        match self.time_format {
+            TimeFormat::Relative => {
+                let since = now.duration_since(at).unwrap_or_default();
+                let since = self.time_units(since, super::DUR_TABLE_PRECISION, None);
+                Span::styled(
+                    format!("{:>width$}", format!("-{}", since.content)),
+                    since.style,
+                )
+            }
+            TimeFormat::Local | TimeFormat::Utc => {
+                let (_, time, _) = self.rfc3339(at);
+                Span::raw(format!("{:>width$}", &time[..width]))
+            }
+        }
+    }
+
+    /// Returns the date, time of day (with millisecond precision), and time
+    /// zone of `at`, in the time zone of the current time format.
+    fn rfc3339(&self, at: SystemTime) -> (String, String, String) {
+        let offset = match self.time_format {
+            TimeFormat::Local => local_offset(at),
+            TimeFormat::Relative | TimeFormat::Utc => 0,
+        };
+        let shifted = if offset >= 0 {
+            at.checked_add(Duration::from_secs(offset.unsigned_abs()))
+        } else {
+            at.checked_sub(Duration::from_secs(offset.unsigned_abs()))
+        };
+        // `humantime` only formats times after the epoch.
+        let shifted = shifted.unwrap_or(at).max(SystemTime::UNIX_EPOCH);
+        // This is always formatted as `YYYY-MM-DDTHH:MM:SS.mmmZ`.
+        let text = humantime::format_rfc3339_millis(shifted).to_string();
+        let zone = if self.time_format == TimeFormat::Utc {
+            self.text(Msg::Utc).to_owned()
+        } else {
+            let minutes = offset.unsigned_abs() / 60;
+            format!(
+                "{}{:02}:{:02}",
+                if offset < 0 { '-' } else { '+' },
+                minutes / 60,
+                minutes % 60
+            )
+        };
+        (text[..10].to_owned(), text[11..23].to_owned(), zone)
+    }
+
     pub fn terminated(&self) -> Style {
         if !self.toggles.color_terminated() {
             return Style::default();
@@ -344,6 +484,35 @@ impl Styles {
     }
 }
 
+/// Returns the local time zone's offset from UTC at `at`, in seconds.
+#[cfg(unix)]
+fn local_offset(at: SystemTime) -> i64 {
+    let Some(secs) = at
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .ok()
+        .and_then(|since| libc::time_t::try_from(since.as_secs()).ok())
+    else {
+        return 0;
+    };
+    // SAFETY: `localtime_r` is thread-safe (unlike `localtime`), and only
+    // writes to the `tm` it's passed, which is valid for writes.
+    unsafe {
+        let mut tm = std::mem::zeroed::<libc::tm>();
+        if libc::localtime_r(&secs, &mut tm).is_null() {
+            return 0;
+        }
+        tm.tm_gmtoff as i64
+    }
+}
+
+/// Returns the local time zone's offset from UTC at `at`, in seconds.
+///
+/// Time zones are only looked up on Unix, so elsewhere local times are UTC.
+#[cfg(not(unix))]
+fn local_offset(_: SystemTime) -> i64 {
+    0
+}
+
 // === impl Palette ===
 
 impl FromStr for Palette {
@@ -362,6 +531,20 @@ impl FromStr for Palette {
     }
 }
 
+impl Palette {
+    /// Returns the richest palette a terminal which supports `colors` colors
+    /// can display.
+    pub(crate) fn from_colors(colors: i32) -> Self {
+        match colors {
+            0x100_0000.. => Palette::All,
+            256.. => Palette::Ansi256,
+            16.. => Palette::Ansi16,
+            8.. => Palette::Ansi8,
+            _ => Palette::NoColors,
+        }
+    }
+}
+
 impl Default for Palette {
     fn default() -> Self {
         Self::NoColors
@@ -1,8 +1,11 @@
 use crate::{
-    input, state,
+    i18n::Msg,
+    input,
+    session::TableSession,
+    state,
     view::{
         self,
-        controls::{controls_paragraph, ControlDisplay, KeyDisplay},
+        controls::{controls_paragraph, noted_controls_paragraph, ControlDisplay, KeyDisplay},
         help::HelpText,
     },
 };
@@ -23,6 +26,10 @@ pub(crate) trait TableList<const N: usize> {
     const HEADER: &'static [&'static str; N];
     const WIDTHS: &'static [usize; N];
 
+    /// A note about the table which is shown above its controls in the help
+    /// popup, if there's more to explain than the controls.
+    const HELP: Option<Msg> = None;
+
     fn render(
         state: &mut TableListState<Self, N>,
         styles: &view::Styles,
@@ -38,6 +45,18 @@ pub(crate) trait SortBy {
     fn as_column(&self) -> usize;
 }
 
+/// Where [`TableListState::select_matching`] starts looking for a matching
+/// item.
+#[derive(Debug, Copy, Clone)]
+pub(crate) enum Seek {
+    /// From the selected item, which stays selected if it matches.
+    Here,
+    /// From the item below the selected one.
+    Next,
+    /// From the item above the selected one, looking upwards.
+    Prev,
+}
+
 pub(crate) struct TableListState<T: TableList<N>, const N: usize> {
     pub(crate) sorted_items: Vec<Weak<RefCell<T::Row>>>,
     pub(crate) sort_by: T::Sort,
@@ -155,21 +174,85 @@ impl<T: TableList<N>, const N: usize> TableListState<T, N> {
     }
 
     pub(in crate::view) fn selected_item(&self) -> Option<Rc<RefCell<T::Row>>> {
-        self.table_state
-            .selected()
-            .and_then(|i| {
-                if self.sort_descending {
-                    if i < self.sorted_items.len() {
-                        Some(self.sorted_items[i].clone())
-                    } else {
-                        None
-                    }
-                } else {
-                    let adjusted_index = self.sorted_items.len().checked_sub(i + 1)?;
-                    self.sorted_items.get(adjusted_index).cloned()
-                }
+        self.table_state.selected().and_then(|i| self.item_at(i))
+    }
+
+    /// Returns the item in row `i` of the table, as it's displayed.
+    fn item_at(&self, i: usize) -> Option<Rc<RefCell<T::Row>>> {
+        if self.sort_descending {
+            self.sorted_items.get(i)?.upgrade()
+        } else {
+            let adjusted_index = self.sorted_items.len().checked_sub(i + 1)?;
+            self.sorted_items.get(adjusted_index)?.upgrade()
+        }
+    }
+
+    /// Selects the first item `f` returns `true` for, looking through the
+    /// table from the position given by `seek` and wrapping around at its
+    /// ends. The selection is left alone if nothing matches.
+    pub(in crate::view) fn select_matching(&mut self, seek: Seek, f: impl Fn(&T::Row) -> bool) {
+        let len = self.sorted_items.len();
+        if len == 0 {
+            return;
+        }
+        let (start, seek) = match (self.table_state.selected(), seek) {
+            (Some(selected), _) => (selected.min(len - 1), seek),
+            // With nothing selected, the next item is the first one.
+            (None, Seek::Next) => (0, Seek::Here),
+            (None, seek) => (0, seek),
+        };
+        let found = (0..len)
+            .map(|offset| match seek {
+                Seek::Here => (start + offset) % len,
+                Seek::Next => (start + 1 + offset) % len,
+                Seek::Prev => (start + len - 1 - offset) % len,
             })
-            .and_then(|weak| weak.upgrade())
+            .find(|&i| self.item_at(i).is_some_and(|item| f(&item.borrow())));
+        if let Some(i) = found {
+            self.table_state.select(Some(i));
+        }
+    }
+
+    /// Returns how many items `f` returns `true` for, and which of them is
+    /// selected, counting from 1, if the selected item is one of them.
+    pub(in crate::view) fn count_matching(
+        &self,
+        f: impl Fn(&T::Row) -> bool,
+    ) -> (usize, Option<usize>) {
+        let selected = self.table_state.selected();
+        let mut count = 0;
+        let mut current = None;
+        for i in 0..self.sorted_items.len() {
+            if self.item_at(i).is_some_and(|item| f(&item.borrow())) {
+                count += 1;
+                if selected == Some(i) {
+                    current = Some(count);
+                }
+            }
+        }
+        (count, current)
+    }
+
+    /// Returns how this table is sorted, to be restored by a later session.
+    pub(in crate::view) fn save_session(&self) -> TableSession {
+        TableSession {
+            sort_column: T::HEADER[self.sort_by.as_column()].to_owned(),
+            sort_descending: self.sort_descending,
+        }
+    }
+
+    /// Sorts this table the way it was sorted in a previous session.
+    pub(in crate::view) fn restore_session(&mut self, session: &TableSession) {
+        let column = T::HEADER
+            .iter()
+            .position(|header| *header == session.sort_column);
+        if let Some(column) = column {
+            if let Ok(sort_by) = T::Sort::try_from(column) {
+                self.sort_by = sort_by;
+                self.selected_column = column;
+            }
+        }
+        self.sort_descending = session.sort_descending;
     }
 
     pub(in crate::view) fn render(
@@ -208,14 +291,17 @@ where
     T: TableList<N>,
 {
     fn render_help_content(&self, styles: &view::Styles) -> Paragraph<'static> {
-        controls_paragraph(view_controls(), styles)
+        match T::HELP {
+            Some(note) => noted_controls_paragraph(note, view_controls(), styles),
+            None => controls_paragraph(view_controls(), styles),
+        }
     }
 }
 
 pub(crate) const fn view_controls() -> &'static [ControlDisplay] {
     &[
         ControlDisplay {
-            action: "select column (sort)",
+            action: Msg::SelectColumn,
             keys: &[
                 KeyDisplay {
                     base: "left, right",
@@ -228,7 +314,7 @@ pub(crate) const fn view_controls() -> &'static [ControlDisplay] {
             ],
         },
         ControlDisplay {
-            action: "scroll",
+            action: Msg::Scroll,
             keys: &[
                 KeyDisplay {
                     base: "up, down",
@@ -241,28 +327,28 @@ pub(crate) const fn view_controls() -> &'static [ControlDisplay] {
             ],
         },
         ControlDisplay {
-            action: "view details",
+            action: Msg::ViewDetails,
             keys: &[KeyDisplay {
                 base: "enter",
                 utf8: Some("\u{21B5}"),
             }],
         },
         ControlDisplay {
-            action: "invert sort (highest/lowest)",
+            action: Msg::InvertSort,
             keys: &[KeyDisplay {
                 base: "i",
                 utf8: None,
             }],
         },
         ControlDisplay {
-            action: "scroll to top",
+            action: Msg::ScrollToTop,
             keys: &[KeyDisplay {
                 base: "gg",
                 utf8: None,
             }],
         },
         ControlDisplay {
-            action: "scroll to bottom",
+            action: Msg::ScrollToBottom,
             keys: &[KeyDisplay {
                 base: "G",
                 utf8: None,
@@ -0,0 +1,293 @@
+use crate::{
+    i18n::Msg,
+    state::{
+        targets::{SortBy, Target},
+        State,
+    },
+    util::Percentage,
+    view::{
+        self, bold,
+        controls::Controls,
+        table::{view_controls, TableList, TableListState},
+        DUR_LEN, DUR_TABLE_PRECISION,
+    },
+};
+use ratatui::{
+    layout,
+    style::{self, Color, Style},
+    text::{Line, Span},
+    widgets::{Cell, Paragraph, Row, Table},
+};
+use std::time::Duration;
+
+#[derive(Debug, Default)]
+pub(crate) struct TargetsTable {}
+
+/// The number of characters in the bar showing each target's share of the
+/// busy time.
+const BAR_LEN: usize = 10;
+
+/// The narrowest block in the treemap. Targets whose share of the busy time
+/// would make their block any narrower are lumped together in one block.
+const MIN_BLOCK_WIDTH: u16 = 6;
+
+/// The colors the treemap's blocks cycle through.
+const BLOCK_COLORS: &[Color] = &[Color::Cyan, Color::Green, Color::Yellow, Color::Magenta];
+
+impl TableList<5> for TargetsTable {
+    type Row = Target;
+    type Sort = SortBy;
+    type Context = ();
+
+    const HEADER: &'static [&'static str; 5] = &["Tasks", "Busy", "Polls", "Share", "Target"];
+
+    const WIDTHS: &'static [usize; 5] = &[
+        Self::HEADER[0].len() + 1,
+        Self::HEADER[1].len() + 1,
+        Self::HEADER[2].len() + 1,
+        Self::HEADER[3].len() + 1,
+        Self::HEADER[4].len() + 1,
+    ];
+
+    fn render(
+        table_list_state: &mut TableListState<Self, 5>,
+        styles: &view::Styles,
+        frame: &mut ratatui::terminal::Frame,
+        area: layout::Rect,
+        state: &mut State,
+        _: Self::Context,
+    ) {
+        let now = if let Some(now) = state.last_updated_at() {
+            now
+        } else {
+            // If we have never gotten an update yet, skip...
+            return;
+        };
+
+        table_list_state
+            .sorted_items
+            .extend(state.tasks_state_mut().targets_mut().take_new_targets());
+        table_list_state
+            .sorted_items
+            .retain(|target| target.upgrade().is_some());
+        table_list_state
+            .sort_by
+            .sort(now, &mut table_list_state.sorted_items);
+
+        // The busy time of every target, busiest first, for the treemap.
+        let mut busy = table_list_state
+            .sorted_items
+            .iter()
+            .filter_map(|target| {
+                let target = target.upgrade()?;
+                let target = target.borrow();
+                Some((target.target().to_owned(), target.busy(now)))
+            })
+            .collect::<Vec<_>>();
+        busy.sort_unstable_by(|(a_target, a_busy), (b_target, b_busy)| {
+            b_busy.cmp(a_busy).then_with(|| a_target.cmp(b_target))
+        });
+        let total_busy = busy.iter().map(|&(_, busy)| busy).sum::<Duration>();
+
+        let mut tasks_width = view::Width::new(Self::WIDTHS[0] as u16);
+        let mut polls_width = view::Width::new(Self::WIDTHS[2] as u16);
+        let share_width = "100% ".len() + BAR_LEN;
+
+        let quotas = state.tasks_state().quotas();
+        let rows = {
+            let tasks_width = &mut tasks_width;
+            let polls_width = &mut polls_width;
+
+            table_list_state
+                .sorted_items
+                .iter()
+                .filter_map(move |target| {
+                    let target = target.upgrade()?;
+                    let target = target.borrow();
+                    let busy = target.busy(now);
+                    let share = busy
+                        .as_secs_f64()
+                        .percent_of(total_busy.as_secs_f64())
+                        .round() as usize;
+                    let filled = (share * BAR_LEN + 50) / 100;
+                    let bar = styles.if_utf8("\u{2588}", "#").repeat(filled);
+                    let row = Row::new(vec![
+                        Cell::from(tasks_width.update_str(target.len().to_string())),
+                        Cell::from(styles.time_units(busy, DUR_TABLE_PRECISION, Some(DUR_LEN))),
+                        Cell::from(polls_width.update_str(target.total_polls().to_string())),
+                        Cell::from(Line::from(vec![
+                            format!("{share:>3}% ").into(),
+                            Span::styled(bar, styles.fg(Color::Cyan)),
+                        ])),
+                        Cell::from(target.target().to_owned()),
+                    ]);
+                    if quotas.is_target_exceeded(target.target()) {
+                        Some(row.style(styles.fg(Color::Red)))
+                    } else {
+                        Some(row)
+                    }
+                })
+        };
+
+        let header_style = if styles.color(Color::Cyan).is_some() {
+            Style::default()
+        } else {
+            Style::default().add_modifier(style::Modifier::REVERSED)
+        };
+        let header_style = header_style.add_modifier(style::Modifier::BOLD);
+
+        let header = Row::new(Self::HEADER.iter().enumerate().map(|(idx, &value)| {
+            if idx == table_list_state.selected_column {
+                if table_list_state.sort_descending {
+                    Cell::from(styles.ascending(value))
+                } else {
+                    Cell::from(styles.descending(value))
+                }
+            } else {
+                Cell::from(value)
+            }
+        }))
+        .height(1)
+        .style(header_style);
+
+        let table = if table_list_state.sort_descending {
+            Table::default().rows(rows)
+        } else {
+            Table::default().rows(rows.rev())
+        };
+
+        let block = styles.border_block().title(vec![bold(
+            styles.fill(Msg::TargetsTitle, &[("count", &table_list_state.len())]),
+        )]);
+
+        let controls = Controls::new(view_controls(), &area, styles);
+        let chunks = layout::Layout::default()
+            .direction(layout::Direction::Vertical)
+            .margin(0)
+            .constraints(
+                [
+                    layout::Constraint::Length(controls.height()),
+                    layout::Constraint::Length(3),
+                    layout::Constraint::Min(0),
+                ]
+                .as_ref(),
+            )
+            .split(area);
+        let (controls_area, treemap_area, table_area) = (chunks[0], chunks[1], chunks[2]);
+        frame.render_widget(controls.into_widget(), controls_area);
+
+        if total_busy == Duration::ZERO {
+            let empty = Paragraph::new(styles.text(Msg::NoBusyTargets)).block(
+                styles
+                    .border_block()
+                    .title(vec![bold(styles.text(Msg::Targets))]),
+            );
+            frame.render_widget(empty, treemap_area.union(table_area));
+            return;
+        }
+        render_treemap(styles, frame, treemap_area, &busy, total_busy);
+
+        let widths = &[
+            tasks_width.constraint(),
+            layout::Constraint::Length(DUR_LEN as u16),
+            polls_width.constraint(),
+            layout::Constraint::Length(share_width as u16),
+            layout::Constraint::Min(Self::WIDTHS[4] as u16),
+        ];
+
+        let table = table
+            .header(header)
+            .block(block)
+            .widths(widths)
+            .highlight_symbol(view::TABLE_HIGHLIGHT_SYMBOL)
+            .highlight_style(Style::default().add_modifier(style::Modifier::BOLD));
+
+        frame.render_stateful_widget(table, table_area, &mut table_list_state.table_state);
+    }
+}
+
+/// Renders a strip of blocks, one for each target, each as wide as the
+/// target's share of the busy time.
+///
+/// `busy` must be sorted busiest first, so that the targets too small to get
+/// a block of their own are all at the end, where they share one block.
+fn render_treemap(
+    styles: &view::Styles,
+    frame: &mut ratatui::terminal::Frame,
+    area: layout::Rect,
+    busy: &[(String, Duration)],
+    total_busy: Duration,
+) {
+    let weights = busy
+        .iter()
+        .map(|(_, busy)| busy.as_secs_f64())
+        .collect::<Vec<_>>();
+    let widths = proportional_widths(&weights, area.width);
+    let shown = widths
+        .iter()
+        .take_while(|&&width| width >= MIN_BLOCK_WIDTH)
+        .count();
+
+    let mut blocks = busy[..shown]
+        .iter()
+        .zip(&widths)
+        .map(|((target, busy), &width)| (target.clone(), *busy, width))
+        .collect::<Vec<_>>();
+    if shown < busy.len() {
+        let rest = busy[shown..].iter().map(|&(_, busy)| busy).sum();
+        let width = widths[shown..].iter().sum();
+        match blocks.last_mut() {
+            // Too narrow for a block of its own, so the rest are only shown
+            // in the table.
+            Some(last) if width < MIN_BLOCK_WIDTH => last.2 += width,
+            _ => blocks.push((styles.text(Msg::OtherTargets).to_owned(), rest, width)),
+        }
+    }
+
+    let mut x = area.x;
+    for (i, (target, busy, width)) in blocks.into_iter().enumerate() {
+        let block_area = layout::Rect { x, width, ..area };
+        x += width;
+        let share = busy
+            .as_secs_f64()
+            .percent_of(total_busy.as_secs_f64())
+            .round();
+        let color = BLOCK_COLORS[i % BLOCK_COLORS.len()];
+        let block = Paragraph::new(format!("{share}%")).block(
+            styles
+                .border_block()
+                .border_style(styles.fg(color))
+                .title(target),
+        );
+        frame.render_widget(block, block_area);
+    }
+}
+
+/// Splits `width` columns between `weights`, in proportion to them.
+///
+/// The widths always add up to `width`: the columns left over from rounding
+/// down go to the weights which lost the most to rounding.
+fn proportional_widths(weights: &[f64], width: u16) -> Vec<u16> {
+    let total = weights.iter().sum::<f64>();
+    if total <= 0.0 {
+        return vec![0; weights.len()];
+    }
+    let exact = weights
+        .iter()
+        .map(|weight| weight / total * f64::from(width))
+        .collect::<Vec<_>>();
+    let mut widths = exact
+        .iter()
+        .map(|&exact| exact.floor() as u16)
+        .collect::<Vec<_>>();
+    let mut by_remainder = (0..weights.len()).collect::<Vec<_>>();
+    by_remainder.sort_by(|&a, &b| {
+        let remainder = |i: usize| exact[i] - exact[i].floor();
+        remainder(b).total_cmp(&remainder(a)).then(a.cmp(&b))
+    });
+    let left_over = width - widths.iter().sum::<u16>();
+    for &i in by_remainder.iter().take(left_over as usize) {
+        widths[i] += 1;
+    }
+    widths
+}
@@ -1,38 +1,94 @@
 use crate::{
+    hdr,
+    i18n::Msg,
     input,
-    state::{tasks::Task, DetailsRef},
+    state::{
+        tasks::{Details, Task, ThreadPolls},
+        DetailsRef, State,
+    },
     util::Percentage,
     view::{
         self, bold,
         controls::{controls_paragraph, ControlDisplay, Controls, KeyDisplay},
-        durations::Durations,
+        durations::{Durations, HistogramMode},
         help::HelpText,
+        hyperlink::Hyperlink,
     },
 };
 use ratatui::{
     layout::{self, Layout},
+    style::{Color, Modifier},
     text::{Line, Span, Text},
     widgets::{List, ListItem, Paragraph},
 };
 use std::{
     cell::RefCell,
     cmp,
+    path::Path,
     rc::Rc,
     time::{Duration, SystemTime},
 };
 
+/// The most async ops listed as being awaited by a task.
+const MAX_AWAITING: usize = 5;
+
 pub(crate) struct TaskView {
     task: Rc<RefCell<Task>>,
     details: DetailsRef,
+    histogram_mode: HistogramMode,
+    /// The areas which were rendered as links in the last frame.
+    links: Vec<Hyperlink>,
 }
 
 impl TaskView {
     pub(super) fn new(task: Rc<RefCell<Task>>, details: DetailsRef) -> Self {
-        TaskView { task, details }
+        TaskView {
+            task,
+            details,
+            histogram_mode: HistogramMode::default(),
+            links: Vec::new(),
+        }
     }
 
-    pub(crate) fn update_input(&mut self, _event: input::Event) {
-        // TODO :D
+    pub(crate) fn links(&self) -> &[Hyperlink] {
+        &self.links
+    }
+
+    pub(crate) fn update_input(&mut self, event: input::Event) {
+        use input::KeyCode::*;
+
+        if let input::Event::Key(input::KeyEvent {
+            code: Char('m'), ..
+        }) = event
+        {
+            self.histogram_mode = match self.histogram_mode {
+                HistogramMode::Chart => HistogramMode::Percentiles,
+                HistogramMode::Percentiles => HistogramMode::Counts,
+                HistogramMode::Counts => HistogramMode::Chart,
+            };
+        }
+    }
+
+    /// Writes the poll times of the task to `path`, for the `:export`
+    /// command, returning the error to show at the prompt if that fails.
+    pub(crate) fn export_poll_times(
+        &self,
+        path: &str,
+        state: &State,
+        styles: &view::Styles,
+    ) -> Result<(), String> {
+        if path.is_empty() {
+            return Err(styles.text(Msg::ExportNeedsPath).to_owned());
+        }
+        let task = self.task.borrow();
+        let details = self.details.borrow();
+        let histogram = details
+            .as_ref()
+            .filter(|details| details.span_id() == task.span_id())
+            .and_then(Details::poll_times_histogram)
+            .ok_or_else(|| styles.text(Msg::NoPollTimes).to_owned())?;
+        let now = state.last_updated_at().unwrap_or_else(SystemTime::now);
+        hdr::export(Path::new(path), &task, histogram, now).map_err(|error| format!("{error:#}"))
     }
 
     pub(crate) fn render(
@@ -40,6 +96,7 @@ impl TaskView {
         styles: &view::Styles,
         frame: &mut ratatui::terminal::Frame,
         area: layout::Rect,
+        state: &State,
         now: SystemTime,
     ) {
         // Rows with the following info:
@@ -56,46 +113,39 @@ impl TaskView {
 
         let controls = Controls::new(view_controls(), &area, styles);
 
-        let warnings: Vec<_> = task
+        let mut warnings: Vec<_> = task
             .warnings()
             .iter()
             .map(|linter| {
                 ListItem::new(Text::from(Line::from(vec![
                     styles.warning_wide(),
                     // TODO(eliza): it would be nice to handle singular vs plural...
-                    Span::from(linter.format(task)),
+                    Span::from(linter.format(task, styles.lang())),
                 ])))
             })
             .collect();
+        if let Some(ended) = details.and_then(Details::ended) {
+            warnings.push(ListItem::new(Text::from(Line::from(vec![
+                styles.warning_wide(),
+                Span::from(ended.describe(styles.lang())),
+            ]))));
+        }
+
+        let awaiting = awaiting(styles, state, task, now);
+
+        // Older subscribers don't record the intervals between wakes.
+        let wake_intervals = details.and_then(|d| d.wake_intervals_histogram());
 
         let (
             controls_area,
+            warnings_area,
             stats_area,
             poll_dur_area,
             scheduled_dur_area,
+            wake_dur_area,
+            awaiting_area,
             fields_area,
-            warnings_area,
-        ) = if warnings.is_empty() {
-            let chunks = Layout::default()
-                .direction(layout::Direction::Vertical)
-                .constraints(
-                    [
-                        // controls
-                        layout::Constraint::Length(controls.height()),
-                        // task stats
-                        layout::Constraint::Length(10),
-                        // poll duration
-                        layout::Constraint::Length(9),
-                        // scheduled duration
-                        layout::Constraint::Length(9),
-                        // fields
-                        layout::Constraint::Percentage(60),
-                    ]
-                    .as_ref(),
-                )
-                .split(area);
-            (chunks[0], chunks[1], chunks[2], chunks[3], chunks[4], None)
-        } else {
+        ) = {
             let chunks = Layout::default()
                 .direction(layout::Direction::Vertical)
                 .constraints(
@@ -103,27 +153,34 @@ impl TaskView {
                         // controls
                         layout::Constraint::Length(controls.height()),
                         // warnings (add 2 for top and bottom borders)
-                        layout::Constraint::Length(warnings.len() as u16 + 2),
+                        layout::Constraint::Length(if warnings.is_empty() {
+                            0
+                        } else {
+                            warnings.len() as u16 + 2
+                        }),
                         // task stats
-                        layout::Constraint::Length(10),
+                        layout::Constraint::Length(14),
                         // poll duration
                         layout::Constraint::Length(9),
                         // scheduled duration
                         layout::Constraint::Length(9),
+                        // wake intervals
+                        layout::Constraint::Length(if wake_intervals.is_some() { 9 } else { 0 }),
+                        // awaited async ops (add 2 for top and bottom borders)
+                        layout::Constraint::Length(if awaiting.is_empty() {
+                            0
+                        } else {
+                            awaiting.len() as u16 + 2
+                        }),
                         // fields
                         layout::Constraint::Percentage(60),
                     ]
                     .as_ref(),
                 )
                 .split(area);
-
             (
-                chunks[0],
-                chunks[2],
-                chunks[3],
-                chunks[4],
-                chunks[5],
-                Some(chunks[1]),
+                chunks[0], chunks[1], chunks[2], chunks[3], chunks[4], chunks[5], chunks[6],
+                chunks[7],
             )
         };
 
@@ -138,21 +195,54 @@ impl TaskView {
             )
             .split(stats_area);
 
-        // Just preallocate capacity for ID, name, target, total, busy, and idle.
-        let mut overview = Vec::with_capacity(8);
+        let side_area = Layout::default()
+            .direction(layout::Direction::Vertical)
+            .constraints(
+                [
+                    // wakers (add 2 for top and bottom borders)
+                    layout::Constraint::Length(3 + 2),
+                    // threads
+                    layout::Constraint::Min(0),
+                ]
+                .as_ref(),
+            )
+            .split(stats_area[1]);
+
+        // Just preallocate capacity for ID, name, target, scheduler, level,
+        // location, total, busy, scheduled, idle, longest poll, and executor
+        // share.
+        let mut overview = Vec::with_capacity(12);
         overview.push(Line::from(vec![
-            bold("ID: "),
+            bold(styles.text(Msg::Id)),
             Span::raw(format!("{} ", task.id_str())),
             task.state().render(styles),
         ]));
 
         if let Some(name) = task.name() {
-            overview.push(Line::from(vec![bold("Name: "), Span::raw(name)]));
+            overview.push(Line::from(vec![
+                bold(styles.text(Msg::Name)),
+                Span::raw(name),
+            ]));
         }
 
-        overview.push(Line::from(vec![bold("Target: "), Span::raw(task.target())]));
+        overview.push(Line::from(vec![
+            bold(styles.text(Msg::Target)),
+            Span::raw(task.target()),
+        ]));
+
+        if let Some(scheduler) = task.scheduler() {
+            overview.push(Line::from(vec![
+                bold(styles.text(Msg::Scheduler)),
+                Span::raw(scheduler),
+            ]));
+        }
 
-        let title = "Location: ";
+        overview.push(Line::from(vec![
+            bold(styles.text(Msg::Level)),
+            Span::raw(task.level().as_str()),
+        ]));
+
+        let title = styles.text(Msg::Location);
         let location_max_width = stats_area[0].width as usize - 2 - title.len(); // NOTE: -2 for the border
         let location = if task.location().len() > location_max_width {
             let ellipsis = styles.if_utf8("\u{2026}", "...");
@@ -162,103 +252,320 @@ impl TaskView {
             task.location().to_string()
         };
 
+        self.links.clear();
+        if let Some(url) = task
+            .source_location()
+            .and_then(|location| styles.location_url(location))
+        {
+            let inner = styles.border_block().inner(stats_area[0]);
+            let x = inner.x + title.len() as u16;
+            let y = inner.y + overview.len() as u16;
+            if x < inner.right() && y < inner.bottom() {
+                let width = cmp::min(location.chars().count() as u16, inner.right() - x);
+                self.links.push(Hyperlink {
+                    area: layout::Rect::new(x, y, width, 1),
+                    url,
+                });
+            }
+        }
+
         overview.push(Line::from(vec![bold(title), Span::raw(location)]));
 
         let total = task.total(now);
 
-        let dur_percent = |name: &'static str, amt: Duration| -> Line {
+        let dur_percent = |name: Msg, amt: Duration| -> Line {
             let percent = amt.as_secs_f64().percent_of(total.as_secs_f64());
             Line::from(vec![
-                bold(name),
+                bold(styles.text(name)),
                 styles.time_units(amt, view::DUR_LIST_PRECISION, None),
                 Span::from(format!(" ({:.2}%)", percent)),
             ])
         };
 
-        overview.push(Line::from(vec![
-            bold("Total Time: "),
+        let mut total_time = vec![
+            bold(styles.text(Msg::TotalTime)),
             styles.time_units(total, view::DUR_LIST_PRECISION, None),
+        ];
+        if task.is_historical() {
+            total_time.push(Span::from(styles.text(Msg::Historical)));
+        }
+        overview.push(Line::from(total_time));
+        overview.push(dur_percent(Msg::Busy, task.busy(now)));
+        overview.push(dur_percent(Msg::Scheduled, task.scheduled(now)));
+        overview.push(dur_percent(Msg::Idle, task.idle(now)));
+        if let Some(delay) = task.first_poll_delay(now) {
+            overview.push(Line::from(vec![
+                bold(styles.text(Msg::FirstPollDelay)),
+                styles.time_units(delay, view::DUR_LIST_PRECISION, None),
+            ]));
+        }
+        if let Some((longest, started)) = task.longest_poll(now) {
+            let mut spans = vec![bold(styles.text(Msg::LongestPoll))];
+            spans.extend(styles.fill_spans(
+                Msg::LongestPollValue,
+                &[
+                    (
+                        "duration",
+                        styles.time_units(longest, view::DUR_LIST_PRECISION, None),
+                    ),
+                    ("at", styles.timestamp(started, now)),
+                ],
+            ));
+            overview.push(Line::from(spans));
+        }
+        overview.push(Line::from(vec![
+            bold(styles.text(Msg::ExecutorShare)),
+            Span::from(styles.fill(
+                Msg::ExecutorShareValue,
+                &[("percent", &format!("{:.2}", task.executor_share() * 100.0))],
+            )),
         ]));
-        overview.push(dur_percent("Busy: ", task.busy(now)));
-        overview.push(dur_percent("Scheduled: ", task.scheduled(now)));
-        overview.push(dur_percent("Idle: ", task.idle(now)));
-
-        let mut waker_stats = vec![Line::from(vec![
-            bold("Current wakers: "),
-            Span::from(format!("{} (", task.waker_count())),
-            bold("clones: "),
-            Span::from(format!("{}, ", task.waker_clones())),
-            bold("drops: "),
-            Span::from(format!("{})", task.waker_drops())),
-        ])];
+
+        let mut wakers = vec![bold(styles.text(Msg::CurrentWakers))];
+        let waker_count = task.waker_count();
+        let waker_count = if waker_count < 0 {
+            Span::styled(waker_count.to_string(), styles.fg(Color::Red))
+        } else {
+            Span::from(waker_count.to_string())
+        };
+        wakers.extend(styles.fill_spans(
+            Msg::WakerCounts,
+            &[
+                ("count", waker_count),
+                ("clones", bold(styles.text(Msg::WakerClones))),
+                ("clone_count", Span::from(task.waker_clones().to_string())),
+                ("drops", bold(styles.text(Msg::WakerDrops))),
+                ("drop_count", Span::from(task.waker_drops().to_string())),
+            ],
+        ));
+        let mut waker_stats = vec![Line::from(wakers)];
 
         let mut wakeups = vec![
-            bold("Woken: "),
-            Span::from(format!("{} times", task.wakes())),
+            bold(styles.text(Msg::Woken)),
+            Span::from(styles.fill(Msg::WokenTimes, &[("count", &task.wakes())])),
         ];
 
-        // If the task has been woken, add the time since wake to its stats as well.
-        if let Some(since) = task.since_wake(now) {
+        // If the task has been woken, add the time of the last wake to its
+        // stats as well.
+        if let Some(woken) = task.last_wake() {
             wakeups.reserve(3);
             wakeups.push(Span::raw(", "));
-            wakeups.push(bold("last woken: "));
-            wakeups.push(styles.time_units(since, view::DUR_LIST_PRECISION, None));
-            wakeups.push(Span::raw(" ago"));
+            wakeups.push(bold(styles.text(Msg::LastWoken)));
+            wakeups.push(styles.timestamp(woken, now));
         }
 
         waker_stats.push(Line::from(wakeups));
 
         if task.self_wakes() > 0 {
             waker_stats.push(Line::from(vec![
-                bold("Self Wakes: "),
-                Span::from(format!(
-                    "{} times ({}%)",
-                    task.self_wakes(),
-                    task.self_wake_percent()
+                bold(styles.text(Msg::SelfWakes)),
+                Span::from(styles.fill(
+                    Msg::SelfWakesValue,
+                    &[
+                        ("count", &task.self_wakes()),
+                        ("percent", &task.self_wake_percent()),
+                    ],
                 )),
             ]));
         }
 
+        // Subtract 2 for the top and bottom borders.
+        let thread_stats = details
+            .map(|details| {
+                thread_stats(
+                    styles,
+                    details.thread_polls(),
+                    details.other_thread_polls(),
+                    side_area[1].height.saturating_sub(2) as usize,
+                )
+            })
+            .unwrap_or_default();
+
         let mut fields = Text::default();
         fields.extend(task.formatted_fields().iter().cloned().map(Line::from));
+        fields.extend(task.formatted_extensions().iter().cloned().map(Line::from));
+        let omitted_fields = task.omitted_fields();
+        if omitted_fields > 0 {
+            fields.extend(Some(Line::from(Span::styled(
+                styles.fill(Msg::FieldsOmitted, &[("count", &omitted_fields)]),
+                styles.fg(Color::Yellow).add_modifier(Modifier::DIM),
+            ))));
+        }
 
-        if let Some(warnings_area) = warnings_area {
-            let warnings = List::new(warnings).block(styles.border_block().title("Warnings"));
+        if !warnings.is_empty() {
+            let warnings =
+                List::new(warnings).block(styles.border_block().title(styles.text(Msg::Warnings)));
             frame.render_widget(warnings, warnings_area);
         }
 
-        let task_widget = Paragraph::new(overview).block(styles.border_block().title("Task"));
-        let wakers_widget = Paragraph::new(waker_stats).block(styles.border_block().title("Waker"));
+        let task_widget =
+            Paragraph::new(overview).block(styles.border_block().title(styles.text(Msg::Task)));
+        let wakers_widget =
+            Paragraph::new(waker_stats).block(styles.border_block().title(styles.text(Msg::Waker)));
+        let threads_widget = Paragraph::new(thread_stats)
+            .block(styles.border_block().title(styles.text(Msg::Threads)));
 
-        let poll_percentiles_title = "Poll Times Percentiles";
-        let scheduled_percentiles_title = "Sched Times Percentiles";
-        let percentiles_width = cmp::max(
+        let poll_percentiles_title = styles.text(Msg::PollTimesPercentiles);
+        let scheduled_percentiles_title = styles.text(Msg::SchedTimesPercentiles);
+        let wake_percentiles_title = styles.text(Msg::WakeIntervalsPercentiles);
+        let mut percentiles_width = cmp::max(
             poll_percentiles_title.len(),
             scheduled_percentiles_title.len(),
-        ) as u16
-            + 2_u16; // extra 2 characters for the border
+        );
+        if wake_intervals.is_some() {
+            percentiles_width = cmp::max(percentiles_width, wake_percentiles_title.len());
+        }
+        let percentiles_width = percentiles_width as u16 + 2_u16; // extra 2 characters for the border
         let poll_durations_widget = Durations::new(styles)
             .histogram(details.and_then(|d| d.poll_times_histogram()))
             .percentiles_title(poll_percentiles_title)
-            .histogram_title("Poll Times Histogram")
-            .percentiles_width(percentiles_width);
+            .histogram_title(styles.text(Msg::PollTimesHistogram))
+            .percentiles_width(percentiles_width)
+            .mode(self.histogram_mode);
         let scheduled_durations_widget = Durations::new(styles)
             .histogram(details.and_then(|d| d.scheduled_times_histogram()))
             .percentiles_title(scheduled_percentiles_title)
-            .histogram_title("Scheduled Times Histogram")
-            .percentiles_width(percentiles_width);
+            .histogram_title(styles.text(Msg::ScheduledTimesHistogram))
+            .percentiles_width(percentiles_width)
+            .mode(self.histogram_mode);
+        let wake_intervals_widget = Durations::new(styles)
+            .histogram(wake_intervals)
+            .percentiles_title(wake_percentiles_title)
+            .histogram_title(styles.text(Msg::WakeIntervalsHistogram))
+            .percentiles_width(percentiles_width)
+            .mode(self.histogram_mode);
 
-        let fields_widget = Paragraph::new(fields).block(styles.border_block().title("Fields"));
+        let fields_widget =
+            Paragraph::new(fields).block(styles.border_block().title(styles.text(Msg::Fields)));
 
         frame.render_widget(controls.into_widget(), controls_area);
         frame.render_widget(task_widget, stats_area[0]);
-        frame.render_widget(wakers_widget, stats_area[1]);
+        frame.render_widget(wakers_widget, side_area[0]);
+        frame.render_widget(threads_widget, side_area[1]);
         frame.render_widget(poll_durations_widget, poll_dur_area);
         frame.render_widget(scheduled_durations_widget, scheduled_dur_area);
+        if wake_intervals.is_some() {
+            frame.render_widget(wake_intervals_widget, wake_dur_area);
+        }
+        if !awaiting.is_empty() {
+            let awaiting_widget = Paragraph::new(awaiting)
+                .block(styles.border_block().title(styles.text(Msg::Awaiting)));
+            frame.render_widget(awaiting_widget, awaiting_area);
+        }
         frame.render_widget(fields_widget, fields_area);
     }
 }
 
+/// Formats the async ops which `task` is currently awaiting, longest waiting
+/// first, in at most `MAX_AWAITING` lines.
+fn awaiting(
+    styles: &view::Styles,
+    state: &State,
+    task: &Task,
+    now: SystemTime,
+) -> Vec<Line<'static>> {
+    let mut async_ops = state
+        .async_ops_state()
+        .awaited_by(task.id())
+        .map(|async_op| async_op.borrow())
+        .collect::<Vec<_>>();
+    async_ops.sort_unstable_by_key(|async_op| cmp::Reverse(async_op.total(now)));
+
+    let shown = if async_ops.len() > MAX_AWAITING {
+        MAX_AWAITING - 1
+    } else {
+        async_ops.len()
+    };
+    let mut lines = async_ops[..shown]
+        .iter()
+        .map(|async_op| {
+            let resource = match state.resources_state().resource(async_op.resource_id()) {
+                Some(resource) => {
+                    let resource = resource.borrow();
+                    format!("{} {}", resource.concrete_type(), resource.id_str())
+                }
+                None => async_op.resource_id().to_string(),
+            };
+            let mut spans = vec![bold(async_op.source().to_string())];
+            spans.extend(styles.fill_spans(
+                Msg::AwaitingOp,
+                &[
+                    ("resource", Span::from(resource)),
+                    (
+                        "duration",
+                        styles.time_units(async_op.total(now), view::DUR_LIST_PRECISION, None),
+                    ),
+                    ("polls", Span::from(async_op.total_polls().to_string())),
+                ],
+            ));
+            Line::from(spans)
+        })
+        .collect::<Vec<_>>();
+    let hidden = async_ops.len() - shown;
+    if hidden > 0 {
+        lines.push(Line::from(Span::styled(
+            styles.fill(Msg::AwaitingMore, &[("count", &hidden)]),
+            styles.fg(Color::Yellow).add_modifier(Modifier::DIM),
+        )));
+    }
+    lines
+}
+
+/// Formats how many times each thread polled the task, most frequent first,
+/// in at most `max_lines` lines.
+fn thread_stats(
+    styles: &view::Styles,
+    threads: &[ThreadPolls],
+    other_polls: u64,
+    max_lines: usize,
+) -> Vec<Line<'static>> {
+    let total = threads.iter().map(|thread| thread.polls).sum::<u64>() + other_polls;
+    let polls = |polls: u64| {
+        Span::from(styles.fill(
+            Msg::ThreadPolls,
+            &[
+                ("polls", &polls),
+                (
+                    "percent",
+                    &format!("{:.2}", (polls as f64).percent_of(total as f64)),
+                ),
+            ],
+        ))
+    };
+
+    // Any threads which don't fit are counted with the untracked threads on
+    // the last line.
+    let mut shown = threads.len();
+    if shown > max_lines || (other_polls > 0 && shown >= max_lines) {
+        shown = max_lines.saturating_sub(1);
+    }
+    let mut lines = threads[..shown]
+        .iter()
+        .map(|thread| {
+            Line::from(vec![
+                bold(format!(
+                    "{} #{}: ",
+                    thread
+                        .name
+                        .as_deref()
+                        .unwrap_or(styles.text(Msg::UnnamedThread)),
+                    thread.id
+                )),
+                polls(thread.polls),
+            ])
+        })
+        .collect::<Vec<_>>();
+    let hidden = &threads[shown..];
+    if !hidden.is_empty() || other_polls > 0 {
+        let hidden_polls = hidden.iter().map(|thread| thread.polls).sum::<u64>() + other_polls;
+        lines.push(Line::from(vec![
+            bold(styles.text(Msg::OtherThreads)),
+            polls(hidden_polls),
+        ]));
+    }
+    lines
+}
+
 impl HelpText for TaskView {
     fn render_help_content(&self, styles: &view::Styles) -> Paragraph<'static> {
         controls_paragraph(view_controls(), styles)
@@ -266,11 +573,20 @@ impl HelpText for TaskView {
 }
 
 const fn view_controls() -> &'static [ControlDisplay] {
-    &[ControlDisplay {
-        action: "return to task list",
-        keys: &[KeyDisplay {
-            base: "esc",
-            utf8: Some("\u{238B} esc"),
-        }],
-    }]
+    &[
+        ControlDisplay {
+            action: Msg::ReturnToTaskList,
+            keys: &[KeyDisplay {
+                base: "esc",
+                utf8: Some("\u{238B} esc"),
+            }],
+        },
+        ControlDisplay {
+            action: Msg::CycleHistogramMode,
+            keys: &[KeyDisplay {
+                base: "m",
+                utf8: None,
+            }],
+        },
+    ]
 }
@@ -0,0 +1,74 @@
+use crate::{
+    i18n::Msg,
+    input,
+    state::{task_groups::TaskGroup, State},
+    view::{
+        self,
+        controls::{controls_paragraph, ControlDisplay, KeyDisplay},
+        help::HelpText,
+        table::TableListState,
+        tasks::{TasksContext, TasksTable},
+    },
+};
+use once_cell::sync::OnceCell;
+use ratatui::{layout, widgets::Paragraph};
+use std::{cell::RefCell, rc::Rc};
+
+/// An expanded task group, listing each of the tasks in the group.
+pub(crate) struct TaskGroupView {
+    group: Rc<RefCell<TaskGroup>>,
+    pub(crate) tasks_table: TableListState<TasksTable, 21>,
+}
+
+impl TaskGroupView {
+    pub(super) fn new(group: Rc<RefCell<TaskGroup>>) -> Self {
+        TaskGroupView {
+            group,
+            tasks_table: TableListState::<TasksTable, 21>::default(),
+        }
+    }
+
+    pub(crate) fn update_input(&mut self, event: input::Event) {
+        self.tasks_table.update_input(event)
+    }
+
+    pub(crate) fn render(
+        &mut self,
+        styles: &view::Styles,
+        frame: &mut ratatui::terminal::Frame,
+        area: layout::Rect,
+        state: &mut State,
+    ) {
+        self.tasks_table.render(
+            styles,
+            frame,
+            area,
+            state,
+            TasksContext {
+                group: Some(self.group.clone()),
+                filter: None,
+            },
+        );
+    }
+}
+
+impl HelpText for TaskGroupView {
+    fn render_help_content(&self, styles: &view::Styles) -> Paragraph<'static> {
+        controls_paragraph(view_controls(), styles)
+    }
+}
+
+fn view_controls() -> &'static [ControlDisplay] {
+    static VIEW_CONTROLS: OnceCell<Vec<ControlDisplay>> = OnceCell::new();
+
+    VIEW_CONTROLS.get_or_init(|| {
+        let group_controls = &[ControlDisplay {
+            action: Msg::CollapseGroup,
+            keys: &[KeyDisplay {
+                base: "esc",
+                utf8: Some("\u{238B} esc"),
+            }],
+        }];
+        [group_controls, view::table::view_controls()].concat()
+    })
+}
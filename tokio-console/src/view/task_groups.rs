@@ -0,0 +1,218 @@
+use crate::{
+    i18n::Msg,
+    state::{
+        task_groups::{SortBy, TaskGroup},
+        tasks::TaskState,
+        State,
+    },
+    view::{
+        self, bold,
+        controls::Controls,
+        table::{view_controls, TableList, TableListState},
+        DUR_LEN, DUR_TABLE_PRECISION,
+    },
+};
+use ratatui::{
+    layout,
+    style::{self, Color, Style},
+    widgets::{Cell, Row, Table},
+};
+
+#[derive(Debug, Default)]
+pub(crate) struct TaskGroupsTable {}
+
+impl TableList<13> for TaskGroupsTable {
+    type Row = TaskGroup;
+    type Sort = SortBy;
+    type Context = ();
+
+    const HEADER: &'static [&'static str; 13] = &[
+        "Tasks", "Running", "Idle", "Done", "Busy", "Sched", "Polls", "P50", "P95", "P99",
+        "1st P99", "Kind", "Location",
+    ];
+
+    const WIDTHS: &'static [usize; 13] = &[
+        Self::HEADER[0].len() + 1,
+        Self::HEADER[1].len() + 1,
+        Self::HEADER[2].len() + 1,
+        Self::HEADER[3].len() + 1,
+        Self::HEADER[4].len() + 1,
+        Self::HEADER[5].len() + 1,
+        Self::HEADER[6].len() + 1,
+        Self::HEADER[7].len() + 1,
+        Self::HEADER[8].len() + 1,
+        Self::HEADER[9].len() + 1,
+        Self::HEADER[10].len() + 1,
+        Self::HEADER[11].len() + 1,
+        Self::HEADER[12].len() + 1,
+    ];
+
+    const HELP: Option<Msg> = Some(Msg::TaskGroupsHelp);
+
+    fn render(
+        table_list_state: &mut TableListState<Self, 13>,
+        styles: &view::Styles,
+        frame: &mut ratatui::terminal::Frame,
+        area: layout::Rect,
+        state: &mut State,
+        _: Self::Context,
+    ) {
+        let now = if let Some(now) = state.last_updated_at() {
+            now
+        } else {
+            // If we have never gotten an update yet, skip...
+            return;
+        };
+
+        table_list_state
+            .sorted_items
+            .extend(state.tasks_state_mut().groups_mut().take_new_groups());
+        table_list_state
+            .sort_by
+            .sort(now, &mut table_list_state.sorted_items);
+
+        let dur_cell = |dur: std::time::Duration| -> Cell<'static> {
+            Cell::from(styles.time_units(dur, DUR_TABLE_PRECISION, Some(DUR_LEN)))
+        };
+        // percentiles are only known once the remote has sent the group's
+        // merged poll duration histogram.
+        let percentile_cell = |group: &TaskGroup, percentile: f64| -> Cell<'static> {
+            group
+                .poll_percentile(percentile)
+                .map(dur_cell)
+                .unwrap_or_else(|| Cell::from("-"))
+        };
+        let first_poll_cell = |group: &TaskGroup| -> Cell<'static> {
+            group
+                .first_poll_percentile(99.0)
+                .map(dur_cell)
+                .unwrap_or_else(|| Cell::from("-"))
+        };
+
+        let mut tasks_width = view::Width::new(Self::WIDTHS[0] as u16);
+        let mut running_width = view::Width::new(Self::WIDTHS[1] as u16);
+        let mut idle_width = view::Width::new(Self::WIDTHS[2] as u16);
+        let mut done_width = view::Width::new(Self::WIDTHS[3] as u16);
+        let mut polls_width = view::Width::new(Self::WIDTHS[6] as u16);
+        let mut kind_width = view::Width::new(Self::WIDTHS[11] as u16);
+        let mut location_width = view::Width::new(Self::WIDTHS[12] as u16);
+
+        let quotas = state.tasks_state().quotas();
+        let rows = {
+            let tasks_width = &mut tasks_width;
+            let running_width = &mut running_width;
+            let idle_width = &mut idle_width;
+            let done_width = &mut done_width;
+            let polls_width = &mut polls_width;
+            let kind_width = &mut kind_width;
+            let location_width = &mut location_width;
+
+            table_list_state
+                .sorted_items
+                .iter()
+                .filter_map(move |group| {
+                    let group = group.upgrade()?;
+                    let group = group.borrow();
+                    let running = group.count(TaskState::Running);
+                    let done = group.count(TaskState::Completed);
+
+                    let mut row = Row::new(vec![
+                        Cell::from(tasks_width.update_str(group.len().to_string())),
+                        Cell::from(running_width.update_str(running.to_string())),
+                        Cell::from(idle_width.update_str(group.count(TaskState::Idle).to_string())),
+                        Cell::from(done_width.update_str(done.to_string())),
+                        dur_cell(group.busy(now)),
+                        dur_cell(group.scheduled(now)),
+                        Cell::from(polls_width.update_str(group.total_polls().to_string())),
+                        percentile_cell(&group, 50.0),
+                        percentile_cell(&group, 95.0),
+                        percentile_cell(&group, 99.0),
+                        first_poll_cell(&group),
+                        Cell::from(kind_width.update_str(group.kind()).to_owned()),
+                        Cell::from(location_width.update_str(group.location()).to_owned()),
+                    ]);
+                    if done == group.len() {
+                        row = row.style(styles.terminated());
+                    } else if quotas.is_location_exceeded(group.location()) {
+                        row = row.style(styles.fg(Color::Red));
+                    }
+                    Some(row)
+                })
+        };
+
+        let header_style = if styles.color(Color::Cyan).is_some() {
+            Style::default()
+        } else {
+            Style::default().add_modifier(style::Modifier::REVERSED)
+        };
+        let header_style = header_style.add_modifier(style::Modifier::BOLD);
+
+        let header = Row::new(Self::HEADER.iter().enumerate().map(|(idx, &value)| {
+            if idx == table_list_state.selected_column {
+                if table_list_state.sort_descending {
+                    Cell::from(styles.ascending(value))
+                } else {
+                    Cell::from(styles.descending(value))
+                }
+            } else {
+                Cell::from(value)
+            }
+        }))
+        .height(1)
+        .style(header_style);
+
+        let table = if table_list_state.sort_descending {
+            Table::default().rows(rows)
+        } else {
+            Table::default().rows(rows.rev())
+        };
+
+        let block = styles.border_block().title(vec![bold(
+            styles.fill(Msg::TaskGroupsTitle, &[("count", &table_list_state.len())]),
+        )]);
+
+        let controls = Controls::new(view_controls(), &area, styles);
+        let chunks = layout::Layout::default()
+            .direction(layout::Direction::Vertical)
+            .margin(0)
+            .constraints(
+                [
+                    layout::Constraint::Length(controls.height()),
+                    layout::Constraint::Max(area.height),
+                ]
+                .as_ref(),
+            )
+            .split(area);
+        let (controls_area, groups_area) = (chunks[0], chunks[1]);
+
+        let widths = &[
+            tasks_width.constraint(),
+            running_width.constraint(),
+            idle_width.constraint(),
+            done_width.constraint(),
+            layout::Constraint::Length(DUR_LEN as u16),
+            layout::Constraint::Length(DUR_LEN as u16),
+            polls_width.constraint(),
+            layout::Constraint::Length(DUR_LEN as u16),
+            layout::Constraint::Length(DUR_LEN as u16),
+            layout::Constraint::Length(DUR_LEN as u16),
+            layout::Constraint::Length(Self::WIDTHS[10] as u16),
+            kind_width.constraint(),
+            location_width.constraint(),
+        ];
+
+        let table = table
+            .header(header)
+            .block(block)
+            .widths(widths)
+            .highlight_symbol(view::TABLE_HIGHLIGHT_SYMBOL)
+            .highlight_style(Style::default().add_modifier(style::Modifier::BOLD));
+
+        frame.render_stateful_widget(table, groups_area, &mut table_list_state.table_state);
+        frame.render_widget(controls.into_widget(), controls_area);
+
+        table_list_state
+            .sorted_items
+            .retain(|g| g.upgrade().is_some());
+    }
+}
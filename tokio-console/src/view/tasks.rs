@@ -1,36 +1,50 @@
 use crate::{
+    i18n::Msg,
+    query::Filter,
     state::{
-        tasks::{SortBy, Task, TaskState},
+        task_groups::TaskGroup,
+        tasks::{Activity, SortBy, Task, TaskState},
         State,
     },
     view::{
         self, bold,
-        controls::Controls,
+        controls::{ControlDisplay, Controls, KeyDisplay},
         table::{view_controls, TableList, TableListState},
-        DUR_LEN, DUR_TABLE_PRECISION,
+        DUR_LEN, DUR_TABLE_PRECISION, TIMESTAMP_LEN,
     },
 };
 use ratatui::{
     layout,
     style::{self, Color, Style},
     text::{Line, Span, Text},
-    widgets::{self, Cell, ListItem, Row, Table},
+    widgets::{self, Cell, ListItem, Row, Sparkline, Table},
 };
+use std::{cell::RefCell, rc::Rc};
 
 #[derive(Debug, Default)]
 pub(crate) struct TasksTable {}
 
-impl TableList<12> for TasksTable {
+/// Which tasks the tasks table lists.
+#[derive(Default)]
+pub(crate) struct TasksContext {
+    /// The group to list the tasks of, or `None` to list all tasks.
+    pub(crate) group: Option<Rc<RefCell<TaskGroup>>>,
+    /// If set, only the tasks the filter selects are listed.
+    pub(crate) filter: Option<Rc<Filter>>,
+}
+
+impl TableList<21> for TasksTable {
     type Row = Task;
     type Sort = SortBy;
-    type Context = ();
+    type Context = TasksContext;
 
-    const HEADER: &'static [&'static str; 12] = &[
-        "Warn", "ID", "State", "Name", "Total", "Busy", "Sched", "Idle", "Polls", "Kind",
-        "Location", "Fields",
+    const HEADER: &'static [&'static str; 21] = &[
+        "Warn", "ID", "State", "Name", "Total", "Busy", "Busy%", "Share", "Sched", "Idle", "Polls",
+        "Max", "Pend%", "Wk/Poll", "Wakers", "Mem", "Created", "Woken", "Kind", "Location",
+        "Fields",
     ];
 
-    const WIDTHS: &'static [usize; 12] = &[
+    const WIDTHS: &'static [usize; 21] = &[
         Self::HEADER[0].len() + 1,
         Self::HEADER[1].len() + 1,
         Self::HEADER[2].len() + 1,
@@ -43,15 +57,24 @@ impl TableList<12> for TasksTable {
         Self::HEADER[9].len() + 1,
         Self::HEADER[10].len() + 1,
         Self::HEADER[11].len() + 1,
+        Self::HEADER[12].len() + 1,
+        Self::HEADER[13].len() + 1,
+        Self::HEADER[14].len() + 1,
+        MEM_LEN + 1,
+        TIMESTAMP_LEN + 1,
+        TIMESTAMP_LEN + 1,
+        Self::HEADER[18].len() + 1,
+        Self::HEADER[19].len() + 1,
+        Self::HEADER[20].len() + 1,
     ];
 
     fn render(
-        table_list_state: &mut TableListState<Self, 12>,
+        table_list_state: &mut TableListState<Self, 21>,
         styles: &view::Styles,
         frame: &mut ratatui::terminal::Frame,
         area: layout::Rect,
         state: &mut State,
-        _: Self::Context,
+        TasksContext { group, filter }: Self::Context,
     ) {
         let state_len: u16 = Self::WIDTHS[2] as u16;
         let now = if let Some(now) = state.last_updated_at() {
@@ -61,9 +84,32 @@ impl TableList<12> for TasksTable {
             return;
         };
 
-        table_list_state
-            .sorted_items
-            .extend(state.tasks_state_mut().take_new_tasks());
+        match (&group, filter) {
+            (Some(group), _) => {
+                // Groups only track the tasks which are still stored, so
+                // refresh the list from the group rather than taking new tasks.
+                table_list_state.sorted_items.clear();
+                table_list_state
+                    .sorted_items
+                    .extend(group.borrow().tasks().iter().cloned());
+            }
+            (None, Some(filter)) => {
+                // A task the filter doesn't select now may be selected later,
+                // so the list is rebuilt from every task on each frame.
+                state.tasks_state_mut().take_new_tasks().for_each(drop);
+                table_list_state.sorted_items.clear();
+                table_list_state.sorted_items.extend(
+                    state
+                        .tasks_state()
+                        .tasks()
+                        .filter(|task| filter.matches(&task.borrow(), now))
+                        .map(Rc::downgrade),
+                );
+            }
+            (None, None) => table_list_state
+                .sorted_items
+                .extend(state.tasks_state_mut().take_new_tasks()),
+        }
 
         table_list_state
             .sort_by
@@ -72,14 +118,32 @@ impl TableList<12> for TasksTable {
         let dur_cell = |dur: std::time::Duration| -> Cell<'static> {
             Cell::from(styles.time_units(dur, DUR_TABLE_PRECISION, Some(DUR_LEN)))
         };
+        let percent_cell = |ratio: f64| -> Cell<'static> {
+            Cell::from(format!(
+                "{:>width$.1}%",
+                ratio * 100.0,
+                width = PERCENT_LEN - 1
+            ))
+        };
+
+        // More wakers having been dropped than were cloned means that the
+        // instrumentation is inconsistent, so make that stand out.
+        let wakers_cell = |count: i64| -> Cell<'static> {
+            let cell = Cell::from(format!("{count:>WAKERS_LEN$}"));
+            if count < 0 {
+                cell.style(styles.fg(Color::Red))
+            } else {
+                cell
+            }
+        };
 
         // Start out wide enough to display the column headers...
         let mut warn_width = view::Width::new(Self::WIDTHS[0] as u16);
         let mut id_width = view::Width::new(Self::WIDTHS[1] as u16);
         let mut name_width = view::Width::new(Self::WIDTHS[3] as u16);
-        let mut polls_width = view::Width::new(Self::WIDTHS[7] as u16);
-        let mut kind_width = view::Width::new(Self::WIDTHS[8] as u16);
-        let mut location_width = view::Width::new(Self::WIDTHS[9] as u16);
+        let mut polls_width = view::Width::new(Self::WIDTHS[10] as u16);
+        let mut kind_width = view::Width::new(Self::WIDTHS[18] as u16);
+        let mut location_width = view::Width::new(Self::WIDTHS[19] as u16);
 
         let mut num_idle = 0;
         let mut num_running = 0;
@@ -131,9 +195,28 @@ impl TableList<12> for TasksTable {
                         Cell::from(name_width.update_str(task.name().unwrap_or("")).to_string()),
                         dur_cell(task.total(now)),
                         dur_cell(task.busy(now)),
+                        percent_cell(task.busy_ratio(now)),
+                        percent_cell(task.executor_share()),
                         dur_cell(task.scheduled(now)),
                         dur_cell(task.idle(now)),
                         Cell::from(polls_width.update_str(task.total_polls().to_string())),
+                        task.longest_poll(now)
+                            .map(|(longest, _)| dur_cell(longest))
+                            .unwrap_or_default(),
+                        percent_cell(task.pending_ratio()),
+                        Cell::from(format!(
+                            "{:>width$.1}",
+                            task.wakes_per_poll(),
+                            width = WAKES_PER_POLL_LEN
+                        )),
+                        wakers_cell(task.waker_count()),
+                        task.allocated_bytes()
+                            .map(|bytes| Cell::from(format!("{:>MEM_LEN$}", format_bytes(bytes))))
+                            .unwrap_or_default(),
+                        Cell::from(styles.timestamp_cell(task.created_at(), now)),
+                        task.last_wake()
+                            .map(|woken| Cell::from(styles.timestamp_cell(woken, now)))
+                            .unwrap_or_default(),
                         Cell::from(kind_width.update_str(task.kind()).to_owned()),
                         Cell::from(location_width.update_str(task.location()).to_owned()),
                         Cell::from(Line::from(
@@ -178,12 +261,22 @@ impl TableList<12> for TasksTable {
             Table::default().rows(rows.rev())
         };
 
+        let title = match group {
+            Some(ref group) => styles.fill(
+                Msg::GroupTasksTitle,
+                &[
+                    ("location", &group.borrow().location()),
+                    ("count", &table_list_state.len()),
+                ],
+            ),
+            None => styles.fill(Msg::TasksTitle, &[("count", &table_list_state.len())]),
+        };
         let block = styles.border_block().title(vec![
-            bold(format!("Tasks ({}) ", table_list_state.len())),
+            bold(title),
             TaskState::Running.render(styles),
-            Span::from(format!(" Running ({}) ", num_running)),
+            Span::from(styles.fill(Msg::RunningCount, &[("count", &num_running)])),
             TaskState::Idle.render(styles),
-            Span::from(format!(" Idle ({})", num_idle)),
+            Span::from(styles.fill(Msg::IdleCount, &[("count", &num_idle)])),
         ]);
 
         /* TODO: use this to adjust the max size of name and kind columns...
@@ -197,14 +290,42 @@ impl TableList<12> for TasksTable {
             + POLLS_LEN as u16
             + kind_width.chars();
         */
+        let quotas = state
+            .tasks_state()
+            .quotas()
+            .exceeded()
+            .map(|(quota, running)| {
+                let summary = match (&quota.target, &quota.location) {
+                    (Some(target), _) => styles.fill(
+                        Msg::TargetQuotaSummary,
+                        &[("target", target), ("max", &quota.max_tasks)],
+                    ),
+                    (None, location) => styles.fill(
+                        Msg::LocationQuotaSummary,
+                        &[
+                            ("location", &location.as_deref().unwrap_or_default()),
+                            ("max", &quota.max_tasks),
+                        ],
+                    ),
+                };
+                (summary, running)
+            });
+        let waker_leaks = state.tasks_state().waker_leaks().leaked(now);
+        let waker_leaks = (waker_leaks > 0)
+            .then(|| (styles.text(Msg::WakerLeaksSummary).to_owned(), waker_leaks));
+        let task_leak = state.tasks_state().task_leak(styles);
         let warnings = state
             .tasks_state()
-            .warnings()
-            .map(|warning| {
+            .unacknowledged_warnings()
+            .map(|(warning, count)| (warning.summary(styles.lang()), count))
+            .chain(quotas)
+            .chain(waker_leaks)
+            .chain(task_leak)
+            .map(|(summary, count)| {
                 ListItem::new(Text::from(Line::from(vec![
                     styles.warning_wide(),
                     // TODO(eliza): it would be nice to handle singular vs plural...
-                    Span::from(format!("{} {}", warning.count(), warning.summary())),
+                    Span::from(format!("{} {}", count, summary)),
                 ])))
             })
             .collect::<Vec<_>>();
@@ -213,33 +334,48 @@ impl TableList<12> for TasksTable {
             .direction(layout::Direction::Vertical)
             .margin(0);
 
-        let controls = Controls::new(view_controls(), &area, styles);
-
-        let (controls_area, tasks_area, warnings_area) = if warnings.is_empty() {
-            let chunks = layout
-                .constraints(
-                    [
-                        layout::Constraint::Length(controls.height()),
-                        layout::Constraint::Max(area.height),
-                    ]
-                    .as_ref(),
-                )
-                .split(area);
-            (chunks[0], chunks[1], None)
+        // Only the list of all tasks can be searched.
+        let controls = match group {
+            Some(_) => Controls::new(view_controls(), &area, styles),
+            None => {
+                let controls = view_controls()
+                    .iter()
+                    .chain(SEARCH_CONTROLS)
+                    .cloned()
+                    .collect::<Vec<_>>();
+                Controls::new(&controls, &area, styles)
+            }
+        };
+
+        // The activity charts cover all tasks, so they're only shown in the
+        // list of all tasks. They change on every update, so they're also
+        // hidden in low-bandwidth mode.
+        let activity_height = if group.is_none()
+            && !styles.low_bandwidth
+            && state.tasks_state().activity().interval().is_some()
+        {
+            ACTIVITY_HEIGHT
         } else {
-            let warnings_height = warnings.len() as u16 + 2;
-            let chunks = layout
-                .constraints(
-                    [
-                        layout::Constraint::Length(controls.height()),
-                        layout::Constraint::Length(warnings_height),
-                        layout::Constraint::Max(area.height),
-                    ]
-                    .as_ref(),
-                )
-                .split(area);
-            (chunks[0], chunks[2], Some(chunks[1]))
+            0
         };
+        let warnings_height = if warnings.is_empty() {
+            0
+        } else {
+            warnings.len() as u16 + 2
+        };
+        let chunks = layout
+            .constraints(
+                [
+                    layout::Constraint::Length(controls.height()),
+                    layout::Constraint::Length(activity_height),
+                    layout::Constraint::Length(warnings_height),
+                    layout::Constraint::Max(area.height),
+                ]
+                .as_ref(),
+            )
+            .split(area);
+        let (controls_area, activity_area, warnings_area, tasks_area) =
+            (chunks[0], chunks[1], chunks[2], chunks[3]);
         // Fill all remaining characters in the frame with the task's fields.
         //
         // Ideally we'd use Min(0), and it would fill the rest of the space. But that is broken
@@ -254,29 +390,60 @@ impl TableList<12> for TasksTable {
             name_width.constraint(),
             layout::Constraint::Length(DUR_LEN as u16),
             layout::Constraint::Length(DUR_LEN as u16),
+            layout::Constraint::Length(PERCENT_LEN as u16),
+            layout::Constraint::Length(PERCENT_LEN as u16),
             layout::Constraint::Length(DUR_LEN as u16),
             layout::Constraint::Length(DUR_LEN as u16),
             polls_width.constraint(),
+            layout::Constraint::Length(DUR_LEN as u16),
+            layout::Constraint::Length(PERCENT_LEN as u16),
+            layout::Constraint::Length(WAKES_PER_POLL_LEN as u16),
+            layout::Constraint::Length(WAKERS_LEN as u16),
+            layout::Constraint::Length(MEM_LEN as u16),
+            layout::Constraint::Length(TIMESTAMP_LEN as u16),
+            layout::Constraint::Length(TIMESTAMP_LEN as u16),
             kind_width.constraint(),
             location_width.constraint(),
             fields_width,
         ];
 
-        let table = table
-            .header(header)
-            .block(block)
-            .widths(widths)
-            .highlight_symbol(view::TABLE_HIGHLIGHT_SYMBOL)
-            .highlight_style(Style::default().add_modifier(style::Modifier::BOLD));
+        if group.is_none()
+            && table_list_state.sorted_items.is_empty()
+            && state.is_without_tokio_unstable()
+        {
+            // Tasks are only instrumented when the remote is built with
+            // `tokio_unstable`, so explain why the list is empty.
+            let cfg = "--cfg tokio_unstable";
+            let help = widgets::Paragraph::new(vec![
+                Line::from(styles.fill_spans(Msg::NoTasksUnstable, &[("cfg", bold(cfg))])),
+                Line::from(styles.fill_spans(
+                    Msg::TasksUnstableRebuild,
+                    &[("rustflags", bold(format!("RUSTFLAGS=\"{cfg}\"")))],
+                )),
+            ])
+            .block(block);
+            frame.render_widget(help, tasks_area);
+        } else {
+            let table = table
+                .header(header)
+                .block(block)
+                .widths(widths)
+                .highlight_symbol(view::TABLE_HIGHLIGHT_SYMBOL)
+                .highlight_style(Style::default().add_modifier(style::Modifier::BOLD));
 
-        frame.render_stateful_widget(table, tasks_area, &mut table_list_state.table_state);
+            frame.render_stateful_widget(table, tasks_area, &mut table_list_state.table_state);
+        }
         frame.render_widget(controls.into_widget(), controls_area);
 
-        if let Some(area) = warnings_area {
+        if activity_height > 0 {
+            render_activity(styles, frame, activity_area, state.tasks_state().activity());
+        }
+
+        if warnings_height > 0 {
             let block = styles
                 .border_block()
-                .title(Line::from(vec![bold("Warnings")]));
-            frame.render_widget(widgets::List::new(warnings).block(block), area);
+                .title(Line::from(vec![bold(styles.text(Msg::Warnings))]));
+            frame.render_widget(widgets::List::new(warnings).block(block), warnings_area);
         }
 
         table_list_state
@@ -284,3 +451,102 @@ impl TableList<12> for TasksTable {
             .retain(|t| t.upgrade().is_some());
     }
 }
+
+/// The width of the busy ratio, executor share and pending ratio columns,
+/// which are formatted like `100.0%`.
+const PERCENT_LEN: usize = 6;
+
+/// The width of the wakes per poll column, which is formatted like
+/// `12345.6`.
+const WAKES_PER_POLL_LEN: usize = 7;
+
+/// The width of the live wakers column, which is as wide as its header.
+const WAKERS_LEN: usize = 6;
+
+/// The width of the memory column, which is formatted like `1023KiB`.
+const MEM_LEN: usize = 7;
+
+/// Formats a number of bytes with a binary unit, like `512B`, `1.5MiB` or
+/// `40KiB`. Only values below 10 have a fractional part, so that the column
+/// stays narrow.
+fn format_bytes(bytes: u64) -> String {
+    const UNITS: &[&str] = &["KiB", "MiB", "GiB", "TiB"];
+    if bytes < 1024 {
+        return format!("{bytes}B");
+    }
+    let mut value = bytes as f64 / 1024.0;
+    let mut units = UNITS.iter();
+    let mut unit = units.next().unwrap();
+    for next in units {
+        if value < 1024.0 {
+            break;
+        }
+        value /= 1024.0;
+        unit = next;
+    }
+    if value < 10.0 {
+        format!("{value:.1}{unit}")
+    } else {
+        format!("{value:.0}{unit}")
+    }
+}
+
+/// The controls for searching the list of all tasks.
+const SEARCH_CONTROLS: &[ControlDisplay] = &[ControlDisplay {
+    action: Msg::Search,
+    keys: &[KeyDisplay {
+        base: "/",
+        utf8: None,
+    }],
+}];
+
+/// The height of the task activity charts, including borders.
+const ACTIVITY_HEIGHT: u16 = 5;
+
+/// Renders sparklines of the task activity recorded by the remote.
+fn render_activity(
+    styles: &view::Styles,
+    frame: &mut ratatui::terminal::Frame,
+    area: layout::Rect,
+    activity: &Activity,
+) {
+    let interval = activity.interval().unwrap_or_default();
+    let chunks = layout::Layout::default()
+        .direction(layout::Direction::Horizontal)
+        .constraints(
+            [
+                layout::Constraint::Ratio(1, 3),
+                layout::Constraint::Ratio(1, 3),
+                layout::Constraint::Ratio(1, 3),
+            ]
+            .as_ref(),
+        )
+        .split(area);
+    let series = [
+        (Msg::Spawned, activity.spawned(), Color::Green),
+        (Msg::Completed, activity.completed(), Color::Blue),
+        (Msg::Wakes, activity.wakes(), Color::Yellow),
+    ];
+    for ((title, samples, color), area) in series.into_iter().zip(chunks.iter()) {
+        // Only show as many of the most recent samples as will fit.
+        let width = area.width.saturating_sub(2) as usize;
+        let data = samples
+            .iter()
+            .skip(samples.len().saturating_sub(width))
+            .copied()
+            .collect::<Vec<_>>();
+        let title = styles.fill(
+            Msg::ActivityTitle,
+            &[
+                ("series", &styles.text(title)),
+                ("count", &data.last().copied().unwrap_or_default()),
+                ("interval", &format!("{interval:?}")),
+            ],
+        );
+        let sparkline = Sparkline::default()
+            .block(styles.border_block().title(title))
+            .data(&data)
+            .style(styles.fg(color));
+        frame.render_widget(sparkline, *area);
+    }
+}
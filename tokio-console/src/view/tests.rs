@@ -0,0 +1,489 @@
+//! Snapshot tests for the console's views.
+//!
+//! Each test replays a recording from `tests/recordings` into the console's
+//! state, rendering the view after each update as the console would, and
+//! compares the last frame against a snapshot in `src/view/snapshots`.
+//!
+//! If a change to a view is intended, review and accept the new snapshots
+//! with `cargo insta review`, or by running the tests with
+//! `INSTA_UPDATE=always`.
+
+use super::{plugin::ViewPlugin, Styles, View};
+use crate::{
+    config::{Preset, SnapshotFormat, TimeFormat, ViewOptions},
+    input::{Event, KeyCode, KeyEvent, KeyModifiers},
+    snapshot::write_buffer,
+    state::{recordings, tasks::DetailsEnded, State},
+};
+use console_api as proto;
+use ratatui::{backend::TestBackend, layout, widgets::Paragraph, Terminal};
+
+const WIDTH: u16 = 120;
+const HEIGHT: u16 = 40;
+
+/// Renders the views of a console, from a recording.
+struct Console {
+    view: View,
+    state: State,
+    terminal: Terminal<TestBackend>,
+}
+
+impl Console {
+    fn new(options: ViewOptions) -> Self {
+        Self::with_width(options, WIDTH)
+    }
+
+    fn with_presets(mut self, presets: &[Preset]) -> Self {
+        self.view = self.view.with_presets(presets);
+        self
+    }
+
+    fn with_plugins(mut self, plugins: Vec<Box<dyn ViewPlugin>>) -> Self {
+        self.view = self.view.with_plugins(plugins);
+        self
+    }
+
+    fn with_width(options: ViewOptions, width: u16) -> Self {
+        Self {
+            view: View::new(Styles::from_config(options)),
+            state: recordings::state(),
+            terminal: Terminal::new(TestBackend::new(width, HEIGHT)).unwrap(),
+        }
+    }
+
+    /// Applies each update in a recording, rendering the current view after
+    /// each one.
+    fn replay(&mut self, recording: &str) {
+        self.replay_updates(recordings::load(recording));
+    }
+
+    /// Applies each of `updates`, rendering the current view after each one.
+    fn replay_updates(&mut self, updates: impl IntoIterator<Item = proto::instrument::Update>) {
+        for update in updates {
+            self.state
+                .update(&self.view.styles, self.view.current_view(), update);
+            self.view.update_plugins(&self.state);
+            self.draw();
+        }
+    }
+
+    fn press(&mut self, key: char) {
+        self.press_key(KeyCode::Char(key));
+    }
+
+    fn press_key(&mut self, code: KeyCode) {
+        let event = Event::Key(KeyEvent::new(code, KeyModifiers::NONE));
+        self.view.update_input(event, &mut self.state);
+    }
+
+    fn open_task(&mut self, name: &str) {
+        let task = self
+            .state
+            .tasks_state()
+            .find(name)
+            .unwrap_or_else(|| panic!("no task named {name}"));
+        self.view.open_task(task, &self.state);
+    }
+
+    fn draw(&mut self) {
+        let Self {
+            view,
+            state,
+            terminal,
+        } = self;
+        terminal
+            .draw(|frame| view.render(frame, frame.size(), state))
+            .unwrap();
+    }
+
+    /// Sets the details of the task named `name` to poll times of 1ms to
+    /// 80ms, with more of the longer polls.
+    fn record_poll_times(&mut self, name: &str) {
+        use hdrhistogram::serialization::Serializer;
+
+        let task = self.state.tasks_state().find(name).unwrap();
+        let span_id = task.borrow().span_id();
+        let mut histogram = hdrhistogram::Histogram::<u64>::new(2).unwrap();
+        for millis in 1..=80 {
+            histogram.record_n(millis * 1_000_000, millis).unwrap();
+        }
+        let mut raw_histogram = Vec::new();
+        hdrhistogram::serialization::V2Serializer::new()
+            .serialize(&histogram, &mut raw_histogram)
+            .unwrap();
+        self.state.update_task_details(proto::tasks::TaskDetails {
+            task_id: Some(span_id.into()),
+            poll_times_histogram: Some(proto::tasks::task_details::PollTimesHistogram::Histogram(
+                proto::tasks::DurationHistogram {
+                    raw_histogram,
+                    max_value: 80_000_000,
+                    high_outliers: 2,
+                    highest_outlier: Some(120_000_000),
+                },
+            )),
+            ..Default::default()
+        });
+    }
+
+    /// Draws the current view, and returns it as plain text.
+    fn frame(&mut self) -> String {
+        self.draw();
+        let mut out = Vec::new();
+        write_buffer(
+            &mut out,
+            self.terminal.backend().buffer(),
+            SnapshotFormat::Plain,
+        )
+        .unwrap();
+        String::from_utf8(out).unwrap()
+    }
+}
+
+#[test]
+fn tasks_list() {
+    let mut console = Console::new(ViewOptions::default());
+    console.replay("tasks");
+    insta::assert_snapshot!(console.frame());
+}
+
+#[test]
+fn tasks_list_search() {
+    // Selecting a task makes room for the highlight symbol, so the terminal
+    // must be wide enough for every column to keep its width.
+    let mut console = Console::with_width(ViewOptions::default(), 160);
+    console.replay("tasks");
+    "/er".chars().for_each(|key| console.press(key));
+    insta::assert_snapshot!(console.frame());
+    console.press_key(KeyCode::Enter);
+    console.press('n');
+    insta::assert_snapshot!("tasks_list_search_next", console.frame());
+}
+
+#[test]
+fn tasks_list_search_filter() {
+    let mut console = Console::with_width(ViewOptions::default(), 160);
+    console.replay("tasks");
+    "/polls >= 8 && name =~ \"^l\""
+        .chars()
+        .for_each(|key| console.press(key));
+    insta::assert_snapshot!(console.frame());
+}
+
+#[test]
+fn tasks_list_search_non_ascii() {
+    // The search is parsed as a filter after every key press, including ones
+    // which end partway through a multi-byte identifier or string.
+    let mut console = Console::with_width(ViewOptions::default(), 160);
+    console.replay("tasks");
+    "/café名 == \"ü\""
+        .chars()
+        .for_each(|key| console.press(key));
+    assert!(console.frame().contains("/café名 == \"ü\""));
+    (0..5).for_each(|_| console.press_key(KeyCode::Backspace));
+    assert!(console.frame().contains("/café名 "));
+}
+
+#[test]
+fn tasks_list_preset() {
+    let mut console = Console::new(ViewOptions::default()).with_presets(&[Preset {
+        name: "busy".to_owned(),
+        key: '1',
+        filter: Some("busy > 500ms".to_owned()),
+        sort: Some("Polls".to_owned()),
+        descending: Some(false),
+    }]);
+    console.replay("tasks");
+    console.press('1');
+    insta::assert_snapshot!(console.frame());
+    // Pressing the key again lists every task, sorted as the preset left it.
+    console.press('1');
+    insta::assert_snapshot!("tasks_list_preset_removed", console.frame());
+}
+
+#[test]
+fn tasks_list_ascii() {
+    let mut console = Console::new(ViewOptions::default().ascii_only());
+    console.replay("tasks");
+    insta::assert_snapshot!(console.frame());
+}
+
+#[test]
+fn tasks_list_utc() {
+    let mut console = Console::new(ViewOptions::default().with_time_format(TimeFormat::Utc));
+    console.replay("tasks");
+    insta::assert_snapshot!(console.frame());
+}
+
+#[test]
+fn tasks_list_without_tokio_unstable() {
+    let mut console = Console::new(ViewOptions::default());
+    console.state.update_state(proto::instrument::State {
+        tokio_unstable: Some(false),
+        ..Default::default()
+    });
+    let update = proto::instrument::Update {
+        now: Some(std::time::SystemTime::UNIX_EPOCH.into()),
+        ..Default::default()
+    };
+    console
+        .state
+        .update(&console.view.styles, console.view.current_view(), update);
+    insta::assert_snapshot!(console.frame());
+}
+
+#[test]
+fn task_details() {
+    let mut console = Console::new(ViewOptions::default());
+    console.replay("tasks");
+    console.open_task("spinner");
+    insta::assert_snapshot!(console.frame());
+}
+
+#[test]
+fn task_details_ascii() {
+    let mut console = Console::new(ViewOptions::default().ascii_only());
+    console.replay("tasks");
+    console.open_task("spinner");
+    insta::assert_snapshot!(console.frame());
+}
+
+#[test]
+fn task_details_ended() {
+    let mut console = Console::new(ViewOptions::default());
+    console.replay("tasks");
+    console.open_task("spinner");
+    let task = console.state.tasks_state().find("spinner").unwrap();
+    let span_id = task.borrow().span_id();
+    console
+        .state
+        .end_task_details(span_id, DetailsEnded::AgedOut);
+    insta::assert_snapshot!(console.frame());
+}
+
+#[test]
+fn task_details_extensions() {
+    let mut console = Console::new(ViewOptions::default());
+    console.replay("tasks");
+    let task = console.state.tasks_state().find("spinner").unwrap();
+    let span_id = task.borrow().span_id();
+    let extension = |type_url: &str, value: &[u8]| prost_types::Any {
+        type_url: type_url.to_owned(),
+        value: value.to_vec(),
+    };
+    let extensions = proto::tasks::Extensions {
+        extensions: vec![
+            extension("type.example.com/app.Peer", b"db-1"),
+            extension("type.example.com/app.Load", &[8, 42]),
+        ],
+    };
+    let update = proto::instrument::Update {
+        task_update: Some(proto::tasks::TaskUpdate {
+            extensions_update: [(span_id, extensions)].into(),
+            ..Default::default()
+        }),
+        ..Default::default()
+    };
+    console
+        .state
+        .update(&console.view.styles, console.view.current_view(), update);
+    console.open_task("spinner");
+    insta::assert_snapshot!(console.frame());
+}
+
+#[test]
+fn task_details_location_link() {
+    let options = ViewOptions::default().with_location_links("file:///app/{file}#L{line}");
+    let mut console = Console::new(options);
+    let mut updates = recordings::load("tasks");
+    let new_tasks = &mut updates[0].task_update.as_mut().unwrap().new_tasks;
+    new_tasks[2].location = Some(proto::Location {
+        file: Some("src/spin.rs".to_owned()),
+        line: Some(7),
+        column: Some(5),
+        ..Default::default()
+    });
+    console.replay_updates(updates);
+    console.open_task("spinner");
+    console.draw();
+
+    let links = console.view.hyperlinks();
+    assert_eq!(links.len(), 1);
+    assert_eq!(links[0].url, "file:///app/src/spin.rs#L7");
+    let buffer = console.terminal.backend().buffer();
+    let area = links[0].area;
+    let text = (area.left()..area.right())
+        .map(|x| buffer.get(x, area.y).symbol())
+        .collect::<String>();
+    assert_eq!(text, "src/spin.rs:7:5");
+
+    // tasks without a file to link to have no links.
+    console.press_key(KeyCode::Esc);
+    console.open_task("listener");
+    console.draw();
+    assert!(console.view.hyperlinks().is_empty());
+}
+
+#[test]
+fn task_details_awaiting() {
+    let mut console = Console::new(ViewOptions::default());
+    console.replay("awaiting");
+    console.open_task("worker");
+    insta::assert_snapshot!(console.frame());
+}
+
+#[test]
+fn task_details_utc() {
+    let mut console = Console::new(ViewOptions::default());
+    console.replay("tasks");
+    console.open_task("spinner");
+    // From relative times, to local times, to UTC.
+    console.press('z');
+    console.press('z');
+    insta::assert_snapshot!(console.frame());
+}
+
+#[test]
+fn task_details_histogram_modes() {
+    let mut console = Console::new(ViewOptions::default());
+    console.replay("tasks");
+    console.record_poll_times("spinner");
+    console.open_task("spinner");
+    let chart = console.frame();
+
+    console.press('m');
+    insta::assert_snapshot!("task_details_percentile_table", console.frame());
+    console.press('m');
+    insta::assert_snapshot!("task_details_bucket_counts", console.frame());
+    console.press('m');
+    assert_eq!(console.frame(), chart);
+}
+
+#[test]
+fn task_details_export() {
+    let path = std::env::temp_dir().join(format!("tokio-console-{}.hgrm", std::process::id()));
+    let mut console = Console::new(ViewOptions::default());
+    console.replay("tasks");
+    console.open_task("spinner");
+    console.press(':');
+    "export ".chars().for_each(|c| console.press(c));
+    console.press_key(KeyCode::Enter);
+    assert!(console.frame().contains("expected a file to export to"));
+
+    console.record_poll_times("spinner");
+    path.to_str()
+        .unwrap()
+        .chars()
+        .for_each(|c| console.press(c));
+    console.press_key(KeyCode::Enter);
+    assert!(!console.view.is_prompting());
+    let hgrm = std::fs::read_to_string(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    assert!(hgrm.contains("#[Max     =       80.216, Total count    =         3240]"));
+}
+
+#[test]
+fn completed_tasks_list() {
+    let mut console = Console::new(ViewOptions::default());
+    console.replay("tasks");
+    console.press('c');
+    insta::assert_snapshot!(console.frame());
+}
+
+#[test]
+fn warnings_list() {
+    let mut console = Console::new(ViewOptions::default());
+    console.replay("tasks");
+    console.press('W');
+    insta::assert_snapshot!(console.frame());
+}
+
+#[test]
+fn warnings_list_acknowledged() {
+    let mut console = Console::new(ViewOptions::default());
+    console.replay("tasks");
+    console.press('W');
+    console.press('j');
+    console.press('a');
+    insta::assert_snapshot!(console.frame());
+    console.press('v');
+    console.press('j');
+    console.press('l');
+    insta::assert_snapshot!("warnings_list_show_acknowledged", console.frame());
+}
+
+#[test]
+fn wake_graph_list() {
+    let mut console = Console::new(ViewOptions::default());
+    console.press('e');
+    console.replay("tasks");
+    insta::assert_snapshot!(console.frame());
+}
+
+#[test]
+fn workers_list() {
+    let mut console = Console::new(ViewOptions::default());
+    console.press('R');
+    console.replay("workers");
+    insta::assert_snapshot!(console.frame());
+}
+
+#[test]
+fn spans_list() {
+    let mut console = Console::new(ViewOptions::default());
+    console.press('u');
+    console.replay("spans");
+    insta::assert_snapshot!(console.frame());
+}
+
+#[test]
+fn targets_list() {
+    let mut console = Console::new(ViewOptions::default());
+    console.press('T');
+    console.replay("targets");
+    insta::assert_snapshot!(console.frame());
+}
+
+/// A custom view which counts the tasks it has been told about.
+#[derive(Default)]
+struct TaskCount {
+    tasks: usize,
+    updates: usize,
+}
+
+impl ViewPlugin for TaskCount {
+    fn name(&self) -> &str {
+        "task count"
+    }
+
+    fn key(&self) -> char {
+        '0'
+    }
+
+    fn update(&mut self, state: &State) {
+        self.tasks = state.tasks_state().tasks().count();
+        self.updates += 1;
+    }
+
+    fn render(
+        &mut self,
+        _styles: &Styles,
+        frame: &mut ratatui::terminal::Frame,
+        area: layout::Rect,
+        _state: &State,
+    ) {
+        let text = format!("{} tasks after {} updates", self.tasks, self.updates);
+        frame.render_widget(Paragraph::new(text), area);
+    }
+}
+
+#[test]
+fn plugin_view() {
+    let mut console =
+        Console::new(ViewOptions::default()).with_plugins(vec![Box::<TaskCount>::default()]);
+    console.replay("tasks");
+    console.press('0');
+    insta::assert_snapshot!(console.frame());
+    // Escape returns to the tasks list.
+    console.press_key(KeyCode::Esc);
+    assert!(console.frame().contains("Tasks ("));
+}
@@ -0,0 +1,198 @@
+use crate::{
+    i18n::Msg,
+    state::{
+        timer_stats::{SortBy, TimerCallsite},
+        State,
+    },
+    view::{
+        self, bold,
+        controls::Controls,
+        durations::Durations,
+        table::{view_controls, TableList, TableListState},
+        DUR_LEN, DUR_TABLE_PRECISION,
+    },
+};
+use ratatui::{
+    layout,
+    style::{self, Color, Style},
+    text::{Line, Span},
+    widgets::{Cell, Paragraph, Row, Table},
+};
+use std::time::Duration;
+
+/// Timers which fire later than this after their deadline are highlighted.
+const LAG_WARNING_THRESHOLD: Duration = Duration::from_millis(10);
+
+#[derive(Debug, Default)]
+pub(crate) struct TimerLagTable {}
+
+impl TableList<5> for TimerLagTable {
+    type Row = TimerCallsite;
+    type Sort = SortBy;
+    type Context = ();
+
+    const HEADER: &'static [&'static str; 5] = &["Fires", "p50", "p99", "Max", "Location"];
+
+    const WIDTHS: &'static [usize; 5] = &[
+        Self::HEADER[0].len() + 1,
+        DUR_LEN + 1,
+        DUR_LEN + 1,
+        DUR_LEN + 1,
+        Self::HEADER[4].len() + 1,
+    ];
+
+    fn render(
+        table_list_state: &mut TableListState<Self, 5>,
+        styles: &view::Styles,
+        frame: &mut ratatui::terminal::Frame,
+        area: layout::Rect,
+        state: &mut State,
+        _: Self::Context,
+    ) {
+        table_list_state.sorted_items.extend(
+            state
+                .resources_state_mut()
+                .timer_stats_mut()
+                .take_new_callsites(),
+        );
+        table_list_state
+            .sort_by
+            .sort(&mut table_list_state.sorted_items);
+
+        let dur_cell = |dur: Duration| -> Cell<'static> {
+            let cell = Cell::from(styles.time_units(dur, DUR_TABLE_PRECISION, Some(DUR_LEN)));
+            if dur >= LAG_WARNING_THRESHOLD {
+                cell.style(styles.fg(Color::Red))
+            } else {
+                cell
+            }
+        };
+
+        let mut fires_width = view::Width::new(Self::WIDTHS[0] as u16);
+        let mut location_width = view::Width::new(Self::WIDTHS[4] as u16);
+        let mut num_lagging = 0;
+
+        let rows = {
+            let fires_width = &mut fires_width;
+            let location_width = &mut location_width;
+            let num_lagging = &mut num_lagging;
+
+            table_list_state
+                .sorted_items
+                .iter()
+                .filter_map(move |callsite| {
+                    let callsite = callsite.upgrade()?;
+                    let callsite = callsite.borrow();
+                    let p99 = callsite.percentile(99.0);
+                    if p99 >= LAG_WARNING_THRESHOLD {
+                        *num_lagging += 1;
+                    }
+
+                    Some(Row::new(vec![
+                        Cell::from(fires_width.update_str(callsite.fires().to_string())),
+                        dur_cell(callsite.percentile(50.0)),
+                        dur_cell(p99),
+                        dur_cell(callsite.max()),
+                        Cell::from(location_width.update_str(callsite.location()).to_owned()),
+                    ]))
+                })
+        };
+
+        let header_style = if styles.color(Color::Cyan).is_some() {
+            Style::default()
+        } else {
+            Style::default().add_modifier(style::Modifier::REVERSED)
+        };
+        let header_style = header_style.add_modifier(style::Modifier::BOLD);
+
+        let header = Row::new(Self::HEADER.iter().enumerate().map(|(idx, &value)| {
+            if idx == table_list_state.selected_column {
+                if table_list_state.sort_descending {
+                    Cell::from(styles.ascending(value))
+                } else {
+                    Cell::from(styles.descending(value))
+                }
+            } else {
+                Cell::from(value)
+            }
+        }))
+        .height(1)
+        .style(header_style);
+
+        let table = if table_list_state.sort_descending {
+            Table::default().rows(rows)
+        } else {
+            Table::default().rows(rows.rev())
+        };
+
+        let block = styles.border_block().title(vec![
+            bold(styles.fill(Msg::TimerLagTitle, &[("count", &table_list_state.len())])),
+            styles.warning_narrow(),
+            Span::from(styles.fill(Msg::LaggingCount, &[("count", &num_lagging)])),
+        ]);
+
+        let selected = table_list_state.selected_item();
+        let selected = selected.as_ref().map(|callsite| callsite.borrow());
+
+        let controls = Controls::new(view_controls(), &area, styles);
+        let chunks = layout::Layout::default()
+            .direction(layout::Direction::Vertical)
+            .margin(0)
+            .constraints(
+                [
+                    layout::Constraint::Length(controls.height()),
+                    layout::Constraint::Min(0),
+                    // overruns of the selected callsite
+                    layout::Constraint::Length(if selected.is_some() { 9 } else { 0 }),
+                ]
+                .as_ref(),
+            )
+            .split(area);
+        let (controls_area, table_area, overruns_area) = (chunks[0], chunks[1], chunks[2]);
+        frame.render_widget(controls.into_widget(), controls_area);
+
+        if table_list_state.sorted_items.is_empty() {
+            // Timers are only instrumented when the remote is built with
+            // `tokio_unstable`, so explain why the table might be empty.
+            let help = Paragraph::new(vec![
+                Line::from(styles.text(Msg::NoTimers)),
+                Line::from(styles.fill_spans(
+                    Msg::TimerLagUnstable,
+                    &[("cfg", bold("--cfg tokio_unstable"))],
+                )),
+            ])
+            .block(
+                styles
+                    .border_block()
+                    .title(vec![bold(styles.text(Msg::TimerLag))]),
+            );
+            frame.render_widget(help, table_area);
+            return;
+        }
+
+        let widths = &[
+            fires_width.constraint(),
+            layout::Constraint::Length(Self::WIDTHS[1] as u16),
+            layout::Constraint::Length(Self::WIDTHS[2] as u16),
+            layout::Constraint::Length(Self::WIDTHS[3] as u16),
+            location_width.constraint(),
+        ];
+
+        let table = table
+            .header(header)
+            .block(block)
+            .widths(widths)
+            .highlight_symbol(view::TABLE_HIGHLIGHT_SYMBOL)
+            .highlight_style(Style::default().add_modifier(style::Modifier::BOLD));
+
+        frame.render_stateful_widget(table, table_area, &mut table_list_state.table_state);
+
+        if let Some(callsite) = selected {
+            let overruns = Durations::new(styles)
+                .histogram(callsite.overruns())
+                .percentiles_title(styles.text(Msg::OverrunPercentiles))
+                .histogram_title(styles.text(Msg::OverrunHistogram));
+            frame.render_widget(overruns, overruns_area);
+        }
+    }
+}
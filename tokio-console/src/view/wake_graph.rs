@@ -0,0 +1,182 @@
+use crate::{
+    i18n::Msg,
+    state::{
+        tasks::Task,
+        wake_graph::{SortBy, WakeEdge},
+        State,
+    },
+    view::{
+        self, bold,
+        controls::Controls,
+        table::{view_controls, TableList, TableListState},
+    },
+};
+use ratatui::{
+    layout,
+    style::{self, Color, Style},
+    text::{Line, Span},
+    widgets::{Cell, Paragraph, Row, Table},
+};
+use std::{cell::RefCell, rc::Rc};
+
+#[derive(Debug, Default)]
+pub(crate) struct WakeGraphTable {}
+
+impl TableList<5> for WakeGraphTable {
+    type Row = WakeEdge;
+    type Sort = SortBy;
+    type Context = ();
+
+    const HEADER: &'static [&'static str; 5] = &["Wakes", "Recent", "Share", "Waker", "Woken"];
+
+    const WIDTHS: &'static [usize; 5] = &[
+        Self::HEADER[0].len() + 1,
+        Self::HEADER[1].len() + 1,
+        Self::HEADER[2].len() + 1,
+        Self::HEADER[3].len() + 1,
+        Self::HEADER[4].len() + 1,
+    ];
+
+    fn render(
+        table_list_state: &mut TableListState<Self, 5>,
+        styles: &view::Styles,
+        frame: &mut ratatui::terminal::Frame,
+        area: layout::Rect,
+        state: &mut State,
+        _: Self::Context,
+    ) {
+        table_list_state
+            .sorted_items
+            .extend(state.tasks_state_mut().wake_graph_mut().take_new_edges());
+        // Edges are removed from the state along with their tasks.
+        table_list_state
+            .sorted_items
+            .retain(|edge| edge.upgrade().is_some());
+        table_list_state
+            .sort_by
+            .sort(&mut table_list_state.sorted_items);
+
+        // Tasks without a name or Tokio task ID are shown by the console's ID
+        // for them.
+        let task_desc = |task: Option<Rc<RefCell<Task>>>| -> String {
+            let Some(task) = task else {
+                return "-".to_string();
+            };
+            let task = task.borrow();
+            match task.short_desc() {
+                "" => task.id().to_string(),
+                desc => desc.to_owned(),
+            }
+        };
+
+        let mut wakes_width = view::Width::new(Self::WIDTHS[0] as u16);
+        let mut waker_width = view::Width::new(Self::WIDTHS[3] as u16);
+        let mut num_recent = 0;
+
+        let rows = {
+            let wakes_width = &mut wakes_width;
+            let waker_width = &mut waker_width;
+            let num_recent = &mut num_recent;
+
+            table_list_state
+                .sorted_items
+                .iter()
+                .filter_map(move |edge| {
+                    let edge = edge.upgrade()?;
+                    let edge = edge.borrow();
+
+                    let recent_wakes = Cell::from(edge.recent_wakes().to_string());
+                    Some(Row::new(vec![
+                        Cell::from(wakes_width.update_str(edge.wakes().to_string())),
+                        if edge.recent_wakes() > 0 {
+                            *num_recent += 1;
+                            recent_wakes.style(styles.fg(Color::Green))
+                        } else {
+                            recent_wakes
+                        },
+                        Cell::from(format!("{}%", edge.share())),
+                        Cell::from(waker_width.update_str(task_desc(edge.waker()))),
+                        Cell::from(task_desc(edge.woken())),
+                    ]))
+                })
+        };
+
+        let header_style = if styles.color(Color::Cyan).is_some() {
+            Style::default()
+        } else {
+            Style::default().add_modifier(style::Modifier::REVERSED)
+        };
+        let header_style = header_style.add_modifier(style::Modifier::BOLD);
+
+        let header = Row::new(Self::HEADER.iter().enumerate().map(|(idx, &value)| {
+            if idx == table_list_state.selected_column {
+                if table_list_state.sort_descending {
+                    Cell::from(styles.ascending(value))
+                } else {
+                    Cell::from(styles.descending(value))
+                }
+            } else {
+                Cell::from(value)
+            }
+        }))
+        .height(1)
+        .style(header_style);
+
+        let table = if table_list_state.sort_descending {
+            Table::default().rows(rows)
+        } else {
+            Table::default().rows(rows.rev())
+        };
+
+        let block = styles.border_block().title(vec![
+            bold(styles.fill(Msg::WakeGraphTitle, &[("count", &table_list_state.len())])),
+            Span::from(styles.fill(Msg::RecentWakesCount, &[("count", &num_recent)])),
+        ]);
+
+        let controls = Controls::new(view_controls(), &area, styles);
+        let chunks = layout::Layout::default()
+            .direction(layout::Direction::Vertical)
+            .margin(0)
+            .constraints(
+                [
+                    layout::Constraint::Length(controls.height()),
+                    layout::Constraint::Min(0),
+                ]
+                .as_ref(),
+            )
+            .split(area);
+        let (controls_area, table_area) = (chunks[0], chunks[1]);
+        frame.render_widget(controls.into_widget(), controls_area);
+
+        if table_list_state.sorted_items.is_empty() {
+            let help = Paragraph::new(vec![
+                Line::from(styles.text(Msg::NoWakes)),
+                Line::from(styles.text(Msg::WakeGraphHint)),
+            ])
+            .block(
+                styles
+                    .border_block()
+                    .title(vec![bold(styles.text(Msg::WakeGraph))]),
+            );
+            frame.render_widget(help, table_area);
+            return;
+        }
+
+        let widths = &[
+            wakes_width.constraint(),
+            layout::Constraint::Length(Self::WIDTHS[1] as u16),
+            layout::Constraint::Length(Self::WIDTHS[2] as u16),
+            waker_width.constraint(),
+            layout::Constraint::Min(Self::WIDTHS[4] as u16),
+        ];
+
+        let table = table
+            .header(header)
+            .block(block)
+            .widths(widths)
+            .highlight_symbol(view::TABLE_HIGHLIGHT_SYMBOL)
+            .highlight_style(Style::default().add_modifier(style::Modifier::BOLD));
+
+        frame.render_stateful_widget(table, table_area, &mut table_list_state.table_state);
+    }
+}
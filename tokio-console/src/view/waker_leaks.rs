@@ -0,0 +1,193 @@
+use crate::{
+    i18n::Msg,
+    state::{
+        waker_leaks::{SortBy, WakerLeak, WakerLeaks},
+        State,
+    },
+    view::{
+        self, bold,
+        controls::Controls,
+        table::{view_controls, TableList, TableListState},
+        DUR_LEN, DUR_TABLE_PRECISION,
+    },
+};
+use ratatui::{
+    layout,
+    style::{self, Color, Style},
+    text::{Line, Span},
+    widgets::{Cell, Paragraph, Row, Table},
+};
+
+/// Completed tasks whose wakers are still alive, which the remote keeps
+/// reporting after it has stopped retaining the tasks themselves.
+#[derive(Debug, Default)]
+pub(crate) struct WakerLeaksTable {}
+
+impl TableList<6> for WakerLeaksTable {
+    type Row = WakerLeak;
+    type Sort = SortBy;
+    type Context = ();
+
+    const HEADER: &'static [&'static str; 6] =
+        &["Live", "Clones", "Drops", "Completed", "Task", "Location"];
+
+    const WIDTHS: &'static [usize; 6] = &[
+        Self::HEADER[0].len() + 1,
+        Self::HEADER[1].len() + 1,
+        Self::HEADER[2].len() + 1,
+        Self::HEADER[3].len() + 1,
+        Self::HEADER[4].len() + 1,
+        Self::HEADER[5].len() + 1,
+    ];
+
+    fn render(
+        table_list_state: &mut TableListState<Self, 6>,
+        styles: &view::Styles,
+        frame: &mut ratatui::terminal::Frame,
+        area: layout::Rect,
+        state: &mut State,
+        _: Self::Context,
+    ) {
+        let now = if let Some(now) = state.last_updated_at() {
+            now
+        } else {
+            // If we have never gotten an update yet, skip...
+            return;
+        };
+
+        table_list_state
+            .sorted_items
+            .extend(state.tasks_state_mut().waker_leaks_mut().take_new_leaks());
+        // Tasks are removed from the state once all of their wakers have
+        // been dropped.
+        table_list_state
+            .sorted_items
+            .retain(|leak| leak.upgrade().is_some());
+        table_list_state
+            .sort_by
+            .sort(&mut table_list_state.sorted_items);
+
+        let mut live_width = view::Width::new(Self::WIDTHS[0] as u16);
+        let mut clones_width = view::Width::new(Self::WIDTHS[1] as u16);
+        let mut drops_width = view::Width::new(Self::WIDTHS[2] as u16);
+        let mut task_width = view::Width::new(Self::WIDTHS[4] as u16);
+        let mut num_leaked = 0;
+
+        let rows = {
+            let live_width = &mut live_width;
+            let clones_width = &mut clones_width;
+            let drops_width = &mut drops_width;
+            let task_width = &mut task_width;
+            let num_leaked = &mut num_leaked;
+
+            table_list_state
+                .sorted_items
+                .iter()
+                .filter_map(move |leak| {
+                    let leak = leak.upgrade()?;
+                    let leak = leak.borrow();
+
+                    let live = Cell::from(live_width.update_str(leak.live().to_string()));
+                    Some(Row::new(vec![
+                        if leak.is_leaked(now) {
+                            *num_leaked += 1;
+                            live.style(styles.fg(Color::Red))
+                        } else {
+                            live
+                        },
+                        Cell::from(clones_width.update_str(leak.clones().to_string())),
+                        Cell::from(drops_width.update_str(leak.drops().to_string())),
+                        Cell::from(styles.time_units(
+                            leak.completed_for(now),
+                            DUR_TABLE_PRECISION,
+                            Some(DUR_LEN),
+                        )),
+                        Cell::from(task_width.update_str(leak.task_desc())),
+                        Cell::from(leak.location().to_owned()),
+                    ]))
+                })
+        };
+
+        let header_style = if styles.color(Color::Cyan).is_some() {
+            Style::default()
+        } else {
+            Style::default().add_modifier(style::Modifier::REVERSED)
+        };
+        let header_style = header_style.add_modifier(style::Modifier::BOLD);
+
+        let header = Row::new(Self::HEADER.iter().enumerate().map(|(idx, &value)| {
+            if idx == table_list_state.selected_column {
+                if table_list_state.sort_descending {
+                    Cell::from(styles.ascending(value))
+                } else {
+                    Cell::from(styles.descending(value))
+                }
+            } else {
+                Cell::from(value)
+            }
+        }))
+        .height(1)
+        .style(header_style);
+
+        let table = if table_list_state.sort_descending {
+            Table::default().rows(rows)
+        } else {
+            Table::default().rows(rows.rev())
+        };
+
+        let block = styles.border_block().title(vec![
+            bold(styles.fill(Msg::WakerLeaksTitle, &[("count", &table_list_state.len())])),
+            Span::from(styles.fill(Msg::LeakedCount, &[("count", &num_leaked)])),
+        ]);
+
+        let controls = Controls::new(view_controls(), &area, styles);
+        let chunks = layout::Layout::default()
+            .direction(layout::Direction::Vertical)
+            .margin(0)
+            .constraints(
+                [
+                    layout::Constraint::Length(controls.height()),
+                    layout::Constraint::Min(0),
+                ]
+                .as_ref(),
+            )
+            .split(area);
+        let (controls_area, table_area) = (chunks[0], chunks[1]);
+        frame.render_widget(controls.into_widget(), controls_area);
+
+        if table_list_state.sorted_items.is_empty() {
+            let after = styles
+                .time_units(WakerLeaks::LEAKED_AFTER, DUR_TABLE_PRECISION, None)
+                .content;
+            let help = Paragraph::new(vec![
+                Line::from(styles.text(Msg::NoWakerLeaks)),
+                Line::from(styles.fill(Msg::WakerLeaksHint, &[("after", &after)])),
+            ])
+            .block(
+                styles
+                    .border_block()
+                    .title(vec![bold(styles.text(Msg::WakerLeaks))]),
+            );
+            frame.render_widget(help, table_area);
+            return;
+        }
+
+        let widths = &[
+            live_width.constraint(),
+            clones_width.constraint(),
+            drops_width.constraint(),
+            layout::Constraint::Length(Self::WIDTHS[3] as u16),
+            task_width.constraint(),
+            layout::Constraint::Min(Self::WIDTHS[5] as u16),
+        ];
+
+        let table = table
+            .header(header)
+            .block(block)
+            .widths(widths)
+            .highlight_symbol(view::TABLE_HIGHLIGHT_SYMBOL)
+            .highlight_style(Style::default().add_modifier(style::Modifier::BOLD));
+
+        frame.render_stateful_widget(table, table_area, &mut table_list_state.table_state);
+    }
+}
@@ -0,0 +1,461 @@
+use crate::{
+    i18n::{Lang, Msg},
+    input,
+    state::{resources::Resource, tasks::Task, State},
+    view::{
+        self, bold,
+        controls::{controls_paragraph, ControlDisplay, Controls, KeyDisplay},
+        help::HelpText,
+    },
+    warnings::Linter,
+};
+use ratatui::{
+    layout,
+    style::{self, Style},
+    text::{Line, Span},
+    widgets::{Cell, Paragraph, Row, Table, TableState},
+};
+use std::{cell::RefCell, cmp::Reverse, collections::HashSet, rc::Rc};
+
+/// All of the warnings currently detected, grouped by lint.
+///
+/// Each lint can be expanded to list the tasks or resources it was detected
+/// for, which can then be opened in their details views. Known-benign
+/// warnings can be acknowledged, either a whole lint at once or one task or
+/// resource at a time, which hides them until they're shown again.
+#[derive(Default)]
+pub(crate) struct WarningsView {
+    /// The lints whose tasks or resources are listed.
+    expanded: HashSet<LintId>,
+    /// Whether acknowledged warnings are listed.
+    show_acknowledged: bool,
+    table_state: TableState,
+}
+
+/// The task or resource selected in the warnings view.
+pub(crate) enum Selection {
+    Task(Rc<RefCell<Task>>),
+    Resource(Rc<RefCell<Resource>>),
+}
+
+/// Identifies a lint by its position in the console's list of task or
+/// resource linters.
+#[derive(Clone, Copy, Debug, Hash, PartialEq, Eq)]
+enum LintId {
+    Task(usize),
+    Resource(usize),
+}
+
+enum Entry {
+    Lint {
+        id: LintId,
+        count: usize,
+        summary: String,
+        acknowledged: bool,
+    },
+    Task {
+        lint: usize,
+        task: Rc<RefCell<Task>>,
+        warning: String,
+        acknowledged: bool,
+    },
+    Resource {
+        lint: usize,
+        resource: Rc<RefCell<Resource>>,
+        warning: String,
+        acknowledged: bool,
+    },
+}
+
+impl WarningsView {
+    /// Handles an input event, returning the task or resource to open, if
+    /// one was chosen.
+    pub(crate) fn update_input(
+        &mut self,
+        event: input::Event,
+        state: &mut State,
+        lang: Lang,
+    ) -> Option<Selection> {
+        use input::KeyCode::*;
+
+        let input::Event::Key(event) = event else {
+            return None;
+        };
+        let entries = self.entries(state, lang);
+        let selected = self.table_state.selected();
+        match event.code {
+            Down | Char('j') => {
+                let last = entries.len().saturating_sub(1);
+                self.table_state
+                    .select(Some(selected.map(|i| (i + 1).min(last)).unwrap_or(0)));
+            }
+            Up | Char('k') => {
+                self.table_state
+                    .select(Some(selected.unwrap_or(0).saturating_sub(1)));
+            }
+            Right | Char('l') | Left | Char('h') | Enter => match entries.get(selected?)? {
+                Entry::Lint { id, .. } => {
+                    let expand = match event.code {
+                        Right | Char('l') => true,
+                        Left | Char('h') => false,
+                        _ => !self.expanded.contains(id),
+                    };
+                    if expand {
+                        self.expanded.insert(*id);
+                    } else {
+                        self.expanded.remove(id);
+                    }
+                }
+                Entry::Task { task, .. } if event.code == Enter => {
+                    return Some(Selection::Task(task.clone()));
+                }
+                Entry::Resource { resource, .. } if event.code == Enter => {
+                    return Some(Selection::Resource(resource.clone()));
+                }
+                _ => {}
+            },
+            Char('a') => match entries.get(selected?)? {
+                Entry::Lint {
+                    id: LintId::Task(lint),
+                    ..
+                } => state.tasks_state_mut().acknowledged.toggle_lint(*lint),
+                Entry::Lint {
+                    id: LintId::Resource(lint),
+                    ..
+                } => state.resources_state_mut().acknowledged.toggle_lint(*lint),
+                // A warning can't be acknowledged apart from the rest of its
+                // lint once the whole lint is.
+                Entry::Task { lint, task, .. }
+                    if !state.tasks_state().acknowledged.is_lint_acknowledged(*lint) =>
+                {
+                    let id = task.borrow().id();
+                    state
+                        .tasks_state_mut()
+                        .acknowledged
+                        .toggle_warning(*lint, id);
+                }
+                Entry::Resource { lint, resource, .. }
+                    if !state
+                        .resources_state()
+                        .acknowledged
+                        .is_lint_acknowledged(*lint) =>
+                {
+                    let id = resource.borrow().id();
+                    state
+                        .resources_state_mut()
+                        .acknowledged
+                        .toggle_warning(*lint, id);
+                }
+                _ => {}
+            },
+            Char('v') => self.show_acknowledged = !self.show_acknowledged,
+            _ => {}
+        }
+        None
+    }
+
+    pub(crate) fn render(
+        &mut self,
+        styles: &view::Styles,
+        frame: &mut ratatui::terminal::Frame,
+        area: layout::Rect,
+        state: &mut State,
+    ) {
+        let controls = Controls::new(view_controls(), &area, styles);
+        let chunks = layout::Layout::default()
+            .direction(layout::Direction::Vertical)
+            .margin(0)
+            .constraints(
+                [
+                    layout::Constraint::Length(controls.height()),
+                    layout::Constraint::Min(0),
+                ]
+                .as_ref(),
+            )
+            .split(area);
+        let (controls_area, table_area) = (chunks[0], chunks[1]);
+        frame.render_widget(controls.into_widget(), controls_area);
+
+        let entries = self.entries(state, styles.lang());
+        if let Some(selected) = self.table_state.selected() {
+            if selected >= entries.len() {
+                self.table_state.select(entries.len().checked_sub(1));
+            }
+        }
+
+        let lints = entries
+            .iter()
+            .filter(|entry| matches!(entry, Entry::Lint { .. }))
+            .count();
+        let rows = entries
+            .iter()
+            .map(|entry| {
+                let mut line = match entry {
+                    Entry::Lint {
+                        id, count, summary, ..
+                    } => {
+                        let toggle = if self.expanded.contains(id) {
+                            styles.if_utf8("\u{25BE} ", "- ")
+                        } else {
+                            styles.if_utf8("\u{25B8} ", "+ ")
+                        };
+                        Line::from(vec![
+                            Span::raw(toggle),
+                            styles.warning_wide(),
+                            Span::from(format!("{count} {summary}")),
+                        ])
+                    }
+                    Entry::Task { task, warning, .. } => {
+                        let task = task.borrow();
+                        let title = match task.name() {
+                            Some(name) => styles.fill(
+                                Msg::NamedTaskWarning,
+                                &[("id", &task.id_str()), ("name", &name)],
+                            ),
+                            None => styles.fill(Msg::TaskWarning, &[("id", &task.id_str())]),
+                        };
+                        Line::from(vec![
+                            Span::raw("    "),
+                            bold(title),
+                            Span::from(warning.clone()),
+                        ])
+                    }
+                    Entry::Resource {
+                        resource, warning, ..
+                    } => {
+                        let resource = resource.borrow();
+                        Line::from(vec![
+                            Span::raw("    "),
+                            bold(styles.fill(
+                                Msg::ResourceWarning,
+                                &[
+                                    ("id", &resource.id_str()),
+                                    ("type", &resource.concrete_type()),
+                                ],
+                            )),
+                            Span::from(warning.clone()),
+                        ])
+                    }
+                };
+                if entry.is_acknowledged() {
+                    line.spans
+                        .push(Span::from(styles.text(Msg::AcknowledgedWarning)));
+                    line = line.patch_style(styles.terminated());
+                }
+                Row::new(vec![Cell::from(line)])
+            })
+            .collect::<Vec<_>>();
+
+        let acknowledged =
+            state.tasks_state().acknowledged.len() + state.resources_state().acknowledged.len();
+        let title = if acknowledged > 0 {
+            styles.fill(
+                Msg::AcknowledgedWarningsTitle,
+                &[("count", &lints), ("acknowledged", &acknowledged)],
+            )
+        } else {
+            styles.fill(Msg::WarningsTitle, &[("count", &lints)])
+        };
+        let title = vec![bold(title)];
+        if rows.is_empty() {
+            let any_warnings = state.tasks_state().warnings().next().is_some()
+                || state.resources_state().warnings().next().is_some();
+            let empty = if any_warnings {
+                Msg::AllWarningsAcknowledged
+            } else {
+                Msg::NoWarnings
+            };
+            let empty =
+                Paragraph::new(styles.text(empty)).block(styles.border_block().title(title));
+            frame.render_widget(empty, table_area);
+            return;
+        }
+
+        let table = Table::new(rows, [layout::Constraint::Percentage(100)])
+            .block(styles.border_block().title(title))
+            .highlight_symbol(view::TABLE_HIGHLIGHT_SYMBOL)
+            .highlight_style(Style::default().add_modifier(style::Modifier::BOLD));
+
+        frame.render_stateful_widget(table, table_area, &mut self.table_state);
+    }
+
+    /// Lists the lints which currently have warnings, each followed by the
+    /// tasks or resources they were detected for if the lint is expanded.
+    ///
+    /// Acknowledged warnings are left out, unless they're being shown.
+    fn entries(&self, state: &State, lang: Lang) -> Vec<Entry> {
+        let mut entries = Vec::new();
+
+        let tasks = state.tasks_state();
+        for (lint, linter) in tasks.linters.iter().enumerate() {
+            let mut affected = tasks
+                .tasks()
+                .filter(|task| find_warning(task.borrow().warnings(), linter).is_some())
+                .map(|task| {
+                    let acknowledged = tasks.acknowledged.is_acknowledged(lint, task.borrow().id());
+                    (task, acknowledged)
+                })
+                .filter(|&(_, acknowledged)| self.show_acknowledged || !acknowledged)
+                .collect::<Vec<_>>();
+            let id = LintId::Task(lint);
+            let acknowledged = tasks.acknowledged.is_lint_acknowledged(lint);
+            if !self.push_lint(&mut entries, id, linter, affected.len(), acknowledged, lang) {
+                continue;
+            }
+            // List the worst offenders first.
+            affected.sort_by_key(|(task, _)| {
+                let task = task.borrow();
+                (Reverse(linter.severity(&task)), task.id())
+            });
+            entries.extend(
+                affected
+                    .into_iter()
+                    .map(|(task, acknowledged)| Entry::Task {
+                        lint,
+                        warning: linter.format(&task.borrow(), lang),
+                        task: task.clone(),
+                        acknowledged,
+                    }),
+            );
+        }
+
+        let resources = state.resources_state();
+        for (lint, linter) in resources.linters.iter().enumerate() {
+            let mut affected = resources
+                .resources()
+                .filter(|resource| find_warning(resource.borrow().warnings(), linter).is_some())
+                .map(|resource| {
+                    let acknowledged = resources
+                        .acknowledged
+                        .is_acknowledged(lint, resource.borrow().id());
+                    (resource, acknowledged)
+                })
+                .filter(|&(_, acknowledged)| self.show_acknowledged || !acknowledged)
+                .collect::<Vec<_>>();
+            let id = LintId::Resource(lint);
+            let acknowledged = resources.acknowledged.is_lint_acknowledged(lint);
+            if !self.push_lint(&mut entries, id, linter, affected.len(), acknowledged, lang) {
+                continue;
+            }
+            affected.sort_by_key(|(resource, _)| {
+                let resource = resource.borrow();
+                (Reverse(linter.severity(&resource)), resource.id())
+            });
+            entries.extend(
+                affected
+                    .into_iter()
+                    .map(|(resource, acknowledged)| Entry::Resource {
+                        lint,
+                        warning: linter.format(&resource.borrow(), lang),
+                        resource: resource.clone(),
+                        acknowledged,
+                    }),
+            );
+        }
+
+        entries
+    }
+
+    /// Adds an entry for `linter` if `count` of its warnings are listed,
+    /// returning whether its tasks or resources should be listed after it.
+    fn push_lint<T>(
+        &self,
+        entries: &mut Vec<Entry>,
+        id: LintId,
+        linter: &Linter<T>,
+        count: usize,
+        acknowledged: bool,
+        lang: Lang,
+    ) -> bool {
+        if count == 0 {
+            return false;
+        }
+        entries.push(Entry::Lint {
+            id,
+            count,
+            summary: linter.summary(lang),
+            acknowledged,
+        });
+        self.expanded.contains(&id)
+    }
+}
+
+impl Entry {
+    fn is_acknowledged(&self) -> bool {
+        match self {
+            Entry::Lint { acknowledged, .. }
+            | Entry::Task { acknowledged, .. }
+            | Entry::Resource { acknowledged, .. } => *acknowledged,
+        }
+    }
+}
+
+/// Returns the warning produced by `linter`, if it is one of `warnings`.
+fn find_warning<'a, T>(warnings: &'a [Linter<T>], linter: &Linter<T>) -> Option<&'a Linter<T>> {
+    warnings.iter().find(|warning| warning.is(linter))
+}
+
+impl HelpText for WarningsView {
+    fn render_help_content(&self, styles: &view::Styles) -> Paragraph<'static> {
+        controls_paragraph(view_controls(), styles)
+    }
+}
+
+const fn view_controls() -> &'static [ControlDisplay] {
+    &[
+        ControlDisplay {
+            action: Msg::Scroll,
+            keys: &[
+                KeyDisplay {
+                    base: "up, down",
+                    utf8: Some("\u{2191}\u{2193}"),
+                },
+                KeyDisplay {
+                    base: "k, j",
+                    utf8: None,
+                },
+            ],
+        },
+        ControlDisplay {
+            action: Msg::ExpandCollapse,
+            keys: &[
+                KeyDisplay {
+                    base: "right, left",
+                    utf8: Some("\u{2192}\u{2190}"),
+                },
+                KeyDisplay {
+                    base: "l, h",
+                    utf8: None,
+                },
+            ],
+        },
+        ControlDisplay {
+            action: Msg::ViewDetails,
+            keys: &[KeyDisplay {
+                base: "enter",
+                utf8: Some("\u{21B5}"),
+            }],
+        },
+        ControlDisplay {
+            action: Msg::Acknowledge,
+            keys: &[KeyDisplay {
+                base: "a",
+                utf8: None,
+            }],
+        },
+        ControlDisplay {
+            action: Msg::ShowAcknowledged,
+            keys: &[KeyDisplay {
+                base: "v",
+                utf8: None,
+            }],
+        },
+        ControlDisplay {
+            action: Msg::ReturnToTaskList,
+            keys: &[KeyDisplay {
+                base: "esc",
+                utf8: Some("\u{238B} esc"),
+            }],
+        },
+    ]
+}
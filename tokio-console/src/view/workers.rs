@@ -0,0 +1,260 @@
+use crate::{
+    i18n::Msg,
+    state::{
+        workers::{SortBy, Worker},
+        State,
+    },
+    view::{
+        self, bold,
+        controls::Controls,
+        table::{view_controls, TableList, TableListState},
+    },
+};
+use ratatui::{
+    layout,
+    style::{self, Color, Style},
+    text::{Line, Span},
+    widgets::{Cell, Paragraph, Row, Table},
+};
+
+/// The most locations listed for each worker.
+const MAX_LOCATIONS: usize = 3;
+
+#[derive(Debug, Default)]
+pub(crate) struct WorkersTable {}
+
+impl TableList<11> for WorkersTable {
+    type Row = Worker;
+    type Sort = SortBy;
+    type Context = ();
+
+    const HEADER: &'static [&'static str; 11] = &[
+        "Thread",
+        "Worker",
+        "Task",
+        "Recent",
+        "Busy",
+        "Polls",
+        "Local",
+        "Steals",
+        "Parks/s",
+        "Unparks/s",
+        "Locations",
+    ];
+
+    const WIDTHS: &'static [usize; 11] = &[
+        Self::HEADER[0].len() + 1,
+        Self::HEADER[1].len() + 1,
+        Self::HEADER[2].len() + 1,
+        Self::HEADER[3].len() + 1,
+        Self::HEADER[4].len() + 1,
+        Self::HEADER[5].len() + 1,
+        Self::HEADER[6].len() + 1,
+        Self::HEADER[7].len() + 1,
+        Self::HEADER[8].len() + 1,
+        Self::HEADER[9].len() + 1,
+        Self::HEADER[10].len() + 1,
+    ];
+
+    fn render(
+        table_list_state: &mut TableListState<Self, 11>,
+        styles: &view::Styles,
+        frame: &mut ratatui::terminal::Frame,
+        area: layout::Rect,
+        state: &mut State,
+        _: Self::Context,
+    ) {
+        table_list_state
+            .sorted_items
+            .extend(state.workers_state_mut().take_new_workers());
+        // Threads which have exited are removed from the state.
+        table_list_state
+            .sorted_items
+            .retain(|worker| worker.upgrade().is_some());
+        table_list_state
+            .sort_by
+            .sort(&mut table_list_state.sorted_items);
+
+        let has_runtime_metrics = state.workers_state().has_runtime_metrics();
+        let tasks_state = state.tasks_state();
+        let optional_cell = |value: Option<String>| -> Cell<'static> {
+            Cell::from(value.unwrap_or_else(|| "-".to_string()))
+        };
+        let rate_cell = |rate: Option<f64>| optional_cell(rate.map(|rate| format!("{rate:.1}")));
+
+        let mut thread_width = view::Width::new(Self::WIDTHS[0] as u16);
+        let mut task_width = view::Width::new(Self::WIDTHS[2] as u16);
+        let mut polls_width = view::Width::new(Self::WIDTHS[5] as u16);
+        let mut num_busy = 0;
+
+        let rows = {
+            let thread_width = &mut thread_width;
+            let task_width = &mut task_width;
+            let polls_width = &mut polls_width;
+            let num_busy = &mut num_busy;
+
+            table_list_state
+                .sorted_items
+                .iter()
+                .filter_map(move |worker| {
+                    let worker = worker.upgrade()?;
+                    let worker = worker.borrow();
+                    // The task may not have been received yet, if it was
+                    // spawned since the last update.
+                    let task = worker.current_task().map(|id| {
+                        *num_busy += 1;
+                        tasks_state
+                            .task(id)
+                            .and_then(|task| task.upgrade())
+                            .map(|task| task.borrow().short_desc().to_owned())
+                            .unwrap_or_else(|| id.to_string())
+                    });
+
+                    let recent_polls = Cell::from(worker.recent_polls().to_string());
+                    // The few locations whose tasks took most of the thread's
+                    // time, so that a worker consumed by one kind of task
+                    // stands out.
+                    let locations = worker
+                        .recent_busy_by_location()
+                        .iter()
+                        .take(MAX_LOCATIONS)
+                        .map(|(location, busy)| format!("{busy:.0}% {location}"))
+                        .collect::<Vec<_>>()
+                        .join(", ");
+                    Some(Row::new(vec![
+                        Cell::from(thread_width.update_str(worker.name()).to_owned()),
+                        // Current-thread runtimes have a single worker, which
+                        // is whichever thread is blocking on the runtime.
+                        optional_cell(worker.index().map(|index| index.to_string()).or_else(
+                            || {
+                                (worker.scheduler() == Some("current_thread"))
+                                    .then(|| styles.text(Msg::CurrentThreadWorker).to_string())
+                            },
+                        )),
+                        optional_cell(task.map(|task| task_width.update_str(task))),
+                        if worker.recent_polls() > 0 {
+                            recent_polls.style(styles.fg(Color::Green))
+                        } else {
+                            recent_polls
+                        },
+                        optional_cell(worker.recent_busy().map(|busy| format!("{busy:.0}%"))),
+                        Cell::from(polls_width.update_str(worker.polls().to_string())),
+                        Cell::from(worker.local_polls().to_string()),
+                        optional_cell(worker.steals().map(|steals| steals.to_string())),
+                        rate_cell(worker.park_rate()),
+                        rate_cell(worker.unpark_rate()),
+                        Cell::from(locations),
+                    ]))
+                })
+        };
+
+        let header_style = if styles.color(Color::Cyan).is_some() {
+            Style::default()
+        } else {
+            Style::default().add_modifier(style::Modifier::REVERSED)
+        };
+        let header_style = header_style.add_modifier(style::Modifier::BOLD);
+
+        let header = Row::new(Self::HEADER.iter().enumerate().map(|(idx, &value)| {
+            if idx == table_list_state.selected_column {
+                if table_list_state.sort_descending {
+                    Cell::from(styles.ascending(value))
+                } else {
+                    Cell::from(styles.descending(value))
+                }
+            } else {
+                Cell::from(value)
+            }
+        }))
+        .height(1)
+        .style(header_style);
+
+        let table = if table_list_state.sort_descending {
+            Table::default().rows(rows)
+        } else {
+            Table::default().rows(rows.rev())
+        };
+
+        let block = styles.border_block().title(vec![
+            bold(styles.fill(
+                Msg::RuntimeWorkersTitle,
+                &[("count", &table_list_state.len())],
+            )),
+            Span::from(styles.fill(Msg::PollingCount, &[("count", &num_busy)])),
+        ]);
+
+        let controls = Controls::new(view_controls(), &area, styles);
+        let chunks = layout::Layout::default()
+            .direction(layout::Direction::Vertical)
+            .margin(0)
+            .constraints(
+                [
+                    layout::Constraint::Length(controls.height()),
+                    layout::Constraint::Min(0),
+                    // explains how to report the runtime's metrics
+                    layout::Constraint::Length(if has_runtime_metrics { 0 } else { 4 }),
+                ]
+                .as_ref(),
+            )
+            .split(area);
+        let (controls_area, table_area, help_area) = (chunks[0], chunks[1], chunks[2]);
+        frame.render_widget(controls.into_widget(), controls_area);
+
+        if !has_runtime_metrics {
+            let help = Paragraph::new(vec![
+                Line::from(styles.fill_spans(
+                    Msg::WorkerMetricsUnstable,
+                    &[("cfg", bold("--cfg tokio_unstable"))],
+                )),
+                Line::from(styles.fill_spans(
+                    Msg::WorkerMetricsBuilder,
+                    &[(
+                        "builder",
+                        bold("console_subscriber::Builder::runtime_metrics"),
+                    )],
+                )),
+            ])
+            .block(
+                styles
+                    .border_block()
+                    .title(vec![bold(styles.text(Msg::RuntimeMetrics))]),
+            );
+            frame.render_widget(help, help_area);
+        }
+
+        if table_list_state.sorted_items.is_empty() {
+            let help = Paragraph::new(Line::from(styles.text(Msg::NoPolls))).block(
+                styles
+                    .border_block()
+                    .title(vec![bold(styles.text(Msg::RuntimeWorkers))]),
+            );
+            frame.render_widget(help, table_area);
+            return;
+        }
+
+        let widths = &[
+            thread_width.constraint(),
+            layout::Constraint::Length(Self::WIDTHS[1] as u16),
+            task_width.constraint(),
+            layout::Constraint::Length(Self::WIDTHS[3] as u16),
+            layout::Constraint::Length(Self::WIDTHS[4] as u16),
+            polls_width.constraint(),
+            layout::Constraint::Length(Self::WIDTHS[6] as u16),
+            layout::Constraint::Length(Self::WIDTHS[7] as u16),
+            layout::Constraint::Length(Self::WIDTHS[8] as u16),
+            layout::Constraint::Length(Self::WIDTHS[9] as u16),
+            // The locations take whatever space is left, rather than
+            // squeezing the other columns.
+            layout::Constraint::Min(Self::WIDTHS[10] as u16),
+        ];
+
+        let table = table
+            .header(header)
+            .block(block)
+            .widths(widths)
+            .highlight_symbol(view::TABLE_HIGHLIGHT_SYMBOL)
+            .highlight_style(Style::default().add_modifier(style::Modifier::BOLD));
+
+        frame.render_stateful_widget(table, table_area, &mut table_list_state.table_state);
+    }
+}
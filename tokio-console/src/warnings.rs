@@ -1,5 +1,13 @@
-use crate::state::tasks::{Task, TaskState};
+use crate::{
+    i18n::{Lang, Msg},
+    state::{
+        resources::Resource,
+        tasks::{Task, TaskState},
+        Id,
+    },
+};
 use std::{
+    collections::HashSet,
     fmt::Debug,
     rc::Rc,
     time::{Duration, SystemTime},
@@ -14,7 +22,8 @@ pub trait Warn<T>: Debug {
     /// Returns if the warning applies to `val`.
     fn check(&self, val: &T) -> Warning;
 
-    /// Formats a description of the warning detected for a *specific* `val`.
+    /// Formats a description of the warning detected for a *specific* `val`,
+    /// in the language `lang`.
     ///
     /// This may include dynamically formatted content specific to `val`, such
     /// as the specific numeric value that was over the line for detecting the
@@ -24,10 +33,10 @@ pub trait Warn<T>: Debug {
     /// for the [`SelfWakePercent`] warning, this returns a string like:
     ///
     /// > "This task has woken itself for more than 50% of its total wakeups (86%)"
-    fn format(&self, val: &T) -> String;
+    fn format(&self, val: &T, lang: Lang) -> String;
 
-    /// Returns a string summarizing the warning *in general*, suitable for
-    /// displaying in a list of all detected warnings.
+    /// Returns a string summarizing the warning *in general*, in the language
+    /// `lang`, suitable for displaying in a list of all detected warnings.
     ///
     /// The list entry will begin with a count of the number of monitored
     /// entities for which the warning was detected. Therefore, this should be a
@@ -47,7 +56,15 @@ pub trait Warn<T>: Debug {
     //
     // TODO(eliza): it would be nice if we had separate plural and singular
     // versions of this, like "56 tasks have..." vs "1 task has...".
-    fn summary(&self) -> &str;
+    fn summary(&self, lang: Lang) -> String;
+
+    /// Returns how severe the warning detected for `val` is, so that the
+    /// worst offenders can be listed first.
+    ///
+    /// By default, every instance of a warning is as severe as the others.
+    fn severity(&self, _val: &T) -> u64 {
+        0
+    }
 }
 
 /// A result for a warning check
@@ -88,18 +105,39 @@ impl<T> Linter<T> {
         Rc::strong_count(&self.0) - 1
     }
 
-    pub(crate) fn format(&self, val: &T) -> String {
+    pub(crate) fn format(&self, val: &T, lang: Lang) -> String {
         debug_assert!(
             matches!(self.0.check(val), Warning::Warn),
             "tried to format a warning for a {} that did not have that warning!",
             std::any::type_name::<T>()
         );
-        self.0.format(val)
+        self.0.format(val, lang)
+    }
+
+    pub(crate) fn summary(&self, lang: Lang) -> String {
+        self.0.summary(lang)
     }
 
-    pub(crate) fn summary(&self) -> &str {
-        self.0.summary()
+    pub(crate) fn severity(&self, val: &T) -> u64 {
+        self.0.severity(val)
     }
+
+    /// Returns `true` if `other` was produced by this linter.
+    pub(crate) fn is(&self, other: &Self) -> bool {
+        Rc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+/// The warnings which have been acknowledged, and are no longer reported.
+///
+/// Either all of a lint's warnings can be acknowledged, or only its warning
+/// for a particular task or resource. Lints are identified by their position
+/// in the console's list of linters for `T`. Acknowledgements last until the
+/// console exits.
+#[derive(Debug)]
+pub(crate) struct Acknowledged<T> {
+    lints: HashSet<usize>,
+    warnings: HashSet<(usize, Id<T>)>,
 }
 
 /// A result for a linter check
@@ -117,22 +155,84 @@ pub(crate) enum Lint<T> {
     Recheck,
 }
 
+// === impl Acknowledged ===
+
+impl<T> Acknowledged<T> {
+    /// Returns `true` if all of the `lint`th linter's warnings were
+    /// acknowledged.
+    pub(crate) fn is_lint_acknowledged(&self, lint: usize) -> bool {
+        self.lints.contains(&lint)
+    }
+
+    /// Returns `true` if the `lint`th linter's warning for `id` was
+    /// acknowledged, either by itself or along with the rest of the lint.
+    pub(crate) fn is_acknowledged(&self, lint: usize, id: Id<T>) -> bool {
+        self.is_lint_acknowledged(lint) || self.warnings.contains(&(lint, id))
+    }
+
+    /// Acknowledges all of the `lint`th linter's warnings, or stops
+    /// acknowledging them if they already were.
+    pub(crate) fn toggle_lint(&mut self, lint: usize) {
+        if !self.lints.remove(&lint) {
+            self.lints.insert(lint);
+        }
+    }
+
+    /// Acknowledges the `lint`th linter's warning for `id`, or stops
+    /// acknowledging it if it already was.
+    pub(crate) fn toggle_warning(&mut self, lint: usize, id: Id<T>) {
+        if !self.warnings.remove(&(lint, id)) {
+            self.warnings.insert((lint, id));
+        }
+    }
+
+    /// Returns the number of lints and individual warnings acknowledged.
+    pub(crate) fn len(&self) -> usize {
+        self.lints.len() + self.warnings.len()
+    }
+
+    /// Returns how many of `linter`'s warnings haven't been acknowledged,
+    /// where `linter` is the `lint`th linter, and `warned` lists the IDs of
+    /// everything it warned about.
+    pub(crate) fn unacknowledged<I>(
+        &self,
+        lint: usize,
+        linter: &Linter<T>,
+        warned: impl FnOnce() -> I,
+    ) -> usize
+    where
+        I: Iterator<Item = Id<T>>,
+    {
+        if self.is_lint_acknowledged(lint) {
+            0
+        } else if self.warnings.iter().any(|&(acked, _)| acked == lint) {
+            warned()
+                .filter(|&id| !self.warnings.contains(&(lint, id)))
+                .count()
+        } else {
+            linter.count()
+        }
+    }
+}
+
+impl<T> Default for Acknowledged<T> {
+    fn default() -> Self {
+        Self {
+            lints: HashSet::new(),
+            warnings: HashSet::new(),
+        }
+    }
+}
+
 #[derive(Clone, Debug)]
 pub(crate) struct SelfWakePercent {
     min_percent: u64,
-    description: String,
 }
 
 impl SelfWakePercent {
     pub(crate) const DEFAULT_PERCENT: u64 = 50;
     pub(crate) fn new(min_percent: u64) -> Self {
-        Self {
-            min_percent,
-            description: format!(
-                "tasks have woken themselves over {}% of the time",
-                min_percent
-            ),
-        }
+        Self { min_percent }
     }
 }
 
@@ -143,8 +243,11 @@ impl Default for SelfWakePercent {
 }
 
 impl Warn<Task> for SelfWakePercent {
-    fn summary(&self) -> &str {
-        self.description.as_str()
+    fn summary(&self, lang: Lang) -> String {
+        lang.fill(
+            Msg::SelfWakePercentSummary,
+            &[("percent", &self.min_percent)],
+        )
     }
 
     fn check(&self, task: &Task) -> Warning {
@@ -160,11 +263,11 @@ impl Warn<Task> for SelfWakePercent {
         }
     }
 
-    fn format(&self, task: &Task) -> String {
+    fn format(&self, task: &Task, lang: Lang) -> String {
         let self_wakes = task.self_wake_percent();
-        format!(
-            "This task has woken itself for more than {}% of its total wakeups ({}%)",
-            self.min_percent, self_wakes
+        lang.fill(
+            Msg::SelfWakePercentWarning,
+            &[("min", &self.min_percent), ("percent", &self_wakes)],
         )
     }
 }
@@ -173,8 +276,8 @@ impl Warn<Task> for SelfWakePercent {
 pub(crate) struct LostWaker;
 
 impl Warn<Task> for LostWaker {
-    fn summary(&self) -> &str {
-        "tasks have lost their wakers"
+    fn summary(&self, lang: Lang) -> String {
+        lang.text(Msg::LostWakerSummary).to_owned()
     }
 
     fn check(&self, task: &Task) -> Warning {
@@ -193,8 +296,44 @@ impl Warn<Task> for LostWaker {
         }
     }
 
-    fn format(&self, _: &Task) -> String {
-        "This task has lost its waker, and will never be woken again.".into()
+    fn format(&self, _: &Task, lang: Lang) -> String {
+        lang.text(Msg::LostWakerWarning).to_owned()
+    }
+}
+
+/// Warning for if more of a task's wakers have been dropped than were cloned.
+///
+/// This can't happen in a correct program, so it means that the
+/// instrumentation missed some of the task's waker clones, and its waker
+/// counts can't be trusted.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct UnbalancedWakers;
+
+impl Warn<Task> for UnbalancedWakers {
+    fn summary(&self, lang: Lang) -> String {
+        lang.text(Msg::UnbalancedWakersSummary).to_owned()
+    }
+
+    fn check(&self, task: &Task) -> Warning {
+        if task.waker_count() < 0 {
+            Warning::Warn
+        } else {
+            Warning::Ok
+        }
+    }
+
+    fn format(&self, task: &Task, lang: Lang) -> String {
+        lang.fill(
+            Msg::UnbalancedWakersWarning,
+            &[
+                ("drops", &task.waker_drops()),
+                ("clones", &task.waker_clones()),
+            ],
+        )
+    }
+
+    fn severity(&self, task: &Task) -> u64 {
+        task.waker_count().unsigned_abs()
     }
 }
 
@@ -202,19 +341,12 @@ impl Warn<Task> for LostWaker {
 #[derive(Clone, Debug)]
 pub(crate) struct NeverYielded {
     min_duration: Duration,
-    description: String,
 }
 
 impl NeverYielded {
     pub(crate) const DEFAULT_DURATION: Duration = Duration::from_secs(1);
     pub(crate) fn new(min_duration: Duration) -> Self {
-        Self {
-            min_duration,
-            description: format!(
-                "tasks have never yielded (threshold {}ms)",
-                min_duration.as_millis()
-            ),
-        }
+        Self { min_duration }
     }
 }
 
@@ -225,8 +357,11 @@ impl Default for NeverYielded {
 }
 
 impl Warn<Task> for NeverYielded {
-    fn summary(&self) -> &str {
-        self.description.as_str()
+    fn summary(&self, lang: Lang) -> String {
+        lang.fill(
+            Msg::NeverYieldedSummary,
+            &[("threshold", &self.min_duration.as_millis())],
+        )
     }
 
     fn check(&self, task: &Task) -> Warning {
@@ -251,12 +386,176 @@ impl Warn<Task> for NeverYielded {
         }
     }
 
-    fn format(&self, task: &Task) -> String {
-        format!(
-            "This task has never yielded ({:?})",
-            task.busy(SystemTime::now()),
+    fn format(&self, task: &Task, lang: Lang) -> String {
+        lang.fill(
+            Msg::NeverYieldedWarning,
+            &[("busy", &format!("{:?}", task.busy(SystemTime::now())))],
+        )
+    }
+}
+
+/// Warning for if a task has been woken, but hasn't been polled since, for a
+/// long time.
+///
+/// Unlike an idle task, which is waiting to be woken, a starved task is ready
+/// to make progress, but hasn't been given the chance to. This is the usual
+/// sign of a runtime whose workers are blocked elsewhere.
+#[derive(Clone, Debug)]
+pub(crate) struct Starved {
+    min_duration: Duration,
+}
+
+impl Starved {
+    pub(crate) const DEFAULT_DURATION: Duration = Duration::from_secs(1);
+    pub(crate) fn new(min_duration: Duration) -> Self {
+        Self { min_duration }
+    }
+}
+
+impl Default for Starved {
+    fn default() -> Self {
+        Self::new(Self::DEFAULT_DURATION)
+    }
+}
+
+impl Warn<Task> for Starved {
+    fn summary(&self, lang: Lang) -> String {
+        lang.fill(
+            Msg::StarvedSummary,
+            &[("threshold", &self.min_duration.as_millis())],
+        )
+    }
+
+    fn check(&self, task: &Task) -> Warning {
+        match task.wait_since_wake(SystemTime::now()) {
+            None => Warning::Ok,
+            Some(wait) if wait >= self.min_duration => Warning::Warn,
+            // The task isn't polled again until it stops being starved, so it
+            // won't be updated in the meantime.
+            Some(_) => Warning::Recheck,
+        }
+    }
+
+    fn format(&self, task: &Task, lang: Lang) -> String {
+        let wait = task
+            .wait_since_wake(SystemTime::now())
+            .expect("warning should not trigger if the task isn't scheduled");
+        lang.fill(Msg::StarvedWarning, &[("wait", &format!("{wait:?}"))])
+    }
+
+    fn severity(&self, task: &Task) -> u64 {
+        task.wait_since_wake(SystemTime::now())
+            .map_or(0, |wait| wait.as_millis() as u64)
+    }
+}
+
+/// Warning for if a task waited a long time between being spawned and being
+/// polled for the first time.
+///
+/// A newly spawned task is ready to run straight away, so a long wait for its
+/// first poll means that the runtime's workers were too busy to get to it. A
+/// spike in these waits is a direct sign of an overloaded executor.
+#[derive(Clone, Debug)]
+pub(crate) struct SlowFirstPoll {
+    min_duration: Duration,
+}
+
+impl SlowFirstPoll {
+    pub(crate) const DEFAULT_DURATION: Duration = Duration::from_millis(100);
+    pub(crate) fn new(min_duration: Duration) -> Self {
+        Self { min_duration }
+    }
+}
+
+impl Default for SlowFirstPoll {
+    fn default() -> Self {
+        Self::new(Self::DEFAULT_DURATION)
+    }
+}
+
+impl Warn<Task> for SlowFirstPoll {
+    fn summary(&self, lang: Lang) -> String {
+        lang.fill(
+            Msg::SlowFirstPollSummary,
+            &[("threshold", &self.min_duration.as_millis())],
+        )
+    }
+
+    fn check(&self, task: &Task) -> Warning {
+        match task.first_poll_delay(SystemTime::now()) {
+            None => Warning::Ok,
+            Some(delay) if delay >= self.min_duration => Warning::Warn,
+            // Once the task has been polled, its first poll delay is final.
+            Some(_) if task.total_polls() > 0 => Warning::Ok,
+            Some(_) => Warning::Recheck,
+        }
+    }
+
+    fn format(&self, task: &Task, lang: Lang) -> String {
+        let delay = task
+            .first_poll_delay(SystemTime::now())
+            .expect("warning should not trigger if the task was never polled");
+        lang.fill(
+            Msg::SlowFirstPollWarning,
+            &[("delay", &format!("{delay:?}"))],
+        )
+    }
+
+    fn severity(&self, task: &Task) -> u64 {
+        task.first_poll_delay(SystemTime::now())
+            .map_or(0, |delay| delay.as_millis() as u64)
+    }
+}
+
+/// Warning for if a task is woken many times for each time it's polled.
+///
+/// Waking a task which is already scheduled doesn't poll it again, so
+/// thousands of wakes per poll mean that whatever is notifying the task is
+/// doing so in a tight loop, wasting time on wakes which have no effect.
+#[derive(Clone, Debug)]
+pub(crate) struct WakeStorm {
+    min_wakes_per_poll: u64,
+}
+
+impl WakeStorm {
+    pub(crate) const DEFAULT_WAKES_PER_POLL: u64 = 1000;
+    pub(crate) fn new(min_wakes_per_poll: u64) -> Self {
+        Self { min_wakes_per_poll }
+    }
+}
+
+impl Default for WakeStorm {
+    fn default() -> Self {
+        Self::new(Self::DEFAULT_WAKES_PER_POLL)
+    }
+}
+
+impl Warn<Task> for WakeStorm {
+    fn summary(&self, lang: Lang) -> String {
+        lang.fill(
+            Msg::WakeStormSummary,
+            &[("threshold", &self.min_wakes_per_poll)],
+        )
+    }
+
+    fn check(&self, task: &Task) -> Warning {
+        if task.wakes_per_poll() >= self.min_wakes_per_poll as f64 {
+            Warning::Warn
+        } else {
+            Warning::Ok
+        }
+    }
+
+    fn format(&self, task: &Task, lang: Lang) -> String {
+        lang.fill(
+            Msg::WakeStormWarning,
+            &[("wakes", &format!("{:.0}", task.wakes_per_poll()))],
         )
     }
+
+    fn severity(&self, task: &Task) -> u64 {
+        task.wakes_per_poll() as u64
+    }
 }
 
 /// Warning for if a task's driving future was auto-boxed by the runtime
@@ -264,8 +563,8 @@ impl Warn<Task> for NeverYielded {
 pub(crate) struct AutoBoxedFuture;
 
 impl Warn<Task> for AutoBoxedFuture {
-    fn summary(&self) -> &str {
-        "tasks have been boxed by the runtime due to their size"
+    fn summary(&self, lang: Lang) -> String {
+        lang.text(Msg::AutoBoxedFutureSummary).to_owned()
     }
 
     fn check(&self, task: &Task) -> Warning {
@@ -282,16 +581,16 @@ impl Warn<Task> for AutoBoxedFuture {
         }
     }
 
-    fn format(&self, task: &Task) -> String {
+    fn format(&self, task: &Task, lang: Lang) -> String {
         let original_size = task
             .original_size_bytes()
             .expect("warning should not trigger if original size is None");
         let boxed_size = task
             .size_bytes()
             .expect("warning should not trigger if size is None");
-        format!(
-            "This task's future was auto-boxed by the runtime when spawning, due to its size (originally \
-            {original_size} bytes, boxed size {boxed_size} bytes)",
+        lang.fill(
+            Msg::AutoBoxedFutureWarning,
+            &[("original", &original_size), ("boxed", &boxed_size)],
         )
     }
 }
@@ -300,15 +599,11 @@ impl Warn<Task> for AutoBoxedFuture {
 #[derive(Clone, Debug)]
 pub(crate) struct LargeFuture {
     min_size: usize,
-    description: String,
 }
 impl LargeFuture {
     pub(crate) const DEFAULT_MIN_SIZE_BYTES: usize = 1024;
     pub(crate) fn new(min_size: usize) -> Self {
-        Self {
-            min_size,
-            description: format!("tasks are {} bytes or larger", min_size),
-        }
+        Self { min_size }
     }
 }
 
@@ -319,8 +614,8 @@ impl Default for LargeFuture {
 }
 
 impl Warn<Task> for LargeFuture {
-    fn summary(&self) -> &str {
-        self.description.as_str()
+    fn summary(&self, lang: Lang) -> String {
+        lang.fill(Msg::LargeFutureSummary, &[("size", &self.min_size)])
     }
 
     fn check(&self, task: &Task) -> Warning {
@@ -337,11 +632,86 @@ impl Warn<Task> for LargeFuture {
         Warning::Ok
     }
 
-    fn format(&self, task: &Task) -> String {
-        format!(
-            "This task occupies a large amount of stack space ({} bytes)",
-            task.size_bytes()
-                .expect("warning should not trigger if size is None"),
+    fn format(&self, task: &Task, lang: Lang) -> String {
+        let size = task
+            .size_bytes()
+            .expect("warning should not trigger if size is None");
+        lang.fill(Msg::LargeFutureWarning, &[("size", &size)])
+    }
+}
+
+/// Warning for if a synchronization primitive, such as a mutex or semaphore,
+/// is contended
+#[derive(Clone, Debug)]
+pub(crate) struct Contention {
+    max_avg_wait: Duration,
+    max_waiters: usize,
+}
+
+impl Contention {
+    pub(crate) const DEFAULT_AVG_WAIT: Duration = Duration::from_millis(100);
+    pub(crate) const DEFAULT_WAITERS: usize = 4;
+    pub(crate) fn new(max_avg_wait: Duration, max_waiters: usize) -> Self {
+        Self {
+            max_avg_wait,
+            max_waiters,
+        }
+    }
+}
+
+impl Default for Contention {
+    fn default() -> Self {
+        Self::new(Self::DEFAULT_AVG_WAIT, Self::DEFAULT_WAITERS)
+    }
+}
+
+impl Warn<Resource> for Contention {
+    fn summary(&self, lang: Lang) -> String {
+        lang.fill(
+            Msg::ContentionSummary,
+            &[
+                ("wait", &self.max_avg_wait.as_millis()),
+                ("waiters", &self.max_waiters),
+            ],
+        )
+    }
+
+    fn check(&self, resource: &Resource) -> Warning {
+        // Only synchronization primitives are expected to be waited on
+        // briefly; channels and timers are waited on by design.
+        if !resource.kind().eq_ignore_ascii_case("sync") {
+            return Warning::Ok;
+        }
+
+        let contention = resource.contention();
+        if contention.avg_wait > self.max_avg_wait || contention.waiters > self.max_waiters {
+            Warning::Warn
+        } else {
+            Warning::Ok
+        }
+    }
+
+    fn format(&self, resource: &Resource, lang: Lang) -> String {
+        let contention = resource.contention();
+        let tasks = contention
+            .waiting_tasks
+            .iter()
+            .map(|id| id.to_string())
+            .collect::<Vec<_>>();
+        let tasks = if tasks.is_empty() {
+            String::new()
+        } else {
+            lang.fill(Msg::ContentionWaitingTasks, &[("tasks", &tasks.join(", "))])
+        };
+        lang.fill(
+            Msg::ContentionWarning,
+            &[
+                ("type", &resource.concrete_type()),
+                ("location", &resource.location()),
+                ("waiters", &contention.waiters),
+                ("tasks", &tasks),
+                ("wait", &format!("{:?}", contention.avg_wait)),
+            ],
         )
     }
 }
@@ -12,6 +12,9 @@ use color_eyre::{
 };
 use regex::Regex;
 
+#[path = "../../console-api/tests/codegen/mod.rs"]
+mod codegen;
+
 /// tokio-console dev tasks
 #[derive(Debug, clap::Parser)]
 struct Args {
@@ -80,11 +83,7 @@ fn gen_proto() -> Result<()> {
 
     let out_dir = api_dir.join("src").join("generated");
 
-    tonic_build::configure()
-        .build_client(true)
-        .build_server(true)
-        .emit_rerun_if_changed(false)
-        .protoc_arg("--experimental_allow_proto3_optional")
+    codegen::configure()
         .out_dir(out_dir)
         .compile_protos(&proto_files[..], &[proto_dir])
         .context("failed to compile protobuf files")